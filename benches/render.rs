@@ -0,0 +1,60 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use blogs_md_easy::{parse_meta_section, parse_placeholder_locations, render, Span, Template};
+
+/// A template the size of a typical blog layout, with a placeholder every few
+/// lines, and some literal text.
+fn template() -> String {
+    let mut template = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<title>{{ £title | uppercase }}</title>\n</head>\n<body>\n");
+    for index in 0..200 {
+        template.push_str(&format!("<section id=\"section-{}\">\n<p>Some text that isn't a placeholder, and goes on for a little while.</p>\n", index));
+        template.push_str("<p>By {{ £author ?? \"Anonymous\" | truncate = characters: 20 }} on {{ £date }}</p>\n");
+        template.push_str("{{ raw }}<code>{{ £example }}</code>{{ endraw }}\n</section>\n");
+    }
+    template.push_str("<main>{{ £content }}</main>\n</body>\n</html>\n");
+    template
+}
+
+/// A Markdown with a meta section of `keys` keys, and a short body.
+fn markdown(keys: usize) -> String {
+    let mut markdown = String::from(":meta\nauthor = John Doe\ndate = 2024-01-01\n");
+    for index in 0..keys {
+        markdown.push_str(&format!("key_{} = \"A value for key {}\"\n", index, index));
+    }
+    markdown.push_str(":meta\n# Hello, World!\n\nA paragraph of **Markdown**, with a [link](https://example.com).\n");
+    markdown
+}
+
+fn parsing(c: &mut Criterion) {
+    let template = template();
+    c.bench_function("parse_placeholder_locations", |b| {
+        b.iter(|| parse_placeholder_locations(Span::new(black_box(&template))).unwrap())
+    });
+
+    let markdown = markdown(100);
+    c.bench_function("parse_meta_section", |b| {
+        b.iter(|| parse_meta_section(Span::new(black_box(&markdown))).unwrap())
+    });
+}
+
+fn rendering(c: &mut Criterion) {
+    let template = template();
+    let markdown = markdown(10);
+    c.bench_function("render", |b| {
+        b.iter(|| render(black_box(&template), black_box(&markdown)).unwrap())
+    });
+
+    // A site of 2,000 posts parses its template once.
+    let markdowns = (0..2000).map(|_| markdown.to_owned()).collect::<Vec<String>>();
+    let mut group = c.benchmark_group("site");
+    group.sample_size(10);
+    group.bench_function("render_2000_posts", |b| {
+        b.iter(|| {
+            let template = Template::new(black_box(&template)).unwrap();
+            markdowns.iter().map(|markdown| template.render_markdown(markdown).unwrap()).collect::<Vec<String>>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parsing, rendering);
+criterion_main!(benches);
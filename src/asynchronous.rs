@@ -0,0 +1,80 @@
+//! `async` variants of the render API, so that the engine can be embedded in
+//! an async service, such as a preview server that renders each post as it is
+//! requested.
+//!
+//! The Markdowns are read with `tokio::fs`, whilst rendering, which reads the
+//! templates and their fragments, and writes the outputs, runs on tokio's
+//! blocking thread pool, so that the runtime is never blocked. The errors are
+//! `Send`, so that they can be returned from a spawned task.
+//!
+//! ```rust
+//! use blogs_md_easy::{asynchronous, BatchOptions};
+//!
+//! let directory = std::env::temp_dir().join("blogs_md_easy_asynchronous");
+//! std::fs::create_dir_all(&directory).unwrap();
+//! std::fs::write(directory.join("post.html"), "<h1>{{ £title }}</h1>").unwrap();
+//! std::fs::write(directory.join("hello.md"), "# Hello").unwrap();
+//!
+//! let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+//! let html = runtime.block_on(async {
+//!     asynchronous::render_markdown(&directory.join("post.html"), &directory.join("hello.md"), &BatchOptions::default()).await
+//! }).unwrap();
+//! assert_eq!(html, "<h1>Hello</h1>");
+//! ```
+//!
+//! Requires the `tokio` feature.
+
+use std::{error::Error as StdError, path::{Path, PathBuf}};
+use crate::{batch::{self, BatchOptions, CancellationToken}, error::SourceError, render::{self, decode_file}};
+
+/// An error that can be sent between threads.
+pub type Error = Box<dyn StdError + Send + Sync>;
+
+/// Read a file into a String without blocking, as [`read_file`](render::read_file)
+/// does.
+pub async fn read_file(path: &Path) -> Result<String, Error> {
+    let bytes = tokio::fs::read(path).await.map_err(|e| format!("Unable to read '{}': {}", path.to_string_lossy(), e))?;
+    decode_file(&bytes, path).map_err(sendable)
+}
+
+/// Read the Markdown at `markdown_path`, and render it into the template at
+/// `template_path`, returning the document, as [`render_markdown`](render::render_markdown)
+/// does.
+pub async fn render_markdown(template_path: &Path, markdown_path: &Path, options: &BatchOptions) -> Result<String, Error> {
+    let markdown = read_file(markdown_path).await?;
+    let (template_path, markdown_path, options) = (template_path.to_owned(), markdown_path.to_owned(), options.to_owned());
+    blocking(move || render::render_markdown(&template_path, &markdown_path, &markdown, &options)).await
+}
+
+/// Read the Markdowns at `markdown_paths`, and render each of them into the
+/// `templates`, returning where they were written, as [`render_batch`](batch::render_batch)
+/// does.
+///
+/// Dropping the future doesn't stop the render, so cancel the `cancellation`
+/// token to stop it.
+pub async fn render_batch(templates: &[PathBuf], markdown_paths: &[PathBuf], options: &BatchOptions, cancellation: &CancellationToken) -> Result<Vec<PathBuf>, Error> {
+    let mut markdowns = Vec::with_capacity(markdown_paths.len());
+    for markdown_path in markdown_paths {
+        markdowns.push((markdown_path.to_owned(), read_file(markdown_path).await?));
+    }
+    let (templates, options, cancellation) = (templates.to_vec(), options.to_owned(), cancellation.to_owned());
+    blocking(move || batch::render_batch(&templates, &markdowns, &options, |_| {}, &cancellation)).await
+}
+
+/// Run `render` on the blocking thread pool.
+async fn blocking<T, F>(render: F) -> Result<T, Error>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, Box<dyn StdError>> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || render().map_err(sendable)).await?
+}
+
+/// Errors aren't `Send`, so only their message is kept, unless they point into
+/// a source.
+fn sendable(e: Box<dyn StdError>) -> Error {
+    match e.downcast::<SourceError>() {
+        Ok(error) => error,
+        Err(e) => e.to_string().into(),
+    }
+}
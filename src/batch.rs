@@ -0,0 +1,970 @@
+//! Rendering a whole batch of Markdowns into templates, along with the options,
+//! progress, and cancellation of a batch.
+
+use std::{borrow::Cow, collections::{hash_map::Entry, BTreeMap, HashMap}, error::Error, fs, io, path::{Path, PathBuf}, sync::{atomic::{AtomicBool, Ordering}, Arc}, time::{Duration, Instant}};
+use crate::{error::BatchError, manifest::{BuildCache, OutputManifest}, meta::{extract_meta, localise_meta, EmbedMeta, Meta}, parser::Syntax, permalink::{batch_output_path, permalinks}, project::{Fediverse, Webmentions}, render::{chosen_profile, chosen_template, chosen_variants, hash_hex, is_draft, is_noindex, is_published_by, load_template, render_document, resolve_links, variant_output_path, Alternate, AnchorMap, DateSource, DescriptionSource, Document, FilterCache, Glossary, Hooks, MetaSchema, MissingVariablePolicy, OutputFormat, Pipeline, Plugin, Profile, Shortcodes, Template, TitlePrecedence, Warning}};
+#[cfg(feature = "parallel")]
+use crate::error::SourceError;
+#[cfg(feature = "compression")]
+use crate::render::Compression;
+
+////////////////////////////////////////////////////////////////////////////////
+// Structs and types
+/// Options that change how [`render_batch`] renders and writes each file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BatchOptions {
+    /// Output directory, defaults to the Markdown's directory.
+    pub output_dir: Option<PathBuf>,
+    /// Where each Markdown is written within the [`BatchOptions::output_dir`],
+    /// from its meta section, such as `/:year/:month/:slug/`, see
+    /// [`expand_permalink`](crate::permalink::expand_permalink).
+    ///
+    /// A permalink that ends with `/` is written to the `index` within it,
+    /// and one without an extension is given the template's.
+    ///
+    /// `Default: None`, which is the name of the Markdown.
+    pub permalink_pattern: Option<String>,
+    /// Embed the original `meta` section into each rendered document.
+    ///
+    /// `Default: None`
+    pub embed_meta: Option<EmbedMeta>,
+    /// The features that are enabled for `{{ if feature "name" }}` blocks, see
+    /// [`apply_features`](crate::render::apply_features).
+    pub features: Vec<String>,
+    /// Default variables for every Markdown, such as those from a [`Theme`](crate::project::Theme) or
+    /// [`load_variables`](crate::meta::load_variables).
+    ///
+    /// Variables declared in the `meta` section of a Markdown take precedence,
+    /// followed by the first of any variables that share a key.
+    pub variables: Vec<Meta>,
+    /// What to do when a template uses a variable that doesn't exist.
+    ///
+    /// `Default: MissingVariablePolicy::Error`
+    pub on_missing: MissingVariablePolicy,
+    /// Skip over the malformed placeholders of each template, leaving them in
+    /// the output as they are written, and reporting them as a
+    /// [`Warning::Malformed`], rather than failing the batch, see
+    /// [`Template::recover`].
+    ///
+    /// `Default: false`
+    pub recover: bool,
+    /// The outputs of expensive [`Filter`](crate::filters::Filter)s, which is
+    /// shared by every file in the batch.
+    ///
+    /// Reuse the same `BatchOptions`, or a clone of the cache, between batches
+    /// so that unchanged Markdowns aren't rendered again. It forgets the
+    /// outputs used least recently beyond its
+    /// [capacity](FilterCache::with_capacity).
+    pub cache: FilterCache,
+    /// Directories whose structure is mirrored into the `output_dir`.
+    ///
+    /// A Markdown within one of these directories is written to the same
+    /// relative path within the `output_dir`, rather than directly inside it.
+    pub input_dirs: Vec<PathBuf>,
+    /// Where the `£description` of each Markdown is taken from, trying each in
+    /// turn, so that its description tags are never empty, see
+    /// [`create_variables`](crate::render::create_variables).
+    ///
+    /// `Default: []`, which is the meta section, then the excerpt, then the
+    /// content.
+    pub description_from: Vec<DescriptionSource>,
+    /// The hash of everything that went into each output, so that outputs
+    /// which would not change are not rendered again.
+    ///
+    /// `Default: None`, which renders every output.
+    pub build_cache: Option<BuildCache>,
+    /// Where every file that is written is recorded, so that they can be
+    /// removed later with [`OutputManifest::clean`].
+    ///
+    /// `Default: None`
+    pub manifest: Option<OutputManifest>,
+    /// The format that each output is written in.
+    ///
+    /// `Default: OutputFormat::Html`
+    pub format: OutputFormat,
+    /// Whether the meta section's `title` or the heading is used as `£title`
+    /// when a Markdown has both.
+    ///
+    /// `Default: TitlePrecedence::Meta`
+    pub title_precedence: TitlePrecedence,
+    /// Remove the heading from the start of `£content`, for templates that
+    /// already place the `£title` in their own `<h1>`.
+    ///
+    /// `Default: false`
+    pub remove_heading: bool,
+    /// How many words a reader gets through each minute, for the
+    /// `£reading_time` of each Markdown, see
+    /// [`create_variables`](crate::render::create_variables).
+    ///
+    /// `Default: None`, which is 200 words per minute.
+    pub words_per_minute: Option<u32>,
+    /// Add an `id` to each heading of the Markdown, from its slug, so that
+    /// `{{ ref "post#anchor" }}` links can point to it, see
+    /// [`add_heading_ids`](crate::render::add_heading_ids).
+    ///
+    /// `Default: false`
+    pub heading_ids: bool,
+    /// Add a `¶` link to each heading of the Markdown that has an `id`, see
+    /// [`add_permalinks`](crate::render::add_permalinks).
+    ///
+    /// `Default: false`
+    pub permalinks: bool,
+    /// Add an `id` to each paragraph of the Markdown, from a hash of its text,
+    /// see [`add_paragraph_ids`](crate::render::add_paragraph_ids).
+    ///
+    /// `Default: false`
+    pub paragraph_ids: bool,
+    /// Load the player of each `{{ youtube }}` and `{{ vimeo }}` embed with the
+    /// page, rather than once it is clicked, see
+    /// [`apply_embeds`](crate::render::apply_embeds).
+    ///
+    /// `Default: false`
+    pub eager_embeds: bool,
+    /// Copy the local images of each Markdown into the
+    /// [`BatchOptions::output_dir`], beside its output, and update the `src`
+    /// of any that are outside of the Markdown's directory.
+    ///
+    /// `Default: false`
+    pub copy_images: bool,
+    /// Add the `width` and `height` of each local image of a Markdown to its
+    /// `<img>`, so that the page doesn't shift as the image loads.
+    ///
+    /// `Default: false`
+    pub image_sizes: bool,
+    /// Inline the stylesheets, images, and fonts of each output into it, so
+    /// that each is a single self-contained file, see
+    /// [`inline_assets`](crate::render::inline_assets).
+    ///
+    /// The assets are found beside the output, the Markdown, and the template,
+    /// or within the [`BatchOptions::output_dir`] or
+    /// [`BatchOptions::asset_dirs`].
+    ///
+    /// `Default: false`
+    pub single_file: bool,
+    /// More directories that the assets of each output may be within, such as
+    /// the static directory of a theme, for [`BatchOptions::single_file`].
+    ///
+    /// `Default: []`
+    pub asset_dirs: Vec<PathBuf>,
+    /// Where the `publish_date` of a Markdown without one in its meta section
+    /// is taken from, trying each in turn.
+    ///
+    /// `Default: []`, so that only the meta section has a `publish_date`.
+    pub publish_date_from: Vec<DateSource>,
+    /// Build the site as it was on a past date, such as `2023-06-01`, leaving
+    /// out every Markdown published after it, see [`is_published_by`].
+    ///
+    /// `Default: None`, which includes every Markdown.
+    pub as_of: Option<String>,
+    /// The Markdowns that `{{ ref "post#anchor" }}` tags may refer to, see
+    /// [`apply_refs`](crate::render::apply_refs).
+    ///
+    /// `Default: None`, which finds the anchors of the Markdowns in the batch.
+    pub anchors: Option<AnchorMap>,
+    /// The extension of the links to other Markdowns, from `{{ ref }}` tags and
+    /// `.md` links, such as an empty string for a host that serves `hello.html`
+    /// at `hello`, see
+    /// [`rewrite_markdown_links`](crate::render::rewrite_markdown_links).
+    ///
+    /// `Default: None`, which is the extension of each output.
+    pub link_extension: Option<String>,
+    /// The sigil and delimiters that the templates and Markdowns are written
+    /// with, such as `[[ $title ]]`, see [`Syntax::translate`].
+    ///
+    /// `Default: £ and {{ }}`
+    pub syntax: Syntax,
+    /// Terms to link to their definitions, whose glossary page is rendered
+    /// along with the Markdowns.
+    ///
+    /// A Markdown can opt out with `glossary = false` in its meta section.
+    ///
+    /// `Default: None`
+    pub glossary: Option<Glossary>,
+    /// The templates of the shortcodes that each Markdown can use, such as
+    /// `{{ figure src: cat.jpg }}`.
+    ///
+    /// `Default: no shortcodes`
+    pub shortcodes: Shortcodes,
+    /// The rules that the meta section of each Markdown must follow, or else
+    /// nothing is rendered.
+    ///
+    /// `Default: no rules`
+    pub schema: MetaSchema,
+    /// The number of posts on each page of an index, see
+    /// [`render_index`](crate::listing::render_index).
+    ///
+    /// `Default: None`, which lists every post on a single page.
+    pub per_page: Option<usize>,
+    /// Render the Markdowns with `draft = true` in their meta section, which
+    /// are otherwise skipped, see [`is_draft`].
+    ///
+    /// `Default: false`
+    pub include_drafts: bool,
+    /// Render each output without writing it, or updating the
+    /// [`BatchOptions::build_cache`], so that the [`BatchProgress`] reports
+    /// what would be written.
+    ///
+    /// `Default: false`
+    pub dry_run: bool,
+    /// Keep rendering the rest of the Markdowns after one of them fails, then
+    /// return every failure at once as a [`BatchError`], so that each broken
+    /// post is found in a single run.
+    ///
+    /// Each missing variable of a template is also reported, rather than only
+    /// the first.
+    ///
+    /// `Default: false`
+    pub collect_errors: bool,
+    /// The webmention and pingback endpoints to advertise in the `<head>` of
+    /// each output, and the mentions of each, which are available as
+    /// `£webmentions`, see [`Webmentions::to_html`].
+    ///
+    /// `Default: None`
+    pub webmentions: Option<Webmentions>,
+    /// The fediverse accounts that each output is attributed to, with a
+    /// `fediverse:creator` meta tag, which is also available as
+    /// `£fediverse_creator`.
+    ///
+    /// A Markdown can declare its own `fediverse_creator` in its meta section.
+    ///
+    /// `Default: None`
+    pub fediverse: Option<Fediverse>,
+    /// Derive the `£og_title`, `£og_description`, `£og_image`, and `£og_url`
+    /// of each Markdown, for its OpenGraph and Twitter card tags.
+    ///
+    /// The title is the `og_title`, or the title. The description is the
+    /// `og_description`, `description` or `summary`, or the excerpt. The
+    /// image is the `og_image`, `image` or `cover`, or the first local image of
+    /// the Markdown, or the `og_image` of the [`BatchOptions::variables`], and
+    /// is missing when there is none. The URL, and a relative image, are
+    /// absolute when there is a `base_url`.
+    ///
+    /// `Default: false`
+    pub open_graph: bool,
+    /// The arguments of each [`Filter`](crate::filters::Filter), by its name,
+    /// for when a template uses it without any, see
+    /// [`apply_filter_defaults`](crate::render::apply_filter_defaults).
+    pub filter_defaults: Vec<Meta>,
+    /// The [`Filter`](crate::filters::Filter) that each alias stands for, by
+    /// the alias, such as `upper` for `uppercase`, see
+    /// [`apply_filter_aliases`](crate::render::apply_filter_aliases).
+    ///
+    /// `Default: no aliases`
+    pub filter_aliases: Vec<Meta>,
+    /// The attributes to add to the elements of each Markdown that is run
+    /// through [`Filter::Markdown`](crate::filters::Filter::Markdown), such as
+    /// `table.class`, see [`add_attributes`](crate::render::add_attributes).
+    pub attributes: Vec<Meta>,
+    /// The element, such as `section`, that wraps each `##` section of each
+    /// Markdown that is run through
+    /// [`Filter::Markdown`](crate::filters::Filter::Markdown), see
+    /// [`wrap_sections`](crate::render::wrap_sections).
+    ///
+    /// `Default: None`
+    pub wrap_sections: Option<String>,
+    /// The formats that each output is also written in, beside it, such as
+    /// `index.html.gz`.
+    ///
+    /// Requires the `compression` feature.
+    #[cfg(feature = "compression")]
+    pub compression: Vec<Compression>,
+    /// The other versions of each output that are written beside it, such as
+    /// `hello.txt` for [`Alternate::Text`].
+    ///
+    /// `Default: []`
+    pub alternates: Vec<Alternate>,
+    /// The directory of the templates that a Markdown can choose for itself,
+    /// with `template = review.html` in its meta section, rather than those of
+    /// the batch.
+    ///
+    /// `Default: None`, which is the directory of the first template.
+    pub templates_dir: Option<PathBuf>,
+    /// The profiles that a Markdown can choose with `profile` in its meta
+    /// section, by their name.
+    ///
+    /// Like a Markdown that chooses its own template, a Markdown with a
+    /// profile is only rendered once, where the first template would write it.
+    ///
+    /// `Default: {}`
+    pub profiles: BTreeMap<String, Profile>,
+    /// The [`Plugin`]s that are called as each Markdown is rendered and each
+    /// file is written.
+    ///
+    /// `Default: no plugins`
+    pub hooks: Hooks,
+    /// The [`Pipeline`] that each Markdown is rendered through, whose stages
+    /// run around those of the batch.
+    ///
+    /// `Default: the built-in stages`
+    pub pipeline: Pipeline,
+    /// The locales, such as `en` and `fr`, that each Markdown is rendered in,
+    /// with its meta values [localised](localise_meta), and its `£lang`.
+    ///
+    /// The first locale is written to the [`BatchOptions::output_dir`], and
+    /// the others to a directory of their name within it. A translation of a
+    /// Markdown, such as `hello.fr.md`, is rendered in place of `hello.md` for
+    /// its locale.
+    ///
+    /// `Default: []`, which renders each Markdown once.
+    pub locales: Vec<String>,
+}
+
+/// The progress of a single Markdown file that has been rendered by
+/// [`render_batch`].
+///
+/// One of these will be passed to the progress callback after each file has
+/// been written, which makes it suitable for driving a progress bar.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchProgress {
+    /// The zero-based position of this file within the batch.
+    pub index: usize,
+    /// The total number of files within the batch; the number of templates
+    /// multiplied by the number of Markdowns.
+    pub total: usize,
+    /// The template that was used for this file.
+    pub template_path: PathBuf,
+    /// The Markdown that populated the template.
+    pub markdown_path: PathBuf,
+    /// The file that was written, or that would have been with
+    /// [`BatchOptions::dry_run`].
+    pub output_path: PathBuf,
+    /// Variables that were declared in the meta section of the Markdown, but
+    /// never used by the template. Those created for every Markdown, such as
+    /// the `content_hash`, are never unused.
+    pub unused_variables: Vec<String>,
+    /// Variables that were used by the template, but never declared, which
+    /// were rendered with the [`BatchOptions::on_missing`] policy.
+    pub missing_variables: Vec<String>,
+    /// Whether the output was already up to date in the
+    /// [`BatchOptions::build_cache`], and so was not written.
+    ///
+    /// The unused and missing variables of a skipped output are not checked.
+    pub skipped: bool,
+    /// Whether the Markdown asked search engines not to index it, see
+    /// [`is_noindex`].
+    pub noindex: bool,
+    /// Everything that is likely to be a mistake, including the unused and
+    /// missing variables.
+    pub warnings: Vec<Warning>,
+    /// The files that the other `templates` of the Markdown were rendered
+    /// into, beside the `output_path`, such as `hello.amp.html`.
+    pub variant_paths: Vec<PathBuf>,
+    /// The meta values that were declared in the Markdown.
+    pub meta: Vec<Meta>,
+    /// The SHA-256 of the contents of the `output_path`, as hexadecimal, or
+    /// `None` if the output was skipped.
+    pub content_hash: Option<String>,
+    /// How long the Markdown took to render, including its variants.
+    pub duration: Duration,
+}
+
+/// A token that can be shared with another thread in order to stop a
+/// [`render_batch`] before it has finished.
+///
+/// Cloning the token will share the same underlying flag, so cancelling any
+/// clone will cancel them all.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::CancellationToken;
+///
+/// let token = CancellationToken::new();
+/// let shared_token = token.clone();
+/// assert!(!token.is_cancelled());
+///
+/// shared_token.cancel();
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a new token that has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request that any batch using this token stops before the next file.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Check whether [`CancellationToken::cancel`] has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Localises the meta values of each Markdown, see [`localise_meta`].
+struct Localise {
+    locale: String,
+    locales: Vec<String>,
+}
+
+impl Plugin for Localise {
+    fn name(&self) -> &str {
+        "localise"
+    }
+
+    fn after_meta(&self, _markdown_path: &Path, meta_values: &mut Vec<Meta>) -> Result<(), Box<dyn Error>> {
+        *meta_values = localise_meta(std::mem::take(meta_values), &self.locale, &self.locales);
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Functions
+/// Render every Markdown into every template, writing the output to disk.
+///
+/// A Markdown with `template` in its meta section is only rendered into that
+/// template, from the [`BatchOptions::templates_dir`], and is written where
+/// the first template would write it.
+///
+/// After each file is written, `on_progress` is called with a
+/// [`BatchProgress`], and before each file the `cancellation` token is checked;
+/// if it has been cancelled then the batch stops, returning an error.
+///
+/// With the `parallel` feature, the Markdowns are rendered across every CPU,
+/// so `on_progress` is called in the order that they finish, and any that had
+/// already started when the batch was cancelled may still be written. The
+/// returned paths are always in the same order as the Markdowns.
+///
+/// Returns the paths of every file that was written.
+///
+/// # Example
+/// ```rust
+/// use std::path::PathBuf;
+/// use blogs_md_easy::{render_batch, BatchOptions, CancellationToken};
+///
+/// let templates = vec![PathBuf::from("tests/template.html")];
+/// let markdowns = vec![(PathBuf::from("tests/one.md"), std::fs::read_to_string("tests/one.md").unwrap())];
+/// let output_dir = std::env::temp_dir().join("blogs_md_easy_render_batch");
+///
+/// let options = BatchOptions { output_dir: Some(output_dir.clone()), ..Default::default() };
+///
+/// let mut progress = vec![];
+/// let token = CancellationToken::new();
+/// let outputs = render_batch(&templates, &markdowns, &options, |p| progress.push(p.index), &token).unwrap();
+/// assert_eq!(outputs, vec![output_dir.join("one.html")]);
+/// assert_eq!(progress, vec![0]);
+///
+/// // Once cancelled, no more files will be rendered.
+/// token.cancel();
+/// assert!(render_batch(&templates, &markdowns, &options, |_| {}, &token).is_err());
+/// ```
+pub fn render_batch<F>(templates: &[PathBuf], markdowns: &[(PathBuf, String)], options: &BatchOptions, mut on_progress: F, cancellation: &CancellationToken) -> Result<Vec<PathBuf>, Box<dyn Error>>
+where
+    F: FnMut(&BatchProgress),
+{
+    if !options.locales.is_empty() {
+        return render_locales(templates, markdowns, options, on_progress, cancellation);
+    }
+
+    // The glossary page is rendered like any other Markdown.
+    let mut markdowns = Cow::Borrowed(markdowns);
+    if !options.include_drafts && markdowns.iter().any(|(_, markdown)| is_draft(markdown)) {
+        markdowns.to_mut().retain(|(markdown_path, markdown)| {
+            let draft = is_draft(markdown);
+            if draft {
+                event!(info, "Skipped '{}', as it is a draft.", markdown_path.to_string_lossy());
+            }
+            !draft
+        });
+    }
+    if let Some(as_of) = &options.as_of {
+        markdowns.to_mut().retain(|(markdown_path, markdown)| {
+            let published = is_published_by(markdown_path, markdown, options);
+            if !published {
+                event!(info, "Skipped '{}', as it was published after {}.", markdown_path.to_string_lossy(), as_of);
+            }
+            published
+        });
+    }
+    // Every Markdown is checked, so that each problem is reported at once.
+    let problems = markdowns
+        .iter()
+        .flat_map(|(markdown_path, markdown)| options.schema.validate(markdown).into_iter().map(move |problem| format!("{}: {}", markdown_path.to_string_lossy(), problem)))
+        .collect::<Vec<String>>();
+    if !problems.is_empty() {
+        Err(format!("The meta sections do not match the schema.\n{}", problems.join("\n")))?;
+    }
+    // The glossary page isn't a post, so it keeps its own name.
+    let permalinks = match templates.first() {
+        Some(template_path) => permalinks(template_path, &markdowns, options)?,
+        None => HashMap::new(),
+    };
+    if let Some(glossary) = &options.glossary {
+        markdowns.to_mut().push((glossary.path.to_owned(), glossary.to_markdown()));
+    }
+    let markdowns = markdowns.as_ref();
+
+    let mut chosen = HashMap::new();
+    if let Some(first) = templates.first() {
+        for (markdown_path, markdown) in markdowns {
+            let profile = chosen_profile(markdown_path, markdown, options)?;
+            if let Some(template_path) = chosen_template(markdown, first, profile.map(|(_, profile)| profile), options) {
+                chosen.insert(markdown_path.as_path(), (template_path, profile));
+            }
+        }
+    }
+    // The variants of a Markdown are rendered along with its chosen template.
+    let mut variants = HashMap::new();
+    if let Some(first) = templates.first() {
+        for (markdown_path, markdown) in markdowns {
+            let paths = chosen_variants(markdown, first, options);
+            if let (false, Some((_, profile))) = (paths.is_empty(), chosen.get(markdown_path.as_path())) {
+                variants.insert(markdown_path.as_path(), (paths, *profile));
+            }
+        }
+    }
+    // The same template is loaded once for each profile that it is used with.
+    let mut chosen_templates = HashMap::new();
+    let variant_templates = variants.iter().flat_map(|(markdown_path, (paths, profile))| paths.iter().map(move |path| (markdown_path, (path, profile))));
+    for (markdown_path, (template_path, profile)) in chosen.iter().map(|(markdown_path, (path, profile))| (markdown_path, (path, profile))).chain(variant_templates) {
+        if let Entry::Vacant(entry) = chosen_templates.entry((template_path.to_owned(), profile.map(|(name, _)| name))) {
+            if !template_path.exists() {
+                Err(format!("The template '{}' of markdown '{}' does not exist.", template_path.to_string_lossy(), markdown_path.to_string_lossy()))?;
+            }
+            let template = match profile {
+                Some((_, profile)) => load_template(template_path, &profile.apply(options))?,
+                None => load_template(template_path, options)?,
+            };
+            entry.insert(template);
+        }
+    }
+
+    let total = match templates.is_empty() {
+        true => 0,
+        false => templates.len() * (markdowns.len() - chosen.len()) + chosen.len(),
+    };
+    let mut outputs = Vec::with_capacity(total);
+    let anchors = options.anchors.to_owned().unwrap_or_else(|| AnchorMap::new(markdowns));
+    // The Markdowns that failed, when every error is collected.
+    let mut errors: Vec<Box<dyn Error>> = vec![];
+
+    for (index, template_path) in templates.iter().enumerate() {
+        // Check that the actual template exists.
+        if !template_path.try_exists().map_err(|_| "The template could not be found.".to_string())? {
+            Err("The template file does not exist.".to_string())?;
+        };
+        span!("template", path = %template_path.to_string_lossy());
+        let template = load_template(template_path, options)?;
+
+        // Markdowns that chose their own template are rendered with the first.
+        let markdowns = markdowns
+            .iter()
+            .filter(|(markdown_path, _)| index == 0 || !chosen.contains_key(markdown_path.as_path()))
+            .collect::<Vec<&(PathBuf, String)>>();
+        let output_path_of = |markdown_path: &Path| match chosen.contains_key(markdown_path) {
+            true => batch_output_path(&templates[0], markdown_path, options, &permalinks, templates.len() > 1),
+            false => batch_output_path(template_path, markdown_path, options, &permalinks, templates.len() > 1),
+        };
+        let render_markdown = |(markdown_path, markdown): &(PathBuf, String)| {
+            if cancellation.is_cancelled() {
+                Err("The build was cancelled.".to_string())?;
+            }
+            let (template_path, template, profile) = match chosen.get(markdown_path.as_path()) {
+                Some((chosen, profile)) => (chosen.as_path(), &chosen_templates[&(chosen.to_owned(), profile.map(|(name, _)| name))], profile.map(|(_, profile)| profile)),
+                None => (template_path.as_path(), &template, None),
+            };
+            let mut templates = vec![(template_path, template)];
+            if let Some((paths, profile)) = variants.get(markdown_path.as_path()) {
+                templates.extend(paths.iter().map(|path| (path.as_path(), &chosen_templates[&(path.to_owned(), profile.map(|(name, _)| name))])));
+            }
+            // Only the alternates of a profile are written differently.
+            let alternated;
+            let options = match profile {
+                Some(profile @ Profile { alternates: Some(_), .. }) => {
+                    alternated = profile.apply(options);
+                    &alternated
+                },
+                _ => options,
+            };
+            render_output(&templates, markdown_path, markdown, &anchors, options, output_path_of)
+        };
+
+        // A single Markdown, such as one that an editor renders on each save,
+        // isn't worth starting the thread pool for.
+        let serial = !cfg!(feature = "parallel") || markdowns.len() < 2;
+        if serial {
+            for markdown in &markdowns {
+                let mut progress = match render_markdown(markdown) {
+                    Ok(progress) => progress,
+                    Err(e) if options.collect_errors && !cancellation.is_cancelled() => {
+                        errors.push(e);
+                        continue;
+                    },
+                    Err(e) => return Err(e),
+                };
+                progress.index = outputs.len();
+                progress.total = total;
+                on_progress(&progress);
+                outputs.push(progress.output_path);
+                outputs.extend(progress.variant_paths);
+            }
+        }
+
+        // Each Markdown is rendered on a worker thread, whilst the progress is
+        // reported on this thread, as it completes, so that `on_progress`
+        // doesn't need to be `Send`.
+        #[cfg(feature = "parallel")]
+        if !serial {
+            use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let rendered = std::thread::scope(|scope| {
+                let workers = scope.spawn(|| {
+                    let rendered = markdowns
+                        .par_iter()
+                        .map_with(sender, |sender, markdown| {
+                            // Errors aren't `Send`, so only their message is
+                            // kept, unless they point into a source.
+                            let progress = render_markdown(markdown).map_err(|e| -> Box<dyn Error + Send + Sync> {
+                                match e.downcast::<SourceError>() {
+                                    Ok(error) => error,
+                                    Err(e) => e.to_string().into(),
+                                }
+                            })?;
+                            let output_paths = [progress.output_path.to_owned()].into_iter().chain(progress.variant_paths.iter().cloned()).collect::<Vec<PathBuf>>();
+                            // The receiver only stops once every sender is gone.
+                            sender.send(progress).ok();
+                            Ok(output_paths)
+                        });
+                    // Without collecting every error, the first stops the rest.
+                    match options.collect_errors {
+                        true => rendered.collect::<Vec<Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>>>>(),
+                        false => match rendered.collect::<Result<Vec<Vec<PathBuf>>, _>>() {
+                            Ok(rendered) => rendered.into_iter().map(Ok).collect(),
+                            Err(e) => vec![Err(e)],
+                        },
+                    }
+                });
+
+                for (index, mut progress) in receiver.into_iter().enumerate() {
+                    // Markdowns that were already rendering may still finish.
+                    if cancellation.is_cancelled() {
+                        break;
+                    }
+                    progress.index = outputs.len() + index;
+                    progress.total = total;
+                    on_progress(&progress);
+                }
+                workers.join()
+            });
+            // The outputs remain in the same order as the Markdowns.
+            let rendered = match rendered {
+                Ok(rendered) => rendered,
+                Err(panic) => std::panic::resume_unwind(panic),
+            };
+            if cancellation.is_cancelled() {
+                Err("The build was cancelled.".to_string())?;
+            }
+            for output in rendered {
+                match output {
+                    Ok(output) => outputs.extend(output),
+                    Err(e) if options.collect_errors => errors.push(e),
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        Err(BatchError { errors })?;
+    }
+    Ok(outputs)
+}
+
+/// Render the batch once for each of the [`BatchOptions::locales`].
+fn render_locales<F>(templates: &[PathBuf], markdowns: &[(PathBuf, String)], options: &BatchOptions, mut on_progress: F, cancellation: &CancellationToken) -> Result<Vec<PathBuf>, Box<dyn Error>>
+where
+    F: FnMut(&BatchProgress),
+{
+    if options.locales.len() > 1 && options.output_dir.is_none() {
+        Err("Rendering more than one locale requires an output directory.".to_string())?;
+    }
+
+    let mut outputs = vec![];
+    for (index, locale) in options.locales.iter().enumerate() {
+        let mut variables = options.variables.to_owned();
+        variables.insert(0, Meta::new("lang", locale));
+        let mut hooks = Hooks::new().with(Localise { locale: locale.to_owned(), locales: options.locales.to_owned() });
+        hooks.plugins.extend(options.hooks.plugins.iter().cloned());
+        let localised = BatchOptions {
+            output_dir: match index {
+                0 => options.output_dir.to_owned(),
+                _ => options.output_dir.as_ref().map(|output_dir| output_dir.join(locale)),
+            },
+            variables,
+            hooks,
+            locales: vec![],
+            ..options.to_owned()
+        };
+
+        // The callback is a trait object, so that `render_batch` isn't
+        // instantiated for a new closure at each level of recursion.
+        let offset = outputs.len();
+        let report: &mut dyn FnMut(&BatchProgress) = &mut |progress| {
+            let mut progress = progress.to_owned();
+            progress.index += offset;
+            on_progress(&progress);
+        };
+        outputs.extend(render_batch(templates, &localised_markdowns(markdowns, locale, &options.locales), &localised, report, cancellation)?);
+    }
+    Ok(outputs)
+}
+
+/// The Markdowns of a `locale`, where a translation, such as `hello.fr.md`,
+/// takes the place of `hello.md`, and the translations into the other
+/// `locales` are left out.
+fn localised_markdowns(markdowns: &[(PathBuf, String)], locale: &str, locales: &[String]) -> Vec<(PathBuf, String)> {
+    let translated = markdowns
+        .iter()
+        .filter_map(|(markdown_path, _)| translation_of(markdown_path, locales))
+        .filter(|(_, translation)| translation == locale)
+        .map(|(original, _)| original)
+        .collect::<Vec<PathBuf>>();
+
+    markdowns
+        .iter()
+        .filter_map(|(markdown_path, markdown)| match translation_of(markdown_path, locales) {
+            Some((original, translation)) => (translation == locale).then(|| (original, markdown.to_owned())),
+            None => (!translated.contains(markdown_path)).then(|| (markdown_path.to_owned(), markdown.to_owned())),
+        })
+        .collect()
+}
+
+/// The Markdown that the one at `markdown_path` is a translation of, and its
+/// locale, such as `hello.md` and `fr` for `hello.fr.md`.
+fn translation_of(markdown_path: &Path, locales: &[String]) -> Option<(PathBuf, String)> {
+    let stem = Path::new(markdown_path.file_stem()?);
+    let locale = stem.extension()?.to_str()?;
+    if !locales.iter().any(|l| l == locale) {
+        return None;
+    }
+    let mut original = markdown_path.with_file_name(stem.file_stem()?);
+    if let Some(extension) = markdown_path.extension() {
+        original.set_extension(extension);
+    }
+    Some((original, locale.to_string()))
+}
+
+/// Render a single Markdown into the `template`, and write it to disk, unless
+/// the [`BatchOptions::build_cache`] shows that it is already up to date, or
+/// it is a [`BatchOptions::dry_run`].
+///
+/// The `index` and `total` of the returned [`BatchProgress`] are left for
+/// [`render_batch`] to fill in.
+fn render_output<F>(templates: &[(&Path, &Template)], markdown_path: &Path, markdown: &str, anchors: &AnchorMap, options: &BatchOptions, output_path_of: F) -> Result<BatchProgress, Box<dyn Error>>
+where
+    F: Fn(&Path) -> PathBuf,
+{
+    // The chosen template, followed by its variants.
+    let Some((&(template_path, template), variants)) = templates.split_first() else {
+        Err("There is no template to render.".to_string())?
+    };
+    span!("markdown", path = %markdown_path.to_string_lossy());
+    let started = Instant::now();
+
+    let output_path = output_path_of(markdown_path);
+    // Links are always to the template's outputs, whatever the format.
+    let written_path = Document::path(&output_path, options.format);
+    let mut progress = BatchProgress {
+        index: 0,
+        total: 0,
+        template_path: template_path.to_owned(),
+        markdown_path: markdown_path.to_owned(),
+        output_path: written_path.to_owned(),
+        unused_variables: vec![],
+        missing_variables: vec![],
+        skipped: false,
+        noindex: is_noindex(markdown),
+        warnings: vec![],
+        variant_paths: vec![],
+        meta: extract_meta(markdown),
+        content_hash: None,
+        duration: Duration::ZERO,
+    };
+
+    // Links to other Markdowns, and embeds, are part of the Markdown, so a
+    // change to their outputs will render this Markdown again.
+    let markdown = &resolve_links(markdown, markdown_path, anchors, &output_path, &output_path_of, options)
+        .map_err(|e| format!("{} In markdown '{}'.", e, markdown_path.to_string_lossy()))?;
+
+    // Skip the output if nothing that it was rendered from has changed.
+    let build_hash = options.build_cache.as_ref().map(|_| {
+        let sources = [template.source()].into_iter().chain(variants.iter().map(|(_, variant)| variant.source())).collect::<Vec<&str>>();
+        build_hash(&sources.join("\n"), markdown, options)
+    });
+    if let (Some(cache), Some(hash)) = (&options.build_cache, &build_hash) {
+        if cache.is_fresh(&written_path, hash) {
+            event!(info, "Skipped '{}', as it hasn't changed since the last build.", markdown_path.to_string_lossy());
+            progress.skipped = true;
+            progress.duration = started.elapsed();
+            return Ok(progress);
+        }
+    }
+
+    let document = render_document(template_path, template, markdown_path, markdown, &output_path, options, &output_path_of)?;
+    progress.unused_variables = document.unused_variables.to_owned();
+    progress.missing_variables = document.missing_variables.to_owned();
+    progress.warnings = template.errors().iter().cloned().map(Warning::Malformed)
+        .chain(document.unused_variables.iter().cloned().map(Warning::UnusedVariable))
+        .chain(document.missing_variables.iter().cloned().map(Warning::MissingVariable))
+        .chain(document.warnings.iter().cloned())
+        .collect();
+
+    let contents = match options.dry_run {
+        true => document.contents(options.format),
+        false => {
+            span!("write", path = %written_path.to_string_lossy());
+            for alternate in &options.alternates {
+                write_output(&output_path.with_extension(alternate.extension()), &alternate.convert(&document.html), options)?;
+            }
+            let contents = document.contents(options.format);
+            write_output(&written_path, &contents, options)?;
+            contents
+        }
+    };
+    progress.content_hash = Some(hash_hex(contents.as_bytes()));
+
+    // The variants share the Markdown, whose links have been resolved, and
+    // its filters through the cache, so only their templates are rendered.
+    for (variant_path, variant) in variants {
+        let variant_output_path = variant_output_path(&output_path, variant_path);
+        let document = render_document(variant_path, variant, markdown_path, markdown, &variant_output_path, options, &output_path_of)?;
+        // A variant, such as a snippet for a feed, needn't use every variable.
+        progress.warnings.extend(document.missing_variables.iter().cloned().map(Warning::MissingVariable).chain(document.warnings.iter().cloned()));
+        let written_path = Document::path(&variant_output_path, options.format);
+        if !options.dry_run {
+            write_output(&written_path, &document.contents(options.format), options)?;
+        }
+        progress.variant_paths.push(written_path);
+    }
+
+    if let (false, Some(cache), Some(hash)) = (options.dry_run, &options.build_cache, build_hash) {
+        cache.insert(written_path, hash);
+    }
+
+    progress.duration = started.elapsed();
+    Ok(progress)
+}
+
+/// Write an output, creating its directory, along with a copy in each of the
+/// [`BatchOptions::compression`] formats.
+pub(crate) fn write_output(output_path: &Path, contents: &str, options: &BatchOptions) -> Result<(), Box<dyn Error>> {
+    // Create all folders from the path.
+    if let Some(path) = output_path.parent() {
+        if !path.as_os_str().is_empty() && !path.exists() {
+            fs::create_dir_all(path)?;
+        }
+    }
+    let hooked;
+    let contents = match options.hooks.is_empty() {
+        true => contents,
+        false => {
+            let mut contents = contents.to_string();
+            options.hooks.before_write(output_path, &mut contents)?;
+            hooked = contents;
+            hooked.as_str()
+        },
+    };
+    match write_if_changed(output_path, contents.as_bytes())? {
+        true => {
+            event!(info, "Wrote '{}'.", output_path.to_string_lossy());
+        },
+        false => {
+            event!(info, "Skipped writing '{}', as it is unchanged.", output_path.to_string_lossy());
+        },
+    }
+    if let Some(manifest) = &options.manifest {
+        manifest.insert(output_path.to_owned());
+    }
+
+    #[cfg(feature = "compression")]
+    for compression in &options.compression {
+        let mut path = output_path.as_os_str().to_owned();
+        path.push(format!(".{}", compression.extension()));
+        write_if_changed(Path::new(&path), &compression.compress(contents.as_bytes())?)?;
+        if let Some(manifest) = &options.manifest {
+            manifest.insert(PathBuf::from(path));
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the `contents` to the file at `path`, unless it already contains
+/// exactly them, returning whether it was written.
+///
+/// An unchanged file keeps its modified time, so that a deploy with `rsync`,
+/// or another watcher, doesn't see it as changed.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::write_if_changed;
+///
+/// let path = std::env::temp_dir().join("blogs_md_easy_write_if_changed.html");
+/// std::fs::remove_file(&path).ok();
+/// assert!(write_if_changed(&path, b"<h1>Hello</h1>").unwrap());
+/// assert!(!write_if_changed(&path, b"<h1>Hello</h1>").unwrap());
+/// assert!(write_if_changed(&path, b"<h1>World</h1>").unwrap());
+/// ```
+pub fn write_if_changed(path: &Path, contents: &[u8]) -> io::Result<bool> {
+    // Only a file of the same length is read, to compare it.
+    let unchanged = fs::metadata(path).is_ok_and(|metadata| metadata.is_file() && metadata.len() == contents.len() as u64)
+        && fs::read(path).is_ok_and(|existing| existing == contents);
+    if !unchanged {
+        fs::write(path, contents)?;
+    }
+    Ok(!unchanged)
+}
+
+/// The hash of everything that an output of [`render_batch`] is rendered from,
+/// including the version of this crate, so that an upgrade renders everything
+/// again.
+fn build_hash(template: &str, markdown: &str, options: &BatchOptions) -> String {
+    let variables = options.variables
+        .iter()
+        .map(|meta| format!("{}={}", meta.key, meta.value))
+        .collect::<Vec<String>>()
+        .join("\n");
+    let attributes = options.attributes
+        .iter()
+        .map(|meta| format!("{}={}", meta.key, meta.value))
+        .collect::<Vec<String>>()
+        .join("\n");
+    let parts = [
+        env!("CARGO_PKG_VERSION"),
+        template,
+        markdown,
+        &variables,
+        &attributes,
+        &format!("{:?}", options.wrap_sections),
+        #[cfg(feature = "compression")]
+        &format!("{:?}", options.compression),
+        &format!("{:?}", options.alternates),
+        &format!("{:?}", options.embed_meta),
+        &format!("{:?}", options.on_missing),
+        &format!("{:?}", options.title_precedence),
+        &options.remove_heading.to_string(),
+        &format!("{:?}", options.words_per_minute),
+        &format!("{:?}", options.publish_date_from),
+        &format!("{:?}", options.as_of),
+        &options.copy_images.to_string(),
+        &options.image_sizes.to_string(),
+        &options.single_file.to_string(),
+        &options.heading_ids.to_string(),
+        &options.permalinks.to_string(),
+        &options.paragraph_ids.to_string(),
+        &format!("{:?}", options.link_extension),
+        &format!("{:?}", options.syntax),
+        &format!("{:?}", options.glossary),
+        &format!("{:?}", options.webmentions),
+        &format!("{:?}", options.fediverse),
+        &format!("{:?}", options.format),
+    ];
+    hash_hex(parts.join("\0").as_bytes())
+}
@@ -0,0 +1,319 @@
+//! The [`Error`] returned when a meta section or template cannot be parsed,
+//! along with where in the source it went wrong.
+
+use alloc::{format, string::{String, ToString}};
+use core::fmt;
+use nom::error::{ErrorKind, ParseError};
+use crate::parser::Span;
+
+////////////////////////////////////////////////////////////////////////////////
+// Structs and types
+/// The location of a parsing error, and a human-readable reason for it.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{Diagnostic, Span};
+/// use nom::Slice;
+///
+/// let input = Span::new(":meta\nauthor John Doe\n:meta");
+/// let diagnostic = Diagnostic::new(input.slice(13..), "expected `=` or `:`");
+///
+/// assert_eq!(diagnostic.line, 2);
+/// assert_eq!(diagnostic.column, 8);
+/// assert_eq!(diagnostic.offset, 13);
+/// assert_eq!(diagnostic.snippet, "2 | author John Doe\n  |        ^");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// The line of the source, starting from 1.
+    pub line: u32,
+    /// The column of the line, in characters, starting from 1.
+    pub column: usize,
+    /// The byte offset of the error within the source.
+    pub offset: usize,
+    /// How many bytes of the source are at fault, which is `0` when the error
+    /// is a single point, such as an unexpected character.
+    pub length: usize,
+    /// Why the source could not be parsed.
+    pub message: String,
+    /// The offending line of the source, with a `^` beneath the column.
+    pub snippet: String,
+}
+
+impl Diagnostic {
+    /// Create a `Diagnostic` pointing at the start of `span`.
+    pub fn new(span: Span, message: &str) -> Self {
+        let line = span.location_line();
+        let column = span.get_utf8_column();
+        let source = core::str::from_utf8(span.get_line_beginning()).unwrap_or_default().trim_end_matches('\r');
+
+        let gutter = line.to_string();
+        let snippet = format!(
+            "{} | {}\n{} | {}^",
+            gutter,
+            source,
+            " ".repeat(gutter.len()),
+            " ".repeat(column - 1),
+        );
+
+        Self {
+            line,
+            column,
+            offset: span.location_offset(),
+            length: 0,
+            message: message.to_string(),
+            snippet,
+        }
+    }
+
+    /// Mark `length` bytes of the source as at fault, rather than a single
+    /// point, such as a whole [`Placeholder`](crate::Placeholder).
+    pub fn with_length(mut self, length: usize) -> Self {
+        self.length = length;
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at line {}, column {}\n{}", self.message, self.line, self.column, self.snippet)
+    }
+}
+
+/// The reasons that a meta section or template cannot be parsed.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_meta_section, Error, Span};
+///
+/// let input = Span::new(":meta\ntitle = Hello\nauthor John Doe\n:meta");
+/// let Err(nom::Err::Error(Error::Meta(diagnostic))) = parse_meta_section(input) else {
+///     panic!("expected a meta error");
+/// };
+///
+/// assert_eq!(diagnostic.line, 3);
+/// assert_eq!(diagnostic.column, 1);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The meta section was opened, but could not be parsed.
+    Meta(Diagnostic),
+    /// A [`Placeholder`](crate::Placeholder) was opened, but could not be
+    /// parsed.
+    Placeholder(Diagnostic),
+    /// Any other input that a parser did not expect.
+    Parse(Diagnostic),
+    /// A [`Placeholder`](crate::Placeholder) was parsed, but could not be
+    /// rendered, such as when its variable doesn't exist.
+    Render(Diagnostic),
+}
+
+impl Error {
+    /// Where in the source the error occurred, and why.
+    pub fn diagnostic(&self) -> &Diagnostic {
+        match self {
+            Self::Meta(diagnostic) | Self::Placeholder(diagnostic) | Self::Parse(diagnostic) | Self::Render(diagnostic) => diagnostic,
+        }
+    }
+
+    /// What failed, without where or why.
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Meta(_) => "Unable to parse the meta section",
+            Self::Placeholder(_) => "Unable to parse the placeholder",
+            Self::Parse(_) => "Unable to parse",
+            Self::Render(_) => "Unable to render the placeholder",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.title(), self.diagnostic())
+    }
+}
+
+impl core::error::Error for Error {}
+
+impl<'a> ParseError<Span<'a>> for Error {
+    fn from_error_kind(input: Span<'a>, kind: ErrorKind) -> Self {
+        Self::Parse(Diagnostic::new(input, &format!("unexpected input, {}", kind.description().to_lowercase())))
+    }
+
+    fn append(_: Span<'a>, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+/// An [`Error`] within a file, along with the file's source, so that it can be
+/// reported with the offending lines.
+///
+/// Requires the `std` feature.
+///
+/// # Example
+/// ```rust
+/// use std::path::PathBuf;
+/// use blogs_md_easy::{error::SourceError, Template};
+///
+/// let source = "<h1>{{ £title | unknown }}</h1>";
+/// let error = Template::new(source).unwrap_err().downcast::<blogs_md_easy::Error>().unwrap();
+/// let error = SourceError::new(PathBuf::from("template.html"), source, *error);
+/// assert!(error.to_string().ends_with("In 'template.html'."));
+/// ```
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceError {
+    /// The file that the error is within.
+    pub path: std::path::PathBuf,
+    /// The contents of the file, which the [`Diagnostic`] points into.
+    pub source: String,
+    /// Why the file could not be parsed, or rendered.
+    pub error: Error,
+    /// Any other errors of the same kind within the file, such as the rest of
+    /// its malformed placeholders, so that they can be fixed together.
+    pub others: Vec<Error>,
+}
+
+#[cfg(feature = "std")]
+impl SourceError {
+    /// Create a `SourceError` for an `error` within `source`, which was read
+    /// from `path`.
+    ///
+    /// The rest of the `source` is searched for any [`others`](Self::others)
+    /// of the same kind, with [`placeholder_errors`](crate::placeholder_errors)
+    /// or [`meta_errors`](crate::meta_errors).
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::path::PathBuf;
+    /// use blogs_md_easy::{error::SourceError, Template};
+    ///
+    /// let source = "<h1>{{ £title | shout }}</h1>\n<p>{{ £author | whisper }}</p>";
+    /// let error = Template::new(source).unwrap_err().downcast::<blogs_md_easy::Error>().unwrap();
+    /// let error = SourceError::new(PathBuf::from("template.html"), source, *error);
+    /// assert_eq!(error.others.len(), 1);
+    /// assert_eq!(error.others[0].diagnostic().message, "unknown filter 'whisper'");
+    /// ```
+    pub fn new(path: std::path::PathBuf, source: &str, error: Error) -> Self {
+        let others = match &error {
+            Error::Meta(_) => crate::parser::meta_errors(Span::new(source)),
+            Error::Placeholder(_) => crate::parser::placeholder_errors(Span::new(source)),
+            _ => Vec::new(),
+        };
+        let others = others.into_iter().filter(|other| other != &error).collect();
+
+        Self { path, source: source.to_string(), error, others }
+    }
+
+    /// A report of each error, with the offending lines of the source and a
+    /// caret beneath the fault, in colour if `colour` is `true`.
+    ///
+    /// Requires the `pretty` feature.
+    #[cfg(feature = "pretty")]
+    pub fn report(&self, colour: bool) -> String {
+        core::iter::once(&self.error)
+            .chain(&self.others)
+            .map(|error| self.report_error(error, colour))
+            .collect()
+    }
+
+    /// A report of a single error, see [`SourceError::report`].
+    #[cfg(feature = "pretty")]
+    fn report_error(&self, error: &Error, colour: bool) -> String {
+        use ariadne::{Color, Config, Label, Report, ReportKind, Source};
+
+        let diagnostic = error.diagnostic();
+        let path = self.path.to_string_lossy().to_string();
+        // Point at the whole of the first character, when only a point is at
+        // fault.
+        let start = diagnostic.offset.min(self.source.len());
+        let length = match diagnostic.length {
+            0 => self.source[start..].chars().next().map(char::len_utf8).unwrap_or_default(),
+            length => length,
+        };
+        let range = start..(start + length).min(self.source.len());
+
+        let mut report = Vec::new();
+        Report::build(ReportKind::Error, (path.as_str(), range.to_owned()))
+            .with_config(Config::default().with_color(colour).with_index_type(ariadne::IndexType::Byte))
+            .with_message(error.title())
+            .with_label(Label::new((path.as_str(), range)).with_message(&diagnostic.message).with_color(Color::Red))
+            .finish()
+            .write((path.as_str(), Source::from(self.source.as_str())), &mut report)
+            .ok();
+
+        String::from_utf8_lossy(&report).to_string()
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        for error in &self.others {
+            write!(f, "\n{}", error)?;
+        }
+        write!(f, "\nIn '{}'.", self.path.to_string_lossy())
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for SourceError {}
+
+/// Every error of a batch that was rendered with
+/// [`BatchOptions::collect_errors`](crate::BatchOptions::collect_errors), in
+/// the order of the Markdowns, so that each broken post can be fixed together.
+///
+/// Requires the `std` feature.
+///
+/// # Example
+/// ```rust
+/// use std::path::PathBuf;
+/// use blogs_md_easy::{error::{BatchError, SourceError}, Template};
+///
+/// let source = "<h1>{{ £title | shout }}</h1>\n<p>{{ £author | whisper }}</p>";
+/// let error = Template::new(source).unwrap_err().downcast::<blogs_md_easy::Error>().unwrap();
+/// let error = SourceError::new(PathBuf::from("template.html"), source, *error);
+///
+/// let batch = BatchError { errors: vec![Box::new(error), "The build was cancelled.".into()] };
+/// assert_eq!(batch.diagnostics().len(), 2);
+/// assert!(batch.to_string().ends_with("The build was cancelled."));
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct BatchError {
+    /// The error of each Markdown that could not be rendered.
+    pub errors: Vec<Box<dyn core::error::Error>>,
+}
+
+#[cfg(feature = "std")]
+impl BatchError {
+    /// Every [`Diagnostic`] within the errors, including the
+    /// [`others`](SourceError::others) of each [`SourceError`].
+    ///
+    /// Errors without a location, such as a hook that failed, have none.
+    pub fn diagnostics(&self) -> Vec<&Diagnostic> {
+        self.errors
+            .iter()
+            .flat_map(|error| match (error.downcast_ref::<SourceError>(), error.downcast_ref::<Error>()) {
+                (Some(error), _) => core::iter::once(&error.error).chain(&error.others).map(Error::diagnostic).collect(),
+                (None, Some(error)) => vec![error.diagnostic()],
+                (None, None) => vec![],
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} of the markdowns could not be rendered.", self.errors.len())?;
+        for error in &self.errors {
+            write!(f, "\n\n{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for BatchError {}
@@ -0,0 +1,119 @@
+//! `extern "C"` functions of the render API, so that the engine can be
+//! embedded in a program that isn't written in Rust, such as a CMS that
+//! previews posts.
+//!
+//! Every string is UTF-8 and NUL terminated. A function that fails returns
+//! `NULL`, and writes its error to `error`, unless `error` is `NULL`. Every
+//! string that is returned, including errors, must be freed with
+//! [`blogs_md_easy_free`].
+//! ```c
+//! char *error = NULL;
+//! char *html = blogs_md_easy_render("<h1>{{ £title }}</h1>", "# Hello", &error);
+//! if (html == NULL) {
+//!     fprintf(stderr, "%s\n", error);
+//!     blogs_md_easy_free(error);
+//! } else {
+//!     puts(html);
+//!     blogs_md_easy_free(html);
+//! }
+//! ```
+//!
+//! Requires the `ffi` feature.
+
+use std::{ffi::{c_char, CStr, CString}, ptr};
+use crate::{error::Error as ParseError, parser::{parse_meta_section, Span}, render};
+
+/// Render a Markdown into a template, see [`render::render`].
+///
+/// # Safety
+/// The `template` and `markdown` must be NUL terminated strings, and `error`
+/// must be `NULL`, or point to a `char *` that can be written to.
+#[no_mangle]
+pub unsafe extern "C" fn blogs_md_easy_render(template: *const c_char, markdown: *const c_char, error: *mut *mut c_char) -> *mut c_char {
+    let output = read_str(template, "template")
+        .and_then(|template| Ok((template, read_str(markdown, "markdown")?)))
+        .and_then(|(template, markdown)| render::render(template, markdown).map_err(|e| e.to_string()));
+    into_c(output, error)
+}
+
+/// Rewrite every placeholder of a template into its canonical text, see
+/// [`render::format_template`].
+///
+/// # Safety
+/// The `template` must be a NUL terminated string, and `error` must be
+/// `NULL`, or point to a `char *` that can be written to.
+#[no_mangle]
+pub unsafe extern "C" fn blogs_md_easy_format_template(template: *const c_char, error: *mut *mut c_char) -> *mut c_char {
+    let output = read_str(template, "template")
+        .and_then(|template| render::format_template(template).map_err(|e| e.to_string()));
+    into_c(output, error)
+}
+
+/// Parse the meta section of a Markdown into a JSON object of its keys and
+/// values, in the order that they are declared, such as
+/// `{"author":"John Doe"}`.
+///
+/// A Markdown without a meta section is an empty object.
+///
+/// # Safety
+/// The `markdown` must be a NUL terminated string, and `error` must be
+/// `NULL`, or point to a `char *` that can be written to.
+#[no_mangle]
+pub unsafe extern "C" fn blogs_md_easy_parse_meta(markdown: *const c_char, error: *mut *mut c_char) -> *mut c_char {
+    let output = read_str(markdown, "markdown").and_then(|markdown| {
+        let meta_values = match parse_meta_section(Span::new(markdown)) {
+            Ok((_, meta_values)) => meta_values,
+            Err(nom::Err::Error(error @ ParseError::Meta(_))) => return Err(error.to_string()),
+            // There is no meta section.
+            Err(_) => vec![],
+        };
+        let object = meta_values
+            .into_iter()
+            .map(|meta| (meta.key, serde_json::Value::String(meta.value)))
+            .collect::<serde_json::Map<String, serde_json::Value>>();
+        Ok(serde_json::Value::Object(object).to_string())
+    });
+    into_c(output, error)
+}
+
+/// Free a string that was returned by one of these functions, which does
+/// nothing for `NULL`.
+///
+/// # Safety
+/// The `string` must have been returned by one of these functions, and not
+/// already freed.
+#[no_mangle]
+pub unsafe extern "C" fn blogs_md_easy_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Read a NUL terminated string, which is named for its errors.
+///
+/// # Safety
+/// The `string` must be `NULL`, or NUL terminated.
+unsafe fn read_str<'a>(string: *const c_char, name: &str) -> Result<&'a str, String> {
+    if string.is_null() {
+        return Err(format!("The {} was NULL.", name));
+    }
+    CStr::from_ptr(string).to_str().map_err(|_| format!("The {} was not valid UTF-8.", name))
+}
+
+/// Return the `output` as a string for C, or otherwise `NULL`, writing the
+/// error to `error`.
+///
+/// # Safety
+/// The `error` must be `NULL`, or point to a `char *` that can be written to.
+unsafe fn into_c(output: Result<String, String>, error: *mut *mut c_char) -> *mut c_char {
+    let output = output.and_then(|output| CString::new(output).map_err(|_| "The output contained a NUL character.".to_string()));
+    match output {
+        Ok(output) => output.into_raw(),
+        Err(message) => {
+            if !error.is_null() {
+                *error = CString::new(message.replace('\0', "")).unwrap_or_default().into_raw();
+            }
+            ptr::null_mut()
+        },
+    }
+}
@@ -0,0 +1,2313 @@
+//! [`Filter`]s that transform a variable as it is rendered into a template.
+
+use alloc::{borrow::Cow, format, string::{String, ToString}, vec, vec::Vec};
+use core::{fmt, ops::{Div, Mul}, str::FromStr};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use unicode_segmentation::UnicodeSegmentation;
+use crate::{meta::{join_meta_list, split_meta_list, split_quoted}, parser::{is_filter_value, parse_literal, Span}};
+
+////////////////////////////////////////////////////////////////////////////////
+// Structs and types
+/// A list of all the available text case `Filter`s.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum TextCase {
+    /// Converts a string into lowercase.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter, TextCase};
+    ///
+    /// let input = "Hello, World!".to_string();
+    /// let filter = Filter::Text { case: TextCase::Lower };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "hello, world!");
+    /// ```
+    Lower,
+    /// Converts a string into uppercase.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter, TextCase};
+    ///
+    /// let input = "Hello, World!".to_string();
+    /// let filter = Filter::Text { case: TextCase::Upper };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "HELLO, WORLD!");
+    /// ```
+    Upper,
+    /// Converts a string into title case.
+    ///
+    /// Every character that supersedes a space or hyphen.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter, TextCase};
+    ///
+    /// let input = "john doe-bloggs".to_string();
+    /// let filter = Filter::Text { case: TextCase::Title };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "John Doe-Bloggs");
+    /// assert_eq!(render_filter("élodie über", &filter).unwrap(), "Élodie Über");
+    /// ```
+    Title,
+    /// Converts a string into sentence case.
+    ///
+    /// Only the first letter is capitalised, and every other character is
+    /// made lowercase.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter, TextCase};
+    ///
+    /// let input = "hELLO, wORLD!".to_string();
+    /// let filter = Filter::Text { case: TextCase::Sentence };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "Hello, world!");
+    /// ```
+    Sentence,
+    /// Converts a string into kebab case.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter, TextCase};
+    ///
+    /// let input = "kebab case".to_string();
+    /// let filter = Filter::Text { case: TextCase::Kebab };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "kebab-case");
+    /// ```
+    /// Converts a string into kebab case.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter, TextCase};
+    ///
+    /// let input = "kebab case".to_string();
+    /// let filter = Filter::Text { case: TextCase::Kebab };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "kebab-case");
+    /// ```
+    Kebab,
+    /// Converts a string into snake case.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter, TextCase};
+    ///
+    /// let input = "snake case".to_string();
+    /// let filter = Filter::Text { case: TextCase::Snake };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "snake_case");
+    /// ```
+    Snake,
+    /// Converts a string into Pascal case.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter, TextCase};
+    ///
+    /// let input = "pascal case".to_string();
+    /// let filter = Filter::Text { case: TextCase::Pascal };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "PascalCase");
+    /// ```
+    Pascal,
+    /// Converts a string into camel case.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter, TextCase};
+    ///
+    /// let input = "camel case".to_string();
+    /// let filter = Filter::Text { case: TextCase::Camel };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "camelCase");
+    /// ```
+    Camel,
+    /// Converts a string by inverting the case.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter, TextCase};
+    ///
+    /// let input = "Hello, World!".to_string();
+    /// let filter = Filter::Text { case: TextCase::Invert };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "hELLO, wORLD!");
+    /// ```
+    Invert,
+}
+
+impl FromStr for TextCase {
+    type Err = String;
+
+    /// Parse a string slice, into a `TextCase`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use blogs_md_easy::TextCase;
+    /// // For both lower and upper, the word "case" can be appended.
+    /// assert_eq!("lower".parse::<TextCase>(), Ok(TextCase::Lower));
+    /// assert_eq!("lowercase".parse::<TextCase>(), Ok(TextCase::Lower));
+    ///
+    /// // For programming cases, the word case can be appended in that style.
+    /// assert_eq!("snake".parse::<TextCase>(), Ok(TextCase::Snake));
+    /// assert_eq!("snake_case".parse::<TextCase>(), Ok(TextCase::Snake));
+    /// assert_eq!("title".parse::<TextCase>(), Ok(TextCase::Title));
+    /// assert_eq!("Title".parse::<TextCase>(), Ok(TextCase::Title));
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lower" | "lowercase" => Ok(Self::Lower),
+            "upper" | "uppercase" | "UPPERCASE" => Ok(Self::Upper),
+            "title" | "Title" => Ok(Self::Title),
+            "sentence" | "Sentence" => Ok(Self::Sentence),
+            "kebab" | "kebab-case" => Ok(Self::Kebab),
+            "snake" | "snake_case" => Ok(Self::Snake),
+            "pascal" | "PascalCase" => Ok(Self::Pascal),
+            "camel" | "camelCase" => Ok(Self::Camel),
+            "invert" | "inverse" => Ok(Self::Invert),
+            _ => Err(format!("Unable to parse TextCase from '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for TextCase {
+    /// The name of the case, which can be [parsed](TextCase::from_str) back
+    /// into the same `TextCase`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::TextCase;
+    ///
+    /// assert_eq!(TextCase::Snake.to_string(), "snake");
+    /// assert_eq!("snake".parse::<TextCase>(), Ok(TextCase::Snake));
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Lower => "lower",
+            Self::Upper => "upper",
+            Self::Title => "title",
+            Self::Sentence => "sentence",
+            Self::Kebab => "kebab",
+            Self::Snake => "snake",
+            Self::Pascal => "pascal",
+            Self::Camel => "camel",
+            Self::Invert => "invert",
+        })
+    }
+}
+
+/// The algorithms of the [`Filter::Hash`] filter.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum HashAlgorithm {
+    /// SHA-256, which is 64 hexadecimal characters.
+    Sha256,
+    /// MD5, which is 32 hexadecimal characters, and only suitable where the
+    /// hash doesn't need to be secure, such as cache-busting.
+    Md5,
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = String;
+
+    /// Parse a string slice, into a `HashAlgorithm`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::HashAlgorithm;
+    ///
+    /// assert_eq!("sha256".parse::<HashAlgorithm>(), Ok(HashAlgorithm::Sha256));
+    /// assert_eq!("SHA-256".parse::<HashAlgorithm>(), Ok(HashAlgorithm::Sha256));
+    /// assert_eq!("md5".parse::<HashAlgorithm>(), Ok(HashAlgorithm::Md5));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sha256" | "sha-256" => Ok(Self::Sha256),
+            "md5" => Ok(Self::Md5),
+            _ => Err(format!("Unable to parse HashAlgorithm from '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    /// The name of the algorithm, which can be
+    /// [parsed](HashAlgorithm::from_str) back into the same `HashAlgorithm`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Sha256 => "sha256",
+            Self::Md5 => "md5",
+        })
+    }
+}
+
+/// The reasons that a [`Filter`] can't render a value, see [`render_filter`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilterError {
+    /// A maths filter was given a value that isn't a number.
+    NotANumber {
+        /// The filter that expected a number.
+        filter: Filter,
+        /// The value that the filter was given.
+        value: String,
+    },
+    /// An argument of the filter isn't valid, such as `divide = 0`, or a `now`
+    /// of `time_ago` that isn't a date.
+    InvalidArgument {
+        /// The filter that the argument was given to.
+        filter: Filter,
+        /// The name of the argument.
+        argument: String,
+        /// The value of the argument.
+        value: String,
+    },
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotANumber { filter, value } => write!(f, "the {} filter expected a number, but was given '{}'", filter.name(), value),
+            Self::InvalidArgument { filter, argument, value } => write!(f, "the {} filter was given an invalid {} '{}'", filter.name(), argument, value),
+        }
+    }
+}
+
+impl core::error::Error for FilterError {}
+
+/// Predefined functions names that will be used within [`render_filter`] to
+/// convert a value.
+///
+/// With the `serde` feature, a filter is an object of its `name`, in snake
+/// case, and its arguments, such as `{"name": "round", "precision": 2}`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(tag = "name", rename_all = "snake_case"))]
+pub enum Filter {
+    // Maths filters
+
+    /// Rounds a numeric value up to the nearest whole number.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "1.234".to_string();
+    /// let filter = Filter::Ceil;
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "2");
+    /// ```
+    Ceil,
+    /// Rounds a numeric value down to the nearest whole number.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "4.567".to_string();
+    /// let filter = Filter::Floor;
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "4");
+    /// ```
+    Floor,
+    /// Round a number to a given precision.
+    ///
+    /// `Default argument: precision`
+    ///
+    /// # Examples
+    /// Precision of 0 to remove decimal place.
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "1.234".to_string();
+    /// let filter = Filter::Round { precision: 0 };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "1");
+    /// ```
+    ///
+    /// Precision of 3 for three decimal places.
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "1.23456789".to_string();
+    /// let filter = Filter::Round { precision: 3 };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "1.235");
+    /// ```
+    Round {
+        /// The number of decimal places to round to.
+        /// A half is rounded down.
+        ///
+        /// `Default: 0`
+        ///
+        /// # Examples
+        /// Providing no arguments.
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("round");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::Round { .. }));
+        /// assert_eq!(filter, Filter::Round { precision: 0 });
+        /// ```
+        ///
+        /// Providing the default argument.
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("round = 3");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::Round { .. }));
+        /// assert_eq!(filter, Filter::Round { precision: 3 });
+        /// ```
+        ///
+        /// Alternatively, it is possible to be more explicit.
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("round = precision: 42");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::Round { .. }));
+        /// assert_eq!(filter, Filter::Round { precision: 42 });
+        /// ```
+        precision: u8,
+    },
+    /// Add a number to a numeric value.
+    ///
+    /// `Default argument: value`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{parse_filter, render_filter, Filter, Span};
+    ///
+    /// let (_, filter) = parse_filter(Span::new("add = 2.5")).unwrap();
+    /// assert_eq!(filter, Filter::Add { value: "2.5".to_string() });
+    /// assert_eq!(render_filter("1", &filter).unwrap(), "3.5");
+    /// ```
+    Add {
+        /// The number to add, as it is written.
+        ///
+        /// `Default: 0`
+        value: String,
+    },
+    /// Subtract a number from a numeric value.
+    ///
+    /// `Default argument: value`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{parse_filter, render_filter, Filter, Span};
+    ///
+    /// let (_, filter) = parse_filter(Span::new("subtract = 5")).unwrap();
+    /// assert_eq!(render_filter("3", &filter).unwrap(), "-2");
+    /// ```
+    Subtract {
+        /// The number to subtract, as it is written.
+        ///
+        /// `Default: 0`
+        value: String,
+    },
+    /// Multiply a numeric value by a number, such as to add tax to a price.
+    ///
+    /// `Default argument: value`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{parse_filter, render_filter, Filter, Span};
+    ///
+    /// let (_, filter) = parse_filter(Span::new("multiply = 1.2")).unwrap();
+    /// assert_eq!(render_filter("10", &filter).unwrap(), "12");
+    /// ```
+    Multiply {
+        /// The number to multiply by, as it is written.
+        ///
+        /// `Default: 1`
+        value: String,
+    },
+    /// Divide a numeric value by a number.
+    ///
+    /// `Default argument: value`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{parse_filter, render_filter, Filter, Span};
+    ///
+    /// let (_, filter) = parse_filter(Span::new("divide = 4")).unwrap();
+    /// assert_eq!(render_filter("10", &filter).unwrap(), "2.5");
+    ///
+    /// // A value can't be divided by zero.
+    /// let (_, filter) = parse_filter(Span::new("divide = 0")).unwrap();
+    /// assert_eq!(filter, Filter::Divide { value: "0".to_string() });
+    /// assert!(render_filter("10", &filter).is_err());
+    /// ```
+    Divide {
+        /// The number to divide by, as it is written.
+        ///
+        /// `Default: 1`
+        value: String,
+    },
+
+    // String filter
+
+    /// Decodes a base64 string.
+    ///
+    /// If the value is not valid base64, then it is a
+    /// [`FilterError::InvalidArgument`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "SGVsbG8sIFdvcmxkIQ==".to_string();
+    /// let filter = Filter::Base64Decode;
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "Hello, World!");
+    /// ```
+    Base64Decode,
+    /// Encodes a string as base64.
+    ///
+    /// This is useful for embedding small assets, such as an inline `data:`
+    /// URI.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "Hello, World!".to_string();
+    /// let filter = Filter::Base64Encode;
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "SGVsbG8sIFdvcmxkIQ==");
+    /// ```
+    Base64Encode,
+    /// Replaces a value that is empty, or a variable that is missing, with
+    /// `value`, so that an optional meta key can be left out of a Markdown.
+    ///
+    /// A placeholder can also be given a default with `??`, such as
+    /// `{{ £author ?? "Anonymous" }}`, which is the same as
+    /// `{{ £author | default = Anonymous }}`.
+    ///
+    /// `Default argument: value`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let filter = Filter::Default { value: "Anonymous".to_string() };
+    ///
+    /// assert_eq!(render_filter("".to_string(), &filter).unwrap(), "Anonymous");
+    /// assert_eq!(render_filter("John Doe".to_string(), &filter).unwrap(), "John Doe");
+    /// ```
+    Default {
+        /// The String that replaces an empty value.
+        ///
+        /// `Default: ""`
+        ///
+        /// # Example
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("default = \"No one\"");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert_eq!(filter, Filter::Default { value: "No one".to_string() });
+        /// ```
+        value: String,
+    },
+    /// Extracts a short excerpt from a Markdown string.
+    ///
+    /// If the string contains a `<!-- more -->` marker, then everything before
+    /// the marker is the excerpt.  \
+    /// Otherwise, the first paragraph will be used, skipping over any headings.
+    ///
+    /// # Examples
+    /// Without a marker, the first paragraph is used.
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "# Markdown Title\nFirst paragraph.\n\nSecond paragraph.".to_string();
+    /// let filter = Filter::Excerpt;
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "First paragraph.");
+    /// ```
+    ///
+    /// With a marker, everything before the marker is used.
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "First paragraph.\n\nSecond paragraph.\n<!-- more -->\nThird paragraph.".to_string();
+    /// let filter = Filter::Excerpt;
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "First paragraph.\n\nSecond paragraph.");
+    /// ```
+    Excerpt,
+    /// Keeps only the first `words` of a string, discarding the rest.
+    ///
+    /// Unlike [`Filter::Truncate`], this will never split a word in half.
+    /// Words are separated by any whitespace, and will be joined back together
+    /// with a single space.
+    ///
+    /// `Default argument: words`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "The quick brown fox jumps over the lazy dog.".to_string();
+    /// let filter = Filter::FirstWords { words: 4 };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "The quick brown fox");
+    /// ```
+    FirstWords {
+        /// The number of words to keep from the start of the String.
+        ///
+        /// If this number is greater than the number of words in the String,
+        /// then every word is kept.
+        ///
+        /// `Default: 10`
+        ///
+        /// # Examples
+        /// Without an argument, this will default to ten words.
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("first_words");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::FirstWords { .. }));
+        /// assert_eq!(filter, Filter::FirstWords { words: 10 });
+        /// ```
+        ///
+        /// Alternatively, it is possible to be more explicit.
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("first_words = words: 3");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::FirstWords { .. }));
+        /// assert_eq!(filter, Filter::FirstWords { words: 3 });
+        /// ```
+        words: u8,
+    },
+    /// Replaces the value with a hexadecimal digest of it, such as for
+    /// cache-busting the URL of an asset, or a stable anchor ID.
+    ///
+    /// `Default argument: algo`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter, HashAlgorithm};
+    ///
+    /// let input = "Hello, World!".to_string();
+    /// let filter = Filter::Hash { algo: HashAlgorithm::Sha256, length: Some(8) };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "dffd6021");
+    /// ```
+    Hash {
+        /// The algorithm of the digest.
+        ///
+        /// `Default: sha256`
+        ///
+        /// # Example
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, HashAlgorithm, Span};
+        ///
+        /// let input = Span::new("hash = md5");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::Hash { .. }));
+        /// assert_eq!(filter, Filter::Hash { algo: HashAlgorithm::Md5, length: None });
+        ///
+        /// // Any other algorithm is an error.
+        /// assert!(parse_filter(Span::new("hash = sha265")).is_err());
+        /// ```
+        algo: HashAlgorithm,
+        /// The number of hexadecimal characters to keep from the start of the
+        /// digest, or all of them if this is `None`.
+        ///
+        /// `Default: None`
+        ///
+        /// # Example
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, HashAlgorithm, Span};
+        ///
+        /// let input = Span::new("hash = length: 10");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::Hash { .. }));
+        /// assert_eq!(filter, Filter::Hash { algo: HashAlgorithm::Sha256, length: Some(10) });
+        /// ```
+        length: Option<u8>,
+    },
+    /// Replaces the value with `then` when it is truthy, or with `otherwise`
+    /// when it is not, such as a `(draft)` label for a `draft = true` meta.
+    ///
+    /// A value is not truthy when it is empty, `false`, `0`, or `no`, ignoring
+    /// case and surrounding whitespace.
+    ///
+    /// `Default argument: then`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let filter = Filter::If { then: "(draft)".to_string(), otherwise: "".to_string() };
+    ///
+    /// assert_eq!(render_filter("true".to_string(), &filter).unwrap(), "(draft)");
+    /// assert_eq!(render_filter("false".to_string(), &filter).unwrap(), "");
+    /// ```
+    If {
+        /// The String that replaces a truthy value.
+        ///
+        /// `Default: ""`
+        ///
+        /// # Example
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("if = \"(draft)\"");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::If { .. }));
+        /// assert_eq!(filter, Filter::If {
+        ///     then: "(draft)".to_string(),
+        ///     otherwise: "".to_string(),
+        /// });
+        /// ```
+        then: String,
+        /// The String that replaces a value that is not truthy, which is
+        /// written as `else`.
+        ///
+        /// `Default: ""`
+        ///
+        /// # Example
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("if = then: Yes, else: No");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::If { .. }));
+        /// assert_eq!(filter, Filter::If {
+        ///     then: "Yes".to_string(),
+        ///     otherwise: "No".to_string(),
+        /// });
+        /// ```
+        #[cfg_attr(feature = "serde", serde(rename = "else"))]
+        otherwise: String,
+    },
+    /// Joins the items of a list with a different `separator`.
+    ///
+    /// Lists, such as `tags = [rust, parsing, nom]` in the meta section, are
+    /// stored with each item separated by a comma and a space.
+    ///
+    /// `Default argument: separator`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "rust, parsing, nom".to_string();
+    /// let filter = Filter::Join { separator: " / ".to_string() };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "rust / parsing / nom");
+    /// ```
+    Join {
+        /// The String that will be placed between each item.
+        ///
+        /// `Default: ", "`
+        ///
+        /// # Examples
+        /// Without an argument, the list is left as it is.
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("join");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::Join { .. }));
+        /// assert_eq!(filter, Filter::Join { separator: ", ".to_string() });
+        /// ```
+        ///
+        /// Alternatively, it is possible to be more explicit.
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("join = separator: <br>");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::Join { .. }));
+        /// assert_eq!(filter, Filter::Join { separator: "<br>".to_string() });
+        /// ```
+        separator: String,
+    },
+    /// Keeps only the last `words` of a string, discarding the rest.
+    ///
+    /// Words are separated by any whitespace, and will be joined back together
+    /// with a single space.
+    ///
+    /// `Default argument: words`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "The quick brown fox jumps over the lazy dog.".to_string();
+    /// let filter = Filter::LastWords { words: 2 };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "lazy dog.");
+    /// ```
+    LastWords {
+        /// The number of words to keep from the end of the String.
+        ///
+        /// If this number is greater than the number of words in the String,
+        /// then every word is kept.
+        ///
+        /// `Default: 10`
+        ///
+        /// # Example
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("last_words = 5");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::LastWords { .. }));
+        /// assert_eq!(filter, Filter::LastWords { words: 5 });
+        /// ```
+        words: u8,
+    },
+    /// Converts a string from Markdown into HTML.
+    ///
+    /// The GitHub-Flavoured Markdown extensions are each enabled with an
+    /// argument, such as `markdown = tables: true, tasklists: true`.
+    ///
+    /// Requires the `markdown` feature, otherwise the value is left unchanged.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "markdown")] {
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = r#"# Markdown Title
+    /// First paragraph.
+    ///
+    /// [example.com](https://example.com)
+    ///
+    /// * Unordered list
+    ///
+    /// 1. Ordered list"#.to_string();
+    /// let filter = Filter::Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, r#"<h1>Markdown Title</h1>
+    /// <p>First paragraph.</p>
+    /// <p><a href="https://example.com">example.com</a></p>
+    /// <ul>
+    /// <li>Unordered list</li>
+    /// </ul>
+    /// <ol>
+    /// <li>Ordered list</li>
+    /// </ol>"#);
+    /// # }
+    /// ```
+    Markdown {
+        /// Whether GitHub-Flavoured Markdown tables are rendered as a
+        /// `<table>`, rather than as a paragraph.
+        ///
+        /// `Default: false`
+        ///
+        /// # Examples
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("markdown = tables: true");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert_eq!(filter, Filter::Markdown { tables: true, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 });
+        /// ```
+        ///
+        /// ```rust
+        /// # #[cfg(feature = "markdown")] {
+        /// use blogs_md_easy::{render_filter, Filter};
+        ///
+        /// let input = "| Name |\n| ---- |\n| Rust |".to_string();
+        /// let filter = Filter::Markdown { tables: true, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 };
+        /// let output = render_filter(input, &filter).unwrap();
+        ///
+        /// assert_eq!(output, "<table>\n<thead>\n<tr>\n<th>Name</th>\n</tr>\n</thead>\n<tbody>\n<tr>\n<td>Rust</td>\n</tr>\n</tbody>\n</table>");
+        /// # }
+        /// ```
+        tables: bool,
+        /// Whether text between tildes, such as `~~old~~`, is struck through
+        /// with `<del>`.
+        ///
+        /// `Default: false`
+        ///
+        /// # Example
+        /// ```rust
+        /// # #[cfg(feature = "markdown")] {
+        /// use blogs_md_easy::{render_filter, Filter};
+        ///
+        /// let input = "~~Old~~ New".to_string();
+        /// let filter = Filter::Markdown { tables: false, strikethrough: true, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 };
+        /// let output = render_filter(input, &filter).unwrap();
+        ///
+        /// assert_eq!(output, "<p><del>Old</del> New</p>");
+        /// # }
+        /// ```
+        strikethrough: bool,
+        /// Whether list items that start with `[ ]` or `[x]` are given a
+        /// checkbox.
+        ///
+        /// `Default: false`
+        ///
+        /// # Example
+        /// ```rust
+        /// # #[cfg(feature = "markdown")] {
+        /// use blogs_md_easy::{render_filter, Filter};
+        ///
+        /// let input = "* [x] Done\n* [ ] To do".to_string();
+        /// let filter = Filter::Markdown { tables: false, strikethrough: false, tasklists: true, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 };
+        /// let output = render_filter(input, &filter).unwrap();
+        ///
+        /// assert_eq!(output, "<ul>\n<li><input type=\"checkbox\" disabled=\"\" checked=\"\" /> Done</li>\n<li><input type=\"checkbox\" disabled=\"\" /> To do</li>\n</ul>");
+        /// # }
+        /// ```
+        tasklists: bool,
+        /// Whether `[^1]` references are linked to their footnote, which are
+        /// each listed in a section at the end of the HTML.
+        ///
+        /// `Default: false`
+        ///
+        /// # Example
+        /// ```rust
+        /// # #[cfg(feature = "markdown")] {
+        /// use blogs_md_easy::{render_filter, Filter};
+        ///
+        /// let input = "Cited.[^1]\n\n[^1]: A source.".to_string();
+        /// let filter = Filter::Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: true, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 };
+        /// let output = render_filter(input, &filter).unwrap();
+        ///
+        /// assert!(output.starts_with("<p>Cited.<sup><a href=\"#user-content-fn-1\""));
+        /// assert!(output.contains("<li id=\"user-content-fn-1\">\n<p>A source."));
+        /// # }
+        /// ```
+        footnotes: bool,
+        /// Whether straight quotes, `--`, `---`, and `...` are converted into
+        /// curly quotes, en and em dashes, and ellipses.
+        ///
+        /// The text of `<code>`, `<pre>`, `<kbd>`, and `<samp>` is left as it
+        /// is, so that code isn't changed.
+        ///
+        /// `Default: false`
+        ///
+        /// # Example
+        /// ```rust
+        /// # #[cfg(feature = "markdown")] {
+        /// use blogs_md_easy::{render_filter, Filter};
+        ///
+        /// let input = "\"It's 9--5...\" --- `a--b`".to_string();
+        /// let filter = Filter::Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: true, hard_breaks: false, html: true, heading_offset: 0 };
+        /// let output = render_filter(input, &filter).unwrap();
+        ///
+        /// assert_eq!(output, "<p>“It’s 9–5…” — <code>a--b</code></p>");
+        /// # }
+        /// ```
+        smart_punctuation: bool,
+        /// Whether each line break within a paragraph is kept as a `<br />`,
+        /// rather than joining the lines with a space.
+        ///
+        /// `Default: false`
+        ///
+        /// # Example
+        /// ```rust
+        /// # #[cfg(feature = "markdown")] {
+        /// use blogs_md_easy::{parse_filter, render_filter, Span};
+        ///
+        /// let (_, filter) = parse_filter(Span::new("markdown = hard_breaks: true")).unwrap();
+        /// let output = render_filter("Roses are red,\nViolets are blue.\n\n```\ncode\nblock\n```".to_string(), &filter).unwrap();
+        ///
+        /// assert_eq!(output, "<p>Roses are red,<br />\nViolets are blue.</p>\n<pre><code>code\nblock\n</code></pre>");
+        /// # }
+        /// ```
+        hard_breaks: bool,
+        /// Whether the raw HTML of the Markdown is kept, otherwise it is
+        /// stripped, such as for comments from readers.
+        ///
+        /// `Default: true`
+        ///
+        /// # Example
+        /// ```rust
+        /// # #[cfg(feature = "markdown")] {
+        /// use blogs_md_easy::{parse_filter, render_filter, Span};
+        ///
+        /// let (_, filter) = parse_filter(Span::new("markdown = html: false")).unwrap();
+        /// let output = render_filter("Hello, <b>World</b>!\n\n<script>alert(1)</script>".to_string(), &filter).unwrap();
+        ///
+        /// assert_eq!(output, "<p>Hello, World!</p>\n");
+        /// # }
+        /// ```
+        html: bool,
+        /// How many levels each heading is moved down, so that a `#` is an
+        /// `<h2>` when the template already has an `<h1>`. No heading is moved
+        /// beyond an `<h6>`.
+        ///
+        /// `Default: 0`
+        ///
+        /// # Example
+        /// ```rust
+        /// # #[cfg(feature = "markdown")] {
+        /// use blogs_md_easy::{parse_filter, render_filter, Span};
+        ///
+        /// let (_, filter) = parse_filter(Span::new("markdown = heading_offset: 1")).unwrap();
+        /// let output = render_filter("# Title\n###### Smallest".to_string(), &filter).unwrap();
+        ///
+        /// assert_eq!(output, "<h2>Title</h2>\n<h6>Smallest</h6>");
+        /// # }
+        /// ```
+        heading_offset: u8,
+    },
+    /// Converts a string from inline Markdown into HTML, such as emphasis,
+    /// links, and code, without wrapping it in a `<p>`, so that it can be used
+    /// inside a `<title>` or `<h1>`.
+    ///
+    /// Block elements, such as headings and lists, are left as text, and
+    /// paragraphs are joined with a space.
+    ///
+    /// Requires the `markdown` feature, otherwise the value is left unchanged.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "markdown")] {
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "Why *Rust* and [`nom`](https://docs.rs/nom)?".to_string();
+    /// let output = render_filter(input, &Filter::MarkdownInline).unwrap();
+    ///
+    /// assert_eq!(output, r#"Why <em>Rust</em> and <a href="https://docs.rs/nom"><code>nom</code></a>?"#);
+    /// # }
+    /// ```
+    MarkdownInline,
+    /// Replace a given substring with another. Optionally, limit the number of
+    /// replacements from the start of the string.
+    ///
+    /// `Default argument: find`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "Hello, World!".to_string();
+    /// let filter = Filter::Replace {
+    ///     find: "World".to_string(),
+    ///     replacement: "Rust".to_string(),
+    ///     limit: None,
+    /// };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "Hello, Rust!");
+    /// ```
+    Replace {
+        /// The substring that we are looking for.
+        find: String,
+        /// The substring that will replace what we `find`.
+        replacement: String,
+        /// Limit the number of replacements from the start of the string.
+        ///
+        /// `Default: None`
+        ///
+        /// # Examples
+        /// Without an argument, this will default to doing nothing.
+        /// ```rust
+        /// use blogs_md_easy::{parse_placeholder, render_filter, Filter, Span};
+        ///
+        /// let input = Span::new("{{ £greeting | replace }}");
+        /// let (_, placeholder) = parse_placeholder(input).unwrap();
+        ///
+        /// assert!(matches!(placeholder.filters[0], Filter::Replace { .. }));
+        /// assert_eq!(placeholder.filters[0], Filter::Replace {
+        ///     find: "".to_string(),
+        ///     replacement: "".to_string(),
+        ///     limit: None,
+        /// });
+        ///
+        /// let greeting = "Hello, World!".to_string();
+        /// // Cloning here, only so we can reuse the `greeting` variable in
+        /// // assert, to prove that they are identical.
+        /// let output = render_filter(greeting.clone(), &placeholder.filters[0]).unwrap();
+        /// assert_eq!(output, greeting);
+        /// ```
+        ///
+        /// Providing the default argument.
+        /// In this case the value will be assigned to `find`, and the
+        /// `replacement` will be an empty String, essentially removing this
+        /// phrase from the string.
+        /// ```rust
+        /// use blogs_md_easy::{parse_placeholder, render_filter, Filter, Span};
+        ///
+        /// let input = Span::new("{{ £greeting | replace = World }}");
+        /// let (_, placeholder) = parse_placeholder(input).unwrap();
+        ///
+        /// assert!(matches!(placeholder.filters[0], Filter::Replace { .. }));
+        /// assert_eq!(placeholder.filters[0], Filter::Replace {
+        ///     find: "World".to_string(),
+        ///     replacement: "".to_string(),
+        ///     limit: None,
+        /// });
+        ///
+        /// let greeting = "Hello, World!".to_string();
+        /// let output = render_filter(greeting, &placeholder.filters[0]).unwrap();
+        /// assert_eq!(output, "Hello, !".to_string());
+        /// ```
+        ///
+        /// Specify the number of replacements.
+        /// ```rust
+        /// use blogs_md_easy::{parse_placeholder, render_filter, Filter, Span};
+        ///
+        /// let input = Span::new("{{ £greeting | replace = !, limit: 2 }}");
+        /// let (_, placeholder) = parse_placeholder(input).unwrap();
+        ///
+        /// assert!(matches!(placeholder.filters[0], Filter::Replace { .. }));
+        /// assert_eq!(placeholder.filters[0], Filter::Replace {
+        ///     find: "!".to_string(),
+        ///     replacement: "".to_string(),
+        ///     limit: Some(2),
+        /// });
+        ///
+        /// let greeting = "Hello, World!!!".to_string();
+        /// let output = render_filter(greeting, &placeholder.filters[0]).unwrap();
+        /// assert_eq!(output, "Hello, World!".to_string());
+        /// ```
+        ///
+        /// Setting all arguments explicitly.
+        /// ```rust
+        /// use blogs_md_easy::{parse_placeholder, render_filter, Filter, Span};
+        ///
+        /// let input = Span::new("{{ £greeting | replace = find: World, replacement: Rust, limit: 1 }}");
+        /// let (_, placeholder) = parse_placeholder(input).unwrap();
+        ///
+        /// assert!(matches!(placeholder.filters[0], Filter::Replace { .. }));
+        /// assert_eq!(placeholder.filters[0], Filter::Replace {
+        ///     find: "World".to_string(),
+        ///     replacement: "Rust".to_string(),
+        ///     limit: Some(1),
+        /// });
+        ///
+        /// let greeting = "Hello, World! Hello, World!".to_string();
+        /// let output = render_filter(greeting, &placeholder.filters[0]).unwrap();
+        /// assert_eq!(output, "Hello, Rust! Hello, World!".to_string());
+        /// ```
+        limit: Option<u8>,
+    },
+    /// Reverse a string, character by character.
+    ///
+    /// Characters are the ones that are seen, so an accented letter or an
+    /// emoji that is made of several code points keeps its order.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "Hello, World!".to_string();
+    /// let filter = Filter::Reverse;
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "!dlroW ,olleH");
+    /// assert_eq!(render_filter("Cafe\u{301} 👍🏽", &filter).unwrap(), "👍🏽 e\u{301}faC");
+    /// ```
+    Reverse,
+    /// Splits a string into a list, on each occurrence of `on`, so that it can
+    /// be used like a list in the meta section, such as with [`Filter::Join`].
+    ///
+    /// Each item is trimmed, and empty items are removed.
+    ///
+    /// `Default argument: on`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "rust;parsing; nom;".to_string();
+    /// let filter = Filter::Split { on: ";".to_string() };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "rust, parsing, nom");
+    /// ```
+    Split {
+        /// The String that separates each item.
+        ///
+        /// `Default: ","`
+        ///
+        /// # Example
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("split = on: \"|\"");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::Split { .. }));
+        /// assert_eq!(filter, Filter::Split { on: "|".to_string() });
+        /// ```
+        on: String,
+    },
+    /// Converts text to another format.
+    ///
+    /// Currently, the only argument is `case`.
+    ///
+    /// `Default argument: case`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter, TextCase};
+    ///
+    /// let input = "Hello, World!".to_string();
+    /// let filter = Filter::Text { case: TextCase::Upper };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "HELLO, WORLD!");
+    /// ```
+    Text {
+        /// Specifies the [`TextCase`] that the font should use.
+        ///
+        /// `Default: lower`
+        ///
+        /// # Examples
+        /// Without an argument, this will default to lowercase.
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span, TextCase};
+        ///
+        /// let input = Span::new("text");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::Text { .. }));
+        /// assert_eq!(filter, Filter::Text { case: TextCase::Lower });
+        /// ```
+        ///
+        /// Passing in a case, without an argument is possible too.
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span, TextCase};
+        ///
+        /// let input = Span::new("text = upper");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::Text { .. }));
+        /// assert_eq!(filter, Filter::Text { case: TextCase::Upper });
+        /// ```
+        ///
+        /// Alternatively, it is possible to be more explicit.
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span, TextCase};
+        ///
+        /// let input = Span::new("text = case: snake");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::Text { .. }));
+        /// assert_eq!(filter, Filter::Text { case: TextCase::Snake });
+        /// ```
+        case: TextCase,
+    },
+    /// Describes how long ago a date was, such as `3 days ago`, or how far
+    /// away it is, such as `in 2 weeks`.
+    ///
+    /// Dates are compared by their day, so a time of day, such as in
+    /// `2024-05-01T09:00:00Z`, is ignored. A value that isn't a date is left
+    /// as it is.
+    ///
+    /// `Default argument: now`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let filter = Filter::TimeAgo { now: "2024-05-04".to_string() };
+    ///
+    /// assert_eq!(render_filter("2024-05-01".to_string(), &filter).unwrap(), "3 days ago");
+    /// assert_eq!(render_filter("2024-05-03".to_string(), &filter).unwrap(), "yesterday");
+    /// assert_eq!(render_filter("2024-06-04".to_string(), &filter).unwrap(), "in 1 month");
+    /// ```
+    TimeAgo {
+        /// The date that the value is compared to, which is useful for
+        /// rendering the same output each time, such as in tests.
+        ///
+        /// An empty `now` is today, according to the system clock, which is
+        /// only available with the `std` feature.
+        ///
+        /// `Default: ""`
+        ///
+        /// # Examples
+        /// Without an argument, the value is compared to today.
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("age");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::TimeAgo { .. }));
+        /// assert_eq!(filter, Filter::TimeAgo { now: "".to_string() });
+        /// ```
+        ///
+        /// Alternatively, it is possible to be more explicit.
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("time_ago = now: 2024-05-04");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::TimeAgo { .. }));
+        /// assert_eq!(filter, Filter::TimeAgo { now: "2024-05-04".to_string() });
+        /// ```
+        now: String,
+    },
+    /// Truncates a string to a given length, and applies a `trail`ing string,
+    /// if the string was truncated.
+    ///
+    /// The length is counted in characters as they are seen, so an accented
+    /// letter or an emoji that is made of several code points is never split.
+    ///
+    /// `Default argument: characters`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "Hello, World!".to_string();
+    /// let filter = Filter::Truncate { characters: 5, trail: "...".to_string() };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "Hello...");
+    ///
+    /// let filter = Filter::Truncate { characters: 4, trail: "…".to_string() };
+    /// assert_eq!(render_filter("Cafe\u{301} au lait".to_string(), &filter).unwrap(), "Cafe\u{301}…");
+    /// ```
+    Truncate {
+        /// The number of characters the String will be cut to.
+        ///
+        /// If this number is greater than the String's length, then nothing
+        /// happens to the String.
+        ///
+        /// `Default: 100`
+        ///
+        /// # Example
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("truncate = trail: --");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::Truncate { .. }));
+        /// assert_eq!(filter, Filter::Truncate {
+        ///     characters: 100,
+        ///     trail: "--".to_string(),
+        /// });
+        /// ```
+        characters: u8,
+        /// The trailing characters to be appended to a truncated String.
+        ///
+        /// Due to this being appended, that means that your string will exceed
+        /// the characters length.  \
+        /// To counter this, you will need to reduce your `characters` value.
+        ///
+        /// `Default: "..."`
+        ///
+        /// # Example
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("truncate = characters: 42");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::Truncate { .. }));
+        /// assert_eq!(filter, Filter::Truncate {
+        ///     characters: 42,
+        ///     trail: "...".to_string(),
+        /// });
+        /// ```
+        trail: String,
+    },
+    /// Truncates a string to a number of whole `words`, and applies a
+    /// `trail`ing string, if the string was truncated.
+    ///
+    /// Words are separated by whitespace, which is replaced by a single space.
+    ///
+    /// `Default argument: words`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let input = "The quick brown fox jumps over the lazy dog".to_string();
+    /// let filter = Filter::TruncateWords { words: 4, trail: "...".to_string() };
+    /// let output = render_filter(input, &filter).unwrap();
+    ///
+    /// assert_eq!(output, "The quick brown fox...");
+    /// ```
+    TruncateWords {
+        /// The number of words the String will be cut to.
+        ///
+        /// `Default: 10`
+        ///
+        /// # Example
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("truncate_words = 20");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::TruncateWords { .. }));
+        /// assert_eq!(filter, Filter::TruncateWords {
+        ///     words: 20,
+        ///     trail: "...".to_string(),
+        /// });
+        /// ```
+        words: u8,
+        /// The trailing characters to be appended to a truncated String.
+        ///
+        /// `Default: "..."`
+        ///
+        /// # Example
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("truncate_words = trail: …");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::TruncateWords { .. }));
+        /// assert_eq!(filter, Filter::TruncateWords {
+        ///     words: 10,
+        ///     trail: "…".to_string(),
+        /// });
+        /// ```
+        trail: String,
+    },
+    /// Surrounds a value with the strings `before` and `after` it, or replaces
+    /// it with nothing if it is empty, such as an optional subtitle.
+    ///
+    /// `Default argument: before`
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{render_filter, Filter};
+    ///
+    /// let filter = Filter::Wrap { before: "<h2>".to_string(), after: "</h2>".to_string() };
+    ///
+    /// assert_eq!(render_filter("Subtitle".to_string(), &filter).unwrap(), "<h2>Subtitle</h2>");
+    /// assert_eq!(render_filter(" ".to_string(), &filter).unwrap(), "");
+    /// ```
+    Wrap {
+        /// The String that is placed before the value.
+        ///
+        /// `Default: ""`
+        ///
+        /// # Example
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("wrap = \"(\"");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::Wrap { .. }));
+        /// assert_eq!(filter, Filter::Wrap {
+        ///     before: "(".to_string(),
+        ///     after: "".to_string(),
+        /// });
+        /// ```
+        before: String,
+        /// The String that is placed after the value.
+        ///
+        /// `Default: ""`
+        ///
+        /// # Example
+        /// ```rust
+        /// use blogs_md_easy::{parse_filter, Filter, Span};
+        ///
+        /// let input = Span::new("wrap = before: <h2>, after: </h2>");
+        /// let (_, filter) = parse_filter(input).unwrap();
+        ///
+        /// assert!(matches!(filter, Filter::Wrap { .. }));
+        /// assert_eq!(filter, Filter::Wrap {
+        ///     before: "<h2>".to_string(),
+        ///     after: "</h2>".to_string(),
+        /// });
+        /// ```
+        after: String,
+    },
+}
+
+impl Filter {
+    /// Whether the filter is slow enough that its output is worth caching, see
+    /// [`FilterCache`](crate::render::FilterCache).
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::Filter;
+    ///
+    /// assert!(Filter::Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 }.is_expensive());
+    /// assert!(!Filter::Reverse.is_expensive());
+    /// ```
+    pub fn is_expensive(&self) -> bool {
+        matches!(self, Self::Markdown { .. } | Self::MarkdownInline)
+    }
+
+    /// Whether the filter expects a numeric value, such as [`Filter::Round`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::Filter;
+    ///
+    /// assert!(Filter::Multiply { value: "1.2".to_string() }.is_maths());
+    /// assert!(!Filter::Reverse.is_maths());
+    /// ```
+    pub fn is_maths(&self) -> bool {
+        matches!(self, Self::Ceil | Self::Floor | Self::Round { .. } | Self::Add { .. } | Self::Subtract { .. } | Self::Multiply { .. } | Self::Divide { .. })
+    }
+
+    /// The name of the filter, as it is written in a placeholder.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{Filter, TextCase};
+    ///
+    /// assert_eq!(Filter::Round { precision: 2 }.name(), "round");
+    /// assert_eq!(Filter::Text { case: TextCase::Snake }.name(), "text");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ceil => "ceil",
+            Self::Floor => "floor",
+            Self::Round { .. } => "round",
+            Self::Add { .. } => "add",
+            Self::Subtract { .. } => "subtract",
+            Self::Multiply { .. } => "multiply",
+            Self::Divide { .. } => "divide",
+            Self::Base64Decode => "base64_decode",
+            Self::Base64Encode => "base64_encode",
+            Self::Default { .. } => "default",
+            Self::Excerpt => "excerpt",
+            Self::FirstWords { .. } => "first_words",
+            Self::Hash { .. } => "hash",
+            Self::If { .. } => "if",
+            Self::Join { .. } => "join",
+            Self::LastWords { .. } => "last_words",
+            Self::Markdown { .. } => "markdown",
+            Self::MarkdownInline => "markdown_inline",
+            Self::Replace { .. } => "replace",
+            Self::Reverse => "reverse",
+            Self::Split { .. } => "split",
+            Self::Text { case: TextCase::Lower } => "lowercase",
+            Self::Text { case: TextCase::Upper } => "uppercase",
+            Self::Text { .. } => "text",
+            Self::TimeAgo { .. } => "time_ago",
+            Self::Truncate { .. } => "truncate",
+            Self::TruncateWords { .. } => "truncate_words",
+            Self::Wrap { .. } => "wrap",
+        }
+    }
+}
+
+impl fmt::Display for Filter {
+    /// The canonical form of the filter, as it is written in a placeholder,
+    /// which is [parsed](crate::parser::parse_filter) back into the same
+    /// `Filter`.
+    ///
+    /// Arguments that have their default value are left out, and a filter
+    /// with only one argument is given it without its name.  \
+    /// An argument that contains a space, `|`, `,`, `:`, `{`, or `}` is
+    /// wrapped in double quotes, unless it contains a `"` itself.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{parse_filter, Filter, Span};
+    ///
+    /// let (_, filter) = parse_filter(Span::new("truncate = trail: …,characters:100")).unwrap();
+    /// assert_eq!(filter.to_string(), "truncate = trail: …");
+    ///
+    /// let (_, filter) = parse_filter(Span::new("text=case:upper")).unwrap();
+    /// assert_eq!(filter.to_string(), "uppercase");
+    ///
+    /// let (_, filter) = parse_filter(Span::new("join = separator: \" | \"")).unwrap();
+    /// assert_eq!(filter.to_string(), "join = \" | \"");
+    ///
+    /// let (_, parsed) = parse_filter(Span::new(&filter.to_string())).unwrap();
+    /// assert_eq!(parsed, filter);
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, args): (&str, Vec<(&str, String)>) = match self {
+            Self::Ceil => ("ceil", vec![]),
+            Self::Floor => ("floor", vec![]),
+            Self::Round { precision } => ("round", if *precision == 0 { vec![] } else { vec![("_", precision.to_string())] }),
+            Self::Add { value } => ("add", if value == "0" { vec![] } else { vec![("_", value.clone())] }),
+            Self::Subtract { value } => ("subtract", if value == "0" { vec![] } else { vec![("_", value.clone())] }),
+            Self::Multiply { value } => ("multiply", if value == "1" { vec![] } else { vec![("_", value.clone())] }),
+            Self::Divide { value } => ("divide", if value == "1" { vec![] } else { vec![("_", value.clone())] }),
+            Self::Base64Decode => ("base64_decode", vec![]),
+            Self::Base64Encode => ("base64_encode", vec![]),
+            Self::Default { value } => ("default", if value.is_empty() { vec![] } else { vec![("_", value.clone())] }),
+            Self::Excerpt => ("excerpt", vec![]),
+            Self::FirstWords { words } => ("first_words", if *words == 10 { vec![] } else { vec![("_", words.to_string())] }),
+            Self::Hash { algo, length } => ("hash", [
+                ("algo", Some(algo.to_string()).filter(|_| *algo != HashAlgorithm::Sha256)),
+                ("length", length.map(|length| length.to_string())),
+            ].into_iter().filter_map(|(key, value)| value.map(|value| (key, value))).collect()),
+            Self::If { then, otherwise } => ("if", [
+                ("then", then),
+                ("else", otherwise),
+            ].into_iter().filter(|(_, value)| !value.is_empty()).map(|(key, value)| (key, value.clone())).collect()),
+            Self::Join { separator } => ("join", if separator == ", " { vec![] } else { vec![("_", separator.clone())] }),
+            Self::LastWords { words } => ("last_words", if *words == 10 { vec![] } else { vec![("_", words.to_string())] }),
+            Self::Markdown { tables, strikethrough, tasklists, footnotes, smart_punctuation, hard_breaks, html, heading_offset } => ("markdown", [
+                ("tables", tables),
+                ("strikethrough", strikethrough),
+                ("tasklists", tasklists),
+                ("footnotes", footnotes),
+                ("smart_punctuation", smart_punctuation),
+                ("hard_breaks", hard_breaks),
+            ].into_iter().filter(|(_, enabled)| **enabled).map(|(key, _)| (key, "true".to_string()))
+                .chain((!html).then(|| ("html", "false".to_string())))
+                .chain((*heading_offset > 0).then(|| ("heading_offset", heading_offset.to_string())))
+                .collect()),
+            Self::MarkdownInline => ("markdown_inline", vec![]),
+            Self::Replace { find, replacement, limit } => ("replace", [
+                ("find", Some(find.clone()).filter(|find| !find.is_empty())),
+                ("replacement", Some(replacement.clone()).filter(|replacement| !replacement.is_empty())),
+                ("limit", limit.map(|limit| limit.to_string())),
+            ].into_iter().filter_map(|(key, value)| value.map(|value| (key, value))).collect()),
+            Self::Reverse => ("reverse", vec![]),
+            Self::Split { on } => ("split", if on == "," { vec![] } else { vec![("_", on.clone())] }),
+            Self::Text { case: TextCase::Lower } => ("lowercase", vec![]),
+            Self::Text { case: TextCase::Upper } => ("uppercase", vec![]),
+            Self::Text { case } => ("text", vec![("_", case.to_string())]),
+            Self::TimeAgo { now } => ("time_ago", if now.is_empty() { vec![] } else { vec![("_", now.clone())] }),
+            Self::Truncate { characters, trail } => ("truncate", [
+                ("characters", Some(characters.to_string()).filter(|_| *characters != 100)),
+                ("trail", Some(trail.clone()).filter(|trail| trail != "...")),
+            ].into_iter().filter_map(|(key, value)| value.map(|value| (key, value))).collect()),
+            Self::TruncateWords { words, trail } => ("truncate_words", [
+                ("words", Some(words.to_string()).filter(|_| *words != 10)),
+                ("trail", Some(trail.clone()).filter(|trail| trail != "...")),
+            ].into_iter().filter_map(|(key, value)| value.map(|value| (key, value))).collect()),
+            Self::Wrap { before, after } => ("wrap", [
+                ("before", before),
+                ("after", after),
+            ].into_iter().filter(|(_, value)| !value.is_empty()).map(|(key, value)| (key, value.clone())).collect()),
+        };
+
+        f.write_str(name)?;
+        for (index, (key, value)) in args.iter().enumerate() {
+            f.write_str(if index == 0 { " = " } else { ", " })?;
+            if *key != "_" {
+                write!(f, "{}: ", key)?;
+            }
+            match value.contains(|c| !is_filter_value(c) || c == ':') && !value.contains('"') {
+                true => write!(f, "\"{}\"", value)?,
+                false => f.write_str(value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Functions
+/// Make the start of each word capital, splitting on `sep`.
+///
+/// # Examples
+/// A simple phrase with a space.
+/// ```rust
+/// use blogs_md_easy::split_string;
+///
+/// let phrase = "Hello World";
+/// let phrase = split_string(phrase.to_string(), &[' ', '-']);
+/// //let phrase = phrase.iter().map(|word| word.as_str()).collect::<&str>();
+/// assert_eq!(phrase, vec!["Hello", " ", "World"]);
+/// ```
+///
+/// A name with a hyphen.
+/// ```rust
+/// use blogs_md_easy::split_string;
+///
+/// let phrase = "John Doe-Bloggs";
+/// let phrase = split_string(phrase.to_string(), &[' ', '-']);
+/// //let phrase = phrase.iter().map(|word| word.as_str()).collect::<&str>();
+/// assert_eq!(phrase, vec!["John", " ", "Doe", "-", "Bloggs"]);
+/// ```
+///
+/// Two separators in a row.
+/// ```rust
+/// use blogs_md_easy::split_string;
+///
+/// let phrase = "Hello, World!";
+/// let phrase = split_string(phrase.to_string(), &[' ', ',', '!']);
+/// //let phrase = phrase.iter().map(|word| word.as_str()).collect::<&str>()
+/// assert_eq!(phrase, vec!["Hello", ",", " ", "World", "!"]);
+/// ```
+pub fn split_string(phrase: String, separators: &[char]) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current_word = String::new();
+
+    for c in phrase.chars() {
+        // If we hit a separator; push the current word, then the separator.
+        // Otherwise, add the character to the current word.
+        if separators.contains(&c) {
+            // Make sure that we aren't pushing an empty string into the Vec.
+            // This cannot be added as an `&&` above, because otherwise it
+            // pushes a separator onto the start of `current_word` in the event
+            // that we have two separators in a row.
+            if !current_word.is_empty() {
+                words.push(current_word.clone());
+                current_word.clear();
+            }
+            words.push(c.to_string());
+        } else {
+            current_word.push(c);
+        }
+    }
+
+    if !current_word.is_empty() {
+        words.push(current_word);
+    }
+    words
+}
+
+/// Take a variable, and run it through a [`Filter`] function to get the new
+/// output.
+///
+/// The variable can be borrowed or owned, and a value that the filter would
+/// leave unchanged is returned without being copied.
+///
+/// A maths filter of a value that isn't a number, or a filter with an
+/// argument that isn't valid, is a [`FilterError`].
+///
+/// For an example of how these [`Filter`]s work within a [`Placeholder`](crate::parser::Placeholder), see
+/// [`parse_placeholder`](crate::parser::parse_placeholder).
+///
+/// # Examples
+/// [`Filter`] that has no arguments.
+/// ```rust
+/// use blogs_md_easy::{render_filter, Filter, TextCase};
+///
+/// let variable = "hello, world!".to_string();
+/// assert_eq!("HELLO, WORLD!", render_filter(variable, &Filter::Text { case: TextCase::Upper }).unwrap());
+/// ```
+///
+/// [`Filter`] that has arguments.
+/// ```rust
+/// use blogs_md_easy::{render_filter, Filter};
+///
+/// let variable = "hello, world!".to_string();
+/// assert_eq!("hello...", render_filter(variable, &Filter::Truncate { characters: 5, trail: "...".to_string() }).unwrap());
+/// ```
+///
+/// [`Filter`] that can't render its value.
+/// ```rust
+/// use blogs_md_easy::{render_filter, Filter, FilterError};
+///
+/// let error = render_filter("free", &Filter::Ceil).unwrap_err();
+/// assert_eq!(error, FilterError::NotANumber { filter: Filter::Ceil, value: "free".to_string() });
+/// assert_eq!(error.to_string(), "the ceil filter expected a number, but was given 'free'");
+/// ```
+pub fn render_filter<'a>(variable: impl Into<Cow<'a, str>>, filter: &Filter) -> Result<Cow<'a, str>, FilterError> {
+    let variable = variable.into();
+    Ok(Cow::Owned(match filter {
+        // Maths filters.
+        Filter::Ceil => float::ceil(number(&variable, filter)?).to_string(),
+        Filter::Floor => float::floor(number(&variable, filter)?).to_string(),
+        Filter::Round { precision } => float::round(number(&variable, filter)?
+            // Be default, Rust rounds away all decimals.
+            // So we want to move the decimal places `precision` places to the
+            // left.
+            .mul(float::powi(10_f64, *precision as i32))
+        )
+            // Now that it's rounded, move the decimal place back.
+            .div(float::powi(10_f64, *precision as i32))
+            .to_string(),
+        Filter::Add { value } => arithmetic(&variable, value, filter, |a, b| a + b)?,
+        Filter::Subtract { value } => arithmetic(&variable, value, filter, |a, b| a - b)?,
+        Filter::Multiply { value } => arithmetic(&variable, value, filter, |a, b| a * b)?,
+        Filter::Divide { value } if value.trim().parse::<f64>() == Ok(0.0) => {
+            return Err(FilterError::InvalidArgument { filter: filter.clone(), argument: "value".to_string(), value: value.clone() });
+        },
+        Filter::Divide { value } => arithmetic(&variable, value, filter, |a, b| a / b)?,
+
+        // String filters.
+        Filter::Base64Decode => match BASE64.decode(variable.trim()) {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+            Err(_) => return Err(FilterError::InvalidArgument { filter: filter.clone(), argument: "value".to_string(), value: variable.to_string() }),
+        },
+        Filter::Base64Encode => BASE64.encode(variable.as_bytes()),
+        Filter::Default { value } => match variable.trim().is_empty() {
+            true => value.clone(),
+            false => return Ok(variable),
+        },
+        Filter::Excerpt => {
+            if let Some((excerpt, _)) = variable.split_once("<!-- more -->") {
+                excerpt.trim().to_string()
+            } else {
+                // Paragraphs are separated by a blank line, so normalise the
+                // line endings before splitting.
+                variable
+                    .replace("\r\n", "\n")
+                    .split("\n\n")
+                    .map(|block| block.trim())
+                    // A heading may be followed immediately by a paragraph, so
+                    // drop any heading lines from the start of the block.
+                    .map(|block| block
+                        .lines()
+                        // Only `<h1>` to `<h6>`, rather than an `<hr>` or `<header>`.
+                        .skip_while(|line| {
+                            let line = line.trim_start();
+                            line.starts_with('#') || line.strip_prefix("<h").is_some_and(|rest| rest.starts_with(['1', '2', '3', '4', '5', '6']))
+                        })
+                        .collect::<Vec<&str>>()
+                        .join("\n")
+                    )
+                    .find(|block| !block.trim().is_empty())
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string()
+            }
+        },
+        Filter::FirstWords { words } => variable
+            .split_whitespace()
+            .take(*words as usize)
+            .collect::<Vec<&str>>()
+            .join(" "),
+        Filter::Hash { algo, length } => {
+            let digest = match algo {
+                HashAlgorithm::Sha256 => Sha256::digest(variable.as_bytes()).to_vec(),
+                HashAlgorithm::Md5 => Md5::digest(variable.as_bytes()).to_vec(),
+            };
+            let mut hex = digest.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+            if let Some(length) = length {
+                hex.truncate(*length as usize);
+            }
+            hex
+        },
+        Filter::If { then, otherwise } => match variable.trim().to_lowercase().as_str() {
+            "" | "false" | "0" | "no" => otherwise.clone(),
+            _ => then.clone(),
+        },
+        Filter::Join { separator } => split_meta_list(&variable).join(separator),
+        Filter::LastWords { words } => {
+            let all_words = variable.split_whitespace().collect::<Vec<&str>>();
+            let skip = all_words.len().saturating_sub(*words as usize);
+            all_words[skip..].join(" ")
+        },
+        #[cfg(feature = "markdown")]
+        Filter::Markdown { tables, strikethrough, tasklists, footnotes, smart_punctuation, hard_breaks, html, heading_offset } => {
+            let parse = markdown::ParseOptions {
+                constructs: markdown::Constructs {
+                    gfm_table: *tables,
+                    gfm_strikethrough: *strikethrough,
+                    gfm_task_list_item: *tasklists,
+                    gfm_footnote_definition: *footnotes,
+                    gfm_label_start_footnote: *footnotes,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            // Raw blocks bypass the Markdown, and are put back as they are.
+            let (source, raw_blocks) = set_aside_raw_blocks(&variable);
+            let mut source = Cow::Borrowed(source.as_ref());
+            if !html {
+                source = Cow::Owned(strip_html(&source, &parse));
+            }
+            if *hard_breaks {
+                source = Cow::Owned(break_lines(&source));
+            }
+            let output = markdown::to_html_with_options(&source, &markdown::Options {
+                parse,
+                compile: markdown::CompileOptions {
+                    allow_dangerous_html: true,
+                    allow_dangerous_protocol: false,
+                    ..Default::default()
+                },
+            }).unwrap_or_default();
+
+            let output = match smart_punctuation {
+                true => smarten_punctuation(&output),
+                false => output,
+            };
+            let output = match heading_offset {
+                0 => output,
+                offset => offset_headings(&output, *offset),
+            };
+            restore_raw_blocks(output, &raw_blocks)
+        },
+        #[cfg(feature = "markdown")]
+        Filter::MarkdownInline => {
+            let (source, raw_blocks) = set_aside_raw_blocks(&variable);
+            let html = markdown::to_html_with_options(&source, &markdown::Options {
+                parse: markdown::ParseOptions {
+                    // Only the constructs that are valid inside a paragraph.
+                    constructs: markdown::Constructs {
+                        block_quote: false,
+                        code_fenced: false,
+                        code_indented: false,
+                        heading_atx: false,
+                        heading_setext: false,
+                        html_flow: false,
+                        list_item: false,
+                        thematic_break: false,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                compile: markdown::CompileOptions {
+                    allow_dangerous_html: true,
+                    allow_dangerous_protocol: false,
+                    ..Default::default()
+                },
+            }).unwrap_or_default();
+
+            let html = html
+                .trim()
+                .trim_start_matches("<p>")
+                .trim_end_matches("</p>")
+                .replace("</p>\n<p>", " ");
+            restore_raw_blocks(html, &raw_blocks)
+        },
+        // Without the `markdown` feature, the value is left as it is, besides
+        // the tags of its raw blocks.
+        #[cfg(not(feature = "markdown"))]
+        Filter::Markdown { .. } | Filter::MarkdownInline => {
+            let (source, raw_blocks) = set_aside_raw_blocks(&variable);
+            if raw_blocks.is_empty() {
+                return Ok(variable);
+            }
+            restore_raw_blocks(source.into_owned(), &raw_blocks)
+        },
+        // Values that a filter wouldn't change are never copied.
+        Filter::Replace { find, .. } if !variable.contains(find.as_str()) => return Ok(variable),
+        Filter::Replace { find, replacement, limit } => {
+            if limit.is_none() {
+                variable.replace(find, replacement)
+            } else {
+                // Subtract 1 to account for the final iteration.
+                let segments = variable.split(find).count() - 1;
+
+                variable
+                .split(find)
+                .enumerate()
+                .map(|(count, part)| {
+                    // We can safely unwrap, because `limit.is_some()`.
+                    if (count as u8) < limit.unwrap() {
+                        format!("{}{}", part, replacement)
+                    } else {
+                        format!("{}{}", part, if count < segments { find } else { "" })
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join("")
+            }
+        },
+        Filter::Reverse => variable.graphemes(true).rev().collect(),
+        Filter::Split { on } => join_meta_list(&split_quoted(&variable, on)),
+        Filter::TimeAgo { now } => {
+            let today = match now.is_empty() {
+                true => today(),
+                false => Some(parse_day(now).ok_or_else(|| FilterError::InvalidArgument { filter: filter.clone(), argument: "now".to_string(), value: now.clone() })?),
+            };
+            match (parse_day(&variable), today) {
+                (Some(day), Some(today)) => time_ago(today - day),
+                _ => return Ok(variable),
+            }
+        },
+        Filter::Truncate { characters, trail } => match variable.grapheme_indices(true).nth(*characters as usize) {
+            // Now truncate and append the trail.
+            Some((end, _)) => format!("{}{}", &variable[..end], trail),
+            None => return Ok(variable),
+        },
+        Filter::TruncateWords { words, trail } => {
+            let all_words = variable.split_whitespace().collect::<Vec<&str>>();
+            match all_words.len() > *words as usize {
+                true => format!("{}{}", all_words[..*words as usize].join(" "), trail),
+                false => all_words.join(" "),
+            }
+        },
+        Filter::Wrap { .. } if variable.trim().is_empty() => String::new(),
+        Filter::Wrap { before, after } => format!("{}{}{}", before, variable, after),
+        // Text that is already in the case is never copied.
+        Filter::Text { case: TextCase::Lower } if variable.chars().all(|c| c.to_lowercase().eq([c])) => return Ok(variable),
+        Filter::Text { case: TextCase::Upper } if variable.chars().all(|c| c.to_uppercase().eq([c])) => return Ok(variable),
+        Filter::Text { case } => {
+            let separators = &[' ', ',', '!', '-', '_'];
+            match case {
+                TextCase::Lower => variable.to_lowercase(),
+                TextCase::Upper => variable.to_uppercase(),
+                TextCase::Title => {
+                    split_string(variable.into_owned(), separators)
+                    .into_iter()
+                    .map(|word| {
+                        if word.len() == 1 && separators.contains(&word.chars().next().unwrap_or_default()) {
+                            word
+                        } else {
+                            map_first_grapheme(&word, str::to_uppercase)
+                        }
+                    })
+                    .collect::<String>()
+                },
+                TextCase::Sentence => map_first_grapheme(&variable.to_lowercase(), str::to_uppercase),
+                TextCase::Kebab => variable
+                    .to_lowercase()
+                    .split(|c| separators.contains(&c))
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<&str>>()
+                    .join("-"),
+                TextCase::Snake => variable
+                    .to_lowercase()
+                    .split(|c| separators.contains(&c))
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<&str>>()
+                    .join("_"),
+                TextCase::Pascal => variable
+                    .split(|c| separators.contains(&c))
+                    .filter(|s| !s.is_empty())
+                    .map(|s| map_first_grapheme(s, str::to_uppercase))
+                    .collect::<Vec<String>>()
+                    .join(""),
+                TextCase::Camel => variable
+                    .split(|c| separators.contains(&c))
+                    .filter(|s| !s.is_empty())
+                    .enumerate()
+                    .map(|(i, s)| match i {
+                        0 => map_first_grapheme(s, str::to_lowercase),
+                        _ => map_first_grapheme(s, str::to_uppercase),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(""),
+                TextCase::Invert => variable.chars().fold(String::new(), |mut str, c| {
+                    if c.is_lowercase() {
+                        str.push_str(&c.to_uppercase().collect::<String>());
+                    } else {
+                        str.push_str(&c.to_lowercase().collect::<String>());
+                    }
+                    str
+                }),
+            }
+        },
+    }))
+}
+
+/// Run a variable through each of the `filters` in turn, as a placeholder
+/// does, stopping at the first [`FilterError`].
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{render_filters, Filter, FilterError};
+///
+/// let filters = [Filter::Multiply { value: "1.2".to_string() }, Filter::Round { precision: 2 }];
+/// assert_eq!(render_filters("19.99", &filters).unwrap(), "23.99");
+///
+/// let filters = [Filter::Divide { value: "0".to_string() }];
+/// assert!(matches!(render_filters("19.99", &filters), Err(FilterError::InvalidArgument { .. })));
+/// ```
+pub fn render_filters<'a>(variable: impl Into<Cow<'a, str>>, filters: &[Filter]) -> Result<Cow<'a, str>, FilterError> {
+    filters.iter().try_fold(variable.into(), render_filter)
+}
+
+/// Replace each `{{ raw }}` block of a Markdown with a marker, so that its
+/// contents bypass the Markdown, along with the contents of each block.
+///
+/// The markers are private use characters around the index of their block,
+/// which the Markdown leaves as they are.
+fn set_aside_raw_blocks(markdown: &str) -> (Cow<'_, str>, Vec<&str>) {
+    if !markdown.contains("{{") {
+        return (Cow::Borrowed(markdown), vec![]);
+    }
+
+    let mut output = String::with_capacity(markdown.len());
+    let mut blocks = vec![];
+    let mut rest = markdown;
+    while let Some(at) = rest.find(['{', '\\']) {
+        output.push_str(&rest[..at]);
+        rest = &rest[at..];
+        match parse_literal(Span::new(rest)) {
+            // A `\{{` is left for the Markdown to unescape.
+            Ok((remaining, _)) if rest.starts_with('\\') => {
+                let end = rest.len() - remaining.fragment().len();
+                output.push_str(&rest[..end]);
+                rest = &rest[end..];
+            },
+            Ok((remaining, literal)) => {
+                output.push_str(&format!("\u{E000}{}\u{E001}", blocks.len()));
+                blocks.push(*literal.fragment());
+                rest = &rest[rest.len() - remaining.fragment().len()..];
+            },
+            Err(_) => {
+                output.push_str(&rest[..1]);
+                rest = &rest[1..];
+            },
+        }
+    }
+    output.push_str(rest);
+
+    (Cow::Owned(output), blocks)
+}
+
+/// Put each block that [`set_aside_raw_blocks`] set aside back in place of its
+/// marker, along with the paragraph that a block on its own is wrapped in.
+fn restore_raw_blocks(html: String, blocks: &[&str]) -> String {
+    blocks.iter().enumerate().fold(html, |html, (index, block)| {
+        let marker = format!("\u{E000}{}\u{E001}", index);
+        html.replace(&format!("<p>{}</p>", marker), block).replace(&marker, block)
+    })
+}
+
+/// Remove the raw HTML of a Markdown, both its blocks and the tags within its
+/// text.
+#[cfg(feature = "markdown")]
+fn strip_html(markdown: &str, options: &markdown::ParseOptions) -> String {
+    fn collect(node: &markdown::mdast::Node, ranges: &mut Vec<(usize, usize)>) {
+        match (node, node.position()) {
+            (markdown::mdast::Node::Html(_), Some(position)) => ranges.push((position.start.offset, position.end.offset)),
+            _ => node.children().into_iter().flatten().for_each(|child| collect(child, ranges)),
+        }
+    }
+
+    let mut ranges = vec![];
+    if let Ok(root) = markdown::to_mdast(markdown, options) {
+        collect(&root, &mut ranges);
+    }
+    let mut output = String::with_capacity(markdown.len());
+    let mut last = 0;
+    for (start, end) in ranges {
+        output.push_str(&markdown[last..start]);
+        last = end;
+    }
+    output.push_str(&markdown[last..]);
+    output
+}
+
+/// End each line of a Markdown that is followed by another with two spaces, so
+/// that the line break is kept, leaving fenced code blocks as they are.
+#[cfg(feature = "markdown")]
+fn break_lines(markdown: &str) -> String {
+    let lines = markdown.lines().collect::<Vec<&str>>();
+    let mut output = String::with_capacity(markdown.len());
+    let mut fence: Option<&str> = None;
+    for (index, line) in lines.iter().enumerate() {
+        let marker = ["```", "~~~"].into_iter().find(|marker| line.trim_start().starts_with(marker));
+        match (fence, marker) {
+            (None, Some(marker)) => fence = Some(marker),
+            (Some(open), Some(marker)) if open == marker => fence = None,
+            _ => {},
+        }
+
+        output.push_str(line);
+        let next_is_text = lines.get(index + 1).is_some_and(|next| !next.trim().is_empty());
+        if fence.is_none() && marker.is_none() && next_is_text && !line.trim().is_empty() && !line.ends_with("  ") {
+            output.push_str("  ");
+        }
+        if index + 1 < lines.len() || markdown.ends_with('\n') {
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// Move each heading of some HTML down by `offset` levels, to at most `<h6>`.
+#[cfg(feature = "markdown")]
+fn offset_headings(html: &str, offset: u8) -> String {
+    let mut output = html.to_string();
+    // From the smallest, so that a moved heading isn't moved again.
+    for level in (1..=6u8).rev() {
+        let to = level.saturating_add(offset).min(6);
+        for tag in ["<h", "</h"] {
+            output = output
+                .replace(&format!("{}{}>", tag, level), &format!("{}{}>", tag, to))
+                .replace(&format!("{}{} ", tag, level), &format!("{}{} ", tag, to));
+        }
+    }
+    output
+}
+
+/// Convert the straight quotes, `--`, `---`, and `...` of the text in some
+/// HTML into their typographic forms, leaving the text of code elements, and
+/// every tag, as they are.
+#[cfg(feature = "markdown")]
+fn smarten_punctuation(html: &str) -> String {
+    const CODE: [&str; 6] = ["code", "pre", "kbd", "samp", "script", "style"];
+
+    let mut output = String::with_capacity(html.len());
+    // Quotes open after whitespace, or an opening bracket or dash, including
+    // at the start of the HTML.
+    let opens = |previous: Option<char>| previous.is_none_or(|c| c.is_whitespace() || "([{–—".contains(c));
+    let mut previous = None;
+    let mut code = 0_usize;
+    let mut rest = html;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            let end = rest.find('>').map_or(rest.len(), |end| end + 1);
+            let tag = &rest[..end];
+            let name = tag
+                .trim_start_matches(['<', '/'])
+                .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                .next()
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            if CODE.contains(&name.as_str()) {
+                match tag.starts_with("</") {
+                    true => code = code.saturating_sub(1),
+                    false => code += 1,
+                }
+            }
+            output.push_str(tag);
+            rest = &rest[end..];
+            continue;
+        }
+        if code > 0 {
+            output.push(c);
+            previous = Some(c);
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+
+        let (replacement, length) = if rest.starts_with("---") {
+            ('—', 3)
+        } else if rest.starts_with("--") {
+            ('–', 2)
+        } else if rest.starts_with("...") {
+            ('…', 3)
+        } else if rest.starts_with("&quot;") || c == '"' {
+            (if opens(previous) { '“' } else { '”' }, if c == '"' { 1 } else { 6 })
+        } else if c == '\'' {
+            // An apostrophe before a number abbreviates it, such as `'90s`.
+            let next = rest[1..].chars().next();
+            (if opens(previous) && next.is_some_and(|c| !c.is_whitespace() && !c.is_ascii_digit()) { '‘' } else { '’' }, 1)
+        } else {
+            (c, c.len_utf8())
+        };
+        output.push(replacement);
+        previous = Some(replacement);
+        rest = &rest[length..];
+    }
+
+    output
+}
+
+/// The `variable` of a maths `filter` as a number.
+fn number(variable: &str, filter: &Filter) -> Result<f64, FilterError> {
+    variable.trim().parse::<f64>().map_err(|_| FilterError::NotANumber { filter: filter.clone(), value: variable.to_string() })
+}
+
+/// Apply an arithmetic `operation` to a numeric `variable` and the `value` of
+/// its `filter`.
+fn arithmetic(variable: &str, value: &str, filter: &Filter, operation: fn(f64, f64) -> f64) -> Result<String, FilterError> {
+    let operand = value.trim().parse::<f64>().ok()
+        .filter(|operand| operand.is_finite())
+        .ok_or_else(|| FilterError::InvalidArgument { filter: filter.clone(), argument: "value".to_string(), value: value.to_string() })?;
+    Ok(operation(number(variable, filter)?, operand).to_string())
+}
+
+/// Change the first grapheme cluster of a word with `map`, leaving the rest of
+/// the word as it is, so that a letter and its combining marks stay together.
+fn map_first_grapheme(word: &str, map: fn(&str) -> String) -> String {
+    let mut graphemes = word.graphemes(true);
+    match graphemes.next() {
+        Some(first) => map(first) + graphemes.as_str(),
+        None => String::new(),
+    }
+}
+
+/// The number of days since the Unix epoch of a `YYYY-MM-DD` date, ignoring
+/// anything after it, such as a time.
+fn parse_day(date: &str) -> Option<i64> {
+    let date = date.trim();
+    let number = |range: core::ops::Range<usize>| date.get(range).and_then(|n| n.parse::<i64>().ok());
+    if date.get(4..5) != Some("-") || date.get(7..8) != Some("-") {
+        return None;
+    }
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    match (1..=12).contains(&month) && (1..=31).contains(&day) {
+        true => Some(days_from_civil(year, month, day)),
+        false => None,
+    }
+}
+
+/// The number of days since the Unix epoch of today, in UTC.
+#[cfg(feature = "std")]
+fn today() -> Option<i64> {
+    let seconds = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
+    Some((seconds / 86_400) as i64)
+}
+
+/// There is no clock without `std`, so a `now` must be given.
+#[cfg(not(feature = "std"))]
+fn today() -> Option<i64> {
+    None
+}
+
+/// Describe a number of `days` in the past, or in the future when negative, in
+/// the largest whole unit, such as `2 weeks ago`.
+fn time_ago(days: i64) -> String {
+    let (count, unit) = match days.abs() {
+        0 => return "today".to_string(),
+        1 if days > 0 => return "yesterday".to_string(),
+        1 => return "tomorrow".to_string(),
+        days @ 2..=6 => (days, "day"),
+        days @ 7..=29 => (days / 7, "week"),
+        // Twelve months would be a year, which isn't reached until 365 days.
+        days @ 30..=364 => ((days / 30).min(11), "month"),
+        days => (days / 365, "year"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    match days > 0 {
+        true => format!("{} {}{} ago", count, unit, plural),
+        false => format!("in {} {}{}", count, unit, plural),
+    }
+}
+
+/// The number of days since the Unix epoch of a date.
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // Howard Hinnant's `days_from_civil`.
+    let year_of_march = if month <= 2 { year - 1 } else { year };
+    let era = year_of_march.div_euclid(400);
+    let year_of_era = year_of_march.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Floating point functions are part of `std`, so [`libm`] is used without it.
+#[cfg(feature = "std")]
+mod float {
+    pub fn ceil(x: f64) -> f64 { x.ceil() }
+    pub fn floor(x: f64) -> f64 { x.floor() }
+    pub fn round(x: f64) -> f64 { x.round() }
+    pub fn powi(x: f64, n: i32) -> f64 { x.powi(n) }
+}
+
+#[cfg(not(feature = "std"))]
+mod float {
+    pub fn ceil(x: f64) -> f64 { libm::ceil(x) }
+    pub fn floor(x: f64) -> f64 { libm::floor(x) }
+    pub fn round(x: f64) -> f64 { libm::round(x) }
+    pub fn powi(x: f64, n: i32) -> f64 { libm::pow(x, n as f64) }
+}
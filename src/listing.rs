@@ -0,0 +1,548 @@
+//! Pages that list the posts of a batch, such as an index, the archives, or a
+//! changelog.
+
+use std::{cmp::Reverse, collections::BTreeMap, error::Error, ffi::OsStr, path::{Path, PathBuf}, str::FromStr};
+use crate::{batch::{write_output, BatchOptions}, error::{Error as ParseError, SourceError}, parser::{parse_meta_section, parse_title, Span}, permalink::{batch_output_path, permalinks}, render::{apply_features, apply_filter_aliases, apply_filter_defaults, apply_fragments, apply_includes, apply_loops, apply_whitespace_control, derive_meta_values, derived_publish_date, description, excerpt, find_title, is_draft, is_noindex, is_private, is_published_by, path_variables, read_file, relative_url, slugify, write_placeholders, ChunkedOutput, FilterCache, Template, TitlePrecedence, Value}};
+
+////////////////////////////////////////////////////////////////////////////////
+// Structs and types
+/// How the entries of a changelog are grouped, see [`render_changelog`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ChangelogGroup {
+    /// By the `version` of each entry, where those without one are
+    /// `Unreleased`.
+    #[default]
+    Version,
+    /// By the month of the `publish_date` or `date` of each entry, such as
+    /// `May 2024`, where those without one are `Undated`.
+    Month,
+}
+
+impl FromStr for ChangelogGroup {
+    type Err = String;
+
+    /// Parse a string slice, into a `ChangelogGroup`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use blogs_md_easy::ChangelogGroup;
+    ///
+    /// assert_eq!("version".parse::<ChangelogGroup>(), Ok(ChangelogGroup::Version));
+    /// assert_eq!("Month".parse::<ChangelogGroup>(), Ok(ChangelogGroup::Month));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "version" => Ok(Self::Version),
+            "month" => Ok(Self::Month),
+            _ => Err(format!("Unable to parse ChangelogGroup from '{}'", s)),
+        }
+    }
+}
+
+/// A Markdown as it is listed by an index template, see [`render_index`].
+///
+/// # Example
+/// ```rust
+/// use std::path::PathBuf;
+/// use blogs_md_easy::{Post, TitlePrecedence};
+///
+/// let markdown = ":meta\ndate = 2024-01-01\n:meta\n# Hello\nThe first post.\n\nAnd more.";
+/// let post = Post::new(PathBuf::from("hello.md"), markdown, PathBuf::from("hello.html"), TitlePrecedence::Meta).unwrap();
+/// assert_eq!(post.variables.get("title").unwrap(), "Hello");
+/// assert_eq!(post.variables.get("date").unwrap(), "2024-01-01");
+/// assert_eq!(post.variables.get("excerpt").unwrap(), "The first post.");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Post {
+    /// The Markdown that the post was rendered from.
+    pub markdown_path: PathBuf,
+    /// Where the post was written.
+    pub output_path: PathBuf,
+    /// Every meta value of the post, along with its `title`, `excerpt`, and
+    /// `content`, which is the Markdown after the heading, and the `filename`,
+    /// `slug`, and `path` of its Markdown.
+    pub variables: BTreeMap<String, String>,
+}
+
+impl Post {
+    /// Summarise a `markdown`, which is written to `output_path`.
+    ///
+    /// The `excerpt` is the first paragraph of the content, unless the meta
+    /// section declares one.
+    pub fn new(markdown_path: PathBuf, markdown: &str, output_path: PathBuf, precedence: TitlePrecedence) -> Result<Self, Box<dyn Error>> {
+        let source = Span::new(markdown);
+        let (markdown, meta_values) = match parse_meta_section(source) {
+            Ok(parsed) => parsed,
+            Err(nom::Err::Error(error @ ParseError::Meta(_))) => {
+                Err(SourceError::new(markdown_path.to_owned(), source.fragment(), error))?
+            },
+            // There is no meta section.
+            Err(_) => (source, vec![]),
+        };
+
+        let mut variables: BTreeMap<String, String> = meta_values
+            .iter()
+            .map(|meta| (meta.key.to_owned(), meta.value.to_owned()))
+            .collect();
+
+        let (title, _) = find_title(markdown, &meta_values, Some(&markdown_path), precedence)
+            .ok_or(format!("Missing title in markdown '{}'.", markdown_path.to_string_lossy()))?;
+        variables.insert("title".to_string(), title);
+        let content = parse_title(markdown).map(|(content, _)| content).unwrap_or(markdown);
+        if !variables.contains_key("excerpt") {
+            variables.insert("excerpt".to_string(), excerpt(content.fragment()));
+        }
+        variables.entry("content".to_string()).or_insert(content.fragment().trim().to_string());
+        for (key, value) in path_variables(&markdown_path) {
+            variables.entry(key.to_string()).or_insert(value);
+        }
+        let derived = derive_meta_values(&meta_values, |name| variables.get(name), &FilterCache::new())
+            .map_err(|e| format!("{} In markdown '{}'.", e, markdown_path.to_string_lossy()))?;
+        variables.extend(derived);
+        if let Some(description) = description(&[], &meta_values, |name| variables.get(name)) {
+            variables.insert("description".to_string(), description);
+        }
+
+        Ok(Self { markdown_path, output_path, variables })
+    }
+
+    /// The id of the post, from the file name of its Markdown, for linking to
+    /// it on a page that lists several posts, such as a changelog.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::path::PathBuf;
+    /// use blogs_md_easy::Post;
+    ///
+    /// let post = Post { markdown_path: PathBuf::from("changes/Version 1.2.md"), ..Default::default() };
+    /// assert_eq!(post.id(), "version-1-2");
+    /// ```
+    pub fn id(&self) -> String {
+        slugify(&self.markdown_path.file_stem().unwrap_or_default().to_string_lossy())
+    }
+
+    /// The variables of the post, as they are listed by a template, along with
+    /// the `url` that it links to, unless the post declares its own.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::{collections::BTreeMap, path::PathBuf};
+    /// use blogs_md_easy::Post;
+    ///
+    /// let post = Post { variables: BTreeMap::from([("title".to_string(), "Hello".to_string())]), ..Default::default() };
+    /// let value = post.to_value("posts/hello.html");
+    /// assert_eq!(value.get("title").as_deref(), Some("Hello"));
+    /// assert_eq!(value.get("url").as_deref(), Some("posts/hello.html"));
+    /// ```
+    pub fn to_value(&self, url: &str) -> Value {
+        let mut values = self.variables
+            .iter()
+            .map(|(key, value)| (key.to_owned(), Value::Text(value.to_owned())))
+            .collect::<BTreeMap<String, Value>>();
+        values.entry("url".to_string()).or_insert(Value::Text(url.to_string()));
+        Value::Map(values)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Functions
+/// Summarise each of the `markdowns`, as
+/// [`render_batch`](crate::batch::render_batch) would write them with the
+/// `templates`, ready for [`render_index`]. Each post links to its output of
+/// the first template.
+///
+/// The posts are sorted by their `date`, newest first, and any without a
+/// `date` are last. A `publish_date` can be derived for each Markdown with
+/// [`BatchOptions::publish_date_from`]. The glossary page is not a post, and
+/// neither are private or noindex Markdowns, see [`is_private`] and
+/// [`is_noindex`], or drafts, unless [`BatchOptions::include_drafts`] is set,
+/// or those published after [`BatchOptions::as_of`].
+pub fn collect_posts(templates: &[PathBuf], markdowns: &[(PathBuf, String)], options: &BatchOptions) -> Result<Vec<Post>, Box<dyn Error>> {
+    let Some(template_path) = templates.first() else {
+        return Ok(vec![]);
+    };
+    let permalinks = permalinks(template_path, markdowns, options)?;
+    let mut posts = markdowns
+        .iter()
+        .filter(|(markdown_path, _)| options.glossary.as_ref().is_none_or(|glossary| &glossary.path != markdown_path))
+        .filter(|(_, markdown)| options.include_drafts || !is_draft(markdown))
+        .filter(|(_, markdown)| !is_private(markdown) && !is_noindex(markdown))
+        .filter(|(markdown_path, markdown)| is_published_by(markdown_path, markdown, options))
+        .map(|(markdown_path, markdown)| {
+            let output_path = batch_output_path(template_path, markdown_path, options, &permalinks, templates.len() > 1);
+            let mut post = Post::new(markdown_path.to_owned(), markdown, output_path, options.title_precedence)?;
+            if !options.description_from.is_empty() {
+                let meta_values = parse_meta_section(Span::new(markdown)).map(|(_, meta_values)| meta_values).unwrap_or_default();
+                match description(&options.description_from, &meta_values, |name| post.variables.get(name)) {
+                    Some(description) => post.variables.insert("description".to_string(), description),
+                    None => post.variables.remove("description"),
+                };
+            }
+            if !post.variables.contains_key("publish_date") {
+                if let Some(date) = derived_publish_date(markdown_path, &options.publish_date_from) {
+                    post.variables.insert("publish_date".to_string(), date);
+                }
+            }
+            Ok(post)
+        })
+        .collect::<Result<Vec<Post>, Box<dyn Error>>>()?;
+    posts.sort_by_cached_key(|post| Reverse(post.variables.get("date").map(|date| (date_key(date), date.to_owned()))));
+
+    Ok(posts)
+}
+
+/// Render an index template, such as a homepage, that lists every one of the
+/// `posts`, and write it to the `output_dir` with the template's file name.
+///
+/// The posts are available to a `{{ for £post in £posts }}` block, see
+/// [`apply_loops`], where each has its variables, along with the `url` of the
+/// post relative to the index. The number of posts is `£posts.count`.
+///
+/// With [`BatchOptions::per_page`], the posts are split across numbered pages,
+/// where the first page keeps the template's file name, and later pages are
+/// suffixed with their number, such as `index-2.html`. Each page has its
+/// `£page` number, the number of `£pages`, and the URLs of the `£prev_page`
+/// and `£next_page`, which are empty on the first and last pages. Then
+/// `£posts.count` is the number of posts on the page, and `£posts.total` the
+/// number of posts on every page.
+///
+/// With [`BatchOptions::dry_run`], the pages are rendered, but not written.
+///
+/// # Example
+/// ```rust
+/// use std::{fs, path::PathBuf};
+/// use blogs_md_easy::{collect_posts, render_index, BatchOptions};
+///
+/// let directory = std::env::temp_dir().join("blogs_md_easy_render_index");
+/// fs::create_dir_all(&directory).unwrap();
+/// let template_path = directory.join("index.html");
+/// fs::write(&template_path, "<ul>{{ for £post in £posts }}<li><a href=\"{{ £post.url }}\">{{ £post.title }}</a></li>{{ endfor }}</ul>").unwrap();
+///
+/// let markdowns = vec![
+///     (PathBuf::from("first.md"), ":meta\ndate = 2024-01-01\n:meta\n# First".to_string()),
+///     (PathBuf::from("second.md"), ":meta\ndate = 2024-02-01\n:meta\n# Second".to_string()),
+/// ];
+/// let options = BatchOptions { output_dir: Some(directory.join("public")), ..Default::default() };
+/// let posts = collect_posts(&[directory.join("post.html")], &markdowns, &options).unwrap();
+/// let output_paths = render_index(&template_path, &posts, &options).unwrap();
+///
+/// assert_eq!(output_paths, vec![directory.join("public").join("index.html")]);
+/// assert_eq!(
+///     fs::read_to_string(&output_paths[0]).unwrap(),
+///     "<ul><li><a href=\"second.html\">Second</a></li><li><a href=\"first.html\">First</a></li></ul>"
+/// );
+/// ```
+pub fn render_index(template_path: &Path, posts: &[Post], options: &BatchOptions) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    span!("index", path = %template_path.to_string_lossy());
+    let first_page = options.output_dir.to_owned().unwrap_or_default().join(template_path.file_name().unwrap_or(OsStr::new("index.html")));
+    let page_path = |page: usize| match page {
+        1 => first_page.to_owned(),
+        page => first_page.with_file_name(format!(
+            "{}-{}{}",
+            first_page.file_stem().unwrap_or_default().to_string_lossy(),
+            page,
+            first_page.extension().map(|extension| format!(".{}", extension.to_string_lossy())).unwrap_or_default(),
+        )),
+    };
+
+    // There is always a page, even if there are no posts to list.
+    let pages = match options.per_page.filter(|per_page| *per_page > 0) {
+        Some(per_page) if !posts.is_empty() => posts.chunks(per_page).collect::<Vec<&[Post]>>(),
+        _ => vec![posts],
+    };
+
+    let source = read_listing_template(template_path, options)?;
+
+    let mut output_paths = Vec::with_capacity(pages.len());
+    for (index, page_posts) in pages.iter().enumerate() {
+        let page = index + 1;
+        let output_path = page_path(page);
+
+        let page_url = |page: usize| match page >= 1 && page <= pages.len() {
+            true => relative_url(&output_path, &page_path(page)),
+            false => String::new(),
+        };
+        let variables = listing_value(BTreeMap::from([
+            ("posts".to_string(), Value::List(page_posts.iter().map(|post| post.to_value(&relative_url(&output_path, &post.output_path))).collect())),
+            ("posts.total".to_string(), Value::Text(posts.len().to_string())),
+            ("page".to_string(), Value::Text(page.to_string())),
+            ("pages".to_string(), Value::Text(pages.len().to_string())),
+            ("prev_page".to_string(), Value::Text(page_url(page - 1))),
+            ("next_page".to_string(), Value::Text(page_url(page + 1))),
+        ]), options);
+
+        // Each page may have a different number of posts to loop over.
+        let template = listing_template(template_path, &source, &variables)?;
+        render_listing(template_path, &template, &variables, &output_path, options)?;
+        output_paths.push(output_path);
+    }
+
+    Ok(output_paths)
+}
+
+/// Render an archive template once for each year, and each month, of the
+/// `posts`, such as `2024/index.html` and `2024/05/index.html` in the
+/// `output_dir`, with the extension of the template.
+///
+/// The posts of each archive are available to a `{{ for £post in £posts }}`
+/// block, see [`apply_loops`], in the same way as [`render_index`], where the
+/// `url` of each post is relative to the archive. The archive has its
+/// `£archive.title`, such as `2024` or `May 2024`, its `£archive.year`, and its
+/// `£archive.month`, such as `05`, which is empty for a year.
+///
+/// Posts are placed, and sorted newest first, by their `publish_date`, or
+/// otherwise their `date`, and those without either are left out.
+///
+/// With [`BatchOptions::dry_run`], the archives are rendered, but not written.
+///
+/// # Example
+/// ```rust
+/// use std::{fs, path::PathBuf};
+/// use blogs_md_easy::{collect_posts, render_archives, BatchOptions};
+///
+/// let directory = std::env::temp_dir().join("blogs_md_easy_render_archives");
+/// fs::create_dir_all(&directory).unwrap();
+/// let template_path = directory.join("archive.html");
+/// fs::write(&template_path, "<h1>{{ £archive.title }}</h1><ul>{{ for £post in £posts }}<li><a href=\"{{ £post.url }}\">{{ £post.title }}</a></li>{{ endfor }}</ul>").unwrap();
+///
+/// let markdowns = vec![
+///     (PathBuf::from("first.md"), ":meta\ndate = 2024-01-01\n:meta\n# First".to_string()),
+///     (PathBuf::from("second.md"), ":meta\ndate = 2024-02-01\n:meta\n# Second".to_string()),
+/// ];
+/// let public = directory.join("public");
+/// let options = BatchOptions { output_dir: Some(public.to_owned()), ..Default::default() };
+/// let posts = collect_posts(&[directory.join("post.html")], &markdowns, &options).unwrap();
+/// let output_paths = render_archives(&template_path, &posts, &options).unwrap();
+///
+/// assert_eq!(output_paths, vec![
+///     public.join("2024").join("index.html"),
+///     public.join("2024").join("02").join("index.html"),
+///     public.join("2024").join("01").join("index.html"),
+/// ]);
+/// assert_eq!(
+///     fs::read_to_string(&output_paths[1]).unwrap(),
+///     "<h1>February 2024</h1><ul><li><a href=\"../../second.html\">Second</a></li></ul>"
+/// );
+/// ```
+pub fn render_archives(template_path: &Path, posts: &[Post], options: &BatchOptions) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    span!("archives", path = %template_path.to_string_lossy());
+    let output_dir = options.output_dir.to_owned().unwrap_or_default();
+    let file_name = format!(
+        "index{}",
+        template_path.extension().map(|extension| format!(".{}", extension.to_string_lossy())).unwrap_or_default(),
+    );
+
+    // Each year is followed by its months, newest first, as are their posts.
+    let mut archives = BTreeMap::new();
+    for post in posts {
+        let Some((year, month)) = post_month(post)? else {
+            continue;
+        };
+        for key in [(Reverse(year), None), (Reverse(year), Some(Reverse(month)))] {
+            archives.entry(key).or_insert_with(Vec::new).push(post);
+        }
+    }
+    for posts in archives.values_mut() {
+        posts.sort_by_cached_key(|post| Reverse(post_date(post).map(|date| (date_key(date), date))));
+    }
+
+    let source = read_listing_template(template_path, options)?;
+
+    let mut output_paths = Vec::with_capacity(archives.len());
+    for ((Reverse(year), month), archive_posts) in archives {
+        let month = month.map(|Reverse(month)| month);
+        let output_path = match month {
+            Some(month) => output_dir.join(year).join(format!("{:02}", month)).join(&file_name),
+            None => output_dir.join(year).join(&file_name),
+        };
+
+        let archive = BTreeMap::from([
+            ("title".to_string(), Value::Text(match month {
+                Some(month) => format!("{} {}", MONTHS[month - 1], year),
+                None => year.to_string(),
+            })),
+            ("year".to_string(), Value::Text(year.to_string())),
+            ("month".to_string(), Value::Text(month.map(|month| format!("{:02}", month)).unwrap_or_default())),
+        ]);
+        let variables = listing_value(BTreeMap::from([
+            ("archive".to_string(), Value::Map(archive)),
+            ("posts".to_string(), Value::List(archive_posts.iter().map(|post| post.to_value(&relative_url(&output_path, &post.output_path))).collect())),
+        ]), options);
+
+        let template = listing_template(template_path, &source, &variables)?;
+        render_listing(template_path, &template, &variables, &output_path, options)?;
+        output_paths.push(output_path);
+    }
+
+    Ok(output_paths)
+}
+
+/// Render a changelog template, which lists every one of the `posts` in
+/// groups, such as by their version, and write it to the `output_dir` with the
+/// template's file name.
+///
+/// The groups are available to a `{{ for £group in £groups }}` block, see
+/// [`apply_loops`], where each has its `title`, the `date` of its newest entry,
+/// and its `entries`, which are looped over within it. Each entry has its
+/// variables, along with its `id`, see [`Post::id`], and the `url` of the
+/// entry within the changelog. The number of groups is `£groups.count`, and
+/// each group has its `£group.entries.count`.
+///
+/// The groups are in the order of their newest entry, so for posts from
+/// [`collect_posts`], the latest version is first.
+///
+/// With [`BatchOptions::dry_run`], the changelog is rendered, but not written.
+///
+/// # Example
+/// ```rust
+/// use std::{fs, path::PathBuf};
+/// use blogs_md_easy::{collect_posts, render_changelog, BatchOptions, ChangelogGroup};
+///
+/// let directory = std::env::temp_dir().join("blogs_md_easy_render_changelog");
+/// fs::create_dir_all(&directory).unwrap();
+/// let template_path = directory.join("changelog.html");
+/// fs::write(&template_path, "{{ for £group in £groups }}<h2>{{ £group.title }}</h2><ul>{{ for £entry in £group.entries }}<li id=\"{{ £entry.id }}\">{{ £entry.title }}</li>{{ endfor }}</ul>{{ endfor }}").unwrap();
+///
+/// let markdowns = vec![
+///     (PathBuf::from("parser.md"), ":meta\ndate = 2024-01-01\nversion = 1.0.0\n:meta\n# A new parser".to_string()),
+///     (PathBuf::from("loops.md"), ":meta\ndate = 2024-02-01\nversion = 1.1.0\n:meta\n# Loops".to_string()),
+///     (PathBuf::from("fix.md"), ":meta\ndate = 2024-01-02\nversion = 1.0.0\n:meta\n# A fix".to_string()),
+/// ];
+/// let options = BatchOptions { output_dir: Some(directory.join("public")), ..Default::default() };
+/// let posts = collect_posts(&[template_path.to_owned()], &markdowns, &options).unwrap();
+/// let output_path = render_changelog(&template_path, &posts, &options, ChangelogGroup::Version).unwrap();
+///
+/// assert_eq!(
+///     fs::read_to_string(&output_path).unwrap(),
+///     "<h2>1.1.0</h2><ul><li id=\"loops\">Loops</li></ul><h2>1.0.0</h2><ul><li id=\"fix\">A fix</li><li id=\"parser\">A new parser</li></ul>"
+/// );
+/// ```
+pub fn render_changelog(template_path: &Path, posts: &[Post], options: &BatchOptions, group_by: ChangelogGroup) -> Result<PathBuf, Box<dyn Error>> {
+    span!("changelog", path = %template_path.to_string_lossy());
+    let output_path = options.output_dir.to_owned().unwrap_or_default().join(template_path.file_name().unwrap_or(OsStr::new("changelog.html")));
+
+    let mut groups: Vec<(String, Vec<&Post>)> = vec![];
+    for post in posts {
+        let title = changelog_group(post, group_by)?;
+        match groups.iter_mut().find(|(group, _)| *group == title) {
+            Some((_, entries)) => entries.push(post),
+            None => groups.push((title, vec![post])),
+        }
+    }
+
+    let groups = groups
+        .iter()
+        .map(|(title, entries)| {
+            let date = entries.iter().find_map(|post| post_date(post)).unwrap_or_default().to_string();
+            let entries = entries
+                .iter()
+                .map(|post| {
+                    let Value::Map(mut entry) = post.to_value(&format!("#{}", post.id())) else {
+                        unreachable!("a post is a map of its variables");
+                    };
+                    entry.entry("id".to_string()).or_insert(Value::Text(post.id()));
+                    Value::Map(entry)
+                })
+                .collect();
+            Value::Map(BTreeMap::from([
+                ("title".to_string(), Value::Text(title.to_owned())),
+                ("date".to_string(), Value::Text(date)),
+                ("entries".to_string(), Value::List(entries)),
+            ]))
+        })
+        .collect();
+    let variables = listing_value(BTreeMap::from([("groups".to_string(), Value::List(groups))]), options);
+
+    let template = listing_template(template_path, &read_listing_template(template_path, options)?, &variables)?;
+    render_listing(template_path, &template, &variables, &output_path, options)?;
+
+    Ok(output_path)
+}
+
+/// The title of the group of a changelog that a post belongs to.
+fn changelog_group(post: &Post, group_by: ChangelogGroup) -> Result<String, Box<dyn Error>> {
+    Ok(match group_by {
+        ChangelogGroup::Version => post.variables.get("version").map(|version| version.trim()).filter(|version| !version.is_empty()).unwrap_or("Unreleased").to_string(),
+        ChangelogGroup::Month => match post_month(post)? {
+            Some((year, month)) => format!("{} {}", MONTHS[month - 1], year),
+            None => "Undated".to_string(),
+        },
+    })
+}
+
+/// The names of the months, for the titles of changelog groups and archives.
+const MONTHS: [&str; 12] = ["January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November", "December"];
+
+/// The year, and the month from `1` to `12`, of the date of a post, see
+/// [`post_date`].
+fn post_month(post: &Post) -> Result<Option<(&str, usize)>, Box<dyn Error>> {
+    let Some(date) = post_date(post) else {
+        return Ok(None);
+    };
+    let month = date.get(5..7).and_then(|month| month.parse::<usize>().ok()).filter(|month| (1..=12).contains(month));
+    match (date.get(0..4).filter(|year| year.chars().all(|c| c.is_ascii_digit())), month, date.get(4..5)) {
+        (Some(year), Some(month), Some("-")) => Ok(Some((year, month))),
+        _ => Err(format!("Unable to parse the date '{}' of '{}', expected YYYY-MM-DD.", date, post.markdown_path.to_string_lossy()))?,
+    }
+}
+
+/// The year, month, and day of a date, such as `2024-05-01` or `2024-5-1`,
+/// ignoring anything after it, such as a time, so that dates are sorted by
+/// when they are, rather than as text.
+fn date_key(date: &str) -> Option<(i64, u32, u32)> {
+    let mut parts = date.trim().splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.split(|c: char| !c.is_ascii_digit()).next()?.parse().ok()?;
+    Some((year, month, day))
+}
+
+/// The `publish_date`, or otherwise the `date`, of a post.
+fn post_date(post: &Post) -> Option<&str> {
+    ["publish_date", "date"].iter().find_map(|key| post.variables.get(*key)).map(|date| date.trim())
+}
+
+/// Read a template that lists posts, such as an index, applying the includes,
+/// features, fragments, filter aliases, and filter defaults, but not yet the loops.
+fn read_listing_template(template_path: &Path, options: &BatchOptions) -> Result<String, Box<dyn Error>> {
+    apply_includes(&apply_whitespace_control(&options.syntax.translate(&read_file(template_path)?)), template_path.parent().unwrap_or(Path::new("")), &options.syntax)
+        .and_then(|template| apply_features(&template, &options.features))
+        .and_then(|template| apply_fragments(&template, template_path.parent().unwrap_or(Path::new(""))))
+        .map(|template| apply_filter_defaults(&apply_filter_aliases(&template, &options.filter_aliases), &options.filter_defaults))
+        .map_err(|e| format!("{} In template '{}'.", e, template_path.to_string_lossy()).into())
+}
+
+/// The variables of a template that lists posts, along with the
+/// [`BatchOptions::variables`] that it doesn't already have.
+fn listing_value(mut values: BTreeMap<String, Value>, options: &BatchOptions) -> Value {
+    for meta in &options.variables {
+        values.entry(meta.key.to_owned()).or_insert(Value::Text(meta.value.to_owned()));
+    }
+    Value::Map(values)
+}
+
+/// Repeat the loops of a template that lists posts, from `source`, once for
+/// each item of the collections of its `variables`, then parse it.
+fn listing_template(template_path: &Path, source: &str, variables: &Value) -> Result<Template, Box<dyn Error>> {
+    let template = apply_loops(source, &variables.collections())
+        .map_err(|e| format!("{} In template '{}'.", e, template_path.to_string_lossy()))?;
+    Template::new(&template).map_err(|e| match e.downcast::<ParseError>() {
+        Ok(error) => Box::new(SourceError::new(template_path.to_owned(), &template, *error)),
+        Err(e) => e,
+    })
+}
+
+/// Render a template that lists posts, and write it to `output_path`, unless
+/// it is a [`BatchOptions::dry_run`].
+fn render_listing(template_path: &Path, template: &Template, variables: &Value, output_path: &Path, options: &BatchOptions) -> Result<(), Box<dyn Error>> {
+    let mut html_doc = ChunkedOutput::default();
+    write_placeholders(template.source(), template.placeholders(), |name| variables.get(name), options, |chunk| html_doc.push(chunk))
+        .map_err(|error| SourceError::new(template_path.to_owned(), template.source(), error))?;
+    let html_doc = html_doc.finish();
+
+    if !options.dry_run {
+        write_output(output_path, &html_doc, options)?;
+    }
+
+    Ok(())
+}
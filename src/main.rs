@@ -1,6 +1,9 @@
-use blogs_md_easy::{create_variables, parse_meta_section, parse_placeholder_locations, render_filter, replace_substring, Placeholder, Span};
-use clap::Parser;
-use std::{collections::HashMap, error::Error, ffi::OsStr, fs, path::PathBuf};
+use blogs_md_easy::{error::{BatchError, SourceError}, apply_whitespace_control, copy_dir, Config, collect_posts, decode_file, edit_meta, export_post, Alternate, Compression, init_project, install_theme, is_draft, is_published_by, lint, load_env_variables, load_variables, new_post, posts_to_ics, posts_to_rss, posts_to_search_index, progress_to_manifest, read_file, render_archetype, render_archives, render_batch, render_changelog, render_filter, render_index, render_markdown, sample_markdown, unix_to_date, write_if_changed, AnchorMap, BatchOptions, Blogroll, BatchProgress, BuildCache, CancellationToken, DescriptionSource, ChangelogGroup, DateSource, EmbedMeta, Fediverse, Filter, Glossary, Meta, MetaEdit, MissingVariablePolicy, OutputFormat, OutputManifest, Post, Shortcodes, Stats, Template, TemplateCoverage, Syntax, TextCase, Theme, TitlePrecedence, Warning, Webmentions};
+use clap::{Args, Parser, Subcommand};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::{borrow::Cow, collections::{BTreeMap, BTreeSet, HashMap}, env, error::Error, ffi::OsString, fmt, fs, io::{self, IsTerminal, Read, Write}, path::{Path, PathBuf}, sync::{atomic::{AtomicUsize, Ordering}, mpsc, Arc, Mutex}, thread, time::{Duration, Instant, SystemTime, UNIX_EPOCH}};
+use tracing::{field::{Field, Visit}, span::{Attributes, Id}, Event, Level, Subscriber};
+use tracing_subscriber::{layer::{Context, SubscriberExt}, registry::LookupSpan, Layer, Registry};
 
 ////////////////////////////////////////////////////////////////////////////////
 // Structs and types
@@ -16,27 +19,715 @@ enum AllowList {
     UnusedVariables,
 }
 
-#[derive(Debug, Parser)]
-#[command(version, about, long_about = None)]
-struct Cli {
+/// Commands that can be run instead of rendering Markdowns.
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Render the project that is described by a config file, using its
+    /// templates, Markdowns, output directory, and variables.
+    Build {
+        /// The config file of the project.
+        #[arg(short, long, value_name = "FILE", default_value = Config::FILE)]
+        config: PathBuf,
+        /// Report which files would be rendered, skipped, and written, without
+        /// writing anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Render dated Markdowns, such as release notes, into a single changelog
+    /// page, grouped by their version or month.
+    Changelog {
+        /// List of Markdown files ending in .md, directories, or globs such as
+        /// "changes/**/*.md".
+        #[arg(short, long, required = true, value_name = "FILES", num_args = 1..)]
+        markdowns: Vec<PathBuf>,
+        /// The template that lists the groups, and the entries of each.
+        #[arg(short, long, value_name = "FILE")]
+        template: PathBuf,
+        /// Output directory, defaults to the current directory.
+        #[arg(short, long, value_name = "DIR")]
+        output_dir: Option<PathBuf>,
+        /// Variables for the changelog, from a `.toml`, `.json`, `.yaml` or
+        /// meta section file.
+        #[arg(long, value_name = "FILE")]
+        variables: Option<PathBuf>,
+        /// Group the entries by their `version`, or by `month`.
+        #[arg(long, value_name = "GROUP", default_value = "version")]
+        group_by: ChangelogGroup,
+        /// Write an RSS feed of the entries, described by the `feed.*`
+        /// variables.
+        #[arg(long, value_name = "FILE")]
+        feed: Option<PathBuf>,
+    },
+    /// Render the Markdowns into a template several times, without writing
+    /// them, and report how long each stage and placeholder took.
+    Bench {
+        /// The template to render.
+        template: PathBuf,
+        /// List of Markdown files ending in .md, directories, or globs such as
+        /// "posts/**/*.md".
+        #[arg(required = true, value_name = "MARKDOWNS", num_args = 1..)]
+        markdowns: Vec<PathBuf>,
+        /// How many times to render every Markdown.
+        #[arg(short = 'n', long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+        iterations: u32,
+    },
+    /// Compare the placeholders of two templates, reporting added and removed
+    /// variables, and changed filters.
+    Diff {
+        /// The template that is currently in use.
+        old: PathBuf,
+        /// The template that will replace it.
+        new: PathBuf,
+    },
+    /// Bundle each Markdown into a zip archive of its source, its rendered
+    /// HTML, its meta section as JSON, and the images that it refers to.
+    Export {
+        /// List of Markdown files ending in .md, directories, or globs such as
+        /// "posts/**/*.md".
+        #[arg(short, long, required = true, value_name = "FILES", num_args = 1..)]
+        markdowns: Vec<PathBuf>,
+        /// The template to render the HTML with.
+        #[arg(short, long, value_name = "FILE")]
+        template: PathBuf,
+        /// The directory to write the archives to.
+        #[arg(short, long, value_name = "DIR", default_value = "export")]
+        output_dir: PathBuf,
+        /// Variables for the templates, from a `.toml`, `.json`, `.yaml` or
+        /// meta section file.
+        #[arg(long, value_name = "FILE")]
+        variables: Option<PathBuf>,
+    },
+    /// Edit the meta sections of Markdowns in place, keeping their comments
+    /// and formatting.
+    Meta {
+        #[command(subcommand)]
+        command: MetaCommand,
+    },
+    /// Create a starter project, with a config file, a template, a partials
+    /// directory of shortcodes, and an example post.
+    Init {
+        /// The directory to create the project in.
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Parse the templates and Markdowns, without writing anything, and report
+    /// their problems, exiting with an error if there are any.
+    Lint {
+        /// List of Markdown files ending in .md, directories, or globs such as
+        /// "posts/**/*.md".
+        #[arg(short, long, required_unless_present = "templates", value_name = "FILES", num_args = 1..)]
+        markdowns: Vec<PathBuf>,
+        /// The templates to check, along with the variables that the Markdowns
+        /// define for them.
+        #[arg(short, long, alias = "template", value_name = "FILES", num_args = 1..)]
+        templates: Vec<PathBuf>,
+        /// Variables for the templates, from a `.toml`, `.json`, `.yaml` or
+        /// meta section file.
+        #[arg(long, value_name = "FILE")]
+        variables: Option<PathBuf>,
+    },
+    /// Remove the files that were written with `--manifest`, along with the
+    /// incremental cache, leaving every other file in place.
+    Clean {
+        /// The manifest of the files that were written.
+        #[arg(long, value_name = "FILE", default_value = OutputManifest::FILE)]
+        manifest: PathBuf,
+        /// The cache of `--incremental`.
+        #[arg(long, value_name = "FILE", default_value = ".blogs-md-easy-cache")]
+        cache: PathBuf,
+        /// The output directory, which along with anything outside of it, is
+        /// never removed.
+        #[arg(short, long, value_name = "DIR", default_value = ".")]
+        output_dir: PathBuf,
+        /// Report which files would be removed, without removing anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Create a new Markdown from an archetype, or a draft post from its
+    /// title.
+    New {
+        /// The path of the Markdown to create, or the title of a post, such as
+        /// "My Post Title", which is created in the first Markdown directory of
+        /// the config, otherwise `posts`.
+        path: PathBuf,
+        /// The name of the archetype to use, such as `link` for
+        /// archetypes/link.md.
+        #[arg(short, long)]
+        kind: Option<String>,
+        /// The title of the Markdown, defaults to the file name in title case.
+        #[arg(long)]
+        title: Option<String>,
+        /// A variable of the archetype, such as `--var url=https://example.com`
+        /// for its `£url`, which can be repeated.
+        #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_var)]
+        vars: Vec<(String, String)>,
+        /// The directory that contains the archetypes.
+        #[arg(long, value_name = "DIR", default_value = "archetypes")]
+        archetypes_dir: PathBuf,
+        /// The config file of the project, whose schema the meta section of a
+        /// post is filled from.
+        #[arg(short, long, value_name = "FILE", default_value = Config::FILE)]
+        config: PathBuf,
+    },
+    /// Create a Markdown that fills every placeholder of a template with a
+    /// sample value, such as `[author]`, to preview its layout.
+    Sample {
+        /// The template to fill.
+        template: PathBuf,
+        /// Write the Markdown to this file, rather than to stdout.
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Render the Markdowns, and serve them on localhost, reloading the
+    /// browser whenever they change.
+    Serve {
+        #[command(flatten)]
+        render: Box<RenderArgs>,
+        /// The port to listen on.
+        #[arg(long, default_value_t = 8000)]
+        port: u16,
+    },
+    /// Summarise the Markdowns and templates, such as the number of posts each
+    /// year, and the most used variables.
+    Stats {
+        /// List of Markdown files ending in .md, directories, or globs such as
+        /// "posts/**/*.md".
+        #[arg(short, long, required = true, value_name = "FILES", num_args = 1..)]
+        markdowns: Vec<PathBuf>,
+        /// Templates whose filters and variables are counted.
+        #[arg(short, long, alias = "template", value_name = "FILES", num_args = 1..)]
+        templates: Vec<PathBuf>,
+        /// Print the statistics as JSON, rather than as a table.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Manage the themes of this project.
+    Theme {
+        #[command(subcommand)]
+        command: ThemeCommand,
+    },
+}
+
+/// Commands for managing themes.
+#[derive(Debug, Subcommand)]
+enum ThemeCommand {
+    /// Copy a theme from a local directory or Git URL into this project.
+    Install {
+        /// A directory containing a theme.toml, or a Git URL.
+        #[arg(value_name = "PATH|GIT-URL")]
+        source: String,
+        /// The directory that themes are installed into.
+        #[arg(long, value_name = "DIR", default_value = "themes")]
+        themes_dir: PathBuf,
+    },
+}
+
+/// Commands for editing the meta sections of Markdowns.
+#[derive(Debug, Subcommand)]
+enum MetaCommand {
+    /// Set the value of a key, adding it to the Markdowns that don't declare
+    /// it.
+    Set {
+        /// The key to set.
+        key: String,
+        /// The value of the key.
+        value: String,
+        /// List of Markdown files ending in .md, directories, or globs such as
+        /// "posts/**/*.md".
+        #[arg(required = true, value_name = "FILES")]
+        markdowns: Vec<PathBuf>,
+    },
+    /// Rename a key, keeping its value.
+    Rename {
+        /// The key to rename.
+        from: String,
+        /// The new name of the key.
+        to: String,
+        /// List of Markdown files ending in .md, directories, or globs such as
+        /// "posts/**/*.md".
+        #[arg(required = true, value_name = "FILES")]
+        markdowns: Vec<PathBuf>,
+    },
+    /// Remove a key, along with its line.
+    Remove {
+        /// The key to remove.
+        key: String,
+        /// List of Markdown files ending in .md, directories, or globs such as
+        /// "posts/**/*.md".
+        #[arg(required = true, value_name = "FILES")]
+        markdowns: Vec<PathBuf>,
+    },
+}
+
+/// The options that control how Markdowns are rendered, which are shared by
+/// the program and the `serve` command.
+#[derive(Args, Debug, Default)]
+struct RenderArgs {
     /// HTML template that the Markdowns will populate.
-    #[arg(short, long, required = true, alias = "template", value_name = "FILES", num_args = 1..)]
+    #[arg(short, long, required_unless_present = "theme", alias = "template", value_name = "FILES", num_args = 1..)]
     templates: Vec<PathBuf>,
 
+    /// Use the templates of a theme, either its directory or its name within
+    /// ./themes. Templates with the same file name replace the theme's.
+    #[arg(long, value_name = "THEME")]
+    theme: Option<PathBuf>,
+
+    /// Directory of the templates that a Markdown can choose with `template`
+    /// in its meta section, defaults to the directory of the first template.
+    #[arg(long, value_name = "DIR")]
+    templates_dir: Option<PathBuf>,
+
     // num_args is required so that we don't have to specify the option before
     // each file...
     // `-m file.md file2.md`    rather than    `-m file.md -m file2.md`
-    /// List of Markdown files ending in .md.
-    #[arg(short, long, required = true, value_name = "FILES", num_args = 1..)]
+    /// List of Markdown files ending in .md, directories, or globs such as
+    /// "posts/**/*.md". The structure of directories and globs is mirrored
+    /// into the output directory.
+    #[arg(short, long, required_unless_present = "stdin", value_name = "FILES", num_args = 1..)]
     markdowns: Vec<PathBuf>,
 
+    /// Read a single Markdown from stdin with `-`, and write its HTML to
+    /// stdout, such as `cat post.md | blogs-md-easy -t template.html -`.
+    #[arg(value_name = "-", value_parser = ["-"], hide_possible_values = true, conflicts_with_all = ["markdowns", "index", "archive", "blogroll", "fediverse_export", "calendar", "feed", "search_index", "anchors", "incremental", "build_manifest", "dry_run"])]
+    stdin: Option<String>,
+
     /// Output directory, defaults to the Markdown's directory.
     #[arg(short, long, value_name = "DIR")]
     output_dir: Option<PathBuf>,
 
+    /// Where each Markdown is written within the output directory, from its
+    /// meta section, such as "/:year/:month/:slug/".
+    #[arg(long, value_name = "PATTERN")]
+    permalink_pattern: Option<String>,
+
+    /// Write each output as `html`, or as `json` with the title, meta section,
+    /// and HTML of its Markdown.
+    #[arg(long, value_name = "FORMAT", default_value = "html")]
+    format: OutputFormat,
+
+    /// Variables for every Markdown, from a `.toml`, `.json`, `.yaml` or meta
+    /// section file. The Markdown's meta section takes precedence.
+    #[arg(long, value_name = "FILE")]
+    variables: Option<PathBuf>,
+
+    /// The environment variables that templates can use, such as `BUILD_SHA`
+    /// for `{{ £env.BUILD_SHA }}`. No others are read.
+    #[arg(long, value_name = "NAMES", num_args = 1.., value_delimiter = ',')]
+    env: Vec<String>,
+
     /// Define an allow list for features.
     #[arg(short, long, value_name = "RULES", num_args = 1..)]
     allow: Vec<String>,
+
+    /// Enable features for `{{ if feature "name" }}` blocks in templates.
+    #[arg(short, long = "feature", value_name = "FEATURES", num_args = 1..)]
+    features: Vec<String>,
+
+    /// Embed the meta section into the output, as either `comment` or `tags`.
+    #[arg(long, value_name = "STYLE", num_args = 0..=1, default_missing_value = "tags")]
+    embed_meta: Option<EmbedMeta>,
+
+    /// What to do when a template uses a variable that doesn't exist, either
+    /// `error`, `empty`, `keep`, or `default=VALUE`.
+    #[arg(long, value_name = "POLICY", default_value = "error")]
+    on_missing: MissingVariablePolicy,
+
+    /// Leave any malformed placeholders of the templates as they are written,
+    /// and report them as warnings, rather than stopping, which `serve` always
+    /// does.
+    #[arg(long)]
+    recover: bool,
+
+    /// Whether the meta section's title, or the Markdown's heading, is used as
+    /// `£title` when there are both, either `meta` or `heading`.
+    #[arg(long, value_name = "SOURCE", default_value = "meta")]
+    title_precedence: TitlePrecedence,
+
+    /// Remove the heading from the start of `£content`, for templates that
+    /// already place the `£title` in an `<h1>`.
+    #[arg(long)]
+    remove_heading: bool,
+
+    /// How many words a reader gets through each minute, for the
+    /// `£reading_time` of each Markdown, defaults to 200.
+    #[arg(long, value_name = "WORDS", value_parser = clap::value_parser!(u32).range(1..))]
+    words_per_minute: Option<u32>,
+
+    /// Add an `id` to each heading of the Markdown, so that
+    /// `{{ ref "post#anchor" }}` links can point to it.
+    #[arg(long)]
+    heading_ids: bool,
+
+    /// Add a `¶` link to each heading of the Markdown, so that a link to the
+    /// heading can be copied.
+    #[arg(long, requires = "heading_ids")]
+    permalinks: bool,
+
+    /// Add an `id` to each paragraph of the Markdown, from a hash of its text,
+    /// so that a link to a paragraph still works after others are edited.
+    #[arg(long)]
+    paragraph_ids: bool,
+
+    /// Load the players of YouTube and Vimeo embeds with the page, rather than
+    /// once they are clicked.
+    #[arg(long)]
+    eager_embeds: bool,
+
+    /// Copy the local images of each Markdown into the output directory,
+    /// beside its output.
+    #[arg(long, requires = "output_dir")]
+    copy_images: bool,
+
+    /// Add the `width` and `height` of each local image to its `<img>`, so
+    /// that the page doesn't shift as it loads.
+    #[arg(long)]
+    image_sizes: bool,
+
+    /// Inline the stylesheets, images, and fonts of each output into it, so
+    /// that each post is a single self-contained HTML file.
+    #[arg(long)]
+    single_file: bool,
+
+    /// Where the `publish_date` of a Markdown without one is taken from, either
+    /// a `filename` that starts with a date, its `modified` time, or both.
+    #[arg(long, value_name = "SOURCES", num_args = 1.., value_delimiter = ',')]
+    publish_date_from: Vec<DateSource>,
+
+    /// Build the site as it was on a past date, such as `2023-06-01`, leaving
+    /// out the Markdowns that were published after it.
+    #[arg(long, value_name = "DATE")]
+    as_of: Option<String>,
+
+    /// With `--as-of`, also read the templates, Markdowns, and other inputs as
+    /// they were committed to git on that date.
+    #[arg(long, requires_all = ["as_of", "output_dir"], conflicts_with = "stdin")]
+    from_git: bool,
+
+    /// Wrap each `##` section of the Markdown in an element, `section` unless
+    /// another is provided, which is labelled by its heading.
+    #[arg(long, value_name = "ELEMENT", num_args = 0..=1, default_missing_value = "section")]
+    wrap_sections: Option<String>,
+
+    /// Also write a compressed copy of each output beside it, for hosts that
+    /// serve pre-compressed files, either `gzip`, `brotli`, or both.
+    #[arg(long, value_name = "FORMATS", num_args = 1.., value_delimiter = ',')]
+    compress: Vec<Compression>,
+
+    /// Render each Markdown once for each locale, such as `en,fr`, the first
+    /// into the output directory, and the others into a directory of their
+    /// name.
+    #[arg(long, value_name = "LOCALES", num_args = 1.., value_delimiter = ',', requires = "output_dir")]
+    locales: Vec<String>,
+
+    /// Also write other versions of each output beside it, either `text`, as a
+    /// `.txt`, `email`, with its styles inlined, as an `.email.html`, or both.
+    #[arg(long, value_name = "VERSIONS", num_args = 1.., value_delimiter = ',')]
+    alternates: Vec<Alternate>,
+
+    /// Write the anchors of every Markdown's headings to a JSON file.
+    #[arg(long, value_name = "FILE")]
+    anchors: Option<PathBuf>,
+
+    /// The extension of links to other Markdowns, from `{{ ref }}` tags and
+    /// `.md` links, such as `""` for a host that serves `hello.html` at
+    /// `hello`, defaults to the extension of the template.
+    #[arg(long, value_name = "EXTENSION")]
+    link_extension: Option<String>,
+
+    /// The sigil of variables within the templates and Markdowns, such as `@`
+    /// for `{{ @title }}`, defaults to `£`. A `$` is always allowed.
+    #[arg(long, value_name = "CHAR")]
+    sigil: Option<char>,
+
+    /// The delimiters of placeholders and tags within the templates and
+    /// Markdowns, such as `--delimiters "[[" "]]"`, defaults to `{{` and `}}`.
+    #[arg(long, num_args = 2, value_names = ["OPEN", "CLOSE"], allow_hyphen_values = true)]
+    delimiters: Vec<String>,
+
+    /// Link the first occurrence of each term in this file to its definition,
+    /// on a glossary page that is rendered with the Markdowns.
+    #[arg(long, value_name = "FILE")]
+    glossary: Option<PathBuf>,
+
+    /// The directory of the templates of the shortcodes that the Markdowns
+    /// can use, such as `figure.html` for `{{ figure src: cat.jpg }}`.
+    #[arg(long, value_name = "DIR")]
+    shortcodes: Option<PathBuf>,
+
+    /// Render a template that lists every Markdown, such as a homepage, into
+    /// the output directory.
+    #[arg(long, value_name = "TEMPLATE")]
+    index: Option<PathBuf>,
+
+    /// Render the Markdowns with `draft = true` in their meta section, which
+    /// are otherwise skipped.
+    #[arg(long)]
+    include_drafts: bool,
+
+    /// Split the posts of the index across pages of this many posts.
+    #[arg(long, value_name = "N", requires = "index")]
+    per_page: Option<usize>,
+
+    /// Render a template that lists the posts of each year and month, such as
+    /// `2024/index.html` and `2024/05/index.html`, into the output directory.
+    #[arg(long, value_name = "TEMPLATE")]
+    archive: Option<PathBuf>,
+
+    /// Recommended blogs and webrings, available to templates as `£blogroll`
+    /// and `£webring.name.prev`, and exported to blogroll.opml.
+    #[arg(long, value_name = "FILE")]
+    blogroll: Option<PathBuf>,
+
+    /// The webmention endpoint, advertised with a `<link>` in each page.
+    #[arg(long, value_name = "URL")]
+    webmention_endpoint: Option<String>,
+
+    /// The pingback endpoint, advertised with a `<link>` in each page.
+    #[arg(long, value_name = "URL")]
+    pingback_endpoint: Option<String>,
+
+    /// Mentions that have been received, as JF2 JSON from a file or URL, which
+    /// are available to templates as `£webmentions`.
+    #[arg(long, value_name = "FILE|URL")]
+    webmentions: Option<String>,
+
+    /// The fediverse account of each author, which each page is attributed to
+    /// with a `fediverse:creator` meta tag.
+    #[arg(long, value_name = "FILE")]
+    fediverse: Option<PathBuf>,
+
+    /// Derive `£og_title`, `£og_description`, `£og_image` and `£og_url` for
+    /// the social-sharing tags of each page.
+    #[arg(long)]
+    open_graph: bool,
+
+    /// Write every post, and the fediverse account of its author, to a JSON
+    /// file for an ActivityPub bridge to publish.
+    #[arg(long, value_name = "FILE", requires = "fediverse")]
+    fediverse_export: Option<PathBuf>,
+
+    /// Write an iCalendar of the posts, on their `event_date`, `publish_date`
+    /// or `date`, for blogs of events and announcements.
+    #[arg(long, value_name = "FILE")]
+    calendar: Option<PathBuf>,
+
+    /// Write an RSS feed of the posts, described by the `feed.*` variables,
+    /// which is also a podcast feed for posts with an `enclosure`.
+    #[arg(long, value_name = "FILE")]
+    feed: Option<PathBuf>,
+
+    /// Write the title, URL, excerpt, tags, and text of each post to a JSON
+    /// file, for a client-side search library such as Lunr or Fuse.
+    #[arg(long, value_name = "FILE")]
+    search_index: Option<PathBuf>,
+
+    /// Warn about variables that the template uses but the Markdown doesn't
+    /// declare, which `--on-missing` would otherwise render silently, and
+    /// images without alt text.
+    #[arg(long)]
+    warnings: bool,
+
+    /// Where the `£description` of each Markdown is taken from, trying each in
+    /// turn, from its `meta` section, `excerpt`, or `content`.
+    #[arg(long, value_name = "SOURCES", num_args = 1.., value_delimiter = ',')]
+    description_from: Vec<DescriptionSource>,
+
+    /// Report the feature branches and placeholders of each template that
+    /// none of the Markdowns used.
+    #[arg(long)]
+    coverage: bool,
+
+    /// Skip the outputs whose template, Markdown, and options haven't changed
+    /// since the last run, remembering them in a cache file.
+    #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = ".blogs-md-easy-cache")]
+    incremental: Option<PathBuf>,
+
+    /// Record every file that is written in a manifest, so that the `clean`
+    /// command can remove them.
+    #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = OutputManifest::FILE)]
+    manifest: Option<PathBuf>,
+
+    /// Write every output to a JSON file, with its Markdown, template, meta,
+    /// content hash, and render duration, for deploy scripts and audits.
+    #[arg(long, value_name = "FILE")]
+    build_manifest: Option<PathBuf>,
+
+    /// Report which files would be rendered, skipped, and written, without
+    /// writing anything.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Keep rendering after a Markdown fails, then report every failure at
+    /// once.
+    #[arg(long)]
+    collect_errors: bool,
+}
+
+#[derive(Debug, Parser)]
+#[command(version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    render: RenderArgs,
+
+    /// Keep running, and render the Markdowns again whenever they, or the
+    /// templates, change.
+    #[arg(long, conflicts_with_all = ["dry_run", "stdin", "from_git"])]
+    watch: bool,
+
+    /// Print how long was spent parsing, creating variables, rendering each
+    /// placeholder, and writing.
+    #[arg(long)]
+    timings: bool,
+
+    /// Print why each Markdown was skipped, and each file that is written,
+    /// then with `-vv`, how long each Markdown took, and which placeholders
+    /// matched.
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+}
+
+/// A `tracing` layer that records how long each span took, by the name of the
+/// span.  \
+/// With `placeholders`, each placeholder is recorded separately, by the name
+/// of its variable.
+#[derive(Clone, Debug, Default)]
+struct Timings {
+    spans: Arc<Mutex<BTreeMap<String, Vec<Duration>>>>,
+    placeholders: bool,
+}
+
+/// Finds a field of a span or event, such as its `name`.
+struct FieldVisitor(&'static str, Option<String>);
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == self.0 {
+            self.1 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == self.0 {
+            self.1 = Some(format!("{:?}", value));
+        }
+    }
+}
+
+impl<S> Layer<S> for Timings where S: Subscriber + for<'a> LookupSpan<'a> {
+    fn on_new_span(&self, attributes: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut key = span.name().to_string();
+            if self.placeholders && key == "placeholder" {
+                let mut visitor = FieldVisitor("name", None);
+                attributes.record(&mut visitor);
+                if let Some(name) = visitor.1 {
+                    key = format!("placeholder £{}", name);
+                }
+            }
+            span.extensions_mut().insert((key, Instant::now()));
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(&id) {
+            if let Some((key, start)) = span.extensions().get::<(String, Instant)>() {
+                let mut spans = self.spans.lock().expect("timings to not be poisoned");
+                spans.entry(key.to_owned()).or_default().push(start.elapsed());
+            }
+        }
+    }
+}
+
+/// A `tracing` layer that prints each event at or above its `level` to stderr,
+/// along with how long each Markdown took, at [`Level::DEBUG`].
+#[derive(Clone, Debug)]
+struct Log {
+    level: Level,
+}
+
+/// When a Markdown started to render, and its path.
+struct Started(String, Instant);
+
+impl Log {
+    /// The layer for the number of `-v` flags.
+    fn new(verbose: u8) -> Self {
+        let level = match verbose {
+            0 | 1 => Level::INFO,
+            2 => Level::DEBUG,
+            _ => Level::TRACE,
+        };
+        Self { level }
+    }
+}
+
+impl<S> Layer<S> for Log where S: Subscriber + for<'a> LookupSpan<'a> {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if *event.metadata().level() <= self.level {
+            let mut visitor = FieldVisitor("message", None);
+            event.record(&mut visitor);
+            eprintln!("{:>5} {}", event.metadata().level(), visitor.1.unwrap_or_default());
+        }
+    }
+
+    fn on_new_span(&self, attributes: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if self.level < Level::DEBUG || attributes.metadata().name() != "markdown" {
+            return;
+        }
+        if let Some(span) = ctx.span(id) {
+            let mut visitor = FieldVisitor("path", None);
+            attributes.record(&mut visitor);
+            span.extensions_mut().insert(Started(visitor.1.unwrap_or_default(), Instant::now()));
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(&id) {
+            if let Some(Started(path, start)) = span.extensions().get::<Started>() {
+                eprintln!("{:>5} Rendered '{}' in {:.3?}.", Level::DEBUG, path, start.elapsed());
+            }
+        }
+    }
+}
+
+impl Timings {
+    /// Print each span's name, count, and total duration.
+    fn print(&self) {
+        println!("{:<12} {:>6} {:>12}", "Span", "Count", "Total");
+        for (name, durations) in self.spans.lock().expect("timings to not be poisoned").iter() {
+            let total = durations.iter().sum::<Duration>();
+            println!("{:<12} {:>6} {:>12}", name, durations.len(), format!("{:.3?}", total));
+        }
+    }
+
+    /// Print each span's name, count, and the 50th, 90th, and 99th percentiles
+    /// and maximum of its durations, slowest first.
+    fn print_percentiles(&self) {
+        let mut spans = self.spans.lock().expect("timings to not be poisoned").clone().into_iter().collect::<Vec<_>>();
+        for (_, durations) in spans.iter_mut() {
+            durations.sort();
+        }
+        // The slowest spans are the most interesting, so they are first.
+        let percentile = |durations: &[Duration], percent: usize| durations[(durations.len() - 1) * percent / 100];
+        spans.sort_by_key(|(_, durations)| std::cmp::Reverse(percentile(durations, 50)));
+
+        let width = spans.iter().map(|(name, _)| name.chars().count()).max().unwrap_or_default().max(12);
+        println!("{:<width$} {:>6} {:>12} {:>12} {:>12} {:>12}", "Span", "Count", "p50", "p90", "p99", "Max");
+        for (name, durations) in spans {
+            println!(
+                "{:<width$} {:>6} {:>12} {:>12} {:>12} {:>12}",
+                name,
+                durations.len(),
+                format!("{:.3?}", percentile(&durations, 50)),
+                format!("{:.3?}", percentile(&durations, 90)),
+                format!("{:.3?}", percentile(&durations, 99)),
+                format!("{:.3?}", percentile(&durations, 100)),
+            );
+        }
+    }
 }
 
 /// Converts a Vector of Strings, into a Vector of `AllowList`.  \
@@ -51,120 +742,1333 @@ fn get_allow_list(allow_list: Vec<String>) -> Vec<AllowList>{
     }).collect()
 }
 
+/// Expand any directories and globs into the Markdowns within them, returning
+/// the Markdowns, and the directories whose structure should be mirrored.
+///
+/// Only the files of a glob that end in `.md` are Markdowns, so that a glob
+/// such as `posts/**/*` skips the images beside them.
+fn find_markdowns(paths: Vec<PathBuf>) -> Result<(Vec<PathBuf>, Vec<PathBuf>), Box<dyn Error>> {
+    let is_glob = |path: &Path| path.to_string_lossy().contains(['*', '?', '[']);
+
+    let mut markdowns = vec![];
+    let mut input_dirs = vec![];
+    for path in paths {
+        if path.is_dir() {
+            markdowns.extend(markdowns_in(&path)?);
+            input_dirs.push(path);
+        } else if is_glob(&path) {
+            for markdown in glob::glob(&path.to_string_lossy())? {
+                let markdown = markdown?;
+                if markdown.is_file() && markdown.extension().unwrap_or_default() == "md" {
+                    markdowns.push(markdown);
+                }
+            }
+            // Everything before the first wildcard is the directory.
+            input_dirs.push(path.components().take_while(|component| !is_glob(Path::new(component.as_os_str()))).collect());
+        } else {
+            markdowns.push(path);
+        }
+    }
+
+    Ok((markdowns, input_dirs))
+}
+
+/// Every Markdown within a directory, and its subdirectories.
+fn markdowns_in(directory: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut markdowns = vec![];
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            markdowns.extend(markdowns_in(&path)?);
+        } else if path.extension().unwrap_or_default() == "md" {
+            markdowns.push(path);
+        }
+    }
+    markdowns.sort();
+
+    Ok(markdowns)
+}
+
 /// Take a Vector of paths, make sure they're Markdown files, then read the
 /// contents.
-fn get_markdowns(paths: Vec<PathBuf>) -> Vec<(PathBuf, String)> {
+fn get_markdowns(paths: Vec<PathBuf>) -> Result<Vec<(PathBuf, String)>, Box<dyn Error>> {
     paths
     .into_iter()
     // Ensure the file exists and is a `.md` file.
     .filter(|file| file.exists() && file.extension().unwrap_or_default() == "md")
     // Now read the contents into a String and convert to tuple.
-    .filter_map(|path| fs::read_to_string(&path).ok().map(|content| (path, content)))
+    .map(|path| read_file(&path).map(|content| (path, content)))
     .collect()
 }
 
-/// Locate all `Placeholder`s from the template.
-fn get_placeholders(template: Span) -> Result<Vec<Placeholder>, Box<dyn Error>> {
-    let mut placeholders = parse_placeholder_locations(template)?;
-    placeholders.sort_by(|a, b| b.selection.start.offset.cmp(&a.selection.start.offset));
-    Ok(placeholders)
+/// Find an installed theme by name, or load it directly from its directory.
+fn get_theme(theme: &Path) -> Result<Theme, Box<dyn Error>> {
+    if theme.join(Theme::MANIFEST).exists() {
+        Theme::load(theme)
+    } else {
+        Theme::load(&Path::new("themes").join(theme))
+    }
+}
+
+/// Replace any of the theme's templates that share a file name with one of the
+/// `templates`, then add the rest.
+fn override_templates(theme: &Theme, templates: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut merged: Vec<PathBuf> = theme.templates
+        .iter()
+        .map(|theme_template| templates
+            .iter()
+            .find(|template| template.file_name() == theme_template.file_name())
+            .unwrap_or(theme_template)
+            .to_owned()
+        )
+        .collect();
+
+    for template in templates {
+        if !merged.contains(&template) {
+            merged.push(template);
+        }
+    }
+
+    merged
+}
+
+/// Remove every file in the manifest, then the manifest itself and the cache,
+/// or only report them with `dry_run`.
+fn clean(manifest_path: &Path, cache_path: &Path, output_dir: &Path, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    if !manifest_path.exists() && !cache_path.exists() {
+        Err(format!("Unable to find the manifest '{}', render with `--manifest` to record the files that are written.", manifest_path.to_string_lossy()))?;
+    }
+
+    let manifest = OutputManifest::load(manifest_path)?;
+    if dry_run {
+        for path in manifest.removable(output_dir).iter().chain([manifest_path.to_owned(), cache_path.to_owned()].iter().filter(|path| path.is_file())) {
+            println!("Would remove '{}'.", path.to_string_lossy());
+        }
+        return Ok(());
+    }
+
+    let mut removed = manifest.clean(output_dir)?;
+    for path in [manifest_path, cache_path].into_iter().filter(|path| path.is_file()) {
+        fs::remove_file(path)?;
+        removed.push(path.to_owned());
+    }
+    for path in &removed {
+        println!("Removed '{}'.", path.to_string_lossy());
+    }
+
+    Ok(())
+}
+
+/// The path of every file within `dir`, relative to it.
+fn relative_files(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        // As with `copy_dir`, version control directories are never copied.
+        if entry.file_name() == ".git" {
+            continue;
+        }
+        match entry.file_type()?.is_dir() {
+            true => files.extend(relative_files(&entry.path())?.into_iter().map(|path| Path::new(&entry.file_name()).join(path))),
+            false => files.push(PathBuf::from(entry.file_name())),
+        }
+    }
+    Ok(files)
+}
+
+/// Create a starter project in `project_dir`, dated today.
+fn init(project_dir: PathBuf) -> Result<(), Box<dyn Error>> {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+    for path in init_project(&project_dir, &unix_to_date(seconds))? {
+        println!("Created '{}'.", path.to_string_lossy());
+    }
+    println!("Render it with `blogs-md-easy build --config {}`.", project_dir.join(Config::FILE).to_string_lossy());
+
+    Ok(())
+}
+
+/// Write a new Markdown from an archetype, without overwriting an existing file.
+fn new_markdown(path: PathBuf, kind: Option<String>, title: Option<String>, vars: Vec<(String, String)>, archetypes_dir: PathBuf) -> Result<(), Box<dyn Error>> {
+    if path.exists() {
+        Err(format!("The file '{}' already exists.", path.to_string_lossy()))?;
+    }
+
+    // Turn `my-first_post.md` into `My First Post`.
+    let title = title.unwrap_or_else(|| render_filter(
+        path.file_stem().unwrap_or_default().to_string_lossy().replace(['-', '_'], " "),
+        &Filter::Text { case: TextCase::Title }
+    ).map(Cow::into_owned).unwrap_or_default());
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+    let mut variables = HashMap::from([
+        ("title".to_string(), title),
+        ("date".to_string(), unix_to_date(seconds)),
+    ]);
+    variables.extend(vars);
+
+    let (markdown, warnings) = render_archetype(&archetypes_dir, kind.as_deref(), &variables)?;
+    for warning in warnings {
+        println!("Warning: {}, which was left empty, provide it with `--var`.", warning);
+    }
+
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    fs::write(&path, markdown)?;
+    println!("Created '{}'.", path.to_string_lossy());
+
+    Ok(())
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let cli = Cli::parse();
+/// Parse a `--var` of `new`, such as `url=https://example.com`.
+fn parse_var(var: &str) -> Result<(String, String), String> {
+    match var.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().trim_start_matches('£').to_string(), value.to_string())),
+        _ => Err(format!("Expected KEY=VALUE, but was given '{}'.", var)),
+    }
+}
 
-    let templates = cli.templates;
-    let allow_list = get_allow_list(cli.allow);
+/// Write a draft post named after its `title` and today's date, filling its
+/// meta section from the schema of the config, if there is one.
+fn new_draft(title: &str, config: PathBuf) -> Result<(), Box<dyn Error>> {
+    let config = match config.exists() {
+        true => Config::load(&config)?,
+        false => Config::default(),
+    };
+    let posts_dir = config.markdowns.iter().find(|path| path.is_dir()).cloned().unwrap_or_else(|| PathBuf::from("posts"));
 
-    // Get only existing markdowns.
-    let markdowns = get_markdowns(cli.markdowns);
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+    let path = new_post(&posts_dir, title, &unix_to_date(seconds), &config.schema())?;
+    println!("Created '{}'.", path.to_string_lossy());
+
+    Ok(())
+}
 
-    for template_path in &templates {
-        // Check that the actual template exists.
-        if !template_path.try_exists().map_err(|_| "The template could not be found.".to_string())? {
-            Err("The template file does not exist.".to_string())?;
+/// Load the variables for every Markdown.
+fn get_variables(path: Option<&Path>, env: &[String], theme: Option<&Theme>) -> Result<Vec<Meta>, Box<dyn Error>> {
+    // Site-wide variables take precedence over those from the theme, so must
+    // come first.
+    let mut variables = match path {
+        Some(path) => load_variables(path)?,
+        None => vec![],
+    };
+    variables.extend(load_env_variables(env));
+    if let Some(theme) = theme {
+        variables.extend(theme.variables.to_owned());
+    }
+    Ok(variables)
+}
+
+/// Everything needed to render the Markdowns, so that they can be rendered
+/// again when they change.
+struct Site {
+    templates: Vec<PathBuf>,
+    markdowns: Vec<PathBuf>,
+    variables_path: Option<PathBuf>,
+    env: Vec<String>,
+    theme: Option<Theme>,
+    options: BatchOptions,
+    allow_list: Vec<AllowList>,
+    warnings: bool,
+    coverage: bool,
+    cache_path: Option<PathBuf>,
+    manifest_path: Option<PathBuf>,
+    build_manifest: Option<PathBuf>,
+    anchors_path: Option<PathBuf>,
+    index: Option<PathBuf>,
+    archive: Option<PathBuf>,
+    blogroll: Option<Blogroll>,
+    fediverse_export: Option<PathBuf>,
+    calendar: Option<PathBuf>,
+    feed: Option<PathBuf>,
+    search_index: Option<PathBuf>,
+    /// The commands that are run on each output of a Markdown, from the
+    /// directory that they are run in.
+    post_render: Vec<String>,
+    commands_dir: PathBuf,
+}
+
+impl Site {
+    /// Find the templates, Markdowns, and variables from the arguments.
+    fn new(args: RenderArgs) -> Result<Self, Box<dyn Error>> {
+        let theme = args.theme.as_deref().map(get_theme).transpose()?;
+        let templates = match &theme {
+            Some(theme) => override_templates(theme, args.templates),
+            None => args.templates,
         };
-        let template = std::fs::read_to_string(&template_path)?;
-        let template = Span::new(&template);
 
-        // All placeholders that are present in the template.
-        let placeholders = get_placeholders(template)?;
+        if let Some(element) = args.wrap_sections.as_ref().filter(|element| element.is_empty() || !element.chars().all(|c| c.is_ascii_alphanumeric())) {
+            Err(format!("Unable to wrap sections in '{}', expected an element name, such as section.", element))?;
+        }
+        if let Some(date) = args.as_of.as_ref().filter(|date| !is_date(date)) {
+            Err(format!("Unable to build as of '{}', expected a date such as 2023-06-01.", date))?;
+        }
 
-        for (markdown_url, markdown) in &markdowns {
-            let markdown = Span::new(markdown);
-            let mut html_doc = template.fragment().to_string();
+        let (markdowns, input_dirs) = find_markdowns(args.markdowns)?;
+        let variables = get_variables(args.variables.as_deref(), &args.env, theme.as_ref())?;
+        let build_cache = args.incremental.as_deref().map(BuildCache::load).transpose()?;
+        // The manifest remembers the files of earlier runs too.
+        let manifest = args.manifest.as_deref().map(OutputManifest::load).transpose()?;
+        let webmentions = match (&args.webmention_endpoint, &args.pingback_endpoint, &args.webmentions) {
+            (None, None, None) => None,
+            (endpoint, pingback, mentions) => {
+                let markup = Webmentions::default().markup.with_variables("webmentions", &variables)?;
+                Some(Webmentions {
+                    endpoint: endpoint.to_owned(),
+                    pingback: pingback.to_owned(),
+                    mentions: mentions.as_deref().map(Webmentions::load).transpose()?.unwrap_or_default(),
+                    markup,
+                })
+            },
+        };
+        let blogroll = match args.blogroll.as_deref().map(Blogroll::load).transpose()? {
+            Some(blogroll) => Some(Blogroll { markup: blogroll.markup.with_variables("blogroll", &variables)?, ..blogroll }),
+            None => None,
+        };
 
-            // Parse the meta values, and combine them with the title and content of
-            // the markdown file.
-            let (markdown, meta_values) = parse_meta_section(markdown).unwrap_or((markdown, vec![]));
-            let variables: HashMap<String, String> = create_variables(markdown, meta_values)?;
-
-            // Check for unused variables.
-            if !allow_list.contains(&AllowList::Unused) && !allow_list.contains(&AllowList::UnusedVariables) {
-                let placeholder_keys = placeholders.iter().map(|p| &p.name).collect::<Vec<&String>>();
-                let unused_variables = variables.keys().filter(|key| !placeholder_keys.contains(key)).collect::<Vec<&String>>();
-                if !unused_variables.is_empty() {
-                    println!(
-                        "Warning: Unused variable{} in '{}': {}",
-                        if unused_variables.len() == 1_usize { "" } else { "s" },
-                        &markdown_url.to_string_lossy(),
-                        unused_variables.iter().map(|v| v.to_string()).collect::<Vec<String>>().join(", ")
-                    );
-                }
-            }
+        // The static assets of the theme are copied beside the outputs.
+        let asset_dirs = theme.iter().map(Theme::static_dir).collect();
 
-            for placeholder in &placeholders {
-                if let Some(variable) = variables.get(&placeholder.name) {
-                    // Used to deref the variable.
-                    let mut variable = variable.to_owned();
+        let syntax = match args.delimiters.as_slice() {
+            [] => Syntax::new(args.sigil.unwrap_or('£'), "{{", "}}")?,
+            [open, close] => Syntax::new(args.sigil.unwrap_or('£'), open, close)?,
+            _ => Err("Expected an opening and a closing delimiter.")?,
+        };
 
-                    for filter in &placeholder.filters {
-                        variable = render_filter(variable, filter);
-                    }
+        Ok(Self {
+            templates,
+            markdowns,
+            variables_path: args.variables,
+            env: args.env,
+            theme,
+            options: BatchOptions {
+                output_dir: args.output_dir,
+                permalink_pattern: args.permalink_pattern,
+                format: args.format,
+                embed_meta: args.embed_meta,
+                features: args.features,
+                variables,
+                on_missing: args.on_missing,
+                recover: args.recover,
+                title_precedence: args.title_precedence,
+                remove_heading: args.remove_heading,
+                words_per_minute: args.words_per_minute,
+                heading_ids: args.heading_ids,
+                permalinks: args.permalinks,
+                paragraph_ids: args.paragraph_ids,
+                link_extension: args.link_extension,
+                syntax,
+                eager_embeds: args.eager_embeds,
+                open_graph: args.open_graph,
+                copy_images: args.copy_images,
+                image_sizes: args.image_sizes,
+                single_file: args.single_file,
+                asset_dirs,
+                publish_date_from: args.publish_date_from,
+                as_of: args.as_of,
+                wrap_sections: args.wrap_sections,
+                compression: args.compress,
+                locales: args.locales,
+                alternates: args.alternates,
+                glossary: args.glossary.as_deref().map(Glossary::load).transpose()?,
+                shortcodes: args.shortcodes.as_deref().map(Shortcodes::load).transpose()?.unwrap_or_default(),
+                per_page: args.per_page,
+                include_drafts: args.include_drafts,
+                dry_run: args.dry_run,
+                collect_errors: args.collect_errors,
+                webmentions,
+                fediverse: args.fediverse.as_deref().map(Fediverse::load).transpose()?,
+                input_dirs,
+                description_from: args.description_from,
+                build_cache,
+                manifest,
+                templates_dir: args.templates_dir,
+                ..Default::default()
+            },
+            allow_list: get_allow_list(args.allow),
+            warnings: args.warnings,
+            coverage: args.coverage,
+            cache_path: args.incremental,
+            manifest_path: args.manifest,
+            build_manifest: args.build_manifest,
+            anchors_path: args.anchors,
+            index: args.index,
+            archive: args.archive,
+            blogroll,
+            fediverse_export: args.fediverse_export,
+            calendar: args.calendar,
+            feed: args.feed,
+            search_index: args.search_index,
+            post_render: vec![],
+            commands_dir: PathBuf::new(),
+        })
+    }
 
-                    html_doc = replace_substring(&html_doc, placeholder.selection.start.offset, placeholder.selection.end.offset, &variable);
-                } else {
-                    let url = markdown_url.to_str().unwrap_or_default();
-                    return Err(format!("Missing variable '{}' in markdown '{}'.", &placeholder.name, url))?;
+    /// Render some of the Markdowns, and place the theme's static assets
+    /// alongside them.
+    fn render(&self, markdowns: Vec<PathBuf>) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        let markdowns = get_markdowns(markdowns)?;
+        // Every Markdown can be referenced and listed, even when only some are
+        // rendered.
+        let others = match markdowns.len() == self.markdowns.len() {
+            true => None,
+            false => Some(get_markdowns(self.markdowns.to_owned())?),
+        };
+        let all_markdowns = others.as_ref().unwrap_or(&markdowns);
+        // Drafts aren't rendered, so they can't be referenced either, and nor
+        // can posts from after the date that the site is built as of.
+        let published = all_markdowns
+            .iter()
+            .filter(|(_, markdown)| self.options.include_drafts || !is_draft(markdown))
+            .filter(|(path, markdown)| is_published_by(path, markdown, &self.options))
+            .cloned()
+            .collect::<Vec<(PathBuf, String)>>();
+        let anchors = AnchorMap::new(&published);
+        if let Some(path) = &self.anchors_path {
+            self.write(path, &anchors.to_json())?;
+        }
+        if self.options.dry_run && !self.options.include_drafts {
+            for (path, _) in markdowns.iter().filter(|(_, markdown)| is_draft(markdown)) {
+                println!("Would skip '{}', as it is a draft.", path.to_string_lossy());
+            }
+        }
+        if let Some(as_of) = self.options.as_of.as_ref().filter(|_| self.options.dry_run) {
+            for (path, _) in markdowns.iter().filter(|(path, markdown)| !is_published_by(path, markdown, &self.options)) {
+                println!("Would skip '{}', as it was published after {}.", path.to_string_lossy(), as_of);
+            }
+        }
+
+        let mut options = BatchOptions { anchors: Some(anchors), ..self.options.clone() };
+        if let Some(blogroll) = &self.blogroll {
+            options.variables.extend(blogroll.variables());
+            let output_dir = options.output_dir.to_owned().unwrap_or_default();
+            self.write(&output_dir.join("blogroll.opml"), &blogroll.to_opml("Blogroll"))?;
+        }
+        let mut coverage: BTreeMap<PathBuf, TemplateCoverage> = BTreeMap::new();
+        let mut malformed = BTreeSet::new();
+        let mut rendered = vec![];
+        let mut built = vec![];
+        let mut on_progress = |progress: &BatchProgress| {
+            if self.options.dry_run {
+                print_dry_run(progress);
+            }
+            if self.build_manifest.is_some() {
+                built.push(progress.clone());
+            }
+            if !progress.skipped && !self.post_render.is_empty() {
+                rendered.push((progress.markdown_path.to_owned(), progress.output_path.to_owned()));
+            }
+            // The malformed placeholders of a template are the same for every
+            // Markdown, so they are only printed once.
+            let errors = progress.warnings.iter().filter(|warning| matches!(warning, Warning::Malformed(_))).collect::<Vec<&Warning>>();
+            if !errors.is_empty() && malformed.insert(progress.template_path.to_owned()) {
+                for error in errors {
+                    println!("Warning: {}\nIn '{}'.", error, progress.template_path.to_string_lossy());
+                }
+            }
+            print_warnings(progress, &self.allow_list, self.warnings);
+            if self.coverage {
+                // A template that a Markdown chose is only known once it is
+                // rendered.
+                if !coverage.contains_key(&progress.template_path) {
+                    if let Ok(template) = read_file(&progress.template_path).and_then(|template| TemplateCoverage::new(&apply_whitespace_control(&self.options.syntax.translate(&template)), &self.options.features)) {
+                        coverage.insert(progress.template_path.to_owned(), template);
+                    }
+                }
+                if let Some(template) = coverage.get_mut(&progress.template_path) {
+                    template.record(progress);
+                }
+            }
+        };
+        let mut outputs = render_batch(&self.templates, &markdowns, &options, &mut on_progress, &CancellationToken::new())?;
+        if let Some(path) = &self.build_manifest {
+            self.write(path, &progress_to_manifest(&built))?;
+        }
+        for (markdown_path, output_path) in &rendered {
+            for command in &self.post_render {
+                if self.options.dry_run {
+                    println!("Would run '{}' on '{}'.", command, output_path.to_string_lossy());
+                    continue;
+                }
+                let env = [("BLOGS_MD_EASY_MARKDOWN", std::path::absolute(markdown_path)?), ("BLOGS_MD_EASY_OUTPUT", std::path::absolute(output_path)?)];
+                run_command(command, &self.commands_dir, &env)?;
+            }
+        }
+        for (path, coverage) in &coverage {
+            print_coverage(path, coverage);
+        }
+        // The index, and the exports, link to the outputs of the first template.
+        let posts = match self.index.is_some() || self.archive.is_some() || self.fediverse_export.is_some() || self.calendar.is_some() || self.feed.is_some() || self.search_index.is_some() {
+            true => collect_posts(&self.templates, all_markdowns, &options)?,
+            false => vec![],
+        };
+        let variable = |key: &str| options.variables.iter().find(|meta| meta.key == key).map(|meta| meta.value.as_str());
+        let output_dir = options.output_dir.to_owned().unwrap_or_default();
+        if let Some(index) = &self.index {
+            let pages = render_index(index, &posts, &options)?;
+            if self.options.dry_run {
+                for page in &pages {
+                    println!("Would write '{}'.", page.to_string_lossy());
                 }
             }
+            outputs.extend(pages);
+        }
+        if let Some(archive) = &self.archive {
+            let pages = render_archives(archive, &posts, &options)?;
+            if self.options.dry_run {
+                for page in &pages {
+                    println!("Would write '{}'.", page.to_string_lossy());
+                }
+            }
+            outputs.extend(pages);
+        }
+        if let (Some(path), Some(fediverse)) = (&self.fediverse_export, &options.fediverse) {
+            self.write(path, &fediverse.to_json(&posts, &output_dir, variable("base_url").unwrap_or_default()))?;
+        }
+        if let Some(path) = &self.calendar {
+            let name = variable("site_name").unwrap_or("Posts");
+            self.write(path, &posts_to_ics(&posts, &output_dir, variable("base_url").unwrap_or_default(), name)?)?;
+        }
+        if let Some(path) = &self.feed {
+            self.write(path, &posts_to_rss(&posts, &output_dir, variable("base_url").unwrap_or_default(), &feed_channel(&options.variables))?)?;
+        }
+        if let Some(path) = &self.search_index {
+            self.write(path, &posts_to_search_index(&posts, &output_dir, variable("base_url").unwrap_or_default()))?;
+        }
+        if let (Some(cache), Some(path), false) = (&self.options.build_cache, &self.cache_path, self.options.dry_run) {
+            cache.save(path)?;
+        }
 
-            // Add newlines before each heading element, because I'd like the HTML
-            // to be easy to read.
-            for h in 2..6 {
-                let h = format!("<h{h}>");
-                html_doc = html_doc.replace(&h, &format!("\n{h}"));
+        if let Some(theme) = self.theme.as_ref().filter(|theme| theme.static_dir().is_dir()) {
+            let mut output_dirs = outputs.iter().filter_map(|output| output.parent()).collect::<Vec<&Path>>();
+            output_dirs.sort();
+            output_dirs.dedup();
+            let static_files = match &self.options.manifest {
+                Some(_) => relative_files(&theme.static_dir())?,
+                None => vec![],
             };
+            for output_dir in output_dirs {
+                match self.options.dry_run {
+                    true => println!("Would copy '{}' into '{}'.", theme.static_dir().to_string_lossy(), output_dir.to_string_lossy()),
+                    false => copy_dir(&theme.static_dir(), output_dir)?,
+                }
+                if let Some(manifest) = self.options.manifest.as_ref().filter(|_| !self.options.dry_run) {
+                    for path in &static_files {
+                        manifest.insert(output_dir.join(path));
+                    }
+                }
+            }
+        }
+        if let (Some(manifest), Some(path), false) = (&self.options.manifest, &self.manifest_path, self.options.dry_run) {
+            manifest.save(path)?;
+        }
 
-            // Get the template extension, because the user might be passing in
-            // something like an SVG.
-            let template_ext = template_path.extension().unwrap_or(OsStr::new("html"));
+        Ok(outputs)
+    }
 
-            // Get the output path where the `.md` is replaced with `.html`.
-            let mut output_path = match cli.output_dir.clone() {
-                Some(path) => path.join(markdown_url.with_extension(template_ext).file_name().unwrap()),
-                None => markdown_url.with_extension(template_ext),
-            };
+    /// Write a file that accompanies the outputs, creating its directory, or
+    /// only report it with `--dry-run`.
+    fn write(&self, path: &Path, contents: &str) -> Result<(), Box<dyn Error>> {
+        if self.options.dry_run {
+            println!("Would write '{}'.", path.to_string_lossy());
+            return Ok(());
+        }
+        if let Some(directory) = path.parent().filter(|directory| !directory.as_os_str().is_empty()) {
+            fs::create_dir_all(directory)?;
+        }
+        write_if_changed(path, contents.as_bytes())?;
+        if let Some(manifest) = &self.options.manifest {
+            manifest.insert(path.to_owned());
+        }
+        Ok(())
+    }
+
+    /// Render the Markdowns again whenever they, the templates, any fragments,
+    /// or the variables file change, until the program is stopped.
+    ///
+    /// Only the Markdowns that changed are rendered again, unless something
+    /// that every Markdown depends on has changed.
+    fn watch<F>(&mut self, mut on_render: F) -> Result<(), Box<dyn Error>>
+    where
+        F: FnMut(),
+    {
+        // Only the Markdowns that exist are watched.
+        let markdowns = self.markdowns.iter().filter(|path| path.exists()).cloned().collect::<Vec<PathBuf>>();
+
+        let canonicalize = |paths: &[PathBuf]| paths.iter().map(|path| fs::canonicalize(path).unwrap_or(path.to_owned())).collect::<Vec<PathBuf>>();
+        let markdown_paths = canonicalize(&markdowns);
+        let template_paths = canonicalize(&self.templates.iter().chain(&self.index).chain(&self.archive).cloned().collect::<Vec<PathBuf>>());
+        let variables_path = self.variables_path.as_ref().map(|path| canonicalize(&[path.to_owned()]).remove(0));
+
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        // Editors often save by replacing the file, which would end a watch on
+        // the file itself, so the directories are watched instead.
+        // Fragments may be nested beneath the templates.
+        let mut directories = template_paths.iter()
+            .filter_map(|path| path.parent())
+            .map(|directory| (directory, RecursiveMode::Recursive))
+            .chain(markdown_paths.iter().chain(&variables_path).filter_map(|path| path.parent()).map(|directory| (directory, RecursiveMode::NonRecursive)))
+            .collect::<Vec<(&Path, RecursiveMode)>>();
+        directories.sort_by_key(|(directory, mode)| (*directory, *mode == RecursiveMode::NonRecursive));
+        directories.dedup_by_key(|(directory, _)| *directory);
+        for (directory, mode) in directories {
+            watcher.watch(directory, mode)?;
+        }
+
+        let changed_paths = |event: notify::Result<notify::Event>| event
+            .ok()
+            .filter(|event| matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)))
+            .map(|event| event.paths)
+            .unwrap_or_default();
+
+        println!("Watching for changes, press Ctrl+C to stop.");
+        while let Ok(event) = receiver.recv() {
+            // A single save can produce several events, so gather them all
+            // before rendering.
+            let mut paths = changed_paths(event);
+            thread::sleep(Duration::from_millis(100));
+            while let Ok(event) = receiver.try_recv() {
+                paths.extend(changed_paths(event));
+            }
 
-            // If there are multiple templates, then add that to the output path
-            // to avoid overwriting issues.
-            if templates.len() > 1 {
-                output_path = output_path.with_file_name(format!(
-                    "{}-{}",
-                    &template_path.file_stem().unwrap_or_default().to_str().unwrap_or_default(),
-                    output_path.file_stem().unwrap_or_default().to_str().unwrap_or_default()
-                )).with_extension("html");
+            // The outputs may be written beside the sources, so only a change
+            // to a source can trigger a render, rather than the render itself.
+            let render_all = paths.iter().any(|path| {
+                template_paths.contains(path)
+                || variables_path.as_ref() == Some(path)
+                || (path.extension().unwrap_or_default() == "md" && !markdown_paths.contains(path))
+            });
+            let changed = markdowns.iter()
+                .zip(&markdown_paths)
+                .filter(|(_, path)| render_all || paths.contains(path))
+                .map(|(markdown, _)| markdown.to_owned())
+                .collect::<Vec<PathBuf>>();
+            if changed.is_empty() {
+                continue;
             }
 
-            // Create all folders from the path.
-            if let Some(path) = output_path.parent() {
-                if !path.exists() {
-                    fs::create_dir_all(path)?;
+            let result = (|| {
+                if render_all {
+                    self.options.variables = get_variables(self.variables_path.as_deref(), &self.env, self.theme.as_ref())?;
                 }
+                self.render(changed)
+            })();
+            match result {
+                Ok(outputs) => {
+                    println!("Rendered {} file{}.", outputs.len(), if outputs.len() == 1 { "" } else { "s" });
+                    on_render();
+                },
+                // Keep watching, so that the mistake can be fixed.
+                Err(e) => print_error(e.as_ref()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The variables that describe a feed, from the `feed.*` variables, named
+/// after the `site_name` unless it has its own `title`.
+fn feed_channel(variables: &[Meta]) -> Vec<Meta> {
+    let mut channel = variables
+        .iter()
+        .filter_map(|meta| Some(Meta::new(meta.key.strip_prefix("feed.")?, &meta.value)))
+        .collect::<Vec<Meta>>();
+    if let Some(site_name) = variables.iter().find(|meta| meta.key == "site_name") {
+        channel.push(Meta::new("title", &site_name.value));
+    }
+    channel
+}
+
+/// Print where an output would be written, or why it would be skipped.
+fn print_dry_run(progress: &BatchProgress) {
+    match progress.skipped {
+        true => println!("Would skip '{}', as '{}' is unchanged.", progress.markdown_path.to_string_lossy(), progress.output_path.to_string_lossy()),
+        false => println!(
+            "Would render '{}' into '{}'{}.",
+            progress.markdown_path.to_string_lossy(),
+            progress.output_path.to_string_lossy(),
+            if progress.noindex { ", hidden from search engines and feeds" } else { "" },
+        ),
+    }
+}
+
+/// Print any unused variables, unless they are allowed, and with `warnings`,
+/// any placeholders that the Markdown never satisfied, along with the rest of
+/// its [`Warning`]s.
+fn print_warnings(progress: &BatchProgress, allow_list: &[AllowList], warnings: bool) {
+    // Check for unused variables.
+    if !allow_list.contains(&AllowList::Unused) && !allow_list.contains(&AllowList::UnusedVariables) {
+        let unused_variables = &progress.unused_variables;
+        if !unused_variables.is_empty() {
+            println!(
+                "Warning: Unused variable{} in '{}': {}",
+                if unused_variables.len() == 1_usize { "" } else { "s" },
+                &progress.markdown_path.to_string_lossy(),
+                unused_variables.join(", ")
+            );
+        }
+    }
+
+    // Check for placeholders that the Markdown never satisfied.
+    if warnings {
+        for name in &progress.missing_variables {
+            let suggestion = did_you_mean(name, &progress.unused_variables)
+                .map(|suggestion| format!(", did you mean '{}'?", suggestion))
+                .unwrap_or_default();
+            println!("Warning: Missing variable in '{}': {}{}", &progress.markdown_path.to_string_lossy(), name, suggestion);
+        }
+        // The unused and missing variables are printed above.
+        for warning in progress.warnings.iter().filter(|warning| matches!(warning, Warning::MissingAltText(_))) {
+            println!("Warning: {} in '{}'.", warning, &progress.markdown_path.to_string_lossy());
+        }
+    }
+}
+
+/// Print the parts of a template that none of the Markdowns used.
+fn print_coverage(path: &Path, coverage: &TemplateCoverage) {
+    for (line, branch) in &coverage.unused_branches {
+        println!("Warning: Unused branch in '{}' on line {}: {}", path.to_string_lossy(), line, branch);
+    }
+    // Every placeholder is unused when the outputs were up to date.
+    if coverage.markdowns > 0 {
+        for (line, name) in &coverage.unused_placeholders {
+            println!("Warning: Unused placeholder in '{}' on line {}: {}", path.to_string_lossy(), line, name);
+        }
+    }
+}
+
+/// Find the `candidate` that is the fewest edits away from `name`, provided it
+/// is close enough to be a typo.
+fn did_you_mean<'a>(name: &str, candidates: &'a [String]) -> Option<&'a str> {
+    // The Levenshtein distance, keeping only the previous row.
+    let distance = |a: &str, b: &str| {
+        let b = b.chars().collect::<Vec<char>>();
+        let mut previous = (0..=b.len()).collect::<Vec<usize>>();
+        for (i, a) in a.chars().enumerate() {
+            let mut current = vec![i + 1];
+            for (j, b) in b.iter().enumerate() {
+                current.push((previous[j] + usize::from(a != *b)).min(previous[j + 1] + 1).min(current[j] + 1));
+            }
+            previous = current;
+        }
+        previous[b.len()]
+    };
+
+    candidates
+        .iter()
+        .map(|candidate| (distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+/// Print the differences between two templates.
+fn diff_templates(old: PathBuf, new: PathBuf) -> Result<(), Box<dyn Error>> {
+    let diff = Template::new(&apply_whitespace_control(&read_file(&old)?))?.diff(&Template::new(&apply_whitespace_control(&read_file(&new)?))?);
+
+    if diff.is_empty() {
+        println!("No differences between '{}' and '{}'.", old.to_string_lossy(), new.to_string_lossy());
+        return Ok(());
+    }
+
+    for variable in &diff.added_variables {
+        println!("+ {variable}");
+    }
+    for variable in &diff.removed_variables {
+        println!("- {variable}");
+    }
+    for change in &diff.changed_filters {
+        println!("~ {}: {:?} -> {:?}", change.name, change.old, change.new);
+    }
+
+    Ok(())
+}
+
+/// Print the problems of the templates and Markdowns, failing if there are
+/// any.
+fn lint_project(markdowns: Vec<PathBuf>, templates: Vec<PathBuf>, variables: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    let markdowns = match markdowns.is_empty() {
+        true => vec![],
+        false => get_markdowns(find_markdowns(markdowns)?.0)?,
+    };
+    let templates = templates
+        .into_iter()
+        .map(|template| read_file(&template).map(|source| (template, source)))
+        .collect::<Result<Vec<(PathBuf, String)>, Box<dyn Error>>>()?;
+    let variables = get_variables(variables.as_deref(), &[], None)?;
+
+    let problems = lint(&templates, &markdowns, &variables);
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    if !problems.is_empty() {
+        Err(format!("Found {} problems in {} templates and {} Markdowns.", problems.len(), templates.len(), markdowns.len()))?;
+    }
+
+    println!("No problems in {} templates and {} Markdowns.", templates.len(), markdowns.len());
+    Ok(())
+}
+
+/// Print the statistics of the Markdowns and templates, as a table or JSON.
+fn print_stats(markdowns: Vec<PathBuf>, templates: Vec<PathBuf>, json: bool) -> Result<(), Box<dyn Error>> {
+    let (markdowns, _) = find_markdowns(markdowns)?;
+    let templates = templates
+        .iter()
+        .map(|template| Template::new(&apply_whitespace_control(&read_file(template)?)))
+        .collect::<Result<Vec<Template>, Box<dyn Error>>>()?;
+    let stats = Stats::new(&get_markdowns(markdowns)?, &templates);
+
+    match json {
+        true => println!("{}", stats.to_json()),
+        false => print!("{}", stats),
+    }
+
+    Ok(())
+}
+
+/// Render the Markdowns into the template `iterations` times, without writing
+/// them, and print the percentiles of how long each stage took.
+fn bench(template: PathBuf, markdowns: Vec<PathBuf>, iterations: u32) -> Result<(), Box<dyn Error>> {
+    let (markdowns, _) = find_markdowns(markdowns)?;
+    let markdowns = get_markdowns(markdowns)?;
+    if markdowns.is_empty() {
+        Err("There are no Markdowns to benchmark.")?;
+    }
+
+    let timings = Timings { placeholders: true, ..Default::default() };
+    tracing::subscriber::set_global_default(Registry::default().with(timings.clone()))?;
+
+    let options = BatchOptions { dry_run: true, ..Default::default() };
+    let start = Instant::now();
+    for _ in 0..iterations {
+        render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new())?;
+    }
+
+    println!("Rendered {} Markdowns {} times in {:.3?}.", markdowns.len(), iterations, start.elapsed());
+    timings.print_percentiles();
+    Ok(())
+}
+
+/// Make the same edit to the meta section of each Markdown, only writing
+/// those that change.
+fn edit_markdowns(edit: MetaEdit, markdowns: Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+    let (markdowns, _) = find_markdowns(markdowns)?;
+    let markdowns = get_markdowns(markdowns)?;
+
+    // Every Markdown is edited before any are written, so that an invalid
+    // meta section doesn't leave the others half edited.
+    let mut edited = vec![];
+    for (path, markdown) in &markdowns {
+        let output = edit_meta(markdown, &edit)
+            .map_err(|e| format!("Unable to edit the meta section of '{}': {}", path.to_string_lossy(), e))?;
+        if &output != markdown {
+            edited.push((path, output));
+        }
+    }
+    for (path, output) in &edited {
+        fs::write(path, output)?;
+    }
+
+    println!("Edited {} of {} Markdowns.", edited.len(), markdowns.len());
+    Ok(())
+}
+
+/// Create a sample Markdown for a template, and write it to `output`, or to
+/// stdout.
+fn sample(template: PathBuf, output: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    let markdown = sample_markdown(&template)?;
+    let Some(path) = output else {
+        print!("{}", markdown);
+        return Ok(());
+    };
+
+    if path.exists() {
+        Err(format!("The file '{}' already exists.", path.to_string_lossy()))?;
+    }
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    fs::write(&path, markdown)?;
+    println!("Created '{}'.", path.to_string_lossy());
+
+    Ok(())
+}
+
+/// Render a Markdown from stdin, and write its HTML to stdout.
+fn render_stdin(args: RenderArgs) -> Result<(), Box<dyn Error>> {
+    let mut bytes = vec![];
+    io::stdin().read_to_end(&mut bytes)?;
+    // The Markdown is named for its errors, and any relative links.
+    let path = Path::new("stdin.md");
+    let markdown = decode_file(&bytes, path)?;
+
+    let site = Site::new(args)?;
+    if site.templates.len() != 1 {
+        Err("Only a single template can be rendered to stdout.".to_string())?;
+    }
+    let html = render_markdown(&site.templates[0], path, &markdown, &site.options)?;
+    io::stdout().write_all(html.as_bytes())?;
+
+    Ok(())
+}
+
+/// A checkout of the git repository as it was on a past date, in a temporary
+/// worktree that is removed once dropped.
+struct Snapshot {
+    /// The directory that was current within the repository, such as `blog/`,
+    /// within the worktree.
+    dir: PathBuf,
+    worktree: PathBuf,
+}
+
+impl Snapshot {
+    /// Check out the last commit on or before the `date`, such as
+    /// `2023-06-01`.
+    fn checkout(date: &str) -> Result<Self, Box<dyn Error>> {
+        let prefix = git(&["rev-parse", "--show-prefix"])?;
+        let commit = git(&["rev-list", "-1", &format!("--before={}T23:59:59", date), "HEAD"])?;
+        if commit.is_empty() {
+            Err(format!("Unable to build as of '{}', as there are no commits on or before it.", date))?;
+        }
+
+        let worktree = env::temp_dir().join(format!("blogs-md-easy-as-of-{}", std::process::id()));
+        git(&["worktree", "add", "--detach", "--quiet", &worktree.to_string_lossy(), &commit])?;
+        Ok(Self { dir: worktree.join(prefix), worktree })
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        let _ = git(&["worktree", "remove", "--force", &self.worktree.to_string_lossy()]);
+    }
+}
+
+/// Run a shell `command` in the `directory`, with the `env` variables, failing
+/// if it does.
+fn run_command(command: &str, directory: &Path, env: &[(&str, PathBuf)]) -> Result<(), Box<dyn Error>> {
+    let (program, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+    let mut shell = std::process::Command::new(program);
+    shell.args([flag, command]);
+    if !directory.as_os_str().is_empty() {
+        shell.current_dir(directory);
+    }
+    let status = shell
+        .envs(env.iter().map(|(key, value)| (key, value.as_os_str())))
+        .status()
+        .map_err(|e| format!("Unable to run '{}': {}", command, e))?;
+    if !status.success() {
+        Err(format!("The command '{}' failed, with {}.", command, status))?;
+    }
+    Ok(())
+}
+
+/// Run git with the `args`, returning its trimmed stdout.
+fn git(args: &[&str]) -> Result<String, Box<dyn Error>> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Unable to run git: {}", e))?;
+    if !output.status.success() {
+        Err(format!("Unable to run 'git {}': {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()))?;
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Render the Markdowns as they were committed on the `--as-of` date, from a
+/// snapshot of the repository, writing the outputs where they would otherwise
+/// be written.
+fn render_from_git(mut args: RenderArgs) -> Result<(), Box<dyn Error>> {
+    let as_of = args.as_of.to_owned().unwrap_or_default();
+    if !is_date(&as_of) {
+        Err(format!("Unable to build as of '{}', expected a date such as 2023-06-01.", as_of))?;
+    }
+
+    // The outputs are written outside of the snapshot, so they must be
+    // resolved before moving into it.
+    let current_dir = env::current_dir()?;
+    for path in [&mut args.output_dir, &mut args.anchors, &mut args.incremental, &mut args.manifest, &mut args.build_manifest, &mut args.fediverse_export, &mut args.calendar, &mut args.feed, &mut args.search_index].into_iter().flatten() {
+        *path = current_dir.join(&path);
+    }
+
+    let snapshot = Snapshot::checkout(&as_of)?;
+    env::set_current_dir(&snapshot.dir)?;
+    let rendered = Site::new(args).and_then(|site| site.render(site.markdowns.clone()));
+    env::set_current_dir(current_dir)?;
+    rendered?;
+
+    Ok(())
+}
+
+/// Whether the `date` is a date such as `2023-06-01`.
+fn is_date(date: &str) -> bool {
+    date.len() == 10 && date.char_indices().all(|(i, c)| if i == 4 || i == 7 { c == '-' } else { c.is_ascii_digit() })
+}
+
+/// Render the Markdowns into a single changelog, along with a feed of them.
+fn changelog(markdowns: Vec<PathBuf>, template: PathBuf, output_dir: Option<PathBuf>, variables: Option<PathBuf>, group_by: ChangelogGroup, feed: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    let (markdowns, _) = find_markdowns(markdowns)?;
+    let options = BatchOptions {
+        output_dir,
+        variables: get_variables(variables.as_deref(), &[], None)?,
+        ..Default::default()
+    };
+    let posts = collect_posts(std::slice::from_ref(&template), &get_markdowns(markdowns)?, &options)?;
+    let output_path = render_changelog(&template, &posts, &options, group_by)?;
+
+    if let Some(path) = feed {
+        // Each entry links to its place within the changelog.
+        let entries = posts
+            .iter()
+            .map(|post| Post { output_path: PathBuf::from(format!("{}#{}", output_path.to_string_lossy(), post.id())), ..post.to_owned() })
+            .collect::<Vec<Post>>();
+        let base_url = options.variables.iter().find(|meta| meta.key == "base_url").map(|meta| meta.value.as_str()).unwrap_or_default();
+        let output_dir = options.output_dir.to_owned().unwrap_or_default();
+        if let Some(directory) = path.parent().filter(|directory| !directory.as_os_str().is_empty()) {
+            fs::create_dir_all(directory)?;
+        }
+        write_if_changed(&path, posts_to_rss(&entries, &output_dir, base_url, &feed_channel(&options.variables))?.as_bytes())?;
+    }
+
+    println!("Rendered '{}'.", output_path.to_string_lossy());
+    Ok(())
+}
+
+/// Write a zip archive of each Markdown to `<output_dir>/<name>.zip`.
+fn export(markdowns: Vec<PathBuf>, template: PathBuf, output_dir: PathBuf, variables: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    let (markdowns, _) = find_markdowns(markdowns)?;
+    let options = BatchOptions {
+        variables: get_variables(variables.as_deref(), &[], None)?,
+        ..Default::default()
+    };
+    fs::create_dir_all(&output_dir)?;
+    for (markdown_path, markdown) in get_markdowns(markdowns)? {
+        let zip = export_post(&template, &markdown_path, &markdown, &options)?;
+        let output_path = output_dir.join(markdown_path.with_extension("zip").file_name().unwrap_or_default());
+        fs::write(&output_path, zip)?;
+        println!("Exported '{}'.", output_path.to_string_lossy());
+    }
+    Ok(())
+}
+
+/// Render the Markdowns of the project that is described by the config file.
+fn build(path: PathBuf, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    if !path.exists() {
+        Err(format!("Unable to find config '{}'.", path.to_string_lossy()))?;
+    }
+    let config = Config::load(&path)?;
+    if config.templates.is_empty() || config.markdowns.is_empty() {
+        Err(format!("The config '{}' must list both templates and markdowns.", path.to_string_lossy()))?;
+    }
+
+    // Commands are run where the config is, as its paths are relative to it.
+    let root = path.parent().unwrap_or(Path::new(""));
+    let run_commands = |commands: &[String]| -> Result<(), Box<dyn Error>> {
+        for command in commands {
+            match dry_run {
+                true => println!("Would run '{}'.", command),
+                false => run_command(command, root, &[])?,
             }
+        }
+        Ok(())
+    };
+    run_commands(&config.pre_build)?;
+
+    let mut site = Site::new(RenderArgs {
+        templates: config.templates.to_owned(),
+        markdowns: config.markdowns.to_owned(),
+        output_dir: config.output_dir.to_owned(),
+        permalink_pattern: config.permalink_pattern.to_owned(),
+        templates_dir: config.templates_dir.to_owned(),
+        wrap_sections: config.wrap_sections.to_owned(),
+        compress: config.compress.iter().map(|format| format.parse()).collect::<Result<_, String>>()?,
+        locales: config.locales.to_owned(),
+        alternates: config.alternates.to_owned(),
+        shortcodes: config.shortcodes.to_owned(),
+        words_per_minute: config.words_per_minute,
+        eager_embeds: config.eager_embeds,
+        open_graph: config.open_graph,
+        copy_images: config.copy_images,
+        link_extension: config.link_extension.to_owned(),
+        sigil: config.sigil,
+        delimiters: config.delimiters.to_owned(),
+        image_sizes: config.image_sizes,
+        single_file: config.single_file,
+        publish_date_from: config.publish_date_from.iter().map(|source| source.parse()).collect::<Result<_, String>>()?,
+        description_from: config.description_from.iter().map(|source| source.parse()).collect::<Result<_, String>>()?,
+        dry_run,
+        collect_errors: config.collect_errors,
+        manifest: config.manifest.to_owned(),
+        env: config.env.to_owned(),
+        ..Default::default()
+    })?;
+    site.options.variables.extend(config.variables());
+    site.options.filter_defaults = config.filter_defaults();
+    site.options.filter_aliases = config.filter_aliases();
+    site.options.attributes = config.attributes();
+    site.options.profiles = config.profiles();
+    site.options.schema = config.schema();
+    site.post_render = config.post_render.to_owned();
+    site.commands_dir = root.to_owned();
+    site.render(site.markdowns.clone())?;
+    run_commands(&config.post_build)?;
+
+    Ok(())
+}
+
+/// Render the Markdowns, then serve them on localhost, reloading the browser
+/// whenever they are rendered again.
+fn serve(mut args: RenderArgs, port: u16) -> Result<(), Box<dyn Error>> {
+    if args.dry_run {
+        Err("Unable to serve a dry run, as nothing is written.".to_string())?;
+    }
+    if args.stdin.is_some() {
+        Err("Unable to serve stdin, as it can only be read once.".to_string())?;
+    }
+    // A template that is being edited can still be previewed.
+    args.recover = true;
+    let mut site = Site::new(args)?;
+    let root = site.options.output_dir
+        .get_or_insert_with(|| env::temp_dir().join("blogs-md-easy-serve"))
+        .to_owned();
+    site.render(site.markdowns.clone())?;
+
+    let server = tiny_http::Server::http(("127.0.0.1", port)).map_err(|e| format!("Unable to serve on port {}: {}", port, e))?;
+    println!("Serving '{}' at http://127.0.0.1:{}/", root.to_string_lossy(), port);
 
-            fs::write(output_path, html_doc)?;
+    // Each page polls the version, and reloads once it changes.
+    let version = Arc::new(AtomicUsize::new(0));
+    let latest = Arc::clone(&version);
+    thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let response = respond(&root, request.url(), latest.load(Ordering::Relaxed));
+            // The browser may have gone away, which is not worth stopping for.
+            let _ = request.respond(response);
         }
+    });
+
+    site.watch(|| {
+        version.fetch_add(1, Ordering::Relaxed);
+    })
+}
+
+/// Respond to a request for `url` with a file from `root`, injecting the live
+/// reload script into any HTML.
+fn respond(root: &Path, url: &str, version: usize) -> tiny_http::Response<io::Cursor<Vec<u8>>> {
+    let response = |body: Vec<u8>, content_type: &str| tiny_http::Response::from_data(body)
+        .with_header(tiny_http::Header::from_bytes("Content-Type", content_type).expect("a valid header"));
+
+    if url == LIVE_RELOAD_URL {
+        return response(version.to_string().into_bytes(), "text/plain; charset=utf-8");
+    }
+
+    let Some(path) = resolve_path(root, url) else {
+        return response(b"Not Found".to_vec(), "text/plain; charset=utf-8").with_status_code(404);
+    };
+
+    if path.is_dir() {
+        // Without an index, list the pages so that they can be found.
+        let mut pages = fs::read_dir(&path)
+            .map(|entries| entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect::<Vec<PathBuf>>())
+            .unwrap_or_default();
+        pages.sort();
+        let base = url.split(['?', '#']).next().unwrap_or_default().trim_end_matches('/');
+        let links = pages.iter()
+            .filter(|page| page.is_dir() || page.extension().unwrap_or_default() == "html")
+            .filter_map(|page| page.file_name())
+            .map(|name| format!("<li><a href=\"{0}/{1}\">{1}</a></li>", base, name.to_string_lossy()))
+            .collect::<String>();
+        let html = format!("<!DOCTYPE html>\n<html>\n<body>\n<ul>{}</ul>\n</body>\n</html>\n", links);
+        return response(inject_live_reload(&html, version).into_bytes(), "text/html; charset=utf-8");
+    }
+
+    match fs::read(&path) {
+        Ok(body) if path.extension().unwrap_or_default() == "html" => {
+            let html = inject_live_reload(&String::from_utf8_lossy(&body), version);
+            response(html.into_bytes(), "text/html; charset=utf-8")
+        },
+        Ok(body) => response(body, content_type(&path)),
+        Err(_) => response(b"Not Found".to_vec(), "text/plain; charset=utf-8").with_status_code(404),
+    }
+}
+
+/// The URL that the live reload script polls for the current version.
+const LIVE_RELOAD_URL: &str = "/__livereload";
+
+/// Find the file within `root` that `url` refers to, preferring the
+/// `index.html` of a directory.  \
+/// Returns `None` if it does not exist, or would be outside of `root`.
+fn resolve_path(root: &Path, url: &str) -> Option<PathBuf> {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    // Decode any percent-encoded characters, such as spaces.
+    let mut bytes = vec![];
+    let mut chars = path.bytes();
+    while let Some(byte) = chars.next() {
+        match byte {
+            b'%' => {
+                let hex = [chars.next()?, chars.next()?];
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            },
+            byte => bytes.push(byte),
+        }
+    }
+    let path = String::from_utf8(bytes).ok()?;
+
+    let mut resolved = root.to_owned();
+    for segment in path.split('/').filter(|segment| !segment.is_empty() && *segment != ".") {
+        if segment == ".." || segment.contains('\\') {
+            return None;
+        }
+        resolved.push(segment);
+    }
+
+    if resolved.join("index.html").is_file() {
+        resolved.push("index.html");
+    }
+
+    resolved.exists().then_some(resolved)
+}
+
+/// Add a script before the closing `</body>` of `html`, or at the end if there
+/// is none, that reloads the page once the version is no longer `version`.
+fn inject_live_reload(html: &str, version: usize) -> String {
+    let script = format!(
+        "<script>setInterval(() => fetch('{}').then(r => r.text()).then(v => v !== '{}' && location.reload()).catch(() => {{}}), 1000);</script>\n",
+        LIVE_RELOAD_URL,
+        version
+    );
+
+    match html.rfind("</body>") {
+        Some(index) => format!("{}{}{}", &html[..index], script, &html[index..]),
+        None => format!("{}{}", html, script),
+    }
+}
+
+/// The `Content-Type` of a file, from its extension.
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().unwrap_or_default().to_string_lossy().to_lowercase().as_str() {
+        "css" => "text/css; charset=utf-8",
+        "gif" => "image/gif",
+        "html" => "text/html; charset=utf-8",
+        "ico" => "image/x-icon",
+        "jpeg" | "jpg" => "image/jpeg",
+        "js" => "text/javascript; charset=utf-8",
+        "json" => "application/json",
+        "png" => "image/png",
+        "svg" => "image/svg+xml",
+        "txt" => "text/plain; charset=utf-8",
+        "webp" => "image/webp",
+        "woff2" => "font/woff2",
+        _ => "application/octet-stream",
+    }
+}
+
+fn main() {
+    if let Err(e) = run() {
+        print_error(e.as_ref());
+        std::process::exit(1);
+    }
+}
+
+/// Print an error, with the offending lines of its source if it has one.
+fn print_error(error: &(dyn Error + 'static)) {
+    if let Some(error) = error.downcast_ref::<BatchError>() {
+        for error in &error.errors {
+            print_error(error.as_ref());
+        }
+        eprintln!("Error: {} of the markdowns could not be rendered.", error.errors.len());
+        return;
+    }
+    match error.downcast_ref::<SourceError>() {
+        Some(error) => eprint!("{}", error.report(io::stderr().is_terminal())),
+        // Print errors with Display rather than Debug, so that the source
+        // snippets of parsing errors are readable.
+        None => eprintln!("Error: {}", error),
+    }
+}
+
+/// Move a bare `-`, for stdin, to the start of the arguments, so that it is
+/// not taken as another of the templates or Markdowns, whose options accept
+/// several values.
+///
+/// Only the options of rendering are rearranged, not those of a subcommand.
+fn stdin_first(mut args: Vec<OsString>) -> Vec<OsString> {
+    let is_option = args.get(1).is_some_and(|arg| arg.to_string_lossy().starts_with('-'));
+    let end = args.iter().position(|arg| arg == "--").unwrap_or(args.len());
+    if let Some(position) = args[..end].iter().skip(1).position(|arg| arg == "-").filter(|_| is_option) {
+        let stdin = args.remove(position + 1);
+        args.insert(1, stdin);
+    }
+    args
+}
+
+/// Parse the arguments, and run the program.
+fn run() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse_from(stdin_first(env::args_os().collect()));
+    let log = (cli.verbose > 0).then(|| Log::new(cli.verbose));
+
+    if let Some(command) = cli.command {
+        // The benchmark records its own timings.
+        if let (Some(log), false) = (log, matches!(command, Command::Bench { .. })) {
+            tracing::subscriber::set_global_default(Registry::default().with(log))?;
+        }
+        return match command {
+            Command::Build { config, dry_run } => build(config, dry_run),
+            Command::Bench { template, markdowns, iterations } => bench(template, markdowns, iterations),
+            Command::Changelog { markdowns, template, output_dir, variables, group_by, feed } => {
+                changelog(markdowns, template, output_dir, variables, group_by, feed)
+            },
+            Command::Diff { old, new } => diff_templates(old, new),
+            Command::Export { markdowns, template, output_dir, variables } => export(markdowns, template, output_dir, variables),
+            Command::Meta { command } => match command {
+                MetaCommand::Set { key, value, markdowns } => edit_markdowns(MetaEdit::Set { key, value }, markdowns),
+                MetaCommand::Rename { from, to, markdowns } => edit_markdowns(MetaEdit::Rename { from, to }, markdowns),
+                MetaCommand::Remove { key, markdowns } => edit_markdowns(MetaEdit::Remove { key }, markdowns),
+            },
+            Command::Lint { markdowns, templates, variables } => lint_project(markdowns, templates, variables),
+            Command::Clean { manifest, cache, output_dir, dry_run } => clean(&manifest, &cache, &output_dir, dry_run),
+            Command::Init { path } => init(path),
+            Command::New { path, kind, title, vars, archetypes_dir, config } => match path.extension().is_some_and(|extension| extension == "md") {
+                true => new_markdown(path, kind, title, vars, archetypes_dir),
+                false => new_draft(&path.to_string_lossy(), config),
+            },
+            Command::Sample { template, output } => sample(template, output),
+            Command::Serve { render, port } => serve(*render, port),
+            Command::Stats { markdowns, templates, json } => print_stats(markdowns, templates, json),
+            Command::Theme { command: ThemeCommand::Install { source, themes_dir } } => {
+                let theme = install_theme(&source, &themes_dir)?;
+                println!("Installed theme '{}' into '{}'.", theme.name, theme.path.to_string_lossy());
+                Ok(())
+            },
+        };
+    }
+
+    let timings = Timings::default();
+    if cli.timings || log.is_some() {
+        tracing::subscriber::set_global_default(Registry::default().with(cli.timings.then(|| timings.clone())).with(log))?;
+    }
+
+    if cli.render.stdin.is_some() {
+        return render_stdin(cli.render);
+    }
+
+    if cli.render.from_git {
+        return render_from_git(cli.render);
+    }
+
+    let mut site = Site::new(cli.render)?;
+    site.render(site.markdowns.clone())?;
+
+    if cli.timings {
+        timings.print();
+    }
+
+    if cli.watch {
+        site.watch(|| {})?;
     }
 
     Ok(())
@@ -173,6 +2077,8 @@ fn main() -> Result<(), Box<dyn Error>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use blogs_md_easy::{create_variables, parse_meta_section, parse_placeholder_locations, render_filters, replace_substring, Span};
+    use std::{collections::HashMap, fs};
 
     #[test]
     fn can_convert_html() {
@@ -182,9 +2088,9 @@ mod tests {
 
         let markdown = PathBuf::from("tests/one.md");
         let output = &markdown.with_file_name("one_output").with_extension("html");
-        let markdowns = get_markdowns(vec![markdown]);
+        let markdowns = get_markdowns(vec![markdown]).expect("to read markdowns");
 
-        let placeholders = get_placeholders(Span::new(&template)).expect("to parse placeholders");
+        let placeholders = parse_placeholder_locations(Span::new(&template)).expect("to parse placeholders");
 
         for (_markdown_url, markdown) in &markdowns {
             let markdown = Span::new(markdown);
@@ -194,11 +2100,8 @@ mod tests {
             let variables: HashMap<String, String> = create_variables(markdown, meta_values).expect("to create variables");
 
             for placeholder in &placeholders {
-                let mut variable = variables.get(&placeholder.name).expect("placeholder to be present in template.").to_owned();
-
-                for filter in &placeholder.filters {
-                    variable = render_filter(variable, filter);
-                }
+                let variable = variables.get(&placeholder.name).expect("placeholder to be present in template.");
+                let variable = render_filters(variable.as_str(), &placeholder.filters).expect("to render filters");
 
                 html_doc = replace_substring(&html_doc, placeholder.selection.start.offset, placeholder.selection.end.offset, &variable);
             }
@@ -222,4 +2125,127 @@ is a newline</p></footer>
 </body>
 "#);
     }
+
+    #[test]
+    fn can_inject_live_reload() {
+        let html = inject_live_reload("<html><body><p>Hello</p></body></html>", 3);
+        assert!(html.starts_with("<html><body><p>Hello</p><script>"));
+        assert!(html.ends_with("</script>\n</body></html>"));
+        assert!(html.contains("v !== '3'"));
+
+        assert!(inject_live_reload("<p>Hello</p>", 0).starts_with("<p>Hello</p><script>"));
+    }
+
+    #[test]
+    fn can_resolve_paths() {
+        let root = Path::new("tests");
+        assert_eq!(resolve_path(root, "/one.md"), Some(root.join("one.md")));
+        assert_eq!(resolve_path(root, "/fragments/bio.md?v=1"), Some(root.join("fragments").join("bio.md")));
+        assert_eq!(resolve_path(root, "/fragments/%62io.md"), Some(root.join("fragments").join("bio.md")));
+        assert_eq!(resolve_path(root, "/"), Some(root.to_owned()));
+        assert_eq!(resolve_path(root, "/../Cargo.toml"), None);
+        assert_eq!(resolve_path(root, "/missing.html"), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn can_run_commands() {
+        let dir = std::env::temp_dir().join("blogs-md-easy-run-command");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).expect("to create directory");
+
+        // Commands are run in the directory, with the env variables.
+        let env = [("BLOGS_MD_EASY_OUTPUT", dir.join("one.html"))];
+        run_command("echo \"$BLOGS_MD_EASY_OUTPUT\" > env.txt", &dir, &env).expect("to run command");
+        assert_eq!(fs::read_to_string(dir.join("env.txt")).expect("to read env"), format!("{}\n", dir.join("one.html").to_string_lossy()));
+
+        let error = run_command("exit 3", &dir, &[]).expect_err("to fail");
+        assert!(error.to_string().starts_with("The command 'exit 3' failed"), "{}", error);
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn can_run_build_hooks() {
+        let dir = std::env::temp_dir().join("blogs-md-easy-build-hooks");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).expect("to create directory");
+        fs::write(dir.join("template.html"), "<h1>{{ £title }}</h1>").expect("to write template");
+        fs::write(dir.join("one.md"), "# One").expect("to write markdown");
+        let config = |pre_build: &str| format!(
+            "templates = [\"template.html\"]\nmarkdowns = [\"one.md\"]\noutput_dir = \"dist\"\npre_build = [{}]\npost_render = [\"echo \\\"$BLOGS_MD_EASY_MARKDOWN $BLOGS_MD_EASY_OUTPUT\\\" >> rendered.txt\"]\npost_build = [\"ls dist > built.txt\"]\n",
+            pre_build,
+        );
+
+        // Each hook runs beside the config, in order, with the paths of each
+        // output.
+        fs::write(dir.join("blogs-md-easy.toml"), config("\"echo pre_build > pre_build.txt\"")).expect("to write config");
+        build(dir.join("blogs-md-easy.toml"), false).expect("to build");
+        assert_eq!(fs::read_to_string(dir.join("pre_build.txt")).expect("to read pre_build"), "pre_build\n");
+        let absolute = |path: &str| std::path::absolute(dir.join(path)).expect("an absolute path").to_string_lossy().to_string();
+        assert_eq!(fs::read_to_string(dir.join("rendered.txt")).expect("to read post_render"), format!("{} {}\n", absolute("one.md"), absolute("dist/one.html")));
+        assert_eq!(fs::read_to_string(dir.join("built.txt")).expect("to read post_build"), "one.html\n");
+
+        // A command that fails stops the build, before anything is rendered.
+        fs::remove_dir_all(dir.join("dist")).ok();
+        fs::remove_file(dir.join("rendered.txt")).ok();
+        fs::write(dir.join("blogs-md-easy.toml"), config("\"exit 1\"")).expect("to write config");
+        let error = build(dir.join("blogs-md-easy.toml"), false).expect_err("to fail to build");
+        assert!(error.to_string().starts_with("The command 'exit 1' failed"), "{}", error);
+        assert!(!dir.join("dist").exists());
+        assert!(!dir.join("rendered.txt").exists());
+
+        // A dry run only reports the commands.
+        fs::write(dir.join("blogs-md-easy.toml"), config("\"echo dry_run > dry_run.txt\"")).expect("to write config");
+        build(dir.join("blogs-md-easy.toml"), true).expect("to build");
+        assert!(!dir.join("dry_run.txt").exists());
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn can_find_markdowns() {
+        let dir = std::env::temp_dir().join("blogs-md-easy-find-markdowns");
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(dir.join("posts/2024")).expect("to create directory");
+        for file in ["posts/one.md", "posts/photo.png", "posts/2024/two.md", "posts/2024/notes.txt"] {
+            fs::write(dir.join(file), "# Hello").expect("to write file");
+        }
+
+        // A glob only matches the Markdowns, not the other files and directories.
+        let (mut markdowns, input_dirs) = find_markdowns(vec![dir.join("posts/**/*")]).expect("to find markdowns");
+        markdowns.sort();
+        assert_eq!(markdowns, vec![dir.join("posts/2024/two.md"), dir.join("posts/one.md")]);
+        assert_eq!(input_dirs, vec![dir.join("posts")]);
+
+        let (markdowns, _) = find_markdowns(vec![dir.join("posts")]).expect("to find markdowns");
+        assert_eq!(markdowns, vec![dir.join("posts/2024/two.md"), dir.join("posts/one.md")]);
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn can_create_markdown_from_archetype() {
+        let dir = std::env::temp_dir().join("blogs-md-easy-new-markdown");
+        fs::remove_dir_all(&dir).ok();
+
+        let vars = vec![parse_var("£url=https://example.com/?a=b").expect("to parse var")];
+        assert_eq!(vars, vec![("url".to_string(), "https://example.com/?a=b".to_string())]);
+        assert!(parse_var("url").is_err());
+        new_markdown(dir.join("a-good-link.md"), Some("link".to_string()), None, vars, PathBuf::from("tests/archetypes")).expect("to create markdown");
+        let markdown = fs::read_to_string(dir.join("a-good-link.md")).expect("to read markdown");
+        assert!(markdown.starts_with(":meta\nurl = https://example.com/?a=b\ndate = "), "{}", markdown);
+        assert!(markdown.ends_with("# A Good Link\n\n> Commentary.\n"), "{}", markdown);
+
+        // Without the `£url`, it is left empty to be filled in.
+        new_markdown(dir.join("another.md"), Some("link".to_string()), None, vec![], PathBuf::from("tests/archetypes")).expect("to create markdown");
+        assert!(fs::read_to_string(dir.join("another.md")).expect("to read markdown").starts_with(":meta\nurl = \n"));
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn can_suggest_variables() {
+        let candidates = vec!["autor".to_string(), "date".to_string()];
+        assert_eq!(did_you_mean("author", &candidates), Some("autor"));
+        assert_eq!(did_you_mean("dates", &candidates), Some("date"));
+        assert_eq!(did_you_mean("subtitle", &candidates), None);
+    }
 }
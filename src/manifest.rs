@@ -0,0 +1,246 @@
+//! What earlier batches have written, so that outputs which haven't changed are
+//! skipped, and those that were written can be cleaned.
+
+use std::{collections::{BTreeMap, BTreeSet}, error::Error, fs, path::{Path, PathBuf}, sync::{Arc, Mutex}};
+use crate::render::read_file;
+
+////////////////////////////////////////////////////////////////////////////////
+// Structs and types
+/// Remembers the hash of the template, Markdown, and options that produced each
+/// output of [`render_batch`](crate::batch::render_batch), so that a later
+/// batch can skip the outputs that would not change.
+///
+/// The cache can be saved to a file, and loaded again on the next run.
+///
+/// # Example
+/// ```rust
+/// use std::path::{Path, PathBuf};
+/// use blogs_md_easy::BuildCache;
+///
+/// let cache = BuildCache::new();
+/// cache.insert(PathBuf::from("tests/one.md"), "abc".to_string());
+/// assert!(cache.is_fresh(Path::new("tests/one.md"), "abc"));
+/// assert!(!cache.is_fresh(Path::new("tests/one.md"), "def"));
+///
+/// let path = std::env::temp_dir().join("blogs_md_easy_build_cache");
+/// cache.save(&path).unwrap();
+/// assert_eq!(BuildCache::load(&path).unwrap(), cache);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BuildCache {
+    hashes: Arc<Mutex<BTreeMap<PathBuf, String>>>,
+}
+
+impl BuildCache {
+    /// Create an empty `BuildCache`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a `BuildCache` that was saved with [`BuildCache::save`].  \
+    /// If the file does not exist, then the cache is empty.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let cache = Self::new();
+        if !path.exists() {
+            return Ok(cache);
+        }
+
+        for line in read_file(path)?.lines().filter(|line| !line.trim().is_empty()) {
+            let Some((hash, output)) = line.split_once("  ") else {
+                Err(format!("Unable to parse the build cache '{}'.", path.to_string_lossy()))?
+            };
+            cache.insert(PathBuf::from(output), hash.to_string());
+        }
+
+        Ok(cache)
+    }
+
+    /// Write every hash to a file, one output per line, in the same format as
+    /// `sha256sum`.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let contents = self.lock()
+            .iter()
+            .map(|(output, hash)| format!("{}  {}\n", hash, output.to_string_lossy()))
+            .collect::<String>();
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Whether the `output` exists, and was last rendered from the same
+    /// `hash`.
+    pub fn is_fresh(&self, output: &Path, hash: &str) -> bool {
+        self.lock().get(output).is_some_and(|cached| cached == hash) && output.exists()
+    }
+
+    /// Remember that the `output` was rendered from the `hash`.
+    pub fn insert(&self, output: PathBuf, hash: String) {
+        self.lock().insert(output, hash);
+    }
+
+    /// The number of outputs that are cached.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether there are no cached outputs.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Forget every output, so that the next batch renders them all again.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<PathBuf, String>> {
+        self.hashes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl PartialEq for BuildCache {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.hashes, &other.hashes) || *self.lock() == *other.lock()
+    }
+}
+
+/// Every file that [`render_batch`](crate::batch::render_batch) has written, so
+/// that they can be removed with [`OutputManifest::clean`], without touching
+/// any other file in the output directory.
+///
+/// The manifest can be saved to a file, and loaded again on the next run, so
+/// that it remembers the outputs of every run, including those that were
+/// skipped as they hadn't changed.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::OutputManifest;
+///
+/// let dir = std::env::temp_dir().join("blogs_md_easy_output_manifest");
+/// std::fs::create_dir_all(dir.join("posts")).unwrap();
+/// std::fs::write(dir.join("posts/hello.html"), "<h1>Hello</h1>").unwrap();
+/// std::fs::write(dir.join("robots.txt"), "").unwrap();
+///
+/// let manifest = OutputManifest::new();
+/// manifest.insert(dir.join("posts/hello.html"));
+/// manifest.save(&dir.join("manifest")).unwrap();
+///
+/// let manifest = OutputManifest::load(&dir.join("manifest")).unwrap();
+/// assert_eq!(manifest.clean(&dir).unwrap(), vec![dir.join("posts/hello.html")]);
+/// // The directory that was left empty is removed too, but not the root.
+/// assert!(!dir.join("posts").exists());
+/// assert!(dir.join("robots.txt").exists());
+/// # std::fs::remove_dir_all(&dir).ok();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct OutputManifest {
+    paths: Arc<Mutex<BTreeSet<PathBuf>>>,
+}
+
+impl OutputManifest {
+    /// The name of the manifest file, unless another is provided.
+    pub const FILE: &'static str = ".blogs-md-easy-manifest";
+
+    /// Create an empty `OutputManifest`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load an `OutputManifest` that was saved with [`OutputManifest::save`].  \
+    /// If the file does not exist, then the manifest is empty.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let manifest = Self::new();
+        if path.exists() {
+            for line in read_file(path)?.lines().filter(|line| !line.trim().is_empty()) {
+                manifest.insert(PathBuf::from(line));
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    /// Write every path to a file, one per line.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let contents = self.lock()
+            .iter()
+            .map(|output| format!("{}\n", output.to_string_lossy()))
+            .collect::<String>();
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Remember that the file at `path` was written.
+    pub fn insert(&self, path: PathBuf) {
+        self.lock().insert(path);
+    }
+
+    /// Every file that was written, in order.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.lock().iter().cloned().collect()
+    }
+
+    /// The number of files that were written.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no files were written.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// The files that [`OutputManifest::clean`] would remove, which are those
+    /// that still exist within `root`.
+    ///
+    /// A path that leads outside of `root`, such as through `..` or a symbolic
+    /// link, or is `root` itself, is never included.
+    pub fn removable(&self, root: &Path) -> Vec<PathBuf> {
+        let Ok(root) = fs::canonicalize(root) else {
+            return Vec::new();
+        };
+        self.paths()
+            .into_iter()
+            .filter(|path| fs::canonicalize(path).is_ok_and(|file| file.starts_with(&root) && file != root && file.is_file()))
+            .collect()
+    }
+
+    /// Remove every file that was written within `root`, see
+    /// [`OutputManifest::removable`], along with any directory within `root`
+    /// that is left empty, then forget them.
+    ///
+    /// `root` itself, and any file or directory outside of it, is never
+    /// removed. Relative paths are resolved from the current directory.
+    ///
+    /// Returns the files that were removed, as those that have since been
+    /// removed by something else are skipped.
+    pub fn clean(&self, root: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        let mut removed = Vec::new();
+        // Without `root`, there is nothing within it to remove.
+        if let Ok(root) = fs::canonicalize(root) {
+            for path in self.removable(&root) {
+                let file = fs::canonicalize(&path)?;
+                fs::remove_file(&file).map_err(|e| format!("Unable to remove '{}': {}.", path.to_string_lossy(), e))?;
+                // Only directories that are empty can be removed, so this
+                // stops at the first that contains anything else.
+                for directory in file.ancestors().skip(1).take_while(|directory| directory.starts_with(&root) && *directory != root) {
+                    if fs::remove_dir(directory).is_err() {
+                        break;
+                    }
+                }
+                removed.push(path);
+            }
+        }
+        self.lock().clear();
+
+        Ok(removed)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeSet<PathBuf>> {
+        self.paths.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl PartialEq for OutputManifest {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.paths, &other.paths) || *self.lock() == *other.lock()
+    }
+}
@@ -0,0 +1,730 @@
+//! The [`Meta`] key-value pairs declared at the start of a Markdown, and
+//! site-wide variables.
+
+use alloc::{format, string::{String, ToString}, vec::Vec};
+use core::{fmt, str::FromStr};
+#[cfg(feature = "std")]
+use nom::{bytes::complete::tag, character::complete::{multispace0, space0}, combinator::{all_consuming, opt, recognize}, sequence::tuple};
+#[cfg(feature = "std")]
+use std::{error::Error, path::Path};
+use crate::parser::{parse_meta_section, parse_title, Span};
+#[cfg(feature = "std")]
+use crate::{parser::{parse_meta_comment, parse_meta_key, parse_meta_key_value, parse_variable_name}, render::{read_file, replace_substring}};
+
+////////////////////////////////////////////////////////////////////////////////
+// Structs and types
+/// A simple struct to store the key value pair from within the meta section of
+/// a Markdown file.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_meta_line, Meta, Span};
+///
+/// let input = Span::new("foo = bar");
+/// let (_, meta) = parse_meta_line(input).unwrap();
+/// // Unwrap because key-values are Some() and comments are None.
+/// let meta = meta.unwrap();
+/// assert_eq!(meta, Meta::new("foo", "bar"));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Meta {
+    pub key: String,
+    pub value: String,
+}
+
+impl Meta {
+    /// Trims the `key` and `value` and stores them in the respective values in
+    /// this struct.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::Meta;
+    ///
+    /// let meta_with_space = Meta::new("  foo  ", "  bar  ");
+    /// let meta_without_space = Meta::new("foo", "bar");
+    /// assert_eq!(meta_with_space, meta_without_space);
+    /// ```
+    pub fn new(key: &str, value: &str) -> Self {
+        Self {
+            key: key.trim().to_string(),
+            value: value.trim().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Meta {
+    /// The line of a `:meta` section that declares this key and value, which
+    /// is [parsed](crate::parser::parse_meta_key_value) back into the same
+    /// `Meta`.
+    ///
+    /// A value that spans several lines, or would otherwise be mistaken for a
+    /// quoted value or a list, is wrapped in double quotes, unless it spans
+    /// several lines and contains a double quote, which is written as a
+    /// [heredoc](crate::parser::parse_meta_heredoc).
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::Meta;
+    ///
+    /// assert_eq!(Meta::new("author", "John Doe").to_string(), "author = John Doe");
+    /// assert_eq!(Meta::new("summary", "Two\nlines").to_string(), "summary = \"Two\nlines\"");
+    /// assert_eq!(Meta::new("title", "[Draft]").to_string(), "title = \"[Draft]\"");
+    /// assert_eq!(Meta::new("summary", "Say\n\"hello\"").to_string(), "summary = <<END\nSay\n\"hello\"\nEND");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = &self.value;
+        if value.contains('\n') && value.contains('"') {
+            // The delimiter mustn't be a line of the value.
+            let delimiter = (0..)
+                .map(|n| if n == 0 { "END".to_string() } else { format!("END_{}", n) })
+                .find(|delimiter| value.lines().all(|line| line.trim() != delimiter))
+                .unwrap_or_default();
+            return write!(f, "{} = <<{2}\n{}\n{2}", self.key, value, delimiter);
+        }
+        match value.contains('\n') || value.starts_with('"') || (value.starts_with('[') && value.ends_with(']')) {
+            true => write!(f, "{} = {}", self.key, quote_meta_value(value)),
+            false => write!(f, "{} = {}", self.key, value),
+        }
+    }
+}
+
+/// A [`Meta`] along with the comments that are written directly above it, see
+/// [`parse_meta_section_with_comments`](crate::parse_meta_section_with_comments).
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{AnnotatedMeta, Meta};
+///
+/// let annotated = AnnotatedMeta::from(Meta::new("author", "John Doe"));
+/// assert!(annotated.comments.is_empty());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnnotatedMeta {
+    pub meta: Meta,
+    /// Each line of the comments, in order, without their `#` or `//`.
+    pub comments: Vec<String>,
+}
+
+impl From<Meta> for AnnotatedMeta {
+    fn from(meta: Meta) -> Self {
+        Self { meta, comments: Vec::new() }
+    }
+}
+
+/// How the original `meta` section should be embedded within a rendered
+/// document, so that other tools can recover it from the static output.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EmbedMeta {
+    /// Embed the meta values as a single HTML comment.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use blogs_md_easy::{embed_meta, EmbedMeta, Meta};
+    ///
+    /// let meta = vec![Meta::new("author", "John Doe")];
+    /// let output = embed_meta("<body></body>", &meta, EmbedMeta::Comment);
+    ///
+    /// assert_eq!(output, "<!--\nauthor: John Doe\n-->\n<body></body>");
+    /// # }
+    /// ```
+    Comment,
+    /// Embed each meta value as a `<meta name>` tag.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use blogs_md_easy::{embed_meta, EmbedMeta, Meta};
+    ///
+    /// let meta = vec![Meta::new("author", "John Doe")];
+    /// let output = embed_meta("<head></head>", &meta, EmbedMeta::Tags);
+    ///
+    /// assert_eq!(output, "<head><meta name=\"author\" content=\"John Doe\">\n</head>");
+    /// # }
+    /// ```
+    Tags,
+}
+
+impl FromStr for EmbedMeta {
+    type Err = String;
+
+    /// Parse a string slice, into an `EmbedMeta`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use blogs_md_easy::EmbedMeta;
+    ///
+    /// assert_eq!("comment".parse::<EmbedMeta>(), Ok(EmbedMeta::Comment));
+    /// assert_eq!("comments".parse::<EmbedMeta>(), Ok(EmbedMeta::Comment));
+    /// assert_eq!("tags".parse::<EmbedMeta>(), Ok(EmbedMeta::Tags));
+    /// assert_eq!("meta".parse::<EmbedMeta>(), Ok(EmbedMeta::Tags));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "comment" | "comments" => Ok(Self::Comment),
+            "tag" | "tags" | "meta" => Ok(Self::Tags),
+            _ => Err(format!("Unable to parse EmbedMeta from '{}'", s)),
+        }
+    }
+}
+
+/// A change to the `meta` section of a Markdown, see [`edit_meta`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum MetaEdit {
+    /// Set the value of a key, which is added to the end of the section if it
+    /// isn't already declared.
+    Set { key: String, value: String },
+    /// Rename a key, keeping its value.
+    Rename { from: String, to: String },
+    /// Remove a key, along with its line.
+    Remove { key: String },
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Parsers
+/// Flatten a structured value into a Vector of [`Meta`].
+///
+/// Nested objects have their keys joined with a dot, so that `{"author":
+/// {"name": "John"}}` becomes `author.name = John`.
+#[cfg(feature = "json")]
+pub(crate) fn flatten_meta(prefix: &str, value: &serde_json::Value, meta_values: &mut Vec<Meta>) {
+    match value {
+        serde_json::Value::Object(object) => {
+            for (key, value) in object {
+                let key = if prefix.is_empty() { key.to_owned() } else { format!("{prefix}.{key}") };
+                flatten_meta(&key, value, meta_values);
+            }
+        },
+        serde_json::Value::Array(array) => {
+            let values = array.iter().map(|value| match value {
+                serde_json::Value::String(value) => value.to_owned(),
+                value => value.to_string(),
+            }).collect::<Vec<String>>();
+            meta_values.push(Meta::new(prefix, &join_meta_list(&values)));
+        },
+        serde_json::Value::String(value) => meta_values.push(Meta::new(prefix, value)),
+        serde_json::Value::Null => meta_values.push(Meta::new(prefix, "")),
+        value => meta_values.push(Meta::new(prefix, &value.to_string())),
+    }
+}
+
+/// Convert a TOML value into JSON, so that it can be passed to [`flatten_meta`].
+///
+/// Converting through TOML's own types keeps dates as they were written.
+#[cfg(feature = "toml")]
+pub(crate) fn toml_to_json(value: toml::Value) -> serde_json::Value {
+    match value {
+        toml::Value::String(value) => serde_json::Value::String(value),
+        toml::Value::Integer(value) => serde_json::Value::from(value),
+        toml::Value::Float(value) => serde_json::Value::from(value),
+        toml::Value::Boolean(value) => serde_json::Value::Bool(value),
+        toml::Value::Datetime(value) => serde_json::Value::String(value.to_string()),
+        toml::Value::Array(array) => serde_json::Value::Array(array.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => serde_json::Value::Object(table.into_iter().map(|(k, v)| (k, toml_to_json(v))).collect()),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Functions
+/// Wrap a meta value in double quotes, escaping any quotes within it that
+/// aren't already escaped.
+fn quote_meta_value(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    let mut escaped = false;
+    for c in value.chars() {
+        if c == '"' && !escaped {
+            quoted.push('\\');
+        }
+        escaped = c == '\\' && !escaped;
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Write a `:meta` section that declares each of the `meta_values` in order,
+/// which is [parsed](crate::parser::parse_meta_section) back into the same
+/// values.
+///
+/// A meta section must declare at least one value, so nothing is written
+/// without any.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{format_meta_section, Meta};
+///
+/// let meta_values = vec![Meta::new("title", "Hello"), Meta::new("tags", "rust, nom")];
+/// assert_eq!(format_meta_section(&meta_values), ":meta\ntitle = Hello\ntags = rust, nom\n:meta\n");
+/// assert_eq!(format_meta_section(&[]), "");
+/// ```
+pub fn format_meta_section(meta_values: &[Meta]) -> String {
+    if meta_values.is_empty() {
+        return String::new();
+    }
+
+    let mut section = String::from(":meta\n");
+    for meta in meta_values {
+        section.push_str(&meta.to_string());
+        section.push('\n');
+    }
+    section.push_str(":meta\n");
+    section
+}
+
+/// The meta values of a Markdown, without rendering anything, such as to list
+/// posts by their dates.
+///
+/// Only the meta section is parsed, so this is much cheaper than rendering the
+/// Markdown. A Markdown without a meta section, or with a malformed one, has no
+/// meta values; use [`parse_meta_section`] to find out why.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{extract_meta, Meta};
+///
+/// let markdown = ":meta\npublish_date = 2024-05-01\ntags = [rust, nom]\n:meta\n# Hello\n{{ £unrendered }}";
+/// assert_eq!(extract_meta(markdown), vec![Meta::new("publish_date", "2024-05-01"), Meta::new("tags", "rust, nom")]);
+/// assert_eq!(extract_meta("# Hello"), vec![]);
+/// ```
+pub fn extract_meta(markdown: &str) -> Vec<Meta> {
+    parse_meta_section(Span::new(markdown))
+        .map(|(_, meta_values)| meta_values)
+        .unwrap_or_default()
+}
+
+/// The title of a Markdown, without rendering anything, which is its meta
+/// `title`, or else its heading, as with the `title` variable.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::extract_title;
+///
+/// assert_eq!(extract_title(":meta\ntitle = Meta Title\n:meta\n# Heading").as_deref(), Some("Meta Title"));
+/// assert_eq!(extract_title(":meta\nauthor = John Doe\n:meta\n# Heading").as_deref(), Some("Heading"));
+/// assert_eq!(extract_title("No heading."), None);
+/// ```
+pub fn extract_title(markdown: &str) -> Option<String> {
+    let (content, meta_values) = parse_meta_section(Span::new(markdown)).unwrap_or((Span::new(markdown), Vec::new()));
+    meta_values
+        .into_iter()
+        .find(|meta| meta.key == "title")
+        .map(|meta| meta.value)
+        .or_else(|| parse_title(content).ok().map(|(_, title)| title.trim().to_string()))
+}
+
+/// Choose the `meta_values` of a `locale`, where a key that is suffixed with
+/// the locale, such as `title.fr`, replaces the key itself, and keys that are
+/// suffixed with any of the other `locales` are removed.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{localise_meta, Meta};
+///
+/// let locales = vec!["en".to_string(), "fr".to_string()];
+/// let meta_values = vec![Meta::new("title", "Hello"), Meta::new("title.fr", "Bonjour"), Meta::new("author.name", "John Doe")];
+/// assert_eq!(localise_meta(meta_values.clone(), "fr", &locales), vec![Meta::new("title", "Bonjour"), Meta::new("author.name", "John Doe")]);
+/// assert_eq!(localise_meta(meta_values, "en", &locales), vec![Meta::new("title", "Hello"), Meta::new("author.name", "John Doe")]);
+/// ```
+pub fn localise_meta(meta_values: Vec<Meta>, locale: &str, locales: &[String]) -> Vec<Meta> {
+    let mut localised: Vec<Meta> = Vec::with_capacity(meta_values.len());
+    // The keys that have been translated, which the key itself can't replace.
+    let mut translated = Vec::new();
+    for meta in meta_values {
+        match meta.key.rsplit_once('.').filter(|(_, suffix)| locales.iter().any(|l| l == suffix)) {
+            Some((key, suffix)) if suffix == locale => {
+                let key = key.to_string();
+                match localised.iter_mut().find(|m| m.key == key) {
+                    Some(existing) => existing.value = meta.value,
+                    None => localised.push(Meta::new(&key, &meta.value)),
+                }
+                translated.push(key);
+            },
+            // The value of another locale.
+            Some(_) => {},
+            None if translated.contains(&meta.key) => {},
+            None => localised.push(meta),
+        }
+    }
+    localised
+}
+
+/// Read a site-wide variables file, which provides default variables for every
+/// Markdown, see [`BatchOptions::variables`](crate::batch::BatchOptions::variables).
+///
+/// The format is chosen by the file's extension: `.toml`, `.json`, `.yaml` or
+/// `.yml`, each of which requires the cargo feature of the same name. Any other
+/// file is parsed as a `meta` section, see [`parse_meta_section`]. Nested keys
+/// are flattened using dot notation.
+///
+/// # Example
+/// ```rust
+/// # #[cfg(feature = "toml")] {
+/// use std::path::Path;
+/// use blogs_md_easy::{load_variables, Meta};
+///
+/// let variables = load_variables(Path::new("tests/variables.toml")).unwrap();
+/// assert_eq!(variables, vec![
+///     Meta::new("site_name", "Example Blog"),
+///     Meta::new("base_url", "https://example.com"),
+///     Meta::new("author.name", "John Doe"),
+/// ]);
+/// # }
+/// ```
+#[cfg(feature = "std")]
+pub fn load_variables(path: &Path) -> Result<Vec<Meta>, Box<dyn Error>> {
+    let contents = read_file(path)?;
+    let invalid = |e: String| format!("Unable to parse the variables in '{}': {}", path.to_string_lossy(), e);
+
+    #[cfg(feature = "json")]
+    {
+        let value = match path.extension().and_then(|extension| extension.to_str()).unwrap_or_default() {
+            #[cfg(feature = "toml")]
+            "toml" => Some(toml_to_json(toml::Value::Table(contents.parse::<toml::Table>().map_err(|e| invalid(e.message().to_string()))?))),
+            "json" => Some(serde_json::from_str::<serde_json::Value>(&contents).map_err(|e| invalid(e.to_string()))?),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => Some(serde_yaml::from_str::<serde_json::Value>(&contents).map_err(|e| invalid(e.to_string()))?),
+            _ => None,
+        };
+
+        if let Some(value) = value {
+            if !value.is_object() {
+                Err(invalid("expected a table of variables".to_string()))?;
+            }
+
+            let mut meta_values = Vec::new();
+            flatten_meta("", &value, &mut meta_values);
+            return Ok(meta_values);
+        }
+    }
+
+    let (_, meta_values) = parse_meta_section(Span::new(&contents))
+        .map_err(|e| invalid(match e {
+            nom::Err::Error(e) | nom::Err::Failure(e) => e.diagnostic().to_string(),
+            nom::Err::Incomplete(_) => "expected a meta section".to_string(),
+        }))?;
+    Ok(meta_values)
+}
+
+/// Read the environment variables with these `names`, such as `BUILD_SHA`,
+/// as variables named `env.BUILD_SHA`, so that a build can stamp details such
+/// as its version into the templates.
+///
+/// Only the named environment variables are read, and those that aren't set
+/// are left out.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::load_env_variables;
+///
+/// let variables = load_env_variables(&["PATH".to_string(), "BLOGS_MD_EASY_UNSET".to_string()]);
+/// assert_eq!(variables.len(), 1);
+/// assert_eq!(variables[0].key, "env.PATH");
+/// ```
+#[cfg(feature = "std")]
+pub fn load_env_variables(names: &[String]) -> Vec<Meta> {
+    names.iter()
+        .filter_map(|name| std::env::var(name).ok().map(|value| Meta::new(&format!("env.{}", name), &value)))
+        .collect()
+}
+
+/// Escape the characters that have a special meaning in HTML, so that a value
+/// can be safely placed within an element or attribute.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::escape_html;
+///
+/// assert_eq!(escape_html(r#"<a href="/">Tom & Jerry</a>"#), "&lt;a href=&quot;/&quot;&gt;Tom &amp; Jerry&lt;/a&gt;");
+/// ```
+pub fn escape_html(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Join the items of a list into a meta value, separated by a comma and a
+/// space, as lists are stored.
+///
+/// An item that contains a comma, or begins with a double quote, is wrapped in
+/// double quotes, so that [`split_meta_list`] finds it again.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::join_meta_list;
+///
+/// assert_eq!(join_meta_list(&["rust", "nom"]), "rust, nom");
+/// assert_eq!(join_meta_list(&["Doe, John", "Jane Doe"]), r#""Doe, John", Jane Doe"#);
+/// ```
+pub fn join_meta_list<S: AsRef<str>>(items: &[S]) -> String {
+    items
+        .iter()
+        .map(|item| match item.as_ref() {
+            item if item.contains(',') || item.starts_with('"') => quote_meta_value(item),
+            item => item.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Split a meta value that holds a list into its items, each of which is
+/// trimmed, skipping those that are empty.
+///
+/// Items wrapped in double quotes, as [`join_meta_list`] writes them, can
+/// contain a comma.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::split_meta_list;
+///
+/// assert_eq!(split_meta_list("rust, nom"), vec!["rust", "nom"]);
+/// assert_eq!(split_meta_list(r#""Doe, John", Jane Doe"#), vec!["Doe, John", "Jane Doe"]);
+/// ```
+pub fn split_meta_list(value: &str) -> Vec<String> {
+    split_quoted(value, ",")
+}
+
+/// Split `value` on each `separator` that isn't within double quotes, as
+/// [`split_meta_list`] does for a comma.
+pub(crate) fn split_quoted(value: &str, separator: &str) -> Vec<String> {
+    if separator.is_empty() {
+        return value.chars().filter(|c| !c.is_whitespace()).map(String::from).collect();
+    }
+
+    let mut items = Vec::new();
+    let mut rest = value.trim_start();
+    while !rest.is_empty() {
+        let mut item = String::new();
+        // The closing quote of a quoted item, otherwise the item is everything
+        // up to the next separator.
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let mut chars = quoted.char_indices();
+            rest = "";
+            while let Some((index, c)) = chars.next() {
+                match c {
+                    '\\' if quoted[index + 1..].starts_with('"') => {
+                        chars.next();
+                        item.push('"');
+                    },
+                    '"' => {
+                        rest = &quoted[index + 1..];
+                        break;
+                    },
+                    c => item.push(c),
+                }
+            }
+        }
+        let (unquoted, remaining) = rest.split_once(separator).unwrap_or((rest, ""));
+        item.push_str(unquoted.trim());
+        if !item.trim().is_empty() {
+            items.push(item);
+        }
+        rest = remaining.trim_start();
+    }
+
+    items
+}
+
+/// Embed the `meta` values into an HTML document, using the given
+/// [`EmbedMeta`] style.
+///
+/// If the document has a `</head>` tag, then the meta will be placed at the end
+/// of the head, otherwise it will be placed at the start of the document.  \
+/// When there are no `meta` values, the document is returned unchanged.
+///
+/// For examples, see [`EmbedMeta`].
+#[cfg(feature = "std")]
+pub fn embed_meta(html_doc: &str, meta_values: &[Meta], style: EmbedMeta) -> String {
+    if meta_values.is_empty() {
+        return html_doc.to_string();
+    }
+
+    let embedded = match style {
+        EmbedMeta::Comment => format!(
+            "<!--\n{}\n-->\n",
+            meta_values
+                .iter()
+                // A double hyphen would end the comment early.
+                .map(|meta| format!("{}: {}", meta.key, meta.value.replace("--", "- -")))
+                .collect::<Vec<String>>()
+                .join("\n")
+        ),
+        EmbedMeta::Tags => meta_values
+            .iter()
+            .map(|meta| format!("<meta name=\"{}\" content=\"{}\">\n", escape_html(&meta.key), escape_html(&meta.value)))
+            .collect::<String>(),
+    };
+
+    match html_doc.find("</head>") {
+        Some(offset) => replace_substring(html_doc, offset, offset, &embedded),
+        None => embedded + html_doc,
+    }
+}
+
+/// Edit the `meta` section of a Markdown in place, leaving its comments, the
+/// formatting of each line, and the rest of the Markdown as they are.
+///
+/// Only `:meta`, `<meta>`, and `<?meta` sections can be edited. Setting a key
+/// of a Markdown without a meta section adds a `:meta` section to the start of
+/// it, and removing the last key removes the section.
+///
+/// # Examples
+/// ```rust
+/// use blogs_md_easy::{edit_meta, MetaEdit};
+///
+/// let markdown = ":meta\n# The author.\n£author = John Doe\ndraft = true\n:meta\n# Hello";
+///
+/// let edit = MetaEdit::Set { key: "author".to_string(), value: "Jane Doe".to_string() };
+/// assert_eq!(edit_meta(markdown, &edit).unwrap(), ":meta\n# The author.\n£author = Jane Doe\ndraft = true\n:meta\n# Hello");
+///
+/// let edit = MetaEdit::Set { key: "tags".to_string(), value: "[rust, nom]".to_string() };
+/// assert_eq!(edit_meta(markdown, &edit).unwrap(), ":meta\n# The author.\n£author = John Doe\ndraft = true\ntags = [rust, nom]\n:meta\n# Hello");
+///
+/// let edit = MetaEdit::Rename { from: "author".to_string(), to: "writer".to_string() };
+/// assert_eq!(edit_meta(markdown, &edit).unwrap(), ":meta\n# The author.\n£writer = John Doe\ndraft = true\n:meta\n# Hello");
+///
+/// let edit = MetaEdit::Remove { key: "draft".to_string() };
+/// assert_eq!(edit_meta(markdown, &edit).unwrap(), ":meta\n# The author.\n£author = John Doe\n:meta\n# Hello");
+/// ```
+///
+/// A Markdown without a meta section is given one.
+/// ```rust
+/// use blogs_md_easy::{edit_meta, MetaEdit};
+///
+/// let edit = MetaEdit::Set { key: "draft".to_string(), value: "true".to_string() };
+/// assert_eq!(edit_meta("# Hello", &edit).unwrap(), ":meta\ndraft = true\n:meta\n# Hello");
+/// ```
+#[cfg(feature = "std")]
+pub fn edit_meta(markdown: &str, edit: &MetaEdit) -> Result<String, Box<dyn Error>> {
+    let keys = match edit {
+        MetaEdit::Set { key, .. } | MetaEdit::Remove { key } => vec![key],
+        MetaEdit::Rename { from, to } => vec![from, to],
+    };
+    for key in keys {
+        if all_consuming(parse_variable_name)(Span::new(key)).is_err() {
+            Err(format!("The key '{}' is not a valid variable name.", key))?;
+        }
+    }
+    // A value that spans several lines must be quoted.
+    let format_value = |value: &str| match value.contains('\n') {
+        true => quote_meta_value(value),
+        false => value.to_string(),
+    };
+    let newline = if markdown.contains("\r\n") { "\r\n" } else { "\n" };
+
+    let start = markdown.len() - markdown.trim_start().len();
+    let section = &markdown[start..];
+    if ["---", "+++", "<?meta-json", "```json"].iter().any(|open| section.starts_with(open)) {
+        Err("Only `:meta`, `<meta>`, and `<?meta` sections can be edited.".to_string())?;
+    }
+    let Some((open, close)) = [(":meta", ":meta"), ("<meta>", "</meta>"), ("<?", "?>")]
+        .into_iter()
+        .find(|(open, _)| section.starts_with(open))
+    else {
+        return Ok(match edit {
+            MetaEdit::Set { key, value } => format!(":meta{0}{1} = {2}{0}:meta{0}{3}", newline, key, format_value(value), markdown),
+            _ => markdown.to_string(),
+        });
+    };
+    // Anything after an invalid line would be mistaken for the Markdown.
+    parse_meta_section(Span::new(markdown)).map_err(|e| match e {
+        nom::Err::Error(e) | nom::Err::Failure(e) => e.diagnostic().to_string(),
+        nom::Err::Incomplete(_) => "expected a meta section".to_string(),
+    })?;
+
+    // The key of each line, the start and end of its line, and the offsets of
+    // its key and value.
+    let mut lines = Vec::new();
+    let mut comments = 0;
+    let (mut input, _) = tuple((multispace0::<Span, nom::error::Error<Span>>, tag(open), opt(tag("meta"))))(Span::new(markdown))
+        .map_err(|_| "expected a meta section".to_string())?;
+    let section_end = loop {
+        let (remaining, _) = multispace0::<Span, nom::error::Error<Span>>(input).map_err(|_| "expected a meta line".to_string())?;
+        let line_start = remaining.location_offset();
+        if remaining.starts_with(close) {
+            break line_start + close.len();
+        }
+        if let Ok((remaining, _)) = parse_meta_comment(remaining) {
+            comments += 1;
+            input = remaining;
+            continue;
+        }
+
+        let (remaining, _) = space0::<Span, nom::error::Error<Span>>(remaining).map_err(|_| "expected a meta line".to_string())?;
+        let (after_key, key) = parse_meta_key(remaining).map_err(|_| "expected a meta key".to_string())?;
+        let (value, _) = recognize(tuple((space0::<Span, nom::error::Error<Span>>, tag("="), space0)))(after_key)
+            .map_err(|_| "expected `=`".to_string())?;
+        let (after_value, _) = parse_meta_key_value(remaining).map_err(|_| "expected a meta value".to_string())?;
+        // Values that aren't quoted continue to the end of the line, which
+        // includes the newline.
+        let value_end = markdown[..after_value.location_offset()].trim_end().len();
+        lines.push((key.fragment().to_string(), line_start, key.location_offset(), key.location_offset() + key.len(), value.location_offset(), value_end));
+        input = after_value;
+    };
+
+    // Lines are replaced from the end, so that the offsets of the rest remain
+    // correct.
+    let mut replacements = Vec::new();
+    match edit {
+        MetaEdit::Set { key, value } => {
+            let value = format_value(value);
+            for (_, _, _, _, value_start, value_end) in lines.iter().filter(|line| &line.0 == key) {
+                replacements.push((*value_start, *value_end, value.to_owned()));
+            }
+            match lines.last() {
+                _ if !replacements.is_empty() => {},
+                // Add the key after the last, with the same indentation.
+                Some((_, line_start, _, _, _, value_end)) => {
+                    let line_start = markdown[..*line_start].rfind('\n').map_or(0, |offset| offset + 1);
+                    let indent = &markdown[line_start..line_start + markdown[line_start..].find(|c: char| !c.is_whitespace()).unwrap_or(0)];
+                    replacements.push((*value_end, *value_end, format!("{}{}{} = {}", newline, indent, key, value)));
+                },
+                // There are only comments, so add the key before the closing
+                // tag's line.
+                None => {
+                    let line_start = markdown[..section_end - close.len()].rfind('\n').map_or(0, |offset| offset + 1);
+                    replacements.push((line_start, line_start, format!("{} = {}{}", key, value, newline)));
+                },
+            }
+        },
+        MetaEdit::Rename { from, to } => {
+            if from != to && lines.iter().any(|line| &line.0 == to) {
+                Err(format!("The key '{}' is already declared.", to))?;
+            }
+            for (_, _, key_start, key_end, _, _) in lines.iter().filter(|line| &line.0 == from) {
+                replacements.push((*key_start, *key_end, to.to_owned()));
+            }
+        },
+        MetaEdit::Remove { key } => {
+            let removed = lines.iter().filter(|line| &line.0 == key).count();
+            if removed > 0 && removed == lines.len() && comments == 0 {
+                // A meta section must declare at least one line.
+                let end = markdown[section_end..].strip_prefix(newline).map_or(section_end, |_| section_end + newline.len());
+                replacements.push((start, end, String::new()));
+            } else {
+                for (_, line_start, _, _, _, value_end) in lines.iter().filter(|line| &line.0 == key) {
+                    // Remove the whole line, unless it shares it with the
+                    // opening tag.
+                    let start = markdown[..*line_start].rfind('\n').map_or(0, |offset| offset + 1);
+                    let start = match markdown[start..*line_start].trim().is_empty() {
+                        true => start,
+                        false => *line_start,
+                    };
+                    let end = markdown[*value_end..].strip_prefix(newline).map_or(*value_end, |_| value_end + newline.len());
+                    replacements.push((start, end, String::new()));
+                }
+            }
+        },
+    }
+
+    let mut markdown = markdown.to_string();
+    for (start, end, replacement) in replacements.into_iter().rev() {
+        markdown.replace_range(start..end, &replacement);
+    }
+    Ok(markdown)
+}
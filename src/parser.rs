@@ -0,0 +1,2523 @@
+//! The nom parsers for templates, Markdown titles and meta sections.
+//!
+//! Most of these are building blocks for [`parse_placeholder_locations`] and
+//! [`parse_meta_section`], and are not part of the stable API; their signatures
+//! may change between minor versions.
+
+use alloc::{borrow::ToOwned, collections::BTreeMap, format, string::{String, ToString}, vec, vec::Vec};
+use core::{cell::RefCell, fmt, str::FromStr};
+use nom::{branch::alt, bytes::complete::{escaped, is_not, tag, take_till, take_until, take_while, take_while1, take_while_m_n}, character::complete::{alphanumeric1, line_ending, multispace0, multispace1, one_of, space0}, combinator::{all_consuming, cut, eof, not, opt, peek, recognize, verify}, multi::{many0, many1, separated_list0, separated_list1}, sequence::{delimited, preceded, separated_pair, terminated, tuple}, IResult, InputTake, Parser, Slice};
+use nom_locate::LocatedSpan;
+use crate::{error::{Diagnostic, Error}, filters::{Filter, HashAlgorithm, TextCase}, meta::{join_meta_list, AnnotatedMeta, Meta}};
+#[cfg(feature = "json")]
+use crate::meta::flatten_meta;
+#[cfg(feature = "toml")]
+use crate::meta::toml_to_json;
+
+////////////////////////////////////////////////////////////////////////////////
+// Structs and types
+/// A [`LocatedSpan`] of a string slice, with lifetime `'a`.
+pub type Span<'a> = LocatedSpan<&'a str>;
+
+/// A position for a Cursor within a [`Span`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Marker {
+    pub line: u32,
+    pub offset: usize,
+}
+
+impl Marker {
+    /// Extracts the `location_line()` and `location_offset()` from the [`Span`].
+    pub fn new(span: Span) -> Self {
+        Self {
+            line: span.location_line(),
+            offset: span.location_offset(),
+        }
+    }
+}
+
+impl Default for Marker {
+    /// Create a `Marker` with a `line` of `1` and `offset` of `1`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::Marker;
+    ///
+    /// let marker_default = Marker::default();
+    /// let marker_new = Marker { line: 1, offset: 1 };
+    /// assert_eq!(marker_default, marker_new);
+    /// ```
+    fn default() -> Self {
+        Self {
+            line: 1,
+            offset: 1,
+        }
+    }
+}
+
+/// A helper struct that contains a start and end [`Marker`] of a [`Span`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Selection {
+    pub start: Marker,
+    pub end: Marker,
+}
+
+impl Selection {
+    /// Generate a new selection from two [`Span`]s.
+    ///
+    /// The `start` argument will simply extract the `location_line` and
+    /// `location_offset` from the [`Span`].
+    /// The `end` argument will use the `location_line`, but will set the offset
+    /// to the `location_offset` added to the `fragment` length to ensure we
+    /// consume the entire match.
+    pub fn from(start: Span, end: Span) -> Self {
+        Self {
+            start: Marker::new(start),
+            // We cannot use `new` because we need to account for the string
+            // fragment length.
+            end: Marker {
+                line: end.location_line(),
+                offset: end.location_offset() + end.fragment().len()
+            }
+        }
+    }
+}
+
+/// A `Placeholder` is a variable that is created within a Template file.
+///
+/// The syntax for a `Placeholder` is as below.
+///
+/// `{{ £variable_name[| filter_name[= [key: ]value]...] }}`
+///
+/// A compulsory `variable_name`, preceded by a `£`.  \
+/// Then an optional pipe (`|`) separated list of [`Filter`]s.  \
+/// Some filters are just a name, although some have additional arguments.
+///
+/// For more explanation on what a `Placeholder` looks like inside a template,
+/// see [`parse_placeholder`].
+///
+/// For more explanation on what a [`Filter`] looks like inside a `Placeholder`,
+/// see [`parse_filter`].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Placeholder {
+    pub name: String,
+    pub selection: Selection,
+    pub filters: Vec<Filter>,
+}
+
+impl Placeholder {
+    /// Whether the placeholder has a [`Filter::Default`], such as
+    /// `{{ £author ?? "Anonymous" }}`, so that its variable can be missing.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{parse_placeholder, Span};
+    ///
+    /// let (_, placeholder) = parse_placeholder(Span::new("{{ £author ?? \"Anonymous\" }}")).unwrap();
+    /// assert!(placeholder.has_default());
+    ///
+    /// let (_, placeholder) = parse_placeholder(Span::new("{{ £author }}")).unwrap();
+    /// assert!(!placeholder.has_default());
+    /// ```
+    pub fn has_default(&self) -> bool {
+        self.filters.iter().any(|filter| matches!(filter, Filter::Default { .. }))
+    }
+
+    /// The names of the variables used as [`Filter`] arguments, such as
+    /// `excerpt_length` in `{{ £content | truncate = characters: £excerpt_length }}`.
+    ///
+    /// The `source` is the template that the placeholder was parsed from.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{parse_placeholder, Span};
+    ///
+    /// let source = "{{ £summary | truncate = characters: £excerpt_length, trail: £ellipsis }}";
+    /// let (_, placeholder) = parse_placeholder(Span::new(source)).unwrap();
+    /// assert_eq!(placeholder.argument_variables(source), vec!["excerpt_length", "ellipsis"]);
+    /// ```
+    pub fn argument_variables(&self, source: &str) -> Vec<String> {
+        // Most placeholders have none, so this is checked before parsing.
+        if !self.has_argument_variables(source) {
+            return Vec::new();
+        }
+
+        let names = RefCell::new(Vec::new());
+        self.with_arguments(source, &|name| {
+            names.borrow_mut().push(name.to_string());
+            None
+        });
+        names.into_inner()
+    }
+
+    /// Resolve the [`Filter`] arguments that are variables, such as
+    /// `£excerpt_length`, with `value_of`.
+    ///
+    /// The `source` is the template that the placeholder was parsed from. An
+    /// argument whose variable has no value is left as it is, so the filter
+    /// uses its default for that argument.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{parse_placeholder, Filter, Span};
+    ///
+    /// let source = "{{ £summary | truncate = characters: £excerpt_length }}";
+    /// let (_, placeholder) = parse_placeholder(Span::new(source)).unwrap();
+    /// assert_eq!(placeholder.filters[0], Filter::Truncate { characters: 100, trail: "...".to_string() });
+    ///
+    /// let placeholder = placeholder.with_arguments(source, &|name| {
+    ///     (name == "excerpt_length").then(|| "20".to_string())
+    /// });
+    /// assert_eq!(placeholder.filters[0], Filter::Truncate { characters: 20, trail: "...".to_string() });
+    /// ```
+    pub fn with_arguments(&self, source: &str, value_of: &dyn Fn(&str) -> Option<String>) -> Self {
+        if !self.has_argument_variables(source) {
+            return self.clone();
+        }
+
+        let text = source.get(self.selection.start.offset..self.selection.end.offset).unwrap_or_default();
+        match parse_placeholder_with(Span::new(text), value_of) {
+            Ok((_, placeholder)) => Placeholder {
+                selection: self.selection,
+                ..placeholder
+            },
+            Err(_) => self.clone(),
+        }
+    }
+
+    /// Whether any [`Filter`] argument could be a variable, without parsing
+    /// the placeholder again.
+    fn has_argument_variables(&self, source: &str) -> bool {
+        let text = source.get(self.selection.start.offset..self.selection.end.offset).unwrap_or_default();
+        // The variable name is always the first `£`, so arguments need another.
+        text.matches(['£', '$']).nth(1).is_some()
+    }
+}
+
+impl fmt::Display for Placeholder {
+    /// The canonical form of the placeholder, with a single space inside its
+    /// braces and around each `|`, and each [`Filter`] in its canonical form.
+    ///
+    /// The `markdown` filter that `£content` is always given is left out,
+    /// unless it has any arguments.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{parse_placeholder, Span};
+    ///
+    /// let (_, placeholder) = parse_placeholder(Span::new("{{£title|text = case: upper|truncate=20}}")).unwrap();
+    /// assert_eq!(placeholder.to_string(), "{{ £title | uppercase | truncate = characters: 20 }}");
+    ///
+    /// let (_, placeholder) = parse_placeholder(Span::new("{{ £content }}")).unwrap();
+    /// assert_eq!(placeholder.to_string(), "{{ £content }}");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let implicit = Filter::Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 };
+        write!(f, "{{{{ £{}", self.name)?;
+        for (index, filter) in self.filters.iter().enumerate() {
+            if self.name.eq_ignore_ascii_case("content") && filter == &implicit && index == self.filters.len() - 1 {
+                continue;
+            }
+            write!(f, " | {}", filter)?;
+        }
+        f.write_str(" }}")
+    }
+}
+
+/// A tag that controls which parts of a template are included, depending on
+/// the features that were enabled for the build.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FeatureTag {
+    /// `{{ if feature "name" }}`, the start of a conditional block.
+    If(String),
+    /// `{{ else }}`, the content to use when the feature is disabled.
+    Else,
+    /// `{{ endif }}`, the end of a conditional block.
+    EndIf,
+}
+
+/// A tag that repeats part of a template for every item of a collection.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoopTag {
+    /// `{{ for £item in £collection }}`, the start of a repeated block.
+    For {
+        /// The variable each item is available as, within the block.
+        item: String,
+        /// The collection being looped over.
+        collection: String,
+    },
+    /// `{{ endfor }}`, the end of a repeated block.
+    EndFor,
+}
+
+/// The kind of media that an [`EmbedTag`] embeds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EmbedKind {
+    /// `{{ video "clip.mp4" }}`, a local video file.
+    Video,
+    /// `{{ audio "episode.mp3" }}`, a local audio file.
+    Audio,
+    /// `{{ youtube "id" }}`, a YouTube video, by its id or URL.
+    YouTube,
+    /// `{{ vimeo "id" }}`, a Vimeo video, by its id or URL.
+    Vimeo,
+}
+
+/// A tag within a Markdown that embeds a video or audio player.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmbedTag {
+    pub kind: EmbedKind,
+    /// The path of the file, or the id or URL of the video.
+    pub source: String,
+    /// An optional title, such as `{{ video "clip.mp4" "A title" }}`.
+    pub title: Option<String>,
+}
+
+/// A `{{ include "path" }}` tag, which is replaced with the template at
+/// `path`, such as a card that is used for both posts and projects.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IncludeTag {
+    /// The path of the template, relative to the template that includes it.
+    pub path: String,
+    /// Each variable of the included template, and the variable that it is
+    /// bound to, such as `title: £post.title`, in the order that they are
+    /// written.
+    pub bindings: Vec<(String, String)>,
+}
+
+/// A tag within a Markdown that is expanded from a small template of its own,
+/// such as `{{ figure src: cat.jpg, caption: My cat }}`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShortcodeTag {
+    /// The name of the shortcode, which is the name of its template.
+    pub name: String,
+    /// Each argument, which is a variable of the template, in the order that
+    /// they are written.
+    pub arguments: Vec<Meta>,
+}
+
+/// The sigil of variables, and the delimiters of placeholders and tags, that a
+/// template or Markdown is written with, such as `[[ $title ]]`.
+///
+/// The parsers only understand the default `£` and `{{ }}`, so anything
+/// written with another syntax is [translated](Syntax::translate) before it is
+/// parsed, and its errors are [located](Syntax::locate) within the text as it
+/// was written. A `$` is always a sigil.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Syntax {
+    sigil: char,
+    open: String,
+    close: String,
+}
+
+impl Default for Syntax {
+    fn default() -> Self {
+        Self { sigil: '£', open: "{{".to_string(), close: "}}".to_string() }
+    }
+}
+
+impl Syntax {
+    /// Create a syntax, returning an error if the `sigil` could be part of a
+    /// variable's name, or either delimiter is empty, or they are the same.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::Syntax;
+    ///
+    /// assert!(Syntax::new('@', "[[", "]]").is_ok());
+    /// assert!(Syntax::new('a', "[[", "]]").is_err());
+    /// assert!(Syntax::new('@', "%%", "%%").is_err());
+    /// ```
+    pub fn new(sigil: char, open: &str, close: &str) -> Result<Self, String> {
+        if sigil.is_alphanumeric() || sigil.is_whitespace() || matches!(sigil, '-' | '_' | '.' | '"') {
+            return Err(format!("The sigil '{}' could be part of a variable's name.", sigil));
+        }
+        if open.trim().is_empty() || close.trim().is_empty() || open == close {
+            return Err(format!("The delimiters '{}' and '{}' must be different, and not empty.", open, close));
+        }
+
+        Ok(Self { sigil, open: open.to_string(), close: close.to_string() })
+    }
+
+    /// Whether this is the default `£` and `{{ }}`, which needs no translating.
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Translate `text` that was written with this syntax into the default
+    /// syntax.
+    ///
+    /// Each tag, such as `[[ @title | uppercase ]]` or `[[ if feature "x" ]]`,
+    /// is given the default delimiters, and the sigils of its variables are
+    /// replaced with a `£`. Any `{{` of the text is escaped, as it isn't a tag,
+    /// and an escaped `\[[`, or a `[[ raw ]]` block, is left as it is.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::Syntax;
+    ///
+    /// let syntax = Syntax::new('@', "[[", "]]").unwrap();
+    /// let template = "<h1>[[ @title | replace = find: \"@\", replacement: \"at\" ]]</h1><code>{{ £title }} \\[[ @title ]]</code>";
+    /// assert_eq!(syntax.translate(template), "<h1>{{ £title | replace = find: \"@\", replacement: \"at\" }}</h1><code>\\{{ £title }} [[ @title ]]</code>");
+    ///
+    /// let template = "[[ raw ]][[ @title ]][[ endraw ]]";
+    /// assert_eq!(syntax.translate(template), "{{ raw }}[[ @title ]]{{ endraw }}");
+    /// ```
+    pub fn translate(&self, text: &str) -> String {
+        self.translate_mapped(text).0
+    }
+
+    /// Translate `text`, as [`translate`](Self::translate) does, along with
+    /// where each piece of the translation came from, as the offsets of its
+    /// start within the translation and within `text`, and whether it was
+    /// copied as it was written.
+    fn translate_mapped(&self, text: &str) -> (String, Vec<(usize, usize, bool)>) {
+        let mut output = String::with_capacity(text.len());
+        let mut pieces = vec![];
+        if self.is_default() {
+            output.push_str(text);
+            pieces.push((0, 0, true));
+            return (output, pieces);
+        }
+
+        // Push a `piece` of the translation, which came from the `source`
+        // offset of `text`.
+        let push = |output: &mut String, pieces: &mut Vec<(usize, usize, bool)>, source: usize, piece: &str, copied: bool| {
+            if !(copied && pieces.last().is_some_and(|(_, _, last)| *last)) {
+                pieces.push((output.len(), source, copied));
+            }
+            output.push_str(piece);
+        };
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            let source = text.len() - rest.len();
+            if let Some(after) = rest.strip_prefix('\\').and_then(|after| after.strip_prefix(self.open.as_str())) {
+                push(&mut output, &mut pieces, source, &self.open.replace("{{", "\\{{"), false);
+                rest = after;
+            } else if let Some((body, after)) = self.tag(rest) {
+                push(&mut output, &mut pieces, source, "{{", false);
+                // The body is copied, other than each sigil that is replaced.
+                let mut copied = 0;
+                for sigil in self.sigils(body) {
+                    push(&mut output, &mut pieces, source + self.open.len() + copied, &body[copied..sigil], true);
+                    push(&mut output, &mut pieces, source + self.open.len() + sigil, "£", false);
+                    copied = sigil + self.sigil.len_utf8();
+                }
+                push(&mut output, &mut pieces, source + self.open.len() + copied, &body[copied..], true);
+                push(&mut output, &mut pieces, source + self.open.len() + body.len(), "}}", false);
+                rest = after;
+
+                // The contents of a raw block are left as they were written.
+                if body.trim() == "raw" {
+                    let mut contents = rest;
+                    while let Some(offset) = contents.find(self.open.as_str()) {
+                        if let Some((body, after)) = self.tag(&contents[offset..]).filter(|(body, _)| body.trim() == "endraw") {
+                            let source = text.len() - rest.len();
+                            let copied = &rest[..rest.len() - contents.len() + offset];
+                            push(&mut output, &mut pieces, source, copied, true);
+                            push(&mut output, &mut pieces, source + copied.len(), &format!("{{{{{}}}}}", body), false);
+                            rest = after;
+                            break;
+                        }
+                        contents = &contents[offset + self.open.len()..];
+                    }
+                }
+            } else if let Some(after) = rest.strip_prefix("{{") {
+                push(&mut output, &mut pieces, source, "\\{{", false);
+                rest = after;
+            } else {
+                push(&mut output, &mut pieces, source, &rest[..c.len_utf8()], true);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+
+        (output, pieces)
+    }
+
+    /// Point an `error` within the [translation](Self::translate) of `text`
+    /// at `text` itself, as it was written, so that its line, column, and
+    /// snippet are those of the source, and its message uses this syntax.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{parse_placeholder_locations, Span, Syntax};
+    ///
+    /// let syntax = Syntax::new('@', "[[", "]]").unwrap();
+    /// let text = "<p>{{ x }}</p>\n<h1>[[ @title | shout ]]</h1>";
+    /// let error = parse_placeholder_locations(Span::new(&syntax.translate(text))).unwrap_err();
+    ///
+    /// let error = syntax.locate(text, error);
+    /// assert_eq!((error.diagnostic().line, error.diagnostic().column), (2, 17));
+    /// assert_eq!(error.diagnostic().snippet, "2 | <h1>[[ @title | shout ]]</h1>\n  |                 ^");
+    /// ```
+    pub fn locate(&self, text: &str, error: Error) -> Error {
+        let (_, pieces) = self.translate_mapped(text);
+        // The offset within `text` of an offset within the translation, which
+        // is the start of a piece that was replaced.
+        let original = |offset: usize| {
+            let index = pieces.partition_point(|(start, _, _)| *start <= offset).saturating_sub(1);
+            match pieces.get(index) {
+                Some((start, source, true)) => (source + offset - start).min(text.len()),
+                Some((_, source, false)) => *source,
+                None => 0,
+            }
+        };
+        let locate = |diagnostic: &Diagnostic| {
+            let offset = original(diagnostic.offset);
+            let length = match diagnostic.length {
+                0 => 0,
+                length => original(diagnostic.offset + length).saturating_sub(offset),
+            };
+            let message = diagnostic.message
+                .replace("`{{`", &format!("`{}`", self.open))
+                .replace("`}}`", &format!("`{}`", self.close))
+                .replace("'£", &format!("'{}", self.sigil));
+            let span = Span::new(text);
+            let span = span.slice(offset.min(text.len())..);
+            // A piece that was copied is on a character boundary.
+            Diagnostic::new(span, &message).with_length(length)
+        };
+        match &error {
+            Error::Meta(diagnostic) => Error::Meta(locate(diagnostic)),
+            Error::Placeholder(diagnostic) => Error::Placeholder(locate(diagnostic)),
+            Error::Parse(diagnostic) => Error::Parse(locate(diagnostic)),
+            Error::Render(diagnostic) => Error::Render(locate(diagnostic)),
+        }
+    }
+
+    /// The body of the tag at the start of `text`, and everything after it.
+    fn tag<'a>(&self, text: &'a str) -> Option<(&'a str, &'a str)> {
+        let after = text.strip_prefix(self.open.as_str())?;
+        let end = after.find(self.close.as_str())?;
+        Some((&after[..end], &after[end + self.close.len()..]))
+    }
+
+    /// The offset of the sigil of each variable within the body of a tag, other
+    /// than within its quoted strings, which is replaced with a `£`.
+    fn sigils(&self, body: &str) -> Vec<usize> {
+        let mut sigils = vec![];
+        let mut quoted = false;
+        let mut chars = body.char_indices().peekable();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => quoted = !quoted,
+                '\\' if quoted => {
+                    chars.next();
+                },
+                c if c == self.sigil && !quoted && chars.peek().is_some_and(|(_, next)| is_alphabetic(*next)) => sigils.push(offset),
+                _ => {},
+            }
+        }
+        sigils
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Parsers
+/// Parse any character until the end of the line.
+/// This will return all characters, except the newline which will be consumed
+/// and discarded.
+///
+/// Both `\n` and `\r\n` line endings are supported, so files written on
+/// Windows will not leak a trailing `\r` into the line.
+///
+/// # Examples
+/// When there is no newline.
+/// ```rust
+/// use blogs_md_easy::{parse_until_eol, Span};
+///
+/// let input = Span::new("Hello, World!");
+/// let (input, until_eol) = parse_until_eol(input).unwrap();
+/// assert_eq!(input.fragment(), &"");
+/// assert_eq!(until_eol.fragment(), &"Hello, World!");
+/// ```
+///
+/// When there is a newline, the newline is consumed.
+/// ```rust
+/// use blogs_md_easy::{parse_until_eol, Span};
+///
+/// let input = Span::new("Hello, World!\nThis is Sparta!");
+/// let (input, until_eol) = parse_until_eol(input).unwrap();
+/// assert_eq!(input.fragment(), &"This is Sparta!");
+/// assert_eq!(until_eol.fragment(), &"Hello, World!");
+/// ```
+///
+/// When there is a Windows newline, both characters are consumed.
+/// ```rust
+/// use blogs_md_easy::{parse_until_eol, Span};
+///
+/// let input = Span::new("Hello, World!\r\nThis is Sparta!");
+/// let (input, until_eol) = parse_until_eol(input).unwrap();
+/// assert_eq!(input.fragment(), &"This is Sparta!");
+/// assert_eq!(until_eol.fragment(), &"Hello, World!");
+/// ```
+pub fn parse_until_eol(input: Span) -> IResult<Span, Span> {
+    terminated(
+        take_till(|c| c == '\r' || c == '\n'),
+        alt((tag("\r\n"), tag("\n"), tag("\r"), tag(""))),
+    )(input)
+}
+
+/// Parse a comment starting with either a `#` or `//` and ending with a newline.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_meta_comment, Span};
+///
+/// let input = Span::new("# This is a comment");
+/// let (input, meta_comment) = parse_meta_comment(input).unwrap();
+/// assert_eq!(input.fragment(), &"");
+/// assert_eq!(meta_comment.fragment(), &"This is a comment");
+/// ```
+pub fn parse_meta_comment(input: Span) -> IResult<Span, Span> {
+    preceded(
+        // All comments start with either a `#` or `//` followed by a space(s).
+        tuple((alt((tag("#"), tag("//"))), space0)),
+        parse_until_eol,
+    )(input)
+}
+
+/// Parse a key, that starts with an optional `£`, followed by an alphabetic
+/// character, then any number of alphanumeric characters, hyphens and
+/// underscores.
+///
+/// # Examples
+/// A valid variable, consisting of letters and underscores.
+/// ```rust
+/// use blogs_md_easy::{parse_meta_key, Span};
+///
+/// let input = Span::new("£publish_date");
+/// let (_, variable) = parse_meta_key(input).unwrap();
+/// assert_eq!(variable.fragment(), &"publish_date");
+///
+/// let input = Span::new("$publish_date");
+/// let (_, variable) = parse_meta_key(input).unwrap();
+/// assert_eq!(variable.fragment(), &"publish_date");
+/// ```
+/// An invalid example, variables cannot start with a number.
+/// ```rust
+/// use blogs_md_easy::{parse_meta_key, Span};
+///
+/// let input = Span::new("£1_to_2");
+/// let variable = parse_meta_key(input);
+/// assert!(variable.is_err());
+/// ```
+pub fn parse_meta_key(input: Span) -> IResult<Span, Span> {
+    preceded(
+        opt(alt((tag("£"), tag("$")))),
+        parse_variable_name
+    )(input)
+}
+
+/// Parse any number of characters until the end of the line or string.
+///
+/// # Examples
+/// Meta values are essentially just anything that isn't a newline.
+/// ```rust
+/// use blogs_md_easy::{parse_meta_value, Span};
+///
+/// let input = Span::new("This is a value");
+/// let (_, value) = parse_meta_value(input).unwrap();
+/// assert_eq!(value.fragment(), &"This is a value");
+/// ```
+///
+/// However, if you need newlines, then wrap the string in double quotes.  \
+/// Don't forget to escape your quotes too!
+/// ```rust
+/// use blogs_md_easy::{parse_meta_value, Span};
+///
+/// let input = Span::new(r#""This \"value\" is on
+/// a new line""#);
+/// let (_, value) = parse_meta_value(input).unwrap();
+/// assert_eq!(value.fragment(), &"This \\\"value\\\" is on\na new line");
+/// ```
+pub fn parse_meta_value(input: Span) -> IResult<Span, Span> {
+    alt((
+        // Match a delimited quote string.
+        delimited(
+            tag(r#"""#),
+            // Match everything that isn't `\"`.
+            escaped(
+                is_not(r#"\""#),
+                '\\',
+                one_of(r#"nrt\""#)
+            ),
+            tag(r#"""#),
+        ),
+        // The value of the variable, everything after the equals sign.
+        // Continue to a newline or the end of the string.
+        parse_until_eol,
+    ))(input)
+}
+
+/// Parse a heredoc, which is a value that spans several lines without being
+/// quoted, so that it can contain anything, including double quotes.
+///
+/// The value begins on the line after `<<` and a delimiter of letters, digits
+/// and underscores, and continues until a line of only the delimiter. The
+/// indentation that every line shares is removed.
+///
+/// # Examples
+/// ```rust
+/// use blogs_md_easy::{parse_meta_heredoc, Span};
+///
+/// let input = Span::new("<<END\n  A \"quoted\" value,\n\n  on two paragraphs.\n  END\nauthor = John Doe");
+/// let (input, value) = parse_meta_heredoc(input).unwrap();
+/// assert_eq!(value, "A \"quoted\" value,\n\non two paragraphs.");
+/// assert_eq!(input.fragment(), &"\nauthor = John Doe");
+/// ```
+///
+/// A heredoc must be closed.
+/// ```rust
+/// use blogs_md_easy::{parse_meta_heredoc, Span};
+///
+/// assert!(parse_meta_heredoc(Span::new("<<END\nNever closed")).is_err());
+/// ```
+pub fn parse_meta_heredoc(input: Span) -> IResult<Span, String> {
+    let (body, delimiter) = parse_heredoc_opener(input)?;
+
+    let mut lines: Vec<&str> = Vec::new();
+    let mut offset = 0;
+    for line in body.fragment().split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        if content.trim() == *delimiter.fragment() {
+            // The line ending is left for the next line to skip.
+            let (remaining, _) = body.take_split(offset + content.len());
+            let indent = lines
+                .iter()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| line.len() - line.trim_start().len())
+                .min()
+                .unwrap_or_default();
+            let value = lines
+                .iter()
+                .map(|line| line.get(indent..).unwrap_or_default())
+                .collect::<Vec<&str>>()
+                .join("\n");
+            return Ok((remaining, value));
+        }
+        lines.push(content);
+        offset += line.len();
+    }
+
+    Err(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::TakeUntil)))
+}
+
+/// Parse the `<<` and delimiter that open a heredoc, see
+/// [`parse_meta_heredoc`], returning the delimiter.
+fn parse_heredoc_opener(input: Span) -> IResult<Span, Span> {
+    delimited(
+        tag("<<"),
+        take_while1(|c: char| c.is_ascii_alphanumeric() || c == '_'),
+        tuple((space0, line_ending)),
+    )(input)
+}
+
+/// Parse a list of values, surrounded by square brackets and separated by
+/// commas.
+///
+/// Items can be wrapped in double quotes, which is only necessary if they
+/// contain a comma or a closing square bracket.
+///
+/// # Examples
+/// ```rust
+/// use blogs_md_easy::{parse_meta_list, Span};
+///
+/// let input = Span::new("[rust, parsing, nom]");
+/// let (_, list) = parse_meta_list(input).unwrap();
+/// assert_eq!(list, vec!["rust", "parsing", "nom"]);
+/// ```
+///
+/// Quoted items.
+/// ```rust
+/// use blogs_md_easy::{parse_meta_list, Span};
+///
+/// let input = Span::new(r#"[ "Doe, John", Jane Doe ]"#);
+/// let (_, list) = parse_meta_list(input).unwrap();
+/// assert_eq!(list, vec!["Doe, John", "Jane Doe"]);
+/// ```
+pub fn parse_meta_list(input: Span) -> IResult<Span, Vec<String>> {
+    delimited(
+        tuple((tag("["), space0)),
+        separated_list0(
+            tuple((space0, tag(","), space0)),
+            alt((
+                delimited(tag(r#"""#), is_not(r#"""#), tag(r#"""#)),
+                is_not(",]\r\n"),
+            )).map(|item: Span| item.fragment().trim().to_string())
+        ),
+        tuple((space0, tag("]"))),
+    )(input)
+}
+
+/// Parse a key-value pair of meta_key and meta_value.
+///
+/// # Examples
+/// ```rust
+/// use blogs_md_easy::{parse_meta_key_value, Span};
+///
+/// let input = Span::new("£publish_date = 2021-01-01");
+/// let (_, meta) = parse_meta_key_value(input).unwrap();
+/// assert_eq!(meta.key, "publish_date");
+/// assert_eq!(meta.value, "2021-01-01");
+/// ```
+///
+/// Lists are stored with each item separated by a comma and a space, see
+/// [`parse_meta_list`], and [`join_meta_list`](crate::join_meta_list) for
+/// items that contain a comma.
+/// ```rust
+/// use blogs_md_easy::{parse_meta_key_value, Span};
+///
+/// let input = Span::new("tags = [rust,parsing,  nom]");
+/// let (_, meta) = parse_meta_key_value(input).unwrap();
+/// assert_eq!(meta.key, "tags");
+/// assert_eq!(meta.value, "rust, parsing, nom");
+/// ```
+///
+/// Values that span several lines can be a heredoc, see
+/// [`parse_meta_heredoc`].
+/// ```rust
+/// use blogs_md_easy::{parse_meta_key_value, Span};
+///
+/// let input = Span::new("description = <<END\nSay \"hello\".\nEND");
+/// let (_, meta) = parse_meta_key_value(input).unwrap();
+/// assert_eq!(meta.value, "Say \"hello\".");
+/// ```
+pub fn parse_meta_key_value(input: Span) -> IResult<Span, Meta> {
+    separated_pair(
+        parse_meta_key,
+        recognize(tuple((space0, tag("="), space0))),
+        alt((
+            // A heredoc's value begins on the next line, and one that is
+            // never closed isn't mistaken for a value of `<<END`.
+            parse_meta_heredoc,
+            preceded(not(parse_heredoc_opener), alt((
+                // A list must be the only thing on the line.
+                terminated(
+                    parse_meta_list,
+                    verify(parse_until_eol, |rest: &Span| rest.fragment().trim().is_empty())
+                ).map(|list| join_meta_list(&list)),
+                // Quoted values can span multiple lines, so make sure that
+                // Windows line endings are consistent with everything else.
+                parse_meta_value.map(|value| value.fragment().replace("\r\n", "\n")),
+            ))),
+        ))
+    )(input)
+    .map(|(input, (key, value))| (input, Meta::new(key.fragment(), &value)))
+}
+
+/// Parse a line of meta data. This can either be a comment or a key-value pair.
+///
+/// # Examples
+/// Parsing of a comment returns None.
+/// ```rust
+/// use blogs_md_easy::{parse_meta_line, Span};
+///
+/// let input = Span::new("# This is a comment");
+/// let (_, meta) = parse_meta_line(input).unwrap();
+/// assert!(meta.is_none());
+/// ```
+/// Parsing of a key-value pair returns a Meta object.
+/// ```rust
+/// use blogs_md_easy::{parse_meta_line, Span};
+///
+/// let input = Span::new("£publish_date = 2021-01-01");
+/// let (_, meta) = parse_meta_line(input).unwrap();
+/// assert!(&meta.is_some());
+/// let meta = meta.unwrap();
+/// assert_eq!(&meta.key, "publish_date");
+/// assert_eq!(&meta.value, "2021-01-01");
+/// ```
+pub fn parse_meta_line(input: Span) -> IResult<Span, Option<Meta>> {
+    let (input, _) = space0(input)?;
+    let (input, res) = alt((
+        parse_meta_comment.map(|_| None),
+        parse_meta_key_value.map(Some),
+    ))(input)?;
+    let (input, _) = multispace0(input)?;
+    Ok((input, res))
+}
+
+/// Parse everything up to the first line that is only the `delimiter`, then
+/// consume that line, so that a value such as `---foo` doesn't close a
+/// frontmatter section.
+#[cfg(any(feature = "yaml", feature = "toml"))]
+fn take_until_line<'a>(delimiter: &'static str) -> impl Fn(Span<'a>) -> IResult<Span<'a>, Span<'a>> {
+    move |input: Span<'a>| {
+        let text = input.fragment();
+        let end = text.match_indices('\n')
+            .map(|(offset, _)| offset)
+            .find(|offset| text[offset + 1..].split('\n').next().unwrap_or_default().trim_end() == delimiter)
+            .ok_or(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::TakeUntil)))?;
+        let (rest, content) = input.take_split(end);
+        let (rest, _) = tuple((tag("\n"), tag(delimiter), space0, alt((line_ending, eof))))(rest)?;
+        Ok((rest, content))
+    }
+}
+
+/// Parse a YAML frontmatter section, delimited by `---` lines, as used by
+/// Jekyll, Hugo and Obsidian.
+///
+/// Nested keys are flattened using dot notation.
+///
+/// Requires the `yaml` feature.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_yaml_section, Meta, Span};
+///
+/// let input = Span::new("---\ntitle: Hello\nauthor:\n  name: John Doe\n---\n# Markdown title");
+/// let (input, meta) = parse_yaml_section(input).unwrap();
+/// assert_eq!(meta, vec![
+///     Meta::new("title", "Hello"),
+///     Meta::new("author.name", "John Doe"),
+/// ]);
+/// assert_eq!(input.fragment(), &"# Markdown title");
+/// ```
+#[cfg(feature = "yaml")]
+pub fn parse_yaml_section(input: Span) -> IResult<Span, Vec<Meta>> {
+    let (remaining, yaml) = delimited(
+        tuple((multispace0, tag("---"), space0, line_ending)),
+        take_until_line("---"),
+        multispace0,
+    )(input)?;
+
+    let value = serde_yaml::from_str::<serde_json::Value>(yaml.fragment())
+        .map_err(|_| nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Verify)))?;
+
+    let mut meta_values = Vec::new();
+    flatten_meta("", &value, &mut meta_values);
+    Ok((remaining, meta_values))
+}
+
+/// Parse a TOML frontmatter section, delimited by `+++` lines, as used by Zola
+/// and Hugo.
+///
+/// Tables are flattened using dot notation.
+///
+/// Requires the `toml` feature.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_toml_section, Meta, Span};
+///
+/// let input = Span::new("+++\ntitle = \"Hello\"\ndate = 2024-01-01\n\n[author]\nname = \"John Doe\"\n+++\n# Markdown title");
+/// let (input, meta) = parse_toml_section(input).unwrap();
+/// assert_eq!(meta, vec![
+///     Meta::new("title", "Hello"),
+///     Meta::new("date", "2024-01-01"),
+///     Meta::new("author.name", "John Doe"),
+/// ]);
+/// assert_eq!(input.fragment(), &"# Markdown title");
+/// ```
+#[cfg(feature = "toml")]
+pub fn parse_toml_section(input: Span) -> IResult<Span, Vec<Meta>> {
+    let (remaining, toml) = delimited(
+        tuple((multispace0, tag("+++"), space0, line_ending)),
+        take_until_line("+++"),
+        multispace0,
+    )(input)?;
+
+    let table = toml.fragment().parse::<toml::Table>()
+        .map_err(|_| nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Verify)))?;
+
+    let mut meta_values = Vec::new();
+    flatten_meta("", &toml_to_json(toml::Value::Table(table)), &mut meta_values);
+    Ok((remaining, meta_values))
+}
+
+/// Parse a JSON object as the meta section, so that metadata exported from a
+/// CMS can be used verbatim.
+///
+/// The object is either surrounded by `<?meta-json` and `?>`, or is within a
+/// ```` ```json meta ```` fenced code block.  \
+/// Nested objects are flattened using dot notation.
+///
+/// Requires the `json` feature.
+///
+/// # Examples
+/// Using the `<?meta-json` tag.
+/// ```rust
+/// use blogs_md_easy::{parse_json_section, Meta, Span};
+///
+/// let input = Span::new("<?meta-json\n{\"title\": \"Hello\", \"author\": {\"name\": \"John Doe\"}}\n?>\n# Markdown title");
+/// let (input, meta) = parse_json_section(input).unwrap();
+/// assert_eq!(meta, vec![
+///     Meta::new("title", "Hello"),
+///     Meta::new("author.name", "John Doe"),
+/// ]);
+/// assert_eq!(input.fragment(), &"# Markdown title");
+/// ```
+///
+/// Using a fenced code block.
+/// ```rust
+/// use blogs_md_easy::{parse_json_section, Meta, Span};
+///
+/// let input = Span::new("```json meta\n{\"title\": \"Hello\"}\n```\n# Markdown title");
+/// let (input, meta) = parse_json_section(input).unwrap();
+/// assert_eq!(meta, vec![Meta::new("title", "Hello")]);
+/// assert_eq!(input.fragment(), &"# Markdown title");
+/// ```
+#[cfg(feature = "json")]
+pub fn parse_json_section(input: Span) -> IResult<Span, Vec<Meta>> {
+    let (remaining, json) = alt((
+        delimited(
+            tuple((multispace0, tag("<?meta-json"), multispace0)),
+            take_until("?>"),
+            tuple((tag("?>"), multispace0)),
+        ),
+        delimited(
+            tuple((multispace0, tag("```json"), space0, tag("meta"), space0, line_ending)),
+            take_until("```"),
+            tuple((tag("```"), multispace0)),
+        ),
+    ))(input)?;
+
+    let value = serde_json::from_str::<serde_json::Value>(json.fragment())
+        .ok()
+        .filter(|value| value.is_object())
+        .ok_or(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Verify)))?;
+
+    let mut meta_values = Vec::new();
+    flatten_meta("", &value, &mut meta_values);
+    Ok((remaining, meta_values))
+}
+
+/// Parse the meta section. This is either a `:meta`, `<meta>`, or `<?meta` tag
+/// surrounding a Vector of [`parse_meta_line`], `---` surrounding YAML
+/// frontmatter, see [`parse_yaml_section`], `+++` surrounding TOML
+/// frontmatter, see [`parse_toml_section`], or a JSON object, see
+/// [`parse_json_section`].
+///
+/// YAML, TOML and JSON are only parsed when their respective feature is enabled.
+///
+/// If a meta section is opened but cannot be parsed, the [`Error::Meta`] points
+/// at the line that could not be parsed.
+///
+/// # Examples
+/// ```rust
+/// use blogs_md_easy::{parse_meta_section, Meta, Span};
+///
+/// let input = Span::new(":meta\n// This is the published date\npublish_date = 2021-01-01\n:meta\n# Markdown title");
+/// let (input, meta) = parse_meta_section(input).unwrap();
+/// // Comments are ignored and removed from the Vector.
+/// assert_eq!(meta.len(), 1);
+/// assert_eq!(meta, vec![
+///     Meta {
+///         key: "publish_date".to_string(),
+///         value: "2021-01-01".to_string(),
+///     },
+/// ]);
+/// assert_eq!(input.fragment(), &"# Markdown title");
+/// ```
+///
+/// A malformed meta line.
+/// ```rust
+/// use blogs_md_easy::{parse_meta_section, Error, Span};
+///
+/// let input = Span::new(":meta\ntitle = Hello\nauthor John Doe\n:meta\n# Markdown title");
+/// let Err(nom::Err::Error(Error::Meta(diagnostic))) = parse_meta_section(input) else {
+///     panic!("expected a meta error");
+/// };
+/// assert_eq!(diagnostic.line, 3);
+/// assert_eq!(diagnostic.message, "expected `key = value`, or the closing `:meta`");
+/// ```
+pub fn parse_meta_section(input: Span) -> IResult<Span, Vec<Meta>, Error> {
+    // Formats whose feature is disabled never match.
+    #[allow(unused)]
+    fn disabled(input: Span) -> IResult<Span, Vec<Meta>> {
+        Err(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Alt)))
+    }
+    #[cfg(not(feature = "json"))]
+    let parse_json_section = disabled;
+    #[cfg(not(feature = "yaml"))]
+    let parse_yaml_section = disabled;
+    #[cfg(not(feature = "toml"))]
+    let parse_toml_section = disabled;
+
+    alt((
+        // JSON must come first, otherwise `<?meta-json` is mistaken for `<?meta`.
+        parse_json_section,
+        alt((
+            // I can't think of a more elegant solution for ensuring the pairs
+            // match one another. The previous solution could open with `:meta`
+            // and close with `</meta>` for example.
+            delimited(
+                tuple((multispace0, tag(":meta"), multispace0)),
+                many1(parse_meta_line),
+                tuple((multispace0, tag(":meta"), multispace0)),
+            ),
+            delimited(
+                tuple((multispace0, tag("<?"), opt(tag("meta")), multispace0)),
+                many1(parse_meta_line),
+                tuple((multispace0, tag("?>"), multispace0)),
+            ),
+            delimited(
+                tuple((multispace0, tag("<meta>"), multispace0)),
+                many1(parse_meta_line),
+                tuple((multispace0, tag("</meta>"), multispace0)),
+            ),
+        ))
+        // Filter out None values, leaving only legitimate meta values.
+        // When calling flatten on Option<> types, None values are considered
+        // empty iterators and removed, Some values are considered iterators
+        // with a single element and are therefore unwrapped and returned.
+        .map(|res| res.into_iter().flatten().collect()),
+        parse_yaml_section,
+        parse_toml_section,
+    ))(input)
+    .map_err(|e| e.map(|_| meta_error(input)))
+}
+
+/// Parse the meta section, as [`parse_meta_section`] does, along with the
+/// comments that are written directly above each key, so that tooling can
+/// document them.
+///
+/// A blank line between a comment and the key beneath it detaches the
+/// comment, as does being after the last key. Only `:meta`, `<meta>`, and
+/// `<?meta` sections have comments, so the keys of YAML, TOML and JSON have
+/// none.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_meta_section_with_comments, Meta, Span};
+///
+/// let input = Span::new(":meta\n// The date that the post is listed by.\n// Use YYYY-MM-DD.\npublish_date = 2021-01-01\n# Detached\n\nauthor = John Doe\n:meta\n# Markdown title");
+/// let (input, meta) = parse_meta_section_with_comments(input).unwrap();
+/// assert_eq!(meta[0].meta, Meta::new("publish_date", "2021-01-01"));
+/// assert_eq!(meta[0].comments, vec!["The date that the post is listed by.", "Use YYYY-MM-DD."]);
+/// assert_eq!(meta[1].meta, Meta::new("author", "John Doe"));
+/// assert!(meta[1].comments.is_empty());
+/// assert_eq!(input.fragment(), &"# Markdown title");
+/// ```
+pub fn parse_meta_section_with_comments(input: Span) -> IResult<Span, Vec<AnnotatedMeta>, Error> {
+    let (remaining, meta_values) = parse_meta_section(input)?;
+
+    let (section, _) = multispace0::<Span, nom::error::Error<Span>>(input).unwrap_or((input, input));
+    let Some((open, close)) = [(":meta", ":meta"), ("<meta>", "</meta>"), ("<?", "?>")]
+        .into_iter()
+        .find(|(open, _)| section.starts_with(open) && !section.starts_with("<?meta-json"))
+    else {
+        return Ok((remaining, meta_values.into_iter().map(AnnotatedMeta::from).collect()));
+    };
+
+    let source = input.fragment();
+    let offset_of = |span: Span| span.location_offset() - input.location_offset();
+    let (mut lines, _) = tuple((tag::<&str, Span, nom::error::Error<Span>>(open), opt(tag("meta"))))(section).unwrap_or((section, (section, None)));
+    let mut previous_end = offset_of(lines);
+    let mut annotated = Vec::with_capacity(meta_values.len());
+    let mut comments = Vec::new();
+    loop {
+        let (line, _) = multispace0::<Span, nom::error::Error<Span>>(lines).unwrap_or((lines, lines));
+        if line.fragment().is_empty() || line.starts_with(close) {
+            break;
+        }
+        // A blank line detaches the comments above it.
+        if source[previous_end..offset_of(line)].matches('\n').count() > 1 {
+            comments.clear();
+        }
+        let next = match parse_meta_comment(line) {
+            Ok((next, comment)) => {
+                comments.push(comment.fragment().trim_end().to_string());
+                next
+            },
+            Err(_) => match parse_meta_key_value(line) {
+                Ok((next, meta)) => {
+                    annotated.push(AnnotatedMeta { meta, comments: core::mem::take(&mut comments) });
+                    next
+                },
+                Err(_) => break,
+            },
+        };
+        previous_end = offset_of(line) + source[offset_of(line)..offset_of(next)].trim_end().len();
+        lines = next;
+    }
+
+    Ok((remaining, annotated))
+}
+
+/// Work out why `input` is not a valid meta section, so that the [`Error`]
+/// points at the offending line, rather than the start of the input.
+fn meta_error(input: Span) -> Error {
+    let (input, _) = multispace0::<Span, nom::error::Error<Span>>(input).unwrap_or((input, input));
+    let error = |span: Span, message: &str| Error::Meta(Diagnostic::new(span, message));
+    let format = |name: &str, feature: &str, enabled: bool| if enabled {
+        format!("invalid {} meta section", name)
+    } else {
+        format!("{} meta sections require the `{}` feature", name, feature)
+    };
+
+    // A Markdown may start with a code block, or a thematic break, so only
+    // those that are clearly meant as a meta section are errors.
+    let first_line = input.fragment().lines().next().unwrap_or_default().trim_end();
+    if input.starts_with("<?meta-json") || first_line.strip_prefix("```json").is_some_and(|rest| rest.trim() == "meta") {
+        return error(input, &format("JSON", "json", cfg!(feature = "json")));
+    }
+    if is_frontmatter(input.fragment(), "---") {
+        return error(input, &format("YAML", "yaml", cfg!(feature = "yaml")));
+    }
+    if is_frontmatter(input.fragment(), "+++") {
+        return error(input, &format("TOML", "toml", cfg!(feature = "toml")));
+    }
+
+    let Some((open, close)) = [(":meta", ":meta"), ("<meta>", "</meta>"), ("<?meta", "?>")]
+        .into_iter()
+        .find(|(open, _)| input.starts_with(open))
+    else {
+        // Nothing resembling a meta section, so it's simply absent.
+        return Error::Parse(Diagnostic::new(input, "expected a meta section"));
+    };
+
+    let (lines, _) = tuple((tag::<&str, Span, nom::error::Error<Span>>(open), opt(tag("meta")), multispace0))(input).unwrap_or((input, (input, None, input)));
+    let (remaining, meta) = many0(parse_meta_line)(lines).unwrap_or((lines, Vec::new()));
+    let (remaining, _) = multispace0::<Span, nom::error::Error<Span>>(remaining).unwrap_or((remaining, remaining));
+
+    if let Some(delimiter) = unclosed_heredoc(remaining) {
+        error(remaining, &format!("expected the heredoc to be closed by a line of `{}`", delimiter))
+    } else if meta.is_empty() {
+        error(remaining, "expected at least one `key = value`")
+    } else {
+        error(remaining, &format!("expected `key = value`, or the closing `{}`", close))
+    }
+}
+
+/// Whether `input` starts with a line of only the `delimiter`, which is closed
+/// by another, as frontmatter is, rather than a thematic break.
+fn is_frontmatter(input: &str, delimiter: &str) -> bool {
+    let mut lines = input.lines().map(str::trim_end);
+    lines.next() == Some(delimiter) && lines.any(|line| line == delimiter)
+}
+
+/// The delimiter of a meta line that opens a heredoc, see
+/// [`parse_meta_heredoc`], which is never closed.
+fn unclosed_heredoc(line: Span<'_>) -> Option<&str> {
+    let (heredoc, _) = tuple((parse_meta_key, space0, tag("="), space0))(line).ok()?;
+    let (_, delimiter) = parse_heredoc_opener(heredoc).ok()?;
+    parse_meta_heredoc(heredoc).is_err().then_some(*delimiter.fragment())
+}
+
+/// Every malformed line of a meta section, rather than only the first that
+/// [`parse_meta_section`] returns, so that they can be fixed together.
+///
+/// Only the first is found for JSON, YAML and TOML, or for a section that is
+/// never closed, as every line of the Markdown would otherwise be an error.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{meta_errors, Span};
+///
+/// let errors = meta_errors(Span::new(":meta\nauthor John Doe\ntitle = Hello\ndate 2024-01-01\n:meta\n# Markdown title"));
+/// assert_eq!(errors.iter().map(|error| error.diagnostic().line).collect::<Vec<u32>>(), vec![2, 4]);
+///
+/// assert!(meta_errors(Span::new(":meta\ntitle = Hello\n:meta")).is_empty());
+/// ```
+pub fn meta_errors(input: Span) -> Vec<Error> {
+    if parse_meta_section(input).is_ok() {
+        return Vec::new();
+    }
+    let first = meta_error(input);
+
+    let (input, _) = multispace0::<Span, nom::error::Error<Span>>(input).unwrap_or((input, input));
+    let Some((open, close)) = [(":meta", ":meta"), ("<meta>", "</meta>"), ("<?meta", "?>")]
+        .into_iter()
+        .find(|(open, _)| input.starts_with(open) && !input.starts_with("<?meta-json"))
+    else {
+        return vec![first];
+    };
+    let (mut lines, _) = tuple((tag::<&str, Span, nom::error::Error<Span>>(open), opt(tag("meta")), multispace0))(input).unwrap_or((input, (input, None, input)));
+    if !lines.fragment().lines().any(|line| line.trim() == close) {
+        return vec![first];
+    }
+
+    let mut errors = vec![first];
+    loop {
+        let (remaining, _) = many0(parse_meta_line)(lines).unwrap_or((lines, Vec::new()));
+        let (remaining, _) = multispace0::<Span, nom::error::Error<Span>>(remaining).unwrap_or((remaining, remaining));
+        if remaining.fragment().is_empty() || remaining.starts_with(close) {
+            break;
+        }
+        // The rest of the section would be the value of the heredoc.
+        if unclosed_heredoc(remaining).is_some() {
+            break;
+        }
+        if errors.iter().all(|error| error.diagnostic().offset != remaining.location_offset()) {
+            errors.push(Error::Meta(Diagnostic::new(remaining, &format!("expected `key = value`, or the closing `{}`", close))));
+        }
+        // Carry on from the next line.
+        lines = parse_until_eol(remaining).map(|(next, _)| next).unwrap_or(remaining);
+    }
+
+    errors
+}
+
+/// Parse the title of the document. This is either a Markdown title, which is
+/// written after a `#` or underlined with `=`, or an HTML heading with the `h1`
+/// tag.
+///
+/// # Examples
+/// Using a Markdown heading.
+/// ```rust
+/// use blogs_md_easy::{parse_title, Span};
+///
+/// let input = Span::new("# This is the title");
+/// let (_, title) = parse_title(input).unwrap();
+/// assert_eq!(title.fragment(), &"This is the title");
+/// ```
+/// Using an HTML heading.
+/// ```rust
+/// use blogs_md_easy::{parse_title, Span};
+///
+/// let input = Span::new("<h1>This is the title</h1>");
+/// let (_, title) = parse_title(input).unwrap();
+/// assert_eq!(title.fragment(), &"This is the title");
+/// ```
+/// Using a setext heading.
+/// ```rust
+/// use blogs_md_easy::{parse_title, Span};
+///
+/// let input = Span::new("This is the title\n=================\nThe content.");
+/// let (content, title) = parse_title(input).unwrap();
+/// assert_eq!(title.fragment(), &"This is the title");
+/// assert_eq!(content.fragment(), &"\nThe content.");
+///
+/// // A paragraph without the line of `=` isn't a title.
+/// assert!(parse_title(Span::new("Just a paragraph.\nWith two lines.")).is_err());
+/// ```
+pub fn parse_title(input: Span) -> IResult<Span, Span> {
+    let (input, _) = multispace0(input)?;
+
+    let (input, title) = alt((
+        // Either a Markdown title...
+        preceded(tuple((tag("#"), space0)), take_till(|c| c == '\n' || c == '\r')),
+        // ... or an HTML title...
+        delimited(tag("<h1>"), take_until("</h1>"), tag("</h1>")),
+        // ... or a line that is underlined with `=`.
+        terminated(
+            take_till(|c| c == '\n' || c == '\r'),
+            tuple((line_ending, take_while1(|c| c == '='), space0, peek(alt((line_ending, eof))))),
+        ),
+    ))(input)?;
+
+    Ok((input.to_owned(), title.to_owned()))
+}
+
+/// Rewrite of the `nom::is_alphabetic` function that takes a char instead.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::is_alphabetic;
+///
+/// assert!(is_alphabetic('a'));
+/// assert!(is_alphabetic('A'));
+/// assert!(!is_alphabetic('1'));
+/// assert!(!is_alphabetic('-'));
+/// ```
+pub fn is_alphabetic(input: char) -> bool {
+    vec!['a'..='z', 'A'..='Z'].into_iter().flatten().any(|c| c == input)
+}
+
+/// A function that checks if a character is valid for a filter name.
+///
+/// The filter name is the value before the `=` in a Template.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::is_filter_name;
+///
+/// assert!(is_filter_name('a'));
+/// assert!(is_filter_name('A'));
+/// assert!(is_filter_name('1'));
+/// assert!(!is_filter_name('-'));
+/// assert!(!is_filter_name(' '));
+/// ```
+pub fn is_filter_name(input: char) -> bool {
+    input.is_alphanumeric() || ['_'].contains(&input)
+}
+
+/// A function that checks if a character is valid for a filter argument name.
+///
+/// This is the string preceding the `=` in the `meta` section.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::is_filter_arg;
+///
+/// assert!(is_filter_arg('a'));
+/// assert!(is_filter_arg('A'));
+/// assert!(is_filter_arg('1'));
+/// assert!(!is_filter_arg('-'));
+/// assert!(!is_filter_arg(' '));
+/// ```
+pub fn is_filter_arg(input: char) -> bool {
+    input.is_alphanumeric() || ['_'].contains(&input)
+}
+
+/// A function that checks if a character is valid for a filter argument value.
+///
+/// This is the string following the `=` in the `meta` section.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::is_filter_value;
+///
+/// assert!(is_filter_value('a'));
+/// assert!(is_filter_value('A'));
+/// assert!(is_filter_value('1'));
+/// assert!(!is_filter_value('|'));
+/// assert!(!is_filter_value(','));
+/// assert!(!is_filter_value('{'));
+/// assert!(!is_filter_value('}'));
+/// ```
+pub fn is_filter_value(input: char) -> bool {
+    input.is_alphanumeric()
+    || ![' ', '|', ',', '{', '}'].contains(&input)
+}
+
+/// Variable names must start with an alphabetic character, then any number of
+/// alphanumeric characters, hyphens and underscores.
+///
+/// Related variables can be grouped using dot notation, where each part of the
+/// name follows the same rules.
+///
+/// # Examples
+/// Variables can consist of letters and underscores.
+/// ```rust
+/// use blogs_md_easy::{parse_variable_name, Span};
+///
+/// let input = Span::new("publish_date");
+/// let (_, variable) = parse_variable_name(input).unwrap();
+/// assert_eq!(variable.fragment(), &"publish_date");
+/// ```
+///
+/// Variables cannot start with a number or underscore.
+/// ```rust
+/// use blogs_md_easy::{parse_variable_name, Span};
+///
+/// let input = Span::new("1_to_2");
+/// let variable = parse_variable_name(input);
+/// assert!(variable.is_err());
+/// ```
+///
+/// Nested variables are separated by a dot.
+/// ```rust
+/// use blogs_md_easy::{parse_variable_name, Span};
+///
+/// let input = Span::new("author.name");
+/// let (_, variable) = parse_variable_name(input).unwrap();
+/// assert_eq!(variable.fragment(), &"author.name");
+///
+/// // A trailing dot is not part of the name.
+/// let input = Span::new("author.");
+/// let (input, variable) = parse_variable_name(input).unwrap();
+/// assert_eq!(variable.fragment(), &"author");
+/// assert_eq!(input.fragment(), &".");
+///
+/// // Only the first segment must start with a letter, so that items of a
+/// // collection can be indexed.
+/// let input = Span::new("posts.0.title");
+/// let (_, variable) = parse_variable_name(input).unwrap();
+/// assert_eq!(variable.fragment(), &"posts.0.title");
+///
+/// // Nested segments can be in any language, such as the section of a heading.
+/// let input = Span::new("section.café-au-lait");
+/// let (_, variable) = parse_variable_name(input).unwrap();
+/// assert_eq!(variable.fragment(), &"section.café-au-lait");
+/// ```
+pub fn parse_variable_name(input: Span) -> IResult<Span, Span> {
+    fn segment(input: Span) -> IResult<Span, Span> {
+        recognize(tuple((
+            take_while_m_n(1, 1, is_alphabetic),
+            many0(alt((alphanumeric1, tag("-"), tag("_")))),
+        )))(input)
+    }
+    let alphanumeric = || take_while1(|c: char| c.is_alphanumeric());
+
+    recognize(tuple((
+        segment,
+        many0(preceded(tag("."), recognize(tuple((
+            alphanumeric(),
+            many0(alt((alphanumeric(), tag("-"), tag("_")))),
+        ))))),
+    )))(input)
+}
+
+/// Parse a template placeholder variable. This is a `£` followed by a variable
+/// name.
+///
+/// # Examples
+/// Variables must start with a `£`.
+/// ```rust
+/// use blogs_md_easy::{parse_variable, Span};
+///
+/// let input = Span::new("£variable");
+/// let (_, variable) = parse_variable(input).unwrap();
+/// assert_eq!(variable.fragment(), &"variable");
+/// ```
+///
+/// In keeping with what people are used to, it is also possible to use a `$`.
+/// ```rust
+/// use blogs_md_easy::{parse_variable, Span};
+///
+/// let input = Span::new("$variable");
+/// let (_, variable) = parse_variable(input).unwrap();
+/// assert_eq!(variable.fragment(), &"variable");
+/// ```
+///
+/// Failing to start with a `£` will return an error.
+/// ```rust
+/// use blogs_md_easy::{parse_variable, Span};
+///
+/// let input = Span::new("variable");
+/// let variable = parse_variable(input);
+/// assert!(variable.is_err());
+/// ```
+pub fn parse_variable(input: Span) -> IResult<Span, Span> {
+    preceded(
+        alt((tag("£"), tag("$"))),
+        parse_variable_name
+    )(input)
+}
+
+/// Parser that will parse exclusively the key-values from after a filter.  \
+/// This will return the key (before the `:`) and the value (after the `:`). It
+/// will also return a key of `_` if no key was provided.
+///
+/// # Examples
+/// Ensure that a key-value pair, separated by a colon, can be parsed into a
+/// tuple.
+/// ```rust
+/// use blogs_md_easy::{parse_filter_key_value, Span};
+///
+/// let input = Span::new("trail: ...");
+/// let (_, args) = parse_filter_key_value(input).unwrap();
+/// assert_eq!(args, ("trail", "..."));
+/// ```
+///
+/// Ensure that a single value can be parsed into a tuple with a key of `_`.
+/// ```rust
+/// use blogs_md_easy::{parse_filter_key_value, Span};
+///
+/// let input = Span::new("20");
+/// let (_, args) = parse_filter_key_value(input).unwrap();
+/// assert_eq!(args, ("_", "20"));
+/// ```
+///
+/// A value wrapped in double quotes can contain any character other than a
+/// `"`, such as a space, comma, colon, or pipe.
+/// ```rust
+/// use blogs_md_easy::{parse_filter_key_value, Span};
+///
+/// let input = Span::new("replacement: \" | \"");
+/// let (_, args) = parse_filter_key_value(input).unwrap();
+/// assert_eq!(args, ("replacement", " | "));
+/// ```
+pub fn parse_filter_key_value<'a>(input: Span<'a>) -> IResult<Span<'a>, (&'a str, &'a str)> {
+    alt((
+        // This matches a key-value separated by a colon.
+        // Example: `truncate = characters: 20`
+        separated_pair(
+            take_while(is_filter_arg).map(|arg: Span| *arg.fragment()),
+            tuple((space0, tag(":"), space0)),
+            parse_filter_value,
+        ),
+        // But it's also possible to just provide a value.
+        // Example: `truncate = 20`
+        parse_filter_value
+        .map(|value| ("_", value))
+    ))(input)
+}
+
+/// Parse the value of a filter argument, which is either wrapped in double
+/// quotes, or continues until a space, `|`, `,`, `{`, or `}`.
+fn parse_filter_value<'a>(input: Span<'a>) -> IResult<Span<'a>, &'a str> {
+    alt((
+        delimited(tag("\""), take_till(|c| c == '"'), tag("\"")),
+        take_while(is_filter_value),
+    ))(input)
+    .map(|(input, value)| (input, *value.fragment()))
+}
+
+/// Parser that will parse exclusively the key-values from after a filter.  \
+/// The signature of a filter is `filter_name = key1: value1, key2: value2,...`,
+/// or just `filter_name = value`.
+///
+/// # Examples
+/// Ensure that a key-value pair, separated by a colon, can be parsed into a
+/// tuple.
+/// ```rust
+/// use blogs_md_easy::{parse_filter_args, Span};
+///
+/// let input = Span::new("characters: 20, trail: ...");
+/// let (_, args) = parse_filter_args(input).unwrap();
+/// assert_eq!(args, vec![
+///     ("characters", "20"),
+///     ("trail", "..."),
+/// ]);
+/// ```
+///
+/// Ensure that a single value can be parsed into a tuple with a key of `_`.
+/// ```rust
+/// use blogs_md_easy::{parse_filter_args, Span};
+///
+/// let input = Span::new("20");
+/// let (_, args) = parse_filter_args(input).unwrap();
+/// assert_eq!(args, vec![
+///     ("_", "20")
+/// ]);
+/// ```
+///
+/// Quoted values can contain the commas that otherwise separate them.
+/// ```rust
+/// use blogs_md_easy::{parse_filter_args, Span};
+///
+/// let input = Span::new("find: \", \", replacement: \" | \"");
+/// let (_, args) = parse_filter_args(input).unwrap();
+/// assert_eq!(args, vec![
+///     ("find", ", "),
+///     ("replacement", " | "),
+/// ]);
+/// ```
+pub fn parse_filter_args<'a>(input: Span<'a>) -> IResult<Span<'a>, Vec<(&'a str, &'a str)>> {
+    separated_list1(
+        tuple((space0, tag(","), space0)),
+        parse_filter_key_value
+    )(input)
+}
+
+/// The `value` argument of an arithmetic [`Filter`], or the `default` if it
+/// isn't given.
+///
+/// The value is kept as it is written, so that [`render_filter`] can report it
+/// when it isn't a number.
+///
+/// [`render_filter`]: crate::render_filter
+fn number_arg(args: &BTreeMap<&str, &str>, default: &str) -> String {
+    args.get("value")
+        .or(args.get("_"))
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .unwrap_or(default)
+        .to_string()
+}
+
+/// Parse a [`Filter`], and optionally its arguments if present.
+///
+/// # Examples
+/// A filter with no arguments.
+/// ```rust
+/// use blogs_md_easy::{parse_filter, Filter, Span, TextCase};
+///
+/// let input = Span::new("lowercase");
+/// let (_, filter) = parse_filter(input).unwrap();
+/// assert!(matches!(filter, Filter::Text { case: TextCase::Lower }));
+/// ```
+///
+/// A filter with just a value, but no key.  \
+/// This will be parsed as a key of `_`, which will then be set to a key of the
+/// given enum Struct variant that is deemed the default.  \
+/// In the case of [`Filter::Truncate`], this will be the `characters`.
+/// ```rust
+/// use blogs_md_easy::{parse_filter, Filter, Span};
+///
+/// let input = Span::new("truncate = 20");
+/// let (_, filter) = parse_filter(input).unwrap();
+/// assert_eq!(filter, Filter::Truncate { characters: 20, trail: "...".to_string() });
+/// ```
+///
+/// A filter with multiple arguments, and given keys.
+/// ```rust
+/// use blogs_md_easy::{parse_filter, Filter, Span};
+///
+/// let input = Span::new("truncate = characters: 15, trail:...");
+/// let (_, filter) = parse_filter(input).unwrap();
+/// assert!(matches!(filter, Filter::Truncate { .. }));
+/// assert_eq!(filter, Filter::Truncate {
+///     characters: 15,
+///     trail: "...".to_string(),
+/// });
+/// ```
+///
+/// For some filters, default values are provided, if not present.
+/// ```rust
+/// use blogs_md_easy::{parse_filter, Filter, Span};
+///
+/// let input = Span::new("truncate = trail:...");
+/// let (_, filter) = parse_filter(input).unwrap();
+/// assert!(matches!(filter, Filter::Truncate { .. }));
+/// assert_eq!(filter, Filter::Truncate {
+///     characters: 100,
+///     trail: "...".to_string(),
+/// });
+/// ```
+///
+/// An argument that can't be parsed is an error, rather than the default.
+/// ```rust
+/// use blogs_md_easy::{parse_filter, Span};
+///
+/// assert!(parse_filter(Span::new("truncate = characters: abc")).is_err());
+/// assert!(parse_filter(Span::new("truncate = 300")).is_err());
+/// ```
+pub fn parse_filter(input: Span) -> IResult<Span, Filter> {
+    parse_filter_with(input, &|_| None)
+}
+
+/// Parse a [`Filter`], as [`parse_filter`] does, but with each argument that is
+/// only a variable, such as `characters: £excerpt_length`, replaced by
+/// `value_of` that variable.
+///
+/// An argument whose variable has no value is left as it is.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_filter_with, Filter, Span};
+///
+/// let input = Span::new("truncate = characters: £excerpt_length, trail: £trail");
+/// let (_, filter) = parse_filter_with(input, &|name| {
+///     (name == "excerpt_length").then(|| "20".to_string())
+/// }).unwrap();
+/// assert_eq!(filter, Filter::Truncate {
+///     characters: 20,
+///     trail: "£trail".to_string(),
+/// });
+/// ```
+pub fn parse_filter_with<'a>(input: Span<'a>, value_of: &dyn Fn(&str) -> Option<String>) -> IResult<Span<'a>, Filter> {
+    let filter = input;
+    let (input, (name, args)) = separated_pair(
+        take_while(is_filter_name),
+        opt(tuple((space0, tag("="), space0))),
+        opt(parse_filter_args)
+    )(input)?;
+    let raw_args = args.unwrap_or_default();
+    // An argument that can't be parsed is marked in full, unless it is a
+    // variable, which is only known once it has a value.
+    let invalid = |key: &str, kind: nom::error::ErrorKind| {
+        let raw = raw_args.iter().rev().find(|(k, _)| *k == key).map(|(_, value)| *value).unwrap_or_default();
+        match all_consuming(parse_variable)(Span::new(raw)).is_ok() {
+            true => None,
+            false => Some(nom::Err::Failure(nom::error::Error::new(argument_span(filter, raw), kind))),
+        }
+    };
+    let argument = |key: &str| invalid(key, nom::error::ErrorKind::MapOpt);
+
+    let values: Vec<(&str, String)> = raw_args.iter().copied()
+        .map(|(key, value)| {
+            let variable = all_consuming(parse_variable)(Span::new(value)).ok()
+                .and_then(|(_, variable)| value_of(variable.fragment()));
+            (key, variable.unwrap_or_else(|| value.to_string()))
+        })
+        .collect();
+    let args: BTreeMap<&str, &str> = values.iter().map(|(key, value)| (*key, value.as_str())).collect();
+
+    Ok((input, match name.fragment().to_lowercase().trim() {
+        // Maths filters.
+        "ceil" => Filter::Ceil,
+        "floor" => Filter::Floor,
+        "round" => Filter::Round {
+            precision: parsed_arg(&args, &["precision", "_"], &argument)?.unwrap_or(0),
+        },
+        "add" => Filter::Add { value: number_arg(&args, "0") },
+        "subtract" => Filter::Subtract { value: number_arg(&args, "0") },
+        "multiply" => Filter::Multiply { value: number_arg(&args, "1") },
+        // Nothing can be divided by zero.
+        "divide" => Filter::Divide { value: number_arg(&args, "1") },
+
+        // String filters.
+        "base64_decode" => Filter::Base64Decode,
+        "base64_encode" => Filter::Base64Encode,
+        "default" => Filter::Default {
+            value: args.get("value").or(args.get("_")).unwrap_or(&"").to_string(),
+        },
+        "excerpt" => Filter::Excerpt,
+        "first_words" => Filter::FirstWords {
+            words: parsed_arg(&args, &["words", "_"], &argument)?.unwrap_or(10),
+        },
+        "hash" => Filter::Hash {
+            algo: match args.get_key_value("algo").or(args.get_key_value("_")) {
+                Some((key, algo)) => match algo.parse::<HashAlgorithm>() {
+                    Ok(algo) => algo,
+                    Err(_) => match invalid(key, nom::error::ErrorKind::MapRes) {
+                        Some(error) => return Err(error),
+                        None => HashAlgorithm::Sha256,
+                    },
+                },
+                None => HashAlgorithm::Sha256,
+            },
+            // A length of zero would always be empty, so it is the whole digest.
+            length: parsed_arg(&args, &["length"], &argument)?.filter(|length| *length > 0),
+        },
+        "if" => Filter::If {
+            then: args.get("then").or(args.get("_")).unwrap_or(&"").to_string(),
+            otherwise: args.get("else").unwrap_or(&"").to_string(),
+        },
+        "join" => Filter::Join {
+            // An empty separator is never intended, so use the default.
+            separator: args.get("separator").or(args.get("_"))
+                .filter(|separator| !separator.is_empty())
+                .unwrap_or(&", ")
+                .to_string(),
+        },
+        "last_words" => Filter::LastWords {
+            words: parsed_arg(&args, &["words", "_"], &argument)?.unwrap_or(10),
+        },
+        "lowercase" => Filter::Text { case: TextCase::Lower },
+        "uppercase" => Filter::Text { case: TextCase::Upper },
+        "markdown" => Filter::Markdown {
+            tables: parsed_arg(&args, &["tables"], &argument)?.unwrap_or(false),
+            strikethrough: parsed_arg(&args, &["strikethrough"], &argument)?.unwrap_or(false),
+            tasklists: parsed_arg(&args, &["tasklists"], &argument)?.unwrap_or(false),
+            footnotes: parsed_arg(&args, &["footnotes"], &argument)?.unwrap_or(false),
+            smart_punctuation: parsed_arg(&args, &["smart_punctuation"], &argument)?.unwrap_or(false),
+            hard_breaks: parsed_arg(&args, &["hard_breaks"], &argument)?.unwrap_or(false),
+            html: parsed_arg(&args, &["html"], &argument)?.unwrap_or(true),
+            heading_offset: parsed_arg(&args, &["heading_offset"], &argument)?.unwrap_or(0),
+        },
+        "markdown_inline" => Filter::MarkdownInline,
+        "replace" => Filter::Replace {
+            find: args.get("find").unwrap_or(
+                args.get("_").unwrap_or(&"")
+            ).to_string(),
+            replacement: args.get("replacement").unwrap_or(&"").to_string(),
+            limit: parsed_arg(&args, &["limit"], &argument)?,
+        },
+        "reverse" => Filter::Reverse,
+        "age" | "time_ago" => Filter::TimeAgo {
+            now: args.get("now").or(args.get("_")).unwrap_or(&"").to_string(),
+        },
+        "split" => Filter::Split {
+            // Splitting on nothing is never intended, so use the default.
+            on: args.get("on").or(args.get("_"))
+                .filter(|on| !on.is_empty())
+                .unwrap_or(&",")
+                .to_string(),
+        },
+        "truncate" => Filter::Truncate {
+            // Attempt to get the characters, but if we can't then we use
+            // the unnamed value, defined as "_".
+            characters: parsed_arg(&args, &["characters", "_"], &argument)?.unwrap_or(100),
+            trail: args.get("trail").unwrap_or(&"...").to_string(),
+        },
+        "truncate_words" => Filter::TruncateWords {
+            words: parsed_arg(&args, &["words", "_"], &argument)?.unwrap_or(10),
+            trail: args.get("trail").unwrap_or(&"...").to_string(),
+        },
+        "wrap" => Filter::Wrap {
+            before: args.get("before").or(args.get("_")).unwrap_or(&"").to_string(),
+            after: args.get("after").unwrap_or(&"").to_string(),
+        },
+        "text" => Filter::Text {
+            // Default is `case: TextCase::Lower`.
+            case: parsed_arg(&args, &["case", "_"], &argument)?.unwrap_or(TextCase::Lower),
+        },
+        // The filter doesn't exist, which is never a valid placeholder.
+        _ => return Err(nom::Err::Failure(nom::error::Error::new(name, nom::error::ErrorKind::Verify))),
+    }))
+}
+
+/// The first of the `keys` arguments of a [`Filter`] that is given, parsed as a
+/// `T`.
+///
+/// An empty argument is treated as if it wasn't given, as is a variable without
+/// a value, whereas any other argument that can't be parsed is an error from
+/// `invalid`.
+fn parsed_arg<'a, T: FromStr>(args: &BTreeMap<&str, &str>, keys: &[&str], invalid: &dyn Fn(&str) -> Option<nom::Err<nom::error::Error<Span<'a>>>>) -> Result<Option<T>, nom::Err<nom::error::Error<Span<'a>>>> {
+    let Some((key, value)) = keys.iter().find_map(|key| args.get_key_value(key)).filter(|(_, value)| !value.is_empty()) else {
+        return Ok(None);
+    };
+    match value.parse::<T>() {
+        Ok(value) => Ok(Some(value)),
+        Err(_) => invalid(key).map_or(Ok(None), Err),
+    }
+}
+
+/// The span of an `argument` within the text of its `filter`, or else the
+/// whole filter, when the argument is not a slice of it.
+fn argument_span<'a>(filter: Span<'a>, argument: &str) -> Span<'a> {
+    match (argument.as_ptr() as usize).checked_sub(filter.fragment().as_ptr() as usize) {
+        Some(offset) if offset + argument.len() <= filter.fragment().len() => filter.slice(offset..offset + argument.len()),
+        _ => filter,
+    }
+}
+
+/// Parsers a pipe (`|`) separated list of [`Filter`]s.
+///
+/// # Examples
+/// A single filter.
+/// ```rust
+/// use blogs_md_easy::{parse_filters, Filter, Span, TextCase};
+///
+/// // As in {{ £my_variable | lowercase }}
+/// let input = Span::new("| lowercase");
+/// let (_, filters) = parse_filters(input).unwrap();
+/// assert!(matches!(filters[0], Filter::Text { case: TextCase::Lower }));
+/// ```
+///
+/// Multiple filters chained together with `|`.
+/// ```rust
+/// use blogs_md_easy::{parse_filters, Filter, Span, TextCase};
+///
+/// // As in {{ £my_variable | lowercase | truncate = trail: ..! }}
+/// let input = Span::new("| lowercase | truncate = trail: ..!");
+/// let (_, filters) = parse_filters(input).unwrap();
+/// assert!(matches!(filters[0], Filter::Text { case: TextCase::Lower }));
+/// assert!(matches!(filters[1], Filter::Truncate { .. }));
+/// assert_eq!(filters[1], Filter::Truncate {
+///     characters: 100,
+///     trail: "..!".to_string(),
+/// });
+/// ```
+pub fn parse_filters(input: Span) -> IResult<Span, Vec<Filter>> {
+    parse_filters_with(input, &|_| None)
+}
+
+/// Parse a pipe (`|`) separated list of [`Filter`]s, with arguments that are
+/// variables resolved as in [`parse_filter_with`].
+pub fn parse_filters_with<'a>(input: Span<'a>, value_of: &dyn Fn(&str) -> Option<String>) -> IResult<Span<'a>, Vec<Filter>> {
+    preceded(
+        tuple((space0, tag("|"), space0)),
+        separated_list1(tuple((space0, tag("|"), space0)), |input| parse_filter_with(input, value_of))
+    )(input)
+}
+
+/// Parse a meta value that is another variable with filters, such as
+/// `slug = £title | text = kebab-case`, into the name of the variable and its
+/// [`Filter`]s.
+///
+/// The whole value must be the variable and its filters, and there must be at
+/// least one filter, so that a value such as `£5 off` is left as it is.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_meta_filters, Filter, Span, TextCase};
+///
+/// let input = Span::new("£title | text = kebab-case");
+/// let (_, (variable, filters)) = parse_meta_filters(input).unwrap();
+/// assert_eq!(variable.fragment(), &"title");
+/// assert_eq!(filters, vec![Filter::Text { case: TextCase::Kebab }]);
+///
+/// assert!(parse_meta_filters(Span::new("£title")).is_err());
+/// assert!(parse_meta_filters(Span::new("£title | uppercase, and more")).is_err());
+/// ```
+pub fn parse_meta_filters(input: Span) -> IResult<Span, (Span, Vec<Filter>)> {
+    all_consuming(tuple((parse_variable, terminated(parse_filters, space0))))(input)
+}
+
+/// Parse a template [`Placeholder`].
+///
+/// This is a variable name, surrounded by `{{` and `}}`.  \
+/// Whitespace is optional.
+///
+/// # Examples
+/// A simple [`Placeholder`].
+/// ```rust
+/// use blogs_md_easy::{parse_placeholder, Span};
+///
+/// let input = Span::new("{{ £variable }}");
+/// let (_, placeholder) = parse_placeholder(input).unwrap();
+/// assert_eq!(placeholder.name.as_str(), "variable");
+/// assert_eq!(placeholder.selection.start.offset, 0);
+/// assert_eq!(placeholder.selection.end.offset, 16);
+/// ```
+///
+/// A [`Placeholder`] without whitespace.
+/// ```rust
+/// use blogs_md_easy::{parse_placeholder, Span};
+///
+/// let input = Span::new("{{£variable}}");
+/// let (_, placeholder) = parse_placeholder(input).unwrap();
+/// assert_eq!(placeholder.name.as_str(), "variable");
+/// assert_eq!(placeholder.selection.start.offset, 0);
+/// assert_eq!(placeholder.selection.end.offset, 14);
+/// ```
+///
+/// A [`Placeholder`] with a single [`Filter`].
+/// ```rust
+/// use blogs_md_easy::{parse_placeholder, Filter, Span, TextCase};
+///
+/// let input = Span::new("{{ £variable | uppercase }}");
+/// let (_, placeholder) = parse_placeholder(input).unwrap();
+/// assert_eq!(placeholder.name.as_str(), "variable");
+/// assert_eq!(placeholder.selection.start.offset, 0);
+/// assert_eq!(placeholder.selection.end.offset, 28);
+/// assert!(matches!(placeholder.filters[0], Filter::Text { case: TextCase::Upper }));
+/// ```
+///
+/// A [`Placeholder`] with a two [`Filter`]s.
+/// ```rust
+/// use blogs_md_easy::{parse_placeholder, Filter, Span, TextCase};
+///
+/// let input = Span::new("{{ £variable | lowercase | truncate = characters: 42 }}");
+/// let (_, placeholder) = parse_placeholder(input).unwrap();
+/// assert_eq!(placeholder.name.as_str(), "variable");
+/// assert_eq!(placeholder.selection.start.offset, 0);
+/// assert_eq!(placeholder.selection.end.offset, 56);
+/// assert!(matches!(placeholder.filters[0], Filter::Text { case: TextCase::Lower }));
+/// assert_eq!(placeholder.filters[1], Filter::Truncate { characters: 42, trail: "...".to_string() });
+/// ```
+///
+/// A [`Placeholder`] with a default, for when its variable is missing, which
+/// is a [`Filter::Default`] before any others.
+/// ```rust
+/// use blogs_md_easy::{parse_placeholder, Filter, Span, TextCase};
+///
+/// let input = Span::new("{{ £author ?? \"No one\" | uppercase }}");
+/// let (_, placeholder) = parse_placeholder(input).unwrap();
+/// assert_eq!(placeholder.filters, vec![
+///     Filter::Default { value: "No one".to_string() },
+///     Filter::Text { case: TextCase::Upper },
+/// ]);
+/// ```
+pub fn parse_placeholder(input: Span) -> IResult<Span, Placeholder> {
+    parse_placeholder_with(input, &|_| None)
+}
+
+/// Parse a template [`Placeholder`], with [`Filter`] arguments that are
+/// variables resolved as in [`parse_filter_with`].
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_placeholder_with, Filter, Span};
+///
+/// let input = Span::new("{{ £summary | truncate = characters: £excerpt_length }}");
+/// let (_, placeholder) = parse_placeholder_with(input, &|name| {
+///     (name == "excerpt_length").then(|| "20".to_string())
+/// }).unwrap();
+/// assert_eq!(placeholder.filters[0], Filter::Truncate { characters: 20, trail: "...".to_string() });
+/// ```
+pub fn parse_placeholder_with<'a>(input: Span<'a>, value_of: &dyn Fn(&str) -> Option<String>) -> IResult<Span<'a>, Placeholder> {
+    tuple((
+        tuple((tag("{{"), multispace0)),
+        parse_variable,
+        // Once a variable is found, anything else is a malformed placeholder.
+        cut(tuple((
+            opt(preceded(tuple((multispace0, tag("??"), multispace0)), parse_filter_value)),
+            opt(|input| parse_filters_with(input, value_of)),
+            tuple((multispace0, tag("}}"))),
+        ))),
+    ))(input)
+    .map(|(input, (start, variable, (default, filters, end)))| {
+        let mut filters = filters.unwrap_or_default();
+
+        // `?? value` is shorthand for a `default` filter before any others.
+        if let Some(value) = default {
+            filters.insert(0, Filter::Default { value: value.to_string() });
+        }
+
+        // By default, £content will always be parsed as Markdown.
+        if variable.to_ascii_lowercase().as_str() == "content" && !filters.iter().any(|filter| matches!(filter, Filter::Markdown { .. } | Filter::MarkdownInline)) {
+            filters.push(Filter::Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 });
+        }
+
+        (input, Placeholder {
+            name: variable.to_string(),
+            filters,
+            selection: Selection::from(start.0, end.1)
+        })
+    })
+}
+
+/// Parse a [`FeatureTag`].
+///
+/// # Examples
+/// ```rust
+/// use blogs_md_easy::{parse_feature_tag, FeatureTag, Span};
+///
+/// let (_, tag) = parse_feature_tag(Span::new("{{ if feature \"comments\" }}")).unwrap();
+/// assert_eq!(tag, FeatureTag::If("comments".to_string()));
+///
+/// let (_, tag) = parse_feature_tag(Span::new("{{else}}")).unwrap();
+/// assert_eq!(tag, FeatureTag::Else);
+///
+/// let (_, tag) = parse_feature_tag(Span::new("{{ endif }}")).unwrap();
+/// assert_eq!(tag, FeatureTag::EndIf);
+/// ```
+pub fn parse_feature_tag(input: Span) -> IResult<Span, FeatureTag> {
+    delimited(
+        tuple((tag("{{"), multispace0)),
+        alt((
+            preceded(
+                tuple((tag("if"), multispace1, tag("feature"), multispace1)),
+                delimited(tag("\""), is_not("\""), tag("\"")),
+            ).map(|name: Span| FeatureTag::If(name.fragment().trim().to_string())),
+            tag("else").map(|_| FeatureTag::Else),
+            tag("endif").map(|_| FeatureTag::EndIf),
+        )),
+        tuple((multispace0, tag("}}"))),
+    )(input)
+}
+
+/// Parse a [`LoopTag`].
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_loop_tag, LoopTag, Span};
+///
+/// let (_, tag) = parse_loop_tag(Span::new("{{ for £post in £posts }}")).unwrap();
+/// assert_eq!(tag, LoopTag::For { item: "post".to_string(), collection: "posts".to_string() });
+///
+/// let (_, tag) = parse_loop_tag(Span::new("{{endfor}}")).unwrap();
+/// assert_eq!(tag, LoopTag::EndFor);
+/// ```
+pub fn parse_loop_tag(input: Span) -> IResult<Span, LoopTag> {
+    delimited(
+        tuple((tag("{{"), multispace0)),
+        alt((
+            tuple((
+                preceded(tuple((tag("for"), multispace1)), parse_variable),
+                preceded(tuple((multispace1, tag("in"), multispace1)), parse_variable),
+            )).map(|(item, collection)| LoopTag::For {
+                item: item.fragment().to_string(),
+                collection: collection.fragment().to_string(),
+            }),
+            tag("endfor").map(|_| LoopTag::EndFor),
+        )),
+        tuple((multispace0, tag("}}"))),
+    )(input)
+}
+
+/// Parse a `{{ render "path" }}` tag, returning the path of the Markdown that
+/// will be rendered in its place.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_render_tag, Span};
+///
+/// let (_, path) = parse_render_tag(Span::new("{{ render \"fragments/bio.md\" }}")).unwrap();
+/// assert_eq!(path, "fragments/bio.md");
+/// ```
+pub fn parse_render_tag(input: Span) -> IResult<Span, String> {
+    delimited(
+        tuple((tag("{{"), multispace0, tag("render"), multispace1)),
+        delimited(tag("\""), is_not("\""), tag("\"")),
+        tuple((multispace0, tag("}}"))),
+    )(input)
+    .map(|(input, path)| (input, path.fragment().trim().to_string()))
+}
+
+/// Parse an [`IncludeTag`], such as `{{ include "card.html" }}`, along with
+/// any variables that it binds, such as `with title: £title, link: £url`.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_include_tag, Span};
+///
+/// let (_, include) = parse_include_tag(Span::new("{{ include \"partials/card.html\" with title: £title, link: $url }}")).unwrap();
+/// assert_eq!(include.path, "partials/card.html");
+/// assert_eq!(include.bindings, vec![("title".to_string(), "title".to_string()), ("link".to_string(), "url".to_string())]);
+///
+/// let (_, include) = parse_include_tag(Span::new("{{ include \"footer.html\" }}")).unwrap();
+/// assert!(include.bindings.is_empty());
+/// ```
+pub fn parse_include_tag(input: Span) -> IResult<Span, IncludeTag> {
+    let binding = separated_pair(
+        recognize(tuple((
+            take_while_m_n(1, 1, is_alphabetic),
+            many0(alt((alphanumeric1, tag("-"), tag("_")))),
+        ))),
+        tuple((multispace0, tag(":"), multispace0)),
+        parse_variable,
+    );
+    delimited(
+        tuple((tag("{{"), multispace0, tag("include"), multispace1)),
+        tuple((
+            delimited(tag("\""), is_not("\""), tag("\"")),
+            opt(preceded(
+                tuple((multispace1, tag("with"), multispace1)),
+                separated_list1(tuple((multispace0, tag(","), multispace0)), binding),
+            )),
+        )),
+        tuple((multispace0, tag("}}"))),
+    )(input)
+    .map(|(input, (path, bindings))| (input, IncludeTag {
+        path: path.fragment().trim().to_string(),
+        bindings: bindings
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, variable)| (name.fragment().to_string(), variable.fragment().to_string()))
+            .collect(),
+    }))
+}
+
+/// Parse a `{{ ref "post#anchor" }}` tag, returning the reference to another
+/// Markdown, and optionally one of its headings.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_ref_tag, Span};
+///
+/// let (_, reference) = parse_ref_tag(Span::new("{{ ref \"other-post#usage\" }}")).unwrap();
+/// assert_eq!(reference, "other-post#usage");
+/// ```
+pub fn parse_ref_tag(input: Span) -> IResult<Span, String> {
+    delimited(
+        tuple((tag("{{"), multispace0, tag("ref"), multispace1)),
+        delimited(tag("\""), is_not("\""), tag("\"")),
+        tuple((multispace0, tag("}}"))),
+    )(input)
+    .map(|(input, reference)| (input, reference.fragment().trim().to_string()))
+}
+
+/// Parse an [`EmbedTag`], such as `{{ youtube "id" }}`, along with an optional
+/// title.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_embed_tag, EmbedKind, EmbedTag, Span};
+///
+/// let (_, embed) = parse_embed_tag(Span::new("{{ video \"clips/demo.mp4\" }}")).unwrap();
+/// assert_eq!(embed, EmbedTag { kind: EmbedKind::Video, source: "clips/demo.mp4".to_string(), title: None });
+///
+/// let (_, embed) = parse_embed_tag(Span::new("{{ youtube \"dQw4w9WgXcQ\" \"The talk\" }}")).unwrap();
+/// assert_eq!(embed.kind, EmbedKind::YouTube);
+/// assert_eq!(embed.title.as_deref(), Some("The talk"));
+/// ```
+pub fn parse_embed_tag(input: Span) -> IResult<Span, EmbedTag> {
+    delimited(
+        tuple((tag("{{"), multispace0)),
+        tuple((
+            alt((
+                tag("video").map(|_| EmbedKind::Video),
+                tag("audio").map(|_| EmbedKind::Audio),
+                tag("youtube").map(|_| EmbedKind::YouTube),
+                tag("vimeo").map(|_| EmbedKind::Vimeo),
+            )),
+            preceded(multispace1, delimited(tag("\""), is_not("\""), tag("\""))),
+            opt(preceded(multispace1, delimited(tag("\""), is_not("\""), tag("\"")))),
+        )),
+        tuple((multispace0, tag("}}"))),
+    )(input)
+    .map(|(input, (kind, source, title))| (input, EmbedTag {
+        kind,
+        source: source.fragment().trim().to_string(),
+        title: title.map(|title| title.fragment().trim().to_string()),
+    }))
+}
+
+/// Parse a [`ShortcodeTag`], such as `{{ youtube id: dQw4w9WgXcQ }}`, whose
+/// arguments are separated by commas, and can be quoted to include one.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_shortcode_tag, Meta, Span};
+///
+/// let (_, shortcode) = parse_shortcode_tag(Span::new("{{ figure src: cat.jpg, caption: \"My cat, Tom\" }}")).unwrap();
+/// assert_eq!(shortcode.name, "figure");
+/// assert_eq!(shortcode.arguments, vec![Meta::new("src", "cat.jpg"), Meta::new("caption", "My cat, Tom")]);
+///
+/// let (_, shortcode) = parse_shortcode_tag(Span::new("{{ divider }}")).unwrap();
+/// assert!(shortcode.arguments.is_empty());
+/// ```
+pub fn parse_shortcode_tag(input: Span) -> IResult<Span, ShortcodeTag> {
+    fn name(input: Span) -> IResult<Span, Span> {
+        recognize(tuple((
+            take_while_m_n(1, 1, is_alphabetic),
+            many0(alt((alphanumeric1, tag("-"), tag("_")))),
+        )))(input)
+    }
+
+    let argument = separated_pair(
+        name,
+        tuple((multispace0, tag(":"), multispace0)),
+        alt((delimited(tag("\""), take_until("\""), tag("\"")), is_not(",}"))),
+    );
+    delimited(
+        tuple((tag("{{"), multispace0)),
+        tuple((
+            name,
+            opt(preceded(multispace1, separated_list1(tuple((multispace0, tag(","), multispace0)), argument))),
+        )),
+        tuple((multispace0, tag("}}"))),
+    )(input)
+    .map(|(input, (name, arguments))| (input, ShortcodeTag {
+        name: name.fragment().to_string(),
+        arguments: arguments
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, value)| Meta::new(key.fragment(), value.fragment()))
+            .collect(),
+    }))
+}
+
+/// Parse literal text, that must not be treated as a tag, returning the text
+/// that it stands for.
+///
+/// This is either `\{{`, which stands for `{{`, or a `{{ raw }}` block, which
+/// stands for everything up to its `{{ endraw }}`. A `{{ raw }}` without an
+/// `{{ endraw }}` is a failure.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_literal, Span};
+///
+/// let (input, literal) = parse_literal(Span::new("\\{{ £title }}")).unwrap();
+/// assert_eq!(literal.fragment(), &"{{");
+/// assert_eq!(input.fragment(), &" £title }}");
+///
+/// let (input, literal) = parse_literal(Span::new("{{ raw }}{{ £title | uppercase }}{{ endraw }}!")).unwrap();
+/// assert_eq!(literal.fragment(), &"{{ £title | uppercase }}");
+/// assert_eq!(input.fragment(), &"!");
+///
+/// assert!(matches!(parse_literal(Span::new("{{ raw }}{{ £title }}")), Err(nom::Err::Failure(_))));
+/// ```
+pub fn parse_literal(input: Span) -> IResult<Span, Span> {
+    fn raw_tag<'a>(name: &'static str) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, Span<'a>> {
+        recognize(tuple((tag("{{"), multispace0, tag(name), multispace0, tag("}}"))))
+    }
+
+    alt((
+        preceded(tag("\\"), tag("{{")),
+        |input| {
+            let (remaining, _) = raw_tag("raw")(input)?;
+            // Only a `{{` can start the `{{ endraw }}`, so the text in between
+            // is skipped in one go.
+            let mut offset = 0;
+            while let Some(at) = remaining.fragment()[offset..].find("{{") {
+                let (rest, literal) = remaining.take_split(offset + at);
+                if let Ok((rest, _)) = raw_tag("endraw")(rest) {
+                    return Ok((rest, literal));
+                }
+                offset += at + 2;
+            }
+            // An unclosed block is reported where it was opened.
+            Err(nom::Err::Failure(nom::error::Error::new(input, nom::error::ErrorKind::Eof)))
+        },
+    ))(input)
+}
+
+/// Parse a string consuming - and discarding - any character, and stopping at
+/// the first matched placeholder, returning a [`Placeholder`] struct.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{take_till_placeholder, Marker, Placeholder, Selection, Span};
+///
+/// let input = Span::new("Hello, {{ £name }}!");
+/// let (input, placeholder) = take_till_placeholder(input).expect("to parse input");
+/// assert_eq!(input.fragment(), &"!");
+/// assert_eq!(placeholder, Placeholder {
+///     name: "name".to_string(),
+///     selection: Selection {
+///         start: Marker {
+///             line: 1,
+///             offset: 7,
+///         },
+///         end: Marker {
+///             line: 1,
+///             offset: 19,
+///         },
+///     },
+///     filters: vec![],
+/// });
+/// ```
+pub fn take_till_placeholder(input: Span) -> IResult<Span, Placeholder> {
+    let mut input = input;
+    loop {
+        // Only a `{{` can start a placeholder, and only a `\` or `{{` can start
+        // literal text, so the text in between is skipped in one go.
+        let Some(at) = input.fragment().find(['{', '\\']) else {
+            let (input, _) = input.take_split(input.fragment().len());
+            return Err(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Eof)));
+        };
+        let (rest, _) = input.take_split(at);
+
+        match parse_placeholder(rest) {
+            Err(nom::Err::Error(_)) => {},
+            result => return result,
+        }
+        // A placeholder within a `{{` that was never closed, such as
+        // `{{ {{ £title }}`, is most likely a mistake.
+        if let Some(body) = rest.fragment().strip_prefix("{{") {
+            let (inner, _) = rest.take_split(rest.fragment().len() - body.trim_start().len());
+            if parse_placeholder(inner).is_ok() {
+                return Err(nom::Err::Failure(nom::error::Error::new(inner, nom::error::ErrorKind::Tag)));
+            }
+        }
+        // Placeholders within literal text are skipped.
+        input = match parse_literal(rest) {
+            Ok((remaining, _)) => remaining,
+            Err(nom::Err::Error(_)) => rest.take_split(1).0,
+            Err(error) => return Err(error),
+        };
+    }
+}
+
+/// Consume an entire string, and return a Vector of a tuple; where the first
+/// element is a String of the variable name, and the second element is the
+/// [`Placeholder`].
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{parse_placeholder_locations, Span};
+///
+/// let input = Span::new("Hello, {{ £name }}!");
+/// let placeholders = parse_placeholder_locations(input).unwrap();
+/// assert_eq!(placeholders.len(), 1);
+/// assert_eq!(placeholders[0].name.as_str(), "name");
+/// assert_eq!(placeholders[0].selection.start.offset, 7);
+/// assert_eq!(placeholders[0].selection.end.offset, 19);
+/// ```
+///
+/// A malformed [`Placeholder`] returns an [`Error::Placeholder`], pointing at
+/// where it went wrong.
+/// ```rust
+/// use blogs_md_easy::{parse_placeholder_locations, Error, Span};
+///
+/// let input = Span::new("<h1>\n  {{ £title | shout }}\n</h1>");
+/// let Err(Error::Placeholder(diagnostic)) = parse_placeholder_locations(input) else {
+///     panic!("expected a placeholder error");
+/// };
+/// assert_eq!(diagnostic.message, "unknown filter 'shout'");
+/// assert_eq!((diagnostic.line, diagnostic.column), (2, 15));
+/// ```
+///
+/// A placeholder can't contain another, so the `{{` of the inner placeholder
+/// is marked, as the outer one is most likely missing its `}}`.
+/// ```rust
+/// use blogs_md_easy::{parse_placeholder_locations, Error, Span};
+///
+/// let input = Span::new("<h1>{{ £title <small>{{ £subtitle }}</small></h1>");
+/// let Err(Error::Placeholder(diagnostic)) = parse_placeholder_locations(input) else {
+///     panic!("expected a placeholder error");
+/// };
+/// assert_eq!(diagnostic.message, "expected `}}` to close the placeholder, which can't contain another");
+/// assert_eq!((diagnostic.column, diagnostic.length), (22, 2));
+/// ```
+pub fn parse_placeholder_locations(input: Span) -> Result<Vec<Placeholder>, Error> {
+    let mut placeholders = match many0(take_till_placeholder)(input) {
+        Ok((_, placeholders)) => placeholders,
+        Err(nom::Err::Failure(mut e)) => {
+            // A placeholder that is missing its `}}` before another `{{` on the
+            // same line is reported at the `{{`, as it can't contain another.
+            let text = e.input.fragment().split('\n').next().unwrap_or_default();
+            if let (nom::error::ErrorKind::Tag, Some(nested)) = (e.code, text.find("{{").filter(|nested| text.find("}}").is_none_or(|end| *nested < end))) {
+                e.input = e.input.take_split(nested).0;
+            }
+            let message = match e.code {
+                nom::error::ErrorKind::Verify if e.input.is_empty() => "expected a filter name".to_string(),
+                nom::error::ErrorKind::Verify => format!("unknown filter '{}'", e.input.fragment()),
+                nom::error::ErrorKind::MapRes => format!("unknown hash algorithm '{}'", e.input.fragment()),
+                nom::error::ErrorKind::MapOpt => format!("invalid filter argument '{}'", e.input.fragment()),
+                nom::error::ErrorKind::Eof => "expected `{{ endraw }}` to close the raw block".to_string(),
+                _ if e.input.fragment().starts_with("{{") => "expected `}}` to close the placeholder, which can't contain another".to_string(),
+                _ => "expected `}}` to close the placeholder".to_string(),
+            };
+            // An unknown filter or argument, or the `{{` of a nested
+            // placeholder, is marked in full.
+            let length = match e.code {
+                nom::error::ErrorKind::Verify | nom::error::ErrorKind::MapRes | nom::error::ErrorKind::MapOpt => e.input.fragment().len(),
+                _ if e.input.fragment().starts_with("{{") => 2,
+                _ => 0,
+            };
+            Err(Error::Placeholder(Diagnostic::new(e.input, &message).with_length(length)))?
+        },
+        Err(_) => Vec::new(),
+    };
+
+    // Sort in reverse so that when we replace each placeholder, the offsets do
+    // not affect offsets after this point.
+    placeholders.sort_by_key(|placeholder| core::cmp::Reverse(placeholder.selection.start.offset));
+
+    // Replacing placeholders that overlap would corrupt the output.
+    if let Some(placeholder) = overlapping_placeholder(&placeholders) {
+        let (start, end) = (placeholder.selection.start.offset, placeholder.selection.end.offset);
+        let (span, _) = input.take_split(start.saturating_sub(input.location_offset()));
+        Err(Error::Placeholder(Diagnostic::new(span, "placeholder overlaps the one before it").with_length(end - start)))?
+    }
+
+    Ok(placeholders)
+}
+
+/// The first placeholder that starts before the one before it has ended, of
+/// `placeholders` that are sorted in reverse.
+fn overlapping_placeholder(placeholders: &[Placeholder]) -> Option<&Placeholder> {
+    placeholders
+        .windows(2)
+        .find(|pair| pair[1].selection.end.offset > pair[0].selection.start.offset)
+        .map(|pair| &pair[0])
+}
+
+/// Parse every [`Placeholder`] of a template, skipping over any that are
+/// malformed, rather than stopping at the first like
+/// [`parse_placeholder_locations`], along with an [`Error`] for each that was
+/// skipped.
+///
+/// After each error, parsing carries on from the next `{{`, or from the `{{`
+/// of a placeholder that was nested within another, unless it is a
+/// `{{ raw }}` block without an `{{ endraw }}`, after which nothing is parsed.
+/// This allows most of a template to be rendered while it is being edited,
+/// such as for a live preview.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{recover_placeholder_locations, Span};
+///
+/// let input = Span::new("<h1>{{ £title }}</h1>\n<p>{{ £author | shout }}</p>\n<p>{{ £date }}</p>");
+/// let (placeholders, errors) = recover_placeholder_locations(input);
+/// assert_eq!(placeholders.iter().map(|placeholder| placeholder.name.as_str()).collect::<Vec<&str>>(), vec!["date", "title"]);
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(errors[0].diagnostic().message, "unknown filter 'shout'");
+/// ```
+pub fn recover_placeholder_locations(input: Span) -> (Vec<Placeholder>, Vec<Error>) {
+    let mut placeholders = Vec::new();
+    let mut errors = Vec::new();
+    let mut input = input;
+    loop {
+        let error = match parse_placeholder_locations(input) {
+            Ok(found) => {
+                placeholders.extend(found);
+                break;
+            },
+            Err(error) => error,
+        };
+
+        // The placeholders before the malformed one are kept.
+        let at = error.diagnostic().offset - input.location_offset();
+        let before = match input.fragment()[at..].starts_with("{{") {
+            true => at,
+            false => input.fragment()[..at].rfind("{{").unwrap_or(at),
+        };
+        if let Ok(found) = parse_placeholder_locations(input.take(before)) {
+            placeholders.extend(found);
+        }
+        errors.push(error);
+
+        let (rest, _) = input.take_split(at);
+        if matches!(parse_literal(rest), Err(nom::Err::Failure(_))) {
+            break;
+        }
+        // The `{{` of a nested placeholder is parsed again on its own.
+        if at > 0 && rest.fragment().starts_with("{{") {
+            input = rest;
+            continue;
+        }
+        let skip = rest.fragment().chars().next().map(char::len_utf8).unwrap_or_default();
+        match rest.fragment()[skip..].find("{{") {
+            Some(offset) => input = rest.take_split(skip + offset).0,
+            None => break,
+        }
+    }
+
+    placeholders.sort_by_key(|placeholder| core::cmp::Reverse(placeholder.selection.start.offset));
+    (placeholders, errors)
+}
+
+/// Every malformed placeholder of a template, rather than only the first that
+/// [`parse_placeholder_locations`] returns, so that they can be fixed
+/// together, see [`recover_placeholder_locations`].
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{placeholder_errors, Span};
+///
+/// let errors = placeholder_errors(Span::new("<h1>{{ £title | shout }}</h1>\n<p>{{ £author </p>\n<p>{{ £date }}</p>"));
+/// assert_eq!(errors.len(), 2);
+/// assert_eq!(errors[0].diagnostic().message, "unknown filter 'shout'");
+/// assert_eq!(errors[1].diagnostic().line, 2);
+///
+/// assert!(placeholder_errors(Span::new("<h1>{{ £title }}</h1>")).is_empty());
+/// ```
+pub fn placeholder_errors(input: Span) -> Vec<Error> {
+    recover_placeholder_locations(input).1
+}
@@ -0,0 +1,148 @@
+//! Where each output of a batch is written, whether beside its Markdown, in the
+//! output directory, or at a permalink such as `/:year/:slug/`.
+
+use std::{collections::HashMap, error::Error, ffi::OsStr, path::{Path, PathBuf}};
+use crate::{batch::BatchOptions, meta::{extract_meta, extract_title}, render::{derived_publish_date, forward_slashes, get_output_path, path_variables, slugify}};
+
+////////////////////////////////////////////////////////////////////////////////
+// Functions
+/// Where [`render_batch`] writes a Markdown, mirroring the input directory if
+/// the Markdown is within one.
+pub(crate) fn batch_output_path(template_path: &Path, markdown_path: &Path, options: &BatchOptions, permalinks: &HashMap<PathBuf, PathBuf>, multiple_templates: bool) -> PathBuf {
+    if let Some(permalink) = permalinks.get(markdown_path) {
+        return match multiple_templates {
+            true => permalink.with_file_name(format!(
+                "{}-{}",
+                &template_path.file_stem().unwrap_or_default().to_string_lossy(),
+                permalink.file_stem().unwrap_or_default().to_string_lossy()
+            )).with_extension("html"),
+            false => permalink.to_owned(),
+        };
+    }
+    let output_dir = options.output_dir.as_ref().map(|output_dir| {
+        options.input_dirs
+            .iter()
+            .find_map(|input_dir| forward_slashes(markdown_path).parent()?.strip_prefix(forward_slashes(input_dir)).ok().map(Path::to_path_buf))
+            .map(|relative| output_dir.join(relative))
+            .unwrap_or(output_dir.to_owned())
+    });
+    get_output_path(template_path, markdown_path, output_dir.as_deref(), multiple_templates)
+}
+
+/// Expand a permalink `pattern`, such as `/:year/:month/:slug/`, with the
+/// `variables` of a Markdown, see [`BatchOptions::permalink_pattern`].
+///
+/// Each `:name`, of letters, digits and underscores, is the variable of that
+/// name. The `:year`, `:month`, and `:day` are those of the `date`, or
+/// otherwise the `publish_date`, and `:title` is the title as a slug.
+///
+/// # Example
+/// ```rust
+/// use std::collections::HashMap;
+/// use blogs_md_easy::expand_permalink;
+///
+/// let variables = HashMap::from([
+///     ("title".to_string(), "Hello, World!".to_string()),
+///     ("slug".to_string(), "hello".to_string()),
+///     ("date".to_string(), "2024-05-01".to_string()),
+/// ]);
+/// assert_eq!(expand_permalink("/:year/:month/:slug/", &variables).unwrap(), "/2024/05/hello/");
+/// assert_eq!(expand_permalink("/posts/:title.html", &variables).unwrap(), "/posts/hello-world.html");
+///
+/// // Windows separators are separators on every platform.
+/// assert_eq!(expand_permalink(r"\:year\:slug\", &variables).unwrap(), "/2024/hello/");
+///
+/// // Every variable must exist.
+/// assert!(expand_permalink("/:category/:slug/", &variables).is_err());
+///
+/// // Nor can a segment be empty, `.`, or `..`.
+/// assert!(expand_permalink("/posts//:slug/", &variables).is_err());
+/// assert!(expand_permalink("/posts/../:slug/", &variables).is_err());
+/// ```
+pub fn expand_permalink(pattern: &str, variables: &HashMap<String, String>) -> Result<String, Box<dyn Error>> {
+    let normalised = pattern.replace('\\', "/");
+    let mut expanded = String::with_capacity(pattern.len());
+    let mut rest = normalised.as_str();
+    while let Some(start) = rest.find(':') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        let end = rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(rest.len());
+        let (name, after) = rest.split_at(end);
+        rest = after;
+
+        let value = match name {
+            // A colon that isn't followed by a name is left as it is.
+            "" => {
+                expanded.push(':');
+                continue;
+            },
+            "year" | "month" | "day" => {
+                let date = variables.get("date").or(variables.get("publish_date"))
+                    .ok_or(format!("The permalink '{}' needs a 'date' or 'publish_date'.", pattern))?;
+                let is_date = |date: &&str| date.char_indices().all(|(i, c)| if i == 4 || i == 7 { c == '-' } else { c.is_ascii_digit() });
+                let day = date.trim().get(..10).filter(is_date)
+                    .ok_or(format!("The permalink '{}' needs a date, but was '{}'.", pattern, date))?;
+                match name {
+                    "year" => day[..4].to_string(),
+                    "month" => day[5..7].to_string(),
+                    _ => day[8..].to_string(),
+                }
+            },
+            "title" => slugify(variables.get("title").ok_or(format!("The permalink '{}' needs a 'title'.", pattern))?),
+            name => variables.get(name).ok_or(format!("The permalink '{}' needs a '{}'.", pattern, name))?.trim().to_string(),
+        };
+        // A variable can't write outside of the output directory.
+        if matches!(value.as_str(), "" | "." | "..") || value.contains(['/', '\\']) {
+            Err(format!("The '{}' of the permalink '{}' can't be '{}'.", name, pattern, value))?;
+        }
+        expanded.push_str(&value);
+    }
+    expanded.push_str(rest);
+    // Nor can the pattern, and an empty or `.` segment is never intended.
+    let segments = expanded.trim_matches('/');
+    if !segments.is_empty() && segments.split('/').any(|segment| matches!(segment, "" | "." | "..")) {
+        Err(format!("The permalink '{}' can't have an empty, '.', or '..' segment, but was '{}'.", pattern, expanded))?;
+    }
+
+    Ok(expanded)
+}
+
+/// Where each of the `markdowns` is written with the
+/// [`BatchOptions::permalink_pattern`] and the template at `template_path`,
+/// which is empty without a pattern.
+pub(crate) fn permalinks(template_path: &Path, markdowns: &[(PathBuf, String)], options: &BatchOptions) -> Result<HashMap<PathBuf, PathBuf>, Box<dyn Error>> {
+    let Some(pattern) = &options.permalink_pattern else {
+        return Ok(HashMap::new());
+    };
+    let template_ext = template_path.extension().unwrap_or(OsStr::new("html"));
+
+    let mut permalinks = HashMap::with_capacity(markdowns.len());
+    let mut written = HashMap::with_capacity(markdowns.len());
+    for (markdown_path, markdown) in markdowns {
+        let mut variables = path_variables(markdown_path).map(|(key, value)| (key.to_string(), value)).into_iter().collect::<HashMap<String, String>>();
+        variables.extend(extract_meta(markdown).into_iter().map(|meta| (meta.key, meta.value)));
+        if let Some(title) = extract_title(markdown) {
+            variables.insert("title".to_string(), title);
+        }
+        if !variables.contains_key("publish_date") {
+            if let Some(date) = derived_publish_date(markdown_path, &options.publish_date_from) {
+                variables.insert("publish_date".to_string(), date);
+            }
+        }
+        let expanded = expand_permalink(pattern, &variables)
+            .map_err(|e| format!("{} In markdown '{}'.", e, markdown_path.to_string_lossy()))?;
+
+        let mut path = options.output_dir.to_owned().unwrap_or_default().join(expanded.trim_start_matches(['/', '\\']));
+        if expanded.is_empty() || expanded.ends_with(['/', '\\']) {
+            path.push(Path::new("index").with_extension(template_ext));
+        } else if path.extension().is_none() {
+            path.set_extension(template_ext);
+        }
+        if let Some(other) = written.insert(path.to_owned(), markdown_path) {
+            Err(format!("The markdowns '{}' and '{}' have the same permalink '{}'.", other.to_string_lossy(), markdown_path.to_string_lossy(), path.to_string_lossy()))?;
+        }
+        permalinks.insert(markdown_path.to_owned(), path);
+    }
+
+    Ok(permalinks)
+}
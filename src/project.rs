@@ -0,0 +1,2240 @@
+//! Themes, archetypes, blogrolls, webmentions, and fediverse accounts, which
+//! are shared between the Markdowns of a project, along with statistics about
+//! them.
+
+use std::{collections::{BTreeMap, BTreeSet, HashMap}, error::Error, fmt, fs, path::{Path, PathBuf}};
+use crate::{error::{Error as ParseError, SourceError}, filters::days_from_civil, listing::Post, meta::{escape_html, load_variables, split_meta_list, Meta}, parser::{parse_meta_section, Span, Syntax}, render::{apply_fragments, apply_includes, apply_whitespace_control, create_variables, read_file, slugify, strip_features, MetaSchema, MissingVariablePolicy, Template, Warning}};
+#[cfg(feature = "toml")]
+use std::path::Component;
+#[cfg(feature = "toml")]
+use crate::{meta::{flatten_meta, toml_to_json}, render::{hash_hex, Alternate, MetaRule, Profile}};
+#[cfg(feature = "json")]
+use crate::{batch::BatchProgress, filters::{render_filter, Filter}, render::html_to_text};
+
+////////////////////////////////////////////////////////////////////////////////
+// Structs and types
+/// A shareable collection of templates, static assets and default variables.
+///
+/// A theme is a directory containing a `theme.toml` manifest.
+/// ```toml
+/// name = "minimal"
+/// # Optional, defaults to every file in the `templates` directory.
+/// templates = ["templates/post.html"]
+///
+/// # Default variables for every Markdown.
+/// [variables]
+/// site_name = "My Blog"
+/// ```
+/// Any files in the `static` directory, such as stylesheets and images, are
+/// copied into the output directory.
+///
+/// Requires the `toml` feature.
+#[cfg(feature = "toml")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    /// The name of the theme, defaults to the name of its directory.
+    pub name: String,
+    /// The directory that contains the `theme.toml` manifest.
+    pub path: PathBuf,
+    /// The templates that Markdowns will be rendered into.
+    pub templates: Vec<PathBuf>,
+    /// Default variables, which are flattened using dot notation in the same
+    /// way as TOML frontmatter.
+    pub variables: Vec<Meta>,
+}
+
+#[cfg(feature = "toml")]
+impl Theme {
+    /// The name of the manifest file within a theme's directory.
+    pub const MANIFEST: &'static str = "theme.toml";
+
+    /// Read the `theme.toml` manifest within the `path` directory.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::path::Path;
+    /// use blogs_md_easy::{Meta, Theme};
+    ///
+    /// let theme = Theme::load(Path::new("tests/theme")).unwrap();
+    /// assert_eq!(theme.name, "example");
+    /// assert_eq!(theme.templates, vec![Path::new("tests/theme/templates/post.html")]);
+    /// assert_eq!(theme.variables, vec![Meta::new("site_name", "Example Blog")]);
+    /// ```
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let manifest = path.join(Self::MANIFEST);
+        let mut table = read_file(&manifest)
+            .map_err(|_| format!("The theme '{}' does not contain a {}.", path.to_string_lossy(), Self::MANIFEST))?
+            .parse::<toml::Table>()
+            .map_err(|e| format!("Unable to parse '{}': {}", manifest.to_string_lossy(), e.message()))?;
+
+        let name = match table.remove("name") {
+            Some(toml::Value::String(name)) => name,
+            _ => path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        };
+        // The name is the directory that the theme is installed into.
+        if name.contains(['/', '\\']) || Path::new(&name).components().count() != 1 || !is_within(Path::new(&name)) {
+            Err(format!("The theme name '{}' in '{}' must be a single directory name.", name, manifest.to_string_lossy()))?;
+        }
+
+        let templates = match table.remove("templates") {
+            Some(toml::Value::Array(templates)) => templates
+                .iter()
+                .filter_map(|template| template.as_str())
+                .map(|template| match is_within(Path::new(template)) {
+                    true => Ok(path.join(template)),
+                    false => Err(format!("The template '{}' in '{}' must be within the theme.", template, manifest.to_string_lossy())),
+                })
+                .collect::<Result<Vec<PathBuf>, String>>()?,
+            _ => {
+                let mut templates = fs::read_dir(path.join("templates"))
+                    .map(|entries| entries
+                        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                        .filter(|template| template.is_file())
+                        .collect::<Vec<PathBuf>>()
+                    )
+                    .unwrap_or_default();
+                templates.sort();
+                templates
+            },
+        };
+
+        let mut variables = Vec::new();
+        if let Some(table @ toml::Value::Table(_)) = table.remove("variables") {
+            flatten_meta("", &toml_to_json(table), &mut variables);
+        }
+
+        Ok(Self { name, path: path.to_owned(), templates, variables })
+    }
+
+    /// The directory of static assets, which may not exist.
+    pub fn static_dir(&self) -> PathBuf {
+        self.path.join("static")
+    }
+}
+
+/// The markup of a generated list, such as the [`Blogroll`], which is wrapped
+/// in a labelled landmark, so that it can be styled, and found by screen
+/// readers.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{ListMarkup, Meta};
+///
+/// let markup = ListMarkup::new("aside", "blogroll", "Blogroll", "ul");
+/// assert_eq!(markup.wrap("<li>Jane</li>\n"), "<aside class=\"blogroll\" aria-label=\"Blogroll\">\n<ul>\n<li>Jane</li>\n</ul>\n</aside>");
+///
+/// let variables = vec![Meta::new("blogroll.wrapper", "nav"), Meta::new("blogroll.class", "friends links")];
+/// let markup = markup.with_variables("blogroll", &variables).unwrap();
+/// assert_eq!(markup.wrap(""), "<nav class=\"friends links\" aria-label=\"Blogroll\">\n<ul>\n</ul>\n</nav>");
+///
+/// // Only elements can be used.
+/// let variables = vec![Meta::new("blogroll.wrapper", "nav onclick")];
+/// assert!(markup.with_variables("blogroll", &variables).is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ListMarkup {
+    /// The landmark element that wraps the list, such as `nav`, `aside` or
+    /// `section`.
+    pub wrapper: String,
+    /// The class of the wrapper, which may be empty.
+    pub class: String,
+    /// The `aria-label` of the wrapper, which names the landmark.
+    pub label: String,
+    /// The list element, either `ul` or `ol`.
+    pub list: String,
+}
+
+impl ListMarkup {
+    /// The markup of a list, with the `wrapper` and `list` element names.
+    pub fn new(wrapper: &str, class: &str, label: &str, list: &str) -> Self {
+        Self {
+            wrapper: wrapper.to_string(),
+            class: class.to_string(),
+            label: label.to_string(),
+            list: list.to_string(),
+        }
+    }
+
+    /// Override the markup with the variables `name.wrapper`, `name.class`,
+    /// `name.label` and `name.list`.
+    pub fn with_variables(mut self, name: &str, variables: &[Meta]) -> Result<Self, Box<dyn Error>> {
+        for meta in variables {
+            let Some(field) = meta.key.strip_prefix(name).and_then(|key| key.strip_prefix('.')) else {
+                continue;
+            };
+            match field {
+                "wrapper" if !meta.value.is_empty() && meta.value.chars().all(|c| c.is_ascii_alphanumeric()) => self.wrapper = meta.value.to_owned(),
+                "wrapper" => Err(format!("Unable to use '{}' as the wrapper of the {}, expected an element name, such as nav.", meta.value, name))?,
+                "class" => self.class = meta.value.to_owned(),
+                "label" => self.label = meta.value.to_owned(),
+                "list" if meta.value == "ul" || meta.value == "ol" => self.list = meta.value.to_owned(),
+                "list" => Err(format!("Unable to use '{}' as the list of the {}, expected ul or ol.", meta.value, name))?,
+                _ => {},
+            }
+        }
+        Ok(self)
+    }
+
+    /// Wrap the `items` of a list, which are `<li>` elements.
+    pub fn wrap(&self, items: &str) -> String {
+        let mut attributes = String::new();
+        if !self.class.is_empty() {
+            attributes.push_str(&format!(" class=\"{}\"", escape_html(&self.class)));
+        }
+        if !self.label.is_empty() {
+            attributes.push_str(&format!(" aria-label=\"{}\"", escape_html(&self.label)));
+        }
+        format!("<{0}{1}>\n<{2}>\n{3}</{2}>\n</{0}>", self.wrapper, attributes, self.list, items)
+    }
+}
+
+/// A blog that is recommended in a [`Blogroll`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Blog {
+    /// The name of the blog.
+    pub title: String,
+    /// The homepage of the blog.
+    pub url: String,
+    /// The RSS or Atom feed of the blog, if it has one.
+    pub feed: Option<String>,
+    /// Why the blog is worth reading.
+    pub description: Option<String>,
+}
+
+/// A webring, which links each member to the previous and next sites in the
+/// ring.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Webring {
+    /// The key of the ring, which names its variables.
+    pub name: String,
+    /// The name of the ring, defaults to its key.
+    pub title: String,
+    /// The homepage of the ring, if it has one.
+    pub home: Option<String>,
+    /// The site before this one in the ring.
+    pub prev: Option<String>,
+    /// The site after this one in the ring.
+    pub next: Option<String>,
+}
+
+/// The blogs that a site recommends, and the webrings that it belongs to.
+///
+/// A blogroll is read from a variables file, in any format that
+/// [`load_variables`](crate::meta::load_variables) can read, with a table for
+/// each blog and ring.
+/// ```toml
+/// [blogs.jane]
+/// title = "Jane's Blog"
+/// url = "https://jane.example"
+/// feed = "https://jane.example/feed.xml"
+///
+/// [rings.rust]
+/// title = "The Rust Webring"
+/// prev = "https://prev.example"
+/// next = "https://next.example"
+/// ```
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{Blogroll, Meta};
+///
+/// let blogroll = Blogroll::new(vec![
+///     Meta::new("blogs.jane.title", "Jane's Blog"),
+///     Meta::new("blogs.jane.url", "https://jane.example"),
+///     Meta::new("rings.rust.next", "https://next.example"),
+/// ]).unwrap();
+///
+/// assert_eq!(blogroll.blogs[0].title, "Jane's Blog");
+/// assert_eq!(blogroll.to_html(), "<aside class=\"blogroll\" aria-label=\"Blogroll\">\n<ul>\n<li><a href=\"https://jane.example\">Jane&#39;s Blog</a></li>\n</ul>\n</aside>");
+/// assert!(blogroll.variables().contains(&Meta::new("webring.rust.next", "https://next.example")));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Blogroll {
+    /// The recommended blogs, in alphabetical order of their title.
+    pub blogs: Vec<Blog>,
+    /// The webrings, in alphabetical order of their name.
+    pub rings: Vec<Webring>,
+    /// The markup of `£blogroll`, which defaults to an `<aside>`.
+    pub markup: ListMarkup,
+}
+
+impl Default for Blogroll {
+    fn default() -> Self {
+        Self {
+            blogs: vec![],
+            rings: vec![],
+            markup: ListMarkup::new("aside", "blogroll", "Blogroll", "ul"),
+        }
+    }
+}
+
+impl Blogroll {
+    /// Group the flattened `variables` of a blogroll file into its blogs and
+    /// rings.
+    pub fn new(variables: Vec<Meta>) -> Result<Self, Box<dyn Error>> {
+        let mut blogs: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+        let mut rings: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+        for meta in variables {
+            let (group, name, field) = match meta.key.splitn(3, '.').collect::<Vec<&str>>()[..] {
+                [group, name, field] => (group, name, field),
+                _ => Err(format!("Unexpected '{}' in the blogroll, expected a table of blogs or rings.", meta.key))?,
+            };
+            let groups = match group {
+                "blogs" => &mut blogs,
+                "rings" => &mut rings,
+                _ => Err(format!("Unexpected '{}' in the blogroll, expected a table of blogs or rings.", meta.key))?,
+            };
+            groups.entry(name.to_string()).or_default().insert(field.to_string(), meta.value);
+        }
+
+        let mut blogs = blogs
+            .into_iter()
+            .map(|(name, mut fields)| Ok(Blog {
+                url: fields.remove("url").ok_or(format!("The blog '{}' in the blogroll has no url.", name))?,
+                title: fields.remove("title").unwrap_or(name),
+                feed: fields.remove("feed"),
+                description: fields.remove("description"),
+            }))
+            .collect::<Result<Vec<Blog>, Box<dyn Error>>>()?;
+        blogs.sort_by_key(|blog| blog.title.to_lowercase());
+
+        let rings = rings
+            .into_iter()
+            .map(|(name, mut fields)| Webring {
+                title: fields.remove("title").unwrap_or(name.to_owned()),
+                home: fields.remove("home"),
+                prev: fields.remove("prev"),
+                next: fields.remove("next"),
+                name,
+            })
+            .collect();
+
+        Ok(Self { blogs, rings, ..Default::default() })
+    }
+
+    /// Read a blogroll file.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Self::new(load_variables(path)?).map_err(|e| format!("{} In '{}'.", e, path.to_string_lossy()).into())
+    }
+
+    /// The blogs as an OPML document, so that they can be imported into a
+    /// feed reader.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{Blog, Blogroll};
+    ///
+    /// let blogroll = Blogroll {
+    ///     blogs: vec![Blog { title: "Jane".to_string(), url: "https://jane.example".to_string(), feed: Some("https://jane.example/feed.xml".to_string()), description: None }],
+    ///     ..Default::default()
+    /// };
+    /// assert!(blogroll.to_opml("Blogroll").contains("<outline type=\"rss\" text=\"Jane\" title=\"Jane\" xmlUrl=\"https://jane.example/feed.xml\" htmlUrl=\"https://jane.example\"/>"));
+    /// ```
+    pub fn to_opml(&self, title: &str) -> String {
+        let outlines = self.blogs
+            .iter()
+            .map(|blog| {
+                let title = escape_html(&blog.title);
+                let url = escape_html(&blog.url);
+                match &blog.feed {
+                    Some(feed) => format!("    <outline type=\"rss\" text=\"{title}\" title=\"{title}\" xmlUrl=\"{}\" htmlUrl=\"{url}\"/>\n", escape_html(feed)),
+                    None => format!("    <outline type=\"link\" text=\"{title}\" url=\"{url}\"/>\n"),
+                }
+            })
+            .collect::<String>();
+
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n  <head>\n    <title>{}</title>\n  </head>\n  <body>\n{}  </body>\n</opml>\n",
+            escape_html(title),
+            outlines,
+        )
+    }
+
+    /// The blogs as an HTML list, with a link to each, followed by its
+    /// description, see [`Blogroll::markup`].
+    pub fn to_html(&self) -> String {
+        let items = self.blogs
+            .iter()
+            .map(|blog| format!(
+                "<li><a href=\"{}\">{}</a>{}</li>\n",
+                escape_html(&blog.url),
+                escape_html(&blog.title),
+                blog.description.as_deref().map(|description| format!(" {}", escape_html(description))).unwrap_or_default(),
+            ))
+            .collect::<String>();
+
+        self.markup.wrap(&items)
+    }
+
+    /// The variables for every Markdown: `blogroll`, the blogs as HTML, and for
+    /// each ring, `webring.name.title`, along with `webring.name.home`,
+    /// `webring.name.prev` and `webring.name.next` for those it has.
+    pub fn variables(&self) -> Vec<Meta> {
+        let mut variables = vec![Meta::new("blogroll", &self.to_html())];
+        for ring in &self.rings {
+            variables.push(Meta::new(&format!("webring.{}.title", ring.name), &ring.title));
+            for (field, url) in [("home", &ring.home), ("prev", &ring.prev), ("next", &ring.next)] {
+                if let Some(url) = url {
+                    variables.push(Meta::new(&format!("webring.{}.{}", ring.name, field), url));
+                }
+            }
+        }
+        variables
+    }
+}
+
+/// A mention of a post by another site, which was received by its webmention
+/// endpoint.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Webmention {
+    /// The page that mentions the post.
+    pub source: String,
+    /// The URL of the post that was mentioned.
+    pub target: String,
+    /// How the post was mentioned, such as `in-reply-to`, `like-of`,
+    /// `repost-of`, `bookmark-of` or `mention-of`.
+    pub kind: String,
+    /// The name of whoever mentioned the post.
+    pub author: String,
+    /// The homepage of the author.
+    pub author_url: Option<String>,
+    /// When the mention was published.
+    pub published: Option<String>,
+    /// The text of a reply or mention.
+    pub content: Option<String>,
+}
+
+impl Webmention {
+    /// A short description of the mention, such as `liked this`.
+    fn verb(&self) -> &'static str {
+        match self.kind.as_str() {
+            "in-reply-to" => "replied",
+            "like-of" => "liked this",
+            "repost-of" => "reposted this",
+            "bookmark-of" => "bookmarked this",
+            _ => "mentioned this",
+        }
+    }
+}
+
+/// The endpoints that a site receives mentions on, and the mentions that it
+/// has received.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{Webmention, Webmentions};
+///
+/// let webmentions = Webmentions {
+///     endpoint: Some("https://webmention.io/example.com/webmention".to_string()),
+///     pingback: None,
+///     mentions: vec![Webmention {
+///         source: "https://jane.example/likes/1".to_string(),
+///         target: "https://example.com/posts/hello.html".to_string(),
+///         kind: "like-of".to_string(),
+///         author: "Jane".to_string(),
+///         ..Default::default()
+///     }],
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(webmentions.link_tags(), "<link rel=\"webmention\" href=\"https://webmention.io/example.com/webmention\">\n");
+/// assert_eq!(webmentions.for_path("posts/hello.html").len(), 1);
+/// assert_eq!(webmentions.for_path("posts/hello/").len(), 1);
+/// assert!(webmentions.for_path("hello.html").is_empty());
+/// assert_eq!(webmentions.to_html("posts/hello.html"), "<section class=\"webmentions\" aria-label=\"Webmentions\">\n<ol>\n<li class=\"webmention like-of\"><a href=\"https://jane.example/likes/1\">Jane</a> <a href=\"https://jane.example/likes/1\">liked this</a></li>\n</ol>\n</section>");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Webmentions {
+    /// The webmention endpoint, advertised with `<link rel="webmention">`.
+    pub endpoint: Option<String>,
+    /// The pingback endpoint, advertised with `<link rel="pingback">`.
+    pub pingback: Option<String>,
+    /// Every mention that has been received.
+    pub mentions: Vec<Webmention>,
+    /// The markup of `£webmentions`, which defaults to a `<section>`.
+    pub markup: ListMarkup,
+}
+
+impl Default for Webmentions {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            pingback: None,
+            mentions: vec![],
+            markup: ListMarkup::new("section", "webmentions", "Webmentions", "ol"),
+        }
+    }
+}
+
+impl Webmentions {
+    /// Parse the mentions from JF2 JSON, as exported by webmention.io, which is
+    /// either a feed with the mentions as its `children`, or a list of them.
+    ///
+    /// Requires the `webmentions` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::Webmentions;
+    ///
+    /// let json = r#"{"children": [{"author": {"name": "Jane", "url": "https://jane.example"}, "url": "https://jane.example/reply", "wm-target": "https://example.com/hello.html", "wm-property": "in-reply-to", "content": {"text": "Great post!"}}]}"#;
+    /// let mentions = Webmentions::parse(json).unwrap();
+    /// assert_eq!(mentions[0].author, "Jane");
+    /// assert_eq!(mentions[0].kind, "in-reply-to");
+    /// assert_eq!(mentions[0].content.as_deref(), Some("Great post!"));
+    /// ```
+    #[cfg(feature = "webmentions")]
+    pub fn parse(json: &str) -> Result<Vec<Webmention>, Box<dyn Error>> {
+        use serde_json::Value;
+
+        let value = serde_json::from_str::<Value>(json).map_err(|e| format!("Unable to parse the webmentions: {}", e))?;
+        let entries = match &value {
+            Value::Array(entries) => entries,
+            Value::Object(feed) => match feed.get("children") {
+                Some(Value::Array(entries)) => entries,
+                _ => Err("Unable to parse the webmentions: expected a list of `children`.".to_string())?,
+            },
+            _ => Err("Unable to parse the webmentions: expected a feed or a list.".to_string())?,
+        };
+
+        let text = |value: Option<&Value>| value.and_then(Value::as_str).filter(|value| !value.is_empty()).map(str::to_string);
+        Ok(entries
+            .iter()
+            .filter_map(|entry| {
+                let kind = text(entry.get("wm-property")).unwrap_or("mention-of".to_string());
+                let target = text(entry.get("wm-target")).or_else(|| text(entry.get(&kind)))?;
+                let source = text(entry.get("wm-source")).or_else(|| text(entry.get("url")))?;
+                let author = entry.get("author");
+                Some(Webmention {
+                    author: text(author.and_then(|author| author.get("name"))).unwrap_or("Someone".to_string()),
+                    author_url: text(author.and_then(|author| author.get("url"))),
+                    published: text(entry.get("published")).or_else(|| text(entry.get("wm-received"))),
+                    content: text(entry.get("content").and_then(|content| content.get("text"))),
+                    source,
+                    target,
+                    kind,
+                })
+            })
+            .collect())
+    }
+
+    /// Read the mentions from a JF2 file, or fetch them from a URL, such as the
+    /// webmention.io API, see [`Webmentions::parse`].
+    ///
+    /// Requires the `webmentions` feature.
+    #[cfg(feature = "webmentions")]
+    pub fn load(source: &str) -> Result<Vec<Webmention>, Box<dyn Error>> {
+        let json = match source.starts_with("http://") || source.starts_with("https://") {
+            true => ureq::get(source)
+                .call()
+                .and_then(|mut response| response.body_mut().read_to_string())
+                .map_err(|e| format!("Unable to fetch the webmentions from '{}': {}", source, e))?,
+            false => read_file(Path::new(source))?,
+        };
+
+        Self::parse(&json).map_err(|e| format!("{} In '{}'.", e, source).into())
+    }
+
+    /// The `<link>` tags that advertise the endpoints, for the `<head>` of each
+    /// page.
+    pub fn link_tags(&self) -> String {
+        [("webmention", &self.endpoint), ("pingback", &self.pingback)]
+            .into_iter()
+            .filter_map(|(rel, href)| href.as_ref().map(|href| format!("<link rel=\"{}\" href=\"{}\">\n", rel, escape_html(href))))
+            .collect()
+    }
+
+    /// The mentions of the page at `path`, relative to the root of the site,
+    /// oldest first.
+    ///
+    /// The scheme and host of each target are ignored, as are a `.html`
+    /// extension, and an `index.html` file name.
+    pub fn for_path(&self, path: &str) -> Vec<&Webmention> {
+        /// The path of a URL, without anything that may differ between links
+        /// to the same page.
+        fn page(url: &str) -> &str {
+            let path = match url.split_once("://") {
+                Some((_, url)) => url.find('/').map(|offset| &url[offset..]).unwrap_or_default(),
+                None => url,
+            };
+            let path = path.split(['?', '#']).next().unwrap_or_default();
+            let path = path.strip_suffix(".html").unwrap_or(path);
+            let path = path.trim_matches('/');
+            match path {
+                "index" => "",
+                path => path.strip_suffix("/index").unwrap_or(path),
+            }
+        }
+
+        let path = page(path);
+        let mut mentions = self.mentions.iter().filter(|mention| page(&mention.target) == path).collect::<Vec<&Webmention>>();
+        mentions.sort_by(|a, b| a.published.cmp(&b.published));
+        mentions
+    }
+
+    /// The mentions of the page at `path` as an HTML list, or nothing if it
+    /// hasn't been mentioned, see [`Webmentions::for_path`] and
+    /// [`Webmentions::markup`].
+    pub fn to_html(&self, path: &str) -> String {
+        let mentions = self.for_path(path);
+        if mentions.is_empty() {
+            return String::new();
+        }
+
+        let items = mentions
+            .iter()
+            .map(|mention| format!(
+                "<li class=\"webmention {}\"><a href=\"{}\">{}</a> <a href=\"{}\">{}</a>{}</li>\n",
+                escape_html(&mention.kind),
+                escape_html(mention.author_url.as_deref().unwrap_or(&mention.source)),
+                escape_html(&mention.author),
+                escape_html(&mention.source),
+                mention.verb(),
+                mention.content.as_deref().map(|content| format!(": {}", escape_html(content))).unwrap_or_default(),
+            ))
+            .collect::<String>();
+
+        self.markup.wrap(&items)
+    }
+}
+
+/// The fediverse accounts that posts are attributed to, with a
+/// `fediverse:creator` meta tag, so that links shared on Mastodon show their
+/// author.
+///
+/// The accounts are read from a variables file, in any format that
+/// [`load_variables`](crate::meta::load_variables) can read, with the account
+/// of each `author` of a post, and a `creator` for any others.
+/// ```toml
+/// creator = "@blog@example.social"
+///
+/// [authors]
+/// "Jane Doe" = "@jane@example.social"
+/// ```
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{Fediverse, Meta};
+///
+/// let fediverse = Fediverse::new(vec![
+///     Meta::new("creator", "@blog@example.social"),
+///     Meta::new("authors.Jane Doe", "@jane@example.social"),
+/// ]).unwrap();
+///
+/// assert_eq!(fediverse.creator(Some("Jane Doe")), Some("@jane@example.social"));
+/// assert_eq!(fediverse.creator(Some("John Doe")), Some("@blog@example.social"));
+/// assert_eq!(Fediverse::meta_tag("@jane@example.social"), "<meta name=\"fediverse:creator\" content=\"@jane@example.social\">\n");
+///
+/// // Handles include the server.
+/// assert!(Fediverse::new(vec![Meta::new("creator", "@blog")]).is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Fediverse {
+    /// The account of any post whose author doesn't have their own.
+    pub creator: Option<String>,
+    /// The account of each author, by their name.
+    pub authors: Vec<Meta>,
+}
+
+impl Fediverse {
+    /// Read the accounts from the flattened `variables` of a fediverse file.
+    pub fn new(variables: Vec<Meta>) -> Result<Self, Box<dyn Error>> {
+        let mut fediverse = Self::default();
+        for meta in variables {
+            if !Self::is_handle(&meta.value) {
+                Err(format!("Unable to parse the fediverse handle '{}', expected @user@example.social.", meta.value))?;
+            }
+            match meta.key.split_once('.') {
+                None if meta.key == "creator" => fediverse.creator = Some(meta.value),
+                Some(("authors", author)) => fediverse.authors.push(Meta::new(author, &meta.value)),
+                _ => Err(format!("Unexpected '{}' in the fediverse accounts, expected a creator or authors.", meta.key))?,
+            }
+        }
+
+        Ok(fediverse)
+    }
+
+    /// Read a file of fediverse accounts.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Self::new(load_variables(path)?).map_err(|e| format!("{} In '{}'.", e, path.to_string_lossy()).into())
+    }
+
+    /// Whether `handle` is a fediverse account, such as `@user@example.social`.
+    fn is_handle(handle: &str) -> bool {
+        handle
+            .strip_prefix('@')
+            .and_then(|handle| handle.split_once('@'))
+            .is_some_and(|(user, server)| !user.is_empty() && server.contains('.') && !handle.contains(char::is_whitespace))
+    }
+
+    /// The account that a post by `author` is attributed to.
+    pub fn creator(&self, author: Option<&str>) -> Option<&str> {
+        author
+            .and_then(|author| self.authors.iter().find(|meta| meta.key == author))
+            .map(|meta| meta.value.as_str())
+            .or(self.creator.as_deref())
+    }
+
+    /// The `fediverse:creator` meta tag that attributes a page to `creator`.
+    pub fn meta_tag(creator: &str) -> String {
+        format!("<meta name=\"fediverse:creator\" content=\"{}\">\n", escape_html(creator))
+    }
+
+    /// The `posts` as JSON, for a separate ActivityPub bridge to publish, with
+    /// the `url` of each relative to `base_url`, and the account of its author.
+    ///
+    /// The `output_dir` is the root of the site, which the output of each post
+    /// is within.
+    ///
+    /// Requires the `json` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::{collections::BTreeMap, path::{Path, PathBuf}};
+    /// use blogs_md_easy::{Fediverse, Post};
+    ///
+    /// let fediverse = Fediverse { creator: Some("@blog@example.social".to_string()), authors: vec![] };
+    /// let post = Post {
+    ///     markdown_path: PathBuf::from("hello.md"),
+    ///     output_path: PathBuf::from("dist/posts/hello.html"),
+    ///     variables: BTreeMap::from([("title".to_string(), "Hello".to_string()), ("tags".to_string(), "rust, nom".to_string())]),
+    /// };
+    ///
+    /// let json = fediverse.to_json(&[post], Path::new("dist"), "https://example.com/");
+    /// assert!(json.contains("\"url\": \"https://example.com/posts/hello.html\""));
+    /// assert!(json.contains("\"creator\": \"@blog@example.social\""));
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn to_json(&self, posts: &[Post], output_dir: &Path, base_url: &str) -> String {
+        let posts = posts
+            .iter()
+            .map(|post| {
+                let variable = |key: &str| post.variables.get(key).map(String::as_str);
+                let tags = variable("tags")
+                    .map(split_meta_list)
+                    .unwrap_or_default();
+
+                serde_json::json!({
+                    "url": post_url(post, output_dir, base_url),
+                    "title": variable("title"),
+                    "summary": variable("excerpt"),
+                    "published": variable("date"),
+                    "author": variable("author"),
+                    "creator": self.creator(variable("author")),
+                    "tags": tags,
+                })
+            })
+            .collect::<Vec<serde_json::Value>>();
+
+        serde_json::to_string_pretty(&posts).unwrap_or_default()
+    }
+}
+
+/// The configuration of a project, which sets the defaults for rendering it,
+/// from a `blogs-md-easy.toml`.
+/// ```toml
+/// templates = ["templates/post.html"]
+/// templates_dir = "templates"
+/// markdowns = ["posts"]
+/// output_dir = "dist"
+/// # Expand `{{ figure src: cat.jpg }}` from `shortcodes/figure.html`.
+/// shortcodes = "shortcodes"
+/// base_url = "https://example.com"
+/// # Wrap each `##` section of the Markdowns in a `<section>`.
+/// wrap_sections = "section"
+/// # Write a `.gz` and `.br` copy of each output.
+/// compress = ["gzip", "brotli"]
+/// # Render each Markdown in English, and into `dist/fr` in French.
+/// locales = ["en", "fr"]
+/// # Also write a `.txt` and `.email.html` version of each output.
+/// alternates = ["text", "email"]
+/// # The rate of the `£reading_time` of each Markdown.
+/// words_per_minute = 250
+/// # Date the Markdowns from `2024-05-01-hello.md`, or when they were modified.
+/// publish_date_from = ["filename", "modified"]
+/// # Describe the Markdowns by their excerpt, even if they declare a description.
+/// description_from = ["excerpt", "meta"]
+/// # The environment variables that templates can use, as `£env.BUILD_SHA`.
+/// env = ["BUILD_SHA"]
+/// # Commands to run before and after the build, and on each output.
+/// pre_build = ["npx tailwindcss -i styles.css -o dist/styles.css"]
+/// post_render = ["npx prettier --write \"$BLOGS_MD_EASY_OUTPUT\""]
+/// post_build = ["rsync -r dist/ example.com:/var/www"]
+///
+/// # Default variables for every Markdown.
+/// [variables]
+/// site_name = "My Blog"
+///
+/// # The arguments of filters that are used without any.
+/// [filters]
+/// truncate = { characters = 50, trail = "…" }
+/// first_words = 20
+///
+/// # The filters that the names of filters from other engines stand for.
+/// [filter_aliases]
+/// upper = "uppercase"
+/// slugify = "text = kebab"
+///
+/// # The attributes of the elements of each Markdown.
+/// [attributes]
+/// table = { class = "prose-table" }
+/// "img[alt]" = { loading = "lazy" }
+///
+/// # The options of the Markdowns with `profile = minimal` in their meta section.
+/// [profiles.minimal]
+/// template = "landing.html"
+/// features = ["analytics"]
+/// filters = { truncate = 100 }
+/// alternates = ["email"]
+///
+/// # The rules of the meta section of each Markdown, which fail the build.
+/// [schema]
+/// title = { required = true }
+/// publish_date = { required = true, type = "date" }
+/// category = { values = ["news", "tutorial"] }
+/// ```
+/// Paths are relative to the directory of the config.
+///
+/// Requires the `toml` feature.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{Config, Meta};
+///
+/// let config = toml::from_str::<Config>("markdowns = [\"posts\"]\nbase_url = \"https://example.com\"\n[variables]\nauthor.name = \"John Doe\"\n[filters]\ntruncate = 50\n[filter_aliases]\nupper = \"uppercase\"\n[attributes]\ntable = { class = \"prose-table\" }").unwrap();
+/// assert_eq!(config.variables(), vec![Meta::new("author.name", "John Doe"), Meta::new("base_url", "https://example.com")]);
+/// assert_eq!(config.filter_defaults(), vec![Meta::new("truncate", "50")]);
+/// assert_eq!(config.filter_aliases(), vec![Meta::new("upper", "uppercase")]);
+/// assert_eq!(config.attributes(), vec![Meta::new("table.class", "prose-table")]);
+/// ```
+#[cfg(feature = "toml")]
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The templates that the Markdowns are rendered into.
+    pub templates: Vec<PathBuf>,
+    /// The directory of the templates that a Markdown can choose with
+    /// `template` in its meta section.
+    pub templates_dir: Option<PathBuf>,
+    /// The Markdowns, their directories, or globs.
+    pub markdowns: Vec<PathBuf>,
+    /// Where the outputs are written, defaults to beside each Markdown.
+    pub output_dir: Option<PathBuf>,
+    /// Where each Markdown is written within the output directory, such as
+    /// `/:year/:month/:slug/`.
+    pub permalink_pattern: Option<String>,
+    /// The directory of the templates of the shortcodes that the Markdowns
+    /// can use.
+    pub shortcodes: Option<PathBuf>,
+    /// The URL that the site is published at, which is available as the
+    /// `base_url` variable.
+    pub base_url: Option<String>,
+    /// The element that wraps each `##` section of the Markdowns.
+    pub wrap_sections: Option<String>,
+    /// The formats, `gzip` or `brotli`, that each output is also written in.
+    pub compress: Vec<String>,
+    /// The locales that each Markdown is rendered in, the first of which is
+    /// written to the output directory.
+    pub locales: Vec<String>,
+    /// The other versions of each output, `text` or `email`, that are written
+    /// beside it.
+    pub alternates: Vec<Alternate>,
+    /// How many words a reader gets through each minute, for the
+    /// `reading_time` variable.
+    pub words_per_minute: Option<u32>,
+    /// Whether the players of YouTube and Vimeo embeds are loaded with the
+    /// page, rather than once they are clicked.
+    pub eager_embeds: bool,
+    /// Whether the `og_title`, `og_description`, `og_image` and `og_url` of
+    /// each Markdown are derived, for its social-sharing tags.
+    pub open_graph: bool,
+    /// The extension of links to other Markdowns, such as `""` for clean
+    /// URLs.
+    pub link_extension: Option<String>,
+    /// The sigil of variables, such as `@` for `{{ @title }}`.
+    pub sigil: Option<char>,
+    /// The opening and closing delimiters of placeholders and tags, such as
+    /// `["[[", "]]"]`.
+    pub delimiters: Vec<String>,
+    /// Whether the local images of each Markdown are copied into the output
+    /// directory.
+    pub copy_images: bool,
+    /// Whether each local image is given its `width` and `height`.
+    pub image_sizes: bool,
+    /// Whether the stylesheets, images, and fonts of each output are inlined
+    /// into it.
+    pub single_file: bool,
+    /// Whether every Markdown that fails is reported at once, rather than
+    /// stopping at the first.
+    pub collect_errors: bool,
+    /// Where every file that is written is recorded, so that the `clean`
+    /// command can remove them.
+    pub manifest: Option<PathBuf>,
+    /// Where the `publish_date` of a Markdown without one is taken from,
+    /// `filename` or `modified`.
+    pub publish_date_from: Vec<String>,
+    /// Where the `£description` of each Markdown is taken from, `meta`,
+    /// `excerpt`, or `content`.
+    pub description_from: Vec<String>,
+    /// The environment variables that templates can use, such as `BUILD_SHA`
+    /// as `£env.BUILD_SHA`.
+    pub env: Vec<String>,
+    /// Shell commands that are run, in order, in the directory of the config,
+    /// before anything is rendered.
+    pub pre_build: Vec<String>,
+    /// Shell commands that are run on each output of a Markdown that is
+    /// rendered, with the paths of the output and its Markdown in the
+    /// `BLOGS_MD_EASY_OUTPUT` and `BLOGS_MD_EASY_MARKDOWN` environment
+    /// variables.
+    pub post_render: Vec<String>,
+    /// Shell commands that are run, in order, in the directory of the config,
+    /// once everything has been rendered.
+    pub post_build: Vec<String>,
+    /// Default variables for every Markdown, which are flattened using dot
+    /// notation in the same way as TOML frontmatter.
+    pub variables: toml::Table,
+    /// The arguments of each filter, by its name, either as they would be
+    /// written in a template, or as a table.
+    pub filters: BTreeMap<String, toml::Value>,
+    /// The filter that each alias stands for, by the alias, as it would be
+    /// written in a template.
+    pub filter_aliases: BTreeMap<String, String>,
+    /// The attributes to add to each element of the Markdowns, by the element,
+    /// such as `table` or `img[alt]`.
+    pub attributes: BTreeMap<String, toml::Table>,
+    /// The profiles that a Markdown can choose with `profile` in its meta
+    /// section, by their name.
+    pub profiles: BTreeMap<String, ProfileConfig>,
+    /// The rule of each meta key, see [`MetaSchema`].
+    pub schema: BTreeMap<String, MetaRule>,
+}
+
+/// A profile of a [`Config`], which a Markdown can choose with `profile` in
+/// its meta section, see [`Profile`].
+///
+/// Requires the `toml` feature.
+#[cfg(feature = "toml")]
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// The template that the Markdowns are rendered into, within the
+    /// `templates_dir`.
+    pub template: Option<PathBuf>,
+    /// The features of the `{{ if feature }}` blocks of the template.
+    pub features: Vec<String>,
+    /// The arguments of each filter, as with the filters of the [`Config`].
+    pub filters: BTreeMap<String, toml::Value>,
+    /// The other versions of each output, `text` or `email`, in place of
+    /// those of the [`Config`].
+    pub alternates: Option<Vec<Alternate>>,
+}
+
+#[cfg(feature = "toml")]
+impl Config {
+    /// The name of the config file, within the root of a project.
+    pub const FILE: &'static str = "blogs-md-easy.toml";
+
+    /// Read the config at `path`, making its paths relative to the current
+    /// directory, rather than to the config.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut config = toml::from_str::<Self>(&read_file(path)?)
+            .map_err(|e| format!("Unable to parse '{}': {}", path.to_string_lossy(), e.message()))?;
+
+        let root = path.parent().unwrap_or(Path::new(""));
+        for path in config.templates.iter_mut().chain(config.markdowns.iter_mut()).chain(config.output_dir.iter_mut()).chain(config.templates_dir.iter_mut()).chain(config.shortcodes.iter_mut()).chain(config.manifest.iter_mut()) {
+            *path = root.join(&*path);
+        }
+
+        Ok(config)
+    }
+
+    /// The default variables for every Markdown, along with the `base_url`.
+    pub fn variables(&self) -> Vec<Meta> {
+        let mut variables = Vec::new();
+        flatten_meta("", &toml_to_json(toml::Value::Table(self.variables.to_owned())), &mut variables);
+        if let Some(base_url) = &self.base_url {
+            variables.push(Meta::new("base_url", base_url));
+        }
+        variables
+    }
+
+    /// The arguments of each filter, as they would be written in a template,
+    /// see [`BatchOptions::filter_defaults`](crate::batch::BatchOptions::filter_defaults).
+    pub fn filter_defaults(&self) -> Vec<Meta> {
+        filter_defaults(&self.filters)
+    }
+
+    /// The filter that each alias stands for, see
+    /// [`BatchOptions::filter_aliases`](crate::batch::BatchOptions::filter_aliases).
+    pub fn filter_aliases(&self) -> Vec<Meta> {
+        self.filter_aliases.iter().map(|(alias, filter)| Meta::new(alias, filter)).collect()
+    }
+
+    /// The profiles that a Markdown can choose, see
+    /// [`BatchOptions::profiles`](crate::batch::BatchOptions::profiles).
+    pub fn profiles(&self) -> BTreeMap<String, Profile> {
+        self.profiles
+            .iter()
+            .map(|(name, profile)| (name.to_owned(), Profile {
+                template: profile.template.to_owned(),
+                features: profile.features.to_owned(),
+                filter_defaults: filter_defaults(&profile.filters),
+                alternates: profile.alternates.to_owned(),
+            }))
+            .collect()
+    }
+
+    /// The rules of the meta section of each Markdown, see
+    /// [`BatchOptions::schema`](crate::batch::BatchOptions::schema).
+    pub fn schema(&self) -> MetaSchema {
+        self.schema
+            .iter()
+            .fold(MetaSchema::new(), |schema, (key, rule)| schema.with(key, rule.to_owned()))
+    }
+
+    /// The attributes of each element, keyed by the element and the name of
+    /// the attribute, see
+    /// [`BatchOptions::attributes`](crate::batch::BatchOptions::attributes).
+    pub fn attributes(&self) -> Vec<Meta> {
+        self.attributes
+            .iter()
+            .flat_map(|(element, attributes)| attributes.iter().map(move |(name, value)| {
+                let value = match value {
+                    toml::Value::String(value) => value.to_owned(),
+                    value => value.to_string(),
+                };
+                Meta::new(&format!("{}.{}", element, name), &value)
+            }))
+            .collect()
+    }
+}
+
+/// The arguments of each of the `filters` of a [`Config`], as they would be
+/// written in a template.
+#[cfg(feature = "toml")]
+fn filter_defaults(filters: &BTreeMap<String, toml::Value>) -> Vec<Meta> {
+    filters
+        .iter()
+        .map(|(name, arguments)| {
+            let arguments = match arguments {
+                toml::Value::String(arguments) => arguments.to_owned(),
+                toml::Value::Table(arguments) => arguments
+                    .iter()
+                    .map(|(key, value)| match value {
+                        toml::Value::String(value) => format!("{}: {}", key, value),
+                        value => format!("{}: {}", key, value),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                arguments => arguments.to_string(),
+            };
+            Meta::new(name, &arguments)
+        })
+        .collect()
+}
+
+/// How many Markdowns declare a meta key, and how many templates use it, see
+/// [`Stats::keys`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct KeyUsage {
+    /// The number of Markdowns that declare the key in their meta section.
+    pub posts: usize,
+    /// The number of templates with a placeholder of the key.
+    pub templates: usize,
+}
+
+/// A summary of the Markdowns of a project, and the templates that they are
+/// rendered into.
+///
+/// Printing the `Stats` gives a table of each summary, with the most frequent
+/// first.
+///
+/// # Example
+/// ```rust
+/// use std::path::PathBuf;
+/// use blogs_md_easy::{Stats, Template};
+///
+/// let markdowns = vec![
+///     (PathBuf::from("one.md"), ":meta\ndate = 2024-01-01\ntags = [rust, nom]\n:meta\n# One\nFour words of content.".to_string()),
+///     (PathBuf::from("two.md"), ":meta\ndate = 2023-06-01\ntags = [rust]\n:meta\n# Two".to_string()),
+/// ];
+/// let templates = vec![Template::new("<h1>{{ £title | uppercase }}</h1>{{ £content }}").unwrap()];
+/// let stats = Stats::new(&markdowns, &templates);
+///
+/// assert_eq!(stats.posts, 2);
+/// assert_eq!(stats.posts_by_tag.get("rust"), Some(&2));
+/// assert_eq!(stats.posts_by_year.get("2023"), Some(&1));
+/// assert_eq!(stats.longest[0], (PathBuf::from("one.md"), 6));
+/// assert_eq!(stats.average_words(), 4);
+/// assert_eq!(stats.variables.get("title"), Some(&1));
+/// assert_eq!(stats.keys["tags"].posts, 2);
+/// assert!(stats.unused_keys().is_empty());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// The number of Markdowns.
+    pub posts: usize,
+    /// The number of Markdowns published in each year, from the first four
+    /// characters of their `date`.
+    pub posts_by_year: BTreeMap<String, usize>,
+    /// The number of Markdowns with each of the `tags` in their meta section.
+    pub posts_by_tag: BTreeMap<String, usize>,
+    /// The number of words in every Markdown, excluding their meta sections.
+    pub words: usize,
+    /// The longest Markdowns, and how many words they have, longest first.
+    pub longest: Vec<(PathBuf, usize)>,
+    /// How many times each [`Filter`](crate::Filter) is used by the templates.
+    pub filters: BTreeMap<String, usize>,
+    /// How many times each variable is used by the templates.
+    pub variables: BTreeMap<String, usize>,
+    /// The number of templates.
+    pub templates: usize,
+    /// How many Markdowns declare each meta key, and how many templates use
+    /// it.
+    pub keys: BTreeMap<String, KeyUsage>,
+}
+
+impl Stats {
+    /// How many of the longest Markdowns are kept.
+    pub const LONGEST: usize = 5;
+
+    /// The meta keys that are read for the Markdown itself, such as by the
+    /// feeds and indexes, so are used even if no template uses them.
+    pub const BUILT_IN_KEYS: [&'static str; 11] = ["author", "date", "draft", "glossary", "noindex", "profile", "publish_date", "tags", "template", "title", "visibility"];
+
+    /// Summarise the `markdowns`, along with the `templates`.
+    pub fn new(markdowns: &[(PathBuf, String)], templates: &[Template]) -> Self {
+        let mut stats = Self { posts: markdowns.len(), templates: templates.len(), ..Default::default() };
+
+        let mut longest = Vec::with_capacity(markdowns.len());
+        for (path, markdown) in markdowns {
+            let markdown = Span::new(markdown);
+            let (content, meta_values) = parse_meta_section(markdown).unwrap_or((markdown, vec![]));
+
+            // A key that is declared twice is still only one post.
+            let keys = meta_values.iter().map(|meta| meta.key.as_str()).collect::<BTreeSet<&str>>();
+            for key in keys {
+                stats.keys.entry(key.to_string()).or_default().posts += 1;
+            }
+            for meta in meta_values {
+                match meta.key.as_str() {
+                    "date" if meta.value.len() >= 4 && meta.value.is_char_boundary(4) => {
+                        *stats.posts_by_year.entry(meta.value[..4].to_string()).or_default() += 1;
+                    },
+                    // Lists are stored with each item separated by a comma.
+                    "tags" => for tag in split_meta_list(&meta.value) {
+                        *stats.posts_by_tag.entry(tag).or_default() += 1;
+                    },
+                    _ => {},
+                }
+            }
+
+            let words = content.fragment().split_whitespace().count();
+            stats.words += words;
+            longest.push((path.to_owned(), words));
+        }
+        longest.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        longest.truncate(Self::LONGEST);
+        stats.longest = longest;
+
+        for placeholder in templates.iter().flat_map(|template| template.placeholders()) {
+            *stats.variables.entry(placeholder.name.to_owned()).or_default() += 1;
+            for filter in &placeholder.filters {
+                *stats.filters.entry(format!("{:?}", filter)).or_default() += 1;
+            }
+        }
+        for template in templates {
+            let variables = template.used_variables();
+            for (key, usage) in stats.keys.iter_mut() {
+                if variables.contains(key) {
+                    usage.templates += 1;
+                }
+            }
+        }
+
+        stats
+    }
+
+    /// The meta keys that every Markdown declares, but no template uses, other
+    /// than the [`BUILT_IN_KEYS`](Stats::BUILT_IN_KEYS).
+    ///
+    /// These are likely left over from an older template, so can be removed
+    /// from the Markdowns. Without any templates, there are none.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::path::PathBuf;
+    /// use blogs_md_easy::{Stats, Template};
+    ///
+    /// let markdowns = vec![
+    ///     (PathBuf::from("one.md"), ":meta\nlayout = post\nauthor = John Doe\nmood = happy\n:meta\n# One".to_string()),
+    ///     (PathBuf::from("two.md"), ":meta\nlayout = post\nauthor = Jane Doe\n:meta\n# Two".to_string()),
+    /// ];
+    /// let templates = vec![Template::new("<h1>{{ £title }}</h1>{{ £content }}").unwrap()];
+    /// let stats = Stats::new(&markdowns, &templates);
+    ///
+    /// assert_eq!(stats.unused_keys(), vec!["layout"]);
+    /// ```
+    pub fn unused_keys(&self) -> Vec<&str> {
+        if self.templates == 0 {
+            return vec![];
+        }
+
+        self.keys
+            .iter()
+            .filter(|(key, usage)| usage.posts == self.posts && usage.templates == 0 && !Self::BUILT_IN_KEYS.contains(&key.as_str()))
+            .map(|(key, _)| key.as_str())
+            .collect()
+    }
+
+    /// The average number of words in each Markdown, rounded down.
+    pub fn average_words(&self) -> usize {
+        self.words.checked_div(self.posts).unwrap_or_default()
+    }
+
+    /// The summaries as JSON, with the counts of each year, tag, filter and
+    /// variable as an object.
+    ///
+    /// Requires the `json` feature.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        let longest = self.longest
+            .iter()
+            .map(|(path, words)| serde_json::json!({ "path": path.to_string_lossy(), "words": words }))
+            .collect::<Vec<serde_json::Value>>();
+
+        serde_json::to_string_pretty(&serde_json::json!({
+            "posts": self.posts,
+            "posts_by_year": self.posts_by_year,
+            "posts_by_tag": self.posts_by_tag,
+            "words": self.words,
+            "average_words": self.average_words(),
+            "longest": longest,
+            "filters": self.filters,
+            "variables": self.variables,
+            "keys": self.keys.iter().map(|(key, usage)| (key.to_owned(), serde_json::json!({ "posts": usage.posts, "templates": usage.templates }))).collect::<serde_json::Map<String, serde_json::Value>>(),
+            "unused_keys": self.unused_keys(),
+        })).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        /// Sort the counts with the most frequent first.
+        fn most_used(counts: &BTreeMap<String, usize>) -> Vec<(String, String)> {
+            let mut counts = counts.iter().collect::<Vec<(&String, &usize)>>();
+            counts.sort_by(|a, b| b.1.cmp(a.1));
+            counts.into_iter().map(|(name, count)| (name.to_owned(), count.to_string())).collect()
+        }
+
+        // Years are in order, rather than by frequency.
+        let years = self.posts_by_year.iter().rev().map(|(year, count)| (year.to_owned(), count.to_string())).collect();
+        let longest = self.longest.iter().map(|(path, words)| (path.to_string_lossy().to_string(), words.to_string())).collect();
+        // Keys are declared by the most posts first, with those that are
+        // unused flagged.
+        let unused = self.unused_keys();
+        let mut keys = self.keys.iter().collect::<Vec<(&String, &KeyUsage)>>();
+        keys.sort_by_key(|(_, usage)| std::cmp::Reverse(usage.posts));
+        let keys = keys.into_iter().map(|(key, usage)| (key.to_owned(), match self.templates {
+            0 => usage.posts.to_string(),
+            _ if unused.contains(&key.as_str()) => format!("{}, {} (unused)", usage.posts, usage.templates),
+            _ => format!("{}, {}", usage.posts, usage.templates),
+        })).collect();
+        let tables = [
+            (("Posts", "Words"), vec![(self.posts.to_string(), format!("{} ({} on average)", self.words, self.average_words()))]),
+            (("Year", "Posts"), years),
+            (("Tag", "Posts"), most_used(&self.posts_by_tag)),
+            (("Longest", "Words"), longest),
+            (("Filter", "Uses"), most_used(&self.filters)),
+            (("Variable", "Uses"), most_used(&self.variables)),
+            (("Meta key", if self.templates == 0 { "Posts" } else { "Posts, templates" }), keys),
+        ];
+
+        let mut first = true;
+        for ((name, count), rows) in tables.iter().filter(|(_, rows)| !rows.is_empty()) {
+            if !first {
+                writeln!(f)?;
+            }
+            first = false;
+
+            let width = rows.iter().map(|(left, _)| left.chars().count()).chain([name.len()]).max().unwrap_or_default();
+            writeln!(f, "{:width$}  {}", name, count)?;
+            for (left, right) in rows {
+                writeln!(f, "{:width$}  {}", left, right)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Functions
+/// Recursively copy the contents of the `from` directory into `to`, creating it
+/// if necessary.
+///
+/// Version control directories, such as `.git`, are skipped.
+pub fn copy_dir(from: &Path, to: &Path) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(to)?;
+
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_name() == ".git" {
+            continue;
+        }
+
+        let destination = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &destination)?;
+        } else {
+            fs::copy(entry.path(), destination)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a theme `source` should be cloned with Git, rather than copied from
+/// a local directory.
+///
+/// A URL has a scheme, such as `https://`, or is the `user@host:path` of SSH,
+/// so a local directory that ends in `.git` is still copied.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::is_git_url;
+///
+/// assert!(is_git_url("https://github.com/user/theme.git"));
+/// assert!(is_git_url("git@github.com:user/theme.git"));
+/// assert!(is_git_url("file:///srv/themes/minimal"));
+/// assert!(!is_git_url("themes/minimal"));
+/// assert!(!is_git_url("themes/minimal.git"));
+/// assert!(!is_git_url("C:\\themes\\minimal.git"));
+/// assert!(!is_git_url("themes/me@home:minimal"));
+/// ```
+pub fn is_git_url(source: &str) -> bool {
+    let scheme = source.split_once("://").is_some_and(|(scheme, _)| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    });
+    let ssh = source.split_once(':').is_some_and(|(user_host, _)| {
+        !user_host.contains(['/', '\\'])
+            && user_host.split_once('@').is_some_and(|(user, host)| !user.is_empty() && !host.is_empty())
+    });
+    scheme || ssh
+}
+
+/// Whether a relative `path` from a manifest stays within the directory that
+/// it is joined onto, because it has no `..`, root, or prefix.
+#[cfg(feature = "toml")]
+fn is_within(path: &Path) -> bool {
+    path.components().next().is_some() && path.components().all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Install a [`Theme`] from a local directory or a Git URL, by copying it into
+/// `themes_dir/<name>`.
+///
+/// Any previously installed version of the theme is replaced.
+///
+/// Requires the `toml` feature.
+///
+/// # Example
+/// ```rust
+/// use std::path::Path;
+/// use blogs_md_easy::install_theme;
+///
+/// let themes_dir = std::env::temp_dir().join("blogs_md_easy_install_theme");
+/// let theme = install_theme("tests/theme", &themes_dir).unwrap();
+/// assert_eq!(theme.path, themes_dir.join("example"));
+/// assert!(theme.path.join("static/style.css").exists());
+/// ```
+#[cfg(feature = "toml")]
+pub fn install_theme(source: &str, themes_dir: &Path) -> Result<Theme, Box<dyn Error>> {
+    let (source_dir, cloned) = if is_git_url(source) {
+        let clone_dir = std::env::temp_dir().join(format!("blogs_md_easy_theme_{}", &hash_hex(source.as_bytes())[..16]));
+        if clone_dir.exists() {
+            fs::remove_dir_all(&clone_dir)?;
+        }
+
+        let status = std::process::Command::new("git")
+            .args(["clone", "--depth", "1", "--", source])
+            .arg(&clone_dir)
+            .status()
+            .map_err(|e| format!("Unable to run git to clone '{}': {}", source, e))?;
+        if !status.success() {
+            Err(format!("Unable to clone the theme from '{}'.", source))?;
+        }
+
+        (clone_dir, true)
+    } else {
+        (PathBuf::from(source), false)
+    };
+
+    let name = Theme::load(&source_dir)?.name;
+    let destination = themes_dir.join(&name);
+    if destination.exists() {
+        fs::remove_dir_all(&destination)?;
+    }
+    copy_dir(&source_dir, &destination)?;
+
+    if cloned {
+        fs::remove_dir_all(&source_dir)?;
+    }
+
+    Theme::load(&destination)
+}
+
+/// The archetype that is used for new Markdowns when the project does not have
+/// an `archetypes/default.md`, see [`render_archetype`].
+pub const DEFAULT_ARCHETYPE: &str = ":meta
+date = {{ £date }}
+:meta
+# {{ £title }}
+";
+
+/// The files of a starter project, by their path within the project, see
+/// [`init_project`]. The `{{ £date }}` of the post, but not of the template,
+/// is the date that it is created.
+const STARTER_PROJECT: [(&str, &str); 4] = [
+    ("blogs-md-easy.toml", r#"templates = ["templates/post.html"]
+markdowns = ["posts"]
+output_dir = "dist"
+shortcodes = "partials"
+base_url = "https://example.com"
+
+[variables]
+site_name = "My Blog"
+"#),
+    ("templates/post.html", r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>{{ £title }} | {{ £site_name }}</title>
+    <meta name="description" content="{{ £description }}">
+</head>
+<body>
+    <header><a href="{{ £base_url }}">{{ £site_name }}</a></header>
+    <main>
+        <article>
+            <h1>{{ £title }}</h1>
+            <p>By {{ £author }} on <time datetime="{{ £date }}">{{ £date }}</time>, {{ £reading_time }} minute read.</p>
+            {{ £content }}
+        </article>
+    </main>
+</body>
+</html>
+"#),
+    ("partials/note.html", r#"<aside class="note">{{ £text }}</aside>
+"#),
+    ("posts/hello-world.md", r#":meta
+// Each key is available to the template as a variable, such as `£author`.
+title = Hello, World!
+author = Jane Doe
+date = {{ £date }}
+description = The first post of my new blog.
+:meta
+Welcome to my new blog, which is written in Markdown and rendered into `templates/post.html`.
+
+{{ note text: "Each template of the partials directory is a shortcode, such as this note." }}
+
+Run `blogs-md-easy build` to write this post to the `dist` directory.
+"#),
+];
+
+/// Create a starter project within `project_dir`: a `blogs-md-easy.toml`, a
+/// template with the common placeholders, a `partials` directory of
+/// shortcodes, and an example post whose `date` is `date`.
+///
+/// Nothing is overwritten, so every file must not already exist.
+///
+/// Returns the path of each file that was created.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::init_project;
+///
+/// let project_dir = std::env::temp_dir().join("blogs_md_easy_init_project");
+/// # std::fs::remove_dir_all(&project_dir).ok();
+/// let files = init_project(&project_dir, "2024-05-01").unwrap();
+/// assert!(files.contains(&project_dir.join("posts/hello-world.md")));
+///
+/// // A project can't be created over another.
+/// assert!(init_project(&project_dir, "2024-05-01").is_err());
+/// # std::fs::remove_dir_all(&project_dir).ok();
+/// ```
+pub fn init_project(project_dir: &Path, date: &str) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let files = STARTER_PROJECT.map(|(path, contents)| (project_dir.join(path), contents));
+    if let Some((path, _)) = files.iter().find(|(path, _)| path.exists()) {
+        Err(format!("The file '{}' already exists.", path.to_string_lossy()))?;
+    }
+
+    for (path, contents) in &files {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match path.extension().is_some_and(|extension| extension == "md") {
+            true => fs::write(path, contents.replace("{{ £date }}", date))?,
+            false => fs::write(path, contents)?,
+        }
+    }
+
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+/// Create a draft post within `posts_dir`, named after its `title` and the
+/// `date` that it is created, such as `2024-05-01-hello-world.md`.
+///
+/// Its meta section has the `title`, the `date` as its `publish_date`, and
+/// `draft = true`, along with any other keys that the `schema` requires, see
+/// [`MetaSchema::fill`].
+///
+/// Returns the path of the post, which must not already exist.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{new_post, MetaSchema};
+///
+/// let posts_dir = std::env::temp_dir().join("blogs_md_easy_new_post");
+/// # std::fs::remove_dir_all(&posts_dir).ok();
+/// let path = new_post(&posts_dir, "Hello, World!", "2024-05-01", &MetaSchema::new()).unwrap();
+/// assert_eq!(path, posts_dir.join("2024-05-01-hello-world.md"));
+/// assert_eq!(
+///     std::fs::read_to_string(&path).unwrap(),
+///     ":meta\ntitle = Hello, World!\npublish_date = 2024-05-01\ndraft = true\n:meta\n"
+/// );
+///
+/// // A post can't be created over another.
+/// assert!(new_post(&posts_dir, "Hello, World!", "2024-05-01", &MetaSchema::new()).is_err());
+/// # std::fs::remove_dir_all(&posts_dir).ok();
+/// ```
+pub fn new_post(posts_dir: &Path, title: &str, date: &str, schema: &MetaSchema) -> Result<PathBuf, Box<dyn Error>> {
+    let slug = slugify(title);
+    if slug.is_empty() {
+        Err(format!("The title '{}' does not have any letters to name the post after.", title))?;
+    }
+    let path = posts_dir.join(format!("{}-{}.md", date, slug));
+    if path.exists() {
+        Err(format!("The file '{}' already exists.", path.to_string_lossy()))?;
+    }
+
+    let markdown = format!(":meta\ntitle = {}\npublish_date = {}\ndraft = true\n:meta\n", title.trim(), date);
+    let markdown = schema.fill(&markdown, date)?;
+
+    fs::create_dir_all(posts_dir)?;
+    fs::write(&path, markdown)?;
+
+    Ok(path)
+}
+
+/// The URL of a post, whose output is within `output_dir`, relative to the
+/// `base_url` of the site.
+fn post_url(post: &Post, output_dir: &Path, base_url: &str) -> String {
+    let path = post.output_path.strip_prefix(output_dir).unwrap_or(&post.output_path);
+    let url = format!("{}/{}", base_url.trim_end_matches('/'), path.to_string_lossy().replace('\\', "/"));
+    url.trim_start_matches('/').to_string()
+}
+
+/// The posts as an iCalendar, with an event for each post on its
+/// `event_date`, or otherwise its `publish_date` or `date`. Posts without any
+/// of these are left out.
+///
+/// Dates, such as `2024-05-01`, are all-day events, whereas times, such as
+/// `2024-05-01T18:30:00Z`, are converted into UTC, unless they don't have an
+/// offset.
+///
+/// The `output_dir` is the root of the site, which the output of each post is
+/// within, and the URL of each event is relative to the `base_url`.
+///
+/// # Example
+/// ```rust
+/// use std::{collections::BTreeMap, path::{Path, PathBuf}};
+/// use blogs_md_easy::{posts_to_ics, Post};
+///
+/// let post = Post {
+///     markdown_path: PathBuf::from("meetup.md"),
+///     output_path: PathBuf::from("dist/meetup.html"),
+///     variables: BTreeMap::from([
+///         ("title".to_string(), "Rust Meetup".to_string()),
+///         ("event_date".to_string(), "2024-05-01T18:30:00+01:00".to_string()),
+///     ]),
+/// };
+///
+/// let ics = posts_to_ics(&[post], Path::new("dist"), "https://example.com", "Events").unwrap();
+/// assert!(ics.contains("\r\nDTSTART:20240501T173000Z\r\n"));
+/// assert!(ics.contains("\r\nSUMMARY:Rust Meetup\r\n"));
+/// assert!(ics.contains("\r\nURL:https://example.com/meetup.html\r\n"));
+/// ```
+pub fn posts_to_ics(posts: &[Post], output_dir: &Path, base_url: &str, name: &str) -> Result<String, Box<dyn Error>> {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:-//blogs-md-easy//{}//EN", env!("CARGO_PKG_VERSION")),
+        format!("X-WR-CALNAME:{}", escape_ics(name)),
+    ];
+
+    for post in posts {
+        let Some(date) = ["event_date", "publish_date", "date"].iter().find_map(|key| post.variables.get(*key)) else {
+            continue;
+        };
+        let (start, is_date) = ics_date(date)
+            .ok_or(format!("Unable to parse the date '{}' of '{}', expected YYYY-MM-DD or YYYY-MM-DDTHH:MM:SS.", date, post.markdown_path.to_string_lossy()))?;
+        let url = post_url(post, output_dir, base_url);
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", url));
+        // The stamp must be in UTC, so a date is the start of its day.
+        lines.push(format!("DTSTAMP:{}", match is_date {
+            true => format!("{}T000000Z", start),
+            false => format!("{}Z", start.trim_end_matches('Z')),
+        }));
+        lines.push(match is_date {
+            true => format!("DTSTART;VALUE=DATE:{}", start),
+            false => format!("DTSTART:{}", start),
+        });
+        if let Some(title) = post.variables.get("title") {
+            lines.push(format!("SUMMARY:{}", escape_ics(title)));
+        }
+        if let Some(excerpt) = post.variables.get("excerpt").filter(|excerpt| !excerpt.is_empty()) {
+            lines.push(format!("DESCRIPTION:{}", escape_ics(excerpt)));
+        }
+        lines.push(format!("URL:{}", url));
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    Ok(lines.iter().map(|line| fold_ics(line) + "\r\n").collect())
+}
+
+/// Convert a date, or date and time, into iCalendar's format, and whether it
+/// is only a date.
+fn ics_date(date: &str) -> Option<(String, bool)> {
+    let date = date.trim();
+    let number = |range: std::ops::Range<usize>| date.get(range).filter(|n| n.chars().all(|c| c.is_ascii_digit())).and_then(|n| n.parse::<i64>().ok());
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    if date.get(4..5)? != "-" || date.get(7..8)? != "-" {
+        return None;
+    }
+    if date.len() == 10 {
+        return Some((format!("{:04}{:02}{:02}", year, month, day), true));
+    }
+
+    if !matches!(date.get(10..11)?, "T" | " ") || date.get(13..14)? != ":" {
+        return None;
+    }
+    let (hour, minute) = (number(11..13)?, number(14..16)?);
+    let (second, rest) = match date.get(16..17) {
+        Some(":") => (number(17..19)?, date.get(19..)?),
+        _ => (0, date.get(16..)?),
+    };
+    // Fractions of a second are ignored.
+    let rest = rest.strip_prefix('.').map(|rest| rest.trim_start_matches(|c: char| c.is_ascii_digit())).unwrap_or(rest);
+
+    let offset = match rest {
+        "" => return Some((format!("{:04}{:02}{:02}T{:02}{:02}{:02}", year, month, day, hour, minute, second), false)),
+        "Z" => 0,
+        offset => {
+            let sign = match offset.get(0..1)? { "+" => 1, "-" => -1, _ => return None };
+            let (hours, minutes) = offset[1..].split_once(':').unwrap_or((offset.get(1..3)?, offset.get(3..).unwrap_or("00")));
+            sign * (hours.parse::<i64>().ok()? * 3_600 + minutes.parse::<i64>().ok()? * 60)
+        },
+    };
+
+    let seconds = days_from_civil(year, month, day) * 86_400 + hour * 3_600 + minute * 60 + second - offset;
+    let time = seconds.rem_euclid(86_400);
+    let date = unix_to_date(seconds.max(0) as u64).replace('-', "");
+    Some((format!("{}T{:02}{:02}{:02}Z", date, time / 3_600, time % 3_600 / 60, time % 60), false))
+}
+
+/// Escape the characters that have a special meaning in iCalendar text.
+fn escape_ics(text: &str) -> String {
+    text
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold a line of iCalendar into lines of at most 75 bytes, where each line
+/// after the first begins with a space.
+fn fold_ics(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+/// The posts as an RSS feed, which is also a podcast feed when the posts have
+/// an `enclosure`.
+///
+/// The `channel` describes the feed, with its `title`, `description`, and
+/// `language`, along with the `author`, cover `image`, `category`, and
+/// whether it is `explicit` for podcast directories.
+///
+/// Each post is an item, with its `title`, its `excerpt` as the description,
+/// and its `publish_date` or `date`. Its `guid` defaults to its URL, relative
+/// to the `base_url`. Podcast episodes are described by the meta section of
+/// their post.
+/// ```md
+/// :meta
+/// date = 2024-05-01
+/// enclosure = episodes/42.mp3
+/// enclosure_length = 24986239
+/// duration = 01:02:03
+/// season = 2
+/// episode = 42
+/// :meta
+/// ```
+/// The `enclosure` is relative to the `base_url`, unless it is a full URL, and
+/// its `enclosure_type` is guessed from its extension when it isn't given.
+///
+/// # Example
+/// ```rust
+/// use std::{collections::BTreeMap, path::{Path, PathBuf}};
+/// use blogs_md_easy::{posts_to_rss, Meta, Post};
+///
+/// let post = Post {
+///     markdown_path: PathBuf::from("pilot.md"),
+///     output_path: PathBuf::from("dist/pilot.html"),
+///     variables: BTreeMap::from([
+///         ("title".to_string(), "Pilot".to_string()),
+///         ("date".to_string(), "2024-05-01".to_string()),
+///         ("enclosure".to_string(), "episodes/1.mp3".to_string()),
+///         ("duration".to_string(), "1800".to_string()),
+///         ("episode".to_string(), "1".to_string()),
+///     ]),
+/// };
+///
+/// let rss = posts_to_rss(&[post], Path::new("dist"), "https://example.com", &[Meta::new("title", "The Show")]).unwrap();
+/// assert!(rss.contains("<pubDate>Wed, 01 May 2024 00:00:00 +0000</pubDate>"));
+/// assert!(rss.contains("<enclosure url=\"https://example.com/episodes/1.mp3\" length=\"0\" type=\"audio/mpeg\"/>"));
+/// assert!(rss.contains("<itunes:duration>1800</itunes:duration>"));
+/// assert!(rss.contains("<itunes:episode>1</itunes:episode>"));
+/// ```
+pub fn posts_to_rss(posts: &[Post], output_dir: &Path, base_url: &str, channel: &[Meta]) -> Result<String, Box<dyn Error>> {
+    let channel = |key: &str| channel.iter().find(|meta| meta.key == key).map(|meta| meta.value.trim()).filter(|value| !value.is_empty());
+    let title = channel("title").unwrap_or("Posts");
+
+    let mut lines = vec![
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>".to_string(),
+        "<rss version=\"2.0\" xmlns:itunes=\"http://www.itunes.com/dtds/podcast-1.0.dtd\" xmlns:podcast=\"https://podcastindex.org/namespace/1.0\">".to_string(),
+        "  <channel>".to_string(),
+        format!("    <title>{}</title>", escape_html(title)),
+        format!("    <link>{}</link>", escape_html(base_url)),
+        format!("    <description>{}</description>", escape_html(channel("description").unwrap_or(title))),
+        format!("    <generator>blogs-md-easy {}</generator>", env!("CARGO_PKG_VERSION")),
+    ];
+    if let Some(language) = channel("language") {
+        lines.push(format!("    <language>{}</language>", escape_html(language)));
+    }
+    if let Some(author) = channel("author") {
+        lines.push(format!("    <itunes:author>{}</itunes:author>", escape_html(author)));
+    }
+    if let Some(image) = channel("image") {
+        lines.push(format!("    <itunes:image href=\"{}\"/>", escape_html(&absolute_url(image, base_url))));
+    }
+    if let Some(category) = channel("category") {
+        lines.push(format!("    <itunes:category text=\"{}\"/>", escape_html(category)));
+    }
+    if let Some(explicit) = channel("explicit") {
+        let explicit = explicit.parse::<bool>().map_err(|_| format!("Unable to parse explicit '{}' of the feed, expected true or false.", explicit))?;
+        lines.push(format!("    <itunes:explicit>{}</itunes:explicit>", explicit));
+    }
+
+    for post in posts {
+        let variable = |key: &str| post.variables.get(key).map(|value| value.trim()).filter(|value| !value.is_empty());
+        let invalid = |key: &str, value: &str, expected: &str| format!("Unable to parse the {} '{}' of '{}', expected {}.", key, value, post.markdown_path.to_string_lossy(), expected);
+        let url = post_url(post, output_dir, base_url);
+
+        lines.push("    <item>".to_string());
+        if let Some(title) = variable("title") {
+            lines.push(format!("      <title>{}</title>", escape_html(title)));
+        }
+        lines.push(format!("      <link>{}</link>", escape_html(&url)));
+        match variable("guid") {
+            Some(guid) => lines.push(format!("      <guid isPermaLink=\"false\">{}</guid>", escape_html(guid))),
+            None => lines.push(format!("      <guid>{}</guid>", escape_html(&url))),
+        }
+        if let Some(excerpt) = variable("excerpt") {
+            lines.push(format!("      <description>{}</description>", escape_html(excerpt)));
+        }
+        if let Some(date) = variable("publish_date").or(variable("date")) {
+            let date = rss_date(date).ok_or(invalid("date", date, "YYYY-MM-DD or YYYY-MM-DDTHH:MM:SS"))?;
+            lines.push(format!("      <pubDate>{}</pubDate>", date));
+        }
+
+        if let Some(enclosure) = variable("enclosure") {
+            let length = variable("enclosure_length").unwrap_or("0");
+            if length.parse::<u64>().is_err() {
+                Err(invalid("enclosure_length", length, "a number of bytes"))?;
+            }
+            let kind = variable("enclosure_type").unwrap_or_else(|| enclosure_type(enclosure));
+            lines.push(format!("      <enclosure url=\"{}\" length=\"{}\" type=\"{}\"/>", escape_html(&absolute_url(enclosure, base_url)), length, escape_html(kind)));
+        }
+        if let Some(duration) = variable("duration") {
+            // Either a number of seconds, or HH:MM:SS, or MM:SS.
+            let parts = duration.split(':').collect::<Vec<&str>>();
+            if parts.len() > 3 || parts.iter().any(|part| part.is_empty() || !part.chars().all(|c| c.is_ascii_digit())) {
+                Err(invalid("duration", duration, "a number of seconds, or HH:MM:SS"))?;
+            }
+            lines.push(format!("      <itunes:duration>{}</itunes:duration>", duration));
+        }
+        for key in ["season", "episode"] {
+            if let Some(number) = variable(key) {
+                if !number.parse::<u32>().is_ok_and(|number| number > 0) {
+                    Err(invalid(key, number, "a positive number"))?;
+                }
+                lines.push(format!("      <itunes:{key}>{number}</itunes:{key}>"));
+                lines.push(format!("      <podcast:{key}>{number}</podcast:{key}>"));
+            }
+        }
+        lines.push("    </item>".to_string());
+    }
+    lines.push("  </channel>".to_string());
+    lines.push("</rss>".to_string());
+
+    Ok(lines.join("\n") + "\n")
+}
+
+/// The posts as a JSON array for a client-side search library, such as Lunr
+/// or Fuse, with the `title`, `url`, `excerpt`, `tags`, and `body` of each.
+///
+/// The `body` is the text of the post, without its heading, any Markdown, or
+/// any shortcodes, and its `url` is relative to the `base_url`, as with [`posts_to_rss`].
+///
+/// Requires the `json` feature.
+///
+/// # Example
+/// ```rust
+/// use std::{collections::BTreeMap, path::{Path, PathBuf}};
+/// use blogs_md_easy::{posts_to_search_index, Post};
+///
+/// let post = Post {
+///     markdown_path: PathBuf::from("hello.md"),
+///     output_path: PathBuf::from("dist/posts/hello.html"),
+///     variables: BTreeMap::from([
+///         ("title".to_string(), "Hello".to_string()),
+///         ("excerpt".to_string(), "The first post.".to_string()),
+///         ("tags".to_string(), "rust, nom".to_string()),
+///         ("content".to_string(), "The first post.".to_string()),
+///     ]),
+/// };
+///
+/// let json = posts_to_search_index(&[post], Path::new("dist"), "https://example.com/");
+/// let index: serde_json::Value = serde_json::from_str(&json).unwrap();
+/// assert_eq!(index[0]["url"], "https://example.com/posts/hello.html");
+/// assert_eq!(index[0]["tags"], serde_json::json!(["rust", "nom"]));
+/// assert_eq!(index[0]["body"], "The first post.");
+/// ```
+#[cfg(feature = "json")]
+pub fn posts_to_search_index(posts: &[Post], output_dir: &Path, base_url: &str) -> String {
+    let posts = posts
+        .iter()
+        .map(|post| {
+            let variable = |key: &str| post.variables.get(key).map(|value| value.trim()).unwrap_or_default();
+            let tags = split_meta_list(variable("tags"));
+            let body = html_to_text(&render_filter(without_tags(variable("content")), &Filter::Markdown {
+                tables: true,
+                strikethrough: true,
+                tasklists: true,
+                footnotes: true,
+                smart_punctuation: false,
+                hard_breaks: false,
+                html: true,
+                heading_offset: 0,
+            }).unwrap_or_default());
+
+            serde_json::json!({
+                "title": variable("title"),
+                "url": post_url(post, output_dir, base_url),
+                "excerpt": variable("excerpt"),
+                "tags": tags,
+                "body": body.trim(),
+            })
+        })
+        .collect::<Vec<serde_json::Value>>();
+
+    serde_json::to_string_pretty(&posts).unwrap_or_default()
+}
+
+/// The outputs of a [`render_batch`](crate::render_batch) as a JSON array, for
+/// deploy scripts and auditing tools, with the `markdown`, `template`,
+/// `output`, `variants`, `meta`, `content_hash`, `duration_ms`, and `skipped`
+/// of each.
+///
+/// The `content_hash` is the SHA-256 of the output, which is `null` when the
+/// output was skipped.
+///
+/// Requires the `json` feature.
+///
+/// # Example
+/// ```rust
+/// use std::{path::PathBuf, time::Duration};
+/// use blogs_md_easy::{progress_to_manifest, BatchProgress, Meta};
+///
+/// let progress = BatchProgress {
+///     index: 0,
+///     total: 1,
+///     template_path: PathBuf::from("post.html"),
+///     markdown_path: PathBuf::from("hello.md"),
+///     output_path: PathBuf::from("dist/hello.html"),
+///     unused_variables: vec![],
+///     missing_variables: vec![],
+///     skipped: false,
+///     noindex: false,
+///     warnings: vec![],
+///     variant_paths: vec![],
+///     meta: vec![Meta::new("author", "John Doe")],
+///     content_hash: Some("dffd6021".to_string()),
+///     duration: Duration::from_millis(12),
+/// };
+///
+/// let json = progress_to_manifest(&[progress]);
+/// let manifest: serde_json::Value = serde_json::from_str(&json).unwrap();
+/// assert_eq!(manifest[0]["output"], "dist/hello.html");
+/// assert_eq!(manifest[0]["meta"]["author"], "John Doe");
+/// assert_eq!(manifest[0]["duration_ms"], 12.0);
+/// ```
+#[cfg(feature = "json")]
+pub fn progress_to_manifest(progress: &[BatchProgress]) -> String {
+    let path = |path: &Path| path.to_string_lossy().replace('\\', "/");
+    let outputs = progress
+        .iter()
+        .map(|progress| {
+            let meta = progress.meta
+                .iter()
+                .map(|meta| (meta.key.to_owned(), serde_json::Value::from(meta.value.to_owned())))
+                .collect::<serde_json::Map<String, serde_json::Value>>();
+
+            serde_json::json!({
+                "markdown": path(&progress.markdown_path),
+                "template": path(&progress.template_path),
+                "output": path(&progress.output_path),
+                "variants": progress.variant_paths.iter().map(|variant| path(variant)).collect::<Vec<String>>(),
+                "meta": meta,
+                "content_hash": progress.content_hash,
+                "duration_ms": progress.duration.as_micros() as f64 / 1000.0,
+                "skipped": progress.skipped,
+            })
+        })
+        .collect::<Vec<serde_json::Value>>();
+
+    serde_json::to_string_pretty(&outputs).unwrap_or_default()
+}
+
+/// The `text` without any `{{ ... }}` tags, such as shortcodes and embeds,
+/// which aren't part of what a post says.
+#[cfg(feature = "json")]
+fn without_tags(text: &str) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        rest = rest[start..].find("}}").map_or("", |end| &rest[start + end + 2..]);
+    }
+    output.push_str(rest);
+    output
+}
+
+/// A URL relative to the `base_url`, unless it is already a full URL.
+fn absolute_url(url: &str, base_url: &str) -> String {
+    match url.contains("://") {
+        true => url.to_string(),
+        false => format!("{}/{}", base_url.trim_end_matches('/'), url.trim_start_matches('/')),
+    }
+}
+
+/// Guess the MIME type of an enclosure from its extension.
+fn enclosure_type(url: &str) -> &'static str {
+    let extension = url.rsplit_once('.').map(|(_, extension)| extension.to_lowercase()).unwrap_or_default();
+    match extension.as_str() {
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/x-m4a",
+        "aac" => "audio/aac",
+        "ogg" | "oga" => "audio/ogg",
+        "opus" => "audio/opus",
+        "flac" => "audio/flac",
+        "wav" => "audio/wav",
+        "mp4" | "m4v" => "video/mp4",
+        "webm" => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Convert a date, or date and time, into the RFC 822 format of RSS, in UTC.
+/// Dates are the start of their day, as are times without an offset.
+fn rss_date(date: &str) -> Option<String> {
+    let (date, _) = ics_date(date)?;
+    let number = |range: std::ops::Range<usize>| date.get(range).and_then(|n| n.parse::<i64>().ok());
+    let (year, month, day) = (number(0..4)?, number(4..6)?, number(6..8)?);
+    let (hour, minute, second) = match date.get(8..9) {
+        Some("T") => (number(9..11)?, number(11..13)?, number(13..15)?),
+        _ => (0, 0, 0),
+    };
+
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    // The Unix epoch was a Thursday.
+    let weekday = WEEKDAYS[days_from_civil(year, month, day).rem_euclid(7) as usize];
+    Some(format!("{}, {:02} {} {:04} {:02}:{:02}:{:02} +0000", weekday, day, MONTHS.get((month - 1) as usize)?, year, hour, minute, second))
+}
+
+/// Convert a number of seconds since the Unix epoch into a `YYYY-MM-DD` date,
+/// in UTC.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::unix_to_date;
+///
+/// assert_eq!(unix_to_date(0), "1970-01-01");
+/// assert_eq!(unix_to_date(951_782_400), "2000-02-29");
+/// assert_eq!(unix_to_date(1_735_689_599), "2024-12-31");
+/// ```
+pub fn unix_to_date(seconds: u64) -> String {
+    // Howard Hinnant's `civil_from_days`, shifting the epoch to 0000-03-01 so
+    // that leap days fall at the end of each year.
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// The width and height of an image, from the header of a PNG, GIF, JPEG, or
+/// WebP, or `None` for any other format.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::image_size;
+///
+/// let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+/// png.extend(640_u32.to_be_bytes());
+/// png.extend(480_u32.to_be_bytes());
+/// assert_eq!(image_size(&png), Some((640, 480)));
+///
+/// assert_eq!(image_size(b"GIF89a\x20\x00\x10\x00"), Some((32, 16)));
+/// assert_eq!(image_size(b"<svg></svg>"), None);
+/// ```
+pub fn image_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |at: usize| Some(u16::from_be_bytes(bytes.get(at..at + 2)?.try_into().ok()?) as u32);
+    let be32 = |at: usize| Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?));
+    let le16 = |at: usize| Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?) as u32);
+    let le24 = |at: usize| Some(le16(at)? | (*bytes.get(at + 2)? as u32) << 16);
+
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => Some((be32(16)?, be32(20)?)),
+        [b'G', b'I', b'F', b'8', ..] => Some((le16(6)?, le16(8)?)),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => match bytes.get(12..16)? {
+            b"VP8 " => Some((le16(26)? & 0x3fff, le16(28)? & 0x3fff)),
+            b"VP8L" => {
+                let bits = le24(21)? | (*bytes.get(24)? as u32) << 24;
+                Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+            },
+            b"VP8X" => Some((le24(24)? + 1, le24(27)? + 1)),
+            _ => None,
+        },
+        [0xff, 0xd8, ..] => {
+            // Find the start of the frame, which has the size of the image.
+            let mut offset = 2;
+            loop {
+                if *bytes.get(offset)? != 0xff {
+                    return None;
+                }
+                let marker = *bytes.get(offset + 1)?;
+                if marker == 0xff {
+                    offset += 1;
+                    continue;
+                }
+                if (0xc0..=0xcf).contains(&marker) && ![0xc4, 0xc8, 0xcc].contains(&marker) {
+                    return Some((be16(offset + 7)?, be16(offset + 5)?));
+                }
+                offset += 2 + be16(offset + 2)? as usize;
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Write the `files`, by their path within the archive, into a zip archive,
+/// deflating each of them.
+///
+/// Every file is dated 1980-01-01, the earliest date of a zip archive, so
+/// that the same files always make the same archive.
+///
+/// Requires the `compression` feature.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::write_zip;
+///
+/// let zip = write_zip(&[("hello.txt".to_string(), b"Hello, World!".to_vec())]).unwrap();
+/// assert!(zip.starts_with(b"PK\x03\x04"));
+/// assert_eq!(zip, write_zip(&[("hello.txt".to_string(), b"Hello, World!".to_vec())]).unwrap());
+/// ```
+#[cfg(feature = "compression")]
+pub fn write_zip(files: &[(String, Vec<u8>)]) -> Result<Vec<u8>, Box<dyn Error>> {
+    use std::io::Write;
+
+    // Names are UTF-8, and each file is deflated.
+    const FLAGS: u16 = 0x0800;
+    const DEFLATE: u16 = 8;
+    const VERSION: u16 = 20;
+    // 1980-01-01 at midnight, in MS-DOS format.
+    const DATE: u16 = (1 << 5) | 1;
+
+    let too_large = || "Unable to write a zip archive larger than 4 GiB.".to_string();
+    let mut zip = Vec::new();
+    let mut directory = Vec::new();
+    for (name, bytes) in files {
+        let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(bytes)?;
+        let compressed = encoder.finish()?;
+        let mut crc = flate2::Crc::new();
+        crc.update(bytes);
+
+        let offset = u32::try_from(zip.len()).map_err(|_| too_large())?;
+        let sizes = [u32::try_from(compressed.len()).map_err(|_| too_large())?, u32::try_from(bytes.len()).map_err(|_| too_large())?];
+        let name_length = u16::try_from(name.len()).map_err(|_| format!("Unable to add '{}' to a zip archive, as its name is too long.", name))?;
+        // The fields that the local header and the central directory share.
+        let mut fields = Vec::new();
+        for field in [VERSION, FLAGS, DEFLATE, 0, DATE] {
+            fields.extend(field.to_le_bytes());
+        }
+        for field in [crc.sum(), sizes[0], sizes[1]] {
+            fields.extend(field.to_le_bytes());
+        }
+        fields.extend(name_length.to_le_bytes());
+        fields.extend(0_u16.to_le_bytes());
+
+        zip.extend(0x04034b50_u32.to_le_bytes());
+        zip.extend(&fields);
+        zip.extend(name.as_bytes());
+        zip.extend(compressed);
+
+        directory.extend(0x02014b50_u32.to_le_bytes());
+        directory.extend(VERSION.to_le_bytes());
+        directory.extend(&fields);
+        // The comment, disk, and attributes are all empty.
+        directory.extend([0; 10]);
+        directory.extend(offset.to_le_bytes());
+        directory.extend(name.as_bytes());
+    }
+
+    let count = u16::try_from(files.len()).map_err(|_| "Unable to write a zip archive of more than 65535 files.".to_string())?;
+    let offset = u32::try_from(zip.len()).map_err(|_| too_large())?;
+    let size = u32::try_from(directory.len()).map_err(|_| too_large())?;
+    zip.extend(directory);
+    zip.extend(0x06054b50_u32.to_le_bytes());
+    zip.extend([0; 4]);
+    zip.extend(count.to_le_bytes());
+    zip.extend(count.to_le_bytes());
+    zip.extend(size.to_le_bytes());
+    zip.extend(offset.to_le_bytes());
+    zip.extend([0; 2]);
+
+    Ok(zip)
+}
+
+/// Create a Markdown that satisfies every placeholder of the template at
+/// `template_path`, with recognisable sample values such as `[author]`, so
+/// that a layout can be previewed before there is any content.
+///
+/// The placeholders within every branch of the template's feature blocks, and
+/// within its includes and fragments, are included. Variables that are created for every
+/// Markdown, such as `£content`, are left to the body of the Markdown.
+///
+/// # Example
+/// ```rust
+/// use std::fs;
+/// use blogs_md_easy::sample_markdown;
+///
+/// let template_path = std::env::temp_dir().join("blogs_md_easy_sample_markdown.html");
+/// fs::write(&template_path, "<h1>{{ £title }}</h1><p>By {{ £author.name }}</p>{{ if feature \"date\" }}{{ £date }}{{ endif }}{{ £content }}").unwrap();
+///
+/// let markdown = sample_markdown(&template_path).unwrap();
+/// assert_eq!(markdown, ":meta\nauthor.name = \"[author.name]\"\ndate = \"[date]\"\n:meta\n# [title]\n\n[content]\n");
+/// ```
+pub fn sample_markdown(template_path: &Path) -> Result<String, Box<dyn Error>> {
+    let directory = template_path.parent().unwrap_or(Path::new(""));
+    let source = apply_includes(&apply_whitespace_control(&read_file(template_path)?), directory, &Syntax::default())
+        .and_then(|source| apply_fragments(&strip_features(&source), directory))
+        .map_err(|e| format!("{} In template '{}'.", e, template_path.to_string_lossy()))?;
+    let template = Template::new(&source).map_err(|e| match e.downcast::<ParseError>() {
+        Ok(error) => Box::new(SourceError::new(template_path.to_owned(), &source, *error)),
+        Err(e) => e,
+    })?;
+
+    let body = "# [title]\n\n[content]\n";
+    let created = create_variables(Span::new(body), vec![])?;
+    let meta = template
+        .required_variables()
+        .into_iter()
+        .filter(|name| !created.contains_key(name))
+        .map(|name| format!("{} = \"[{}]\"\n", name, name))
+        .collect::<String>();
+
+    Ok(match meta.is_empty() {
+        true => body.to_string(),
+        false => format!(":meta\n{}:meta\n{}", meta, body),
+    })
+}
+
+/// Create the contents of a new Markdown from an archetype.
+///
+/// Archetypes are Markdown files within `archetypes_dir`, named after their
+/// `kind`, such as `archetypes/review.md`, which contain pre-filled meta keys
+/// and a skeleton for the body. Any placeholders are replaced with the
+/// `variables`, and those without a variable are left empty, with a
+/// [`Warning::MissingVariable`] for each, so that they can be filled in.
+///
+/// Without a `kind`, then `archetypes/default.md` is used if it exists,
+/// otherwise [`DEFAULT_ARCHETYPE`].
+///
+/// # Example
+/// ```rust
+/// use std::{collections::HashMap, path::Path};
+/// use blogs_md_easy::render_archetype;
+///
+/// let variables = HashMap::from([
+///     ("title".to_string(), "Hello World".to_string()),
+///     ("date".to_string(), "2024-01-01".to_string()),
+/// ]);
+/// let (markdown, warnings) = render_archetype(Path::new("archetypes"), None, &variables).unwrap();
+/// assert_eq!(markdown, ":meta\ndate = 2024-01-01\n:meta\n# Hello World\n");
+/// assert!(warnings.is_empty());
+///
+/// // A kind must exist.
+/// assert!(render_archetype(Path::new("archetypes"), Some("review"), &variables).is_err());
+/// ```
+pub fn render_archetype(archetypes_dir: &Path, kind: Option<&str>, variables: &HashMap<String, String>) -> Result<(String, Vec<Warning>), Box<dyn Error>> {
+    let archetype = match kind {
+        Some(kind) => {
+            let path = archetypes_dir.join(kind).with_extension("md");
+            if !path.exists() {
+                Err(format!("The archetype '{}' could not be found in '{}'.", kind, archetypes_dir.to_string_lossy()))?;
+            }
+            read_file(&path)?
+        },
+        None => {
+            let path = archetypes_dir.join("default.md");
+            if path.exists() { read_file(&path)? } else { DEFAULT_ARCHETYPE.to_string() }
+        },
+    };
+
+    let template = Template::new(&archetype)?;
+    let warnings = template.missing_variables(variables).into_iter().map(Warning::MissingVariable).collect();
+    Ok((template.render_with_policy(variables, &MissingVariablePolicy::ReplaceWithEmpty)?, warnings))
+}
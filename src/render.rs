@@ -0,0 +1,6162 @@
+//! Rendering Markdowns into templates, from a single [`Template`] to each file
+//! of a batch, see [`crate::batch`] for the batch itself.
+
+use std::{borrow::Cow, collections::{BTreeMap, HashMap}, error::Error, ffi::OsStr, fmt, fs, io, ops::Range, path::{Path, PathBuf}, str::FromStr, sync::{Arc, Mutex}};
+use nom::{InputTake, Slice};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use sha2::{Digest, Sha256};
+use crate::{batch::{BatchOptions, BatchProgress}, error::{Diagnostic, Error as ParseError, SourceError}, filters::{render_filter, render_filters, Filter, FilterError}, meta::{embed_meta, escape_html, load_variables, edit_meta, split_meta_list, Meta, MetaEdit}, parser::{meta_errors, parse_feature_tag, parse_include_tag, parse_loop_tag, parse_meta_filters, parse_meta_section, parse_embed_tag, parse_literal, parse_placeholder_locations, parse_ref_tag, parse_render_tag, parse_shortcode_tag, parse_title, parse_variable, recover_placeholder_locations, EmbedKind, EmbedTag, FeatureTag, LoopTag, Placeholder, Selection, Span, Syntax}, permalink::{batch_output_path, permalinks}, project::{image_size, unix_to_date, Fediverse, Webmentions}};
+#[cfg(all(feature = "compression", feature = "json"))]
+use crate::project::write_zip;
+
+////////////////////////////////////////////////////////////////////////////////
+// Structs and types
+/// A template that has been parsed, ready to be populated with variables.
+///
+/// The [`Placeholder`]s are located once, when the `Template` is created, so
+/// the same `Template` can be rendered with any number of Markdowns.
+///
+/// # Example
+/// ```rust
+/// use std::collections::HashMap;
+/// use blogs_md_easy::Template;
+///
+/// let template = Template::new("<h1>{{ £title | uppercase }}</h1>").unwrap();
+/// let variables = HashMap::from([("title".to_string(), "Hello, World!".to_string())]);
+/// assert_eq!(template.render(&variables).unwrap(), "<h1>HELLO, WORLD!</h1>");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Template {
+    source: String,
+    placeholders: Vec<Placeholder>,
+    errors: Vec<ParseError>,
+}
+
+impl Template {
+    /// Parse the `source` of a template, locating all of its [`Placeholder`]s.
+    ///
+    /// Literal text, such as `\{{` or a `{{ raw }}` block, is unescaped, so its
+    /// placeholders are rendered as they are written.
+    pub fn new(source: &str) -> Result<Self, Box<dyn Error>> {
+        let placeholders = parse_placeholder_locations(Span::new(source))?;
+        Ok(Self::with_placeholders(source, placeholders, vec![]))
+    }
+
+    /// Parse the `source` of a template, like [`Template::new`], but skipping
+    /// over any malformed placeholders, which are left in the template as they
+    /// are written, and kept as its [`errors`](Template::errors).
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use blogs_md_easy::Template;
+    ///
+    /// let template = Template::recover("<h1>{{ £title }}</h1><p>{{ £author | shout }}</p>");
+    /// assert_eq!(template.errors()[0].diagnostic().message, "unknown filter 'shout'");
+    ///
+    /// let variables = HashMap::from([("title".to_string(), "Hello, World!".to_string())]);
+    /// assert_eq!(template.render(&variables).unwrap(), "<h1>Hello, World!</h1><p>{{ £author | shout }}</p>");
+    /// ```
+    pub fn recover(source: &str) -> Self {
+        let (placeholders, errors) = recover_placeholder_locations(Span::new(source));
+        Self::with_placeholders(source, placeholders, errors)
+    }
+
+    /// Unescape the literal text of the `source`, moving the `placeholders`
+    /// back by however much of it before them was removed.
+    fn with_placeholders(source: &str, mut placeholders: Vec<Placeholder>, errors: Vec<ParseError>) -> Self {
+        let (source, removed) = unescape_with_offsets(source);
+        for placeholder in placeholders.iter_mut() {
+            for marker in [&mut placeholder.selection.start, &mut placeholder.selection.end] {
+                let index = removed.partition_point(|(offset, _)| *offset <= marker.offset);
+                marker.offset -= index.checked_sub(1).map(|index| removed[index].1).unwrap_or_default();
+            }
+        }
+
+        Self { source, placeholders, errors }
+    }
+
+    /// The malformed placeholders that [`Template::recover`] skipped over.
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+
+    /// The unrendered template, with its literal text unescaped.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Every [`Placeholder`] in the template, sorted in reverse order of where
+    /// they appear.
+    pub fn placeholders(&self) -> &[Placeholder] {
+        &self.placeholders
+    }
+
+    /// The names of every variable that must be provided in order to render
+    /// this template, sorted alphabetically, and without duplicates.
+    ///
+    /// A variable whose every placeholder has a default, such as
+    /// `{{ £author ?? "Anonymous" }}`, is not required. Variables used as
+    /// filter arguments, such as `£excerpt_length`, are.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::Template;
+    ///
+    /// let template = Template::new("<title>{{ £title }}</title><h1>{{ £title }}</h1>{{ £author ?? Anonymous }}{{ £content }}").unwrap();
+    /// assert_eq!(template.required_variables(), vec!["content", "title"]);
+    ///
+    /// let template = Template::new("{{ £summary | truncate = characters: £excerpt_length }}").unwrap();
+    /// assert_eq!(template.required_variables(), vec!["excerpt_length", "summary"]);
+    /// ```
+    pub fn required_variables(&self) -> Vec<String> {
+        let mut variables = self.placeholders.iter()
+            .flat_map(|p| p.argument_variables(&self.source).into_iter().chain((!p.has_default()).then(|| p.name.to_owned())))
+            .collect::<Vec<String>>();
+        variables.sort();
+        variables.dedup();
+        variables
+    }
+
+    /// The names of every variable that the template uses, including those
+    /// with a default, sorted alphabetically, and without duplicates.
+    pub(crate) fn used_variables(&self) -> Vec<String> {
+        let mut variables = self.placeholders.iter()
+            .flat_map(|p| p.argument_variables(&self.source).into_iter().chain([p.name.to_owned()]))
+            .collect::<Vec<String>>();
+        variables.sort();
+        variables.dedup();
+        variables
+    }
+
+    /// The names of the variables declared in `meta_values` that are never used
+    /// by this template, sorted alphabetically, and without duplicates.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{Meta, Template};
+    ///
+    /// let template = Template::new("<h1>{{ £title }}</h1>").unwrap();
+    /// let meta_values = vec![Meta::new("title", "Hello"), Meta::new("author", "John Doe")];
+    /// assert_eq!(template.unused_variables(&meta_values), vec!["author"]);
+    /// ```
+    pub fn unused_variables(&self, meta_values: &[Meta]) -> Vec<String> {
+        let mut required_variables = self.used_variables();
+        // A variable that another meta value is derived from is used by it.
+        required_variables.extend(meta_values.iter().filter_map(|meta| {
+            parse_meta_filters(Span::new(&meta.value)).ok().map(|(_, (name, _))| name.to_string())
+        }));
+        let mut variables = meta_values
+            .iter()
+            .map(|meta| meta.key.to_owned())
+            .filter(|key| !required_variables.contains(key))
+            .collect::<Vec<String>>();
+        variables.sort();
+        variables.dedup();
+        variables
+    }
+
+    /// The names of the variables used by this template that are not within
+    /// `variables`, sorted alphabetically, and without duplicates.
+    ///
+    /// This catches typos, such as `£titel`, that would otherwise be handled
+    /// silently by a [`MissingVariablePolicy`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use blogs_md_easy::Template;
+    ///
+    /// let template = Template::new("<h1>{{ £titel }}</h1>").unwrap();
+    /// let variables = HashMap::from([("title".to_string(), "Hello".to_string())]);
+    /// assert_eq!(template.missing_variables(&variables), vec!["titel"]);
+    /// ```
+    pub fn missing_variables(&self, variables: &HashMap<String, String>) -> Vec<String> {
+        self.required_variables().into_iter().filter(|name| !variables.contains_key(name)).collect()
+    }
+
+    /// Every distinct [`Filter`] used by the template, in the order that they
+    /// first appear.
+    ///
+    /// This includes any [`Filter`] that is applied automatically, such as the
+    /// [`Filter::Markdown`] on `£content`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{Filter, Template, TextCase};
+    ///
+    /// let template = Template::new("{{ £title | uppercase }} {{ £author | uppercase | reverse }}").unwrap();
+    /// assert_eq!(template.used_filters(), vec![
+    ///     Filter::Text { case: TextCase::Upper },
+    ///     Filter::Reverse,
+    /// ]);
+    /// ```
+    pub fn used_filters(&self) -> Vec<Filter> {
+        // Placeholders are stored in reverse, so flip them back to find the
+        // order of appearance.
+        self.placeholders.iter().rev().flat_map(|p| &p.filters).fold(Vec::new(), |mut filters, filter| {
+            if !filters.contains(filter) {
+                filters.push(filter.to_owned());
+            }
+            filters
+        })
+    }
+
+    /// Every distinct chain of [`Filter`]s that is applied to the variable
+    /// `name`, in the order that they first appear.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{Filter, Template, TextCase};
+    ///
+    /// let template = Template::new("{{ £title }} {{ £title | uppercase }} {{ £title }}").unwrap();
+    /// assert_eq!(template.filter_chains("title"), vec![
+    ///     vec![],
+    ///     vec![Filter::Text { case: TextCase::Upper }],
+    /// ]);
+    /// ```
+    pub fn filter_chains(&self, name: &str) -> Vec<Vec<Filter>> {
+        self.placeholders.iter().rev().filter(|p| p.name == name).fold(Vec::new(), |mut chains, placeholder| {
+            if !chains.contains(&placeholder.filters) {
+                chains.push(placeholder.filters.to_owned());
+            }
+            chains
+        })
+    }
+
+    /// Compare the variables and [`Filter`]s of this template with a `newer`
+    /// template.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{Filter, Template};
+    ///
+    /// let old = Template::new("{{ £title }} {{ £author }}").unwrap();
+    /// let new = Template::new("{{ £title | reverse }} {{ £date }}").unwrap();
+    /// let diff = old.diff(&new);
+    ///
+    /// assert_eq!(diff.added_variables, vec!["date"]);
+    /// assert_eq!(diff.removed_variables, vec!["author"]);
+    /// assert_eq!(diff.changed_filters[0].name, "title");
+    /// assert_eq!(diff.changed_filters[0].new, vec![vec![Filter::Reverse]]);
+    /// ```
+    pub fn diff(&self, newer: &Template) -> TemplateDiff {
+        let old_variables = self.used_variables();
+        let new_variables = newer.used_variables();
+
+        TemplateDiff {
+            added_variables: new_variables.iter().filter(|v| !old_variables.contains(v)).cloned().collect(),
+            removed_variables: old_variables.iter().filter(|v| !new_variables.contains(v)).cloned().collect(),
+            changed_filters: old_variables
+                .iter()
+                .filter(|v| new_variables.contains(v))
+                .map(|name| FilterChange {
+                    name: name.to_owned(),
+                    old: self.filter_chains(name),
+                    new: newer.filter_chains(name),
+                })
+                .filter(|change| {
+                    change.old.len() != change.new.len()
+                    || change.old.iter().any(|chain| !change.new.contains(chain))
+                })
+                .collect(),
+        }
+    }
+
+    /// Replace every [`Placeholder`] with its variable, see
+    /// [`render_placeholders`].
+    pub fn render(&self, variables: &HashMap<String, String>) -> Result<String, Box<dyn Error>> {
+        render_placeholders(&self.source, &self.placeholders, variables)
+    }
+
+    /// Render a Markdown into this template, as [`render`] does, but without
+    /// parsing the template again, so that one `Template` can render any
+    /// number of Markdowns.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::Template;
+    ///
+    /// let template = Template::new("<h1>{{ £title }}</h1><p>By {{ £author }}</p>").unwrap();
+    /// for (markdown, html) in [
+    ///     (":meta\nauthor = John Doe\n:meta\n# Hello", "<h1>Hello</h1><p>By John Doe</p>"),
+    ///     (":meta\nauthor = Jane Doe\n:meta\n# World", "<h1>World</h1><p>By Jane Doe</p>"),
+    /// ] {
+    ///     assert_eq!(template.render_markdown(markdown).unwrap(), html);
+    /// }
+    /// ```
+    pub fn render_markdown(&self, markdown: &str) -> Result<String, RenderError> {
+        self.render_markdown_with_warnings(markdown).map(|(output, _)| output)
+    }
+
+    /// Render a Markdown into this template, along with its warnings, as
+    /// [`render_with_warnings`] does, but without parsing the template again.
+    pub fn render_markdown_with_warnings(&self, markdown: &str) -> Result<(String, Vec<Warning>), RenderError> {
+        let mut rendering = Rendering {
+            markdown: markdown.to_string(),
+            parsed: self.clone(),
+            ..Default::default()
+        };
+        parse_markdown(&mut rendering)?;
+        for step in [Step::Resolve, Step::Filter, Step::Substitute, Step::Postprocess] {
+            BuiltIn(step).run(&mut rendering)?;
+        }
+        Ok((rendering.output, rendering.warnings))
+    }
+
+    /// Replace every [`Placeholder`] with its variable, as
+    /// [`Template::render`] does, but write the output to the `writer` as it
+    /// is rendered, rather than returning it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use blogs_md_easy::Template;
+    ///
+    /// let template = Template::new("<h1>{{ £title | uppercase }}</h1>").unwrap();
+    /// let variables = HashMap::from([("title".to_string(), "Hello, World!".to_string())]);
+    ///
+    /// let mut output = Vec::new();
+    /// template.render_to(&mut output, &variables).unwrap();
+    /// assert_eq!(output, b"<h1>HELLO, WORLD!</h1>");
+    /// ```
+    pub fn render_to<W: io::Write>(&self, mut writer: W, variables: &HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+        let options = BatchOptions::default();
+        // Once a write fails, the rest of the output is skipped.
+        let mut written = Ok(());
+        write_placeholders(&self.source, &self.placeholders, |name| variables.get(name).map(|value| Cow::Borrowed(value.as_str())), &options, |chunk| {
+            if written.is_ok() {
+                written = writer.write_all(chunk.as_bytes());
+            }
+        })?;
+        Ok(written?)
+    }
+
+    /// Render a Markdown into this template, as
+    /// [`Template::render_markdown`] does, but write the output to the
+    /// `writer` as it is rendered, see [`render_to`].
+    pub fn render_markdown_to<W: io::Write>(&self, writer: W, markdown: &str) -> Result<(), RenderError> {
+        let mut rendering = Rendering {
+            markdown: markdown.to_string(),
+            parsed: self.clone(),
+            ..Default::default()
+        };
+        parse_markdown(&mut rendering)?;
+        BuiltIn(Step::Resolve).run(&mut rendering)?;
+        if let Some(name) = self.required_variables().into_iter().find(|name| !rendering.variables.contains_key(name)) {
+            return Err(RenderError::MissingVariable(name));
+        }
+        self.render_to(writer, &rendering.variables).map_err(|e| match e.downcast::<io::Error>() {
+            Ok(error) => RenderError::Write(error.to_string()),
+            Err(error) => RenderError::Template(error.to_string()),
+        })
+    }
+
+    /// Replace every [`Placeholder`] with its variable, handling variables
+    /// that don't exist with the `policy`, see
+    /// [`render_placeholders_with_policy`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use blogs_md_easy::{MissingVariablePolicy, Template};
+    ///
+    /// let template = Template::new("<h1>{{ £title }}</h1><p>{{ £author }}</p>").unwrap();
+    /// let variables = HashMap::from([("title".to_string(), "Hello".to_string())]);
+    /// let policy = MissingVariablePolicy::UseDefault("Anonymous".to_string());
+    /// assert_eq!(template.render_with_policy(&variables, &policy).unwrap(), "<h1>Hello</h1><p>Anonymous</p>");
+    /// ```
+    pub fn render_with_policy(&self, variables: &HashMap<String, String>, policy: &MissingVariablePolicy) -> Result<String, Box<dyn Error>> {
+        render_placeholders_with_policy(&self.source, &self.placeholders, variables, policy)
+    }
+}
+
+/// The differences between the placeholders of two [`Template`]s, as returned
+/// by [`Template::diff`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TemplateDiff {
+    /// Variables that are only required by the newer template.
+    pub added_variables: Vec<String>,
+    /// Variables that are only required by the older template.
+    pub removed_variables: Vec<String>,
+    /// Variables required by both templates, but with different [`Filter`]s.
+    pub changed_filters: Vec<FilterChange>,
+}
+
+impl TemplateDiff {
+    /// Whether the two templates have identical placeholders.
+    pub fn is_empty(&self) -> bool {
+        self.added_variables.is_empty()
+        && self.removed_variables.is_empty()
+        && self.changed_filters.is_empty()
+    }
+}
+
+/// A variable whose [`Filter`]s are different between two [`Template`]s.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FilterChange {
+    /// The name of the variable.
+    pub name: String,
+    /// The filter chains of the older template, see [`Template::filter_chains`].
+    pub old: Vec<Vec<Filter>>,
+    /// The filter chains of the newer template, see [`Template::filter_chains`].
+    pub new: Vec<Vec<Filter>>,
+}
+
+/// The parts of a template that none of the Markdowns of a batch used, in
+/// order to find template code that can be removed.
+///
+/// The branches of feature blocks are the same for every Markdown, so they
+/// are found from the features alone. A placeholder is unused until a
+/// Markdown that is rendered into the template has its variable, which is
+/// recorded from the [`BatchProgress`] of each Markdown.
+///
+/// # Example
+/// ```rust
+/// use std::{path::PathBuf, time::Duration};
+/// use blogs_md_easy::{BatchProgress, TemplateCoverage};
+///
+/// let template = "<h1>{{ £title }}</h1>\n{{ if feature \"comments\" }}\n<p>{{ £comments }}</p>\n{{ else }}\n<p>{{ £subtitle }}</p>\n{{ endif }}";
+/// let mut coverage = TemplateCoverage::new(template, &[]).unwrap();
+/// assert_eq!(coverage.unused_branches, vec![(2, "{{ if feature \"comments\" }}".to_string())]);
+///
+/// // Every Markdown was missing a subtitle.
+/// let progress = |missing_variables: Vec<String>| BatchProgress {
+///     index: 0,
+///     total: 1,
+///     template_path: PathBuf::from("post.html"),
+///     markdown_path: PathBuf::from("post.md"),
+///     output_path: PathBuf::from("post.html"),
+///     unused_variables: vec![],
+///     missing_variables,
+///     skipped: false,
+///     noindex: false,
+///     warnings: vec![],
+///     variant_paths: vec![],
+///     meta: vec![],
+///     content_hash: None,
+///     duration: Duration::ZERO,
+/// };
+/// coverage.record(&progress(vec!["subtitle".to_string()]));
+/// assert_eq!(coverage.unused_placeholders, vec![(5, "subtitle".to_string())]);
+/// coverage.record(&progress(vec![]));
+/// assert!(coverage.unused_placeholders.is_empty());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TemplateCoverage {
+    /// The line of each `{{ if feature }}` or `{{ else }}` whose content is
+    /// never included, along with the tag.
+    pub unused_branches: Vec<(u32, String)>,
+    /// The line of each placeholder whose variable none of the Markdowns had,
+    /// along with its variable.
+    ///
+    /// Every placeholder is unused until a Markdown is recorded.
+    pub unused_placeholders: Vec<(u32, String)>,
+    /// The number of Markdowns that have been recorded.
+    pub markdowns: usize,
+}
+
+impl TemplateCoverage {
+    /// Find the branches of a template that the `features` never include, and
+    /// the placeholders of the rest.
+    pub fn new(template: &str, features: &[String]) -> Result<Self, Box<dyn Error>> {
+        let mut coverage = Self::default();
+        // Whether each of the open blocks is included, and its feature.
+        let mut blocks: Vec<(bool, String)> = Vec::new();
+        // The byte ranges of the template that are never included.
+        let mut excluded = Vec::new();
+        let mut input = Span::new(template);
+
+        while !input.fragment().is_empty() {
+            let included = blocks.iter().all(|(included, _)| *included);
+
+            if let Ok((remaining, feature_tag)) = parse_feature_tag(input) {
+                let tag = input.fragment()[..input.fragment().len() - remaining.fragment().len()].to_string();
+                let branch = match feature_tag {
+                    FeatureTag::If(name) => {
+                        blocks.push((features.contains(&name), name));
+                        Some(tag)
+                    },
+                    FeatureTag::Else => match blocks.pop() {
+                        Some((branch_included, name)) => {
+                            let tag = format!("{} of \"{}\"", tag, name);
+                            blocks.push((!branch_included, name));
+                            Some(tag)
+                        },
+                        None => Err(format!("Unexpected {{{{ else }}}} on line {}.", input.location_line()))?,
+                    },
+                    FeatureTag::EndIf => match blocks.pop() {
+                        Some(_) => None,
+                        None => Err(format!("Unexpected {{{{ endif }}}} on line {}.", input.location_line()))?,
+                    },
+                };
+                // Branches within a branch that is never included are
+                // reported by their outer branch.
+                if let (Some(branch), true, Some((false, _))) = (branch, included, blocks.last()) {
+                    coverage.unused_branches.push((input.location_line(), branch));
+                }
+                input = remaining;
+            } else {
+                let (remaining, text) = take_till_tag(input);
+                if !included {
+                    excluded.push(text.location_offset()..remaining.location_offset());
+                }
+                input = remaining;
+            }
+        }
+
+        if !blocks.is_empty() {
+            Err("Missing {{ endif }} for a feature block.".to_string())?;
+        }
+
+        let mut placeholders = parse_placeholder_locations(Span::new(template))?;
+        placeholders.reverse();
+        coverage.unused_placeholders = placeholders
+            .into_iter()
+            .filter(|placeholder| !excluded.iter().any(|range| range.contains(&placeholder.selection.start.offset)))
+            .map(|placeholder| (placeholder.selection.start.line, placeholder.name))
+            .collect();
+
+        Ok(coverage)
+    }
+
+    /// Record a Markdown that was rendered into this template, so that the
+    /// placeholders whose variables it had are no longer unused.
+    ///
+    /// Outputs that were skipped are ignored, as their variables aren't
+    /// checked.
+    pub fn record(&mut self, progress: &BatchProgress) {
+        if progress.skipped {
+            return;
+        }
+
+        self.markdowns += 1;
+        self.unused_placeholders.retain(|(_, name)| progress.missing_variables.contains(name));
+    }
+
+    /// Whether every part of the template was used.
+    pub fn is_empty(&self) -> bool {
+        self.unused_branches.is_empty() && (self.markdowns == 0 || self.unused_placeholders.is_empty())
+    }
+}
+
+/// A part of a rendered document that has changed, as returned by
+/// [`LivePreview::set_variable`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartialRender {
+    /// The byte offset where the change starts, in the document before the
+    /// change was made.
+    pub start: usize,
+    /// The byte offset where the change ends, in the document before the change
+    /// was made.
+    pub end: usize,
+    /// The text that replaces everything between `start` and `end`.
+    pub output: String,
+}
+
+/// A rendered [`Template`] that remembers where each [`Placeholder`] was
+/// rendered, so that changing a single variable only re-renders the parts of
+/// the document that use it.
+///
+/// This is intended for live preview editors, which can apply each
+/// [`PartialRender`] to their own copy of the document, rather than replacing
+/// the entire document on every keystroke.
+///
+/// # Example
+/// ```rust
+/// use std::collections::HashMap;
+/// use blogs_md_easy::{replace_substring, LivePreview, Template};
+///
+/// let template = Template::new("<title>{{ £title }}</title><h1>{{ £title | uppercase }}</h1><p>{{ £author }}</p>").unwrap();
+/// let variables = HashMap::from([
+///     ("title".to_string(), "Hello".to_string()),
+///     ("author".to_string(), "John Doe".to_string()),
+/// ]);
+///
+/// let mut preview = LivePreview::new(&template, variables).unwrap();
+/// let mut editor = preview.output().to_string();
+/// assert_eq!(editor, "<title>Hello</title><h1>HELLO</h1><p>John Doe</p>");
+///
+/// // Only the two title placeholders are re-rendered.
+/// let changes = preview.set_variable("title", "Hi").unwrap();
+/// assert_eq!(changes.len(), 2);
+/// for change in changes {
+///     editor = replace_substring(&editor, change.start, change.end, &change.output);
+/// }
+/// assert_eq!(editor, "<title>Hi</title><h1>HI</h1><p>John Doe</p>");
+/// assert_eq!(editor, preview.output());
+/// ```
+#[derive(Clone, Debug)]
+pub struct LivePreview<'a> {
+    template: &'a Template,
+    variables: HashMap<String, String>,
+    output: String,
+    /// Where each placeholder was rendered, in the order they appear.
+    ranges: Vec<(usize, usize)>,
+}
+
+impl<'a> LivePreview<'a> {
+    /// Render the `template` in full, remembering where each [`Placeholder`]
+    /// was rendered.
+    pub fn new(template: &'a Template, variables: HashMap<String, String>) -> Result<Self, Box<dyn Error>> {
+        let source = template.source();
+        let mut output = String::with_capacity(source.len());
+        let mut ranges = Vec::with_capacity(template.placeholders().len());
+        let mut last_offset = 0;
+
+        // Placeholders are stored in reverse, but we're building the output
+        // from start to finish.
+        for placeholder in template.placeholders().iter().rev() {
+            let variable = variables
+                .get(&placeholder.name)
+                .map(String::as_str)
+                .or(placeholder.has_default().then_some(""))
+                .ok_or(format!("Missing variable '{}'.", &placeholder.name))?;
+
+            output.push_str(&source[last_offset..placeholder.selection.start.offset]);
+            let start = output.len();
+            output.push_str(&Self::render_placeholder(template, &variables, placeholder, variable)?);
+            ranges.push((start, output.len()));
+            last_offset = placeholder.selection.end.offset;
+        }
+        output.push_str(&source[last_offset..]);
+
+        Ok(Self { template, variables, output, ranges })
+    }
+
+    /// The rendered document, including every change made so far.
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    /// Change the value of a single variable, returning only the parts of the
+    /// document that changed.
+    ///
+    /// The changes are sorted in reverse order, so that they can be applied
+    /// one after another, without affecting the offsets of the others.
+    ///
+    /// If a filter can't render the new value, such as a maths filter of a
+    /// value that isn't a number, the document is left as it was.
+    pub fn set_variable(&mut self, name: &str, value: &str) -> Result<Vec<PartialRender>, FilterError> {
+        let previous = self.variables.insert(name.to_string(), value.to_string());
+
+        let changes = self.template.placeholders()
+            .iter()
+            .rev()
+            .zip(self.ranges.iter())
+            .enumerate()
+            // Those that use it as a filter argument change too.
+            .filter(|(_, (placeholder, _))| placeholder.name == name || placeholder.argument_variables(self.template.source()).iter().any(|argument| argument == name))
+            .map(|(index, (placeholder, (start, end)))| Ok((index, PartialRender {
+                start: *start,
+                end: *end,
+                output: Self::render_placeholder(self.template, &self.variables, placeholder, self.variables.get(&placeholder.name).map(String::as_str).unwrap_or_default())?,
+            })))
+            .collect::<Result<Vec<(usize, PartialRender)>, FilterError>>();
+        let changes = match changes {
+            Ok(changes) => changes,
+            Err(error) => {
+                match previous {
+                    Some(previous) => self.variables.insert(name.to_string(), previous),
+                    None => self.variables.remove(name),
+                };
+                return Err(error);
+            },
+        };
+
+        // Shift every range by the difference in length of the changes before
+        // it.
+        let mut difference = 0_isize;
+        let mut changed = changes.iter().peekable();
+        for (index, (start, end)) in self.ranges.iter_mut().enumerate() {
+            let length = (*end - *start) as isize;
+            *start = (*start as isize + difference) as usize;
+            *end = (*end as isize + difference) as usize;
+
+            if let Some((_, change)) = changed.next_if(|(changed_index, _)| *changed_index == index) {
+                *end = *start + change.output.len();
+                difference += change.output.len() as isize - length;
+            }
+        }
+
+        let mut changes = changes.into_iter().map(|(_, change)| change).collect::<Vec<PartialRender>>();
+        changes.reverse();
+        for change in &changes {
+            self.output.replace_range(change.start..change.end, &change.output);
+        }
+
+        Ok(changes)
+    }
+
+    fn render_placeholder(template: &Template, variables: &HashMap<String, String>, placeholder: &Placeholder, variable: &str) -> Result<String, FilterError> {
+        let placeholder = placeholder.with_arguments(template.source(), &|name| variables.get(name).cloned());
+        render_filters(variable, &placeholder.filters).map(Cow::into_owned)
+    }
+}
+
+/// What to do with a [`Placeholder`] whose variable doesn't exist.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum MissingVariablePolicy {
+    /// Stop rendering, and return an error.
+    #[default]
+    Error,
+    /// Replace the placeholder with an empty string.
+    ReplaceWithEmpty,
+    /// Leave the placeholder in the output, exactly as it was written.
+    KeepPlaceholder,
+    /// Replace the placeholder with this value, after applying the
+    /// placeholder's [`Filter`]s.
+    UseDefault(String),
+}
+
+impl FromStr for MissingVariablePolicy {
+    type Err = String;
+
+    /// Parse a string slice, into a `MissingVariablePolicy`.
+    ///
+    /// A default value follows `default=`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use blogs_md_easy::MissingVariablePolicy;
+    ///
+    /// assert_eq!("error".parse::<MissingVariablePolicy>(), Ok(MissingVariablePolicy::Error));
+    /// assert_eq!("empty".parse::<MissingVariablePolicy>(), Ok(MissingVariablePolicy::ReplaceWithEmpty));
+    /// assert_eq!("keep".parse::<MissingVariablePolicy>(), Ok(MissingVariablePolicy::KeepPlaceholder));
+    /// assert_eq!("default=N/A".parse::<MissingVariablePolicy>(), Ok(MissingVariablePolicy::UseDefault("N/A".to_string())));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((policy, value)) = s.split_once('=') {
+            if policy.trim().to_lowercase() == "default" {
+                return Ok(Self::UseDefault(value.to_string()));
+            }
+        }
+
+        match s.trim().to_lowercase().as_str() {
+            "error" => Ok(Self::Error),
+            "empty" | "replace_with_empty" => Ok(Self::ReplaceWithEmpty),
+            "keep" | "keep_placeholder" => Ok(Self::KeepPlaceholder),
+            _ => Err(format!("Unable to parse MissingVariablePolicy from '{}'", s)),
+        }
+    }
+}
+
+/// Where the `£description` of a Markdown is taken from, see
+/// [`BatchOptions::description_from`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DescriptionSource {
+    /// The `description` in the meta section.
+    Meta,
+    /// The `£excerpt`, without its Markdown.
+    Excerpt,
+    /// The first 30 words of the `£content`, without its Markdown, or its
+    /// headings and code blocks.
+    Content,
+}
+
+impl FromStr for DescriptionSource {
+    type Err = String;
+
+    /// Parse a string slice, into a `DescriptionSource`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use blogs_md_easy::DescriptionSource;
+    ///
+    /// assert_eq!("meta".parse::<DescriptionSource>(), Ok(DescriptionSource::Meta));
+    /// assert_eq!("Excerpt".parse::<DescriptionSource>(), Ok(DescriptionSource::Excerpt));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "meta" => Ok(Self::Meta),
+            "excerpt" => Ok(Self::Excerpt),
+            "content" => Ok(Self::Content),
+            _ => Err(format!("Unable to parse DescriptionSource from '{}'", s)),
+        }
+    }
+}
+
+/// The order that the `£description` of a Markdown is taken from, unless
+/// [`BatchOptions::description_from`] is set.
+const DESCRIPTION_FROM: [DescriptionSource; 3] = [DescriptionSource::Meta, DescriptionSource::Excerpt, DescriptionSource::Content];
+
+/// Which `£title` to use when a Markdown has both a `title` in its meta
+/// section, and a heading.
+///
+/// Both are always available separately, as `£heading` for the heading, and
+/// the meta section's `£title` when [`TitlePrecedence::Meta`] is used.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TitlePrecedence {
+    /// The `title` in the meta section, falling back to the heading.
+    #[default]
+    Meta,
+    /// The heading, falling back to the `title` in the meta section.
+    Heading,
+}
+
+impl FromStr for TitlePrecedence {
+    type Err = String;
+
+    /// Parse a string slice, into a `TitlePrecedence`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use blogs_md_easy::TitlePrecedence;
+    ///
+    /// assert_eq!("meta".parse::<TitlePrecedence>(), Ok(TitlePrecedence::Meta));
+    /// assert_eq!("Heading".parse::<TitlePrecedence>(), Ok(TitlePrecedence::Heading));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "meta" => Ok(Self::Meta),
+            "heading" => Ok(Self::Heading),
+            _ => Err(format!("Unable to parse TitlePrecedence from '{}'", s)),
+        }
+    }
+}
+
+/// Where the `£title` of a Markdown came from, see [`resolve_title`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TitleSource {
+    /// The `title` in the meta section.
+    Meta,
+    /// The heading, which is written after a `#`, underlined with `=`, or in an
+    /// `<h1>`.
+    Heading,
+    /// The file name of the Markdown, such as `My first post` for
+    /// `my-first-post.md`, when it has neither.
+    Filename,
+}
+
+/// Where the `publish_date` of a Markdown without one in its meta section is
+/// taken from, see [`BatchOptions::publish_date_from`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DateSource {
+    /// A file name that starts with a `YYYY-MM-DD` date, such as
+    /// `2024-05-01-hello.md`.
+    FileName,
+    /// The date that the Markdown was last modified, in UTC.
+    Modified,
+}
+
+impl FromStr for DateSource {
+    type Err = String;
+
+    /// Parse a string slice, into a `DateSource`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use blogs_md_easy::DateSource;
+    ///
+    /// assert_eq!("filename".parse::<DateSource>(), Ok(DateSource::FileName));
+    /// assert_eq!("mtime".parse::<DateSource>(), Ok(DateSource::Modified));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "filename" | "file_name" => Ok(Self::FileName),
+            "modified" | "mtime" => Ok(Self::Modified),
+            _ => Err(format!("Unable to parse DateSource from '{}'", s)),
+        }
+    }
+}
+
+/// A set of options that a Markdown can opt into with `profile = minimal` in
+/// its meta section, such as a landing page without the chrome of a blog
+/// post, see [`BatchOptions::profiles`].
+///
+/// # Example
+/// ```rust
+/// use std::{collections::BTreeMap, path::PathBuf};
+/// use blogs_md_easy::{render_markdown, BatchOptions, Profile};
+///
+/// let template_path = std::env::temp_dir().join("blogs_md_easy_profile.html");
+/// std::fs::write(&template_path, "{{ if feature \"nav\" }}<nav></nav>{{ endif }}{{ £title }}").unwrap();
+///
+/// let options = BatchOptions {
+///     features: vec!["nav".to_string()],
+///     profiles: BTreeMap::from([("minimal".to_string(), Profile::default())]),
+///     ..Default::default()
+/// };
+/// assert_eq!(render_markdown(&template_path, "post.md".as_ref(), "# Post", &options).unwrap(), "<nav></nav>Post");
+/// assert_eq!(render_markdown(&template_path, "now.md".as_ref(), ":meta\nprofile = minimal\n:meta\n# Now", &options).unwrap(), "Now");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Profile {
+    /// The template that the Markdown is rendered into, within the
+    /// [`BatchOptions::templates_dir`], as with `template` in its meta
+    /// section, which takes precedence.
+    ///
+    /// `Default: None`, which is the first template of the batch.
+    pub template: Option<PathBuf>,
+    /// The features of the `{{ if feature }}` blocks of the template, in place
+    /// of the [`BatchOptions::features`].
+    ///
+    /// `Default: []`
+    pub features: Vec<String>,
+    /// The arguments of each [`Filter`], which take precedence over the
+    /// [`BatchOptions::filter_defaults`].
+    ///
+    /// `Default: []`
+    pub filter_defaults: Vec<Meta>,
+    /// The other versions of each output, in place of the
+    /// [`BatchOptions::alternates`], such as for a template of newsletters.
+    ///
+    /// `Default: None`, which are those of the batch.
+    pub alternates: Option<Vec<Alternate>>,
+}
+
+impl Profile {
+    /// The `options` of a batch, with those of this profile in their place.
+    pub(crate) fn apply(&self, options: &BatchOptions) -> BatchOptions {
+        let mut filter_defaults = self.filter_defaults.to_owned();
+        filter_defaults.extend(options.filter_defaults.iter().filter(|meta| self.filter_defaults.iter().all(|default| default.key != meta.key)).cloned());
+        let alternates = self.alternates.to_owned().unwrap_or_else(|| options.alternates.to_owned());
+        BatchOptions { features: self.features.to_owned(), filter_defaults, alternates, ..options.clone() }
+    }
+}
+
+/// The format that each output of [`render_batch`](crate::batch::render_batch)
+/// is written in.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OutputFormat {
+    /// The rendered template.
+    #[default]
+    Html,
+    /// A JSON object of the Markdown's `path`, its `title`, the key/values of
+    /// its `meta` section, and the rendered template as its `html`, which is
+    /// written with a `.json` extension.
+    ///
+    /// Requires the `json` feature.
+    #[cfg(feature = "json")]
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    /// Parse a string slice, into an `OutputFormat`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use blogs_md_easy::OutputFormat;
+    ///
+    /// assert_eq!("html".parse::<OutputFormat>(), Ok(OutputFormat::Html));
+    /// # #[cfg(feature = "json")]
+    /// assert_eq!("JSON".parse::<OutputFormat>(), Ok(OutputFormat::Json));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "html" => Ok(Self::Html),
+            #[cfg(feature = "json")]
+            "json" => Ok(Self::Json),
+            _ => Err(format!("Unable to parse OutputFormat from '{}'", s)),
+        }
+    }
+}
+
+/// A format that each output of [`render_batch`](crate::batch::render_batch) is
+/// also written in, already compressed, for static hosts that serve
+/// pre-compressed files.
+///
+/// Requires the `compression` feature.
+#[cfg(feature = "compression")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compression {
+    /// A gzip copy, with a `.gz` extension.
+    Gzip,
+    /// A Brotli copy, with a `.br` extension.
+    Brotli,
+}
+
+#[cfg(feature = "compression")]
+impl Compression {
+    /// The extension that is added to the path of a compressed copy.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gz",
+            Self::Brotli => "br",
+        }
+    }
+
+    /// Compress some bytes, as small as possible, since each output is only
+    /// compressed once.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::io::Read;
+    /// use blogs_md_easy::Compression;
+    ///
+    /// let html = "<p>Hello, World!</p>".repeat(100);
+    /// let compressed = Compression::Gzip.compress(html.as_bytes()).unwrap();
+    /// assert!(compressed.len() < html.len());
+    /// assert!(Compression::Brotli.compress(html.as_bytes()).unwrap().len() < html.len());
+    /// ```
+    pub fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        use std::io::Write;
+
+        match self {
+            Self::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+                encoder.write_all(bytes)?;
+                Ok(encoder.finish()?)
+            },
+            Self::Brotli => {
+                let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 11, 22);
+                encoder.write_all(bytes)?;
+                Ok(encoder.into_inner())
+            },
+        }
+    }
+}
+
+#[cfg(feature = "compression")]
+impl FromStr for Compression {
+    type Err = String;
+
+    /// Parse a string slice, into a `Compression`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use blogs_md_easy::Compression;
+    ///
+    /// assert_eq!("gzip".parse::<Compression>(), Ok(Compression::Gzip));
+    /// assert_eq!("br".parse::<Compression>(), Ok(Compression::Brotli));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "gzip" | "gz" => Ok(Self::Gzip),
+            "brotli" | "br" => Ok(Self::Brotli),
+            _ => Err(format!("Unable to parse Compression from '{}'", s)),
+        }
+    }
+}
+
+/// Another version of each output of
+/// [`render_batch`](crate::batch::render_batch), which is written beside it,
+/// such as to send a post as a newsletter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "toml"), derive(serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum Alternate {
+    /// The text of the output, see [`html_to_text`], with a `.txt` extension.
+    #[cfg_attr(any(feature = "serde", feature = "toml"), serde(alias = "txt"))]
+    Text,
+    /// The output with its stylesheets inlined into each element, and without
+    /// any scripts, for email clients, see [`inline_styles`], with an
+    /// `.email.html` extension.
+    Email,
+}
+
+impl Alternate {
+    /// The extension that replaces that of the output.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Email => "email.html",
+        }
+    }
+
+    /// Convert the HTML of an output into this version of it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::Alternate;
+    ///
+    /// let html = "<style>p { color: red; }</style><h1>Hello</h1><p>World</p><script>track();</script>";
+    /// assert_eq!(Alternate::Text.convert(html), "Hello\n\nWorld\n");
+    /// assert_eq!(Alternate::Email.convert(html), "<h1>Hello</h1><p style=\"color: red\">World</p>");
+    /// ```
+    pub fn convert(&self, html: &str) -> String {
+        match self {
+            Self::Text => html_to_text(html),
+            Self::Email => inline_styles(&remove_elements(html, "script")),
+        }
+    }
+}
+
+impl FromStr for Alternate {
+    type Err = String;
+
+    /// Parse a string slice, into an `Alternate`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use blogs_md_easy::Alternate;
+    ///
+    /// assert_eq!("text".parse::<Alternate>(), Ok(Alternate::Text));
+    /// assert_eq!("Email".parse::<Alternate>(), Ok(Alternate::Email));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "text" | "txt" => Ok(Self::Text),
+            "email" => Ok(Self::Email),
+            _ => Err(format!("Unable to parse Alternate from '{}'", s)),
+        }
+    }
+}
+
+/// Remembers the output of expensive [`Filter`]s, such as
+/// [`Filter::Markdown`], keyed by the SHA-256 of their input.
+///
+/// Clones share the same entries, so a single cache can be kept between
+/// batches, and a rebuild only pays for the Markdowns that have changed.
+///
+/// The outputs of old inputs, such as each save of a Markdown whilst
+/// watching, are forgotten once the cache holds more than its
+/// [capacity](Self::with_capacity), starting with those used least recently.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{Filter, FilterCache};
+///
+/// let cache = FilterCache::new();
+/// let html = cache.render("# Hello".to_string(), &Filter::Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 }).unwrap();
+/// assert_eq!(cache.len(), 1);
+///
+/// // Rendering the same input again uses the cached output.
+/// assert_eq!(cache.clone().render("# Hello".to_string(), &Filter::Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 }).unwrap(), html);
+/// assert_eq!(cache.len(), 1);
+///
+/// // Cheap filters are never cached.
+/// cache.render("Hello".to_string(), &Filter::Reverse).unwrap();
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FilterCache {
+    entries: Arc<Mutex<CacheEntries>>,
+}
+
+/// The outputs of a [`FilterCache`], along with when each was last used.
+#[derive(Debug)]
+struct CacheEntries {
+    outputs: HashMap<(Filter, String), (String, u64)>,
+    /// The key of each output, by when it was last used.
+    recency: BTreeMap<u64, (Filter, String)>,
+    uses: u64,
+    /// The bytes of every output.
+    size: usize,
+    capacity: usize,
+}
+
+impl Default for CacheEntries {
+    fn default() -> Self {
+        Self { outputs: HashMap::new(), recency: BTreeMap::new(), uses: 0, size: 0, capacity: FILTER_CACHE_CAPACITY }
+    }
+}
+
+/// The bytes of outputs that a [`FilterCache`] holds by default.
+const FILTER_CACHE_CAPACITY: usize = 64 * 1024 * 1024;
+
+impl CacheEntries {
+    fn get(&mut self, key: &(Filter, String)) -> Option<String> {
+        let (output, used) = self.outputs.get_mut(key)?;
+        if let Some(key) = self.recency.remove(used) {
+            self.uses += 1;
+            *used = self.uses;
+            self.recency.insert(self.uses, key);
+        }
+        Some(output.to_owned())
+    }
+
+    fn insert(&mut self, key: (Filter, String), output: String) {
+        self.uses += 1;
+        self.size += output.len();
+        if let Some((old, used)) = self.outputs.insert(key.to_owned(), (output, self.uses)) {
+            self.size -= old.len();
+            self.recency.remove(&used);
+        }
+        self.recency.insert(self.uses, key);
+
+        // The output that was just inserted is kept, even if it is larger
+        // than the capacity on its own.
+        while self.size > self.capacity && self.outputs.len() > 1 {
+            let Some((_, key)) = self.recency.pop_first() else { break };
+            if let Some((output, _)) = self.outputs.remove(&key) {
+                self.size -= output.len();
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.outputs.clear();
+        self.recency.clear();
+        self.size = 0;
+    }
+}
+
+impl FilterCache {
+    /// Create an empty `FilterCache`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty `FilterCache`, which holds up to `capacity` bytes of
+    /// outputs, rather than the default of 64 MiB.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{Filter, FilterCache};
+    ///
+    /// let markdown = Filter::Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 };
+    /// let cache = FilterCache::with_capacity(32);
+    /// cache.render("# One".to_string(), &markdown).unwrap();
+    /// cache.render("# Two".to_string(), &markdown).unwrap();
+    /// cache.render("# One".to_string(), &markdown).unwrap();
+    /// assert_eq!(cache.len(), 2);
+    ///
+    /// // The output of `# Two` was used least recently, so it is forgotten.
+    /// cache.render("# Three".to_string(), &markdown).unwrap();
+    /// assert_eq!(cache.len(), 2);
+    /// assert!(cache.size() <= 32);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { entries: Arc::new(Mutex::new(CacheEntries { capacity, ..Default::default() })) }
+    }
+
+    /// Run the `variable` through the `filter`, using the cached output if the
+    /// same input has been rendered before.
+    pub fn render<'a>(&self, variable: impl Into<Cow<'a, str>>, filter: &Filter) -> Result<Cow<'a, str>, FilterError> {
+        let variable = variable.into();
+        if !filter.is_expensive() {
+            return render_filter(variable, filter);
+        }
+
+        let key = (filter.to_owned(), hash_hex(variable.as_bytes()));
+        if let Some(output) = self.lock().get(&key) {
+            return Ok(Cow::Owned(output));
+        }
+
+        // Don't hold the lock while rendering, so that other threads can
+        // continue to use the cache.
+        let output = render_filter(variable, filter)?.into_owned();
+        self.lock().insert(key, output.to_owned());
+        Ok(Cow::Owned(output))
+    }
+
+    /// The number of outputs that are cached.
+    pub fn len(&self) -> usize {
+        self.lock().outputs.len()
+    }
+
+    /// The bytes of every output that is cached.
+    pub fn size(&self) -> usize {
+        self.lock().size
+    }
+
+    /// Whether there are no cached outputs.
+    pub fn is_empty(&self) -> bool {
+        self.lock().outputs.is_empty()
+    }
+
+    /// Remove every cached output, such as when the outputs of old inputs are
+    /// no longer needed.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheEntries> {
+        // The entries are always valid, even if a thread panicked whilst
+        // holding the lock.
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl PartialEq for FilterCache {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.entries, &other.entries) || *self.lock() == *other.lock()
+    }
+}
+
+impl PartialEq for CacheEntries {
+    /// The same outputs, no matter when they were used.
+    fn eq(&self, other: &Self) -> bool {
+        self.outputs.len() == other.outputs.len()
+            && self.outputs.iter().all(|(key, (output, _))| other.outputs.get(key).is_some_and(|(other, _)| output == other))
+    }
+}
+
+/// Callbacks into the render pipeline of
+/// [`render_batch`](crate::batch::render_batch) and [`render_markdown`], so
+/// that an application can change what is rendered, such as by expanding
+/// shortcodes or adding an analytics snippet, see [`Hooks`].
+///
+/// Every callback does nothing unless it is implemented, and an error stops
+/// the batch, as with any other error of the Markdown.
+///
+/// # Example
+/// ```rust
+/// use std::{collections::HashMap, error::Error, path::Path};
+/// use blogs_md_easy::{render_markdown, BatchOptions, Hooks, Plugin};
+///
+/// struct Analytics;
+///
+/// impl Plugin for Analytics {
+///     fn after_render(&self, _markdown_path: &Path, html: &mut String) -> Result<(), Box<dyn Error>> {
+///         html.push_str("<script src=\"/stats.js\"></script>");
+///         Ok(())
+///     }
+/// }
+///
+/// let template_path = std::env::temp_dir().join("blogs_md_easy_plugin.html");
+/// std::fs::write(&template_path, "<h1>{{ £title }}</h1>").unwrap();
+///
+/// let options = BatchOptions { hooks: Hooks::new().with(Analytics), ..Default::default() };
+/// let html = render_markdown(&template_path, Path::new("hello.md"), "# Hello", &options).unwrap();
+/// assert_eq!(html, "<h1>Hello</h1><script src=\"/stats.js\"></script>");
+/// ```
+pub trait Plugin: Send + Sync {
+    /// The name of the plugin, for debugging.
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Called once the meta section of a Markdown has been parsed, before its
+    /// values are used.
+    fn after_meta(&self, _markdown_path: &Path, _meta_values: &mut Vec<Meta>) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Called with the variables of a Markdown, including its `content` that
+    /// is yet to be run through [`Filter::Markdown`], before they are rendered
+    /// into the template.
+    fn before_render(&self, _markdown_path: &Path, _variables: &mut HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Called with the HTML of a Markdown, once it has been rendered into the
+    /// template.
+    fn after_render(&self, _markdown_path: &Path, _html: &mut String) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Called with the contents of each file, including any indexes, before
+    /// it is written to the `output_path`.
+    fn before_write(&self, _output_path: &Path, _contents: &mut String) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+/// The [`Plugin`]s of a batch, which are called in the order that they were
+/// added, see [`BatchOptions::hooks`].
+///
+/// Clones share the same plugins. Outputs that are skipped by the
+/// [`BatchOptions::build_cache`] aren't rendered, so a change to a plugin
+/// isn't noticed by it.
+#[derive(Clone, Default)]
+pub struct Hooks {
+    pub(crate) plugins: Vec<Arc<dyn Plugin>>,
+}
+
+impl Hooks {
+    /// Create `Hooks` without any plugins.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a `plugin`, which is called after those already added.
+    pub fn with(mut self, plugin: impl Plugin + 'static) -> Self {
+        self.plugins.push(Arc::new(plugin));
+        self
+    }
+
+    /// Whether there are no plugins.
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Call [`Plugin::after_meta`] of each plugin.
+    fn after_meta(&self, markdown_path: &Path, meta_values: &mut Vec<Meta>) -> Result<(), Box<dyn Error>> {
+        self.plugins.iter().try_for_each(|plugin| plugin.after_meta(markdown_path, meta_values))
+    }
+
+    /// Call [`Plugin::before_render`] of each plugin.
+    fn before_render(&self, markdown_path: &Path, variables: &mut HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+        self.plugins.iter().try_for_each(|plugin| plugin.before_render(markdown_path, variables))
+    }
+
+    /// Call [`Plugin::after_render`] of each plugin.
+    fn after_render(&self, markdown_path: &Path, html: &mut String) -> Result<(), Box<dyn Error>> {
+        self.plugins.iter().try_for_each(|plugin| plugin.after_render(markdown_path, html))
+    }
+
+    /// Call [`Plugin::before_write`] of each plugin.
+    pub(crate) fn before_write(&self, output_path: &Path, contents: &mut String) -> Result<(), Box<dyn Error>> {
+        self.plugins.iter().try_for_each(|plugin| plugin.before_write(output_path, contents))
+    }
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.plugins.iter().map(|plugin| plugin.name())).finish()
+    }
+}
+
+impl PartialEq for Hooks {
+    fn eq(&self, other: &Self) -> bool {
+        self.plugins.len() == other.plugins.len() && self.plugins.iter().zip(&other.plugins).all(|(a, b)| Arc::ptr_eq(a, b))
+    }
+}
+
+/// A step of a [`Pipeline`], in the order that they run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Step {
+    /// Parse the template's placeholders, and remove the meta section from
+    /// the Markdown.
+    Parse,
+    /// Create the variables from the meta values and the Markdown, see
+    /// [`create_variables`].
+    Resolve,
+    /// Run the variable of each placeholder through its [`Filter`]s.
+    Filter,
+    /// Replace each placeholder with its filtered value.
+    Substitute,
+    /// Check the output, such as for images without alt text.
+    Postprocess,
+}
+
+/// Where a [`Stage`] runs, relative to its [`Step`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Position {
+    Before,
+    Instead,
+    After,
+}
+
+/// A Markdown and template as they pass through a [`Pipeline`], where each
+/// [`Step`] fills in what the next one needs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Rendering {
+    /// The template, as it was given.
+    pub template: String,
+    /// The Markdown, whose meta section is removed by [`Step::Parse`].
+    pub markdown: String,
+    /// The template and its placeholders, from [`Step::Parse`].
+    pub parsed: Template,
+    /// The values of the meta section, from [`Step::Parse`].
+    pub meta_values: Vec<Meta>,
+    /// The variables of the Markdown, from [`Step::Resolve`].
+    pub variables: HashMap<String, String>,
+    /// The filtered value of each placeholder, in the order that they appear
+    /// within the template, from [`Step::Filter`].
+    pub values: Vec<String>,
+    /// The rendered template, from [`Step::Substitute`].
+    pub output: String,
+    /// Where each placeholder was rendered within the `output`, from
+    /// [`Step::Substitute`]. A later stage that changes the `output` must
+    /// update it to match.
+    pub source_map: Vec<SourceMapping>,
+    /// Anything that is likely to be a mistake, see [`Warning`].
+    pub warnings: Vec<Warning>,
+}
+
+/// Where a [`Placeholder`] was rendered within the output, see
+/// [`render_with_source_map`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SourceMapping {
+    /// The name of the placeholder's variable, such as `title`.
+    pub name: String,
+    /// Where the placeholder is within the [`Template::source`], which has
+    /// its whitespace control applied, and its literal text unescaped.
+    pub selection: Selection,
+    /// The byte range of its value within the output.
+    pub output: Range<usize>,
+}
+
+/// A stage of a [`Pipeline`], which reads and changes the [`Rendering`].
+///
+/// Any closure that takes a `&mut Rendering` is a `Stage`.
+pub trait Stage: Send + Sync {
+    /// The name of the stage, for debugging.
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Run the stage, or fail with the reason why.
+    fn run(&self, rendering: &mut Rendering) -> Result<(), RenderError>;
+}
+
+impl<F> Stage for F
+where
+    F: Fn(&mut Rendering) -> Result<(), RenderError> + Send + Sync,
+{
+    fn run(&self, rendering: &mut Rendering) -> Result<(), RenderError> {
+        self(rendering)
+    }
+}
+
+impl Step {
+    /// Every step, in the order that they run.
+    const ALL: [Step; 5] = [Step::Parse, Step::Resolve, Step::Filter, Step::Substitute, Step::Postprocess];
+
+    /// The name of the step's built-in stage.
+    fn name(self) -> &'static str {
+        match self {
+            Step::Parse => "parse",
+            Step::Resolve => "resolve",
+            Step::Filter => "filter",
+            Step::Substitute => "substitute",
+            Step::Postprocess => "postprocess",
+        }
+    }
+}
+
+/// The built-in [`Stage`] of each [`Step`], which [`render`] runs.
+struct BuiltIn(Step);
+
+impl Stage for BuiltIn {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn run(&self, rendering: &mut Rendering) -> Result<(), RenderError> {
+        match self.0 {
+            Step::Parse => {
+                span!("parse");
+                rendering.parsed = Template::new(&apply_whitespace_control(&rendering.template)).map_err(|e| RenderError::Template(e.to_string()))?;
+                parse_markdown(rendering)?;
+            },
+            Step::Resolve => {
+                span!("variables");
+                rendering.variables = create_variables(Span::new(&rendering.markdown), rendering.meta_values.clone()).map_err(|e| match e.downcast::<RenderError>() {
+                    Ok(error) => *error,
+                    Err(e) => RenderError::Variables(e.to_string()),
+                })?;
+            },
+            Step::Filter => filter_values(rendering, &BatchOptions::default())?,
+            Step::Substitute => {
+                let placeholders = in_template_order(rendering.parsed.placeholders());
+                if placeholders.len() != rendering.values.len() {
+                    return Err(RenderError::Stage(format!("Expected {} values, but there were {}.", placeholders.len(), rendering.values.len())));
+                }
+                // Each value moves those after it by the difference between
+                // its length, and that of its placeholder.
+                let mut moved = 0_isize;
+                rendering.source_map = placeholders
+                    .iter()
+                    .zip(&rendering.values)
+                    .map(|(placeholder, value)| {
+                        let start = (placeholder.selection.start.offset as isize + moved) as usize;
+                        moved += value.len() as isize - (placeholder.selection.end.offset - placeholder.selection.start.offset) as isize;
+                        SourceMapping { name: placeholder.name.to_owned(), selection: placeholder.selection, output: start..start + value.len() }
+                    })
+                    .collect();
+                let replacements = placeholders
+                    .into_iter()
+                    .zip(&rendering.values)
+                    .map(|(placeholder, value)| (placeholder.selection.start.offset..placeholder.selection.end.offset, Cow::Borrowed(value.as_str())))
+                    .collect();
+                rendering.output = assemble(rendering.parsed.source(), replacements);
+            },
+            Step::Postprocess => {
+                rendering.warnings.extend(images_without_alt(&rendering.output).into_iter().map(Warning::MissingAltText));
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Run the variable of each placeholder of the `rendering` through its
+/// filters, with the filter cache and aliases of the `options`, as
+/// [`Step::Filter`].
+fn filter_values(rendering: &mut Rendering, options: &BatchOptions) -> Result<(), RenderError> {
+    if let Some(name) = rendering.parsed.required_variables().into_iter().find(|name| !rendering.variables.contains_key(name)) {
+        return Err(RenderError::MissingVariable(name));
+    }
+    rendering.values = in_template_order(rendering.parsed.placeholders())
+        .into_iter()
+        // A variable that is missing has a default.
+        .map(|placeholder| {
+            // Most placeholders have no arguments to resolve, so aren't copied.
+            let resolved;
+            let placeholder = match placeholder.argument_variables(rendering.parsed.source()).is_empty() {
+                true => placeholder,
+                false => {
+                    resolved = placeholder.with_arguments(rendering.parsed.source(), &|name| rendering.variables.get(name).cloned());
+                    &resolved
+                },
+            };
+            filter_placeholder(placeholder, rendering.variables.get(&placeholder.name).map(String::as_str).unwrap_or_default(), options).map(Cow::into_owned)
+        })
+        .collect::<Result<Vec<String>, FilterError>>()
+        .map_err(RenderError::Filter)?;
+    Ok(())
+}
+
+/// Remove the meta section from the Markdown of the `rendering`, as the second
+/// half of [`Step::Parse`], once its template has been parsed.
+fn parse_markdown(rendering: &mut Rendering) -> Result<(), RenderError> {
+    let (markdown, meta_values) = match parse_meta_section(Span::new(&rendering.markdown)) {
+        Ok((markdown, meta_values)) => (markdown.fragment().to_string(), meta_values),
+        Err(nom::Err::Error(error @ ParseError::Meta(_))) => return Err(RenderError::Meta(error)),
+        // There is no meta section.
+        Err(_) => (rendering.markdown.to_owned(), vec![]),
+    };
+    rendering.warnings.extend(rendering.parsed.unused_variables(&meta_values).into_iter().map(Warning::UnusedVariable));
+    rendering.markdown = markdown;
+    rendering.meta_values = meta_values;
+    Ok(())
+}
+
+/// The stages that render a Markdown into a template, one [`Step`] after
+/// another, as [`render`] does, but where any step can be replaced, or have
+/// stages added before or after it.
+///
+/// Clones share the same stages. Stages that are added to the same step, and
+/// position, run in the order that they were added.
+///
+/// [`render_batch`](crate::batch::render_batch) runs the
+/// [`BatchOptions::pipeline`] too, where the built-in stages are those of the
+/// batch, so that its steps also create the variables of the [`BatchOptions`],
+/// and its postprocessing also adds heading IDs, or embeds the meta values.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{Pipeline, Rendering, RenderError, Step};
+///
+/// // Give every Markdown an author, unless it has one.
+/// let pipeline = Pipeline::new()
+///     .after(Step::Resolve, |rendering: &mut Rendering| {
+///         rendering.variables.entry("author".to_string()).or_insert("Anonymous".to_string());
+///         Ok(())
+///     })
+///     // Shout every value, rather than run the filters.
+///     .replace(Step::Filter, |rendering: &mut Rendering| {
+///         rendering.values = rendering.parsed.placeholders().iter().rev()
+///             .map(|placeholder| rendering.variables.get(&placeholder.name).map(|value| value.to_uppercase()).ok_or(RenderError::MissingVariable(placeholder.name.to_owned())))
+///             .collect::<Result<_, _>>()?;
+///         Ok(())
+///     });
+///
+/// let (output, _) = pipeline.run("<h1>{{ £title }}</h1><p>By {{ £author }}</p>", "# Hello").unwrap();
+/// assert_eq!(output, "<h1>HELLO</h1><p>By ANONYMOUS</p>");
+/// ```
+#[derive(Clone)]
+pub struct Pipeline {
+    stages: Vec<(Step, Position, PipelineStage)>,
+}
+
+/// A stage of a [`Pipeline`], where `None` is the built-in stage of its step.
+type PipelineStage = Option<Arc<dyn Stage>>;
+
+impl Pipeline {
+    /// Create a `Pipeline` of the built-in stages, which renders the same as
+    /// [`render_with_warnings`].
+    pub fn new() -> Self {
+        Self {
+            stages: Step::ALL.into_iter().map(|step| (step, Position::Instead, None)).collect(),
+        }
+    }
+
+    /// Run the `stage` instead of the built-in stage of the `step`, or any
+    /// stage that has already replaced it.
+    pub fn replace(mut self, step: Step, stage: impl Stage + 'static) -> Self {
+        self.stages.retain(|(s, position, _)| (*s, *position) != (step, Position::Instead));
+        self.stages.push((step, Position::Instead, Some(Arc::new(stage))));
+        self
+    }
+
+    /// Run the `stage` before the `step`.
+    pub fn before(mut self, step: Step, stage: impl Stage + 'static) -> Self {
+        self.stages.push((step, Position::Before, Some(Arc::new(stage))));
+        self
+    }
+
+    /// Run the `stage` after the `step`.
+    pub fn after(mut self, step: Step, stage: impl Stage + 'static) -> Self {
+        self.stages.push((step, Position::After, Some(Arc::new(stage))));
+        self
+    }
+
+    /// Whether any stage has been added to the `step`, or has replaced it.
+    fn is_extended(&self, step: Step) -> bool {
+        self.stages.iter().any(|(s, _, stage)| *s == step && stage.is_some())
+    }
+
+    /// Run each stage of the `step` in turn, where `built_in` runs in place of
+    /// the built-in stage, unless it was replaced, and an error of any other
+    /// stage is passed through `stage_error`.
+    fn run_step<E>(&self, step: Step, rendering: &mut Rendering, built_in: impl FnOnce(&mut Rendering) -> Result<(), E>, stage_error: impl Fn(RenderError) -> E) -> Result<(), E> {
+        let mut stages = self.stages.iter().filter(|(s, _, _)| *s == step).collect::<Vec<_>>();
+        // The sort is stable, so stages keep the order that they were added.
+        stages.sort_by_key(|(_, position, _)| *position);
+
+        let mut built_in = Some(built_in);
+        for (_, _, stage) in stages {
+            match stage {
+                Some(stage) => stage.run(rendering).map_err(&stage_error)?,
+                None => if let Some(built_in) = built_in.take() {
+                    built_in(rendering)?;
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Render a Markdown into a template, running each stage in turn, and
+    /// return the output and warnings.
+    pub fn run(&self, template: &str, markdown: &str) -> Result<(String, Vec<Warning>), RenderError> {
+        self.render(template, markdown).map(|rendering| (rendering.output, rendering.warnings))
+    }
+
+    /// Render a Markdown into a template, running each stage in turn, and
+    /// return everything that the stages produced, such as the
+    /// [`Rendering::source_map`].
+    pub fn render(&self, template: &str, markdown: &str) -> Result<Rendering, RenderError> {
+        let mut rendering = Rendering {
+            template: template.to_string(),
+            markdown: markdown.to_string(),
+            ..Default::default()
+        };
+        for step in Step::ALL {
+            self.run_step(step, &mut rendering, |rendering| BuiltIn(step).run(rendering), |error| error)?;
+        }
+        Ok(rendering)
+    }
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut stages = self.stages.iter().collect::<Vec<_>>();
+        stages.sort_by_key(|(step, position, _)| (*step, *position));
+        f.debug_list().entries(stages.into_iter().map(|(step, position, stage)| (step, position, stage.as_ref().map_or(step.name(), |stage| stage.name())))).finish()
+    }
+}
+
+impl PartialEq for Pipeline {
+    fn eq(&self, other: &Self) -> bool {
+        self.stages.len() == other.stages.len() && self.stages.iter().zip(&other.stages).all(|((step, position, stage), (other_step, other_position, other_stage))| {
+            (step, position) == (other_step, other_position) && match (stage, other_stage) {
+                (Some(stage), Some(other_stage)) => Arc::ptr_eq(stage, other_stage),
+                (stage, other_stage) => stage.is_none() && other_stage.is_none(),
+            }
+        })
+    }
+}
+
+/// The headings of every Markdown in a batch, so that a
+/// `{{ ref "post#anchor" }}` tag can be resolved, see [`apply_refs`].
+///
+/// Each Markdown is keyed by its path without the extension, such as
+/// `posts/hello`, and a reference may use any trailing part of it, such as
+/// `hello`, provided that only one Markdown matches.
+///
+/// # Example
+/// ```rust
+/// use std::path::{Path, PathBuf};
+/// use blogs_md_easy::AnchorMap;
+///
+/// let anchors = AnchorMap::new(&[(PathBuf::from("posts/hello.md"), "# Hello\n## Getting Started".to_string())]);
+/// assert_eq!(anchors.find("hello#getting-started").unwrap(), (Path::new("posts/hello.md"), Some("getting-started")));
+/// assert!(anchors.find("hello#missing").is_err());
+/// assert!(anchors.find("goodbye").is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnchorMap {
+    /// The path of each Markdown, and the anchors of its headings, keyed by
+    /// the path without its extension.
+    pub posts: BTreeMap<String, (PathBuf, Vec<String>)>,
+}
+
+impl AnchorMap {
+    /// Find the anchors of every Markdown.
+    pub fn new(markdowns: &[(PathBuf, String)]) -> Self {
+        let posts = markdowns
+            .iter()
+            .map(|(path, markdown)| (
+                path.with_extension("").to_string_lossy().replace('\\', "/"),
+                (path.to_owned(), heading_anchors(markdown)),
+            ))
+            .collect();
+
+        Self { posts }
+    }
+
+    /// Find the Markdown, and the anchor, that a `reference` such as
+    /// `post#anchor` refers to.  \
+    /// Returns an error if there isn't exactly one such Markdown, or it has no
+    /// such heading.
+    pub fn find<'a>(&'a self, reference: &'a str) -> Result<(&'a Path, Option<&'a str>), Box<dyn Error>> {
+        let (post, anchor) = match reference.split_once('#') {
+            Some((post, anchor)) => (post, Some(anchor)),
+            None => (reference, None),
+        };
+
+        let mut matches = self.posts
+            .iter()
+            .filter(|(key, _)| *key == post || key.ends_with(&format!("/{}", post)))
+            .map(|(_, post)| post);
+        let (path, anchors) = match (matches.next(), matches.next()) {
+            (Some(post), None) => post,
+            (Some(_), Some(_)) => Err(format!("The reference '{}' matches more than one Markdown.", reference))?,
+            (None, _) => Err(format!("The reference '{}' does not match any Markdown.", reference))?,
+        };
+
+        if anchor.is_some_and(|anchor| !anchors.iter().any(|a| a == anchor)) {
+            Err(format!("The reference '{}' does not match any heading in '{}', expected one of: {}.", reference, path.to_string_lossy(), anchors.join(", ")))?;
+        }
+
+        Ok((path, anchor))
+    }
+
+    /// The anchors of each Markdown as JSON, keyed by its path without the
+    /// extension.
+    ///
+    /// Requires the `json` feature.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        let posts = self.posts
+            .iter()
+            .map(|(key, (path, anchors))| (key.to_owned(), serde_json::json!({
+                "path": path.to_string_lossy(),
+                "anchors": anchors,
+            })))
+            .collect::<serde_json::Map<String, serde_json::Value>>();
+
+        serde_json::to_string_pretty(&posts).unwrap_or_default()
+    }
+}
+
+/// Terms that are linked to their definitions on a glossary page, the first
+/// time that each appears in a Markdown, see [`Glossary::link_terms`].
+///
+/// The glossary page is rendered by
+/// [`render_batch`](crate::batch::render_batch) alongside the Markdowns, as
+/// though it were a Markdown at `path`.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{Glossary, Meta};
+///
+/// let glossary = Glossary::new(vec![Meta::new("Borrow checker", "Checks that references are valid.")]);
+/// let markdown = glossary.link_terms("The borrow checker, and the `borrow checker`.\nThe borrow checker.", "glossary.html");
+/// assert_eq!(markdown, "The [borrow checker](glossary.html#borrow-checker), and the `borrow checker`.\nThe borrow checker.");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Glossary {
+    /// Each term, and its definition in Markdown.
+    pub terms: Vec<Meta>,
+    /// Where the glossary page would be, if it were a Markdown, which decides
+    /// where it is written.
+    ///
+    /// `Default: glossary.md`
+    pub path: PathBuf,
+}
+
+impl Glossary {
+    /// Marks the start of some Markdown whose terms shouldn't be linked.
+    pub const OFF: &'static str = "<!-- glossary off -->";
+    /// Marks the end of some Markdown whose terms shouldn't be linked.
+    pub const ON: &'static str = "<!-- glossary on -->";
+
+    /// Create a `Glossary` of `terms`, whose page is `glossary.md`.
+    pub fn new(terms: Vec<Meta>) -> Self {
+        Self { terms, path: PathBuf::from("glossary.md") }
+    }
+
+    /// Read the terms from a file, in any format that [`load_variables`] can
+    /// read.
+    ///
+    /// [`load_variables`]: crate::meta::load_variables
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::new(load_variables(path)?))
+    }
+
+    /// The Markdown of the glossary page, with a heading for each term, in
+    /// alphabetical order, that has the `id` that [`Glossary::link_terms`]
+    /// links to.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{Glossary, Meta};
+    ///
+    /// let glossary = Glossary::new(vec![Meta::new("Trait", "Shared behaviour."), Meta::new("Borrow checker", "Checks references.")]);
+    /// assert_eq!(glossary.to_markdown(), "# Glossary\n<h2 id=\"borrow-checker\">Borrow checker</h2>\n\nChecks references.\n<h2 id=\"trait\">Trait</h2>\n\nShared behaviour.\n");
+    /// ```
+    pub fn to_markdown(&self) -> String {
+        let mut terms = self.terms.iter().collect::<Vec<&Meta>>();
+        terms.sort_by_key(|term| term.key.to_lowercase());
+
+        terms.iter().fold("# Glossary\n".to_string(), |markdown, term| format!(
+            "{}<h2 id=\"{}\">{}</h2>\n\n{}\n",
+            markdown,
+            slugify(&term.key),
+            escape_html(&term.key),
+            term.value.trim(),
+        ))
+    }
+
+    /// Link the first occurrence of each term in the `markdown` to its
+    /// definition, at `url`, ignoring case.
+    ///
+    /// Terms within code, headings, links, and HTML tags are never linked,
+    /// nor are any between [`Glossary::OFF`] and [`Glossary::ON`].
+    pub fn link_terms(&self, markdown: &str, url: &str) -> String {
+        // The longest terms are linked first, so that `borrow checker` isn't
+        // linked as `borrow`.
+        let mut terms = self.terms.iter().filter(|term| !term.key.trim().is_empty()).collect::<Vec<&Meta>>();
+        terms.sort_by_key(|term| std::cmp::Reverse(term.key.len()));
+
+        let mut fenced = false;
+        let mut enabled = true;
+        let mut lines = Vec::new();
+        for line in markdown.split('\n') {
+            let trimmed = line.trim();
+            if trimmed.starts_with("```") {
+                fenced = !fenced;
+            } else if trimmed == Self::OFF {
+                enabled = false;
+            } else if trimmed == Self::ON {
+                enabled = true;
+            }
+
+            if fenced || !enabled || trimmed.starts_with('#') || terms.is_empty() {
+                lines.push(line.to_string());
+                continue;
+            }
+
+            let mut segments = linkable_segments(line);
+            terms.retain(|term| !link_term(&mut segments, &term.key, &format!("{}#{}", url, slugify(&term.key))));
+            lines.push(segments.into_iter().map(|(segment, _)| segment).collect());
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// A variable of a template that lists posts, such as an index, which is
+/// either a single value, or a collection of them that a
+/// `{{ for £item in £collection }}` block loops over, see [`apply_loops`].
+///
+/// A placeholder reaches into a collection by its path, such as
+/// `£posts.0.title`, and `£posts.count` is the number of items within it.
+///
+/// # Example
+/// ```rust
+/// use std::collections::BTreeMap;
+/// use blogs_md_easy::Value;
+///
+/// let post = Value::Map(BTreeMap::from([("title".to_string(), Value::Text("Hello".to_string()))]));
+/// let site = Value::Map(BTreeMap::from([("posts".to_string(), Value::List(vec![post]))]));
+/// assert_eq!(site.get("posts.0.title").as_deref(), Some("Hello"));
+/// assert_eq!(site.get("posts.count").as_deref(), Some("1"));
+/// assert_eq!(site.get("posts.1.title"), None);
+/// assert_eq!(site.collections().get("posts"), Some(&1));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// A single value, such as the title of a post.
+    Text(String),
+    /// A collection of values, such as every post of an index.
+    List(Vec<Value>),
+    /// Named values, such as the variables of a post.
+    Map(BTreeMap<String, Value>),
+}
+
+impl Value {
+    /// The text at the `path` within this value, such as `posts.0.title`, or
+    /// `posts.count` for the number of items within a collection.
+    ///
+    /// The names of a [`Value::Map`] may contain a `.` themselves, such as
+    /// `section.usage`, so the longest name that the path starts with is
+    /// used.
+    pub fn get(&self, path: &str) -> Option<Cow<'_, str>> {
+        match self {
+            Self::Text(text) => path.is_empty().then_some(Cow::Borrowed(text.as_str())),
+            Self::List(items) => {
+                let (index, rest) = path.split_once('.').unwrap_or((path, ""));
+                match index {
+                    "count" if rest.is_empty() => Some(Cow::Owned(items.len().to_string())),
+                    index => items.get(index.parse::<usize>().ok()?)?.get(rest),
+                }
+            },
+            Self::Map(values) => values
+                .iter()
+                .filter_map(|(name, value)| {
+                    let rest = path.strip_prefix(name.as_str())?;
+                    match rest.strip_prefix('.') {
+                        Some(rest) => Some((name.len(), value, rest)),
+                        None => rest.is_empty().then_some((name.len(), value, rest)),
+                    }
+                })
+                .max_by_key(|(length, _, _)| *length)
+                .and_then(|(_, value, rest)| value.get(rest)),
+        }
+    }
+
+    /// The number of items within each collection of this value, by its path,
+    /// such as `posts`, and `posts.0.tags`, for [`apply_loops`].
+    pub fn collections(&self) -> HashMap<String, usize> {
+        fn collect(value: &Value, path: &str, collections: &mut HashMap<String, usize>) {
+            let join = |name: &str| if path.is_empty() { name.to_string() } else { format!("{}.{}", path, name) };
+            match value {
+                Value::Text(_) => {},
+                Value::List(items) => {
+                    collections.insert(path.to_string(), items.len());
+                    for (index, item) in items.iter().enumerate() {
+                        collect(item, &join(&index.to_string()), collections);
+                    }
+                },
+                Value::Map(values) => {
+                    for (name, value) in values {
+                        collect(value, &join(name), collections);
+                    }
+                },
+            }
+        }
+
+        let mut collections = HashMap::new();
+        collect(self, "", &mut collections);
+        collections
+    }
+}
+
+/// Builds a document from borrowed slices of the template and rendered
+/// variables, so that each part is only copied once, into a `String` of exactly
+/// the right size.
+///
+/// Replacing each placeholder in turn would instead copy the whole document for
+/// every placeholder, which is slow when a variable, such as `£content`, is
+/// very large.
+#[derive(Debug, Default)]
+pub(crate) struct ChunkedOutput<'a> {
+    chunks: Vec<Cow<'a, str>>,
+    len: usize,
+}
+
+impl<'a> ChunkedOutput<'a> {
+    pub(crate) fn push(&mut self, chunk: Cow<'a, str>) {
+        self.len += chunk.len();
+        self.chunks.push(chunk);
+    }
+
+    pub(crate) fn finish(self) -> String {
+        let mut output = String::with_capacity(self.len);
+        for chunk in self.chunks {
+            output.push_str(&chunk);
+        }
+        output
+    }
+}
+
+/// Something that didn't stop a Markdown from being rendered, but is likely
+/// to be a mistake, as returned by [`render_with_warnings`] and within each
+/// [`BatchProgress`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Warning {
+    /// A variable that was declared in the Markdown's meta section, but never
+    /// used by the template.
+    UnusedVariable(String),
+    /// A variable that was used by the template, but never declared, which was
+    /// rendered with the [`BatchOptions::on_missing`] policy.
+    MissingVariable(String),
+    /// An `<img>` with an empty, or without any, `alt` text, with its `src`.
+    ///
+    /// An image that is hidden from screen readers, with `aria-hidden="true"`
+    /// or `role="presentation"`, doesn't need any.
+    MissingAltText(String),
+    /// A malformed placeholder of the template, that was left as it is written
+    /// with [`BatchOptions::recover`].
+    Malformed(ParseError),
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnusedVariable(name) => write!(f, "Unused variable '{}'", name),
+            Self::MissingVariable(name) => write!(f, "Missing variable '{}'", name),
+            Self::MissingAltText(src) => write!(f, "Missing alt text for image '{}'", src),
+            Self::Malformed(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// The reasons that [`render`] can fail.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RenderError {
+    /// The template could not be parsed, with the reason why.
+    Template(String),
+    /// The Markdown's meta section was opened, but could not be parsed.
+    Meta(ParseError),
+    /// The Markdown has no `title` in its meta section, nor a heading.
+    MissingTitle,
+    /// The variables of the Markdown could not be created, with the reason
+    /// why.
+    Variables(String),
+    /// The template uses a variable that the Markdown does not declare.
+    MissingVariable(String),
+    /// A [`Filter`] couldn't render its value, such as a maths filter of a
+    /// value that isn't a number.
+    Filter(FilterError),
+    /// A [`Stage`] of a [`Pipeline`] failed, with the reason why.
+    Stage(String),
+    /// The output could not be written by [`render_to`], with the reason why.
+    Write(String),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Template(reason) => write!(f, "Unable to parse the template: {}", reason),
+            Self::Meta(error) => write!(f, "{}", error),
+            Self::MissingTitle => write!(f, "Missing title"),
+            Self::Variables(reason) => write!(f, "Unable to create the variables: {}", reason),
+            Self::MissingVariable(name) => write!(f, "Missing variable '{}'.", name),
+            Self::Filter(error) => write!(f, "Unable to render a filter: {}.", error),
+            Self::Stage(reason) => write!(f, "{}", reason),
+            Self::Write(reason) => write!(f, "Unable to write the output: {}", reason),
+        }
+    }
+}
+
+impl Error for RenderError {}
+
+////////////////////////////////////////////////////////////////////////////////
+// Parsers
+/// Include or remove the conditional blocks of a template, depending on
+/// whether their feature is within `features`.
+///
+/// This allows one template to produce variations, such as with or without
+/// comments, from the same sources. Blocks can be nested.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::apply_features;
+///
+/// let template = "<main></main>{{ if feature \"comments\" }}<section id=\"comments\"></section>{{ else }}<p>Comments are closed.</p>{{ endif }}";
+///
+/// let output = apply_features(template, &["comments".to_string()]).unwrap();
+/// assert_eq!(output, "<main></main><section id=\"comments\"></section>");
+///
+/// let output = apply_features(template, &[]).unwrap();
+/// assert_eq!(output, "<main></main><p>Comments are closed.</p>");
+/// ```
+pub fn apply_features(template: &str, features: &[String]) -> Result<String, Box<dyn Error>> {
+    let mut output = String::with_capacity(template.len());
+    // Whether each of the open blocks is currently being included.
+    let mut blocks: Vec<(bool, FeatureTag)> = Vec::new();
+    let mut input = Span::new(template);
+
+    while !input.fragment().is_empty() {
+        let included = blocks.iter().all(|(included, _)| *included);
+
+        if let Ok((remaining, feature_tag)) = parse_feature_tag(input) {
+            match feature_tag {
+                FeatureTag::If(ref name) => blocks.push((features.contains(name), feature_tag)),
+                FeatureTag::Else => match blocks.pop() {
+                    Some((included, FeatureTag::If(_))) => blocks.push((!included, FeatureTag::Else)),
+                    _ => Err(format!("Unexpected {{{{ else }}}} on line {}.", input.location_line()))?,
+                },
+                FeatureTag::EndIf => if blocks.pop().is_none() {
+                    Err(format!("Unexpected {{{{ endif }}}} on line {}.", input.location_line()))?;
+                },
+            }
+            input = remaining;
+        } else {
+            let (remaining, text) = take_till_tag(input);
+            if included {
+                output.push_str(text.fragment());
+            }
+            input = remaining;
+        }
+    }
+
+    if !blocks.is_empty() {
+        Err("Missing {{ endif }} for a feature block.".to_string())?;
+    }
+
+    Ok(output)
+}
+
+/// Remove the whitespace, including newlines, before each `{{-` and after each
+/// `-}}` of a template, leaving the tags as `{{ ` and ` }}`.
+///
+/// This keeps the lines of conditional blocks and loops out of the output.
+/// It is applied before the features and loops, and literal text is left as it
+/// is.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::apply_whitespace_control;
+///
+/// let template = "<ul>\n  {{- if feature \"tags\" }}\n  <li>{{ £tags }}</li>\n  {{- endif -}}\n</ul>";
+/// assert_eq!(apply_whitespace_control(template), "<ul>{{  if feature \"tags\" }}\n  <li>{{ £tags }}</li>{{  endif  }}</ul>");
+///
+/// let template = "<p>\\{{- £title -}}</p>";
+/// assert_eq!(apply_whitespace_control(template), template);
+/// ```
+pub fn apply_whitespace_control(template: &str) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    loop {
+        // Only a `{{` or a `\` can start a tag or literal text, so the text in
+        // between is copied in one go.
+        let at = rest.find(['{', '\\']).unwrap_or(rest.len());
+        output.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let Some(c) = rest.chars().next() else {
+            break;
+        };
+
+        if let Ok((remaining, _)) = parse_literal(Span::new(rest)) {
+            let end = rest.len() - remaining.fragment().len();
+            output.push_str(&rest[..end]);
+            rest = &rest[end..];
+        } else if let Some((body, after)) = rest.strip_prefix("{{").and_then(|after| placeholder_end(after).map(|end| (&after[..end], &after[end + 2..]))) {
+            let (trim_before, body) = body.strip_prefix('-').map(|body| (true, body)).unwrap_or((false, body));
+            let (trim_after, body) = body.strip_suffix('-').map(|body| (true, body)).unwrap_or((false, body));
+            if trim_before {
+                output.truncate(output.trim_end().len());
+            }
+            output.push_str(if trim_before { "{{ " } else { "{{" });
+            output.push_str(body);
+            output.push_str(if trim_after { " }}" } else { "}}" });
+            rest = if trim_after { after.trim_start() } else { after };
+        } else {
+            output.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    output
+}
+
+/// The offset of the `}}` that closes the body of a tag, ignoring any within
+/// its quoted strings.
+fn placeholder_end(body: &str) -> Option<usize> {
+    let mut quoted = false;
+    let mut chars = body.char_indices();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' if quoted => {
+                chars.next();
+            },
+            '}' if !quoted && body[offset..].starts_with("}}") => return Some(offset),
+            _ => {},
+        }
+    }
+    None
+}
+
+/// Remove the feature tags of a template, keeping every branch of their
+/// blocks, so that none of their placeholders are left out.
+pub(crate) fn strip_features(template: &str) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut input = Span::new(template);
+
+    while !input.fragment().is_empty() {
+        input = match parse_feature_tag(input) {
+            Ok((remaining, _)) => remaining,
+            Err(_) => {
+                let (remaining, text) = take_till_tag(input);
+                output.push_str(text.fragment());
+                remaining
+            },
+        };
+    }
+
+    output
+}
+
+/// Repeat the `{{ for £item in £collection }}` blocks of a template, once
+/// for each item of their collection.
+///
+/// The `collections` are the number of items within each collection, whose
+/// variables are named `collection.0`, `collection.1`, and so on. Within each
+/// repetition of the block, `£item` refers to that item, so `£item.title` is
+/// rendered as `£collection.0.title`, then `£collection.1.title`.
+///
+/// Loops can be nested, to loop over a collection of each item, such as
+/// `{{ for £entry in £group.entries }}`, which is the collection
+/// `group.0.entries`, then `group.1.entries`.
+///
+/// # Example
+/// ```rust
+/// use std::collections::HashMap;
+/// use blogs_md_easy::apply_loops;
+///
+/// let template = "<ul>{{ for £post in £posts }}<li>{{ £post.title }}</li>{{ endfor }}</ul>";
+/// let output = apply_loops(template, &HashMap::from([("posts".to_string(), 2)])).unwrap();
+/// assert_eq!(output, "<ul><li>{{ £posts.0.title }}</li><li>{{ £posts.1.title }}</li></ul>");
+///
+/// let template = "{{ for £group in £groups }}{{ for £entry in £group.entries }}{{ £entry.title }}{{ endfor }}{{ endfor }}";
+/// let collections = HashMap::from([("groups".to_string(), 2), ("groups.0.entries".to_string(), 2), ("groups.1.entries".to_string(), 1)]);
+/// let output = apply_loops(template, &collections).unwrap();
+/// assert_eq!(output, "{{ £groups.0.entries.0.title }}{{ £groups.0.entries.1.title }}{{ £groups.1.entries.0.title }}");
+/// ```
+pub fn apply_loops(template: &str, collections: &HashMap<String, usize>) -> Result<String, Box<dyn Error>> {
+    let mut output = String::with_capacity(template.len());
+    // The item, collection, and body of the open block.
+    let mut block: Option<(String, String, String)> = None;
+    // The number of loops that are open within the block.
+    let mut depth = 0;
+    let mut input = Span::new(template);
+
+    while !input.fragment().is_empty() {
+        if let Ok((remaining, loop_tag)) = parse_loop_tag(input) {
+            let tag = &input.fragment()[..input.fragment().len() - remaining.fragment().len()];
+            match (loop_tag, block.take()) {
+                (LoopTag::For { item, collection }, None) => {
+                    if !collections.contains_key(&collection) {
+                        Err(format!("Unknown collection '{}' on line {}.", collection, input.location_line()))?;
+                    }
+                    block = Some((item, collection, String::new()));
+                },
+                // Nested loops are kept in the body, until their collection is
+                // known.
+                (LoopTag::For { .. }, Some((item, collection, body))) => {
+                    depth += 1;
+                    block = Some((item, collection, body + tag));
+                },
+                (LoopTag::EndFor, Some((item, collection, body))) if depth > 0 => {
+                    depth -= 1;
+                    block = Some((item, collection, body + tag));
+                },
+                (LoopTag::EndFor, Some((item, collection, body))) => {
+                    for index in 0..collections[&collection] {
+                        let body = rename_variable(&body, &item, &format!("{}.{}", collection, index));
+                        output.push_str(&apply_loops(&body, collections)?);
+                    }
+                },
+                (LoopTag::EndFor, None) => {
+                    Err(format!("Unexpected {{{{ endfor }}}} on line {}.", input.location_line()))?;
+                },
+            }
+            input = remaining;
+        } else {
+            let (remaining, text) = take_till_tag(input);
+            match &mut block {
+                Some((_, _, body)) => body.push_str(text.fragment()),
+                None => output.push_str(text.fragment()),
+            }
+            input = remaining;
+        }
+    }
+
+    if block.is_some() {
+        Err("Missing {{ endfor }} for a loop.".to_string())?;
+    }
+
+    Ok(output)
+}
+
+/// Replace each filter of a template that is named after one of the `aliases`
+/// with the filter that it stands for, such as `upper` for `uppercase`, or
+/// `slugify` for `text = kebab`.
+///
+/// This allows a template that was written for another engine, such as
+/// Liquid or Jinja, to keep its filter names. The arguments of an alias are
+/// added to those of its filter, and an alias is never expanded again.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{apply_filter_aliases, Meta};
+///
+/// let aliases = vec![Meta::new("upper", "uppercase"), Meta::new("slugify", "text = kebab"), Meta::new("cut", "truncate = trail: …")];
+/// let output = apply_filter_aliases("{{ £title | upper }} {{ £title | slugify }} {{ £summary | cut = characters: 20 }}", &aliases);
+/// assert_eq!(output, "{{ £title | uppercase }} {{ £title | text = kebab }} {{ £summary | truncate = trail: …, characters: 20 }}");
+/// ```
+pub fn apply_filter_aliases(template: &str, aliases: &[Meta]) -> String {
+    if aliases.is_empty() {
+        return template.to_string();
+    }
+
+    let mut output = String::with_capacity(template.len());
+    let mut input = Span::new(template);
+    while !input.fragment().is_empty() {
+        let (remaining, text) = take_till_tag(input);
+        input = remaining;
+        // The placeholders can't be parsed until their aliases are replaced,
+        // so each tag whose body starts with a variable is split by hand.
+        let body = text.fragment()
+            .strip_prefix("{{")
+            .and_then(|after| placeholder_end(after).map(|end| (&after[..end], &after[end..])))
+            .filter(|(body, _)| body.trim_start().starts_with(['£', '$']));
+        let Some((body, after)) = body else {
+            output.push_str(text.fragment());
+            continue;
+        };
+
+        // Each part after the variable is a filter, as a `|` can only be
+        // within a quoted argument otherwise.
+        let mut parts = vec![String::new()];
+        let mut quoted = false;
+        for c in body.chars() {
+            match c {
+                '"' => quoted = !quoted,
+                '|' if !quoted => {
+                    parts.push(String::new());
+                    continue;
+                },
+                _ => {},
+            }
+            if let Some(part) = parts.last_mut() {
+                part.push(c);
+            }
+        }
+        for part in parts.iter_mut().skip(1) {
+            let (name, arguments) = part.split_once('=').map_or((part.trim(), None), |(name, arguments)| (name.trim(), Some(arguments.trim())));
+            let Some(alias) = aliases.iter().find(|alias| alias.key.eq_ignore_ascii_case(name)) else {
+                continue;
+            };
+            let filter = alias.value.trim();
+            *part = match (arguments, filter.contains('=')) {
+                (Some(arguments), true) => format!(" {}, {} ", filter, arguments),
+                (Some(arguments), false) => format!(" {} = {} ", filter, arguments),
+                (None, _) => format!(" {} ", filter),
+            };
+        }
+
+        output.push_str("{{");
+        output.push_str(&parts.join("|"));
+        output.push_str(after);
+    }
+
+    output
+}
+
+/// Give each [`Filter`] of a template that is used without any arguments the
+/// arguments in `defaults`, by the name of the filter.
+///
+/// This allows a site to choose its own defaults, such as the length of every
+/// `truncate`, whilst a template can still provide its own arguments. The
+/// `markdown` filter that `£content` is given without asking for it has the
+/// defaults too.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{apply_filter_defaults, Meta};
+///
+/// let defaults = vec![Meta::new("truncate", "characters: 20, trail: …")];
+/// let output = apply_filter_defaults("{{ £title | truncate }} {{ £summary | uppercase | truncate = 50 }}", &defaults);
+/// assert_eq!(output, "{{ £title | truncate = characters: 20, trail: … }} {{ £summary | uppercase | truncate = 50 }}");
+///
+/// let defaults = vec![Meta::new("markdown", "hard_breaks: true")];
+/// let output = apply_filter_defaults("{{ £content }} {{ £content | markdown_inline }}", &defaults);
+/// assert_eq!(output, "{{ £content | markdown = hard_breaks: true }} {{ £content | markdown_inline }}");
+/// ```
+pub fn apply_filter_defaults(template: &str, defaults: &[Meta]) -> String {
+    // A malformed placeholder is reported once the template is parsed.
+    let placeholders = match parse_placeholder_locations(Span::new(template)) {
+        Ok(placeholders) if !defaults.is_empty() => placeholders,
+        _ => return template.to_string(),
+    };
+
+    let mut replacements = vec![];
+    for placeholder in placeholders {
+        let (start, end) = (placeholder.selection.start.offset, placeholder.selection.end.offset);
+        let source = &template[start..end];
+        // Each part after the variable is a filter, as a `|` can only be
+        // within a quoted argument otherwise.
+        let inner = source.trim_start_matches("{{").trim_end_matches("}}");
+        let mut parts = vec![String::new()];
+        let mut quoted = false;
+        for c in inner.chars() {
+            match c {
+                '"' => quoted = !quoted,
+                '|' if !quoted => {
+                    parts.push(String::new());
+                    continue;
+                },
+                _ => {},
+            }
+            if let Some(part) = parts.last_mut() {
+                part.push(c);
+            }
+        }
+        let mut changed = false;
+        for part in parts.iter_mut().skip(1) {
+            let name = part.trim().to_lowercase();
+            if let Some(meta) = defaults.iter().find(|meta| meta.key.to_lowercase() == name) {
+                *part = format!(" {} = {} ", part.trim(), meta.value.trim());
+                changed = true;
+            }
+        }
+        // As is the `markdown` filter that `£content` is always given.
+        let filter_name = |part: &String| part.split('=').next().unwrap_or_default().trim().to_lowercase();
+        if placeholder.name.eq_ignore_ascii_case("content") && !parts.iter().skip(1).any(|part| filter_name(part).starts_with("markdown")) {
+            if let Some(meta) = defaults.iter().find(|meta| meta.key.eq_ignore_ascii_case("markdown")) {
+                parts.push(format!(" markdown = {} ", meta.value.trim()));
+                changed = true;
+            }
+        }
+
+        if changed {
+            replacements.push((start..end, Cow::Owned(format!("{{{{{}}}}}", parts.join("|")))));
+        }
+    }
+
+    assemble(template, replacements)
+}
+
+/// Rewrite each placeholder of a template in its canonical form, such as
+/// `{{ £title | uppercase }}`, leaving the rest of the template, including its
+/// other tags and literal text, as it is written.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::format_template;
+///
+/// let template = "<h1>{{$title|text=upper}}</h1>{{ if feature \"toc\" }}<nav>\\{{£toc}}</nav>{{ endif }}{{   £content   }}";
+/// let output = format_template(template).unwrap();
+/// assert_eq!(output, "<h1>{{ £title | uppercase }}</h1>{{ if feature \"toc\" }}<nav>\\{{£toc}}</nav>{{ endif }}{{ £content }}");
+/// ```
+pub fn format_template(template: &str) -> Result<String, Box<dyn Error>> {
+    let replacements = parse_placeholder_locations(Span::new(template))?
+        .into_iter()
+        .map(|placeholder| (placeholder.selection.start.offset..placeholder.selection.end.offset, Cow::Owned(placeholder.to_string())))
+        .collect();
+
+    Ok(assemble(template, replacements))
+}
+
+/// Replace each `{{ include "path" }}` tag of a template with the template at
+/// `path`, relative to `directory`, so that a partial, such as a card, can be
+/// shared between templates.
+///
+/// The variables that an include binds, such as
+/// `{{ include "card.html" with title: £post.title, link: £post.url }}`, are
+/// renamed within the partial, along with their nested variables, so `£title`
+/// is rendered as `£post.title`. Any other variables are left as they are, so
+/// they fall through to those of the template, such as a `£site_name`.
+/// Partials can include other partials, relative to themselves, and are
+/// written with the same `syntax`.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{apply_includes, Syntax};
+///
+/// let directory = std::env::temp_dir().join("blogs_md_easy_apply_includes");
+/// std::fs::create_dir_all(&directory).unwrap();
+/// std::fs::write(directory.join("card.html"), "<a href=\"{{ £link }}\">{{ £title | uppercase }}</a>").unwrap();
+///
+/// let template = "{{ include \"card.html\" with title: £post.title, link: £post.url }}";
+/// let output = apply_includes(template, &directory, &Syntax::default()).unwrap();
+/// assert_eq!(output, "<a href=\"{{ £post.url }}\">{{ £post.title | uppercase }}</a>");
+///
+/// // The `£link` isn't bound, so it is that of the template.
+/// let output = apply_includes("{{ include \"card.html\" with title: £post.title }}", &directory, &Syntax::default()).unwrap();
+/// assert_eq!(output, "<a href=\"{{ £link }}\">{{ £post.title | uppercase }}</a>");
+/// ```
+pub fn apply_includes(template: &str, directory: &Path, syntax: &Syntax) -> Result<String, Box<dyn Error>> {
+    include_partials(template, directory, syntax, &mut Vec::new())
+}
+
+/// Replace each include of a template, where `parents` are the partials that
+/// are being included, so that a partial can't include itself.
+fn include_partials(template: &str, directory: &Path, syntax: &Syntax, parents: &mut Vec<PathBuf>) -> Result<String, Box<dyn Error>> {
+    let mut output = String::with_capacity(template.len());
+    let mut input = Span::new(template);
+
+    while !input.fragment().is_empty() {
+        if let Ok((remaining, include)) = parse_include_tag(input) {
+            let path = directory.join(&include.path);
+            if parents.contains(&path) {
+                Err(format!("The partial '{}' includes itself, on line {}.", path.to_string_lossy(), input.location_line()))?;
+            }
+            let partial = read_file(&path)
+                .map_err(|e| format!("{} In include on line {}.", e, input.location_line()))?;
+
+            parents.push(path.to_owned());
+            let partial = include_partials(&apply_whitespace_control(&syntax.translate(&partial)), path.parent().unwrap_or(Path::new("")), syntax, parents)
+                .map_err(|e| format!("{} In partial '{}'.", e, path.to_string_lossy()))?;
+            parents.pop();
+
+            output.push_str(&rename_variables(&partial, &include.bindings));
+            input = remaining;
+        } else {
+            let (remaining, text) = take_till_tag(input);
+            output.push_str(text.fragment());
+            input = remaining;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Replace each `{{ render "path" }}` tag of a template with the Markdown at
+/// `path`, relative to `directory`, rendered into HTML.
+///
+/// This allows a page, such as a landing page, to be assembled from several
+/// fragments of content. The meta section of each fragment is ignored.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::apply_fragments;
+///
+/// let directory = std::env::temp_dir().join("blogs_md_easy_apply_fragments");
+/// std::fs::create_dir_all(&directory).unwrap();
+/// std::fs::write(directory.join("bio.md"), ":meta\nauthor = John Doe\n:meta\nHello, I'm **John**.").unwrap();
+///
+/// let output = apply_fragments("<aside>{{ render \"bio.md\" }}</aside>", &directory).unwrap();
+/// # #[cfg(feature = "markdown")]
+/// assert_eq!(output, "<aside><p>Hello, I'm <strong>John</strong>.</p></aside>");
+/// ```
+pub fn apply_fragments(template: &str, directory: &Path) -> Result<String, Box<dyn Error>> {
+    let mut output = String::with_capacity(template.len());
+    let mut input = Span::new(template);
+
+    while !input.fragment().is_empty() {
+        if let Ok((remaining, path)) = parse_render_tag(input) {
+            let path = directory.join(path);
+            let markdown = read_file(&path)
+                .map_err(|e| format!("{} In fragment on line {}.", e, input.location_line()))?;
+
+            let markdown = Span::new(&markdown);
+            let (markdown, _) = match parse_meta_section(markdown) {
+                Ok(parsed) => parsed,
+                Err(nom::Err::Error(error @ ParseError::Meta(_))) => {
+                    Err(format!("{}\nIn fragment '{}'.", error, path.to_string_lossy()))?
+                },
+                // There is no meta section.
+                Err(_) => (markdown, vec![]),
+            };
+
+            output.push_str(&render_filter(markdown.fragment().trim(), &Filter::Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 })?);
+            input = remaining;
+        } else {
+            let (remaining, text) = take_till_tag(input);
+            output.push_str(text.fragment());
+            input = remaining;
+        }
+    }
+
+    Ok(output)
+}
+
+/// The anchor of every heading of a Markdown, ignoring any headings within
+/// fenced code blocks.
+///
+/// Each anchor is the slug of its heading, and headings that share a slug are
+/// numbered, such as `usage` and `usage-1`.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::heading_anchors;
+///
+/// let anchors = heading_anchors("# Hello, World!\n## Usage\n```md\n## Not a heading\n```\n### Usage");
+/// assert_eq!(anchors, vec!["hello-world", "usage", "usage-1"]);
+/// ```
+pub fn heading_anchors(markdown: &str) -> Vec<String> {
+    let mut anchors = Vec::new();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut fenced = false;
+
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            fenced = !fenced;
+        }
+
+        let level = line.chars().take_while(|c| *c == '#').count();
+        let Some(heading) = line[level..].strip_prefix(' ').filter(|_| !fenced && (1..=6).contains(&level)) else {
+            continue;
+        };
+
+        let slug = slugify(heading);
+        if !slug.is_empty() {
+            anchors.push(numbered_slug(slug, &mut counts));
+        }
+    }
+
+    anchors
+}
+
+/// Replace each `{{ ref "post#anchor" }}` tag of a Markdown with the URL of
+/// that Markdown's output, relative to the `output_path` of this Markdown.
+///
+/// The `#anchor` is optional, and a reference of only `#anchor` refers to
+/// this Markdown. The output of each Markdown is found with `output_path_of`.
+///
+/// Returns an error for any reference that doesn't match exactly one Markdown,
+/// or one of its headings.
+///
+/// # Example
+/// ```rust
+/// use std::path::{Path, PathBuf};
+/// use blogs_md_easy::{apply_refs, AnchorMap};
+///
+/// let anchors = AnchorMap::new(&[
+///     (PathBuf::from("posts/one.md"), "# One".to_string()),
+///     (PathBuf::from("posts/2024/two.md"), "# Two\n## Usage".to_string()),
+/// ]);
+/// let output_path_of = |markdown: &Path| markdown.with_extension("html");
+///
+/// let output = apply_refs("See [usage]({{ ref \"two#usage\" }}).", &anchors, Path::new("posts/one.html"), output_path_of).unwrap();
+/// assert_eq!(output, "See [usage](2024/two.html#usage).");
+///
+/// assert!(apply_refs("{{ ref \"two#missing\" }}", &anchors, Path::new("posts/one.html"), output_path_of).is_err());
+/// ```
+pub fn apply_refs<F>(markdown: &str, anchors: &AnchorMap, output_path: &Path, output_path_of: F) -> Result<String, Box<dyn Error>>
+where
+    F: Fn(&Path) -> PathBuf,
+{
+    let mut output = String::with_capacity(markdown.len());
+    let mut input = Span::new(markdown);
+
+    while !input.fragment().is_empty() {
+        if let Ok((remaining, reference)) = parse_ref_tag(input) {
+            match reference.strip_prefix('#') {
+                Some(anchor) if heading_anchors(markdown).iter().any(|a| a == anchor) => output.push_str(&format!("#{}", anchor)),
+                Some(_) => Err(format!("The reference '{}' does not match any heading. On line {}.", reference, input.location_line()))?,
+                None => {
+                    let (path, anchor) = anchors.find(&reference)
+                        .map_err(|e| format!("{} On line {}.", e, input.location_line()))?;
+                    output.push_str(&relative_url(output_path, &output_path_of(path)));
+                    if let Some(anchor) = anchor {
+                        output.push_str(&format!("#{}", anchor));
+                    }
+                },
+            }
+            input = remaining;
+        } else {
+            let (remaining, text) = take_till_tag(input);
+            output.push_str(text.fragment());
+            input = remaining;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Rewrite each relative link of a Markdown to another Markdown of the batch,
+/// such as `[part 1](part-1.md)` or `<a href="part-1.md#usage">`, into the URL
+/// of that Markdown's output, relative to the `output_path` of this Markdown.
+///
+/// The output of each Markdown is found with `output_path_of`. Links to a
+/// Markdown that isn't one of the `anchors`, and any within a fenced code
+/// block, are left as they are.
+///
+/// # Example
+/// ```rust
+/// use std::path::{Path, PathBuf};
+/// use blogs_md_easy::{rewrite_markdown_links, AnchorMap};
+///
+/// let anchors = AnchorMap::new(&[
+///     (PathBuf::from("posts/part-1.md"), "# Part 1".to_string()),
+///     (PathBuf::from("posts/2024/part-2.md"), "# Part 2".to_string()),
+/// ]);
+/// let output_path_of = |markdown: &Path| Path::new("dist").join(markdown.with_extension("html"));
+///
+/// let output = rewrite_markdown_links("See [part 2](2024/part-2.md#usage), or the [notes](notes.md).", Path::new("posts/part-1.md"), &anchors, Path::new("dist/posts/part-1.html"), output_path_of);
+/// assert_eq!(output, "See [part 2](2024/part-2.html#usage), or the [notes](notes.md).");
+/// ```
+pub fn rewrite_markdown_links<F>(markdown: &str, markdown_path: &Path, anchors: &AnchorMap, output_path: &Path, output_path_of: F) -> String
+where
+    F: Fn(&Path) -> PathBuf,
+{
+    let markdown_dir = markdown_path.parent().unwrap_or(Path::new(""));
+    let mut output = String::with_capacity(markdown.len());
+    let mut fenced = false;
+
+    for line in markdown.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
+            fenced = !fenced;
+        }
+        if fenced {
+            output.push_str(line);
+            continue;
+        }
+
+        let mut rest = line;
+        while let Some((start, open)) = ["](", "href=\"", "href='"].iter().filter_map(|open| rest.find(open).map(|start| (start, *open))).min() {
+            let (text, target) = rest.split_at(start + open.len());
+            output.push_str(text);
+            let length = match open {
+                "](" => target.find(|c: char| c == ')' || c.is_whitespace()),
+                _ => target.find(open.chars().last().unwrap_or('"')),
+            }.unwrap_or(target.len());
+            let (target, remaining) = target.split_at(length);
+
+            let (path, fragment) = target.split_once('#').map_or((target, None), |(path, fragment)| (path, Some(fragment)));
+            let linked = Some(path)
+                .filter(|path| path.ends_with(".md") && !path.contains(':') && !path.starts_with('/'))
+                .map(|path| normalise_path(&markdown_dir.join(path)).with_extension("").to_string_lossy().replace('\\', "/"))
+                .and_then(|key| anchors.posts.get(&key));
+            match linked {
+                Some((linked, _)) => {
+                    output.push_str(&relative_url(output_path, &output_path_of(linked)));
+                    if let Some(fragment) = fragment {
+                        output.push_str(&format!("#{}", fragment));
+                    }
+                },
+                None => output.push_str(target),
+            }
+            rest = remaining;
+        }
+        output.push_str(rest);
+    }
+
+    output
+}
+
+/// Resolve the links of a Markdown, written with the [`BatchOptions::syntax`],
+/// to the others of the batch, from its `{{ ref }}` tags and `.md` links, then
+/// replace its embed tags, and unescape its literal text.
+///
+/// Raw blocks are kept, so that their contents bypass the `markdown` filter.
+pub(crate) fn resolve_links<F>(markdown: &str, markdown_path: &Path, anchors: &AnchorMap, output_path: &Path, output_path_of: F, options: &BatchOptions) -> Result<String, Box<dyn Error>>
+where
+    F: Fn(&Path) -> PathBuf,
+{
+    let link_path_of = |path: &Path| match &options.link_extension {
+        Some(extension) => output_path_of(path).with_extension(extension),
+        None => output_path_of(path),
+    };
+    let markdown = apply_refs(&options.syntax.translate(markdown), anchors, output_path, link_path_of)?;
+    let markdown = rewrite_markdown_links(&markdown, markdown_path, anchors, output_path, link_path_of);
+    let markdown = options.shortcodes.expand(&markdown)?;
+    let markdown = apply_embeds(&markdown, options.eager_embeds)?;
+
+    Ok(unescape_outside_raw_blocks(&markdown))
+}
+
+/// Unescape each `\{{` of a Markdown, as [`unescape_literals`] does, but keep
+/// its `{{ raw }}` blocks as they are written.
+fn unescape_outside_raw_blocks(markdown: &str) -> String {
+    let mut output = String::with_capacity(markdown.len());
+    let mut input = Span::new(markdown);
+
+    while !input.fragment().is_empty() {
+        let (remaining, text) = take_till_tag(input);
+        match parse_literal(input) {
+            Ok((_, literal)) if input.fragment().starts_with('\\') => output.push_str(literal.fragment()),
+            _ => output.push_str(text.fragment()),
+        }
+        input = remaining;
+    }
+
+    output
+}
+
+/// Replace the literal text of a template or Markdown with the text that it
+/// stands for, so `\{{` is `{{`, and the `{{ raw }}` and `{{ endraw }}` tags
+/// are removed, leaving everything between them as it is.
+///
+/// This allows a post about templates to show a placeholder, or a tag, without
+/// it being rendered.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::unescape_literals;
+///
+/// let output = unescape_literals("<code>\\{{ £title }}</code>{{ raw }}<code>{{ £title | uppercase }}</code>{{ endraw }}");
+/// assert_eq!(output, "<code>{{ £title }}</code><code>{{ £title | uppercase }}</code>");
+/// ```
+pub fn unescape_literals(text: &str) -> String {
+    unescape_with_offsets(text).0
+}
+
+/// Unescape the literal text, along with the offset of the end of each literal,
+/// and the number of bytes that had been removed by that point.
+fn unescape_with_offsets(text: &str) -> (String, Vec<(usize, usize)>) {
+    let mut output = String::with_capacity(text.len());
+    let mut removed = vec![];
+    let mut input = Span::new(text);
+
+    while !input.fragment().is_empty() {
+        if let Ok((remaining, literal)) = parse_literal(input) {
+            output.push_str(literal.fragment());
+            let total = removed.last().map(|(_, total)| *total).unwrap_or_default();
+            removed.push((remaining.location_offset(), total + remaining.location_offset() - input.location_offset() - literal.fragment().len()));
+            input = remaining;
+        } else {
+            let (remaining, text) = take_till_tag(input);
+            output.push_str(text.fragment());
+            input = remaining;
+        }
+    }
+
+    (output, removed)
+}
+
+/// The templates of the shortcodes that a Markdown can use, such as
+/// `{{ figure src: cat.jpg, caption: My cat }}`, by their name, see
+/// [`Shortcodes::expand`].
+///
+/// Each argument of a shortcode is a variable of its template, and those that
+/// aren't given are empty.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::Shortcodes;
+///
+/// let shortcodes = Shortcodes::new().with("figure", "<figure>\n<img src=\"{{ £src }}\" alt=\"\">\n\n<figcaption>{{ £caption }}</figcaption>\n</figure>").unwrap();
+/// let markdown = shortcodes.expand("# Cats\n{{ figure src: cat.jpg, caption: My cat }}\n{{ unknown }}").unwrap();
+/// assert_eq!(markdown, "# Cats\n<figure>\n<img src=\"cat.jpg\" alt=\"\">\n<figcaption>My cat</figcaption>\n</figure>\n{{ unknown }}");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Shortcodes {
+    templates: BTreeMap<String, Template>,
+}
+
+impl Shortcodes {
+    /// Create `Shortcodes` without any templates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the template of the shortcode called `name`, replacing any that it
+    /// already has.
+    pub fn with(mut self, name: &str, template: &str) -> Result<Self, Box<dyn Error>> {
+        let template = Template::new(template).map_err(|e| format!("{} In shortcode '{}'.", e, name))?;
+        self.templates.insert(name.to_string(), template);
+        Ok(self)
+    }
+
+    /// Read the template of each shortcode from the files of a directory,
+    /// where each is named after its file, without its extension, such as
+    /// `figure` for `figure.html`.
+    pub fn load(dir: &Path) -> Result<Self, Box<dyn Error>> {
+        if !dir.is_dir() {
+            Err(format!("Unable to find the shortcodes directory '{}'.", dir.to_string_lossy()))?;
+        }
+
+        let mut shortcodes = Self::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_stem().and_then(OsStr::to_str).filter(|_| path.is_file()) else {
+                continue;
+            };
+            shortcodes = shortcodes.with(name, &read_file(&path)?)?;
+        }
+        Ok(shortcodes)
+    }
+
+    /// Whether there are no shortcodes.
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// Replace each shortcode of a Markdown with its template, rendered with
+    /// its arguments, before the Markdown is run through [`Filter::Markdown`].
+    ///
+    /// A template that renders HTML, starting with a tag, has its blank lines
+    /// removed, so that it remains a single block of HTML within the Markdown.
+    /// Tags that aren't the name of a shortcode are left as they are.
+    pub fn expand(&self, markdown: &str) -> Result<String, Box<dyn Error>> {
+        if self.is_empty() {
+            return Ok(markdown.to_string());
+        }
+
+        let mut output = String::with_capacity(markdown.len());
+        let mut input = Span::new(markdown);
+        while !input.fragment().is_empty() {
+            let shortcode = parse_shortcode_tag(input)
+                .ok()
+                .and_then(|(remaining, shortcode)| Some((remaining, self.templates.get(&shortcode.name)?, shortcode)));
+            let Some((remaining, template, shortcode)) = shortcode else {
+                let (remaining, text) = take_till_tag(input);
+                output.push_str(text.fragment());
+                input = remaining;
+                continue;
+            };
+
+            let variables = shortcode.arguments.into_iter().map(|meta| (meta.key, meta.value)).collect();
+            let html = render_placeholders_with_policy(template.source(), template.placeholders(), &variables, &MissingVariablePolicy::ReplaceWithEmpty)
+                .map_err(|e| format!("{} In shortcode '{}'. On line {}.", e, shortcode.name, input.location_line()))?;
+            match html.trim_start().starts_with('<') {
+                true => output.push_str(&html.lines().filter(|line| !line.trim().is_empty()).collect::<Vec<&str>>().join("\n")),
+                false => output.push_str(&html),
+            }
+            input = remaining;
+        }
+
+        Ok(output)
+    }
+}
+
+/// The type of a meta value, see [`MetaRule::kind`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "toml"), derive(serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum MetaType {
+    /// Any value.
+    String,
+    /// A number, such as `42` or `-1.5`.
+    Number,
+    /// Either `true` or `false`.
+    Bool,
+    /// A date, such as `2024-05-01`, which may be followed by a time, such as
+    /// `2024-05-01T09:00:00Z`.
+    Date,
+}
+
+impl MetaType {
+    /// Whether the `value` is of this type.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::MetaType;
+    ///
+    /// assert!(MetaType::Date.matches("2024-02-29T09:00:00Z"));
+    /// assert!(!MetaType::Date.matches("2023-02-29"));
+    /// assert!(!MetaType::Number.matches("ten"));
+    /// ```
+    pub fn matches(&self, value: &str) -> bool {
+        let value = value.trim();
+        match self {
+            Self::String => true,
+            Self::Number => value.parse::<f64>().is_ok_and(f64::is_finite),
+            Self::Bool => matches!(value, "true" | "false"),
+            Self::Date => {
+                let number = |range: Range<usize>| value.get(range).filter(|n| n.bytes().all(|b| b.is_ascii_digit())).and_then(|n| n.parse::<u32>().ok());
+                let (Some(year), Some(month), Some(day)) = (number(0..4), number(5..7), number(8..10)) else {
+                    return false;
+                };
+                let days_in_month = match month {
+                    2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+                    2 => 28,
+                    4 | 6 | 9 | 11 => 30,
+                    1..=12 => 31,
+                    _ => 0,
+                };
+                value.get(4..5) == Some("-")
+                    && value.get(7..8) == Some("-")
+                    && (1..=days_in_month).contains(&day)
+                    && matches!(value.get(10..11), None | Some("T") | Some(" "))
+            },
+        }
+    }
+}
+
+impl FromStr for MetaType {
+    type Err = String;
+
+    /// Parse a string slice, into a `MetaType`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::MetaType;
+    ///
+    /// assert_eq!("date".parse::<MetaType>(), Ok(MetaType::Date));
+    /// assert!("list".parse::<MetaType>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "string" => Ok(Self::String),
+            "number" => Ok(Self::Number),
+            "bool" => Ok(Self::Bool),
+            "date" => Ok(Self::Date),
+            _ => Err(format!("Unable to parse MetaType from '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for MetaType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::String => "string",
+            Self::Number => "number",
+            Self::Bool => "bool",
+            Self::Date => "date",
+        })
+    }
+}
+
+/// What the value of a meta key must be, within a [`MetaSchema`].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "toml"), derive(serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct MetaRule {
+    /// Whether every Markdown must declare the key.
+    ///
+    /// `Default: false`
+    pub required: bool,
+    /// The type of the value.
+    ///
+    /// `Default: None`, which is any value.
+    #[cfg_attr(any(feature = "serde", feature = "toml"), serde(rename = "type"))]
+    pub kind: Option<MetaType>,
+    /// The only values that are allowed.
+    ///
+    /// `Default: []`, which is any value.
+    pub values: Vec<String>,
+}
+
+/// The rules that the meta section of each Markdown must follow, such as
+/// requiring a `title`, or that the `publish_date` is a date, which are checked
+/// by [`render_batch`](crate::batch::render_batch) before anything is rendered.
+///
+/// The `title` can also be the heading of the Markdown.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{MetaRule, MetaSchema, MetaType};
+///
+/// let schema = MetaSchema::new()
+///     .with("title", MetaRule { required: true, ..Default::default() })
+///     .with("publish_date", MetaRule { required: true, kind: Some(MetaType::Date), ..Default::default() })
+///     .with("category", MetaRule { values: vec!["news".to_string(), "tutorial".to_string()], ..Default::default() });
+///
+/// assert!(schema.validate(":meta\npublish_date = 2024-05-01\ncategory = news\n:meta\n# Hello").is_empty());
+/// assert_eq!(schema.validate(":meta\npublish_date = 2024-13-01\ncategory = misc\n:meta\nHello"), vec![
+///     "'category' must be one of news, tutorial, but was 'misc'.",
+///     "'publish_date' must be a date, but was '2024-13-01'.",
+///     "Missing 'title'.",
+/// ]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MetaSchema {
+    rules: BTreeMap<String, MetaRule>,
+}
+
+impl MetaSchema {
+    /// Create a `MetaSchema` without any rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the rule of the meta `key`, replacing any that it already has.
+    pub fn with(mut self, key: &str, rule: MetaRule) -> Self {
+        self.rules.insert(key.to_string(), rule);
+        self
+    }
+
+    /// Whether there are no rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Each way that the meta section of a Markdown breaks the rules, in the
+    /// order of their keys.
+    pub fn validate(&self, markdown: &str) -> Vec<String> {
+        let (content, meta_values) = parse_meta_section(Span::new(markdown)).unwrap_or((Span::new(markdown), vec![]));
+        let mut problems = Vec::new();
+        for (key, rule) in &self.rules {
+            let Some(meta) = meta_values.iter().find(|meta| &meta.key == key) else {
+                if rule.required && !(key == "title" && parse_title(content).is_ok()) {
+                    problems.push(format!("Missing '{}'.", key));
+                }
+                continue;
+            };
+            let value = meta.value.trim();
+            if let Some(kind) = rule.kind.filter(|kind| !kind.matches(value)) {
+                problems.push(format!("'{}' must be a {}, but was '{}'.", key, kind, value));
+            }
+            if !rule.values.is_empty() && !rule.values.iter().any(|allowed| allowed == value) {
+                problems.push(format!("'{}' must be one of {}, but was '{}'.", key, rule.values.join(", "), value));
+            }
+        }
+        problems
+    }
+
+    /// Add each required key that the meta section of a Markdown doesn't
+    /// declare, with a value to be replaced: the first of its `values`,
+    /// otherwise `false` for a bool, `0` for a number, `today` for a date, or
+    /// an empty string.
+    ///
+    /// # Example
+    /// ```rust
+    /// use blogs_md_easy::{MetaRule, MetaSchema, MetaType};
+    ///
+    /// let schema = MetaSchema::new()
+    ///     .with("title", MetaRule { required: true, ..Default::default() })
+    ///     .with("category", MetaRule { required: true, values: vec!["news".to_string()], ..Default::default() })
+    ///     .with("author", MetaRule::default());
+    ///
+    /// let markdown = schema.fill(":meta\ntitle = Hello\n:meta\n", "2024-05-01").unwrap();
+    /// assert_eq!(markdown, ":meta\ntitle = Hello\ncategory = news\n:meta\n");
+    /// ```
+    pub fn fill(&self, markdown: &str, today: &str) -> Result<String, Box<dyn Error>> {
+        let (_, meta_values) = parse_meta_section(Span::new(markdown)).unwrap_or((Span::new(markdown), vec![]));
+        self.rules
+            .iter()
+            .filter(|(key, rule)| rule.required && !meta_values.iter().any(|meta| &&meta.key == key))
+            .try_fold(markdown.to_string(), |markdown, (key, rule)| {
+                let value = match (rule.values.first(), rule.kind) {
+                    (Some(value), _) => value.to_string(),
+                    (None, Some(MetaType::Bool)) => "false".to_string(),
+                    (None, Some(MetaType::Number)) => "0".to_string(),
+                    (None, Some(MetaType::Date)) => today.to_string(),
+                    (None, _) => "\"\"".to_string(),
+                };
+                edit_meta(&markdown, &MetaEdit::Set { key: key.to_string(), value })
+            })
+    }
+}
+
+/// Replace each embed tag of a Markdown with a player, within a `<figure>`.
+///
+/// * `{{ video "clip.mp4" }}` and `{{ audio "episode.mp3" }}` are a `<video>`
+///   or `<audio>` element with its controls, that only preloads the metadata
+///   of the file, and otherwise links to it.
+/// * `{{ youtube "id" }}` and `{{ vimeo "id" }}` are an `<iframe>` of the
+///   player, from the id or URL of the video, without tracking cookies.
+///
+/// Each can be given a title, such as `{{ video "clip.mp4" "A title" }}`,
+/// which is the caption of the `<figure>`.
+///
+/// Unless `eager`, the player of a YouTube or Vimeo video is only loaded once
+/// it is clicked, so that a page doesn't request anything from them beforehand,
+/// other than the thumbnail of a YouTube video. This doesn't need JavaScript.
+///
+/// Returns an error for a YouTube or Vimeo video that isn't an id or URL.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::apply_embeds;
+///
+/// let output = apply_embeds("{{ audio \"episode.mp3\" }}", false).unwrap();
+/// assert_eq!(output, "<figure class=\"embed embed-audio\"><audio src=\"episode.mp3\" controls preload=\"metadata\"><a href=\"episode.mp3\">Download the audio</a></audio></figure>");
+///
+/// let output = apply_embeds("{{ youtube \"https://youtu.be/dQw4w9WgXcQ\" }}", true).unwrap();
+/// assert!(output.contains("src=\"https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ\""));
+///
+/// assert!(apply_embeds("{{ vimeo \"not-an-id\" }}", false).is_err());
+/// ```
+pub fn apply_embeds(markdown: &str, eager: bool) -> Result<String, Box<dyn Error>> {
+    let mut output = String::with_capacity(markdown.len());
+    let mut input = Span::new(markdown);
+
+    while !input.fragment().is_empty() {
+        if let Ok((remaining, embed)) = parse_embed_tag(input) {
+            let html = embed_html(&embed, eager)
+                .ok_or(format!("Unable to embed '{}', expected the id or URL of a video. On line {}.", embed.source, input.location_line()))?;
+            output.push_str(&html);
+            input = remaining;
+        } else {
+            let (remaining, text) = take_till_tag(input);
+            output.push_str(text.fragment());
+            input = remaining;
+        }
+    }
+
+    Ok(output)
+}
+
+/// The styles of the page that a YouTube or Vimeo player is loaded from, when
+/// it is clicked.
+const EMBED_PREVIEW_STYLE: &str = "*{margin:0;padding:0;overflow:hidden}html,body{height:100%;background:#000}a{display:block;height:100%}img{width:100%;height:100%;object-fit:cover}span{position:absolute;top:50%;left:50%;transform:translate(-50%,-50%);font:48px/1 sans-serif;color:#fff;text-shadow:0 0 .5em #000}";
+
+/// The HTML of an embed, or `None` if it is a YouTube or Vimeo video without a
+/// valid id.
+fn embed_html(embed: &EmbedTag, eager: bool) -> Option<String> {
+    let title = embed.title.as_deref();
+    let source = escape_html(&embed.source);
+    let (class, player) = match embed.kind {
+        EmbedKind::Video => ("video", format!(
+            "<video src=\"{}\" controls preload=\"metadata\" playsinline><a href=\"{}\">{}</a></video>",
+            source, source, escape_html(title.unwrap_or("Download the video")),
+        )),
+        EmbedKind::Audio => ("audio", format!(
+            "<audio src=\"{}\" controls preload=\"metadata\"><a href=\"{}\">{}</a></audio>",
+            source, source, escape_html(title.unwrap_or("Download the audio")),
+        )),
+        kind => {
+            let id = embed_id(kind, &embed.source)?;
+            let (class, url, thumbnail, name) = match kind {
+                EmbedKind::YouTube => ("youtube", format!("https://www.youtube-nocookie.com/embed/{}", id), Some(format!("https://i.ytimg.com/vi/{}/hqdefault.jpg", id)), "YouTube video"),
+                _ => ("vimeo", format!("https://player.vimeo.com/video/{}?dnt=1", id), None, "Vimeo video"),
+            };
+            let title = escape_html(title.unwrap_or(name));
+            let attributes = format!("width=\"560\" height=\"315\" title=\"{}\" loading=\"lazy\" allow=\"autoplay; encrypted-media; fullscreen; picture-in-picture\" allowfullscreen", title);
+            let player = match eager {
+                true => format!("<iframe src=\"{}\" {}></iframe>", url, attributes),
+                false => {
+                    // The player replaces the preview, once it is clicked.
+                    let url = format!("{}{}autoplay=1", url, if url.contains('?') { '&' } else { '?' });
+                    let thumbnail = thumbnail.map(|thumbnail| format!("<img src=\"{}\" alt=\"\">", thumbnail)).unwrap_or_default();
+                    let preview = format!("<style>{}</style><a href=\"{}\" aria-label=\"Play {}\">{}<span>&#9654;</span></a>", EMBED_PREVIEW_STYLE, url, title, thumbnail);
+                    format!("<iframe src=\"{}\" srcdoc=\"{}\" {}></iframe>", url, escape_html(&preview), attributes)
+                },
+            };
+            (class, player)
+        },
+    };
+    let caption = title.map(|title| format!("<figcaption>{}</figcaption>", escape_html(title))).unwrap_or_default();
+
+    Some(format!("<figure class=\"embed embed-{}\">{}{}</figure>", class, player, caption))
+}
+
+/// The id of a YouTube or Vimeo video, from either the id or a URL of it.
+fn embed_id(kind: EmbedKind, source: &str) -> Option<String> {
+    let id = match kind {
+        EmbedKind::YouTube => ["v=", "youtu.be/", "/embed/", "/shorts/"]
+            .iter()
+            .find_map(|prefix| source.split_once(prefix).map(|(_, id)| id))
+            .unwrap_or(source),
+        _ => source.trim_end_matches('/').rsplit('/').next().unwrap_or(source),
+    };
+    let id = id.split(['?', '&', '#']).next().unwrap_or_default();
+    let valid = match kind {
+        EmbedKind::YouTube => id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+        _ => id.chars().all(|c| c.is_ascii_digit()),
+    };
+
+    (!id.is_empty() && valid).then(|| id.to_string())
+}
+
+/// Copy the local images that a Markdown refers to, with `![alt](src)` or an
+/// `<img>`, into the [`BatchOptions::output_dir`], and update the `src` of
+/// each `<img>` of its rendered `html` that shows one.
+///
+/// An image within the directory of the Markdown keeps its path relative to
+/// the output, so its `src` is unchanged, whereas an image outside of it, such
+/// as `../assets/logo.png`, is copied into `assets/logo.png` of the output
+/// directory. Images that are not found, or that are URLs, are left alone, as
+/// is every image without an `output_dir`, or with
+/// [`BatchOptions::dry_run`].
+///
+/// With [`BatchOptions::image_sizes`], each `<img>` without a `width` or
+/// `height` is also given the size of a PNG, GIF, JPEG, or WebP image.
+fn copy_images(html: &str, markdown: &str, markdown_path: &Path, output_path: &Path, options: &BatchOptions) -> Result<String, Box<dyn Error>> {
+    let markdown_dir = markdown_path.parent().unwrap_or(Path::new(""));
+    let mut images = HashMap::new();
+    for src in image_sources(markdown) {
+        let source = normalise_path(&markdown_dir.join(&src));
+        if images.contains_key(&src) || !source.is_file() {
+            continue;
+        }
+
+        let mut url = src.to_owned();
+        if let (true, Some(output_dir)) = (options.copy_images, &options.output_dir) {
+            let relative = normalise_path(Path::new(&src));
+            let destination = match relative.starts_with("..") {
+                true => output_dir.join(relative.components().filter(|component| matches!(component, std::path::Component::Normal(_))).collect::<PathBuf>()),
+                false => output_path.parent().unwrap_or(Path::new("")).join(relative),
+            };
+            // An image that many Markdowns share is only copied once.
+            let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+            if !options.dry_run && destination != source && modified(&destination) < modified(&source) {
+                if let Some(parent) = destination.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::copy(&source, &destination).map_err(|e| format!("Unable to copy the image '{}': {}.", source.to_string_lossy(), e))?;
+            }
+            if let Some(manifest) = options.manifest.as_ref().filter(|_| !options.dry_run && destination != source) {
+                manifest.insert(destination.to_owned());
+            }
+            url = relative_url(output_path, &destination);
+        }
+        let size = options.image_sizes.then(|| fs::read(&source).ok().and_then(|bytes| image_size(&bytes))).flatten();
+        images.insert(src, (url, size));
+    }
+    if images.is_empty() {
+        return Ok(html.to_string());
+    }
+
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find("<img") {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = tag_end(rest).filter(|_| rest[4..].starts_with(|c: char| c.is_whitespace()));
+        let Some(end) = end else {
+            output.push('<');
+            rest = &rest[1..];
+            continue;
+        };
+        let inner = rest[4..end].trim_end();
+        let self_closing = inner.ends_with('/');
+        let mut attributes = tag_attributes(inner.trim_end_matches('/'));
+        let image = attributes
+            .iter()
+            .find(|(name, _)| name == "src")
+            .and_then(|(_, src)| images.get(&src.as_deref()?.replace("&amp;", "&").replace("%20", " ")));
+        let Some((url, size)) = image else {
+            output.push_str(&rest[..=end]);
+            rest = &rest[end + 1..];
+            continue;
+        };
+
+        for (name, value) in attributes.iter_mut() {
+            if name == "src" {
+                *value = Some(escape_html(url));
+            }
+        }
+        if let Some((width, height)) = size.filter(|_| !attributes.iter().any(|(name, _)| name == "width" || name == "height")) {
+            attributes.push(("width".to_string(), Some(width.to_string())));
+            attributes.push(("height".to_string(), Some(height.to_string())));
+        }
+        output.push_str(&format_tag("img", attributes, self_closing));
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+/// Inline the local stylesheets, images, and fonts of some HTML, so that it is
+/// a single self-contained file, such as for archiving or emailing an article.
+///
+/// Each `<link rel="stylesheet">` is replaced by a `<style>` of its CSS, and
+/// each `<img>`, along with every `url()` of the CSS, such as a font, is given
+/// a `data:` URI. An asset is found within the first of the `dirs` that has
+/// it, or beside the stylesheet that refers to it, and a root-relative URL,
+/// such as `/style.css`, is relative to each of the `dirs`. Assets that aren't
+/// found, or that are URLs, are left alone.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::inline_assets;
+///
+/// let dir = std::env::temp_dir().join("blogs_md_easy_inline_assets");
+/// std::fs::create_dir_all(dir.join("css")).unwrap();
+/// std::fs::write(dir.join("css/style.css"), "h1 { background: url('../dot.svg'); }").unwrap();
+/// std::fs::write(dir.join("dot.svg"), "<svg/>").unwrap();
+///
+/// let html = inline_assets("<link rel=\"stylesheet\" href=\"/css/style.css\"><img src=\"dot.svg\" alt=\"\"><img src=\"https://example.com/a.png\" alt=\"\">", &[dir]);
+/// assert_eq!(html, "<style>h1 { background: url(\"data:image/svg+xml;base64,PHN2Zy8+\"); }</style><img src=\"data:image/svg+xml;base64,PHN2Zy8+\" alt=\"\"><img src=\"https://example.com/a.png\" alt=\"\">");
+/// ```
+pub fn inline_assets(html: &str, dirs: &[PathBuf]) -> String {
+    let value = |attributes: &[(String, Option<String>)], name: &str| attributes
+        .iter()
+        .find(|(attribute, _)| attribute.eq_ignore_ascii_case(name))
+        .and_then(|(_, value)| value.to_owned());
+
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let length = rest[1..].find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len() - 1);
+        let name = rest[1..1 + length].to_ascii_lowercase();
+        let Some(end) = tag_end(rest).filter(|_| matches!(name.as_str(), "link" | "img" | "style")) else {
+            output.push('<');
+            rest = &rest[1..];
+            continue;
+        };
+        let inner = rest[1 + length..end].trim_end();
+        let self_closing = inner.ends_with('/');
+        let mut attributes = tag_attributes(inner.trim_end_matches('/'));
+
+        match name.as_str() {
+            "link" if value(&attributes, "rel").is_some_and(|rel| rel.split_whitespace().any(|rel| rel.eq_ignore_ascii_case("stylesheet"))) => {
+                let stylesheet = value(&attributes, "href")
+                    .and_then(|href| find_asset(&href, dirs))
+                    .and_then(|path| Some((fs::read_to_string(&path).ok()?, path)));
+                if let Some((css, path)) = stylesheet {
+                    // The URLs of a stylesheet are relative to it.
+                    let css_dirs = path.parent().map(Path::to_path_buf).into_iter().chain(dirs.iter().cloned()).collect::<Vec<PathBuf>>();
+                    let media = value(&attributes, "media").map(|media| format!(" media=\"{}\"", media)).unwrap_or_default();
+                    output.push_str(&format!("<style{}>{}</style>", media, inline_css_urls(&css, &css_dirs)));
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            },
+            "img" => {
+                let uri = value(&attributes, "src")
+                    .and_then(|src| find_asset(&src.replace("&amp;", "&"), dirs))
+                    .and_then(|path| data_uri(&path));
+                if let Some(uri) = uri {
+                    // Other sources would be chosen over the inlined one.
+                    attributes.retain(|(name, _)| !name.eq_ignore_ascii_case("srcset") && !name.eq_ignore_ascii_case("sizes"));
+                    for (name, value) in attributes.iter_mut() {
+                        if name.eq_ignore_ascii_case("src") {
+                            *value = Some(uri.to_owned());
+                        }
+                    }
+                    output.push_str(&format_tag("img", attributes, self_closing));
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            },
+            "style" => {
+                if let Some(close) = rest[end + 1..].find("</style>") {
+                    output.push_str(&rest[..=end]);
+                    output.push_str(&inline_css_urls(&rest[end + 1..end + 1 + close], dirs));
+                    rest = &rest[end + 1 + close..];
+                    continue;
+                }
+            },
+            _ => {},
+        }
+        output.push_str(&rest[..=end]);
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+
+    output
+}
+
+/// Replace each `url()` of some CSS that is a local asset, within the `dirs`,
+/// with a `data:` URI of it.
+fn inline_css_urls(css: &str, dirs: &[PathBuf]) -> String {
+    let mut output = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("url(") {
+        output.push_str(&rest[..start + 4]);
+        rest = &rest[start + 4..];
+        let Some(end) = rest.find(')') else {
+            break;
+        };
+        let url = rest[..end].trim().trim_matches(['"', '\'']);
+        if let Some(uri) = find_asset(url, dirs).and_then(|path| data_uri(&path)) {
+            output.push_str(&format!("\"{}\"", uri));
+            rest = &rest[end..];
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// The path of the local asset at `url`, within the first of the `dirs` that
+/// has it, ignoring any query or fragment.
+fn find_asset(url: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    if url.is_empty() || url.contains(':') || url.starts_with("//") {
+        return None;
+    }
+    let relative = url.trim_start_matches('/').replace("%20", " ");
+    dirs.iter().map(|dir| normalise_path(&dir.join(&relative))).find(|path| path.is_file())
+}
+
+/// The file at `path`, as a base64 `data:` URI.
+fn data_uri(path: &Path) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    let media_type = match path.extension().unwrap_or_default().to_string_lossy().to_lowercase().as_str() {
+        "avif" => "image/avif",
+        "css" => "text/css",
+        "gif" => "image/gif",
+        "ico" => "image/x-icon",
+        "jpeg" | "jpg" => "image/jpeg",
+        "otf" => "font/otf",
+        "png" => "image/png",
+        "svg" => "image/svg+xml",
+        "ttf" => "font/ttf",
+        "webp" => "image/webp",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        _ => "application/octet-stream",
+    };
+    Some(format!("data:{};base64,{}", media_type, BASE64.encode(bytes)))
+}
+
+/// Write a tag, with the `attributes` that were read by [`tag_attributes`].
+fn format_tag(name: &str, attributes: Vec<(String, Option<String>)>, self_closing: bool) -> String {
+    let mut tag = format!("<{}", name);
+    for (name, value) in attributes {
+        match value {
+            Some(value) => tag.push_str(&format!(" {}=\"{}\"", name, value.replace('"', "&quot;"))),
+            None => tag.push_str(&format!(" {}", name)),
+        }
+    }
+    tag.push_str(if self_closing { " />" } else { ">" });
+    tag
+}
+
+/// The `src` of each local image of a Markdown, from `![alt](src)` or an
+/// `<img>`, leaving out URLs and absolute paths.
+fn image_sources(markdown: &str) -> Vec<String> {
+    let mut sources = Vec::new();
+    let mut rest = markdown;
+    while let Some(start) = rest.find(['!', '<']) {
+        rest = &rest[start..];
+        let src = match rest.strip_prefix("![") {
+            Some(image) => image
+                .find("](")
+                .map(|middle| image[middle + 2..].trim_start())
+                .and_then(|src| match src.strip_prefix('<') {
+                    Some(src) => src.split_once('>').map(|(src, _)| src),
+                    None => src.split([')', ' ', '\t', '\n']).next(),
+                })
+                .map(str::to_string),
+            None if rest.starts_with("<img") => tag_end(rest)
+                .and_then(|end| tag_attributes(rest[4..end].trim_end_matches('/')).into_iter().find(|(name, _)| name == "src"))
+                .and_then(|(_, src)| src),
+            None => None,
+        };
+        if let Some(src) = src.filter(|src| !src.is_empty() && !src.contains(':') && !src.starts_with(['/', '#', '\\'])) {
+            sources.push(src);
+        }
+        rest = &rest[1..];
+    }
+    sources
+}
+
+/// The `src` of each `<img>` of some HTML with an empty, or without any, `alt`
+/// text, unless it is hidden from screen readers.
+fn images_without_alt(html: &str) -> Vec<String> {
+    let mut sources = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find("<img") {
+        rest = &rest[start + 4..];
+        let Some(end) = tag_end(rest) else {
+            break;
+        };
+        let attributes = tag_attributes(rest[..end].trim_end_matches('/'));
+        let value = |name: &str| attributes.iter().find(|(attribute, _)| attribute == name).map(|(_, value)| value.as_deref().unwrap_or_default());
+        let hidden = value("aria-hidden") == Some("true") || value("role") == Some("presentation");
+        if !hidden && value("alt").is_none_or(|alt| alt.trim().is_empty()) {
+            sources.push(value("src").unwrap_or_default().to_string());
+        }
+    }
+    sources
+}
+
+/// Remove the `.` and `..` components of a path, without touching the disk.
+fn normalise_path(path: &Path) -> PathBuf {
+    let mut normalised = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {},
+            std::path::Component::ParentDir if matches!(normalised.components().next_back(), Some(std::path::Component::Normal(_))) => {
+                normalised.pop();
+            },
+            component => normalised.push(component),
+        }
+    }
+    normalised
+}
+
+/// Add an `id` to each heading element of some HTML, without one, whose slug is
+/// one of the `anchors`, so that the [`heading_anchors`] of a Markdown can be
+/// linked to.
+///
+/// Headings that already have an `id`, such as those from [`wrap_sections`],
+/// are still numbered, and an `id` is never given to two elements.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::add_heading_ids;
+///
+/// let html = add_heading_ids("<h1>Hello</h1><h2>Getting <em>Started</em></h2><h2 id=\"own\">Own</h2>", &["getting-started".to_string()]);
+/// assert_eq!(html, "<h1>Hello</h1><h2 id=\"getting-started\">Getting <em>Started</em></h2><h2 id=\"own\">Own</h2>");
+///
+/// let html = add_heading_ids("<h2 id=\"usage\">Usage</h2><h3>Usage</h3>", &["usage".to_string(), "usage-1".to_string()]);
+/// assert_eq!(html, "<h2 id=\"usage\">Usage</h2><h3 id=\"usage-1\">Usage</h3>");
+/// ```
+pub fn add_heading_ids(html: &str, anchors: &[String]) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find("<h") {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // Headings with an `id` are numbered, but left as they are.
+        if let Some((_, end, inner)) = heading_tag(rest).filter(|(_, end, _)| tag_attributes(&rest[3..*end]).iter().any(|(name, _)| name == "id")) {
+            numbered_slug(slugify(&heading_text(inner)), &mut counts);
+            output.push_str(&rest[..=end]);
+            rest = &rest[end + 1..];
+            continue;
+        }
+
+        // Only heading elements without any attributes, such as `<h2>`.
+        let level = rest.as_bytes().get(2).filter(|level| (b'1'..=b'6').contains(level) && rest.as_bytes().get(3) == Some(&b'>'));
+        let inner = level.and_then(|level| rest[4..].find(&format!("</h{}>", *level as char)).map(|end| &rest[4..4 + end]));
+        let Some(inner) = inner else {
+            output.push_str("<h");
+            rest = &rest[2..];
+            continue;
+        };
+
+        let anchor = numbered_slug(slugify(&heading_text(inner)), &mut counts);
+        if !anchor.is_empty() && anchors.contains(&anchor) && !html.contains(&format!(" id=\"{}\"", anchor)) {
+            output.push_str(&format!("{} id=\"{}\">", &rest[..3], anchor));
+        } else {
+            output.push_str(&rest[..4]);
+        }
+        rest = &rest[4..];
+    }
+    output.push_str(rest);
+
+    output
+}
+
+/// Add an `id` to each paragraph element of some HTML, without any attributes,
+/// so that a reader can link to, or annotate, a single paragraph.
+///
+/// The `id` is from a hash of the paragraph's text, such as `p-1a2b3c4d`,
+/// so that editing one paragraph doesn't change the `id` of any other. A
+/// paragraph with the same text as an earlier one is numbered, such as
+/// `p-1a2b3c4d-1`.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::add_paragraph_ids;
+///
+/// let html = add_paragraph_ids("<p>Hello, <em>World</em>!</p><p class=\"note\">Left alone.</p><p>Hello, World!</p>");
+/// assert_eq!(html, "<p id=\"p-dffd6021\">Hello, <em>World</em>!</p><p class=\"note\">Left alone.</p><p id=\"p-dffd6021-1\">Hello, World!</p>");
+/// ```
+pub fn add_paragraph_ids(html: &str) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find("<p>") {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // Whitespace is collapsed, so that rewrapping a paragraph keeps its id.
+        let text = rest[3..].find("</p>").map(|end| heading_text(&rest[3..3 + end])).unwrap_or_default();
+        let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+        let id = numbered_slug(format!("p-{}", &hash_hex(text.as_bytes())[..8]), &mut counts);
+        match text.is_empty() || html.contains(&format!(" id=\"{}\"", id)) {
+            true => output.push_str("<p>"),
+            false => output.push_str(&format!("<p id=\"{}\">", id)),
+        }
+        rest = &rest[3..];
+    }
+    output.push_str(rest);
+
+    output
+}
+
+/// Add a `¶` link to the end of each heading element of some HTML that has an
+/// `id`, so that a link to the heading can be copied.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::add_permalinks;
+///
+/// let html = add_permalinks("<h1>Hello</h1><h2 id=\"usage\">Usage &amp; Examples</h2>");
+/// assert_eq!(html, "<h1>Hello</h1><h2 id=\"usage\">Usage &amp; Examples <a class=\"permalink\" href=\"#usage\" aria-label=\"Link to Usage &amp; Examples\">¶</a></h2>");
+/// ```
+pub fn add_permalinks(html: &str) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find("<h") {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let heading = heading_tag(rest).and_then(|(level, end, inner)| {
+            let id = tag_attributes(&rest[3..end]).into_iter().find(|(name, _)| name == "id")?.1?;
+            Some((level, end, inner, id))
+        });
+        let Some((level, end, inner, id)) = heading else {
+            output.push_str("<h");
+            rest = &rest[2..];
+            continue;
+        };
+
+        output.push_str(&format!(
+            "{}{} <a class=\"permalink\" href=\"#{}\" aria-label=\"Link to {}\">¶</a></h{}>",
+            &rest[..=end],
+            inner,
+            id,
+            escape_html(&heading_text(inner)),
+            level,
+        ));
+        rest = &rest[end + 1 + inner.len() + 5..];
+    }
+    output.push_str(rest);
+
+    output
+}
+
+/// Wrap each section of some HTML, from an `<h2>` until the next `<h1>` or
+/// `<h2>`, in an `element`, such as `<section>`, which is labelled by the
+/// heading, for screen readers.
+///
+/// Each `<h2>` without an `id` is given the same one as its anchor from
+/// [`heading_anchors`]. The footnotes of the Markdown are already a section of
+/// their own, so they are left as they are.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::wrap_sections;
+///
+/// let html = wrap_sections("<h1>Hello</h1>\n<p>Intro</p>\n<h2>Usage</h2>\n<p>Run it.</p>\n<h2 id=\"faq\">Questions</h2>\n<h3>Why?</h3>\n", "section");
+/// assert_eq!(html, "<h1>Hello</h1>\n<p>Intro</p>\n<section aria-labelledby=\"usage\">\n<h2 id=\"usage\">Usage</h2>\n<p>Run it.</p>\n</section>\n<section aria-labelledby=\"faq\">\n<h2 id=\"faq\">Questions</h2>\n<h3>Why?</h3>\n</section>\n");
+/// ```
+pub fn wrap_sections(html: &str, element: &str) -> String {
+    let (html, footnotes) = html.split_at(html.find("<section data-footnotes").unwrap_or(html.len()));
+    // The level of each heading, the start and end of its opening tag, its
+    // `id`, and its anchor.
+    let mut headings = Vec::new();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut offset = 0;
+    while let Some(start) = html[offset..].find("<h").map(|start| offset + start) {
+        offset = start + 2;
+        let Some((level, end, inner)) = heading_tag(&html[start..]) else {
+            continue;
+        };
+        let id = tag_attributes(&html[start + 3..start + end]).into_iter().find(|(name, _)| name == "id").and_then(|(_, id)| id);
+        let anchor = numbered_slug(slugify(&heading_text(inner)), &mut counts);
+        headings.push((level, start, start + end, id, anchor));
+    }
+
+    let mut output = String::with_capacity(html.len());
+    let mut cursor = 0;
+    let mut open = false;
+    // Close the open section before any whitespace, so that it stays on the
+    // following line.
+    let push_until = |output: &mut String, text: &str, open: bool| match open {
+        true => {
+            let content = text.trim_end();
+            output.push_str(&format!("{}\n</{}>{}", content, element, &text[content.len()..]));
+        },
+        false => output.push_str(text),
+    };
+    for (level, start, end, id, anchor) in headings.into_iter().filter(|(level, ..)| *level <= 2) {
+        push_until(&mut output, &html[cursor..start], open);
+        cursor = start;
+        open = level == 2;
+        if !open {
+            continue;
+        }
+
+        match (id, anchor.is_empty()) {
+            (Some(id), _) => output.push_str(&format!("<{} aria-labelledby=\"{}\">\n{}", element, id, &html[start..=end])),
+            (None, true) => output.push_str(&format!("<{}>\n{}", element, &html[start..=end])),
+            (None, false) => output.push_str(&format!("<{0} aria-labelledby=\"{1}\">\n<h2 id=\"{1}\"{2}", element, anchor, &html[start + 3..=end])),
+        }
+        cursor = end + 1;
+    }
+    push_until(&mut output, &html[cursor..], open);
+    output.push_str(footnotes);
+
+    output
+}
+
+/// The level of the heading element at the start of some HTML, the offset of
+/// the `>` that ends its opening tag, and its inner HTML.
+fn heading_tag(html: &str) -> Option<(u8, usize, &str)> {
+    let level = html.strip_prefix("<h")?.bytes().next().filter(|level| (b'1'..=b'6').contains(level))?;
+    if !html.as_bytes().get(3).is_some_and(|c| *c == b'>' || c.is_ascii_whitespace()) {
+        return None;
+    }
+    let end = tag_end(html)?;
+    let length = html[end + 1..].find(&format!("</h{}>", level as char))?;
+    Some((level - b'0', end, &html[end + 1..end + 1 + length]))
+}
+
+/// The text of a heading's inner HTML, without any tags or entities.
+fn heading_text(inner: &str) -> String {
+    strip_tags(inner).replace("&amp;", "&").replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&#39;", "'")
+}
+
+/// Add attributes to the elements of some HTML, such as a `class` for every
+/// `<table>`, so that it can be styled without processing the HTML again.
+///
+/// The key of each of the `attributes` is the element, then the name of the
+/// attribute, such as `table.class`. The element can also require an attribute
+/// that it already has, such as `a[title].rel` for the links with a title.
+/// A `class` is added to any that the element already has, whereas
+/// any other attribute is replaced.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{add_attributes, Meta};
+///
+/// let attributes = vec![
+///     Meta::new("table.class", "prose-table"),
+///     Meta::new("img[alt].loading", "lazy"),
+/// ];
+/// let html = add_attributes("<table class=\"wide\"><tr><td><img src=\"a.png\" alt=\"A\" /><img src=\"b.png\"></td></tr></table>", &attributes);
+/// assert_eq!(html, "<table class=\"wide prose-table\"><tr><td><img src=\"a.png\" alt=\"A\" loading=\"lazy\" /><img src=\"b.png\"></td></tr></table>");
+/// ```
+pub fn add_attributes(html: &str, attributes: &[Meta]) -> String {
+    let rules = attributes
+        .iter()
+        .filter_map(|meta| {
+            let (selector, name) = meta.key.rsplit_once('.')?;
+            let (element, required) = match selector.strip_suffix(']').and_then(|selector| selector.split_once('[')) {
+                Some((element, required)) => (element, Some(required)),
+                None => (selector, None),
+            };
+            Some((element, required, name, meta.value.as_str()))
+        })
+        .collect::<Vec<(&str, Option<&str>, &str, &str)>>();
+
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // Closing tags, comments and doctypes have no element name.
+        let length = rest[1..].find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len() - 1);
+        let element = &rest[1..1 + length];
+        let matching = rules.iter().filter(|(name, ..)| name.eq_ignore_ascii_case(element)).collect::<Vec<_>>();
+        let end = tag_end(rest);
+        let (Some(end), false) = (end, element.is_empty() || matching.is_empty()) else {
+            output.push('<');
+            rest = &rest[1..];
+            continue;
+        };
+
+        let inner = rest[1 + length..end].trim_end();
+        let self_closing = inner.ends_with('/');
+        let mut existing = tag_attributes(inner.trim_end_matches('/'));
+        let mut changed = false;
+        for (_, required, name, value) in matching {
+            if required.is_some_and(|required| !existing.iter().any(|(existing, _)| existing == required)) {
+                continue;
+            }
+            changed = true;
+            let value = escape_html(value);
+            match existing.iter_mut().find(|(existing, _)| existing == name) {
+                Some((_, Some(class))) if *name == "class" && !class.is_empty() => class.push_str(&format!(" {}", value)),
+                Some((_, current)) => *current = Some(value),
+                None => existing.push((name.to_string(), Some(value))),
+            }
+        }
+
+        match changed {
+            true => {
+                output.push_str(&format!("<{}", element));
+                for (name, value) in existing {
+                    match value {
+                        Some(value) => output.push_str(&format!(" {}=\"{}\"", name, value.replace('"', "&quot;"))),
+                        None => output.push_str(&format!(" {}", name)),
+                    }
+                }
+                output.push_str(if self_closing { " />" } else { ">" });
+            },
+            false => output.push_str(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+
+    output
+}
+
+/// Convert some HTML into plain text, such as for a newsletter, where each
+/// block element is a paragraph of its own, each list item is a `-` bullet,
+/// and each link is followed by its URL.
+///
+/// Only the `<body>` of a document is converted, and the contents of its
+/// scripts and styles are removed.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::html_to_text;
+///
+/// let html = "<html><head><title>Hello</title></head><body><h1>Hello</h1>\n<p>Read  the <a href=\"https://example.com\">docs</a> &amp; more.</p><ul><li>One</li><li>Two</li></ul></body></html>";
+/// assert_eq!(html_to_text(html), "Hello\n\nRead the docs (https://example.com) & more.\n\n- One\n- Two\n");
+/// ```
+pub fn html_to_text(html: &str) -> String {
+    const BLOCKS: [&str; 22] = ["address", "article", "aside", "blockquote", "div", "figcaption", "figure", "footer", "h1", "h2", "h3", "h4", "h5", "h6", "header", "hr", "main", "nav", "ol", "p", "section", "ul"];
+
+    let body = html
+        .find("<body")
+        .and_then(|start| Some(start + tag_end(&html[start..])? + 1))
+        .map(|start| &html[start..html[start..].find("</body>").map_or(html.len(), |end| start + end)])
+        .unwrap_or(html);
+
+    let mut text = String::with_capacity(body.len());
+    let mut href = None;
+    let mut preformatted = false;
+    let mut rest = body;
+    while !rest.is_empty() {
+        let start = rest.find('<').unwrap_or(rest.len());
+        let run = decode_entities(&rest[..start]);
+        match preformatted {
+            true => text.push_str(&run),
+            false => {
+                // Whitespace is collapsed, as a browser would.
+                let collapsed = run.split_whitespace().collect::<Vec<&str>>().join(" ");
+                if run.starts_with(char::is_whitespace) && !text.ends_with([' ', '\n']) {
+                    text.push(' ');
+                }
+                text.push_str(&collapsed);
+                if !collapsed.is_empty() && run.ends_with(char::is_whitespace) {
+                    text.push(' ');
+                }
+            },
+        }
+        rest = &rest[start..];
+        if rest.is_empty() {
+            break;
+        }
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(end) = tag_end(rest) else {
+            text.push('<');
+            rest = &rest[1..];
+            continue;
+        };
+        let closing = rest[1..].starts_with('/');
+        let name = rest[1 + closing as usize..end].split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or_default().to_lowercase();
+        let attributes = &rest[1 + closing as usize + name.len()..end];
+        rest = &rest[end + 1..];
+
+        match name.as_str() {
+            "head" | "script" | "style" | "template" if !closing => {
+                rest = rest.find(&format!("</{}", name)).map_or("", |end| &rest[end..]);
+            },
+            "br" => text.push('\n'),
+            "li" if !closing => text.push_str("\n- "),
+            "pre" => {
+                preformatted = !closing;
+                text.push_str("\n\n");
+            },
+            "a" if !closing => {
+                href = tag_attributes(attributes.trim_end_matches('/'))
+                    .into_iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case("href"))
+                    .and_then(|(_, href)| href)
+                    .map(|href| decode_entities(&href));
+            },
+            "a" => {
+                // Links within the page are meaningless without it.
+                if let Some(href) = href.take().filter(|href| !href.starts_with('#')) {
+                    text.push_str(&format!(" ({})", href));
+                }
+            },
+            "img" => {
+                let alt = tag_attributes(attributes.trim_end_matches('/')).into_iter().find(|(name, _)| name.eq_ignore_ascii_case("alt"));
+                if let Some((_, Some(alt))) = alt {
+                    text.push_str(&decode_entities(&alt));
+                }
+            },
+            name if BLOCKS.contains(&name) || name == "tr" => text.push_str("\n\n"),
+            _ => {},
+        }
+    }
+
+    // Each paragraph is separated by a single blank line.
+    let mut output = String::with_capacity(text.len());
+    let mut blank = false;
+    for line in text.lines().map(str::trim_end) {
+        if line.is_empty() {
+            blank = !output.is_empty();
+            continue;
+        }
+        if blank {
+            output.push('\n');
+            blank = false;
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output
+}
+
+/// Inline the `<style>`s of some HTML into the `style` of each element that
+/// they apply to, for email clients that ignore stylesheets.
+///
+/// Only rules whose selectors are an element, a class, or both, such as `p`,
+/// `.note`, or `p.note`, are inlined, in order of their specificity; the rest,
+/// such as media queries, are kept in a `<style>`. An element's own `style`
+/// takes precedence over every rule.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::inline_styles;
+///
+/// let html = "<style>p { margin: 0; } .note { color: red; } @media (max-width: 600px) { p { margin: 1em; } }</style><p class=\"note\" style=\"font-weight: bold\">Hi</p>";
+/// assert_eq!(inline_styles(html), "<style>@media (max-width: 600px) { p { margin: 1em; } }</style><p class=\"note\" style=\"margin: 0; color: red; font-weight: bold\">Hi</p>");
+/// ```
+pub fn inline_styles(html: &str) -> String {
+    // Each rule, as its selector's element and class, and its declarations.
+    let mut rules = Vec::new();
+    let mut kept = Vec::new();
+    let mut first_style = None;
+    let mut without_styles = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find("<style") {
+        without_styles.push_str(&rest[..start]);
+        let Some((css, remaining)) = tag_end(&rest[start..])
+            .map(|end| &rest[start + end + 1..])
+            .and_then(|css| css.find("</style>").map(|end| (&css[..end], &css[end + "</style>".len()..])))
+        else {
+            without_styles.push_str("<style");
+            rest = &rest[start + "<style".len()..];
+            continue;
+        };
+        first_style.get_or_insert(without_styles.len());
+
+        for (prelude, body) in css_rules(css) {
+            let selectors = prelude.split(',').map(|selector| {
+                let selector = selector.trim();
+                let (element, class) = selector.split_once('.').unwrap_or((selector, ""));
+                let simple = |part: &str| part.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+                (!selector.trim_matches('.').is_empty() && simple(element) && simple(class)).then(|| (element.to_string(), class.to_string()))
+            }).collect::<Option<Vec<_>>>();
+            match selectors {
+                Some(selectors) if !prelude.starts_with('@') => {
+                    let declarations = body.split(';').map(str::trim).filter(|declaration| !declaration.is_empty()).collect::<Vec<_>>().join("; ");
+                    rules.extend(selectors.into_iter().map(|(element, class)| (element, class, declarations.to_owned())));
+                },
+                _ => kept.push(format!("{} {{ {} }}", prelude, body)),
+            }
+        }
+        rest = remaining;
+    }
+    without_styles.push_str(rest);
+    if let (Some(offset), false) = (first_style, kept.is_empty()) {
+        without_styles.insert_str(offset, &format!("<style>{}</style>", kept.join(" ")));
+    }
+    // An element is more specific than nothing, and a class than an element.
+    rules.sort_by_key(|(element, class, _)| (!class.is_empty(), !element.is_empty()));
+
+    let mut output = String::with_capacity(without_styles.len());
+    let mut rest = without_styles.as_str();
+    while let Some(start) = rest.find('<') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // Closing tags, comments and doctypes have no element name.
+        let length = rest[1..].find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len() - 1);
+        let element = &rest[1..1 + length];
+        let (Some(end), false) = (tag_end(rest), element.is_empty() || rules.is_empty()) else {
+            output.push('<');
+            rest = &rest[1..];
+            continue;
+        };
+
+        // Styles would be shown as the text of these elements.
+        if ["style", "script", "title"].iter().any(|name| name.eq_ignore_ascii_case(element)) {
+            let end = rest.find(&format!("</{}", element)).unwrap_or(end);
+            output.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        let inner = rest[1 + length..end].trim_end();
+        let self_closing = inner.ends_with('/');
+        let mut attributes = tag_attributes(inner.trim_end_matches('/'));
+        let classes = attributes.iter().find(|(name, _)| name == "class").and_then(|(_, class)| class.to_owned()).unwrap_or_default();
+        let mut declarations = rules
+            .iter()
+            .filter(|(rule, class, _)| (rule.is_empty() || rule.eq_ignore_ascii_case(element)) && (class.is_empty() || classes.split_whitespace().any(|c| c == *class)))
+            .map(|(_, _, declarations)| declarations.as_str())
+            .filter(|declarations| !declarations.is_empty())
+            .collect::<Vec<&str>>();
+        if declarations.is_empty() {
+            output.push_str(&rest[..=end]);
+            rest = &rest[end + 1..];
+            continue;
+        }
+
+        let existing = attributes.iter().find(|(name, _)| name == "style").and_then(|(_, style)| style.to_owned()).unwrap_or_default();
+        let existing = existing.trim().trim_end_matches(';');
+        if !existing.is_empty() {
+            declarations.push(existing);
+        }
+        let style = declarations.join("; ");
+        match attributes.iter_mut().find(|(name, _)| name == "style") {
+            Some((_, current)) => *current = Some(style),
+            None => attributes.push(("style".to_string(), Some(style))),
+        }
+        output.push_str(&format_tag(element, attributes, self_closing));
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+
+    output
+}
+
+/// Split a stylesheet into the prelude and body of each of its rules, without
+/// comments, where an at-rule keeps its nested rules in its body.
+fn css_rules(css: &str) -> Vec<(String, String)> {
+    let mut css = css.to_string();
+    while let Some(start) = css.find("/*") {
+        let end = css[start..].find("*/").map_or(css.len(), |end| start + end + 2);
+        css.replace_range(start..end, "");
+    }
+
+    let mut rules = Vec::new();
+    let mut rest = css.as_str();
+    while let Some(open) = rest.find('{') {
+        let mut depth = 0;
+        let Some(close) = rest[open..].char_indices().find_map(|(offset, c)| {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {},
+            }
+            (depth == 0).then_some(open + offset)
+        }) else {
+            break;
+        };
+        rules.push((rest[..open].trim().to_string(), rest[open + 1..close].trim().to_string()));
+        rest = &rest[close + 1..];
+    }
+    rules
+}
+
+/// Remove every `element` of some HTML, along with its contents.
+fn remove_elements(html: &str, element: &str) -> String {
+    let (open, close) = (format!("<{}", element), format!("</{}>", element));
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find(&open) {
+        // Only the element itself, rather than one that starts with its name.
+        if !rest[start + open.len()..].starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace()) {
+            output.push_str(&rest[..start + open.len()]);
+            rest = &rest[start + open.len()..];
+            continue;
+        }
+        output.push_str(&rest[..start]);
+        rest = rest[start..].find(&close).map_or("", |end| &rest[start + end + close.len()..]);
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Replace the entities that [`escape_html`] writes, and `&nbsp;`, with their
+/// characters.
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&#39;", "'").replace("&nbsp;", " ").replace("&amp;", "&")
+}
+
+/// The offset of the `>` that ends the tag at the start of some HTML, ignoring
+/// any within the quoted values of its attributes.
+fn tag_end(html: &str) -> Option<usize> {
+    let mut quote = None;
+    for (offset, c) in html.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(open)) if c == open => quote = None,
+            ('>', None) => return Some(offset),
+            _ => {},
+        }
+    }
+    None
+}
+
+/// The attributes of a tag, without its element name, and their values, which
+/// are left escaped.
+fn tag_attributes(tag: &str) -> Vec<(String, Option<String>)> {
+    let mut attributes = Vec::new();
+    let mut rest = tag.trim_start();
+    while !rest.is_empty() {
+        let length = rest.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(rest.len());
+        let name = rest[..length].to_string();
+        rest = rest[length..].trim_start();
+
+        let value = match rest.strip_prefix('=') {
+            Some(value) => {
+                let value = value.trim_start();
+                let (value, remaining) = match value.chars().next() {
+                    Some(quote @ ('"' | '\'')) => match value[1..].find(quote) {
+                        Some(end) => (&value[1..1 + end], &value[2 + end..]),
+                        None => (&value[1..], ""),
+                    },
+                    _ => value.split_at(value.find(char::is_whitespace).unwrap_or(value.len())),
+                };
+                rest = remaining.trim_start();
+                Some(value.to_string())
+            },
+            None => None,
+        };
+        attributes.push((name, value));
+    }
+    attributes
+}
+
+/// Split Markdown into the content beneath each `##` heading, along with the
+/// slug of the heading, and the heading itself, ignoring any headings within
+/// fenced code blocks.
+///
+/// A heading that has already been seen is numbered, such as `usage-1`, as
+/// with [`add_heading_ids`].
+fn split_sections(markdown: &str) -> Vec<(String, String, String)> {
+    let mut sections: Vec<(String, &str, Vec<&str>)> = Vec::new();
+    let mut fence: Option<(char, usize)> = None;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        // A fence is closed by a line of at least as many of its character,
+        // and nothing else, so a `~~~` within a ```` ``` ```` block is code.
+        let marker = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'));
+        let length = marker.map_or(0, |marker| trimmed.chars().take_while(|c| *c == marker).count());
+        match (fence, marker) {
+            (Some((open, open_length)), Some(marker)) if marker == open && length >= open_length && trimmed[length..].trim().is_empty() => fence = None,
+            (None, Some(marker)) if length >= 3 => fence = Some((marker, length)),
+            _ => {},
+        }
+
+        match line.strip_prefix("## ") {
+            Some(heading) if fence.is_none() => sections.push((slugify(heading), heading.trim().trim_end_matches('#').trim_end(), Vec::new())),
+            _ => if let Some((_, _, lines)) = sections.last_mut() {
+                lines.push(line);
+            },
+        }
+    }
+
+    let mut counts = HashMap::new();
+    sections
+        .into_iter()
+        // A heading without any letters can't be used as a variable name.
+        .filter(|(slug, ..)| !slug.is_empty())
+        .map(|(slug, heading, lines)| (numbered_slug(slug, &mut counts), heading.to_string(), lines.join("\n").trim().to_string()))
+        .collect()
+}
+
+/// Convert a heading into a valid variable name, such as `getting-started`,
+/// keeping the letters and numbers of any language, such as `café`.
+pub(crate) fn slugify(heading: &str) -> String {
+    heading
+        .trim()
+        .trim_end_matches('#')
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-")
+        // Variable names must start with a letter.
+        .trim_start_matches(|c: char| !c.is_alphabetic())
+        .to_string()
+}
+
+/// The `filename`, `slug`, and `path` variables of the Markdown at
+/// `markdown_path`, where the `slug` is from its file name, as with
+/// [`Post::id`].
+pub(crate) fn path_variables(markdown_path: &Path) -> [(&'static str, String); 3] {
+    let markdown_path = forward_slashes(markdown_path);
+    [
+        ("filename", markdown_path.file_name().unwrap_or_default().to_string_lossy().to_string()),
+        ("slug", slugify(&markdown_path.file_stem().unwrap_or_default().to_string_lossy())),
+        ("path", markdown_path.to_string_lossy().to_string()),
+    ]
+}
+
+/// The `og_title`, `og_description`, `og_image`, and `og_url` of a Markdown,
+/// see [`BatchOptions::open_graph`], where `path` is where it is written,
+/// relative to the root of the site.
+fn open_graph_variables(meta_values: &[Meta], markdown: &str, variables: &HashMap<String, String>, path: &str) -> Vec<(&'static str, String)> {
+    let meta = |keys: &[&str]| keys.iter().find_map(|key| meta_values.iter().find(|meta| &meta.key == key && !meta.value.is_empty())).map(|meta| meta.value.to_owned());
+    let variable = |key: &str| variables.get(key).filter(|value| !value.is_empty()).cloned();
+
+    let base_url = variable("base_url").map(|base_url| base_url.trim_end_matches('/').to_string());
+    let url = match &base_url {
+        Some(base_url) => format!("{}/{}", base_url, path.trim_start_matches('/')),
+        None => path.to_string(),
+    };
+    let mut open_graph = vec![
+        ("og_title", meta(&["og_title"]).or(variable("title")).unwrap_or_default()),
+        ("og_description", meta(&["og_description", "description", "summary"]).or(variable("excerpt")).unwrap_or_default()),
+    ];
+
+    let image = meta(&["og_image", "image", "cover"])
+        .or_else(|| image_sources(markdown).into_iter().next())
+        .or(variable("og_image"));
+    if let Some(image) = image {
+        // Relative images are relative to the page, and absolute paths to the
+        // root of the site.
+        let image = match (&base_url, image.contains(':')) {
+            (_, true) | (None, _) => image,
+            (Some(base_url), false) if image.starts_with('/') => format!("{}{}", base_url, image),
+            (Some(_), false) => format!("{}{}", &url[..url.rfind('/').map_or(0, |slash| slash + 1)], image),
+        };
+        open_graph.push(("og_image", image));
+    }
+    open_graph.push(("og_url", url));
+
+    open_graph
+}
+
+/// The `publish_date` of the Markdown at `markdown_path`, from the first of the
+/// `sources` that has one.
+pub(crate) fn derived_publish_date(markdown_path: &Path, sources: &[DateSource]) -> Option<String> {
+    sources.iter().find_map(|source| match source {
+        DateSource::FileName => {
+            let stem = markdown_path.file_stem()?.to_string_lossy();
+            let date = stem.get(..10)?;
+            let is_date = date.char_indices().all(|(i, c)| if i == 4 || i == 7 { c == '-' } else { c.is_ascii_digit() });
+            // The date must be followed by the title, rather than more digits.
+            let separated = stem[10..].chars().next().is_none_or(|c| !c.is_ascii_alphanumeric());
+            (is_date && separated).then(|| date.to_string())
+        },
+        DateSource::Modified => {
+            let modified = fs::metadata(markdown_path).ok()?.modified().ok()?;
+            let seconds = modified.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
+            Some(unix_to_date(seconds))
+        },
+    })
+}
+
+/// Number a slug that has already been seen, such as `usage-1`.
+fn numbered_slug(slug: String, counts: &mut HashMap<String, usize>) -> String {
+    let count = counts.entry(slug.to_owned()).or_default();
+    let numbered = if *count == 0 { slug } else { format!("{}-{}", slug, count) };
+    *count += 1;
+    numbered
+}
+
+/// The URL of `to`, relative to the directory of `from`.
+pub(crate) fn relative_url(from: &Path, to: &Path) -> String {
+    let from = from.parent().unwrap_or(Path::new("")).components().collect::<Vec<_>>();
+    let to = to.components().collect::<Vec<_>>();
+    let common = from.iter().zip(&to).take_while(|(from, to)| from == to).count();
+
+    std::iter::repeat_n("..".to_string(), from.len() - common)
+        .chain(to[common..].iter().map(|component| component.as_os_str().to_string_lossy().to_string()))
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+/// Split a line of Markdown into segments, and whether each may contain a
+/// link, so that code, links, and HTML tags are left alone.
+fn linkable_segments(line: &str) -> Vec<(String, bool)> {
+    let mut segments = Vec::new();
+    let mut rest = line;
+
+    while let Some(start) = rest.find(['`', '[', '<']) {
+        let close = match &rest[start..start + 1] {
+            "`" => rest[start + 1..].find('`').map(|end| start + 1 + end + 1),
+            "[" => rest[start..].find("](").and_then(|middle| rest[start + middle..].find(')').map(|end| start + middle + end + 1)),
+            _ => rest[start..].find('>').map(|end| start + end + 1),
+        };
+        let Some(end) = close else {
+            segments.push((rest[..start + 1].to_string(), true));
+            rest = &rest[start + 1..];
+            continue;
+        };
+
+        segments.push((rest[..start].to_string(), true));
+        segments.push((rest[start..end].to_string(), false));
+        rest = &rest[end..];
+    }
+    segments.push((rest.to_string(), true));
+
+    segments
+}
+
+/// Replace the first occurrence of `term` in the linkable `segments` with a link
+/// to `url`, returning whether it was found.
+fn link_term(segments: &mut Vec<(String, bool)>, term: &str, url: &str) -> bool {
+    let term = term.trim().to_ascii_lowercase();
+    let is_boundary = |c: Option<char>| c.is_none_or(|c| !c.is_alphanumeric());
+
+    for index in 0..segments.len() {
+        let (segment, linkable) = &segments[index];
+        if !linkable {
+            continue;
+        }
+
+        let lowercase = segment.to_ascii_lowercase();
+        // Terms within a URL, such as `https://www.rust-lang.org`, aren't
+        // linked either.
+        let is_url = |start: usize| {
+            let word_start = segment[..start].rfind(char::is_whitespace).map(|index| index + 1).unwrap_or_default();
+            let word_end = segment[start..].find(char::is_whitespace).map(|index| start + index).unwrap_or(segment.len());
+            segment[word_start..word_end].contains("://")
+        };
+        let found = lowercase.match_indices(&term).map(|(start, _)| start).find(|start| {
+            is_boundary(segment[..*start].chars().next_back()) && is_boundary(segment[start + term.len()..].chars().next()) && !is_url(*start)
+        });
+        if let Some(start) = found {
+            let end = start + term.len();
+            let replacement = vec![
+                (segment[..start].to_string(), true),
+                (format!("[{}]({})", &segment[start..end], url), false),
+                (segment[end..].to_string(), true),
+            ];
+            segments.splice(index..=index, replacement);
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Rename every use of the variable `from`, including its nested variables,
+/// to `to`, whether it is written with a `£` or a `$`.
+fn rename_variable(template: &str, from: &str, to: &str) -> String {
+    rename_variables(template, &[(from.to_string(), to.to_string())])
+}
+
+/// Rename every use of each variable, such as `(from, to)`, at once, so that
+/// two variables can swap their names.
+fn rename_variables(template: &str, renames: &[(String, String)]) -> String {
+    if renames.is_empty() {
+        return template.to_string();
+    }
+
+    let mut output = String::with_capacity(template.len());
+    let mut input = Span::new(template);
+
+    while !input.fragment().is_empty() {
+        let (remaining, text) = take_till_tag(input);
+        // Literal text is left as it is.
+        match parse_literal(input) {
+            Ok(_) => output.push_str(text.fragment()),
+            Err(_) => output.push_str(&rename_variables_in(text.fragment(), renames)),
+        }
+        input = remaining;
+    }
+
+    output
+}
+
+/// Rename each variable within some text, that has no literal text.
+fn rename_variables_in(template: &str, renames: &[(String, String)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(['£', '$']) {
+        let sigil = rest[start..].chars().next().unwrap_or_default();
+        let name = &rest[start + sigil.len_utf8()..];
+        output.push_str(&rest[..start + sigil.len_utf8()]);
+        // Only whole names are renamed, so `£postage` is not `£post`.
+        let rename = renames.iter().find(|(from, _)| name.strip_prefix(from.as_str())
+            .is_some_and(|after| !after.starts_with(|c: char| c.is_alphanumeric() || c == '-' || c == '_')));
+        rest = match rename {
+            Some((from, to)) => {
+                output.push_str(to);
+                &name[from.len()..]
+            },
+            None => name,
+        };
+    }
+    output.push_str(rest);
+    output
+}
+
+/// The first paragraph of some Markdown, ignoring headings and code blocks, or
+/// everything before a `<!-- more -->` marker.
+pub(crate) fn excerpt(markdown: &str) -> String {
+    if let Some((excerpt, _)) = markdown.split_once("<!-- more -->") {
+        return excerpt.trim().to_string();
+    }
+
+    let mut fenced = false;
+    markdown
+        .replace("\r\n", "\n")
+        .split("\n\n")
+        .map(str::trim)
+        .find(|paragraph| {
+            // A code block may contain blank lines.
+            if paragraph.matches("```").count() % 2 == 1 {
+                fenced = !fenced;
+                return false;
+            }
+            !fenced && !paragraph.is_empty() && !paragraph.starts_with('#') && !paragraph.starts_with("```")
+        })
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Split off everything up to the next possible tag, always consuming at least
+/// one character in case this is a `{{` that isn't a tag.
+///
+/// Literal text is split off in its entirety, so that its tags are left as
+/// they are.
+fn take_till_tag(input: Span) -> (Span, Span) {
+    let text = input.fragment();
+    if let Ok((remaining, _)) = parse_literal(input) {
+        return input.take_split(text.len() - remaining.fragment().len());
+    }
+
+    let skip = text.chars().next().map(char::len_utf8).unwrap_or_default();
+    let end = text[skip..].find("{{").map(|offset| offset + skip).unwrap_or(text.len());
+    // A `\{{` is split off before its backslash.
+    let end = if text[..end].ends_with('\\') { end - 1 } else { end };
+    input.take_split(end)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Functions
+/// Replaces a substring in the original string with a replacement string.
+///
+/// # Arguments
+///
+/// * `original` - The original string.
+/// * `start` - The start position of the substring in the original string.
+/// * `end` - The end position of the substring in the original string.
+/// * `replacement` - The string to replace the substring.
+///
+/// # Returns
+///
+/// * A new string with the replacement in place of the original substring.
+///
+/// # Example
+/// ```
+/// use blogs_md_easy::replace_substring;
+///
+/// let original = "Hello, World!";
+/// let start = 7;
+/// let end = 12;
+/// let replacement = "Rust";
+/// let result = replace_substring(original, start, end, replacement);
+/// println!("{}", result);  // Prints: "Hello, Rust!"
+/// ```
+pub fn replace_substring(original: &str, start: usize, end: usize, replacement: &str) -> String {
+    let mut result = String::with_capacity(original.len() - (end - start) + replacement.len());
+    result.push_str(&original[..start]);
+    result.push_str(replacement);
+    result.push_str(&original[end..]);
+    result
+}
+
+/// Decode the raw bytes of a file into a String.
+///
+/// A UTF-8 byte order mark is removed, and files that start with a UTF-16 byte
+/// order mark are converted into UTF-8.  \
+/// Anything else must be valid UTF-8, otherwise an error naming the `path` is
+/// returned; the `path` is only used for this message.
+///
+/// # Examples
+/// A UTF-8 byte order mark is removed.
+/// ```rust
+/// use std::path::Path;
+/// use blogs_md_easy::decode_file;
+///
+/// let bytes = b"\xEF\xBB\xBF:meta\ntitle = Hello\n:meta";
+/// let contents = decode_file(bytes, Path::new("post.md")).unwrap();
+/// assert_eq!(contents, ":meta\ntitle = Hello\n:meta");
+/// ```
+///
+/// UTF-16 files are converted.
+/// ```rust
+/// use std::path::Path;
+/// use blogs_md_easy::decode_file;
+///
+/// let bytes = [0xFF, 0xFE, b'H', 0, b'i', 0];
+/// let contents = decode_file(&bytes, Path::new("post.md")).unwrap();
+/// assert_eq!(contents, "Hi");
+/// ```
+///
+/// Other encodings, such as Latin-1, are rejected.
+/// ```rust
+/// use std::path::Path;
+/// use blogs_md_easy::decode_file;
+///
+/// let bytes = b"Caf\xE9";
+/// let error = decode_file(bytes, Path::new("post.md")).unwrap_err();
+/// assert!(error.to_string().contains("post.md"));
+/// ```
+pub fn decode_file(bytes: &[u8], path: &Path) -> Result<String, Box<dyn Error>> {
+    let path = path.to_string_lossy();
+    let from_utf16 = |bytes: &[u8], from_bytes: fn([u8; 2]) -> u16| -> Result<String, Box<dyn Error>> {
+        let units = bytes
+            .chunks(2)
+            .map(|pair| from_bytes([pair[0], *pair.get(1).unwrap_or(&0)]))
+            .collect::<Vec<u16>>();
+        Ok(String::from_utf16(&units)
+            .map_err(|_| format!("The file '{path}' has a UTF-16 byte order mark, but is not valid UTF-16."))?)
+    };
+
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => from_utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => from_utf16(rest, u16::from_be_bytes),
+        _ => {
+            // Strip the UTF-8 byte order mark, if there is one.
+            let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+            Ok(String::from_utf8(bytes.to_vec())
+                .map_err(|_| format!("The file '{path}' is not valid UTF-8, please save it with UTF-8 encoding."))?)
+        },
+    }
+}
+
+/// Read a file into a String, using [`decode_file`] to handle byte order marks
+/// and report encoding problems.
+pub fn read_file(path: &Path) -> Result<String, Box<dyn Error>> {
+    let bytes = fs::read(path).map_err(|e| format!("Unable to read '{}': {}", path.to_string_lossy(), e))?;
+    decode_file(&bytes, path)
+}
+
+/// Replace every [`Placeholder`] within a template with its variable, after
+/// running the variable through the [`Placeholder`]'s [`Filter`]s.
+///
+/// The `placeholders` can be in any order. The output is built in a single
+/// pass from the start of the template, so no placeholder moves the offsets of
+/// another.
+///
+/// # Example
+/// ```rust
+/// use std::collections::HashMap;
+/// use blogs_md_easy::{parse_placeholder_locations, render_placeholders, Span};
+///
+/// let template = "<h1>{{ £title | uppercase }}</h1>";
+/// let placeholders = parse_placeholder_locations(Span::new(template)).unwrap();
+/// let variables = HashMap::from([("title".to_string(), "Hello, World!".to_string())]);
+///
+/// let output = render_placeholders(template, &placeholders, &variables).unwrap();
+/// assert_eq!(output, "<h1>HELLO, WORLD!</h1>");
+/// ```
+pub fn render_placeholders(template: &str, placeholders: &[Placeholder], variables: &HashMap<String, String>) -> Result<String, Box<dyn Error>> {
+    render_placeholders_with_policy(template, placeholders, variables, &MissingVariablePolicy::Error)
+}
+
+/// Replace every [`Placeholder`] within a template with its variable, as with
+/// [`render_placeholders`], but handle variables that don't exist with the
+/// [`MissingVariablePolicy`].
+///
+/// # Example
+/// ```rust
+/// use std::collections::HashMap;
+/// use blogs_md_easy::{parse_placeholder_locations, render_placeholders_with_policy, MissingVariablePolicy, Span};
+///
+/// let template = "<h1>{{ £title }}</h1><p>{{ £author }}</p>";
+/// let placeholders = parse_placeholder_locations(Span::new(template)).unwrap();
+/// let variables = HashMap::from([("title".to_string(), "Hello".to_string())]);
+///
+/// let output = render_placeholders_with_policy(template, &placeholders, &variables, &MissingVariablePolicy::KeepPlaceholder).unwrap();
+/// assert_eq!(output, "<h1>Hello</h1><p>{{ £author }}</p>");
+///
+/// let output = render_placeholders_with_policy(template, &placeholders, &variables, &MissingVariablePolicy::ReplaceWithEmpty).unwrap();
+/// assert_eq!(output, "<h1>Hello</h1><p></p>");
+/// ```
+pub fn render_placeholders_with_policy(template: &str, placeholders: &[Placeholder], variables: &HashMap<String, String>, policy: &MissingVariablePolicy) -> Result<String, Box<dyn Error>> {
+    Ok(render_placeholders_cached(template, placeholders, variables, &BatchOptions { on_missing: policy.to_owned(), ..Default::default() })?)
+}
+
+/// Replace every [`Placeholder`], running expensive [`Filter`]s through the
+/// [`BatchOptions::cache`].
+///
+/// The HTML of each [`Filter::Markdown`] is given its heading ids and
+/// permalinks, has its sections wrapped, then is given the
+/// [`BatchOptions::attributes`].
+///
+/// A filter that can't render its value is an error at its placeholder.
+fn render_placeholders_cached(template: &str, placeholders: &[Placeholder], variables: &HashMap<String, String>, options: &BatchOptions) -> Result<String, ParseError> {
+    let mut output = ChunkedOutput::default();
+    write_placeholders(template, placeholders, |name| variables.get(name).map(|value| Cow::Borrowed(value.as_str())), options, |chunk| output.push(chunk))?;
+    Ok(output.finish())
+}
+
+/// A [`Diagnostic`] for each placeholder of the `template` whose variable is
+/// missing, in the order that they appear.
+fn missing_variable_diagnostics(template: &Template, variables: &HashMap<String, String>) -> Vec<Diagnostic> {
+    let missing = template.missing_variables(variables);
+    in_template_order(template.placeholders())
+        .into_iter()
+        .filter(|placeholder| missing.contains(&placeholder.name))
+        .map(|placeholder| {
+            let (start, end) = (placeholder.selection.start.offset, placeholder.selection.end.offset);
+            Diagnostic::new(Span::new(template.source()).slice(start..), &format!("missing variable '{}'", &placeholder.name))
+                .with_length(end - start)
+        })
+        .collect()
+}
+
+/// Replace every [`Placeholder`], as [`render_placeholders_cached`] does, but
+/// pass each chunk of the output to `write`, in order, rather than keeping it.
+///
+/// Each `variable` is looked up by its name, so that the variables can be a
+/// [`HashMap`], or a [`Value`] with collections.
+pub(crate) fn write_placeholders<'a>(template: &'a str, placeholders: &[Placeholder], variable: impl Fn(&str) -> Option<Cow<'a, str>>, options: &'a BatchOptions, mut write: impl FnMut(Cow<'a, str>)) -> Result<(), ParseError> {
+    // Walk the placeholders from the start of the template, so that the output
+    // can be built in order.
+    let placeholders = in_template_order(placeholders);
+
+    let mut cursor = 0;
+    for placeholder in placeholders {
+        span!("placeholder", name = placeholder.name.as_str());
+        let (start, end) = (placeholder.selection.start.offset, placeholder.selection.end.offset);
+
+        let value = match (variable(&placeholder.name), &options.on_missing) {
+            (Some(value), _) => {
+                event!(debug, "Matched £{} on line {}.", placeholder.name, placeholder.selection.start.line);
+                value
+            },
+            // The default is filled in by its filter.
+            (None, _) if placeholder.has_default() => {
+                event!(debug, "Defaulted £{} on line {}, as it is missing.", placeholder.name, placeholder.selection.start.line);
+                Cow::Borrowed("")
+            },
+            (None, MissingVariablePolicy::Error) => {
+                let diagnostic = Diagnostic::new(Span::new(template).slice(start..), &format!("missing variable '{}'", &placeholder.name));
+                return Err(ParseError::Render(diagnostic.with_length(end - start)));
+            },
+            // Empty strings are not passed through the filters, which could
+            // otherwise add to them, such as the trail of a truncate.
+            (None, MissingVariablePolicy::ReplaceWithEmpty) => {
+                write(Cow::Borrowed(&template[cursor..start]));
+                cursor = end;
+                continue;
+            },
+            // Leave the placeholder to be copied along with the template.
+            (None, MissingVariablePolicy::KeepPlaceholder) => continue,
+            (None, MissingVariablePolicy::UseDefault(default)) => Cow::Borrowed(default.as_str()),
+        };
+
+        // Filter arguments can be variables too, such as `£excerpt_length`.
+        let arguments = placeholder.argument_variables(template);
+        if let (Some(name), MissingVariablePolicy::Error) = (arguments.iter().find(|name| variable(name).is_none()), &options.on_missing) {
+            let diagnostic = Diagnostic::new(Span::new(template).slice(start..), &format!("missing variable '{}'", name));
+            return Err(ParseError::Render(diagnostic.with_length(end - start)));
+        }
+        let resolved;
+        let placeholder = match arguments.is_empty() {
+            true => placeholder,
+            false => {
+                resolved = placeholder.with_arguments(template, &|name| variable(name).map(Cow::into_owned));
+                &resolved
+            },
+        };
+
+        // Variables without filters are never copied until the output is built.
+        let filtered = filter_placeholder(placeholder, value, options).map_err(|error| {
+            let diagnostic = Diagnostic::new(Span::new(template).slice(start..), &format!("{}, in variable '{}'", error, &placeholder.name));
+            ParseError::Render(diagnostic.with_length(end - start))
+        })?;
+        write(Cow::Borrowed(&template[cursor..start]));
+        write(filtered);
+        cursor = end;
+    }
+    write(Cow::Borrowed(&template[cursor..]));
+
+    Ok(())
+}
+
+/// Run the `variable` of a [`Placeholder`] through each of its [`Filter`]s,
+/// through the [`BatchOptions::cache`].
+fn filter_placeholder<'a>(placeholder: &Placeholder, variable: impl Into<Cow<'a, str>>, options: &BatchOptions) -> Result<Cow<'a, str>, FilterError> {
+    placeholder.filters.iter().try_fold(variable.into(), |variable, filter| {
+        // The anchors are those of the Markdown, before it is rendered.
+        let anchors = match filter {
+            Filter::Markdown { .. } if options.heading_ids => heading_anchors(&variable),
+            _ => vec![],
+        };
+        let mut variable = options.cache.render(variable, filter)?;
+        if matches!(filter, Filter::Markdown { .. }) {
+            if options.heading_ids {
+                variable = Cow::Owned(add_heading_ids(&variable, &anchors));
+            }
+            if options.permalinks {
+                variable = Cow::Owned(add_permalinks(&variable));
+            }
+            if let Some(element) = &options.wrap_sections {
+                variable = Cow::Owned(wrap_sections(&variable, element));
+            }
+            if !options.attributes.is_empty() {
+                variable = Cow::Owned(add_attributes(&variable, &options.attributes));
+            }
+        }
+        Ok(variable)
+    })
+}
+
+/// Build the `template` in a single pass, with each range of the
+/// `replacements` replaced by its text, in whichever order they are given.
+///
+/// The ranges must not overlap.
+fn assemble<'a>(template: &'a str, mut replacements: Vec<(Range<usize>, Cow<'a, str>)>) -> String {
+    replacements.sort_by_key(|(range, _)| range.start);
+
+    let mut output = ChunkedOutput::default();
+    let mut cursor = 0;
+    for (range, replacement) in replacements {
+        output.push(Cow::Borrowed(&template[cursor..range.start]));
+        output.push(replacement);
+        cursor = range.end;
+    }
+    output.push(Cow::Borrowed(&template[cursor..]));
+    output.finish()
+}
+
+/// The `placeholders` sorted by where they start within the template.
+fn in_template_order(placeholders: &[Placeholder]) -> Vec<&Placeholder> {
+    let mut placeholders = placeholders.iter().collect::<Vec<&Placeholder>>();
+    placeholders.sort_by_key(|placeholder| placeholder.selection.start.offset);
+    placeholders
+}
+
+/// Render a Markdown into a template, in a single call.
+///
+/// This parses the template's placeholders and the Markdown's meta section,
+/// creates the variables with [`create_variables`], then replaces each
+/// placeholder. Nothing is read from, or written to, disk.
+///
+/// Each of these steps can be replaced, or extended, with a [`Pipeline`]. To
+/// render many Markdowns into the same template, parse it once with
+/// [`Template::new`], then use [`Template::render_markdown`].
+///
+/// # Examples
+/// ```rust
+/// use blogs_md_easy::render;
+///
+/// let template = "<h1>{{ £title | uppercase }}</h1><p>By {{ £author }}</p>";
+/// let markdown = ":meta\nauthor = John Doe\n:meta\n# Hello, World!";
+/// assert_eq!(render(template, markdown).unwrap(), "<h1>HELLO, WORLD!</h1><p>By John Doe</p>");
+/// ```
+///
+/// Each kind of failure can be handled separately.
+/// ```rust
+/// use blogs_md_easy::{render, RenderError};
+///
+/// let error = render("{{ £author }}", "# Hello, World!").unwrap_err();
+/// assert_eq!(error, RenderError::MissingVariable("author".to_string()));
+///
+/// let error = render("{{ £title }}", "No heading here.").unwrap_err();
+/// assert_eq!(error, RenderError::MissingTitle);
+///
+/// let error = render("{{ £title }}", ":meta\ntitle Hello\n:meta").unwrap_err();
+/// assert!(matches!(error, RenderError::Meta(_)));
+/// ```
+pub fn render(template: &str, markdown: &str) -> Result<String, RenderError> {
+    render_with_warnings(template, markdown).map(|(output, _)| output)
+}
+
+/// Render a Markdown into a template, in a single call, along with anything
+/// that is likely to be a mistake, see [`Warning`].
+///
+/// This is the same as [`render`], so a missing variable is still an error.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{render_with_warnings, Warning};
+///
+/// let template = "<h1>{{ £title }}</h1><p>{{ £rating | ceil }}</p>{{ £content }}";
+/// let markdown = ":meta\nrating = 4.5\nauthor = John Doe\n:meta\n# Hello, World!\n![](photo.jpg)";
+/// let (_, warnings) = render_with_warnings(template, markdown).unwrap();
+/// assert_eq!(warnings, vec![
+///     Warning::UnusedVariable("author".to_string()),
+/// # #[cfg(feature = "markdown")]
+///     Warning::MissingAltText("photo.jpg".to_string()),
+/// ]);
+/// ```
+pub fn render_with_warnings(template: &str, markdown: &str) -> Result<(String, Vec<Warning>), RenderError> {
+    Pipeline::new().run(template, markdown)
+}
+
+/// Render a Markdown into a template, in a single call, along with where each
+/// placeholder was rendered within the output, such as to highlight the value
+/// of a meta key within a preview.
+///
+/// The ranges are in bytes, in the order that the placeholders appear within
+/// the template.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::render_with_source_map;
+///
+/// let (output, source_map) = render_with_source_map("<h1>{{ £title }}</h1><p>By {{ £author | uppercase }}</p>", ":meta\nauthor = John Doe\n:meta\n# Hello").unwrap();
+/// assert_eq!(output, "<h1>Hello</h1><p>By JOHN DOE</p>");
+/// assert_eq!(source_map[1].name, "author");
+/// assert_eq!(&output[source_map[1].output.clone()], "JOHN DOE");
+/// ```
+pub fn render_with_source_map(template: &str, markdown: &str) -> Result<(String, Vec<SourceMapping>), RenderError> {
+    Pipeline::new().render(template, markdown).map(|rendering| (rendering.output, rendering.source_map))
+}
+
+/// What a template is checked against by [`validate_template`], which is
+/// either a sample Markdown, or the [`MetaSchema`] that every Markdown follows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ValidationSource<'a> {
+    /// A Markdown, whose variables are created as they would be by [`render`].
+    Markdown(&'a str),
+    /// The keys of a [`MetaSchema`], along with the variables that every
+    /// Markdown has, such as `content` and `reading_time`.
+    Schema(&'a MetaSchema),
+}
+
+impl<'a> From<&'a str> for ValidationSource<'a> {
+    fn from(markdown: &'a str) -> Self {
+        Self::Markdown(markdown)
+    }
+}
+
+impl<'a> From<&'a MetaSchema> for ValidationSource<'a> {
+    fn from(schema: &'a MetaSchema) -> Self {
+        Self::Schema(schema)
+    }
+}
+
+/// The variables of a template that don't match those of a Markdown, or a
+/// [`MetaSchema`], see [`validate_template`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TemplateValidation {
+    /// The variables of the placeholders that have no value, sorted
+    /// alphabetically.
+    pub missing_variables: Vec<String>,
+    /// The variables of the meta section, or schema, that no placeholder uses,
+    /// sorted alphabetically.
+    pub unused_variables: Vec<String>,
+}
+
+impl TemplateValidation {
+    /// Whether every placeholder has a variable, and every variable has a
+    /// placeholder.
+    pub fn is_valid(&self) -> bool {
+        self.missing_variables.is_empty() && self.unused_variables.is_empty()
+    }
+}
+
+/// A problem that [`lint`] found in a template or Markdown, such as an unknown
+/// filter, which is displayed as `path:line:column: message`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LintProblem {
+    /// The template or Markdown with the problem.
+    pub path: PathBuf,
+    /// The line of the problem, starting from 1.
+    pub line: u32,
+    /// The column of the line, in characters, starting from 1.
+    pub column: usize,
+    /// What the problem is.
+    pub message: String,
+}
+
+impl LintProblem {
+    fn new(path: &Path, diagnostic: &Diagnostic) -> Self {
+        Self {
+            path: path.to_owned(),
+            line: diagnostic.line,
+            column: diagnostic.column,
+            message: diagnostic.message.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for LintProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}: {}", self.path.to_string_lossy(), self.line, self.column, self.message)
+    }
+}
+
+/// Check the placeholders of a template against the variables of a Markdown,
+/// or a [`MetaSchema`], without rendering anything, such as to check every
+/// template against a sample post in CI.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::{validate_template, MetaRule, MetaSchema};
+///
+/// let template = "<h1>{{ £title }}</h1><p>By {{ £autor }}</p>{{ £content }}";
+/// let validation = validate_template(template, ":meta\nauthor = John Doe\n:meta\n# Hello").unwrap();
+/// assert_eq!(validation.missing_variables, vec!["autor"]);
+/// assert_eq!(validation.unused_variables, vec!["author"]);
+///
+/// let schema = MetaSchema::new().with("autor", MetaRule::default());
+/// assert!(validate_template(template, &schema).unwrap().is_valid());
+/// ```
+pub fn validate_template<'a>(template: &str, source: impl Into<ValidationSource<'a>>) -> Result<TemplateValidation, Box<dyn Error>> {
+    let template = Template::new(template)?;
+    let (missing_variables, unused_variables) = match source.into() {
+        ValidationSource::Markdown(markdown) => {
+            let (content, meta_values) = match parse_meta_section(Span::new(markdown)) {
+                Ok((content, meta_values)) => (content, meta_values),
+                Err(nom::Err::Error(error @ ParseError::Meta(_))) => Err(error.to_string())?,
+                // There is no meta section.
+                Err(_) => (Span::new(markdown), vec![]),
+            };
+            // A Markdown without a title is reported, rather than an error.
+            let untitled = parse_title(content).is_err() && !meta_values.iter().any(|meta| meta.key == "title");
+            let mut title = meta_values.to_owned();
+            if untitled {
+                title.push(Meta::new("title", ""));
+            }
+            let mut variables = create_variables(content, title)?;
+            if untitled {
+                variables.remove("title");
+            }
+            (template.missing_variables(&variables), template.unused_variables(&meta_values))
+        },
+        ValidationSource::Schema(schema) => {
+            // The variables that every Markdown has, see `create_variables`.
+            let variables = ["title", "content", "excerpt", "word_count", "reading_time", "content_hash"]
+                .into_iter()
+                .map(str::to_string)
+                .chain(schema.rules.keys().cloned())
+                .map(|name| (name, String::new()))
+                .collect::<HashMap<String, String>>();
+            let meta_values = schema.rules.keys().map(|key| Meta::new(key, "")).collect::<Vec<Meta>>();
+            (template.missing_variables(&variables), template.unused_variables(&meta_values))
+        },
+    };
+    Ok(TemplateValidation { missing_variables, unused_variables })
+}
+
+/// Parse every template and Markdown, without rendering or writing anything,
+/// and report their problems, such as in CI.
+///
+/// The problems are the malformed lines of a meta section, the malformed
+/// placeholders of a template, such as an unknown filter, the `{{` and `}}` of
+/// a template that aren't balanced, and the variables of a template that a
+/// Markdown doesn't define, where the `variables` are those shared by every
+/// Markdown. They are sorted by their path, then where they are.
+///
+/// # Example
+/// ```rust
+/// use std::path::PathBuf;
+/// use blogs_md_easy::lint;
+///
+/// let templates = vec![(PathBuf::from("post.html"), "<h1>{{ £titel }}</h1>\n<p>{{ £autor | shout }}</p>\n<p>£date }}</p>".to_string())];
+/// let markdowns = vec![
+///     (PathBuf::from("hello.md"), ":meta\nauthor = John Doe\ndate\n:meta\n# Hello".to_string()),
+///     (PathBuf::from("world.md"), "# World".to_string()),
+/// ];
+/// let problems = lint(&templates, &markdowns, &[]).iter().map(ToString::to_string).collect::<Vec<String>>();
+/// assert_eq!(problems, vec![
+///     "hello.md:3:1: expected `key = value`, or the closing `:meta`",
+///     "post.html:1:5: undefined variable '£titel'",
+///     "post.html:2:16: unknown filter 'shout'",
+///     "post.html:3:10: `}}` without an opening `{{`",
+/// ]);
+/// ```
+pub fn lint(templates: &[(PathBuf, String)], markdowns: &[(PathBuf, String)], variables: &[Meta]) -> Vec<LintProblem> {
+    let mut problems = vec![];
+
+    // The variables of each Markdown, skipping those that can't be parsed.
+    let mut markdown_variables = vec![];
+    for (markdown_path, markdown) in markdowns {
+        let (content, meta_values) = match parse_meta_section(Span::new(markdown)) {
+            Ok(parsed) => parsed,
+            Err(nom::Err::Error(ParseError::Meta(_))) => {
+                problems.extend(meta_errors(Span::new(markdown)).iter().map(|error| LintProblem::new(markdown_path, error.diagnostic())));
+                continue;
+            },
+            // There is no meta section.
+            Err(_) => (Span::new(markdown), vec![]),
+        };
+        // A Markdown without a title is titled by its file name.
+        match create_variables_cached(content, meta_values, Some(markdown_path), TitlePrecedence::Meta, false, WORDS_PER_MINUTE, &FilterCache::new()) {
+            Ok(mut created) => {
+                for (key, value) in path_variables(markdown_path).into_iter().map(|(key, value)| (key.to_string(), value)).chain(variables.iter().map(|meta| (meta.key.to_owned(), meta.value.to_owned()))) {
+                    created.entry(key).or_insert(value);
+                }
+                markdown_variables.push((markdown_path, created));
+            },
+            Err(e) => problems.push(LintProblem { path: markdown_path.to_owned(), line: 1, column: 1, message: e.to_string() }),
+        }
+    }
+
+    for (template_path, template) in templates {
+        // The whitespace control is blanked, rather than applied, so that the
+        // lines and columns are those of the template.
+        let template = template.replace("{{-", "{{ ").replace("-}}", " }}");
+        let parsed = Template::recover(&template);
+        let errors = parsed.errors().iter().map(|error| LintProblem::new(template_path, error.diagnostic())).collect::<Vec<LintProblem>>();
+        // A placeholder that isn't closed is already one of the errors.
+        problems.extend(unbalanced_braces(&template).into_iter()
+            .map(|diagnostic| LintProblem::new(template_path, &diagnostic))
+            .filter(|problem| !errors.iter().any(|error| error.line == problem.line))
+            .collect::<Vec<LintProblem>>());
+        problems.extend(errors);
+
+        // The items of a loop are only defined within it.
+        let mut items = vec![];
+        let mut input = Span::new(&template);
+        while !input.fragment().is_empty() {
+            if let Ok((_, LoopTag::For { item, .. })) = parse_loop_tag(input) {
+                items.push(item);
+            }
+            input = take_till_tag(input).0;
+        }
+        let is_item = |name: &str| items.iter().any(|item| name == item || name.strip_prefix(item.as_str()).is_some_and(|rest| rest.starts_with('.')));
+
+        let mut undefined: BTreeMap<String, Vec<&PathBuf>> = BTreeMap::new();
+        for (markdown_path, variables) in &markdown_variables {
+            for name in parsed.missing_variables(variables).into_iter().filter(|name| !is_item(name)) {
+                undefined.entry(name).or_default().push(*markdown_path);
+            }
+        }
+        for (name, paths) in undefined {
+            // The first placeholder to use the variable, as they are in reverse.
+            let Some(placeholder) = parsed.placeholders().iter().rev().find(|placeholder| {
+                placeholder.name == name || placeholder.argument_variables(parsed.source()).contains(&name)
+            }) else {
+                continue;
+            };
+            let offset = placeholder.selection.start.offset;
+            let line_start = parsed.source()[..offset].rfind('\n').map(|index| index + 1).unwrap_or_default();
+            let message = match paths.len() == markdown_variables.len() {
+                true => format!("undefined variable '£{}'", name),
+                false => format!("undefined variable '£{}', in {}", name, paths.iter().map(|path| format!("'{}'", path.to_string_lossy())).collect::<Vec<String>>().join(", ")),
+            };
+            problems.push(LintProblem {
+                path: template_path.to_owned(),
+                line: placeholder.selection.start.line,
+                column: parsed.source()[line_start..offset].chars().count() + 1,
+                message,
+            });
+        }
+    }
+
+    problems.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+    problems
+}
+
+/// The `{{` of a template that are never closed, and the `}}` after a variable
+/// that were never opened, such as `£title }}`.
+///
+/// Any other `}}` is left alone, as it may well be a script or stylesheet.
+fn unbalanced_braces(template: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let mut input = Span::new(template);
+
+    while !input.fragment().is_empty() {
+        let text = input.fragment();
+        let skip = match parse_literal(input) {
+            Ok((remaining, _)) => text.len() - remaining.fragment().len(),
+            Err(nom::Err::Failure(_)) => {
+                diagnostics.push(Diagnostic::new(input, "expected `{{ endraw }}` to close the raw block"));
+                break;
+            },
+            Err(_) if text.starts_with("{{") => match placeholder_end(&text[2..]) {
+                // The body of a tag can't open another.
+                Some(end) if !text[2..2 + end].contains("{{") => end + 4,
+                _ => {
+                    diagnostics.push(Diagnostic::new(input, "`{{` without a closing `}}`"));
+                    2
+                },
+            },
+            Err(_) if text.starts_with("}}") => {
+                let before = core::str::from_utf8(input.get_line_beginning()).unwrap_or_default();
+                let before = before[..input.get_column() - 1].trim_end();
+                let is_variable = before.rfind(['£', '$']).is_some_and(|index| {
+                    nom::combinator::all_consuming(parse_variable)(Span::new(&before[index..])).is_ok()
+                });
+                if is_variable {
+                    diagnostics.push(Diagnostic::new(input, "`}}` without an opening `{{`"));
+                }
+                2
+            },
+            Err(_) => text.chars().next().map(char::len_utf8).unwrap_or_default(),
+        };
+        input = input.take_split(skip).0;
+    }
+
+    diagnostics
+}
+
+/// Render a Markdown into a template, as [`render`] does, but write the output
+/// to the `writer` as it is rendered, such as to a file or an HTTP response,
+/// rather than returning it.
+///
+/// Only the template, and the variables of the Markdown, are held in memory,
+/// rather than the whole output as well. Nothing is checked once the output
+/// has been written, so there are no [`Warning`]s.
+///
+/// # Example
+/// ```rust
+/// use std::io::BufWriter;
+/// use blogs_md_easy::render_to;
+///
+/// let path = std::env::temp_dir().join("blogs_md_easy_render_to.html");
+/// let file = std::fs::File::create(&path).unwrap();
+/// render_to(BufWriter::new(file), "<h1>{{ £title }}</h1>{{ £content }}", "# Hello, World!\nA long post.").unwrap();
+/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "<h1>Hello, World!</h1><h1>Hello, World!</h1>\n<p>A long post.</p>");
+/// ```
+pub fn render_to<W: io::Write>(writer: W, template: &str, markdown: &str) -> Result<(), RenderError> {
+    let template = Template::new(&apply_whitespace_control(template)).map_err(|e| RenderError::Template(e.to_string()))?;
+    template.render_markdown_to(writer, markdown)
+}
+
+/// Get the path that a rendered Markdown will be written to.
+///
+/// The file will have the same name as the Markdown, but with the extension of
+/// the template. If there is more than one template, then the template's name
+/// will prefix the file name, to avoid templates overwriting one another.
+///
+/// # Example
+/// ```rust
+/// use std::path::{Path, PathBuf};
+/// use blogs_md_easy::get_output_path;
+///
+/// let output = get_output_path(Path::new("template.html"), Path::new("posts/hello.md"), None, false);
+/// assert_eq!(output, PathBuf::from("posts/hello.html"));
+///
+/// let output = get_output_path(Path::new("feed.xml"), Path::new("posts/hello.md"), Some(Path::new("dist")), true);
+/// assert_eq!(output, PathBuf::from("dist/feed-hello.html"));
+///
+/// // Windows separators are separators on every platform.
+/// let output = get_output_path(Path::new("template.html"), Path::new(r"posts\2024\hello.md"), Some(Path::new("dist")), false);
+/// assert_eq!(output, PathBuf::from("dist/hello.html"));
+/// ```
+pub fn get_output_path(template_path: &Path, markdown_path: &Path, output_dir: Option<&Path>, multiple_templates: bool) -> PathBuf {
+    let template_path = &forward_slashes(template_path);
+    let markdown_path = &forward_slashes(markdown_path);
+
+    // Get the template extension, because the user might be passing in
+    // something like an SVG.
+    let template_ext = template_path.extension().unwrap_or(OsStr::new("html"));
+
+    // Get the output path where the `.md` is replaced with `.html`.
+    let mut output_path = match output_dir {
+        Some(path) => path.join(markdown_path.with_extension(template_ext).file_name().unwrap_or_default()),
+        None => markdown_path.with_extension(template_ext),
+    };
+
+    // If there are multiple templates, then add that to the output path
+    // to avoid overwriting issues.
+    if multiple_templates {
+        output_path = output_path.with_file_name(format!(
+            "{}-{}",
+            &template_path.file_stem().unwrap_or_default().to_string_lossy(),
+            output_path.file_stem().unwrap_or_default().to_string_lossy()
+        )).with_extension("html");
+    }
+
+    output_path
+}
+
+/// The `path` with each `\` as a `/`, so that a path that was written on
+/// Windows has the same components on every platform.
+pub(crate) fn forward_slashes(path: &Path) -> Cow<'_, Path> {
+    match path.to_str() {
+        Some(text) if text.contains('\\') => Cow::Owned(PathBuf::from(text.replace('\\', "/"))),
+        _ => Cow::Borrowed(path),
+    }
+}
+
+/// Whether a Markdown is a draft, with `draft = true` in its meta section.
+///
+/// Drafts are skipped by [`render_batch`](crate::batch::render_batch), unless
+/// [`BatchOptions::include_drafts`] is set.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::is_draft;
+///
+/// assert!(is_draft(":meta\ndraft = true\n:meta\n# Hello"));
+/// assert!(!is_draft(":meta\ndraft = false\n:meta\n# Hello"));
+/// assert!(!is_draft("# Hello"));
+/// ```
+pub fn is_draft(markdown: &str) -> bool {
+    parse_meta_section(Span::new(markdown))
+        .map(|(_, meta_values)| meta_values.iter().any(|meta| meta.key == "draft" && meta.value.trim() == "true"))
+        .unwrap_or_default()
+}
+
+/// Whether a Markdown is private, with `visibility = private` in its meta
+/// section.
+///
+/// Private Markdowns are still rendered by
+/// [`render_batch`](crate::batch::render_batch), so that they can be shared by
+/// their URL, but they are left out of
+/// [`collect_posts`](crate::listing::collect_posts), and so every index, feed,
+/// and calendar.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::is_private;
+///
+/// assert!(is_private(":meta\nvisibility = private\n:meta\n# Hello"));
+/// assert!(!is_private(":meta\nvisibility = public\n:meta\n# Hello"));
+/// assert!(!is_private("# Hello"));
+/// ```
+pub fn is_private(markdown: &str) -> bool {
+    parse_meta_section(Span::new(markdown))
+        .map(|(_, meta_values)| meta_values.iter().any(|meta| meta.key == "visibility" && meta.value.trim() == "private"))
+        .unwrap_or_default()
+}
+
+/// Whether a Markdown has asked search engines not to index it, with
+/// `noindex = true` in its meta section.
+///
+/// The output of [`render_batch`](crate::batch::render_batch) is given a
+/// `<meta name="robots" content="noindex">` tag, and the Markdown is left out
+/// of [`collect_posts`](crate::listing::collect_posts), and so every index,
+/// feed, and calendar.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::is_noindex;
+///
+/// assert!(is_noindex(":meta\nnoindex = true\n:meta\n# Hello"));
+/// assert!(!is_noindex(":meta\nnoindex = false\n:meta\n# Hello"));
+/// assert!(!is_noindex("# Hello"));
+/// ```
+pub fn is_noindex(markdown: &str) -> bool {
+    parse_meta_section(Span::new(markdown))
+        .map(|(_, meta_values)| meta_values.iter().any(|meta| meta.key == "noindex" && meta.value.trim() == "true"))
+        .unwrap_or_default()
+}
+
+/// Whether a Markdown had been published by [`BatchOptions::as_of`], from the
+/// `publish_date` or `date` in its meta section, or else a `publish_date`
+/// derived with [`BatchOptions::publish_date_from`].
+///
+/// A Markdown without a date, or a batch without an `as_of`, is always
+/// published.
+///
+/// # Example
+/// ```rust
+/// use std::path::Path;
+/// use blogs_md_easy::{is_published_by, BatchOptions};
+///
+/// let options = BatchOptions { as_of: Some("2023-06-01".to_string()), ..Default::default() };
+/// let path = Path::new("hello.md");
+/// assert!(is_published_by(path, ":meta\ndate = 2023-06-01\n:meta\n# Hello", &options));
+/// assert!(!is_published_by(path, ":meta\npublish_date = 2023-06-02T09:00\n:meta\n# Hello", &options));
+/// assert!(is_published_by(path, "# Hello", &options));
+/// ```
+pub fn is_published_by(markdown_path: &Path, markdown: &str, options: &BatchOptions) -> bool {
+    let Some(as_of) = &options.as_of else {
+        return true;
+    };
+    let meta_values = parse_meta_section(Span::new(markdown)).map(|(_, meta_values)| meta_values).unwrap_or_default();
+    let date = ["publish_date", "date"]
+        .iter()
+        .find_map(|key| meta_values.iter().find(|meta| meta.key == *key).map(|meta| meta.value.trim().to_string()))
+        .or_else(|| derived_publish_date(markdown_path, &options.publish_date_from));
+    // Dates are compared by their day, so that a time of day is ignored.
+    date.is_none_or(|date| date.get(..10).unwrap_or(&date) <= as_of.as_str())
+}
+
+/// Render a single Markdown into the template at `template_path`, with the same
+/// options as [`render_batch`](crate::batch::render_batch), returning the
+/// document rather than writing it, such as for a Markdown that was piped in.
+/// The document is in the [`BatchOptions::format`].
+///
+/// The `{{ ref }}` links of the Markdown are relative to where the batch
+/// would write it, and can refer to the [`BatchOptions::anchors`], or
+/// otherwise only to the Markdown itself.
+///
+/// The `markdown_path` doesn't need to exist, as it is only the `£filename`,
+/// `£slug`, and `£path` of the Markdown, and where it would be written.
+///
+/// # Example
+/// ```rust
+/// use std::{fs, path::Path};
+/// use blogs_md_easy::{render_markdown, BatchOptions, Meta};
+///
+/// let template_path = std::env::temp_dir().join("blogs_md_easy_render_markdown.html");
+/// fs::write(&template_path, "<h1>{{ £title }}</h1><p>{{ £site }}</p>").unwrap();
+///
+/// let options = BatchOptions { variables: vec![Meta::new("site", "My Blog")], ..Default::default() };
+/// let html = render_markdown(&template_path, Path::new("stdin.md"), "# Hello", &options).unwrap();
+/// assert_eq!(html, "<h1>Hello</h1><p>My Blog</p>");
+/// ```
+pub fn render_markdown(template_path: &Path, markdown_path: &Path, markdown: &str, options: &BatchOptions) -> Result<String, Box<dyn Error>> {
+    let profile = chosen_profile(markdown_path, markdown, options)?.map(|(_, profile)| profile);
+    let chosen = chosen_template(markdown, template_path, profile, options);
+    let template = match profile {
+        Some(profile) => load_template(chosen.as_deref().unwrap_or(template_path), &profile.apply(options))?,
+        None => load_template(chosen.as_deref().unwrap_or(template_path), options)?,
+    };
+    let permalinks = permalinks(template_path, &[(markdown_path.to_owned(), markdown.to_string())], options)?;
+    let output_path_of = |markdown_path: &Path| batch_output_path(template_path, markdown_path, options, &permalinks, false);
+    let output_path = output_path_of(markdown_path);
+    let anchors = options.anchors.to_owned().unwrap_or_else(|| AnchorMap::new(&[(markdown_path.to_owned(), markdown.to_string())]));
+    let markdown = resolve_links(markdown, markdown_path, &anchors, &output_path, output_path_of, options)
+        .map_err(|e| format!("{} In markdown '{}'.", e, markdown_path.to_string_lossy()))?;
+
+    let document = render_document(template_path, &template, markdown_path, &markdown, &output_path, options, output_path_of)?;
+    Ok(document.contents(options.format))
+}
+
+/// Bundle a Markdown into a zip archive, for backups, or for platforms that
+/// accept uploads, with [`write_zip`].
+///
+/// The archive contains the Markdown itself, its HTML from [`render_markdown`],
+/// its meta section as `meta.json`, and the local images that it refers to.
+/// An image within the Markdown's directory keeps its path, whereas one
+/// outside of it, such as `../assets/logo.png`, is stored as
+/// `assets/logo.png`, and its `src` is updated to match.
+///
+/// Requires the `compression` and `json` features.
+///
+/// # Example
+/// ```rust
+/// use std::path::Path;
+/// use blogs_md_easy::{export_post, BatchOptions};
+///
+/// let template_path = std::env::temp_dir().join("blogs_md_easy_export.html");
+/// std::fs::write(&template_path, "<h1>{{ £title }}</h1>").unwrap();
+///
+/// let zip = export_post(&template_path, Path::new("hello.md"), ":meta\nauthor = John Doe\n:meta\n# Hello", &BatchOptions::default()).unwrap();
+/// assert!(zip.starts_with(b"PK\x03\x04"));
+/// assert!(zip.windows(9).any(|name| name == b"meta.json"));
+/// ```
+#[cfg(all(feature = "compression", feature = "json"))]
+pub fn export_post(template_path: &Path, markdown_path: &Path, markdown: &str, options: &BatchOptions) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut html = render_markdown(template_path, markdown_path, markdown, &BatchOptions { format: OutputFormat::Html, ..options.clone() })?;
+    let meta_values = parse_meta_section(Span::new(markdown)).map(|(_, meta_values)| meta_values).unwrap_or_default();
+    let meta = meta_values
+        .into_iter()
+        .map(|meta| (meta.key, serde_json::Value::String(meta.value)))
+        .collect::<serde_json::Map<String, serde_json::Value>>();
+
+    let file_name = markdown_path.file_name().unwrap_or(OsStr::new("post.md")).to_string_lossy().to_string();
+    let mut files = vec![
+        (file_name.to_owned(), markdown.as_bytes().to_vec()),
+        (Path::new(&file_name).with_extension("html").to_string_lossy().to_string(), vec![]),
+        ("meta.json".to_string(), serde_json::to_string_pretty(&meta)?.into_bytes()),
+    ];
+    let markdown_dir = markdown_path.parent().unwrap_or(Path::new(""));
+    for src in image_sources(markdown) {
+        let Ok(bytes) = fs::read(normalise_path(&markdown_dir.join(&src))) else {
+            continue;
+        };
+        let name = normalise_path(Path::new(&src))
+            .components()
+            .filter(|component| matches!(component, std::path::Component::Normal(_)))
+            .collect::<PathBuf>()
+            .to_string_lossy()
+            .replace('\\', "/");
+        if files.iter().any(|(file, _)| *file == name) {
+            continue;
+        }
+        if name != src {
+            html = html.replace(&format!("\"{}\"", src), &format!("\"{}\"", name));
+        }
+        files.push((name, bytes));
+    }
+    files[1].1 = html.into_bytes();
+
+    write_zip(&files)
+}
+
+/// Render a Markdown, whose `{{ ref }}` links have been resolved, into the
+/// `template`.
+pub(crate) fn render_document<F>(template_path: &Path, template: &Template, markdown_path: &Path, markdown: &str, output_path: &Path, options: &BatchOptions, output_path_of: F) -> Result<Document, Box<dyn Error>>
+where
+    F: Fn(&Path) -> PathBuf,
+{
+    let source = markdown;
+    let in_markdown = |e: RenderError| -> Box<dyn Error> { format!("{} In markdown '{}'.", e, markdown_path.to_string_lossy()).into() };
+    let pipeline = &options.pipeline;
+    let mut rendering = Rendering {
+        template: template.source().to_string(),
+        markdown: source.to_string(),
+        parsed: template.clone(),
+        ..Default::default()
+    };
+
+    // Parse the meta values, and combine them with the title and
+    // content of the markdown file.
+    let mut embedded_meta = None;
+    pipeline.run_step(Step::Parse, &mut rendering, |rendering| {
+        span!("parse");
+        let markdown = std::mem::take(&mut rendering.markdown);
+        let (content, mut meta_values) = match parse_meta_section(Span::new(&markdown)) {
+            Ok(parsed) => parsed,
+            Err(nom::Err::Error(error @ ParseError::Meta(_))) => {
+                Err(SourceError::new(markdown_path.to_owned(), &markdown, error))?
+            },
+            // There is no meta section.
+            Err(_) => (Span::new(&markdown), vec![]),
+        };
+        options.hooks.after_meta(markdown_path, &mut meta_values)
+            .map_err(|e| format!("{} In markdown '{}'.", e, markdown_path.to_string_lossy()))?;
+        embedded_meta = options.embed_meta.map(|style| (style, meta_values.clone()));
+
+        rendering.markdown = match &options.glossary {
+            Some(glossary) if glossary.path != markdown_path && !meta_values.iter().any(|meta| meta.key == "glossary" && meta.value.trim() == "false") => {
+                glossary.link_terms(content.fragment(), &relative_url(output_path, &output_path_of(&glossary.path)))
+            },
+            _ => content.fragment().to_string(),
+        };
+        rendering.meta_values = meta_values;
+        Ok::<(), Box<dyn Error>>(())
+    }, in_markdown)?;
+
+    // Only variables declared in the meta section can be unused, those
+    // generated for every Markdown are optional for the template.
+    let mut unused_variables = template.unused_variables(&rendering.meta_values);
+    // Opting out of the glossary is for the batch, rather than the template.
+    if options.glossary.is_some() {
+        unused_variables.retain(|name| name != "glossary");
+    }
+    // As is choosing a template or profile, or hiding from search engines.
+    unused_variables.retain(|name| !["template", "templates", "profile", "noindex"].contains(&name.as_str()));
+
+    pipeline.run_step(Step::Resolve, &mut rendering, |rendering| {
+        span!("variables");
+        let meta_values = &rendering.meta_values;
+        let mut variables = create_variables_cached(Span::new(&rendering.markdown), meta_values.clone(), Some(markdown_path), options.title_precedence, options.remove_heading, options.words_per_minute.unwrap_or(WORDS_PER_MINUTE), &options.cache)
+            .map_err(|e| match e.downcast::<RenderError>() {
+                // A meta value was derived from a variable that doesn't exist.
+                Ok(error) => in_markdown(*error),
+                Err(e) => e,
+            })?;
+        if !options.description_from.is_empty() {
+            match description(&options.description_from, meta_values, |name| variables.get(name)) {
+                Some(description) => variables.insert("description".to_string(), description),
+                None => variables.remove("description"),
+            };
+        }
+        for (key, value) in path_variables(markdown_path) {
+            variables.entry(key.to_string()).or_insert(value);
+        }
+        if !variables.contains_key("publish_date") {
+            if let Some(date) = derived_publish_date(markdown_path, &options.publish_date_from) {
+                variables.insert("publish_date".to_string(), date);
+            }
+        }
+        for meta in &options.variables {
+            variables.entry(meta.key.to_owned()).or_insert(meta.value.to_owned());
+        }
+        if let Some(webmentions) = &options.webmentions {
+            // The targets of the mentions are relative to the root of the site.
+            let path = options.output_dir.as_ref().and_then(|output_dir| output_path.strip_prefix(output_dir).ok()).unwrap_or(output_path);
+            variables.entry("webmentions".to_string()).or_insert(webmentions.to_html(&path.to_string_lossy().replace('\\', "/")));
+        }
+        if let Some(creator) = options.fediverse.as_ref().and_then(|fediverse| fediverse.creator(variables.get("author").map(String::as_str))) {
+            variables.entry("fediverse_creator".to_string()).or_insert(creator.to_string());
+        }
+        if options.open_graph {
+            let path = options.output_dir.as_ref().and_then(|output_dir| output_path.strip_prefix(output_dir).ok()).unwrap_or(output_path);
+            for (key, value) in open_graph_variables(meta_values, source, &variables, &path.to_string_lossy().replace('\\', "/")) {
+                variables.insert(key.to_string(), value);
+            }
+        }
+        options.hooks.before_render(markdown_path, &mut variables)
+            .map_err(|e| format!("{} In markdown '{}'.", e, markdown_path.to_string_lossy()))?;
+        rendering.variables = variables;
+        Ok::<(), Box<dyn Error>>(())
+    }, in_markdown)?;
+
+    let missing_variables = template.missing_variables(&rendering.variables);
+    match pipeline.is_extended(Step::Filter) || pipeline.is_extended(Step::Substitute) {
+        // Each value is only needed by the stages that were added, otherwise
+        // the placeholders are filtered as they are written.
+        true => {
+            pipeline.run_step(Step::Filter, &mut rendering, |rendering| filter_values(rendering, options), |error| error).map_err(in_markdown)?;
+            pipeline.run_step(Step::Substitute, &mut rendering, |rendering| BuiltIn(Step::Substitute).run(rendering), |error| error).map_err(in_markdown)?;
+        },
+        false => {
+            rendering.output = render_placeholders_cached(template.source(), template.placeholders(), &rendering.variables, options)
+                .map_err(|error| {
+                    // The error is within the template, so name the Markdown instead.
+                    let in_markdown = |diagnostic: Diagnostic| ParseError::Render(Diagnostic {
+                        message: format!("{}, in markdown '{}'", diagnostic.message, markdown_path.to_string_lossy()),
+                        ..diagnostic
+                    });
+                    let error = match error {
+                        ParseError::Render(diagnostic) => in_markdown(diagnostic),
+                        error => error,
+                    };
+                    let mut error = SourceError::new(template_path.to_owned(), template.source(), error);
+                    if options.collect_errors && matches!(error.error, ParseError::Render(_)) {
+                        error.others = missing_variable_diagnostics(template, &rendering.variables)
+                            .into_iter()
+                            .map(in_markdown)
+                            .filter(|other| other != &error.error)
+                            .collect();
+                    }
+                    error
+                })?;
+        },
+    }
+
+    pipeline.run_step(Step::Postprocess, &mut rendering, |rendering| {
+        let mut html_doc = std::mem::take(&mut rendering.output);
+
+        // Add newlines before each heading element, because I'd like the
+        // HTML to be easy to read.
+        // Match the template's line endings, so that Windows templates
+        // don't end up with a mixture.
+        let newline = if template.source().contains("\r\n") { "\r\n" } else { "\n" };
+        for h in 2..6 {
+            let h = format!("<h{h}>");
+            html_doc = html_doc.replace(&h, &format!("{newline}{h}"));
+        };
+
+        if options.heading_ids {
+            html_doc = add_heading_ids(&html_doc, &heading_anchors(&rendering.markdown));
+        }
+
+        if options.paragraph_ids {
+            html_doc = add_paragraph_ids(&html_doc);
+        }
+
+        if let Some((style, meta_values)) = embedded_meta.take() {
+            html_doc = embed_meta(&html_doc, &meta_values, style);
+        }
+
+        let mut head_tags = options.webmentions.as_ref().map(Webmentions::link_tags).unwrap_or_default();
+        if rendering.meta_values.iter().any(|meta| meta.key == "noindex" && meta.value.trim() == "true") {
+            head_tags.push_str("<meta name=\"robots\" content=\"noindex\">\n");
+        }
+        if let Some(creator) = options.fediverse.as_ref().and_then(|_| rendering.variables.get("fediverse_creator")) {
+            head_tags.push_str(&Fediverse::meta_tag(creator));
+        }
+        // A template without a `<head>`, such as a feed, has nowhere for them.
+        if let Some(offset) = html_doc.find("</head>").filter(|_| !head_tags.is_empty()) {
+            html_doc.insert_str(offset, &head_tags);
+        }
+
+        if options.copy_images || options.image_sizes {
+            span!("images");
+            html_doc = copy_images(&html_doc, source, markdown_path, output_path, options)
+                .map_err(|e| format!("{} In markdown '{}'.", e, markdown_path.to_string_lossy()))?;
+        }
+        if options.single_file {
+            let mut dirs = [output_path, markdown_path, template_path]
+                .iter()
+                .map(|path| path.parent().unwrap_or(Path::new("")).to_path_buf())
+                .collect::<Vec<PathBuf>>();
+            dirs.extend(options.output_dir.iter().chain(&options.asset_dirs).cloned());
+            html_doc = inline_assets(&html_doc, &dirs);
+        }
+        options.hooks.after_render(markdown_path, &mut html_doc)
+            .map_err(|e| format!("{} In markdown '{}'.", e, markdown_path.to_string_lossy()))?;
+        rendering.warnings.extend(images_without_alt(&html_doc).into_iter().map(Warning::MissingAltText));
+        rendering.output = html_doc;
+        Ok::<(), Box<dyn Error>>(())
+    }, in_markdown)?;
+
+    Ok(Document {
+        html: rendering.output,
+        #[cfg(feature = "json")]
+        markdown_path: markdown_path.to_owned(),
+        #[cfg(feature = "json")]
+        title: rendering.variables.get("title").cloned().unwrap_or_default(),
+        #[cfg(feature = "json")]
+        meta_values: rendering.meta_values,
+        unused_variables,
+        missing_variables,
+        warnings: rendering.warnings,
+    })
+}
+
+/// A Markdown that has been rendered into a template, see [`render_document`].
+pub(crate) struct Document {
+    pub(crate) html: String,
+    #[cfg(feature = "json")]
+    pub(crate) markdown_path: PathBuf,
+    #[cfg(feature = "json")]
+    pub(crate) title: String,
+    #[cfg(feature = "json")]
+    pub(crate) meta_values: Vec<Meta>,
+    pub(crate) unused_variables: Vec<String>,
+    pub(crate) missing_variables: Vec<String>,
+    /// The warnings of the filters and images, see [`Warning`].
+    pub(crate) warnings: Vec<Warning>,
+}
+
+impl Document {
+    /// Where the document is written in the `format`, rather than as the
+    /// `output_path` of the template.
+    pub(crate) fn path(output_path: &Path, format: OutputFormat) -> PathBuf {
+        match format {
+            OutputFormat::Html => output_path.to_owned(),
+            #[cfg(feature = "json")]
+            OutputFormat::Json => output_path.with_extension("json"),
+        }
+    }
+
+    /// The contents of the document in the `format`.
+    pub(crate) fn contents(self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Html => self.html,
+            #[cfg(feature = "json")]
+            OutputFormat::Json => {
+                let meta = self.meta_values
+                    .into_iter()
+                    .map(|meta| (meta.key, serde_json::Value::String(meta.value)))
+                    .collect::<serde_json::Map<String, serde_json::Value>>();
+                serde_json::to_string_pretty(&serde_json::json!({
+                    "path": self.markdown_path.to_string_lossy().replace('\\', "/"),
+                    "title": self.title,
+                    "meta": meta,
+                    "html": self.html,
+                })).unwrap_or_default()
+            },
+        }
+    }
+}
+
+/// Read the template at `template_path`, applying its includes, features,
+/// fragments, filter aliases, and filter defaults, then parse its placeholders.
+pub(crate) fn load_template(template_path: &Path, options: &BatchOptions) -> Result<Template, Box<dyn Error>> {
+    span!("parse");
+    let source = read_file(template_path)?;
+    // Includes, like fragments, are relative to the template.
+    let template = apply_includes(&apply_whitespace_control(&options.syntax.translate(&source)), template_path.parent().unwrap_or(Path::new("")), &options.syntax)
+        .and_then(|template| apply_features(&template, &options.features))
+        .map_err(|e| format!("{} In template '{}'.", e, template_path.to_string_lossy()))?;
+    // Fragments are relative to the template.
+    let template = apply_fragments(&template, template_path.parent().unwrap_or(Path::new("")))
+        .map(|template| apply_filter_defaults(&apply_filter_aliases(&template, &options.filter_aliases), &options.filter_defaults))
+        .map_err(|e| format!("{} In template '{}'.", e, template_path.to_string_lossy()))?;
+    if options.recover {
+        return Ok(Template::recover(&template));
+    }
+    Template::new(&template).map_err(|e| match e.downcast::<ParseError>() {
+        Ok(error) => Box::new(syntax_error(template_path, &source, &template, *error, &options.syntax)),
+        Err(e) => e,
+    })
+}
+
+/// A [`SourceError`] for an `error` of a `template`, which was read from
+/// `source`.
+///
+/// When the `source` is written with another [`Syntax`], its errors are
+/// reported as it was written, rather than within its translation, unless the
+/// `error` only came from applying its includes, features, or fragments.
+fn syntax_error(template_path: &Path, source: &str, template: &str, error: ParseError, syntax: &Syntax) -> SourceError {
+    if !syntax.is_default() {
+        // The whitespace control is blanked, rather than applied, so that the
+        // offsets are those of the translation.
+        let translated = syntax.translate(source).replace("{{-", "{{ ").replace("-}}", " }}");
+        let mut errors = Template::recover(&translated).errors().iter()
+            .map(|error| syntax.locate(source, error.clone()))
+            .collect::<Vec<ParseError>>();
+        if !errors.is_empty() {
+            let error = errors.remove(0);
+            return SourceError { path: template_path.to_owned(), source: source.to_string(), error, others: errors };
+        }
+    }
+    SourceError::new(template_path.to_owned(), template, error)
+}
+
+/// The template that a Markdown chose for itself, with `template` in its meta
+/// section, or that of its `profile`, within the
+/// [`BatchOptions::templates_dir`], or otherwise beside the `template_path`.
+///
+/// A Markdown with a profile always has a template, which is the
+/// `template_path` if neither chose one.
+pub(crate) fn chosen_template(markdown: &str, template_path: &Path, profile: Option<&Profile>, options: &BatchOptions) -> Option<PathBuf> {
+    let meta_values = parse_meta_section(Span::new(markdown)).map(|(_, meta_values)| meta_values).unwrap_or_default();
+    let first = |meta: &Meta| split_meta_list(&meta.value).into_iter().next().unwrap_or_default();
+    let name = match meta_values.iter().find(|meta| meta.key == "template").map(|meta| meta.value.trim().to_string()).or_else(|| meta_values.iter().find(|meta| meta.key == "templates").map(first)) {
+        Some(name) => PathBuf::from(name),
+        None => match profile? {
+            Profile { template: Some(template), .. } => template.to_owned(),
+            Profile { template: None, .. } => return Some(template_path.to_owned()),
+        },
+    };
+    let templates_dir = options.templates_dir.as_deref().unwrap_or(template_path.parent().unwrap_or(Path::new("")));
+    Some(templates_dir.join(name))
+}
+
+/// The templates after the first of `templates` in the meta section of a
+/// Markdown, which are rendered beside its output, found as [`chosen_template`]
+/// finds its template.
+pub(crate) fn chosen_variants(markdown: &str, template_path: &Path, options: &BatchOptions) -> Vec<PathBuf> {
+    let meta_values = parse_meta_section(Span::new(markdown)).map(|(_, meta_values)| meta_values).unwrap_or_default();
+    let Some(meta) = meta_values.iter().find(|meta| meta.key == "templates") else {
+        return vec![];
+    };
+    // With a `template`, every one of the `templates` is a variant.
+    let skip = usize::from(!meta_values.iter().any(|meta| meta.key == "template"));
+    let templates_dir = options.templates_dir.as_deref().unwrap_or(template_path.parent().unwrap_or(Path::new("")));
+    split_meta_list(&meta.value)
+        .into_iter()
+        .skip(skip)
+        .map(|name| templates_dir.join(name))
+        .collect()
+}
+
+/// Where a variant of a Markdown is written, which is beside its `output_path`
+/// with the name of the `variant_path` before its extension, such as
+/// `hello.amp.html`.
+pub(crate) fn variant_output_path(output_path: &Path, variant_path: &Path) -> PathBuf {
+    output_path.with_file_name(format!(
+        "{}.{}.{}",
+        output_path.file_stem().unwrap_or_default().to_string_lossy(),
+        variant_path.file_stem().unwrap_or_default().to_string_lossy(),
+        variant_path.extension().unwrap_or(OsStr::new("html")).to_string_lossy(),
+    ))
+}
+
+/// The profile that a Markdown chose, with `profile` in its meta section, from
+/// the [`BatchOptions::profiles`], along with its name.
+pub(crate) fn chosen_profile<'a>(markdown_path: &Path, markdown: &str, options: &'a BatchOptions) -> Result<Option<(&'a str, &'a Profile)>, Box<dyn Error>> {
+    let Ok((_, meta_values)) = parse_meta_section(Span::new(markdown)) else {
+        return Ok(None);
+    };
+    let Some(name) = meta_values.iter().find(|meta| meta.key == "profile").map(|meta| meta.value.trim()) else {
+        return Ok(None);
+    };
+    match options.profiles.get_key_value(name) {
+        Some((name, profile)) => Ok(Some((name.as_str(), profile))),
+        None => Err(format!("The profile '{}' of markdown '{}' does not exist.", name, markdown_path.to_string_lossy()))?,
+    }
+}
+
+/// Get the SHA-256 of some bytes, as a lowercase hexadecimal string.
+///
+/// # Example
+/// ```rust
+/// use blogs_md_easy::hash_hex;
+///
+/// assert_eq!(hash_hex(b"Hello, World!"), "dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f");
+/// ```
+pub fn hash_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// The title of a Markdown, and where it came from.
+///
+/// This is the `title` in its meta section or its heading, whichever comes
+/// first by `precedence`, and otherwise the file name of `markdown_path`, so
+/// that a Markdown without either still has a `£title`.
+///
+/// # Example
+/// ```rust
+/// use std::path::Path;
+/// use blogs_md_easy::{resolve_title, TitlePrecedence, TitleSource};
+///
+/// let markdown = ":meta\ntitle = Meta Title\n:meta\nMarkdown Title\n==============";
+/// assert_eq!(resolve_title(markdown, None, TitlePrecedence::Meta), Some(("Meta Title".to_string(), TitleSource::Meta)));
+/// assert_eq!(resolve_title(markdown, None, TitlePrecedence::Heading), Some(("Markdown Title".to_string(), TitleSource::Heading)));
+///
+/// let path = Path::new("posts/my-first_post.md");
+/// assert_eq!(resolve_title("No heading.", Some(path), TitlePrecedence::Meta), Some(("My first post".to_string(), TitleSource::Filename)));
+/// assert_eq!(resolve_title("No heading.", None, TitlePrecedence::Meta), None);
+/// ```
+pub fn resolve_title(markdown: &str, markdown_path: Option<&Path>, precedence: TitlePrecedence) -> Option<(String, TitleSource)> {
+    let (content, meta_values) = parse_meta_section(Span::new(markdown)).unwrap_or((Span::new(markdown), Vec::new()));
+    find_title(content, &meta_values, markdown_path, precedence)
+}
+
+/// The title of the `content` of a Markdown that has already been parsed, see
+/// [`resolve_title`].
+pub(crate) fn find_title(content: Span, meta_values: &[Meta], markdown_path: Option<&Path>, precedence: TitlePrecedence) -> Option<(String, TitleSource)> {
+    // A key that is repeated takes its last value, as with the variables.
+    let meta = meta_values.iter().rev().find(|meta| meta.key == "title").map(|meta| (meta.value.to_owned(), TitleSource::Meta));
+    let heading = parse_title(content).ok().map(|(_, heading)| (heading.to_string(), TitleSource::Heading));
+    let (first, second) = match precedence {
+        TitlePrecedence::Meta => (meta, heading),
+        TitlePrecedence::Heading => (heading, meta),
+    };
+
+    first.or(second).or_else(|| {
+        let stem = markdown_path?.file_stem()?.to_string_lossy().to_string();
+        let words = stem.split(['-', '_', ' ']).filter(|word| !word.is_empty()).collect::<Vec<&str>>().join(" ");
+        let mut chars = words.chars();
+        chars.next().map(|first| (first.to_uppercase().collect::<String>() + chars.as_str(), TitleSource::Filename))
+    })
+}
+
+/// Creates a HashMap of key-value pairs from meta values.
+///
+/// # Arguments
+/// * `markdown` - A LocatedSpan of the markdown file.
+/// * `meta_values` - An optional vector of Meta values.
+///
+/// # Returns
+/// Convert the meta_values into a [`HashMap`], then parse the title and content
+/// from the markdown file.
+///
+/// A `content_hash` variable is also created, which is the SHA-256 of the
+/// `content`, as Markdown. This is useful as an ETag, or to detect when a post
+/// has changed.
+///
+/// The heading of the markdown file is available as `heading`, and is used as
+/// the `title` unless the meta section declares one, see
+/// [`create_variables_with_precedence`].
+///
+/// An `excerpt` variable is the first paragraph of the `content`, after the
+/// heading, or everything before a `<!-- more -->` marker, unless the meta
+/// section declares one. It is still Markdown, for a description or a card.
+///
+/// The `word_count` is the number of words after the heading, and the
+/// `reading_time` is how many minutes they take to read, rounded up, at 200
+/// words per minute, see [`BatchOptions::words_per_minute`].
+///
+/// Each `##` section of the `content` is also available as `section.name`,
+/// where the name is the slug of its heading, so that parts of a Markdown can
+/// be placed in different regions of a template. The section does not include
+/// its heading, which is `section.name.heading`, and a name that is repeated
+/// is numbered, such as `section.usage-1`.
+///
+/// A `description` variable is the `description` of the meta section, or the
+/// excerpt without its Markdown, or else the first 30 words of the `content`,
+/// so that description tags are never empty, see
+/// [`BatchOptions::description_from`].
+///
+/// # Example
+/// ```
+/// use blogs_md_easy::{create_variables, parse_meta_section, Span};
+///
+/// let markdown = Span::new(":meta\nauthor = John Doe\n:meta\n# Markdown title\nContent paragraph");
+/// let (markdown, meta_values) = parse_meta_section(markdown).unwrap_or((markdown, vec![]));
+/// let variables = create_variables(markdown, meta_values).expect("to create variables");
+/// assert_eq!(variables.get("title").unwrap(), "Markdown title");
+/// assert_eq!(variables.get("heading").unwrap(), "Markdown title");
+/// assert_eq!(variables.get("author").unwrap(), "John Doe");
+/// assert_eq!(variables.get("content").unwrap(), "# Markdown title\nContent paragraph");
+/// assert_eq!(variables.get("content_hash").unwrap().len(), 64);
+/// assert_eq!(variables.get("description").unwrap(), "Content paragraph");
+/// assert_eq!(variables.get("excerpt").unwrap(), "Content paragraph");
+/// assert_eq!(variables.get("word_count").unwrap(), "2");
+/// assert_eq!(variables.get("reading_time").unwrap(), "1");
+/// ```
+///
+/// A meta value can be another variable with filters, which is evaluated once
+/// here, rather than in every placeholder that uses it.
+/// ```
+/// use blogs_md_easy::{create_variables, parse_meta_section, Span};
+///
+/// let markdown = Span::new(":meta\nslug = £title | text = kebab-case\n:meta\n# Hello World");
+/// let (markdown, meta_values) = parse_meta_section(markdown).unwrap();
+/// let variables = create_variables(markdown, meta_values).expect("to create variables");
+/// assert_eq!(variables.get("slug").unwrap(), "hello-world");
+/// ```
+///
+/// Sections of the content.
+/// ```
+/// use blogs_md_easy::{create_variables, Span};
+///
+/// let markdown = Span::new("# Title\n## Getting Started\nRun it.\n## Usage\nUse it.");
+/// let variables = create_variables(markdown, vec![]).expect("to create variables");
+/// assert_eq!(variables.get("section.getting-started").unwrap(), "Run it.");
+/// assert_eq!(variables.get("section.getting-started.heading").unwrap(), "Getting Started");
+/// assert_eq!(variables.get("section.usage").unwrap(), "Use it.");
+/// ```
+pub fn create_variables(markdown: Span, meta_values: Vec<Meta>) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    create_variables_cached(markdown, meta_values, None, TitlePrecedence::Meta, false, WORDS_PER_MINUTE, &FilterCache::new())
+}
+
+/// Creates the variables in the same way as [`create_variables`], but with
+/// `precedence` deciding whether the meta section's `title` or the heading is
+/// used as the `title` when the Markdown has both.
+///
+/// # Example
+/// ```
+/// use blogs_md_easy::{create_variables_with_precedence, parse_meta_section, Span, TitlePrecedence};
+///
+/// let markdown = Span::new(":meta\ntitle = Meta title\n:meta\n# Markdown title\nContent paragraph");
+/// let (markdown, meta_values) = parse_meta_section(markdown).unwrap();
+///
+/// let variables = create_variables_with_precedence(markdown, meta_values.clone(), TitlePrecedence::Meta).unwrap();
+/// assert_eq!(variables.get("title").unwrap(), "Meta title");
+/// assert_eq!(variables.get("heading").unwrap(), "Markdown title");
+///
+/// let variables = create_variables_with_precedence(markdown, meta_values, TitlePrecedence::Heading).unwrap();
+/// assert_eq!(variables.get("title").unwrap(), "Markdown title");
+/// ```
+pub fn create_variables_with_precedence(markdown: Span, meta_values: Vec<Meta>, precedence: TitlePrecedence) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    create_variables_cached(markdown, meta_values, None, precedence, false, WORDS_PER_MINUTE, &FilterCache::new())
+}
+
+/// How many words a reader gets through each minute, unless
+/// [`BatchOptions::words_per_minute`] is set.
+const WORDS_PER_MINUTE: u32 = 200;
+
+/// The value of each meta value that is another variable with filters, such as
+/// `slug = £title | text = kebab-case`, in the order that they are declared,
+/// see [`parse_meta_filters`].
+///
+/// The `variable` of that name is filtered, unless a meta value before it has
+/// been derived with that name.
+pub(crate) fn derive_meta_values<'a>(meta_values: &[Meta], variable: impl Fn(&str) -> Option<&'a String>, cache: &FilterCache) -> Result<Vec<(String, String)>, RenderError> {
+    let mut derived: Vec<(String, String)> = Vec::new();
+    for meta in meta_values {
+        let Ok((_, (name, filters))) = parse_meta_filters(Span::new(&meta.value)) else {
+            continue;
+        };
+        let value = derived.iter().rev().find(|(key, _)| key == name.fragment()).map(|(_, value)| value.to_owned())
+            .or_else(|| variable(name.fragment()).cloned())
+            .ok_or(RenderError::MissingVariable(name.to_string()))?;
+        let value = filters.iter().try_fold(Cow::Owned(value), |value, filter| cache.render(value, filter)).map_err(RenderError::Filter)?;
+        derived.push((meta.key.to_owned(), value.into_owned()));
+    }
+
+    Ok(derived)
+}
+
+/// Create the variables, rendering the derived meta values through the
+/// `cache`.
+///
+/// The `title` falls back to the file name of the `markdown_path`, when there
+/// is one, see [`resolve_title`]. With `remove_heading`, the heading is removed from the start of the
+/// `content`. The `reading_time` is at `words_per_minute`.
+fn create_variables_cached(markdown: Span, meta_values: Vec<Meta>, markdown_path: Option<&Path>, precedence: TitlePrecedence, remove_heading: bool, words_per_minute: u32, cache: &FilterCache) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    let mut variables: HashMap<String, String> = meta_values
+        .iter()
+        .map(|meta| (meta.key.to_owned(), meta.value.to_owned()))
+        .collect();
+
+    // Make sure that we have a title and content variable.
+    let heading = parse_title(markdown).ok().map(|(_, heading)| heading.to_string());
+    if let Some(heading) = &heading {
+        variables.entry("heading".to_string()).or_insert(heading.to_owned());
+    }
+    let (title, _) = find_title(markdown, &meta_values, markdown_path, precedence).ok_or(RenderError::MissingTitle)?;
+    variables.insert("title".to_string(), title);
+    if !variables.contains_key("content") {
+        let content = match parse_title(markdown) {
+            Ok((remaining, _)) if remove_heading => remaining.fragment().trim().to_string(),
+            _ => markdown.fragment().trim().to_string(),
+        };
+        variables.insert("content".to_string(), content);
+    }
+    if !variables.contains_key("excerpt") {
+        let body = parse_title(markdown).map_or(markdown, |(remaining, _)| remaining);
+        variables.insert("excerpt".to_string(), excerpt(body.fragment()));
+    }
+    let body = parse_title(markdown).map_or(markdown, |(remaining, _)| remaining);
+    let word_count = body.fragment().split_whitespace().count();
+    variables.entry("word_count".to_string()).or_insert(word_count.to_string());
+    let reading_time = word_count.div_ceil(words_per_minute.max(1) as usize);
+    variables.entry("reading_time".to_string()).or_insert(reading_time.to_string());
+    if !variables.contains_key("content_hash") {
+        // The Markdown is hashed, rather than rendered only to be hashed.
+        let content_hash = hash_hex(variables.get("content").map(String::as_str).unwrap_or_default().as_bytes());
+        variables.insert("content_hash".to_string(), content_hash);
+    }
+    for (slug, heading, section) in split_sections(&variables.get("content").cloned().unwrap_or_default()) {
+        variables.entry(format!("section.{}.heading", slug)).or_insert(heading);
+        variables.entry(format!("section.{}", slug)).or_insert(section);
+    }
+    let derived = derive_meta_values(&meta_values, |name| variables.get(name), cache)?;
+    variables.extend(derived);
+    if let Some(description) = description(&[], &meta_values, |name| variables.get(name)) {
+        variables.insert("description".to_string(), description);
+    }
+
+    Ok(variables)
+}
+
+/// The `description` of a Markdown, from the first of the `sources` that has
+/// one, where `variable` is each of its variables, and `meta_values` are those
+/// of its meta section, see
+/// [`BatchOptions::description_from`].
+pub(crate) fn description<'a>(sources: &[DescriptionSource], meta_values: &[Meta], variable: impl Fn(&str) -> Option<&'a String>) -> Option<String> {
+    let sources = if sources.is_empty() { &DESCRIPTION_FROM[..] } else { sources };
+    sources.iter().find_map(|source| {
+        let description = match source {
+            // The meta value may be derived, such as from the `£title`.
+            DescriptionSource::Meta => meta_values.iter().any(|meta| meta.key == "description")
+                .then(|| variable("description").map(|description| description.trim().to_string()))
+                .flatten(),
+            DescriptionSource::Excerpt => variable("excerpt").map(|excerpt| strip_markdown(excerpt)),
+            DescriptionSource::Content => variable("content")
+                .and_then(|content| render_filter(strip_markdown(content), &Filter::TruncateWords { words: 30, trail: "…".to_string() }).ok())
+                .map(Cow::into_owned),
+        };
+        description.filter(|description| !description.is_empty())
+    })
+}
+
+/// The text of some Markdown on a single line, without its headings, code
+/// blocks, tags, or the URLs of its links.
+fn strip_markdown(markdown: &str) -> String {
+    let mut fence: Option<&str> = None;
+    let text = markdown
+        .lines()
+        .map(str::trim_start)
+        .filter(|line| {
+            if let Some(marker) = fence {
+                if line.starts_with(marker) {
+                    fence = None;
+                }
+                return false;
+            }
+            if let Some(marker) = ["```", "~~~"].into_iter().find(|marker| line.starts_with(marker)) {
+                fence = Some(marker);
+                return false;
+            }
+            !line.starts_with('#')
+        })
+        .map(|mut line| {
+            // A marker is only a list or quote when whitespace follows it, unlike `-5°`.
+            while let Some(rest) = line.strip_prefix(['>', '-', '+']).filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace)) {
+                line = rest.trim_start();
+            }
+            line
+        })
+        .collect::<Vec<&str>>()
+        .join(" ");
+
+    // Links and images keep their text, but not where they point.
+    let text = strip_tags(&text);
+    let mut plain = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find("](") {
+        plain.push_str(&rest[..start]);
+        rest = rest[start + 2..].find(')').map_or("", |end| &rest[start + 3 + end..]);
+    }
+    plain.push_str(rest);
+
+    plain.replace("![", "").replace("~~", "").replace(['[', '*', '`'], "").split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Remove every HTML tag, leaving only the text.
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {},
+        }
+    }
+    text
+}
@@ -0,0 +1,70 @@
+//! A [`wasm-bindgen`](https://docs.rs/wasm-bindgen) wrapper of the render API,
+//! so that the same templates can be previewed in a browser, built for
+//! `wasm32-unknown-unknown`.
+//!
+//! Nothing is read from, or written to, disk, so each function takes the
+//! template and Markdown themselves.
+//! ```js
+//! import init, { render, renderWithWarnings } from "./pkg/blogs_md_easy.js";
+//!
+//! await init();
+//! preview.innerHTML = render(template, editor.value);
+//! for (const warning of renderWithWarnings(template, editor.value).warnings) {
+//!     console.warn(warning);
+//! }
+//! ```
+//!
+//! Requires the `wasm` feature.
+
+use wasm_bindgen::prelude::*;
+use crate::render::{self, Warning};
+
+/// A Markdown that has been rendered into a template, along with anything
+/// that is likely to be a mistake, see [`renderWithWarnings`](render_with_warnings).
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Preview {
+    html: String,
+    warnings: Vec<String>,
+}
+
+#[wasm_bindgen]
+impl Preview {
+    /// The rendered HTML.
+    #[wasm_bindgen(getter)]
+    pub fn html(&self) -> String {
+        self.html.to_owned()
+    }
+
+    /// Each warning, as a message such as `Unused variable 'draft'`.
+    #[wasm_bindgen(getter)]
+    pub fn warnings(&self) -> Vec<String> {
+        self.warnings.to_owned()
+    }
+}
+
+/// Render a Markdown into a template, see [`render::render`].
+///
+/// Throws an `Error` with the message of the [`RenderError`](crate::render::RenderError).
+#[wasm_bindgen]
+pub fn render(template: &str, markdown: &str) -> Result<String, JsError> {
+    render::render(template, markdown).map_err(|error| JsError::new(&error.to_string()))
+}
+
+/// Render a Markdown into a template, along with its warnings, see
+/// [`render::render_with_warnings`].
+#[wasm_bindgen(js_name = renderWithWarnings)]
+pub fn render_with_warnings(template: &str, markdown: &str) -> Result<Preview, JsError> {
+    let (html, warnings) = render::render_with_warnings(template, markdown).map_err(|error| JsError::new(&error.to_string()))?;
+    Ok(Preview {
+        html,
+        warnings: warnings.iter().map(Warning::to_string).collect(),
+    })
+}
+
+/// Rewrite every placeholder of a template into its canonical text, see
+/// [`render::format_template`].
+#[wasm_bindgen(js_name = formatTemplate)]
+pub fn format_template(template: &str) -> Result<String, JsError> {
+    render::format_template(template).map_err(|error| JsError::new(&error.to_string()))
+}
@@ -1,6 +1,14 @@
-use std::collections::HashMap;
-
-use blogs_md_easy::{create_variables, parse_filter, parse_filter_args, parse_filter_key_value, parse_filters, parse_meta_comment, parse_meta_key_value, parse_meta_section, parse_placeholder, parse_placeholder_locations, parse_title, parse_until_eol, parse_variable, render_filter, replace_substring, Filter, Marker, Meta, Selection, Span, TextCase};
+use std::{collections::{BTreeMap, HashMap}, path::{Path, PathBuf}};
+
+use blogs_md_easy::{error::{BatchError, SourceError}, AnnotatedMeta, apply_features, apply_filter_aliases, apply_loops, apply_fragments, apply_includes, apply_whitespace_control, collect_posts, create_variables, decode_file, edit_meta, embed_meta, extract_meta, extract_title, is_draft, is_published_by, join_meta_list, lint, load_env_variables, format_meta_section, format_template, get_output_path, hash_hex, parse_filter, parse_filter_args, parse_filter_key_value, parse_filters, parse_meta_comment, meta_errors, new_post, parse_meta_key_value, parse_meta_section_with_comments, parse_meta_section, parse_placeholder, parse_placeholder_locations, parse_title, parse_until_eol, parse_variable, posts_to_ics, posts_to_rss, recover_placeholder_locations, render, render_archetype, render_archives, render_batch, render_changelog, render_filter, render_filters, render_index, render_markdown, render_with_warnings, replace_substring, resolve_title, sample_markdown, split_meta_list, unix_to_date, validate_template, write_if_changed, Alternate, BatchOptions, BuildCache, CancellationToken, ChangelogGroup, DateSource, DescriptionSource, EmbedMeta, Error, Fediverse, Filter, FilterCache, FilterError, FilterChange, Glossary, HashAlgorithm, Hooks, KeyUsage, ListMarkup, LivePreview, MetaRule, MetaSchema, MetaType, Meta, MetaEdit, MissingVariablePolicy, OutputManifest, Pipeline, Plugin, Post, Profile, RenderError, Rendering, Span, Stage, Shortcodes, Stats, Step, Syntax, Template, TemplateCoverage, TemplateValidation, TextCase, TitlePrecedence, TitleSource, Warning, Webmention, Webmentions};
+#[cfg(feature = "markdown")]
+use blogs_md_easy::{Marker, Selection};
+#[cfg(feature = "compression")]
+use blogs_md_easy::Compression;
+#[cfg(feature = "json")]
+use blogs_md_easy::{posts_to_search_index, progress_to_manifest, OutputFormat};
+#[cfg(feature = "toml")]
+use blogs_md_easy::{apply_filter_defaults, init_project, install_theme, load_variables, Blogroll, Config, Theme};
 use nom::combinator::opt;
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -20,6 +28,18 @@ fn can_parse_until_eol() {
     assert_eq!(input.fragment(), &"");
 }
 
+#[test]
+fn can_parse_until_eol_with_crlf() {
+    let input = Span::new("This is the first line\r\nThis is the second line.\r\n");
+    let (input, line) = parse_until_eol(input).expect("to parse line");
+    assert_eq!(line.fragment(), &"This is the first line");
+    assert_eq!(input.fragment(), &"This is the second line.\r\n");
+
+    let (input, line) = parse_until_eol(input).expect("to parse line");
+    assert_eq!(line.fragment(), &"This is the second line.");
+    assert_eq!(input.fragment(), &"");
+}
+
 #[test]
 fn can_parse_variable() {
     let input = Span::new("£content }}");
@@ -133,6 +153,18 @@ fn can_parse_html_title() {
     assert_eq!(input.fragment(), &"\nMy content");
 }
 
+#[test]
+fn can_parse_setext_title() {
+    let markdown = Span::new("My Title\r\n======  \r\nMy content");
+    let (input, title) = parse_title(markdown).expect("to parse title");
+    assert_eq!(title.fragment(), &"My Title");
+    assert_eq!(input.fragment(), &"\r\nMy content");
+
+    // A line of `-` is a second level heading.
+    assert!(parse_title(Span::new("My Subtitle\n---\nMy content")).is_err());
+    assert!(parse_title(Span::new("My content\n== is not a heading")).is_err());
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Meta Section
 
@@ -210,6 +242,153 @@ fn can_parse_metadata_php() {
     assert_eq!(input.fragment(), &"# Markdown title\nThis is my content");
 }
 
+#[test]
+fn can_parse_meta_section_with_crlf() {
+    let input = Span::new(":meta\r\n// A comment\r\ntitle = My Title\r\nfooter = \"Multi\r\nline\"\r\n:meta\r\n# Markdown Title\r\nContent");
+    let (input, meta) = parse_meta_section(input).expect("to parse meta section");
+    assert_eq!(meta, vec![
+        Meta::new("title", "My Title"),
+        Meta::new("footer", "Multi\nline"),
+    ]);
+    assert_eq!(input.fragment(), &"# Markdown Title\r\nContent");
+
+    let (_, title) = parse_title(input).expect("to parse title");
+    assert_eq!(title.fragment(), &"Markdown Title");
+
+    let (_, comment) = parse_meta_comment(Span::new("# A comment\r\n")).expect("to parse comment");
+    assert_eq!(comment.fragment(), &"A comment");
+}
+
+#[test]
+fn can_parse_meta_section_with_heredoc() {
+    let input = Span::new(":meta\ntitle = My Title\n  description = <<END\n    Say \"hello\",\r\n\r\n    :meta\n    and goodbye.\n  END\nauthor = John Doe\n:meta\n# Markdown Title");
+    let (input, meta) = parse_meta_section(input).expect("to parse meta section");
+    assert_eq!(meta, vec![
+        Meta::new("title", "My Title"),
+        Meta::new("description", "Say \"hello\",\n\n:meta\nand goodbye."),
+        Meta::new("author", "John Doe"),
+    ]);
+    assert_eq!(input.fragment(), &"# Markdown Title");
+    // Values that would need escaping are written back as a heredoc.
+    assert_eq!(parse_meta_key_value(Span::new(&meta[1].to_string())).expect("to parse heredoc").1, meta[1]);
+
+    let input = Span::new(":meta\ntitle = My Title\ndescription = <<END\nNever closed\n:meta\n# Markdown Title");
+    let Err(nom::Err::Error(Error::Meta(diagnostic))) = parse_meta_section(input) else {
+        panic!("expected a meta error");
+    };
+    assert_eq!(diagnostic.line, 3);
+    assert_eq!(diagnostic.message, "expected the heredoc to be closed by a line of `END`");
+    assert_eq!(meta_errors(input).len(), 1);
+}
+
+#[test]
+fn can_parse_meta_section_with_annotated_comments() {
+    let input = Span::new("<meta>\r\n  // The author.\r\n  $author = John Doe\r\n  summary = <<END\r\n  # Not a comment\r\n  END\r\n  # Describes the post,\r\n  #   for search engines.\r\n  description = Hello\r\n  # Left over\r\n</meta>\r\n# Markdown Title");
+    let (input, meta) = parse_meta_section_with_comments(input).expect("to parse meta section");
+    assert_eq!(meta, vec![
+        AnnotatedMeta { meta: Meta::new("author", "John Doe"), comments: vec!["The author.".to_string()] },
+        AnnotatedMeta { meta: Meta::new("summary", "# Not a comment"), comments: vec![] },
+        AnnotatedMeta { meta: Meta::new("description", "Hello"), comments: vec!["Describes the post,".to_string(), "for search engines.".to_string()] },
+    ]);
+    assert_eq!(input.fragment(), &"# Markdown Title");
+
+    // Sections without comments have the same keys as `parse_meta_section`.
+    let markdown = ":meta\ntitle = Hello\ntags = [rust, nom]\n:meta\n# Markdown Title";
+    let (_, meta) = parse_meta_section_with_comments(Span::new(markdown)).expect("to parse meta section");
+    let (_, meta_values) = parse_meta_section(Span::new(markdown)).expect("to parse meta section");
+    assert_eq!(meta, meta_values.into_iter().map(AnnotatedMeta::from).collect::<Vec<AnnotatedMeta>>());
+
+    assert!(parse_meta_section_with_comments(Span::new(":meta\nauthor John Doe\n:meta")).is_err());
+}
+
+#[test]
+#[cfg(feature = "yaml")]
+fn can_parse_meta_section_yaml() {
+    let input = Span::new("---\ntitle: \"Hello: World\"\ndraft: false\nweight: 3\nauthor:\n  name: John Doe\n  social:\n    github: johndoe\n---\n# Markdown title");
+    let (input, meta) = parse_meta_section(input).expect("to parse yaml");
+    assert_eq!(meta, vec![
+        Meta::new("title", "Hello: World"),
+        Meta::new("draft", "false"),
+        Meta::new("weight", "3"),
+        Meta::new("author.name", "John Doe"),
+        Meta::new("author.social.github", "johndoe"),
+    ]);
+    assert_eq!(input.fragment(), &"# Markdown title");
+
+    // Windows line endings work too.
+    let input = Span::new("---\r\ntitle: Hello\r\n---\r\n# Markdown title");
+    let (input, meta) = parse_meta_section(input).expect("to parse yaml");
+    assert_eq!(meta, vec![Meta::new("title", "Hello")]);
+    assert_eq!(input.fragment(), &"# Markdown title");
+
+    // Only a line of `---` on its own closes the section.
+    let input = Span::new("---\ntitle: Hello\n---foo: bar\n---  \n# Markdown title");
+    let (input, meta) = parse_meta_section(input).expect("to parse yaml");
+    assert_eq!(meta, vec![Meta::new("title", "Hello"), Meta::new("---foo", "bar")]);
+    assert_eq!(input.fragment(), &"# Markdown title");
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn can_parse_meta_section_toml() {
+    let input = Span::new("+++\ntitle = \"Hello, World\"\ndate = 2024-01-01T12:00:00Z\ndraft = true\n\n[extra]\nauthor = \"John Doe\"\n+++\n# Markdown title");
+    let (input, meta) = parse_meta_section(input).expect("to parse toml");
+    assert_eq!(meta, vec![
+        Meta::new("title", "Hello, World"),
+        Meta::new("date", "2024-01-01T12:00:00Z"),
+        Meta::new("draft", "true"),
+        Meta::new("extra.author", "John Doe"),
+    ]);
+    assert_eq!(input.fragment(), &"# Markdown title");
+
+    // Only a line of `+++` on its own closes the section.
+    let input = Span::new("+++\ntitle = \"\"\"\n+++ Hello\n\"\"\"\n+++\n# Markdown title");
+    let (input, meta) = parse_meta_section(input).expect("to parse toml");
+    assert_eq!(meta, vec![Meta::new("title", "+++ Hello\n")]);
+    assert_eq!(input.fragment(), &"# Markdown title");
+
+    // Invalid TOML is not a meta section.
+    let input = Span::new("+++\ntitle = Hello\n+++\n# Markdown title");
+    assert!(parse_meta_section(input).is_err());
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn can_parse_meta_section_json() {
+    let input = Span::new("<?meta-json\n{\n  \"title\": \"Hello, World\",\n  \"tags\": [\"rust\", \"nom\"],\n  \"seo\": { \"description\": \"A post.\" }\n}\n?>\n# Markdown title");
+    let (input, meta) = parse_meta_section(input).expect("to parse json");
+    assert_eq!(meta, vec![
+        Meta::new("title", "Hello, World"),
+        Meta::new("tags", "rust, nom"),
+        Meta::new("seo.description", "A post."),
+    ]);
+    assert_eq!(input.fragment(), &"# Markdown title");
+
+    let input = Span::new("```json meta\n{\"title\": \"Hello, World\"}\n```\n# Markdown title");
+    let (input, meta) = parse_meta_section(input).expect("to parse json");
+    assert_eq!(meta, vec![Meta::new("title", "Hello, World")]);
+    assert_eq!(input.fragment(), &"# Markdown title");
+
+    // Only objects can be meta sections.
+    let input = Span::new("<?meta-json\n[1, 2, 3]\n?>\n# Markdown title");
+    assert!(parse_meta_section(input).is_err());
+
+    // A regular JSON code block is content, not meta.
+    let input = Span::new("```json\n{\"title\": \"Hello, World\"}\n```\n");
+    assert!(parse_meta_section(input).is_err());
+}
+
+#[test]
+#[cfg(feature = "yaml")]
+fn cannot_parse_invalid_yaml() {
+    let input = Span::new("---\ntitle: [unclosed\n---\n# Markdown title");
+    assert!(parse_meta_section(input).is_err());
+
+    // A horizontal rule in the body is not frontmatter.
+    let input = Span::new("# Markdown title\n---\nContent");
+    assert!(parse_meta_section(input).is_err());
+}
+
 #[test]
 fn can_parse_when_no_meta_section() {
     let input = Span::new("# Markdown title\nThis is my content");
@@ -227,6 +406,67 @@ fn cannot_parse_mismatch_meta_tags() {
     assert_eq!(input.fragment(), &":meta\nauthor = John Doe\n</meta>");
 }
 
+#[test]
+fn can_locate_meta_section_errors() {
+    let input = Span::new("\n<meta>\ntitle = Hello\n\n  author = John Doe\n  tags\n</meta>\n# Markdown title");
+    let Err(nom::Err::Error(Error::Meta(diagnostic))) = parse_meta_section(input) else {
+        panic!("expected a meta error");
+    };
+    assert_eq!(diagnostic.line, 6);
+    assert_eq!(diagnostic.column, 3);
+    assert_eq!(diagnostic.message, "expected `key = value`, or the closing `</meta>`");
+    assert_eq!(diagnostic.snippet, "6 |   tags\n  |   ^");
+
+    // Without a meta section, there is nothing to report.
+    let input = Span::new("# Markdown title");
+    assert!(matches!(parse_meta_section(input), Err(nom::Err::Error(Error::Parse(_)))));
+
+    // A Markdown can start with a thematic break, an XML declaration, or a
+    // code block, none of which are meta sections.
+    for markdown in ["---\n\n# Hi\n\ntext", "+++\n# Hi", "<?xml version=\"1.0\"?>\n# Hi", "```json\n{}\n```\n# Hi"] {
+        assert!(matches!(parse_meta_section(Span::new(markdown)), Err(nom::Err::Error(Error::Parse(_)))), "{}", markdown);
+    }
+    // Their heading isn't first, so they are only missing a title.
+    assert!(matches!(render("{{ £content }}", "---\n\n# Hi\n\ntext"), Err(RenderError::MissingTitle)));
+    assert!(matches!(render("{{ £content }}", "<?xml version=\"1.0\"?>\n# Hi"), Err(RenderError::MissingTitle)));
+
+    // Whereas frontmatter that is closed is reported.
+    assert!(matches!(parse_meta_section(Span::new("+++\ntitle = \n+++\n# Hi")), Err(nom::Err::Error(Error::Meta(_)))));
+}
+
+#[test]
+fn can_locate_placeholder_errors() {
+    let Err(Error::Placeholder(diagnostic)) = parse_placeholder_locations(Span::new("<p>{{ £title | }}</p>")) else {
+        panic!("expected a placeholder error");
+    };
+    assert_eq!(diagnostic.message, "expected a filter name");
+    assert_eq!((diagnostic.line, diagnostic.column), (1, 16));
+
+    let Err(Error::Placeholder(diagnostic)) = parse_placeholder_locations(Span::new("<p>\n{{ £title }</p>")) else {
+        panic!("expected a placeholder error");
+    };
+    assert_eq!(diagnostic.message, "expected `}}` to close the placeholder");
+    assert_eq!((diagnostic.line, diagnostic.column), (2, 11));
+
+    // Placeholders can't be nested, whether the outer one has a variable or not.
+    for (template, column) in [("<p>{{ £title {{ £author }}</p>", 14), ("<p>{{ {{ £author }}</p>", 7), ("{{ £title | truncate = {{ £length }} }}", 24)] {
+        let Err(Error::Placeholder(diagnostic)) = parse_placeholder_locations(Span::new(template)) else {
+            panic!("expected a placeholder error");
+        };
+        assert_eq!(diagnostic.message, "expected `}}` to close the placeholder, which can't contain another");
+        assert_eq!((diagnostic.line, diagnostic.column, diagnostic.length), (1, column, 2));
+    }
+
+    // The nested placeholder is still found when recovering.
+    let (placeholders, errors) = recover_placeholder_locations(Span::new("<p>{{ £title {{ £author }}</p>{{ £date }}"));
+    assert_eq!(placeholders.iter().map(|placeholder| placeholder.name.as_str()).collect::<Vec<&str>>(), vec!["date", "author"]);
+    assert_eq!(errors.len(), 1);
+
+    // Braces that aren't a placeholder are left alone.
+    assert!(parse_placeholder_locations(Span::new("<script>let a = {{}};</script>")).is_ok());
+    assert!(parse_placeholder_locations(Span::new("{{ £title ?? \"{{ £none }}\" }}")).is_ok());
+}
+
 #[test]
 fn can_parse_meta_section_with_comments() {
     let input = Span::new(":meta\n// This is an author\nauthor = John Doe\n# This is the publish date\npublish_date = 2024-01-01\n:meta\n# Markdown title\nThis is my content");
@@ -286,6 +526,47 @@ fn can_parse_placeholders() {
     assert_eq!(placeholders[1].name, "title".to_string());
 }
 
+#[test]
+fn can_parse_placeholders_with_crlf() {
+    let template = "<title>{{ £title }}</title>\r\n<main>{{ £content }}</main>\r\n";
+    let placeholders = parse_placeholder_locations(Span::new(template)).expect("to parse placeholders");
+    assert_eq!(placeholders.len(), 2);
+
+    // Offsets must select exactly the placeholder, regardless of the `\r`.
+    for placeholder in &placeholders {
+        let selected = &template[placeholder.selection.start.offset..placeholder.selection.end.offset];
+        assert!(selected.starts_with("{{") && selected.ends_with("}}"));
+    }
+    assert_eq!(placeholders[0].selection.start.line, 2);
+    assert_eq!(placeholders[1].selection.start.line, 1);
+}
+
+#[test]
+fn can_write_outputs_of_windows_paths() {
+    let template = Path::new("template.html");
+    assert_eq!(get_output_path(template, Path::new(r"posts\2024\a.md"), None, false), PathBuf::from("posts/2024/a.html"));
+    assert_eq!(get_output_path(template, Path::new(r"posts\2024\a.md"), Some(Path::new("dist")), false), PathBuf::from("dist/a.html"));
+    assert_eq!(get_output_path(Path::new(r"amp\post.html"), Path::new(r"posts\2024\a.md"), Some(Path::new("dist")), true), PathBuf::from("dist/post-a.html"));
+
+    // The structure of an input directory is mirrored, whichever separator is
+    // used, and so are the separators of a permalink.
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-windows-paths");
+    std::fs::remove_dir_all(&output_dir).ok();
+    let template = std::env::temp_dir().join("blogs-md-easy-windows-paths.html");
+    std::fs::write(&template, "<h1>{{ £title }}</h1><p>{{ £filename }} {{ £path }}</p>").expect("to write template");
+    let markdowns = vec![(PathBuf::from(r"posts\2024\a.md"), ":meta\ndate = 2024-05-01\n:meta\n# A".to_string())];
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), input_dirs: vec![PathBuf::from("posts")], ..Default::default() };
+    let outputs = render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs, vec![output_dir.join("2024/a.html")]);
+    assert_eq!(std::fs::read_to_string(&outputs[0]).expect("to read output"), "<h1>A</h1><p>a.md posts/2024/a.md</p>");
+
+    let options = BatchOptions { permalink_pattern: Some(r"\:year\:slug\".to_string()), ..options };
+    let outputs = render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs, vec![output_dir.join("2024/a/index.html")]);
+    std::fs::remove_file(template).ok();
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
 #[test]
 fn can_parse_when_no_placeholders() {
     let input = Span::new("<h1>My Title\n<p>My content");
@@ -448,6 +729,23 @@ fn can_parse_filter_with_args_not_provided() {
     }
 }
 
+#[test]
+fn can_report_invalid_filter_args() {
+    let invalid = [
+        "truncate = characters: abc", "truncate = 300", "round = two", "first_words = -1", "last_words = many", "truncate_words = words: 1.5",
+        "markdown = tables: yes", "markdown = heading_offset: 256", "hash = length: long", "replace = find: a, limit: all", "text = shouting",
+    ];
+    for input in invalid {
+        assert!(parse_filter(Span::new(input)).is_err(), "{}", input);
+    }
+
+    let error = parse_placeholder_locations(Span::new("{{ £title | truncate = characters: abc }}")).expect_err("to be an invalid argument");
+    assert_eq!(error.diagnostic().message, "invalid filter argument 'abc'");
+    assert_eq!((error.diagnostic().column, error.diagnostic().length), (36, 3));
+    // A variable is only known once it has a value.
+    assert!(Template::new("{{ £title | truncate = characters: £length }}").is_ok());
+}
+
 #[test]
 fn can_parse_two_filters() {
     let input = Span::new("| truncate = characters: 20 | lowercase");
@@ -474,21 +772,41 @@ fn can_parse_all_filters() {
         (Filter::Ceil, parse_filter(Span::new("ceil")).expect("ceil").1),
         (Filter::Floor, parse_filter(Span::new("floor")).expect("floor").1),
         (Filter::Round { precision: 3 }, parse_filter(Span::new("round = 3")).expect("round").1),
+        (Filter::Add { value: "2".to_string() }, parse_filter(Span::new("add = 2")).expect("add").1),
+        (Filter::Subtract { value: "0".to_string() }, parse_filter(Span::new("subtract")).expect("subtract").1),
+        (Filter::Multiply { value: "1.2".to_string() }, parse_filter(Span::new("multiply = value: 1.2")).expect("multiply").1),
+        (Filter::Divide { value: "1".to_string() }, parse_filter(Span::new("divide")).expect("divide").1),
 
         // Lower case and uppercase have aliased filters...
         (Filter::Text { case: TextCase::Lower }, parse_filter(Span::new("lowercase")).expect("lower").1),
         (Filter::Text { case: TextCase::Upper }, parse_filter(Span::new("UPPERCASE")).expect("upper").1),
         // ...however, the others need to be provided to the Text filter.
         (Filter::Text { case: TextCase::Title }, parse_filter(Span::new("text = Title")).expect("title").1),
+        (Filter::Text { case: TextCase::Sentence }, parse_filter(Span::new("text = sentence")).expect("sentence").1),
         (Filter::Text { case: TextCase::Kebab }, parse_filter(Span::new("text = kebab-case")).expect("kebab").1),
         (Filter::Text { case: TextCase::Snake }, parse_filter(Span::new("text = snake_case")).expect("snake").1),
         (Filter::Text { case: TextCase::Pascal }, parse_filter(Span::new("text = PascalCase")).expect("pascal").1),
         (Filter::Text { case: TextCase::Camel }, parse_filter(Span::new("text = camelCase")).expect("camel").1),
         (Filter::Text { case: TextCase::Invert }, parse_filter(Span::new("text = invert")).expect("invert").1),
-        (Filter::Markdown, parse_filter(Span::new("markdown")).expect("markdown").1),
+        (Filter::Base64Decode, parse_filter(Span::new("base64_decode")).expect("base64_decode").1),
+        (Filter::Base64Encode, parse_filter(Span::new("base64_encode")).expect("base64_encode").1),
+        (Filter::Default { value: "".to_string() }, parse_filter(Span::new("default")).expect("default").1),
+        (Filter::Excerpt, parse_filter(Span::new("excerpt")).expect("excerpt").1),
+        (Filter::FirstWords { words: 10 }, parse_filter(Span::new("first_words")).expect("first_words").1),
+        (Filter::Hash { algo: HashAlgorithm::Md5, length: Some(8) }, parse_filter(Span::new("hash = algo: md5, length: 8")).expect("hash").1),
+        (Filter::If { then: "(draft)".to_string(), otherwise: "".to_string() }, parse_filter(Span::new("if = \"(draft)\"")).expect("if").1),
+        (Filter::Join { separator: ", ".to_string() }, parse_filter(Span::new("join")).expect("join").1),
+        (Filter::LastWords { words: 10 }, parse_filter(Span::new("last_words")).expect("last_words").1),
+        (Filter::Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 }, parse_filter(Span::new("markdown")).expect("markdown").1),
+        (Filter::MarkdownInline, parse_filter(Span::new("markdown_inline")).expect("markdown_inline").1),
         (Filter::Replace { find: "".to_string(), replacement: "".to_string(), limit: None }, parse_filter(Span::new("replace")).expect("replace").1),
         (Filter::Reverse, parse_filter(Span::new("reverse")).expect("reverse").1),
+        (Filter::Split { on: ",".to_string() }, parse_filter(Span::new("split")).expect("split").1),
+        (Filter::TimeAgo { now: "".to_string() }, parse_filter(Span::new("age")).expect("age").1),
+        (Filter::TimeAgo { now: "2024-05-04".to_string() }, parse_filter(Span::new("time_ago = 2024-05-04")).expect("time_ago").1),
         (Filter::Truncate { characters: 100, trail: "...".to_string() }, parse_filter(Span::new("truncate")).expect("truncate").1),
+        (Filter::TruncateWords { words: 10, trail: "...".to_string() }, parse_filter(Span::new("truncate_words")).expect("truncate_words").1),
+        (Filter::Wrap { before: "".to_string(), after: "".to_string() }, parse_filter(Span::new("wrap")).expect("wrap").1),
     ];
 
     // Maybe a bit verbose, but this ensures that the compiler will catch new
@@ -499,65 +817,274 @@ fn can_parse_all_filters() {
             Filter::Ceil => assert_eq!(expected_filter, Filter::Ceil),
             Filter::Floor => assert_eq!(expected_filter, Filter::Floor),
             Filter::Round { precision } => assert_eq!(expected_filter, Filter::Round { precision }),
+            Filter::Add { value } => assert_eq!(expected_filter, Filter::Add { value }),
+            Filter::Subtract { value } => assert_eq!(expected_filter, Filter::Subtract { value }),
+            Filter::Multiply { value } => assert_eq!(expected_filter, Filter::Multiply { value }),
+            Filter::Divide { value } => assert_eq!(expected_filter, Filter::Divide { value }),
 
             // String filters.
             Filter::Text { case: TextCase::Lower } => assert_eq!(expected_filter, Filter::Text { case: TextCase::Lower }),
             Filter::Text { case: TextCase::Upper } => assert_eq!(expected_filter, Filter::Text { case: TextCase::Upper }),
             Filter::Text { case: TextCase::Title } => assert_eq!(expected_filter, Filter::Text { case: TextCase::Title }),
+            Filter::Text { case: TextCase::Sentence } => assert_eq!(expected_filter, Filter::Text { case: TextCase::Sentence }),
             Filter::Text { case: TextCase::Kebab } => assert_eq!(expected_filter, Filter::Text { case: TextCase::Kebab }),
             Filter::Text { case: TextCase::Snake } => assert_eq!(expected_filter, Filter::Text { case: TextCase::Snake }),
             Filter::Text { case: TextCase::Pascal } => assert_eq!(expected_filter, Filter::Text { case: TextCase::Pascal }),
             Filter::Text { case: TextCase::Camel } => assert_eq!(expected_filter, Filter::Text { case: TextCase::Camel }),
             Filter::Text { case: TextCase::Invert } => assert_eq!(expected_filter, Filter::Text { case: TextCase::Invert }),
-            Filter::Markdown => assert_eq!(expected_filter, Filter::Markdown),
+            Filter::Base64Decode => assert_eq!(expected_filter, Filter::Base64Decode),
+            Filter::Base64Encode => assert_eq!(expected_filter, Filter::Base64Encode),
+            Filter::Default { value } => assert_eq!(expected_filter, Filter::Default { value }),
+            Filter::Excerpt => assert_eq!(expected_filter, Filter::Excerpt),
+            Filter::FirstWords { words } => assert_eq!(expected_filter, Filter::FirstWords { words }),
+            Filter::Hash { algo, length } => assert_eq!(expected_filter, Filter::Hash { algo, length }),
+            Filter::If { then, otherwise } => assert_eq!(expected_filter, Filter::If { then, otherwise }),
+            Filter::Join { separator } => assert_eq!(expected_filter, Filter::Join { separator }),
+            Filter::LastWords { words } => assert_eq!(expected_filter, Filter::LastWords { words }),
+            Filter::Markdown { tables, strikethrough, tasklists, footnotes, smart_punctuation, hard_breaks, html, heading_offset } => assert_eq!(expected_filter, Filter::Markdown { tables, strikethrough, tasklists, footnotes, smart_punctuation, hard_breaks, html, heading_offset }),
+            Filter::MarkdownInline => assert_eq!(expected_filter, Filter::MarkdownInline),
             Filter::Replace { find, replacement, limit } => assert_eq!(expected_filter, Filter::Replace { find, replacement, limit }),
             Filter::Reverse => assert_eq!(expected_filter, Filter::Reverse),
-            Filter::Truncate { characters, trail } => assert_eq!(expected_filter, Filter::Truncate { characters, trail })
+            Filter::Split { on } => assert_eq!(expected_filter, Filter::Split { on }),
+            Filter::TimeAgo { now } => assert_eq!(expected_filter, Filter::TimeAgo { now }),
+            Filter::Truncate { characters, trail } => assert_eq!(expected_filter, Filter::Truncate { characters, trail }),
+            Filter::TruncateWords { words, trail } => assert_eq!(expected_filter, Filter::TruncateWords { words, trail }),
+            Filter::Wrap { before, after } => assert_eq!(expected_filter, Filter::Wrap { before, after }),
         }
     }
 }
 
+#[test]
+fn filters_borrow_unchanged_values() {
+    use std::borrow::Cow;
+
+    let truncate = Filter::Truncate { characters: 10, trail: "...".to_string() };
+    assert!(matches!(render_filter("Short", &truncate).expect("to render filter"), Cow::Borrowed("Short")));
+    assert!(matches!(render_filter("Long enough to cut", &truncate).expect("to render filter"), Cow::Owned(output) if output == "Long enoug..."));
+
+    let replace = Filter::Replace { find: "World".to_string(), replacement: "Rust".to_string(), limit: None };
+    assert!(matches!(render_filter("Hello, you!", &replace).expect("to render filter"), Cow::Borrowed("Hello, you!")));
+    assert_eq!(render_filter("Hello, World!", &replace).expect("to render filter"), "Hello, Rust!");
+
+    let default = Filter::Default { value: "Anonymous".to_string() };
+    assert!(matches!(render_filter("John Doe", &default).expect("to render filter"), Cow::Borrowed("John Doe")));
+    let lowercase = Filter::Text { case: TextCase::Lower };
+    assert!(matches!(render_filter("hello, world!", &lowercase).expect("to render filter"), Cow::Borrowed("hello, world!")));
+    assert_eq!(render_filter("Hello, ǅ!", &lowercase).expect("to render filter"), "hello, ǆ!");
+    let uppercase = Filter::Text { case: TextCase::Upper };
+    assert!(matches!(render_filter("HELLO, 123", &uppercase).expect("to render filter"), Cow::Borrowed("HELLO, 123")));
+    assert_eq!(render_filter("STRASSE, straße", &uppercase).expect("to render filter"), "STRASSE, STRASSE");
+
+    // A chain of filters that change nothing borrows the value throughout.
+    let filters = [default, lowercase, truncate, replace];
+    assert!(matches!(render_filters("hello, you", &filters).expect("to render filters"), Cow::Borrowed("hello, you")));
+}
+
 #[test]
 fn filter_ceil_works() {
     let input = "-1.234".to_string();
-    let output = render_filter(input, &Filter::Ceil);
+    let output = render_filter(input, &Filter::Ceil).expect("to render filter");
     assert_eq!(output, "-1");
 
     let input = "1.234".to_string();
-    let output = render_filter(input, &Filter::Ceil);
+    let output = render_filter(input, &Filter::Ceil).expect("to render filter");
     assert_eq!(output, "2");
 }
 
 #[test]
 fn filter_floor_works() {
     let input = "-1.234".to_string();
-    let output = render_filter(input, &Filter::Floor);
+    let output = render_filter(input, &Filter::Floor).expect("to render filter");
     assert_eq!(output, "-2");
 
     let input = "1.234".to_string();
-    let output = render_filter(input, &Filter::Floor);
+    let output = render_filter(input, &Filter::Floor).expect("to render filter");
     assert_eq!(output, "1");
 }
 
 #[test]
 fn filter_round_works() {
     let input = "-1.23456789".to_string();
-    let output = render_filter(input, &Filter::Round { precision: 3 });
+    let output = render_filter(input, &Filter::Round { precision: 3 }).expect("to render filter");
     assert_eq!(output, "-1.235");
 
     let input = "1.23456789".to_string();
-    let output = render_filter(input, &Filter::Round { precision: 3 });
+    let output = render_filter(input, &Filter::Round { precision: 3 }).expect("to render filter");
     assert_eq!(output, "1.235");
 
     let input = "1.23456789".to_string();
-    let output = render_filter(input, &Filter::Round { precision: 0 });
+    let output = render_filter(input, &Filter::Round { precision: 0 }).expect("to render filter");
     assert_eq!(output, "1");
 
     let input = "9.87654321".to_string();
-    let output = render_filter(input, &Filter::Round { precision: 0 });
+    let output = render_filter(input, &Filter::Round { precision: 0 }).expect("to render filter");
     assert_eq!(output, "10");
 }
 
+#[test]
+fn filter_arithmetic_works() {
+    assert_eq!(render_filter("19.99", &Filter::Add { value: "0.01".to_string() }).expect("to render filter"), "20");
+    assert_eq!(render_filter("10", &Filter::Subtract { value: "12.5".to_string() }).expect("to render filter"), "-2.5");
+    assert_eq!(render_filter("7", &Filter::Multiply { value: "3".to_string() }).expect("to render filter"), "21");
+    assert_eq!(render_filter("1", &Filter::Divide { value: "8".to_string() }).expect("to render filter"), "0.125");
+    // A value that isn't a number is an error.
+    assert_eq!(
+        render_filter("free", &Filter::Add { value: "1".to_string() }),
+        Err(FilterError::NotANumber { filter: Filter::Add { value: "1".to_string() }, value: "free".to_string() }),
+    );
+
+    // They can be chained, such as to add tax to a price.
+    let template = Template::new("{{ £price | multiply = 1.2 | round = 2 }}").expect("to parse template");
+    let variables = HashMap::from([("price".to_string(), "19.99".to_string())]);
+    assert_eq!(template.render(&variables).expect("to render"), "23.99");
+}
+
+#[test]
+fn filter_errors_are_reported() {
+    let divide = Filter::Divide { value: "0".to_string() };
+    assert_eq!(render_filter("10", &divide), Err(FilterError::InvalidArgument { filter: divide.to_owned(), argument: "value".to_string(), value: "0".to_string() }));
+    let multiply = Filter::Multiply { value: "£rate".to_string() };
+    assert!(matches!(render_filter("10", &multiply), Err(FilterError::InvalidArgument { .. })));
+    // Arguments that aren't numbers are kept as they are written, to be reported.
+    let (_, divide) = parse_filter(Span::new("divide = 0")).expect("to parse divide");
+    assert_eq!(render_filter("10", &divide).unwrap_err().to_string(), "the divide filter was given an invalid value '0'");
+    let (_, add) = parse_filter(Span::new("add = abc")).expect("to parse add");
+    assert_eq!(add, Filter::Add { value: "abc".to_string() });
+    assert_eq!(render_filter("10", &add).unwrap_err().to_string(), "the add filter was given an invalid value 'abc'");
+    let error = render("{{ £title }} {{ £count | subtract = inf }}", ":meta\ncount = 2\n:meta\n# Hello").expect_err("to fail to render");
+    assert!(matches!(error, RenderError::Filter(FilterError::InvalidArgument { .. })), "{:?}", error);
+    let time_ago = Filter::TimeAgo { now: "tomorrow".to_string() };
+    assert_eq!(render_filter("2024-05-04", &time_ago).unwrap_err().to_string(), "the time_ago filter was given an invalid now 'tomorrow'");
+
+    // A chain stops at the first filter that fails.
+    let filters = [Filter::Text { case: TextCase::Upper }, Filter::Round { precision: 2 }];
+    assert_eq!(render_filters("free", &filters).unwrap_err().to_string(), "the round filter expected a number, but was given 'FREE'");
+
+    // The placeholder of the filter is pointed to.
+    let template = Template::new("<p>{{ £price | ceil }}</p>").expect("to parse template");
+    let variables = HashMap::from([("price".to_string(), "free".to_string())]);
+    let error = template.render(&variables).expect_err("to fail to render");
+    assert!(error.to_string().contains("the ceil filter expected a number, but was given 'free', in variable 'price'"), "{}", error);
+
+    let error = render("{{ £title }} {{ £price | floor }}", ":meta\nprice = free\n:meta\n# Hello").expect_err("to fail to render");
+    assert_eq!(error, RenderError::Filter(FilterError::NotANumber { filter: Filter::Floor, value: "free".to_string() }));
+    // A meta value that is derived with a filter is reported as such, rather than as a missing title.
+    let error = render("{{ £title }} {{ £total }}", ":meta\nprice = free\ntotal = £price | ceil\n:meta\n# Hello").expect_err("to fail to render");
+    assert_eq!(error, RenderError::Filter(FilterError::NotANumber { filter: Filter::Ceil, value: "free".to_string() }));
+    assert_eq!(render("{{ £title }}", "No heading here.").expect_err("to fail to render"), RenderError::MissingTitle);
+
+    // A live preview keeps the document as it was.
+    let template = Template::new("{{ £price | ceil }}").expect("to parse template");
+    let mut preview = LivePreview::new(&template, HashMap::from([("price".to_string(), "1.5".to_string())])).expect("to render preview");
+    assert!(preview.set_variable("price", "free").is_err());
+    assert_eq!(preview.output(), "2");
+    assert_eq!(preview.set_variable("price", "2.5").expect("to set variable")[0].output, "3");
+}
+
+#[test]
+fn filter_hash_works() {
+    let sha256 = Filter::Hash { algo: HashAlgorithm::Sha256, length: None };
+    assert_eq!(render_filter("Hello, World!", &sha256).expect("to render filter"), hash_hex(b"Hello, World!"));
+
+    // The test suite of RFC 1321.
+    let md5 = Filter::Hash { algo: HashAlgorithm::Md5, length: None };
+    let cases = [
+        ("", "d41d8cd98f00b204e9800998ecf8427e"),
+        ("a", "0cc175b9c0f1b6a831c399e269772661"),
+        ("abc", "900150983cd24fb0d6963f7d28e17f72"),
+        ("message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+        ("abcdefghijklmnopqrstuvwxyz", "c3fcd3d76192e4007dfb496cca67e13b"),
+        ("12345678901234567890123456789012345678901234567890123456789012345678901234567890", "57edf4a22be3c955ac49da2e2107b67a"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(render_filter(input, &md5).expect("to render filter"), expected, "{:?}", input);
+    }
+
+    // Such as a cache-busting query string.
+    let template = Template::new("<link href=\"style.css?v={{ £style | hash = length: 8 }}\">").expect("to parse template");
+    let variables = HashMap::from([("style".to_string(), "Hello, World!".to_string())]);
+    assert_eq!(template.render(&variables).expect("to render"), "<link href=\"style.css?v=dffd6021\">");
+
+    // A misspelt algorithm is an error, rather than another algorithm.
+    assert!(parse_filter(Span::new("hash = algo: sha265")).is_err());
+    let error = parse_placeholder_locations(Span::new("{{ £style | hash = sha265 }}")).expect_err("to be an unknown algorithm");
+    assert_eq!(error.diagnostic().message, "unknown hash algorithm 'sha265'");
+    assert_eq!((error.diagnostic().column, error.diagnostic().length), (20, 6));
+    assert!(Template::new("{{ £style | hash = sha265 }}").is_err());
+    // A variable is only known once it has a value.
+    assert!(Template::new("{{ £style | hash = algo: £algo }}").is_ok());
+}
+
+#[test]
+fn filter_if_works() {
+    let filter = Filter::If { then: "(draft)".to_string(), otherwise: "".to_string() };
+    assert_eq!(render_filter("true", &filter).expect("to render filter"), "(draft)");
+    assert_eq!(render_filter("yes", &filter).expect("to render filter"), "(draft)");
+    // Empty, false, zero, and no are not truthy.
+    for value in ["", "  ", "false", "FALSE", "0", "no"] {
+        assert_eq!(render_filter(value, &filter).expect("to render filter"), "", "{:?}", value);
+    }
+
+    let template = Template::new("<h1>{{ £title }}{{ £draft | if = then: \" (draft)\", else: \"\" }}</h1>").expect("to parse template");
+    let mut variables = HashMap::from([
+        ("title".to_string(), "Hello".to_string()),
+        ("draft".to_string(), "true".to_string()),
+    ]);
+    assert_eq!(template.render(&variables).expect("to render"), "<h1>Hello (draft)</h1>");
+    variables.insert("draft".to_string(), "false".to_string());
+    assert_eq!(template.render(&variables).expect("to render"), "<h1>Hello</h1>");
+}
+
+#[test]
+#[cfg(feature = "markdown")]
+fn filter_markdown_inline_works() {
+    assert_eq!(render_filter("**Hello**, `World`!", &Filter::MarkdownInline).expect("to render filter"), "<strong>Hello</strong>, <code>World</code>!");
+    // Block elements are left as text, and paragraphs are joined.
+    assert_eq!(render_filter("# Not a heading\n\n- Nor a list", &Filter::MarkdownInline).expect("to render filter"), "# Not a heading - Nor a list");
+
+    let template = Template::new("<title>{{ £title | markdown_inline }}</title>").expect("to parse template");
+    let variables = HashMap::from([("title".to_string(), "Using _nom_".to_string())]);
+    assert_eq!(template.render(&variables).expect("to render"), "<title>Using <em>nom</em></title>");
+
+    // The content isn't rendered as Markdown a second time.
+    let template = Template::new("<h1>{{ £content | markdown_inline }}</h1>").expect("to parse template");
+    let variables = HashMap::from([("content".to_string(), "*Hello*".to_string())]);
+    assert_eq!(template.render(&variables).expect("to render"), "<h1><em>Hello</em></h1>");
+}
+
+#[test]
+fn filter_time_ago_works() {
+    let filter = Filter::TimeAgo { now: "2024-05-04".to_string() };
+    let cases = [
+        ("2024-05-04", "today"),
+        ("2024-05-03T23:59:59Z", "yesterday"),
+        ("2024-05-05", "tomorrow"),
+        ("2024-05-01", "3 days ago"),
+        ("2024-04-27", "1 week ago"),
+        ("2024-04-13", "3 weeks ago"),
+        ("2024-05-18", "in 2 weeks"),
+        ("2024-02-29", "2 months ago"),
+        // 360 and 364 days are still months, until a year at 365 days.
+        ("2023-05-10", "11 months ago"),
+        ("2023-05-06", "11 months ago"),
+        ("2023-05-05", "1 year ago"),
+        ("2023-05-04", "1 year ago"),
+        ("2021-01-01", "3 years ago"),
+        // Anything that isn't a date is left as it is.
+        ("soon", "soon"),
+    ];
+    for (date, expected) in cases {
+        assert_eq!(render_filter(date, &filter).expect("to render filter"), expected, "{}", date);
+    }
+
+    // Without `now`, the date is compared to today.
+    assert!(render_filter("2000-01-01", &Filter::TimeAgo { now: "".to_string() }).expect("to render filter").ends_with("years ago"));
+
+    let template = Template::new("<time>{{ £date | age = now: 2024-05-04 }}</time>").expect("to parse template");
+    let variables = HashMap::from([("date".to_string(), "2024-05-01".to_string())]);
+    assert_eq!(template.render(&variables).expect("to render"), "<time>3 days ago</time>");
+}
+
 #[test]
 fn can_parse_round_filter() {
     // Providing no arguments.
@@ -585,55 +1112,322 @@ fn can_render_round_filter() {
     let input = Span::new("{{ £number | round }}");
     let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
     let number = "1.234567890".to_string();
-    assert_eq!(render_filter(number, &placeholder.filters[0]), "1".to_string());
+    assert_eq!(render_filter(number, &placeholder.filters[0]).expect("to render filter"), "1".to_string());
 
     // Providing the default argument.
     let input = Span::new("{{ £number | round = 3 }}");
     let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
     let number = "1.234567890".to_string();
-    assert_eq!(render_filter(number, &placeholder.filters[0]), "1.235".to_string());
+    assert_eq!(render_filter(number, &placeholder.filters[0]).expect("to render filter"), "1.235".to_string());
 
     // Providing the named argument.
     let input = Span::new("{{ £number | round = precision: 6 }}");
     let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
     let number = "1.234567890".to_string();
-    assert_eq!(render_filter(number, &placeholder.filters[0]), "1.234568".to_string());
+    assert_eq!(render_filter(number, &placeholder.filters[0]).expect("to render filter"), "1.234568".to_string());
 }
 
 #[test]
 fn filter_lowercase_works() {
     let input = "HELLO, WORLD!".to_string();
-    let output = render_filter(input, &Filter::Text { case: TextCase::Lower });
+    let output = render_filter(input, &Filter::Text { case: TextCase::Lower }).expect("to render filter");
     assert_eq!(output, "hello, world!");
 }
 
 #[test]
 fn filter_uppercase_works() {
     let input = "hello, world!".to_string();
-    let output = render_filter(input, &Filter::Text { case: TextCase::Upper });
+    let output = render_filter(input, &Filter::Text { case: TextCase::Upper }).expect("to render filter");
     assert_eq!(output, "HELLO, WORLD!");
 }
 
 #[test]
+fn string_filters_keep_graphemes_together() {
+    // A family of four, a flag, and an "e" with a combining acute accent.
+    let input = "👨‍👩‍👧‍👦🇬🇧 cafe\u{301}";
+    assert_eq!(render_filter(input, &Filter::Reverse).expect("to render filter"), "e\u{301}fac 🇬🇧👨‍👩‍👧‍👦");
+
+    let filter = Filter::Truncate { characters: 2, trail: "…".to_string() };
+    assert_eq!(render_filter(input, &filter).expect("to render filter"), "👨‍👩‍👧‍👦🇬🇧…");
+    let filter = Filter::Truncate { characters: 7, trail: "…".to_string() };
+    assert_eq!(render_filter(input, &filter).expect("to render filter"), input);
+
+    let input = "élan über-straße";
+    assert_eq!(render_filter(input, &Filter::Text { case: TextCase::Title }).expect("to render filter"), "Élan Über-Straße");
+    assert_eq!(render_filter(input, &Filter::Text { case: TextCase::Upper }).expect("to render filter"), "ÉLAN ÜBER-STRASSE");
+    assert_eq!(render_filter(input, &Filter::Text { case: TextCase::Pascal }).expect("to render filter"), "ÉlanÜberStraße");
+    assert_eq!(render_filter(input, &Filter::Text { case: TextCase::Camel }).expect("to render filter"), "élanÜberStraße");
+    assert_eq!(render_filter("ÉLAN", &Filter::Text { case: TextCase::Sentence }).expect("to render filter"), "Élan");
+    assert_eq!(render_filter("e\u{301}lan", &Filter::Text { case: TextCase::Sentence }).expect("to render filter"), "E\u{301}lan");
+}
+
+#[test]
+fn filter_base64_works() {
+    let input = "<svg></svg>".to_string();
+    let output = render_filter(input, &Filter::Base64Encode).expect("to render filter");
+    assert_eq!(output, "PHN2Zz48L3N2Zz4=");
+
+    let output = render_filter(output, &Filter::Base64Decode).expect("to render filter");
+    assert_eq!(output, "<svg></svg>");
+
+    // Invalid base64 cannot be decoded.
+    let error = FilterError::InvalidArgument { filter: Filter::Base64Decode, argument: "value".to_string(), value: "Not base64!".to_string() };
+    assert_eq!(render_filter("Not base64!", &Filter::Base64Decode), Err(error));
+}
+
+#[test]
+fn filter_excerpt_works() {
+    // The first paragraph, skipping the title.
+    let input = "# Title\nFirst paragraph\nstill the first paragraph.\n\nSecond paragraph.".to_string();
+    let output = render_filter(input, &Filter::Excerpt).expect("to render filter");
+    assert_eq!(output, "First paragraph\nstill the first paragraph.");
+
+    // Headings separated by blank lines are skipped too.
+    let input = "# Title\n\n## Subtitle\n\nFirst paragraph.".to_string();
+    let output = render_filter(input, &Filter::Excerpt).expect("to render filter");
+    assert_eq!(output, "First paragraph.");
+
+    // As are HTML headings, but not other elements that start with an `h`.
+    let input = "<h1>Title</h1>\n<header>First paragraph.</header>\n\nSecond paragraph.".to_string();
+    let output = render_filter(input, &Filter::Excerpt).expect("to render filter");
+    assert_eq!(output, "<header>First paragraph.</header>");
+    let input = "<hr>\nFirst paragraph.".to_string();
+    let output = render_filter(input, &Filter::Excerpt).expect("to render filter");
+    assert_eq!(output, "<hr>\nFirst paragraph.");
+
+    // The marker takes priority over paragraphs.
+    let input = "# Title\nFirst paragraph.\n\nSecond paragraph.<!-- more -->Third paragraph.".to_string();
+    let output = render_filter(input, &Filter::Excerpt).expect("to render filter");
+    assert_eq!(output, "# Title\nFirst paragraph.\n\nSecond paragraph.");
+}
+
+#[test]
+#[cfg(feature = "markdown")]
+fn can_render_excerpt_filter() {
+    // Excerpt is applied before the automatic markdown filter of £content.
+    let input = Span::new("{{ £content | excerpt }}");
+    let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
+    assert_eq!(placeholder.filters, vec![Filter::Excerpt, Filter::Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 }]);
+
+    let mut content = "# Title\nThis is **bold**.\n\nSecond paragraph.".to_string();
+    for filter in &placeholder.filters {
+        content = render_filter(content, filter).expect("to render filter").into_owned();
+    }
+    assert_eq!(content, "<p>This is <strong>bold</strong>.</p>".to_string());
+}
+
+#[test]
+fn can_create_excerpt_variable() {
+    // The heading, and anything after the first paragraph, is left out.
+    let variables = create_variables(Span::new("# Title\n\nThe *first* paragraph.\n\nThe second."), vec![]).expect("to create variables");
+    assert_eq!(variables.get("excerpt").expect("excerpt"), "The *first* paragraph.");
+
+    // A marker keeps every paragraph before it.
+    let variables = create_variables(Span::new("# Title\nOne.\n\nTwo.\n<!-- more -->\nThree."), vec![]).expect("to create variables");
+    assert_eq!(variables.get("excerpt").expect("excerpt"), "One.\n\nTwo.");
+
+    let variables = create_variables(Span::new("# Title\nOne."), vec![Meta::new("excerpt", "A summary.")]).expect("to create variables");
+    assert_eq!(variables.get("excerpt").expect("excerpt"), "A summary.");
+}
+
+#[test]
+fn can_create_reading_time_variables() {
+    // The heading is not counted, and part of a minute is rounded up.
+    let words = vec!["word"; 450].join(" ");
+    let variables = create_variables(Span::new(&format!("# Title\n{}", words)), vec![]).expect("to create variables");
+    assert_eq!(variables.get("word_count").expect("word_count"), "450");
+    assert_eq!(variables.get("reading_time").expect("reading_time"), "3");
+
+    let variables = create_variables(Span::new("# Title\nOne."), vec![Meta::new("reading_time", "5")]).expect("to create variables");
+    assert_eq!(variables.get("reading_time").expect("reading_time"), "5");
+
+    let markdowns = vec![(PathBuf::from("post.md"), format!("# Title\n{}", words))];
+    let template = std::env::temp_dir().join("blogs-md-easy-reading-time.html");
+    std::fs::write(&template, "{{ £word_count }} words, {{ £reading_time }} min").expect("to write template");
+    let options = BatchOptions {
+        output_dir: Some(std::env::temp_dir().join("blogs-md-easy-reading-time")),
+        words_per_minute: Some(150),
+        ..Default::default()
+    };
+    let outputs = render_batch(&[template], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(std::fs::read_to_string(&outputs[0]).expect("to read output"), "450 words, 3 min");
+    std::fs::remove_dir_all(std::env::temp_dir().join("blogs-md-easy-reading-time")).ok();
+}
+
+#[test]
+fn filter_first_words_works() {
+    let input = "The quick brown fox jumps over the lazy dog.".to_string();
+    let output = render_filter(input, &Filter::FirstWords { words: 3 }).expect("to render filter");
+    assert_eq!(output, "The quick brown");
+
+    // Extra whitespace between words is collapsed.
+    let input = "The   quick\nbrown fox".to_string();
+    let output = render_filter(input, &Filter::FirstWords { words: 3 }).expect("to render filter");
+    assert_eq!(output, "The quick brown");
+
+    // Asking for more words than exist returns every word.
+    let input = "Hello, World!".to_string();
+    let output = render_filter(input, &Filter::FirstWords { words: 10 }).expect("to render filter");
+    assert_eq!(output, "Hello, World!");
+}
+
+#[test]
+fn filter_last_words_works() {
+    let input = "The quick brown fox jumps over the lazy dog.".to_string();
+    let output = render_filter(input, &Filter::LastWords { words: 3 }).expect("to render filter");
+    assert_eq!(output, "the lazy dog.");
+
+    // Asking for more words than exist returns every word.
+    let input = "Hello, World!".to_string();
+    let output = render_filter(input, &Filter::LastWords { words: 10 }).expect("to render filter");
+    assert_eq!(output, "Hello, World!");
+}
+
+#[test]
+fn can_render_first_and_last_words_filter() {
+    let input = Span::new("{{ £summary | first_words = 2 }}");
+    let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
+    let summary = "Hello, World! Hello, Rust!".to_string();
+    assert_eq!(render_filter(summary, &placeholder.filters[0]).expect("to render filter"), "Hello, World!".to_string());
+
+    let input = Span::new("{{ £summary | last_words = words: 2 }}");
+    let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
+    let summary = "Hello, World! Hello, Rust!".to_string();
+    assert_eq!(render_filter(summary, &placeholder.filters[0]).expect("to render filter"), "Hello, Rust!".to_string());
+}
+
+#[test]
+#[cfg(feature = "markdown")]
 fn filter_markdown_works() {
     let input = "# Title\nFirst _paragraph_.  \nNewline.\n\nSecond paragraph with [link](https://example.com).\n\n* Unordered list.\n\n1. Ordered list.".to_string();
-    let output = render_filter(input, &Filter::Markdown);
+    let output = render_filter(input, &Filter::Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 }).expect("to render filter");
     assert_eq!(output, "<h1>Title</h1>\n<p>First <em>paragraph</em>.<br />\nNewline.</p>\n<p>Second paragraph with <a href=\"https://example.com\">link</a>.</p>\n<ul>\n<li>Unordered list.</li>\n</ul>\n<ol>\n<li>Ordered list.</li>\n</ol>");
 }
 
+#[test]
+#[cfg(feature = "markdown")]
+fn filter_markdown_extensions_work() {
+    let input = "| Task | Done |\n| ---- | ---- |\n| ~~Write~~ | [x] |\n\n* [x] Tables\n* [ ] Footnotes".to_string();
+
+    // Without any arguments, the extensions of GitHub-Flavoured Markdown are
+    // left as they are written.
+    let (_, placeholder) = parse_placeholder(Span::new("{{ £content }}")).expect("to parse placeholder");
+    let output = render_filter(input.to_owned(), &placeholder.filters[0]).expect("to render filter");
+    assert_eq!(output, "<p>| Task | Done |\n| ---- | ---- |\n| ~~Write~~ | [x] |</p>\n<ul>\n<li>[x] Tables</li>\n<li>[ ] Footnotes</li>\n</ul>");
+
+    let (_, placeholder) = parse_placeholder(Span::new("{{ £content | markdown = tables: true, strikethrough: true, tasklists: true }}")).expect("to parse placeholder");
+    assert_eq!(placeholder.filters, vec![Filter::Markdown { tables: true, strikethrough: true, tasklists: true, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 }]);
+    let output = render_filter(input, &placeholder.filters[0]).expect("to render filter");
+    assert_eq!(output, [
+        "<table>",
+        "<thead>",
+        "<tr>",
+        "<th>Task</th>",
+        "<th>Done</th>",
+        "</tr>",
+        "</thead>",
+        "<tbody>",
+        "<tr>",
+        "<td><del>Write</del></td>",
+        "<td>[x]</td>",
+        "</tr>",
+        "</tbody>",
+        "</table>",
+        "<ul>",
+        "<li><input type=\"checkbox\" disabled=\"\" checked=\"\" /> Tables</li>",
+        "<li><input type=\"checkbox\" disabled=\"\" /> Footnotes</li>",
+        "</ul>",
+    ].join("\n"));
+}
+
+#[test]
+#[cfg(feature = "markdown")]
+fn filter_markdown_smart_punctuation_works() {
+    let (_, filter) = parse_filter(Span::new("markdown = smart_punctuation: true")).expect("to parse filter");
+    let input = "## \"Quoted\" -- 'single'\n\n<abbr title=\"It's\">I'm</abbr> told---'90s style...\n\n```\nlet s = \"--\";\n```".to_string();
+
+    // Attributes and code blocks are left as they are.
+    assert_eq!(render_filter(input.to_owned(), &filter).expect("to render filter"), [
+        "<h2>“Quoted” – ‘single’</h2>",
+        "<p><abbr title=\"It's\">I’m</abbr> told—’90s style…</p>",
+        "<pre><code>let s = &quot;--&quot;;",
+        "</code></pre>",
+    ].join("\n"));
+    // Without the argument, nothing is converted.
+    assert!(render_filter(input, &Filter::Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 }).expect("to render filter").contains("told---'90s style..."));
+}
+
+#[test]
+#[cfg(feature = "markdown")]
+fn filter_markdown_options_work() {
+    let (_, filter) = parse_filter(Span::new("markdown = hard_breaks: true, html: false, heading_offset: 2")).expect("to parse filter");
+    assert_eq!(filter, Filter::Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: true, html: false, heading_offset: 2 });
+    assert_eq!(filter.to_string(), "markdown = hard_breaks: true, html: false, heading_offset: 2");
+
+    let input = "# Poem\nRoses are <em>red</em>,\nViolets are blue.\n\n<div class=\"ad\">Buy now</div>\n\n##### Deep\n\n~~~\nkept\nas is\n~~~".to_string();
+    assert_eq!(render_filter(input, &filter).expect("to render filter"), [
+        "<h3>Poem</h3>",
+        "<p>Roses are red,<br />",
+        "Violets are blue.</p>",
+        "<h6>Deep</h6>",
+        "<pre><code>kept",
+        "as is",
+        "</code></pre>",
+    ].join("\n"));
+
+    // The defaults of a config are given to the `markdown` filter of £content,
+    // even when it isn't named.
+    let template = std::env::temp_dir().join("blogs-md-easy-markdown-options.html");
+    std::fs::write(&template, "<h1>{{ £title }}</h1>{{ £content }}").expect("to write template");
+    let options = BatchOptions { filter_defaults: vec![Meta::new("markdown", "heading_offset: 1")], ..Default::default() };
+    let html = render_markdown(&template, Path::new("post.md"), "# Post\n## Section", &options).expect("to render");
+    assert_eq!(html, "<h1>Post</h1>\n<h2>Post</h2>\n\n<h3>Section</h3>");
+}
+
+#[test]
+#[cfg(feature = "markdown")]
+fn filter_markdown_footnotes_work() {
+    let template = std::env::temp_dir().join("blogs-md-easy-footnotes.html");
+    std::fs::write(&template, "{{ £content | markdown = footnotes: true }}").expect("to write template");
+    let markdown = "# Paper\n## Method\nCited.[^source] Noted.[^note]\n\n[^source]: A source.\n[^note]: A note.";
+    let options = BatchOptions { wrap_sections: Some("section".to_string()), ..Default::default() };
+
+    // The footnotes are numbered in the order that they are referenced, and
+    // are listed in their own section, after the last section of the content.
+    let html = render_markdown(&template, Path::new("paper.md"), markdown, &options).expect("to render");
+    assert_eq!(html, [
+        "<h1>Paper</h1>",
+        "<section aria-labelledby=\"method\">",
+        "<h2 id=\"method\">Method</h2>",
+        "<p>Cited.<sup><a href=\"#user-content-fn-source\" id=\"user-content-fnref-source\" data-footnote-ref=\"\" aria-describedby=\"footnote-label\">1</a></sup> Noted.<sup><a href=\"#user-content-fn-note\" id=\"user-content-fnref-note\" data-footnote-ref=\"\" aria-describedby=\"footnote-label\">2</a></sup></p>",
+        "</section>",
+        "<section data-footnotes=\"\" class=\"footnotes\"><h2 id=\"footnote-label\" class=\"sr-only\">Footnotes</h2>",
+        "<ol>",
+        "<li id=\"user-content-fn-source\">",
+        "<p>A source. <a href=\"#user-content-fnref-source\" data-footnote-backref=\"\" aria-label=\"Back to content\" class=\"data-footnote-backref\">↩</a></p>",
+        "</li>",
+        "<li id=\"user-content-fn-note\">",
+        "<p>A note. <a href=\"#user-content-fnref-note\" data-footnote-backref=\"\" aria-label=\"Back to content\" class=\"data-footnote-backref\">↩</a></p>",
+        "</li>",
+        "</ol>",
+        "</section>",
+        "",
+    ].join("\n"));
+}
+
 #[test]
 fn filter_replace_works() {
     let input = "Hello, World! Hello, World!".to_string();
-    let output = render_filter(input, &Filter::Replace { find: "World".to_string(), replacement: "Rust".to_string(), limit: None });
+    let output = render_filter(input, &Filter::Replace { find: "World".to_string(), replacement: "Rust".to_string(), limit: None }).expect("to render filter");
     assert_eq!(output, "Hello, Rust! Hello, Rust!");
 
     let input = "Hello, World! Hello, World!".to_string();
-    let output = render_filter(input, &Filter::Replace { find: "World".to_string(), replacement: "Rust".to_string(), limit: Some(1) });
+    let output = render_filter(input, &Filter::Replace { find: "World".to_string(), replacement: "Rust".to_string(), limit: Some(1) }).expect("to render filter");
     assert_eq!(output, "Hello, Rust! Hello, World!");
 
     // Replacing is a walk in the park!
     let input = "pawalkrk".to_string();
-    let output = render_filter(input, &Filter::Replace { find: "walk".to_string(), replacement: "".to_string(), limit: None });
+    let output = render_filter(input, &Filter::Replace { find: "walk".to_string(), replacement: "".to_string(), limit: None }).expect("to render filter");
     assert_eq!(output, "park");
 }
 
@@ -671,48 +1465,52 @@ fn can_render_replace_filter() {
     let input = Span::new("{{ £greeting | replace = find: World, replacement: Rust, limit: 1 }}");
     let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
     let title = "Hello, World! Hello, World!".to_string();
-    assert_eq!(render_filter(title, &placeholder.filters[0]), "Hello, Rust! Hello, World!".to_string());
+    assert_eq!(render_filter(title, &placeholder.filters[0]).expect("to render filter"), "Hello, Rust! Hello, World!".to_string());
 
     // Using the default argument.
     let input = Span::new("{{ £greeting | replace = World, replacement: Rust, limit: 1 }}");
     let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
     let title = "Hello, World! Hello, World!".to_string();
-    assert_eq!(render_filter(title, &placeholder.filters[0]), "Hello, Rust! Hello, World!".to_string());
+    assert_eq!(render_filter(title, &placeholder.filters[0]).expect("to render filter"), "Hello, Rust! Hello, World!".to_string());
 
     // Removing a word by not providing a replacement.
     let input = Span::new("{{ £greeting | replace = World, limit: 1 }}");
     let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
     let title = "Hello, World! Hello, World!".to_string();
-    assert_eq!(render_filter(title, &placeholder.filters[0]), "Hello, ! Hello, World!".to_string());
+    assert_eq!(render_filter(title, &placeholder.filters[0]).expect("to render filter"), "Hello, ! Hello, World!".to_string());
 
     // Removing all words by not providing a replacement and limit.
     // It is also possible to include `replacement` here to replace them all.
     let input = Span::new("{{ £greeting | replace = World }}");
     let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
     let title = "Hello, World! Hello, World!".to_string();
-    assert_eq!(render_filter(title, &placeholder.filters[0]), "Hello, ! Hello, !".to_string());
+    assert_eq!(render_filter(title, &placeholder.filters[0]).expect("to render filter"), "Hello, ! Hello, !".to_string());
 }
 
 #[test]
 fn filter_reverse_works() {
     let input = "Hello, World!".to_string();
-    let output = render_filter(input, &Filter::Reverse);
+    let output = render_filter(input, &Filter::Reverse).expect("to render filter");
     assert_eq!(output, "!dlroW ,olleH");
 }
 
 #[test]
 fn filter_text_works() {
     let input = "Hello, World!".to_string();
-    let output = render_filter(input, &Filter::Text { case: TextCase::Lower });
+    let output = render_filter(input, &Filter::Text { case: TextCase::Lower }).expect("to render filter");
     assert_eq!(output, "hello, world!");
 
     let input = "hello, world!".to_string();
-    let output = render_filter(input, &Filter::Text { case: TextCase::Title });
+    let output = render_filter(input, &Filter::Text { case: TextCase::Title }).expect("to render filter");
     assert_eq!(output, "Hello, World!");
 
     let input = "Hello, World!".to_string();
-    let output = render_filter(input, &Filter::Text { case: TextCase::Kebab });
+    let output = render_filter(input, &Filter::Text { case: TextCase::Kebab }).expect("to render filter");
     assert_eq!(output, "hello-world");
+
+    let input = "the QUICK brown Fox".to_string();
+    let output = render_filter(input, &Filter::Text { case: TextCase::Sentence }).expect("to render filter");
+    assert_eq!(output, "The quick brown fox");
 }
 
 #[test]
@@ -742,26 +1540,77 @@ fn can_render_text_filter() {
     let input = Span::new("{{ £title | text }}");
     let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
     let title = "HELLO, WORLD!".to_string();
-    assert_eq!(render_filter(title, &placeholder.filters[0]), "hello, world!".to_string());
+    assert_eq!(render_filter(title, &placeholder.filters[0]).expect("to render filter"), "hello, world!".to_string());
 
     // Providing unnamed argument.
     let input = Span::new("{{ £title | text = pascal }}");
     let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
     let title = "hello, world!".to_string();
-    assert_eq!(render_filter(title, &placeholder.filters[0]), "HelloWorld".to_string());
+    assert_eq!(render_filter(title, &placeholder.filters[0]).expect("to render filter"), "HelloWorld".to_string());
 
     // Providing a named argument.
     let input = Span::new("{{ £title | text = case: snake }}");
     let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
     let title = "Hello, World!".to_string();
-    assert_eq!(render_filter(title, &placeholder.filters[0]), "hello_world".to_string());
+    assert_eq!(render_filter(title, &placeholder.filters[0]).expect("to render filter"), "hello_world".to_string());
 }
 
 #[test]
 fn filter_truncate_works() {
     let input = "Hello, World!".to_string();
-    let output = render_filter(input, &Filter::Truncate { characters: 7, trail: "--".to_string() });
+    let output = render_filter(input, &Filter::Truncate { characters: 7, trail: "--".to_string() }).expect("to render filter");
     assert_eq!(output, "Hello, --");
+
+    // Characters that are made of several code points are never split.
+    let filter = Filter::Truncate { characters: 2, trail: "".to_string() };
+    assert_eq!(render_filter("日本語", &filter).expect("to render filter"), "日本");
+    assert_eq!(render_filter("e\u{301}e\u{301}e\u{301}", &filter).expect("to render filter"), "e\u{301}e\u{301}");
+    assert_eq!(render_filter("👍🏽👨\u{200D}👩\u{200D}👧!", &filter).expect("to render filter"), "👍🏽👨\u{200D}👩\u{200D}👧");
+    assert_eq!(render_filter("🇬🇧🇫🇷🇩🇪", &filter).expect("to render filter"), "🇬🇧🇫🇷");
+    // A string that is short enough is left as it is.
+    assert_eq!(render_filter("🇬🇧🇫🇷", &filter).expect("to render filter"), "🇬🇧🇫🇷");
+}
+
+#[test]
+fn filter_wrap_works() {
+    let template = Template::new("<h1>{{ £title }}</h1>{{ £subtitle | wrap = before: \"<h2>\", after: \"</h2>\" }}").expect("to parse template");
+    let mut variables = HashMap::from([
+        ("title".to_string(), "Hello".to_string()),
+        ("subtitle".to_string(), "World".to_string()),
+    ]);
+    assert_eq!(template.render(&variables).expect("to render"), "<h1>Hello</h1><h2>World</h2>");
+
+    // An empty value outputs nothing at all.
+    variables.insert("subtitle".to_string(), "".to_string());
+    assert_eq!(template.render(&variables).expect("to render"), "<h1>Hello</h1>");
+}
+
+#[test]
+fn filter_split_works() {
+    assert_eq!(render_filter("rust,parsing , nom,,", &Filter::Split { on: ",".to_string() }).expect("to render filter"), "rust, parsing, nom");
+    assert_eq!(render_filter("", &Filter::Split { on: ",".to_string() }).expect("to render filter"), "");
+    // Items that contain a comma are quoted, so that they can be joined again.
+    let list = render_filter("Doe, John|Jane Doe", &Filter::Split { on: "|".to_string() }).expect("to render filter");
+    assert_eq!(list, "\"Doe, John\", Jane Doe");
+    assert_eq!(render_filter(list, &Filter::Join { separator: " & ".to_string() }).expect("to render filter"), "Doe, John & Jane Doe");
+
+    // A string can be promoted into a list, and then joined like any other.
+    let template = Template::new("<p>{{ £keywords | split = on: \"|\" | join = \" / \" }}</p><p>{{ £tags | join = separator: \"; \" }}</p>").expect("to parse template");
+    let markdown = ":meta\nkeywords = rust|parsing|nom\ntags = [rust, parsing]\n:meta\n# Hello";
+    let (content, meta_values) = parse_meta_section(Span::new(markdown)).expect("to parse meta");
+    let variables = create_variables(content, meta_values).expect("to create variables");
+    assert_eq!(template.render(&variables).expect("to render"), "<p>rust / parsing / nom</p><p>rust; parsing</p>");
+}
+
+#[test]
+fn filter_truncate_words_works() {
+    let filter = Filter::TruncateWords { words: 3, trail: "…".to_string() };
+    assert_eq!(render_filter("The quick  brown\nfox jumps", &filter).expect("to render filter"), "The quick brown…");
+    assert_eq!(render_filter("The quick brown", &filter).expect("to render filter"), "The quick brown");
+
+    let (_, filter) = parse_filter(Span::new("truncate_words = words: 2, trail: --")).expect("to parse filter");
+    assert_eq!(filter, Filter::TruncateWords { words: 2, trail: "--".to_string() });
+    assert_eq!(render_filter("Hello, World! Goodbye", &filter).expect("to render filter"), "Hello, World!--");
 }
 
 #[test]
@@ -803,49 +1652,50 @@ fn can_render_truncate_filter() {
     let input = Span::new("{{ £title | truncate = characters: 7, trail: -- }}");
     let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
     let title = "Hello, World!".to_string();
-    assert_eq!(render_filter(title, &placeholder.filters[0]), "Hello, --".to_string());
+    assert_eq!(render_filter(title, &placeholder.filters[0]).expect("to render filter"), "Hello, --".to_string());
 
     // Providing just characters.
     let input = Span::new("{{ £title | truncate = characters: 7 }}");
     let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
     let title = "Hello, World!".to_string();
-    assert_eq!(render_filter(title, &placeholder.filters[0]), "Hello, ...".to_string());
+    assert_eq!(render_filter(title, &placeholder.filters[0]).expect("to render filter"), "Hello, ...".to_string());
 
     // Providing just trail.
     let input = Span::new("{{ £title | truncate = trail: -- }}");
     let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
     let title = "Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! Hello, World!".to_string();
-    assert_eq!(render_filter(title, &placeholder.filters[0]), "Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! He--".to_string());
+    assert_eq!(render_filter(title, &placeholder.filters[0]).expect("to render filter"), "Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! He--".to_string());
 
     // Providing just trail on a short string (no trail added).
     let input = Span::new("{{ £title | truncate = trail: -- }}");
     let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
     let title = "Hello, World!".to_string();
-    assert_eq!(render_filter(title, &placeholder.filters[0]), "Hello, World!".to_string());
+    assert_eq!(render_filter(title, &placeholder.filters[0]).expect("to render filter"), "Hello, World!".to_string());
 
     // Providing just default argument.
     let input = Span::new("{{ £title | truncate = 8 }}");
     let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
     let title = "Hello, World! Hello, World!".to_string();
-    assert_eq!(render_filter(title, &placeholder.filters[0]), "Hello, W...".to_string());
+    assert_eq!(render_filter(title, &placeholder.filters[0]).expect("to render filter"), "Hello, W...".to_string());
 
     // Providing no arguments.
     let input = Span::new("{{ £title | truncate }}");
     let (_, placeholder) = parse_placeholder(input).expect("to parse placeholder");
     let title = "Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! Hello, World!".to_string();
-    assert_eq!(render_filter(title, &placeholder.filters[0]), "Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! He...".to_string());
+    assert_eq!(render_filter(title, &placeholder.filters[0]).expect("to render filter"), "Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! Hello, World! He...".to_string());
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 // Integration tests
 
 #[test]
+#[cfg(feature = "markdown")]
 fn can_replace_placeholder_from_meta() {
     let input = Span::new("<meta>\ntitle = Meta title\n£author = John Doe\n</meta>\n# Markdown title\nThis is my content");
     let template = Span::new("<html>\n<head>\n<title>{{ £title }}</title>\n</head>\n<body>\n<h1>{{ £title }}</h1>\n<small>By {{ £author }}</small>\n<section>{{ £content }}</section>\n</body>\n</html>");
 
     let mut placeholders = parse_placeholder_locations(template).expect("to parse placeholders");
-    placeholders.sort_by(|a, b| b.selection.start.offset.cmp(&a.selection.start.offset));
+    placeholders.sort_by_key(|placeholder| std::cmp::Reverse(placeholder.selection.start.offset));
 
     let mut placeholder_title_iter = placeholders.iter().filter(|p| &p.name == "title");
     assert!(placeholder_title_iter.clone().count() == 2);
@@ -886,7 +1736,7 @@ fn can_replace_placeholder_from_meta() {
             let mut variable = variable.to_owned();
 
             for filter in &placeholder.filters {
-                variable = render_filter(variable, filter);
+                variable = render_filter(variable, filter).expect("to render filter").into_owned();
             }
 
             html_doc = replace_substring(&html_doc, placeholder.selection.start.offset, placeholder.selection.end.offset, &variable);
@@ -897,3 +1747,3065 @@ fn can_replace_placeholder_from_meta() {
 
     assert_eq!(html_doc, "<html>\n<head>\n<title>Meta title</title>\n</head>\n<body>\n<h1>Meta title</h1>\n<small>By John Doe</small>\n<section><h1>Markdown title</h1>\n<p>This is my content</p></section>\n</body>\n</html>");
 }
+
+#[test]
+fn can_cancel_batch_from_progress() {
+    let templates = vec![PathBuf::from("tests/template.html")];
+    let markdown = std::fs::read_to_string("tests/one.md").expect("to read markdown");
+    let markdowns = vec![
+        (PathBuf::from("tests/one.md"), markdown.clone()),
+        (PathBuf::from("tests/two.md"), markdown),
+    ];
+    let output_dir = std::env::temp_dir().join("blogs_md_easy_cancel_batch");
+    std::fs::remove_dir_all(&output_dir).ok();
+
+    let token = CancellationToken::new();
+    let mut progress = vec![];
+    let options = BatchOptions { output_dir: Some(output_dir.clone()), ..Default::default() };
+    let result = render_batch(&templates, &markdowns, &options, |p| {
+        progress.push((p.index, p.total, p.output_path.clone()));
+        // Cancel after the first file, the second should never be rendered.
+        token.cancel();
+    }, &token);
+
+    assert!(result.is_err());
+    #[cfg(not(feature = "parallel"))]
+    {
+        assert_eq!(progress, vec![(0, 2, output_dir.join("one.html"))]);
+        assert!(!output_dir.join("two.html").exists());
+    }
+    // Either file may finish first, but no progress is reported once cancelled.
+    #[cfg(feature = "parallel")]
+    assert_eq!(progress.len(), 1);
+    std::fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn can_render_markdown_with_bom() {
+    let bytes = "\u{feff}:meta\nauthor = John Doe\n:meta\n# Title".as_bytes();
+    let markdown = decode_file(bytes, Path::new("bom.md")).expect("to decode markdown");
+    let (markdown, meta_values) = parse_meta_section(Span::new(&markdown)).expect("to parse meta section");
+    assert_eq!(meta_values, vec![Meta::new("author", "John Doe")]);
+    assert_eq!(markdown.fragment(), &"# Title");
+
+    // Latin-1 is not UTF-8, so report the file that caused the problem.
+    let error = decode_file(b"title = Caf\xE9", Path::new("latin.md")).expect_err("to reject latin-1");
+    assert_eq!(error.to_string(), "The file 'latin.md' is not valid UTF-8, please save it with UTF-8 encoding.");
+}
+
+#[test]
+fn can_embed_meta_in_output() {
+    let meta = vec![
+        Meta::new("author", "John \"JD\" Doe"),
+        Meta::new("note", "A comment -- with dashes"),
+    ];
+
+    let html = "<html>\n<head>\n<title>Title</title>\n</head>\n<body></body>\n</html>";
+    let output = embed_meta(html, &meta, EmbedMeta::Tags);
+    assert_eq!(output, "<html>\n<head>\n<title>Title</title>\n<meta name=\"author\" content=\"John &quot;JD&quot; Doe\">\n<meta name=\"note\" content=\"A comment -- with dashes\">\n</head>\n<body></body>\n</html>");
+
+    let output = embed_meta(html, &meta, EmbedMeta::Comment);
+    assert_eq!(output, "<html>\n<head>\n<title>Title</title>\n<!--\nauthor: John \"JD\" Doe\nnote: A comment - - with dashes\n-->\n</head>\n<body></body>\n</html>");
+
+    // Nothing to embed, nothing changes.
+    assert_eq!(embed_meta(html, &[], EmbedMeta::Tags), html);
+}
+
+#[test]
+fn can_introspect_template() {
+    let template = std::fs::read_to_string("tests/template.html").expect("to read template");
+    let template = Template::new(&template).expect("to parse template");
+
+    assert_eq!(template.required_variables(), vec!["author", "content", "multi_line", "number", "package", "title"]);
+    assert_eq!(template.used_filters(), vec![
+        Filter::Text { case: TextCase::Upper },
+        Filter::Ceil,
+        Filter::Floor,
+        Filter::Text { case: TextCase::Snake },
+        Filter::Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 },
+    ]);
+}
+
+#[test]
+fn can_parse_meta_lists() {
+    let markdown = Span::new(":meta\ntags = [rust, \"parsing, nom\", markdown]\nnot_a_list = [rust] and more\n:meta\n# Title");
+    let (_, meta) = parse_meta_section(markdown).expect("to parse meta");
+    assert_eq!(meta, vec![
+        Meta::new("tags", "rust, \"parsing, nom\", markdown"),
+        Meta::new("not_a_list", "[rust] and more"),
+    ]);
+    // An item that contains a comma is still a single item.
+    assert_eq!(split_meta_list(&meta[0].value), vec!["rust", "parsing, nom", "markdown"]);
+    assert_eq!(join_meta_list(&split_meta_list(&meta[0].value)), meta[0].value);
+    assert_eq!(split_meta_list(r#""Say \"hi\", then go", " padded ", ,"#), vec!["Say \"hi\", then go", " padded "]);
+
+    let (_, empty) = parse_meta_section(Span::new(":meta\ntags = []\n:meta\n")).expect("to parse meta");
+    assert_eq!(empty, vec![Meta::new("tags", "")]);
+
+    let template = Template::new("<ul><li>{{ £tags | join = separator: </li><li> }}</li></ul>").expect("to parse template");
+    let variables = HashMap::from([("tags".to_string(), "rust, parsing, nom".to_string())]);
+    assert_eq!(template.render(&variables).expect("to render"), "<ul><li>rust</li><li>parsing</li><li>nom</li></ul>");
+
+    let markdown = ":meta\nauthors = [\"Doe, John\", Jane Doe]\n:meta\n# Hello";
+    assert_eq!(render("{{ £authors | join = \" & \" }}", markdown).expect("to render"), "Doe, John & Jane Doe");
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn can_install_and_render_theme() {
+    let themes_dir = std::env::temp_dir().join("blogs_md_easy_can_install_theme");
+    let theme = install_theme("tests/theme", &themes_dir).expect("to install theme");
+    assert_eq!(theme.name, "example");
+    assert_eq!(theme.templates, vec![themes_dir.join("example/templates/post.html")]);
+    assert!(theme.static_dir().join("style.css").exists());
+
+    // Installing again replaces the previous version.
+    assert_eq!(install_theme("tests/theme", &themes_dir).expect("to reinstall theme"), theme);
+
+    let markdowns = vec![(PathBuf::from("one.md"), ":meta\nauthor = John Doe\n:meta\n# Title".to_string())];
+    let options = BatchOptions {
+        output_dir: Some(themes_dir.join("output")),
+        variables: theme.variables.to_owned(),
+        ..Default::default()
+    };
+    let outputs = render_batch(&theme.templates, &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let output = std::fs::read_to_string(&outputs[0]).expect("to read output");
+    assert!(output.contains("<title>Title | Example Blog</title>"));
+
+    // The meta section takes precedence over the theme's variables.
+    let markdowns = vec![(PathBuf::from("one.md"), ":meta\nsite_name = My Site\n:meta\n# Title".to_string())];
+    let outputs = render_batch(&theme.templates, &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let output = std::fs::read_to_string(&outputs[0]).expect("to read output");
+    assert!(output.contains("<title>Title | My Site</title>"));
+
+    assert!(Theme::load(Path::new("tests")).is_err());
+
+    // A theme can't be installed, or read templates, outside of its directory.
+    let unsafe_theme = std::env::temp_dir().join("blogs_md_easy_unsafe_theme");
+    std::fs::create_dir_all(&unsafe_theme).expect("to create theme");
+    for manifest in ["name = \"..\"", "name = \".\"", "name = \"a/b\"", "name = \"/tmp\"", "templates = [\"../post.html\"]", "templates = [\"/etc/hosts\"]"] {
+        std::fs::write(unsafe_theme.join("theme.toml"), manifest).expect("to write manifest");
+        assert!(install_theme(&unsafe_theme.to_string_lossy(), &themes_dir).is_err(), "{}", manifest);
+    }
+    assert!(themes_dir.join("example").exists());
+    std::fs::remove_dir_all(unsafe_theme).ok();
+}
+
+#[test]
+fn can_render_archetypes() {
+    let variables = HashMap::from([
+        ("title".to_string(), "A Good Link".to_string()),
+        ("date".to_string(), unix_to_date(1_704_067_200)),
+        ("url".to_string(), "https://example.com".to_string()),
+    ]);
+
+    let (markdown, warnings) = render_archetype(Path::new("tests/archetypes"), Some("link"), &variables).expect("to render archetype");
+    assert_eq!(markdown, ":meta\nurl = https://example.com\ndate = 2024-01-01\n:meta\n# A Good Link\n\n> Commentary.\n");
+    assert!(warnings.is_empty());
+
+    // The created Markdown can then be parsed.
+    let (markdown, meta) = parse_meta_section(Span::new(&markdown)).expect("to parse meta");
+    assert_eq!(meta[0], Meta::new("url", "https://example.com"));
+    assert_eq!(parse_title(markdown).expect("to parse title").1.fragment(), &"A Good Link");
+
+    // Without a default archetype, the built in one is used.
+    let (markdown, _) = render_archetype(Path::new("tests/archetypes"), None, &variables).expect("to render archetype");
+    assert_eq!(markdown, ":meta\ndate = 2024-01-01\n:meta\n# A Good Link\n");
+
+    assert!(render_archetype(Path::new("tests/archetypes"), Some("review"), &variables).is_err());
+
+    // Variables that weren't provided are left empty, with a warning.
+    let variables = HashMap::from([("title".to_string(), "A Good Link".to_string()), ("date".to_string(), "2024-01-01".to_string())]);
+    let (markdown, warnings) = render_archetype(Path::new("tests/archetypes"), Some("link"), &variables).expect("to render archetype");
+    assert_eq!(markdown, ":meta\nurl = \ndate = 2024-01-01\n:meta\n# A Good Link\n\n> Commentary.\n");
+    assert_eq!(warnings, vec![Warning::MissingVariable("url".to_string())]);
+}
+
+#[test]
+fn can_render_nested_variables() {
+    let markdown = Span::new(":meta\nauthor.name = John Doe\n£author.url = https://example.com\n:meta\n# Title");
+    let (markdown, meta) = parse_meta_section(markdown).expect("to parse meta");
+    let variables = create_variables(markdown, meta).expect("to create variables");
+
+    let template = Template::new("<a href=\"{{ £author.url }}\">{{ £author.name | uppercase }}</a>.").expect("to parse template");
+    assert_eq!(template.required_variables(), vec!["author.name", "author.url"]);
+    assert_eq!(template.render(&variables).expect("to render"), "<a href=\"https://example.com\">JOHN DOE</a>.");
+
+    // Nested variables are created in the same way from YAML.
+    #[cfg(feature = "yaml")]
+    {
+        let markdown = Span::new("---\nauthor:\n  name: John Doe\n---\n# Title");
+        let (markdown, meta) = parse_meta_section(markdown).expect("to parse meta");
+        let variables = create_variables(markdown, meta).expect("to create variables");
+        assert_eq!(variables.get("author.name").expect("author.name to exist"), "John Doe");
+    }
+}
+
+#[test]
+fn can_use_allowed_environment_variables() {
+    std::env::set_var("BLOGS_MD_EASY_BUILD_SHA", "abc123");
+    std::env::set_var("BLOGS_MD_EASY_SECRET", "hunter2");
+
+    // Only the allowed names are read.
+    let variables = load_env_variables(&["BLOGS_MD_EASY_BUILD_SHA".to_string(), "BLOGS_MD_EASY_UNSET".to_string()]);
+    assert_eq!(variables, vec![Meta::new("env.BLOGS_MD_EASY_BUILD_SHA", "abc123")]);
+
+    let template = Template::new("<footer>{{ £env.BLOGS_MD_EASY_BUILD_SHA }}{{ £env.BLOGS_MD_EASY_SECRET ?? \"\" }}</footer>").expect("to parse template");
+    let variables = variables.into_iter().map(|meta| (meta.key, meta.value)).collect::<HashMap<String, String>>();
+    assert_eq!(template.render(&variables).expect("to render"), "<footer>abc123</footer>");
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn can_merge_global_variables() {
+    let mut variables = load_variables(Path::new("tests/variables.toml")).expect("to load variables");
+    variables.push(Meta::new("site_name", "Theme Blog"));
+
+    let output_dir = std::env::temp_dir().join("blogs_md_easy_can_merge_global_variables");
+    let template = output_dir.join("template.html");
+    std::fs::create_dir_all(&output_dir).expect("to create output dir");
+    std::fs::write(&template, "{{ £site_name }} | {{ £title }} by {{ £author.name }}").expect("to write template");
+
+    let markdowns = vec![
+        (PathBuf::from("one.md"), "# One".to_string()),
+        (PathBuf::from("two.md"), ":meta\nauthor.name = Jane Doe\n:meta\n# Two".to_string()),
+    ];
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        variables,
+        ..Default::default()
+    };
+    let outputs = render_batch(&[template], &markdowns, &options, |progress| {
+        // Global variables are never unused.
+        assert!(progress.unused_variables.is_empty());
+    }, &CancellationToken::new()).expect("to render");
+
+    assert_eq!(std::fs::read_to_string(&outputs[0]).expect("to read output"), "Example Blog | One by John Doe");
+    assert_eq!(std::fs::read_to_string(&outputs[1]).expect("to read output"), "Example Blog | Two by Jane Doe");
+}
+
+#[test]
+#[cfg(feature = "markdown")]
+fn can_render_in_one_call() {
+    let template = std::fs::read_to_string("tests/template.html").expect("to read template");
+    let markdown = std::fs::read_to_string("tests/one.md").expect("to read markdown");
+
+    let output = render(&template, &markdown).expect("to render").replace("\r", "");
+    assert_eq!(output, r#"<head>
+    <title>MARKDOWN TITLE | 2 | 1</title>
+</head>
+<body>
+    <p>blogs_md_easy by British Werewolf</p>
+    <main><h1>Markdown Title</h1>
+<p>This is the first paragraph of this file.</p>
+<p>Now we have a new paragraph.<br />
+And this is a newline.</p></main>
+    <footer><p>Hello, &quot;World&quot; this<br />
+is a newline</p></footer>
+</body>
+"#);
+}
+
+#[test]
+fn can_report_render_errors() {
+    assert_eq!(render("{{ £title }} {{ £missing }}", "# Title"), Err(RenderError::MissingVariable("missing".to_string())));
+    assert_eq!(render("{{ £title }}", ":meta\nauthor = John Doe\n:meta\nNo title."), Err(RenderError::MissingTitle));
+    assert_eq!(RenderError::MissingVariable("author".to_string()).to_string(), "Missing variable 'author'.");
+}
+
+#[test]
+fn can_diff_templates() {
+    let old = Template::new("{{ £title | uppercase }} {{ £author }} {{ £date }}").expect("to parse template");
+    let new = Template::new("{{ £title }} {{ £date }} {{ £summary }}").expect("to parse template");
+    let diff = old.diff(&new);
+
+    assert_eq!(diff.added_variables, vec!["summary"]);
+    assert_eq!(diff.removed_variables, vec!["author"]);
+    assert_eq!(diff.changed_filters, vec![FilterChange {
+        name: "title".to_string(),
+        old: vec![vec![Filter::Text { case: TextCase::Upper }]],
+        new: vec![vec![]],
+    }]);
+    assert!(!diff.is_empty());
+
+    // A template is always the same as itself.
+    assert!(old.diff(&old).is_empty());
+}
+
+#[test]
+fn can_lint_templates_and_markdowns() {
+    let templates = vec![(PathBuf::from("post.html"), [
+        "<h1>{{ £title }}</h1>",
+        "<p>{{ £author | uppercase }}, {{ £date ?? Undated }}</p>",
+        "{{ for £tag in £tags }}{{ £tag.name }}{{ endfor }}",
+        "{{ £summary | shout }}",
+        "<p>{{ £title </p>",
+        "<script>if (a) { b() }}</script>",
+        "{{ if feature \"toc\"",
+    ].join("\n"))];
+    let markdowns = vec![
+        (PathBuf::from("one.md"), ":meta\nauthor = John Doe\n:meta\n# One".to_string()),
+        (PathBuf::from("two.md"), "# Two".to_string()),
+        (PathBuf::from("three.md"), ":meta\nauthor John Doe\n:meta\n# Three".to_string()),
+    ];
+
+    let problems = lint(&templates, &markdowns, &[]);
+    assert_eq!(problems.iter().map(ToString::to_string).collect::<Vec<String>>(), vec![
+        "post.html:2:4: undefined variable '£author', in 'two.md'",
+        "post.html:4:15: unknown filter 'shout'",
+        "post.html:5:14: expected `}}` to close the placeholder",
+        "post.html:7:1: `{{` without a closing `}}`",
+        "three.md:2:1: expected at least one `key = value`",
+    ]);
+
+    // Variables shared by every Markdown are defined.
+    let problems = lint(&templates[..], &markdowns[..2], &[Meta::new("author", "Jane Doe")]);
+    assert!(problems.iter().all(|problem| !problem.message.starts_with("undefined")));
+    assert!(lint(&[], &markdowns[..2], &[]).is_empty());
+}
+
+#[test]
+fn can_create_content_hash_variable() {
+    let markdown = Span::new("# Title\nSome content.");
+    let variables = create_variables(markdown, vec![]).expect("to create variables");
+    let content_hash = variables.get("content_hash").expect("content_hash to exist").to_owned();
+    assert_eq!(content_hash, hash_hex(b"# Title\nSome content."));
+
+    // The same content always has the same hash...
+    let variables = create_variables(Span::new("# Title\nSome content."), vec![]).expect("to create variables");
+    assert_eq!(variables.get("content_hash"), Some(&content_hash));
+
+    // ...but any change to the content will change it.
+    let variables = create_variables(Span::new("# Title\nSome other content."), vec![]).expect("to create variables");
+    assert_ne!(variables.get("content_hash"), Some(&content_hash));
+
+    // Only the variables of the meta section can be unused, not the `content_hash`.
+    let (_, warnings) = render_with_warnings("<h1>{{ £title }}</h1>", "# Title\nSome content.").expect("to render");
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn can_apply_features() {
+    let template = "<main>{{ £content }}</main>\n{{ if feature \"comments\" }}<aside>{{ if feature \"analytics\" }}<script></script>{{ endif }}</aside>{{ else }}<p>No comments.</p>{{ endif }}";
+
+    // Nothing is enabled.
+    let output = apply_features(template, &[]).expect("to apply features");
+    assert_eq!(output, "<main>{{ £content }}</main>\n<p>No comments.</p>");
+
+    // Nested blocks depend on the parent block.
+    let output = apply_features(template, &["analytics".to_string()]).expect("to apply features");
+    assert_eq!(output, "<main>{{ £content }}</main>\n<p>No comments.</p>");
+
+    let output = apply_features(template, &["comments".to_string(), "analytics".to_string()]).expect("to apply features");
+    assert_eq!(output, "<main>{{ £content }}</main>\n<aside><script></script></aside>");
+
+    // Placeholders are left untouched for rendering.
+    let output = apply_features("£{{ £price }}{{", &[]).expect("to apply features");
+    assert_eq!(output, "£{{ £price }}{{");
+}
+
+#[test]
+fn cannot_apply_unbalanced_features() {
+    assert!(apply_features("{{ if feature \"comments\" }}", &[]).is_err());
+    assert!(apply_features("{{ endif }}", &[]).is_err());
+    assert!(apply_features("{{ else }}", &[]).is_err());
+}
+
+#[test]
+fn can_report_template_coverage() {
+    let template = std::env::temp_dir().join("blogs-md-easy-coverage.html");
+    std::fs::write(&template, [
+        "<h1>{{ £title }}</h1>",
+        "{{ if feature \"series\" }}",
+        "<nav>{{ £series }}</nav>",
+        "{{ if feature \"comments\" }}<section></section>{{ endif }}",
+        "{{ else }}",
+        "<p>{{ £subtitle }} by {{ £author }}</p>",
+        "{{ endif }}",
+    ].join("\n")).expect("to write template");
+    let markdowns = vec![
+        (PathBuf::from("one.md"), ":meta\nauthor = John Doe\n:meta\n# One".to_string()),
+        (PathBuf::from("two.md"), "# Two".to_string()),
+    ];
+    let options = BatchOptions { on_missing: MissingVariablePolicy::ReplaceWithEmpty, dry_run: true, ..Default::default() };
+
+    // The nested branch is only reported by its outer branch, and only the
+    // placeholders that every Markdown was missing are unused.
+    let mut coverage = TemplateCoverage::new(&std::fs::read_to_string(&template).expect("to read template"), &options.features).expect("to parse template");
+    render_batch(&[template], &markdowns, &options, |progress| coverage.record(progress), &CancellationToken::new()).expect("to render");
+    assert_eq!(coverage, TemplateCoverage {
+        unused_branches: vec![(2, "{{ if feature \"series\" }}".to_string())],
+        unused_placeholders: vec![(6, "subtitle".to_string())],
+        markdowns: 2,
+    });
+
+    assert!(TemplateCoverage::new("{{ else }}", &[]).is_err());
+}
+
+#[test]
+fn can_partially_render_live_preview() {
+    let template = Template::new("<title>{{ £title }}</title>\n<main>{{ £content }}</main>\n<footer>{{ £title | reverse }} by {{ £author }}</footer>").expect("to parse template");
+    let mut variables = HashMap::from([
+        ("title".to_string(), "Hello".to_string()),
+        ("author".to_string(), "John Doe".to_string()),
+        ("content".to_string(), "Some *content*.".to_string()),
+    ]);
+
+    let mut preview = LivePreview::new(&template, variables.clone()).expect("to render preview");
+    assert_eq!(preview.output(), template.render(&variables).expect("to render template"));
+
+    // Make a few changes of different lengths, the preview must always match a
+    // full render.
+    for (name, value) in [("title", "A much longer title"), ("content", "# Heading"), ("title", ""), ("author", "Jane")] {
+        let mut editor = preview.output().to_string();
+        let changes = preview.set_variable(name, value).expect("to set variable");
+        assert_eq!(changes.len(), template.placeholders().iter().filter(|p| p.name == name).count());
+        for change in changes {
+            editor = replace_substring(&editor, change.start, change.end, &change.output);
+        }
+
+        variables.insert(name.to_string(), value.to_string());
+        let expected = template.render(&variables).expect("to render template");
+        assert_eq!(preview.output(), expected);
+        assert_eq!(editor, expected);
+    }
+
+    // Changing a variable that isn't used changes nothing.
+    assert!(preview.set_variable("unused", "value").expect("to set variable").is_empty());
+}
+
+#[test]
+fn can_render_inline_defaults() {
+    let template = Template::new("<p>{{ £author ?? \"No one\" | uppercase }}</p><p>{{ £editor | default = Staff }}</p><p>{{ £title }}</p>").expect("to parse template");
+    assert_eq!(template.required_variables(), vec!["title"]);
+
+    let variables = HashMap::from([("title".to_string(), "Hello".to_string())]);
+    assert!(template.missing_variables(&variables).is_empty());
+    assert_eq!(template.render(&variables).expect("to render"), "<p>NO ONE</p><p>Staff</p><p>Hello</p>");
+
+    // An empty value is also replaced, but any other is kept.
+    let variables = HashMap::from([
+        ("title".to_string(), "Hello".to_string()),
+        ("author".to_string(), "Jane Doe".to_string()),
+        ("editor".to_string(), " ".to_string()),
+    ]);
+    assert_eq!(template.render(&variables).expect("to render"), "<p>JANE DOE</p><p>Staff</p><p>Hello</p>");
+    // A variable with a default is still used.
+    assert!(template.unused_variables(&[Meta::new("author", "Jane Doe")]).is_empty());
+
+    // The policy is never needed, so doesn't apply.
+    let variables = HashMap::from([("title".to_string(), "Hello".to_string())]);
+    assert_eq!(
+        template.render_with_policy(&variables, &MissingVariablePolicy::KeepPlaceholder).expect("to render"),
+        "<p>NO ONE</p><p>Staff</p><p>Hello</p>"
+    );
+}
+
+#[test]
+fn can_render_variables_as_filter_arguments() {
+    let template = Template::new("<p>{{ £summary | truncate = characters: £excerpt_length, trail: £ellipsis }}</p>").expect("to parse template");
+    assert_eq!(template.required_variables(), vec!["ellipsis", "excerpt_length", "summary"]);
+    assert!(template.unused_variables(&[Meta::new("excerpt_length", "5")]).is_empty());
+
+    let mut variables = HashMap::from([
+        ("summary".to_string(), "Hello, World!".to_string()),
+        ("excerpt_length".to_string(), "5".to_string()),
+        ("ellipsis".to_string(), "…".to_string()),
+    ]);
+    assert_eq!(template.render(&variables).expect("to render"), "<p>Hello…</p>");
+
+    // A missing argument is an error, unless another policy is used, in which
+    // case the filter's default is used.
+    variables.remove("excerpt_length");
+    assert!(template.render(&variables).is_err());
+    assert_eq!(
+        template.render_with_policy(&variables, &MissingVariablePolicy::ReplaceWithEmpty).expect("to render"),
+        "<p>Hello, World!</p>"
+    );
+
+    // Changing an argument re-renders its placeholder.
+    variables.insert("excerpt_length".to_string(), "5".to_string());
+    let mut preview = LivePreview::new(&template, variables).expect("to render");
+    assert_eq!(preview.output(), "<p>Hello…</p>");
+    preview.set_variable("excerpt_length", "7").expect("to set variable");
+    assert_eq!(preview.output(), "<p>Hello, …</p>");
+}
+
+#[test]
+fn can_render_missing_variables_with_policy() {
+    let template = Template::new("<h1>{{ £title }}</h1><p>{{ £author | uppercase }}</p>").expect("to parse template");
+    let variables = HashMap::from([("title".to_string(), "Hello".to_string())]);
+
+    assert!(template.render_with_policy(&variables, &MissingVariablePolicy::Error).is_err());
+    assert_eq!(
+        template.render_with_policy(&variables, &MissingVariablePolicy::ReplaceWithEmpty).expect("to render"),
+        "<h1>Hello</h1><p></p>"
+    );
+    assert_eq!(
+        template.render_with_policy(&variables, &MissingVariablePolicy::KeepPlaceholder).expect("to render"),
+        "<h1>Hello</h1><p>{{ £author | uppercase }}</p>"
+    );
+    // Defaults are passed through the filters.
+    assert_eq!(
+        template.render_with_policy(&variables, &MissingVariablePolicy::UseDefault("Anonymous".to_string())).expect("to render"),
+        "<h1>Hello</h1><p>ANONYMOUS</p>"
+    );
+
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-on-missing");
+    // The maths filters of the template need a number, rather than the default.
+    let markdowns = vec![(PathBuf::from("one.md"), ":meta\nnumber = 1.5\n:meta\n# Title".to_string())];
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        on_missing: "default=TBC".parse().expect("to parse policy"),
+        ..Default::default()
+    };
+    let templates = [PathBuf::from("tests/template.html")];
+    let outputs = render_batch(&templates, &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let output = std::fs::read_to_string(&outputs[0]).expect("to read output");
+    assert!(output.contains("TBC"));
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_render_large_variables() {
+    let content = "Lorem ipsum dolor sit amet. ".repeat(100_000);
+    let template = Template::new("<p>{{ £body }}</p><p>{{ £body | uppercase }}</p><p>{{ £author }}</p>").expect("to parse template");
+    let variables = HashMap::from([
+        ("body".to_string(), content.to_owned()),
+        ("author".to_string(), "John Doe".to_string()),
+    ]);
+
+    let output = template.render(&variables).expect("to render");
+    assert_eq!(output, format!("<p>{}</p><p>{}</p><p>John Doe</p>", content, content.to_uppercase()));
+}
+
+#[test]
+fn can_cache_expensive_filters() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-filter-cache");
+    let templates = [PathBuf::from("tests/template.html")];
+    let markdowns = vec![
+        (PathBuf::from("one.md"), ":meta\nnumber = 1\npackage = one\nauthor = John Doe\nmulti_line = Hi\n:meta\n# One\nFirst".to_string()),
+        (PathBuf::from("two.md"), ":meta\nnumber = 2\npackage = two\nauthor = Jane Doe\nmulti_line = Hi\n:meta\n# Two\nSecond".to_string()),
+    ];
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), ..Default::default() };
+
+    let outputs = render_batch(&templates, &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let first = std::fs::read_to_string(&outputs[0]).expect("to read output");
+    // Each content, and the shared `multi_line`.
+    assert_eq!(options.cache.len(), 3);
+
+    // A rebuild with the same cache renders the same output, without adding to it.
+    let outputs = render_batch(&templates, &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(std::fs::read_to_string(&outputs[0]).expect("to read output"), first);
+    assert_eq!(options.cache.len(), 3);
+
+    // Each edit of a Markdown adds an output, but the cache stays within its
+    // capacity, forgetting the output of the oldest edit.
+    let options = BatchOptions { cache: FilterCache::with_capacity(options.cache.size()), ..options };
+    render_batch(&templates, &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let capacity = options.cache.size();
+    let mut markdowns = markdowns;
+    for edit in 0..10 {
+        markdowns[1].1 = format!(":meta\nnumber = 2\npackage = two\nauthor = Jane Doe\nmulti_line = Hi\n:meta\n# Two\nSecond, edit {}", edit);
+        render_batch(&templates, &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+        assert!(options.cache.size() <= capacity);
+    }
+    assert!(options.cache.len() <= 3);
+
+    let cache = FilterCache::new();
+    assert_eq!(cache.render("Hello".to_string(), &Filter::Reverse).expect("to render filter"), "olleH");
+    assert!(cache.is_empty());
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_report_unused_and_missing_variables() {
+    let template = Template::new("<h1>{{ £title }}</h1><p>{{ £author }}</p>").expect("to parse template");
+    let meta_values = vec![Meta::new("autor", "John Doe")];
+    assert_eq!(template.unused_variables(&meta_values), vec!["autor"]);
+
+    let variables = HashMap::from([("title".to_string(), "Hello".to_string())]);
+    assert_eq!(template.missing_variables(&variables), vec!["author"]);
+
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-missing-variables");
+    let markdowns = vec![(PathBuf::from("one.md"), ":meta\nautor = John Doe\n:meta\n# Title".to_string())];
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        on_missing: MissingVariablePolicy::ReplaceWithEmpty,
+        ..Default::default()
+    };
+    let template = output_dir.join("template.html");
+    std::fs::create_dir_all(&output_dir).expect("to create directory");
+    std::fs::write(&template, "<h1>{{ £title }}</h1><p>{{ £author }}</p>").expect("to write template");
+
+    render_batch(&[template], &markdowns, &options, |progress| {
+        assert_eq!(progress.unused_variables, vec!["autor"]);
+        assert_eq!(progress.missing_variables, vec!["author"]);
+    }, &CancellationToken::new()).expect("to render");
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_render_fragments() {
+    let output = apply_fragments("<aside>{{ render \"fragments/bio.md\" }}</aside>", Path::new("tests")).expect("to render fragments");
+    #[cfg(feature = "markdown")]
+    assert_eq!(output, "<aside><p>Hello, I write about <strong>Rust</strong>.</p></aside>");
+    #[cfg(not(feature = "markdown"))]
+    assert_eq!(output, "<aside>Hello, I write about **Rust**.</aside>");
+
+    assert!(apply_fragments("{{ render \"fragments/missing.md\" }}", Path::new("tests")).is_err());
+
+    // Fragments are relative to the template.
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-fragments");
+    let markdowns = vec![(PathBuf::from("one.md"), "# Title\nWelcome".to_string())];
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), ..Default::default() };
+    let outputs = render_batch(&[PathBuf::from("tests/composite.html")], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let output = std::fs::read_to_string(&outputs[0]).expect("to read output");
+    assert!(output.contains("<aside>"));
+    assert!(output.contains("Rust"));
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_fall_back_to_description() {
+    let declared = ":meta\ndescription = Declared.\n:meta\n# Hello\n\nThe *first* [paragraph](https://example.com).";
+    let excerpt = "# Hello\n\n```rust\nlet code = 1;\n```\n\nThe *first* [paragraph](https://example.com).";
+    let content = format!("# Hello\n\n![](cat.png)\n\n{}", "word ".repeat(40));
+
+    // The meta section, then the excerpt, then the content.
+    let description = |markdown: &str| {
+        let markdown = Span::new(markdown);
+        let (markdown, meta_values) = parse_meta_section(markdown).unwrap_or((markdown, vec![]));
+        create_variables(markdown, meta_values).expect("to create variables").remove("description")
+    };
+    assert_eq!(description(declared).as_deref(), Some("Declared."));
+    assert_eq!(description(excerpt).as_deref(), Some("The first paragraph."));
+    assert_eq!(description(&content), Some(format!("{}…", ["word"; 30].join(" "))));
+    assert_eq!(description("# Hello\n\n> - A quoted item.").as_deref(), Some("A quoted item."));
+    assert_eq!(description("# Hello\n\n-5° outside, +1 inside.").as_deref(), Some("-5° outside, +1 inside."));
+
+    // The order can be changed, or sources left out.
+    let template = std::env::temp_dir().join("blogs-md-easy-description.html");
+    std::fs::write(&template, "{{ £description }}").expect("to write template");
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-description");
+    let render = |markdown: &str, description_from| {
+        let options = BatchOptions { output_dir: Some(output_dir.to_owned()), description_from, ..Default::default() };
+        let markdowns = vec![(PathBuf::from("post.md"), markdown.to_string())];
+        render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new())
+            .map(|outputs| std::fs::read_to_string(&outputs[0]).expect("to read output"))
+    };
+    assert_eq!(render(declared, vec![DescriptionSource::Excerpt, DescriptionSource::Meta]).expect("to render"), "The first paragraph.");
+    let error = render("# Hello\n\nText.", vec![DescriptionSource::Meta]).expect_err("to be missing");
+    assert!(error.to_string().contains("description"));
+
+    let posts = collect_posts(std::slice::from_ref(&template), &[(PathBuf::from("declared.md"), declared.to_string())], &BatchOptions::default()).expect("to collect posts");
+    assert_eq!(posts[0].variables.get("description").unwrap(), "Declared.");
+    std::fs::remove_file(template).ok();
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_include_partials() {
+    let output = apply_includes("{{ include \"partials/card.html\" with title: £post.title, link: £post.url }}", Path::new("tests"), &Syntax::default()).expect("to include partials");
+    assert_eq!(output, "<a href=\"{{ £post.url }}\">{{ £post.title }}</a>\n");
+
+    // Bindings are renamed at once, and nested partials are relative to their parent.
+    let output = apply_includes("{{ include \"partials/item.html\" with label: £href, href: £label }}", Path::new("tests"), &Syntax::default()).expect("to include partials");
+    assert_eq!(output, "<li><a href=\"{{ £label }}\">{{ £href }}</a>\n</li>\n");
+
+    // A variable that isn't bound falls through to the including template, so
+    // `£link` is whatever the template has.
+    let output = apply_includes("{{ include \"partials/card.html\" with title: £post.title }}", Path::new("tests"), &Syntax::default()).expect("to include partials");
+    assert_eq!(output, "<a href=\"{{ £link }}\">{{ £post.title }}</a>\n");
+    let variables = HashMap::from([("post.title".to_string(), "Hello".to_string()), ("link".to_string(), "/home".to_string())]);
+    assert_eq!(Template::new(&output).expect("to parse template").render(&variables).expect("to render"), "<a href=\"/home\">Hello</a>\n");
+
+    // A binding renames its nested variables, but not others that start with its name.
+    let output = apply_includes("{{ include \"partials/byline.html\" with author: £post.author }}", Path::new("tests"), &Syntax::default()).expect("to include partials");
+    assert_eq!(output, "<span>{{ £post.author.name }}, {{ £author_url }}, {{ £site_name }}</span>\n");
+
+    assert!(apply_includes("{{ include \"partials/missing.html\" }}", Path::new("tests"), &Syntax::default()).is_err());
+    assert!(apply_includes("{{ include \"partials/loop.html\" }}", Path::new("tests"), &Syntax::default()).is_err());
+
+    // The same partial can be used within a loop, for each item.
+    let template = "{{ for £post in £posts }}{{ include \"partials/card.html\" with title: £post.title, link: £post.url }}{{ endfor }}";
+    let template = apply_loops(&apply_includes(template, Path::new("tests"), &Syntax::default()).expect("to include partials"), &HashMap::from([("posts".to_string(), 2)])).expect("to apply loops");
+    assert_eq!(template, "<a href=\"{{ £posts.0.url }}\">{{ £posts.0.title }}</a>\n<a href=\"{{ £posts.1.url }}\">{{ £posts.1.title }}</a>\n");
+}
+
+#[test]
+fn can_mirror_input_directories() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-input-dirs");
+    let markdowns = vec![
+        (PathBuf::from("posts/2024/one.md"), "# One".to_string()),
+        (PathBuf::from("posts/two.md"), "# Two".to_string()),
+        (PathBuf::from("drafts/three.md"), "# Three".to_string()),
+    ];
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        input_dirs: vec![PathBuf::from("posts")],
+        ..Default::default()
+    };
+
+    let outputs = render_batch(&[PathBuf::from("tests/composite.html")], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs, vec![
+        output_dir.join("2024/one.html"),
+        output_dir.join("two.html"),
+        // Markdowns outside of the input directories are placed directly inside.
+        output_dir.join("three.html"),
+    ]);
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_skip_unchanged_outputs() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-incremental");
+    let templates = [PathBuf::from("tests/composite.html")];
+    let mut markdowns = vec![
+        (PathBuf::from("one.md"), "# One".to_string()),
+        (PathBuf::from("two.md"), "# Two".to_string()),
+    ];
+    let mut options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        build_cache: Some(BuildCache::new()),
+        ..Default::default()
+    };
+    let skipped = |options: &BatchOptions, markdowns: &[(PathBuf, String)]| {
+        let mut skipped = vec![];
+        render_batch(&templates, markdowns, options, |progress| skipped.push((progress.markdown_path.to_owned(), progress.skipped)), &CancellationToken::new()).expect("to render");
+        // The Markdowns may be rendered in any order.
+        skipped.sort();
+        skipped.into_iter().map(|(_, skipped)| skipped).collect::<Vec<bool>>()
+    };
+
+    assert_eq!(skipped(&options, &markdowns), vec![false, false]);
+    assert_eq!(skipped(&options, &markdowns), vec![true, true]);
+
+    // Only the Markdown that changed is rendered again.
+    markdowns[1].1 = "# Two, again".to_string();
+    assert_eq!(skipped(&options, &markdowns), vec![true, false]);
+
+    // Changing the options renders everything again.
+    options.on_missing = MissingVariablePolicy::ReplaceWithEmpty;
+    assert_eq!(skipped(&options, &markdowns), vec![false, false]);
+
+    // As does deleting an output.
+    std::fs::remove_file(output_dir.join("one.html")).expect("to remove output");
+    assert_eq!(skipped(&options, &markdowns), vec![false, true]);
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_keep_the_modified_time_of_identical_outputs() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-identical-outputs");
+    std::fs::remove_dir_all(&output_dir).ok();
+    let templates = [PathBuf::from("tests/composite.html")];
+    let mut markdowns = vec![(PathBuf::from("one.md"), "# One".to_string())];
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), ..Default::default() };
+    let output = render_batch(&templates, &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render").remove(0);
+
+    // Without the build cache, the output is rendered again, but the same
+    // contents aren't written.
+    let past = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+    std::fs::File::options().write(true).open(&output).expect("to open output").set_modified(past).expect("to set modified time");
+    render_batch(&templates, &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(std::fs::metadata(&output).and_then(|metadata| metadata.modified()).expect("to read modified time"), past);
+
+    markdowns[0].1 = "# One, again".to_string();
+    render_batch(&templates, &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_ne!(std::fs::metadata(&output).and_then(|metadata| metadata.modified()).expect("to read modified time"), past);
+    assert!(!write_if_changed(&output, std::fs::read(&output).expect("to read output").as_slice()).expect("to compare output"));
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_clean_only_the_written_outputs() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-manifest");
+    std::fs::remove_dir_all(&output_dir).ok();
+    std::fs::create_dir_all(&output_dir).expect("to create output directory");
+    std::fs::write(output_dir.join("CNAME"), "example.com").expect("to write CNAME");
+    let templates = [PathBuf::from("tests/composite.html")];
+    let markdowns = vec![
+        (PathBuf::from("one.md"), "# One".to_string()),
+        (PathBuf::from("two.md"), "# Two".to_string()),
+    ];
+    let manifest_path = output_dir.join("manifest");
+    let render = |markdowns: &[(PathBuf, String)]| {
+        let options = BatchOptions {
+            output_dir: Some(output_dir.to_owned()),
+            build_cache: Some(BuildCache::new()),
+            manifest: Some(OutputManifest::load(&manifest_path).expect("to load manifest")),
+            ..Default::default()
+        };
+        render_batch(&templates, markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+        let manifest = options.manifest.expect("a manifest");
+        manifest.save(&manifest_path).expect("to save manifest");
+        manifest
+    };
+
+    assert_eq!(render(&markdowns).paths(), vec![output_dir.join("one.html"), output_dir.join("two.html")]);
+    // The outputs of earlier runs are remembered.
+    let manifest = render(&markdowns[..1]);
+    assert_eq!(manifest.len(), 2);
+
+    assert_eq!(manifest.clean(&output_dir).expect("to clean"), vec![output_dir.join("one.html"), output_dir.join("two.html")]);
+    assert!(manifest.is_empty());
+    assert!(output_dir.join("CNAME").exists());
+    assert!(manifest.clean(&output_dir).expect("to clean").is_empty());
+
+    // Directories that are left empty are removed, up to but not including
+    // the output directory, even when it is left empty itself.
+    std::fs::remove_dir_all(&output_dir).ok();
+    std::fs::create_dir_all(output_dir.join("posts/2024")).expect("to create output directory");
+    std::fs::write(output_dir.join("posts/2024/one.html"), "One").expect("to write output");
+    manifest.insert(output_dir.join("posts/2024/one.html"));
+    assert_eq!(manifest.clean(&output_dir).expect("to clean"), vec![output_dir.join("posts/2024/one.html")]);
+    assert!(!output_dir.join("posts").exists());
+    assert!(output_dir.is_dir());
+
+    // Nor is any directory outside of the output directory.
+    std::fs::create_dir_all(output_dir.join("posts")).expect("to create output directory");
+    std::fs::write(output_dir.join("posts/two.html"), "Two").expect("to write output");
+    manifest.insert(output_dir.join("posts/two.html"));
+    manifest.clean(&output_dir.join("elsewhere")).expect("to clean");
+    assert!(output_dir.join("posts").is_dir());
+
+    // Nor is any file outside of the output directory, even through `..`.
+    std::fs::create_dir_all(output_dir.join("dist")).expect("to create output directory");
+    manifest.insert(output_dir.join("dist/../posts/two.html"));
+    manifest.insert(output_dir.join("posts/two.html"));
+    manifest.insert(output_dir.join("dist"));
+    assert!(manifest.removable(&output_dir.join("dist")).is_empty());
+    assert!(manifest.clean(&output_dir.join("dist")).expect("to clean").is_empty());
+    assert!(output_dir.join("posts/two.html").exists());
+    assert!(output_dir.join("dist").is_dir());
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_choose_title_precedence() {
+    let markdowns = vec![
+        (PathBuf::from("both.md"), ":meta\ntitle = Meta Title\n:meta\n# Markdown Title".to_string()),
+        (PathBuf::from("meta.md"), ":meta\ntitle = Meta Title\n:meta\nNo heading.".to_string()),
+        (PathBuf::from("setext.md"), "Setext Title\n============\nContent.".to_string()),
+        // Without a title or heading, the file name is used.
+        (PathBuf::from("untitled-post.md"), "No heading.".to_string()),
+    ];
+    let template = std::env::temp_dir().join("blogs-md-easy-title.html");
+    std::fs::write(&template, "{{ £title }}").expect("to write template");
+
+    for (precedence, expected) in [(TitlePrecedence::Meta, ["Meta Title", "Meta Title", "Setext Title", "Untitled post"]), (TitlePrecedence::Heading, ["Markdown Title", "Meta Title", "Setext Title", "Untitled post"])] {
+        let options = BatchOptions {
+            output_dir: Some(std::env::temp_dir().join("blogs-md-easy-title")),
+            title_precedence: precedence,
+            ..Default::default()
+        };
+        let outputs = render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+        let outputs = outputs.iter().map(|output| std::fs::read_to_string(output).expect("to read output")).collect::<Vec<String>>();
+        assert_eq!(outputs, expected);
+    }
+
+    // Both are available to the template.
+    let output = render("{{ £title }} | {{ £heading }}", ":meta\ntitle = Meta Title\n:meta\n# Markdown Title").expect("to render");
+    assert_eq!(output, "Meta Title | Markdown Title");
+    assert!(matches!(render("{{ £title }}", "No heading."), Err(RenderError::MissingTitle)));
+
+    assert_eq!(resolve_title("No heading.", Some(Path::new("untitled-post.md")), TitlePrecedence::Meta), Some(("Untitled post".to_string(), TitleSource::Filename)));
+    assert_eq!(resolve_title("Setext Title\n===", None, TitlePrecedence::Meta), Some(("Setext Title".to_string(), TitleSource::Heading)));
+    std::fs::remove_dir_all(std::env::temp_dir().join("blogs-md-easy-title")).ok();
+}
+
+#[test]
+fn can_remove_heading() {
+    let markdowns = vec![
+        (PathBuf::from("heading.md"), "# Markdown Title\nContent.".to_string()),
+        (PathBuf::from("html.md"), "<h1>HTML Title</h1>\n\nContent.".to_string()),
+        (PathBuf::from("meta.md"), ":meta\ntitle = Meta Title\n:meta\nContent.\n# Not the heading".to_string()),
+    ];
+    let template = std::env::temp_dir().join("blogs-md-easy-remove-heading.html");
+    std::fs::write(&template, "<h1>{{ £title }}</h1>{{ £content }}").expect("to write template");
+
+    let options = BatchOptions {
+        output_dir: Some(std::env::temp_dir().join("blogs-md-easy-remove-heading")),
+        remove_heading: true,
+        ..Default::default()
+    };
+    let outputs = render_batch(&[template], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let outputs = outputs.iter().map(|output| std::fs::read_to_string(output).expect("to read output")).collect::<Vec<String>>();
+    #[cfg(feature = "markdown")]
+    assert_eq!(outputs, [
+        "<h1>Markdown Title</h1><p>Content.</p>",
+        "<h1>HTML Title</h1><p>Content.</p>",
+        // Only a heading at the start of the content is removed.
+        "<h1>Meta Title</h1><p>Content.</p>\n<h1>Not the heading</h1>",
+    ]);
+    std::fs::remove_dir_all(std::env::temp_dir().join("blogs-md-easy-remove-heading")).ok();
+}
+
+#[test]
+fn can_embed_video_and_audio() {
+    let markdowns = vec![(PathBuf::from("embeds.md"), "# Embeds\n{{ video \"clips/demo.mp4\" \"A demo\" }}\n\n{{ youtube \"https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=1\" }}".to_string())];
+    let template = std::env::temp_dir().join("blogs-md-easy-embeds.html");
+    std::fs::write(&template, "{{ £content }}").expect("to write template");
+    let options = BatchOptions {
+        output_dir: Some(std::env::temp_dir().join("blogs-md-easy-embeds")),
+        remove_heading: true,
+        ..Default::default()
+    };
+    let outputs = render_batch(std::slice::from_ref(&template), &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let output = std::fs::read_to_string(&outputs[0]).expect("to read output");
+    #[cfg(feature = "markdown")]
+    assert!(output.starts_with("<figure class=\"embed embed-video\"><video src=\"clips/demo.mp4\" controls preload=\"metadata\" playsinline><a href=\"clips/demo.mp4\">A demo</a></video><figcaption>A demo</figcaption></figure>\n<figure class=\"embed embed-youtube\">"));
+    // The player is only loaded once the thumbnail is clicked.
+    assert!(output.contains("<iframe src=\"https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ?autoplay=1\" srcdoc=\""));
+    assert!(output.contains("&lt;img src=&quot;https://i.ytimg.com/vi/dQw4w9WgXcQ/hqdefault.jpg&quot;"));
+
+    let options = BatchOptions { eager_embeds: true, ..options };
+    let outputs = render_batch(&[template], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let output = std::fs::read_to_string(&outputs[0]).expect("to read output");
+    assert!(output.contains("<iframe src=\"https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ\" width=\"560\""));
+    assert!(!output.contains("srcdoc"));
+    std::fs::remove_dir_all(std::env::temp_dir().join("blogs-md-easy-embeds")).ok();
+}
+
+#[test]
+fn can_rewrite_markdown_links() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-md-links");
+    let template = std::env::temp_dir().join("blogs-md-easy-md-links.html");
+    std::fs::write(&template, "{{ £content }}").expect("to write template");
+    let markdowns = vec![
+        (PathBuf::from("posts/part-1.md"), "# Part 1\nRead [part 2](2024/part-2.md#usage), and the [README](../README.md).\n```md\n[part 2](2024/part-2.md)\n```".to_string()),
+        (PathBuf::from("posts/2024/part-2.md"), "# Part 2\n## Usage\nBack to <a href=\"../part-1.md\">part 1</a>.".to_string()),
+    ];
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        input_dirs: vec![PathBuf::from("posts")],
+        remove_heading: true,
+        ..Default::default()
+    };
+
+    let outputs = render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let outputs = outputs.iter().map(|output| std::fs::read_to_string(output).expect("to read output")).collect::<Vec<String>>();
+    // Links to files outside of the batch, and within code, are left alone.
+    #[cfg(feature = "markdown")]
+    assert_eq!(outputs, [
+        "<p>Read <a href=\"2024/part-2.html#usage\">part 2</a>, and the <a href=\"../README.md\">README</a>.</p>\n<pre><code class=\"language-md\">[part 2](2024/part-2.md)\n</code></pre>",
+        "\n<h2>Usage</h2>\n<p>Back to <a href=\"../part-1.html\">part 1</a>.</p>",
+    ]);
+
+    // Hosts that serve clean URLs can be linked to without the extension.
+    let options = BatchOptions { link_extension: Some(String::new()), ..options };
+    let outputs = render_batch(&[template], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let output = std::fs::read_to_string(&outputs[1]).expect("to read output");
+    assert!(output.contains("<a href=\"../part-1\">part 1</a>"));
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_copy_images() {
+    let root = std::env::temp_dir().join("blogs-md-easy-images");
+    std::fs::create_dir_all(root.join("posts/images")).expect("to create images");
+    std::fs::create_dir_all(root.join("assets")).expect("to create assets");
+    let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+    png.extend(640_u32.to_be_bytes());
+    png.extend(480_u32.to_be_bytes());
+    std::fs::write(root.join("posts/images/photo.png"), &png).expect("to write image");
+    std::fs::write(root.join("assets/logo.gif"), b"GIF89a\x20\x00\x10\x00").expect("to write image");
+    let template = root.join("template.html");
+    std::fs::write(&template, "{{ £content }}").expect("to write template");
+
+    let markdowns = vec![(root.join("posts/hello.md"), "# Hello\n![A photo](images/photo.png)\n\n<img src=\"../assets/logo.gif\" alt=\"Logo\">\n\n![Remote](https://example.com/a.png)".to_string())];
+    let options = BatchOptions {
+        output_dir: Some(root.join("dist")),
+        input_dirs: vec![root.join("posts")],
+        remove_heading: true,
+        copy_images: true,
+        image_sizes: true,
+        ..Default::default()
+    };
+    let outputs = render_batch(&[template], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let output = std::fs::read_to_string(&outputs[0]).expect("to read output");
+    #[cfg(feature = "markdown")]
+    assert_eq!(output, [
+        "<p><img src=\"images/photo.png\" alt=\"A photo\" width=\"640\" height=\"480\" /></p>",
+        "<img src=\"assets/logo.gif\" alt=\"Logo\" width=\"32\" height=\"16\">",
+        "<p><img src=\"https://example.com/a.png\" alt=\"Remote\" /></p>",
+    ].join("\n"));
+    assert_eq!(std::fs::read(root.join("dist/images/photo.png")).expect("to copy image"), png);
+    assert!(root.join("dist/assets/logo.gif").is_file());
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[test]
+fn can_inline_assets_into_single_file() {
+    let root = std::env::temp_dir().join("blogs-md-easy-single-file");
+    std::fs::create_dir_all(root.join("posts")).expect("to create posts");
+    std::fs::create_dir_all(root.join("static/fonts")).expect("to create assets");
+    std::fs::write(root.join("posts/photo.png"), b"PNG").expect("to write image");
+    std::fs::write(root.join("static/site.css"), "@font-face { src: url('fonts/serif.woff2') format('woff2'); }").expect("to write stylesheet");
+    std::fs::write(root.join("static/fonts/serif.woff2"), b"FONT").expect("to write font");
+    let template = root.join("template.html");
+    std::fs::write(&template, "<link rel=\"stylesheet\" href=\"/site.css\" media=\"screen\"><link rel=\"stylesheet\" href=\"missing.css\">{{ £content }}").expect("to write template");
+
+    let markdowns = vec![(root.join("posts/hello.md"), "# Hello\n<img src=\"photo.png\" srcset=\"photo-2x.png 2x\" alt=\"A photo\">".to_string())];
+    let options = BatchOptions {
+        output_dir: Some(root.join("dist")),
+        remove_heading: true,
+        single_file: true,
+        asset_dirs: vec![root.join("static")],
+        ..Default::default()
+    };
+    let outputs = render_batch(&[template], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let output = std::fs::read_to_string(&outputs[0]).expect("to read output");
+    assert_eq!(output, [
+        "<style media=\"screen\">@font-face { src: url(\"data:font/woff2;base64,Rk9OVA==\") format('woff2'); }</style>",
+        "<link rel=\"stylesheet\" href=\"missing.css\">",
+        "<img src=\"data:image/png;base64,UE5H\" alt=\"A photo\">",
+    ].join(""));
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[test]
+fn can_add_paragraph_ids() {
+    let template = std::env::temp_dir().join("blogs-md-easy-paragraph-ids.html");
+    std::fs::write(&template, "{{ £content }}").expect("to write template");
+    let options = BatchOptions { paragraph_ids: true, remove_heading: true, ..Default::default() };
+    let ids = |markdown: &str| {
+        let html = render_markdown(&template, Path::new("post.md"), markdown, &options).expect("to render");
+        html.match_indices("<p id=\"").map(|(start, _)| html[start + 7..start + 17].to_string()).collect::<Vec<String>>()
+    };
+
+    // Editing, or adding, a paragraph doesn't change the ids of the others.
+    let before = ids("# Post\nFirst.\n\nSecond\nparagraph.\n\nThird.");
+    let after = ids("# Post\nAn introduction.\n\nFirst, edited.\n\nSecond paragraph.\n\nThird.");
+    assert_eq!(before.len(), 3);
+    assert_eq!(after.len(), 4);
+    assert_ne!(before[0], after[1]);
+    assert_eq!(before[1..], after[2..]);
+}
+
+#[test]
+fn can_link_between_markdowns() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-refs");
+    let template = std::env::temp_dir().join("blogs-md-easy-refs.html");
+    std::fs::write(&template, "{{ £content }}").expect("to write template");
+    let mut markdowns = vec![
+        (PathBuf::from("posts/one.md"), "# One\nSee [usage]({{ ref \"two#usage\" }}), or [below]({{ ref \"#more\" }}).\n## More".to_string()),
+        (PathBuf::from("posts/2024/two.md"), "# Two\n## Usage\nBack to [one]({{ ref \"posts/one\" }}).".to_string()),
+    ];
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        input_dirs: vec![PathBuf::from("posts")],
+        heading_ids: true,
+        ..Default::default()
+    };
+
+    let outputs = render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let outputs = outputs.iter().map(|output| std::fs::read_to_string(output).expect("to read output")).collect::<Vec<String>>();
+    #[cfg(feature = "markdown")]
+    assert_eq!(outputs, [
+        "<h1 id=\"one\">One</h1>\n<p>See <a href=\"2024/two.html#usage\">usage</a>, or <a href=\"#more\">below</a>.</p>\n<h2 id=\"more\">More</h2>",
+        "<h1 id=\"two\">Two</h1>\n<h2 id=\"usage\">Usage</h2>\n<p>Back to <a href=\"../one.html\">one</a>.</p>",
+    ]);
+
+    // A reference to a heading that doesn't exist fails the build.
+    markdowns[0].1 = "# One\n{{ ref \"two#missing\" }}".to_string();
+    let error = render_batch(&[template], &markdowns, &options, |_| {}, &CancellationToken::new()).expect_err("a dangling reference");
+    assert!(error.to_string().starts_with("The reference 'two#missing' does not match any heading in 'posts/2024/two.md'"));
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_link_glossary_terms() {
+    let glossary = Glossary::new(vec![
+        Meta::new("Rust", "A programming language."),
+        Meta::new("Borrow checker", "Checks that references are valid."),
+    ]);
+
+    let markdown = "# Rust\nThe borrow checker of Rust, see [Rust](https://www.rust-lang.org).\n```rust\n// Rust\n```\nRust, and rust.";
+    assert_eq!(glossary.link_terms(markdown, "../glossary.html"), "# Rust\nThe [borrow checker](../glossary.html#borrow-checker) of [Rust](../glossary.html#rust), see [Rust](https://www.rust-lang.org).\n```rust\n// Rust\n```\nRust, and rust.");
+
+    // Terms aren't linked within words, URLs, or between the markers.
+    let markdown = "Trusty https://rust-lang.org\n<!-- glossary off -->\nRust\n<!-- glossary on -->\nRust";
+    assert_eq!(glossary.link_terms(markdown, "glossary.html"), "Trusty https://rust-lang.org\n<!-- glossary off -->\nRust\n<!-- glossary on -->\n[Rust](glossary.html#rust)");
+
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-glossary");
+    let template = std::env::temp_dir().join("blogs-md-easy-glossary.html");
+    std::fs::write(&template, "{{ £content }}").expect("to write template");
+    let markdowns = vec![
+        (PathBuf::from("posts/one.md"), "# One\nWritten in Rust.".to_string()),
+        (PathBuf::from("posts/two.md"), ":meta\nglossary = false\n:meta\n# Two\nWritten in Rust.".to_string()),
+    ];
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        input_dirs: vec![PathBuf::from("posts")],
+        glossary: Some(glossary),
+        ..Default::default()
+    };
+
+    let mut unused_variables = vec![];
+    let outputs = render_batch(&[template], &markdowns, &options, |progress| unused_variables.extend(progress.unused_variables.to_owned()), &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs.last(), Some(&output_dir.join("glossary.html")));
+    assert!(unused_variables.is_empty());
+    let outputs = outputs.iter().map(|output| std::fs::read_to_string(output).expect("to read output")).collect::<Vec<String>>();
+    #[cfg(feature = "markdown")]
+    assert_eq!(outputs, [
+        "<h1>One</h1>\n<p>Written in <a href=\"glossary.html#rust\">Rust</a>.</p>",
+        "<h1>Two</h1>\n<p>Written in Rust.</p>",
+        "<h1>Glossary</h1>\n<h2 id=\"borrow-checker\">Borrow checker</h2>\n<p>Checks that references are valid.</p>\n<h2 id=\"rust\">Rust</h2>\n<p>A programming language.</p>",
+    ]);
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_render_index() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-index");
+    let template = std::env::temp_dir().join("blogs-md-easy-index-post.html");
+    let index = std::env::temp_dir().join("blogs-md-easy-index.html");
+    std::fs::write(&template, "{{ £content }}").expect("to write template");
+    std::fs::write(&index, "<h1>{{ £site }}</h1>\n<p>{{ £posts.count }} posts, the latest is {{ £posts.0.title }}.</p>\n<ul>\n{{ for £post in £posts }}  <li><a href=\"{{ £post.url }}\">{{ £post.title | uppercase }}</a> {{ £post.excerpt }}</li>\n{{ endfor }}</ul>").expect("to write index");
+    let markdowns = vec![
+        (PathBuf::from("posts/old.md"), ":meta\ndate = 2023-05-01\n:meta\n# Old\nAn old post.\n\nMore.".to_string()),
+        (PathBuf::from("posts/new.md"), ":meta\ndate = 2024-05-01\nexcerpt = A summary.\n:meta\n# New\n```\ncode\n```\n\nA new post.".to_string()),
+        (PathBuf::from("posts/notes/undated.md"), "# Undated\n\n## Heading\n\nSome notes.".to_string()),
+    ];
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        input_dirs: vec![PathBuf::from("posts")],
+        variables: vec![Meta::new("site", "My Blog")],
+        ..Default::default()
+    };
+
+    let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+    let output_paths = render_index(&index, &posts, &options).expect("to render index");
+    assert_eq!(output_paths, vec![output_dir.join("blogs-md-easy-index.html")]);
+    assert_eq!(std::fs::read_to_string(&output_paths[0]).expect("to read index"), [
+        "<h1>My Blog</h1>",
+        "<p>3 posts, the latest is New.</p>",
+        "<ul>",
+        "  <li><a href=\"new.html\">NEW</a> A summary.</li>",
+        "  <li><a href=\"old.html\">OLD</a> An old post.</li>",
+        "  <li><a href=\"notes/undated.html\">UNDATED</a> Some notes.</li>",
+        "</ul>",
+    ].join("\n"));
+
+    // The posts can be split across pages.
+    std::fs::write(&index, "{{ £page }}/{{ £pages }} [{{ £prev_page }}] [{{ £next_page }}] {{ £posts.count }} of {{ £posts.total }}:{{ for £post in £posts }} {{ £post.title }}{{ endfor }}").expect("to write index");
+    let options = BatchOptions { per_page: Some(2), ..options };
+    let output_paths = render_index(&index, &posts, &options).expect("to render index");
+    assert_eq!(output_paths, vec![output_dir.join("blogs-md-easy-index.html"), output_dir.join("blogs-md-easy-index-2.html")]);
+    let pages = output_paths.iter().map(|output| std::fs::read_to_string(output).expect("to read page")).collect::<Vec<String>>();
+    assert_eq!(pages, [
+        "1/2 [] [blogs-md-easy-index-2.html] 2 of 3: New Old",
+        "2/2 [blogs-md-easy-index.html] [] 1 of 3: Undated",
+    ]);
+
+    // Loops must be closed, and over a known collection.
+    std::fs::write(&index, "{{ for £post in £posts }}{{ £post.title }}").expect("to write index");
+    assert!(render_index(&index, &posts, &options).is_err());
+    std::fs::write(&index, "{{ for £tag in £tags }}{{ endfor }}").expect("to write index");
+    assert!(render_index(&index, &posts, &options).is_err());
+
+    // Dates are sorted by when they are, rather than as text.
+    let markdowns = vec![
+        (PathBuf::from("posts/may.md"), ":meta
+date = 2024-5-1
+:meta
+# May".to_string()),
+        (PathBuf::from("posts/december.md"), ":meta
+date = 2024-12-01
+:meta
+# December".to_string()),
+        (PathBuf::from("posts/june.md"), ":meta
+date = 2024-06-01T09:00
+:meta
+# June".to_string()),
+    ];
+    let options = BatchOptions { per_page: None, ..options };
+    let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+    assert_eq!(posts.iter().map(|post| post.variables["title"].as_str()).collect::<Vec<&str>>(), ["December", "June", "May"]);
+
+    // With several templates, the posts link to the outputs of the first, as
+    // they are written.
+    let amp = std::env::temp_dir().join("blogs-md-easy-index-amp.html");
+    std::fs::write(&amp, "{{ £title }}").expect("to write template");
+    let templates = [template.to_owned(), amp.to_owned()];
+    let outputs = render_batch(&templates, &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let posts = collect_posts(&templates, &markdowns, &options).expect("to collect posts");
+    assert!(posts.iter().all(|post| outputs.contains(&post.output_path)), "{:?}", posts);
+    assert_eq!(posts[0].output_path, output_dir.join("blogs-md-easy-index-post-december.html"));
+    std::fs::remove_file(amp).ok();
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_render_archives() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-archives");
+    let template = std::env::temp_dir().join("blogs-md-easy-archives-post.html");
+    let archive = std::env::temp_dir().join("blogs-md-easy-archive.html");
+    std::fs::write(&template, "{{ £content }}").expect("to write template");
+    std::fs::write(&archive, "{{ £archive.title }} ({{ £archive.year }}/{{ £archive.month }}):{{ for £post in £posts }} <a href=\"{{ £post.url }}\">{{ £post.title }}</a>{{ endfor }}").expect("to write archive");
+    let markdowns = vec![
+        (PathBuf::from("posts/old.md"), ":meta\ndate = 2023-12-01\n:meta\n# Old".to_string()),
+        (PathBuf::from("posts/new.md"), ":meta\ndate = 2024-05-20\n:meta\n# New".to_string()),
+        (PathBuf::from("posts/newer.md"), ":meta\npublish_date = 2024-05-21\n:meta\n# Newer".to_string()),
+        (PathBuf::from("posts/spring.md"), ":meta\ndate = 2024-03-01\n:meta\n# Spring".to_string()),
+        (PathBuf::from("posts/undated.md"), "# Undated".to_string()),
+    ];
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        input_dirs: vec![PathBuf::from("posts")],
+        ..Default::default()
+    };
+
+    let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+    let output_paths = render_archives(&archive, &posts, &options).expect("to render archives");
+    assert_eq!(output_paths, vec![
+        output_dir.join("2024/index.html"),
+        output_dir.join("2024/05/index.html"),
+        output_dir.join("2024/03/index.html"),
+        output_dir.join("2023/index.html"),
+        output_dir.join("2023/12/index.html"),
+    ]);
+    let pages = output_paths.iter().map(|output| std::fs::read_to_string(output).expect("to read archive")).collect::<Vec<String>>();
+    assert_eq!(pages, [
+        "2024 (2024/): <a href=\"../newer.html\">Newer</a> <a href=\"../new.html\">New</a> <a href=\"../spring.html\">Spring</a>",
+        "May 2024 (2024/05): <a href=\"../../newer.html\">Newer</a> <a href=\"../../new.html\">New</a>",
+        "March 2024 (2024/03): <a href=\"../../spring.html\">Spring</a>",
+        "2023 (2023/): <a href=\"../old.html\">Old</a>",
+        "December 2023 (2023/12): <a href=\"../../old.html\">Old</a>",
+    ]);
+
+    // A date that can't be placed is an error.
+    let markdowns = vec![(PathBuf::from("posts/bad.md"), ":meta\ndate = May 2024\n:meta\n# Bad".to_string())];
+    let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+    assert!(render_archives(&archive, &posts, &options).is_err());
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_summarise_stats() {
+    let markdowns = vec![
+        (PathBuf::from("posts/one.md"), ":meta\ndate = 2024-01-01\ntags = [rust, nom]\n:meta\n# One\nThe first post.".to_string()),
+        (PathBuf::from("posts/two.md"), ":meta\ndate = 2024-03-01\ntags = [rust]\n:meta\n# Two\nThe second post, which is longer.".to_string()),
+        (PathBuf::from("posts/three.md"), ":meta\ndate = 2023-06-01\n:meta\n# Three".to_string()),
+    ];
+    let templates = vec![
+        Template::new("<h1>{{ £title | uppercase }}</h1>{{ £content }}").expect("to parse template"),
+        Template::new("<title>{{ £title | uppercase | reverse }}</title>").expect("to parse template"),
+    ];
+
+    let stats = Stats::new(&markdowns, &templates);
+    assert_eq!(stats.to_string(), [
+        "Posts  Words",
+        "3      15 (5 on average)",
+        "",
+        "Year  Posts",
+        "2024  2",
+        "2023  1",
+        "",
+        "Tag   Posts",
+        "rust  2",
+        "nom   1",
+        "",
+        "Longest         Words",
+        "posts/two.md    8",
+        "posts/one.md    5",
+        "posts/three.md  2",
+        "",
+        "Filter                                                                                                                                                             Uses",
+        "Text { case: Upper }                                                                                                                                               2",
+        "Markdown { tables: false, strikethrough: false, tasklists: false, footnotes: false, smart_punctuation: false, hard_breaks: false, html: true, heading_offset: 0 }  1",
+        "Reverse                                                                                                                                                            1",
+        "",
+        "Variable  Uses",
+        "title     2",
+        "content   1",
+        "",
+        "Meta key  Posts, templates",
+        "date      3, 0",
+        "tags      2, 0",
+        "",
+    ].join("\n"));
+}
+
+#[test]
+fn can_find_unused_meta_keys() {
+    let markdowns = vec![
+        (PathBuf::from("one.md"), ":meta\nlayout = post\ncolour = red\ncolour = blue\nsummary = One\ndraft = false\n:meta\n# One".to_string()),
+        (PathBuf::from("two.md"), ":meta\nlayout = post\ncolour = green\nsummary = Two\ndraft = false\n:meta\n# Two".to_string()),
+    ];
+    let templates = vec![
+        Template::new("<h1>{{ £title }}</h1>{{ £content }}").expect("to parse template"),
+        Template::new("<meta name=\"description\" content=\"{{ £summary }}\">").expect("to parse template"),
+    ];
+    let stats = Stats::new(&markdowns, &templates);
+
+    assert_eq!(stats.keys["colour"], KeyUsage { posts: 2, templates: 0 });
+    assert_eq!(stats.keys["summary"], KeyUsage { posts: 2, templates: 1 });
+    // Drafts are read by the build, rather than the templates.
+    assert_eq!(stats.unused_keys(), vec!["colour", "layout"]);
+    assert!(stats.to_string().contains("layout    2, 0 (unused)"));
+
+    // Without templates, nothing can be known to be unused.
+    assert!(Stats::new(&markdowns, &[]).unused_keys().is_empty());
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn can_load_blogroll() {
+    let blogroll = Blogroll::load(Path::new("tests/blogroll.toml")).expect("to load blogroll");
+    assert_eq!(blogroll.to_opml("Blogroll"), [
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>",
+        "<opml version=\"2.0\">",
+        "  <head>",
+        "    <title>Blogroll</title>",
+        "  </head>",
+        "  <body>",
+        "    <outline type=\"link\" text=\"amy\" url=\"https://amy.example\"/>",
+        "    <outline type=\"rss\" text=\"Jane&#39;s Blog\" title=\"Jane&#39;s Blog\" xmlUrl=\"https://jane.example/feed.xml\" htmlUrl=\"https://jane.example\"/>",
+        "  </body>",
+        "</opml>",
+        "",
+    ].join("\n"));
+
+    let template = Template::new("{{ £blogroll }}\n<a href=\"{{ £webring.rust.prev }}\">Previous</a> {{ £webring.rust.title }} <a href=\"{{ £webring.rust.next }}\">Next</a>").expect("to parse template");
+    let variables = blogroll.variables().into_iter().map(|meta| (meta.key, meta.value)).collect::<HashMap<String, String>>();
+    assert_eq!(template.render(&variables).expect("to render"), [
+        "<aside class=\"blogroll\" aria-label=\"Blogroll\">",
+        "<ul>",
+        "<li><a href=\"https://amy.example\">amy</a></li>",
+        "<li><a href=\"https://jane.example\">Jane&#39;s Blog</a> Notes on parsers &amp; compilers.</li>",
+        "</ul>",
+        "</aside>",
+        "<a href=\"https://prev.example\">Previous</a> The Rust Webring <a href=\"https://next.example\">Next</a>",
+    ].join("\n"));
+
+    // Every blog needs a url, and only blogs and rings are expected.
+    assert!(Blogroll::new(vec![Meta::new("blogs.amy.title", "Amy")]).is_err());
+    assert!(Blogroll::new(vec![Meta::new("friends.amy.url", "https://amy.example")]).is_err());
+}
+
+#[test]
+fn can_skip_drafts() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-drafts");
+    let template = std::env::temp_dir().join("blogs-md-easy-drafts.html");
+    std::fs::write(&template, "{{ £title }}").expect("to write template");
+    let markdowns = vec![
+        (PathBuf::from("published.md"), ":meta\ndraft = false\n:meta\n# Published".to_string()),
+        (PathBuf::from("draft.md"), ":meta\ndraft = true\n:meta\n# Draft".to_string()),
+    ];
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), ..Default::default() };
+
+    let outputs = render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs, vec![output_dir.join("published.html")]);
+    assert_eq!(collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts").len(), 1);
+
+    let options = BatchOptions { include_drafts: true, ..options };
+    let outputs = render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs, vec![output_dir.join("published.html"), output_dir.join("draft.html")]);
+    assert_eq!(collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts").len(), 2);
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_hide_private_markdowns() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-private");
+    let template = std::env::temp_dir().join("blogs-md-easy-private.html");
+    std::fs::write(&template, "{{ £title }}").expect("to write template");
+    let markdowns = vec![
+        (PathBuf::from("public.md"), "# Public".to_string()),
+        (PathBuf::from("private.md"), ":meta\nvisibility = private\n:meta\n# Private".to_string()),
+    ];
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), include_drafts: true, ..Default::default() };
+
+    // The page is still written, but isn't listed.
+    let outputs = render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs, vec![output_dir.join("public.html"), output_dir.join("private.html")]);
+    let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+    assert_eq!(posts.iter().map(|post| post.variables["title"].as_str()).collect::<Vec<&str>>(), ["Public"]);
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_create_path_variables() {
+    let template = std::env::temp_dir().join("blogs-md-easy-path-variables.html");
+    std::fs::write(&template, "{{ £filename }} {{ £slug }} {{ £path }}").expect("to write template");
+
+    let html = render_markdown(&template, Path::new("posts/2024-05-01 Hello World.md"), "# Hello", &BatchOptions::default()).expect("to render");
+    assert_eq!(html, "2024-05-01 Hello World.md hello-world posts/2024-05-01 Hello World.md");
+
+    // The meta section can choose its own slug.
+    let html = render_markdown(&template, Path::new("posts/hello.md"), ":meta\nslug = greeting\n:meta\n# Hello", &BatchOptions::default()).expect("to render");
+    assert_eq!(html, "hello.md greeting posts/hello.md");
+
+    let post = Post::new(PathBuf::from("posts/hello.md"), "# Hello", PathBuf::from("hello.html"), TitlePrecedence::Meta).expect("to summarise");
+    assert_eq!(post.variables["slug"], "hello");
+}
+
+#[test]
+fn can_hide_noindex_markdowns() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-noindex");
+    let template = std::env::temp_dir().join("blogs-md-easy-noindex.html");
+    std::fs::write(&template, "<head></head>{{ £title }}").expect("to write template");
+    let markdowns = vec![
+        (PathBuf::from("public.md"), "# Public".to_string()),
+        (PathBuf::from("hidden.md"), ":meta\nnoindex = true\n:meta\n# Hidden".to_string()),
+    ];
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), ..Default::default() };
+
+    let mut progress = vec![];
+    let outputs = render_batch(&[template.to_owned()], &markdowns, &options, |p| progress.push((p.noindex, p.unused_variables.to_owned())), &CancellationToken::new()).expect("to render");
+    assert_eq!(progress, [(false, vec![]), (true, vec![])]);
+    assert_eq!(std::fs::read_to_string(&outputs[0]).expect("to read output"), "<head></head>Public");
+    assert_eq!(std::fs::read_to_string(&outputs[1]).expect("to read output"), "<head><meta name=\"robots\" content=\"noindex\">\n</head>Hidden");
+
+    // A template without a `<head>`, such as a feed, is left without the tag.
+    let feed = std::env::temp_dir().join("blogs-md-easy-noindex.xml");
+    std::fs::write(&feed, "<rss><item>{{ £title }}</item></rss>").expect("to write template");
+    let outputs = render_batch(std::slice::from_ref(&feed), &markdowns[1..], &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(std::fs::read_to_string(&outputs[0]).expect("to read output"), "<rss><item>Hidden</item></rss>");
+    std::fs::remove_file(feed).ok();
+
+    let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+    assert_eq!(posts.iter().map(|post| post.variables["title"].as_str()).collect::<Vec<&str>>(), ["Public"]);
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_derive_publish_date() {
+    let template = std::env::temp_dir().join("blogs-md-easy-publish-date.html");
+    std::fs::write(&template, "{{ £publish_date }}").expect("to write template");
+    let modified = std::env::temp_dir().join("blogs-md-easy-modified.md");
+    std::fs::write(&modified, "# Modified").expect("to write markdown");
+    let markdowns = vec![
+        (PathBuf::from("2023-01-02-older.md"), "# Older".to_string()),
+        (PathBuf::from("2024-05-01-newer.md"), "# Newer".to_string()),
+        (PathBuf::from("2024-06-01.md"), ":meta\npublish_date = 2020-01-01\n:meta\n# Declared".to_string()),
+        (PathBuf::from("20240501-undated.md"), "# Undated".to_string()),
+    ];
+
+    // The meta section's date is kept, and others are only from the file name.
+    let options = BatchOptions { publish_date_from: vec![DateSource::FileName], ..Default::default() };
+    let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+    let dates = posts.iter().map(|post| post.variables.get("publish_date").map(String::as_str)).collect::<Vec<Option<&str>>>();
+    assert_eq!(dates, [Some("2023-01-02"), Some("2024-05-01"), Some("2020-01-01"), None]);
+
+    let html = render_markdown(&template, Path::new("2024-05-01-newer.md"), "# Newer", &options).expect("to render");
+    assert_eq!(html, "2024-05-01");
+
+    let options = BatchOptions { publish_date_from: vec![DateSource::FileName, DateSource::Modified], ..Default::default() };
+    let html = render_markdown(&template, &modified, "# Modified", &options).expect("to render");
+    assert_eq!(html.len(), "YYYY-MM-DD".len());
+    assert!(html.starts_with("20"));
+    std::fs::remove_file(modified).ok();
+}
+
+#[test]
+fn can_build_as_of_a_date() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-as-of");
+    let template = std::env::temp_dir().join("blogs-md-easy-as-of.html");
+    std::fs::write(&template, "{{ £title }}").expect("to write template");
+    let markdowns = vec![
+        (PathBuf::from("2023-01-02-older.md"), "# Older".to_string()),
+        (PathBuf::from("newer.md"), ":meta\ndate = 2023-06-01\n:meta\n# Newer".to_string()),
+        (PathBuf::from("future.md"), ":meta\npublish_date = 2023-06-02T09:00\n:meta\n# Future".to_string()),
+        (PathBuf::from("undated.md"), "# Undated".to_string()),
+    ];
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        publish_date_from: vec![DateSource::FileName],
+        as_of: Some("2023-06-01".to_string()),
+        ..Default::default()
+    };
+
+    let outputs = render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs, vec![output_dir.join("2023-01-02-older.html"), output_dir.join("newer.html"), output_dir.join("undated.html")]);
+    let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+    assert_eq!(posts.len(), 3);
+    assert!(!is_published_by(Path::new("future.md"), &markdowns[2].1, &options));
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_render_markdown_without_writing() {
+    let template = std::env::temp_dir().join("blogs-md-easy-stdin.html");
+    std::fs::write(&template, "<title>{{ £title }}</title>{{ if feature \"site\" }}{{ £site }}{{ endif }}{{ £content | markdown }}").expect("to write template");
+    let options = BatchOptions {
+        features: vec!["site".to_string()],
+        variables: vec![Meta::new("site", "My Blog")],
+        heading_ids: true,
+        ..Default::default()
+    };
+
+    // Links can only refer to the Markdown itself.
+    let markdown = "# Hello\n## Getting Started\n{{ ref \"stdin#getting-started\" }}";
+    let html = render_markdown(&template, Path::new("stdin.md"), markdown, &options).expect("to render");
+    assert_eq!(html, "<title>Hello</title>My Blog<h1 id=\"hello\">Hello</h1>\n<h2 id=\"getting-started\">Getting Started</h2>\n<p>stdin.html#getting-started</p>");
+    assert!(!Path::new("stdin.html").exists());
+
+    assert!(render_markdown(&template, Path::new("stdin.md"), "# Hello\n{{ ref \"other\" }}", &options).is_err());
+}
+
+#[test]
+fn can_generate_sample_markdown() {
+    let template = std::env::temp_dir().join("blogs-md-easy-sample.html");
+    std::fs::write(&template, "<title>{{ £title }}</title>{{ if feature \"site\" }}{{ £site }}{{ else }}{{ £author }}{{ endif }}{{ £content | markdown }}").expect("to write template");
+
+    // Every branch of a feature is filled, as it may be enabled or not.
+    let markdown = sample_markdown(&template).expect("to generate sample");
+    assert_eq!(markdown, ":meta\nauthor = \"[author]\"\nsite = \"[site]\"\n:meta\n# [title]\n\n[content]\n");
+
+    let html = render_markdown(&template, Path::new("sample.md"), &markdown, &BatchOptions::default()).expect("to render");
+    assert_eq!(html, "<title>[title]</title>[author]<h1>[title]</h1>\n<p>[content]</p>");
+}
+
+#[test]
+fn can_edit_meta_sections() {
+    let markdown = "<meta>\n  # Who wrote it.\n  £author = John Doe\n  summary = \"Line one\nline two\"\n</meta>\n# Hello";
+    let set = |key: &str, value: &str| MetaEdit::Set { key: key.to_string(), value: value.to_string() };
+    let remove = |key: &str| MetaEdit::Remove { key: key.to_string() };
+
+    // Quoted values are replaced entirely, and new keys keep the indentation.
+    let edited = edit_meta(markdown, &set("summary", "One line")).expect("to set summary");
+    assert_eq!(edited, "<meta>\n  # Who wrote it.\n  £author = John Doe\n  summary = One line\n</meta>\n# Hello");
+    let edited = edit_meta(&edited, &set("about", "Line one\n\"Line\" two")).expect("to set about");
+    assert_eq!(edited, "<meta>\n  # Who wrote it.\n  £author = John Doe\n  summary = One line\n  about = \"Line one\n\\\"Line\\\" two\"\n</meta>\n# Hello");
+    let (_, meta) = parse_meta_section(Span::new(&edited)).expect("to parse meta section");
+    assert_eq!(meta.len(), 3);
+
+    // Removing every key leaves the comment, until it is the only line.
+    let edited = edit_meta(markdown, &remove("author")).expect("to remove author");
+    let edited = edit_meta(&edited, &remove("summary")).expect("to remove summary");
+    assert_eq!(edited, "<meta>\n  # Who wrote it.\n</meta>\n# Hello");
+    assert_eq!(edit_meta(&edited, &set("draft", "true")).expect("to set draft"), "<meta>\n  # Who wrote it.\ndraft = true\n</meta>\n# Hello");
+    assert_eq!(edit_meta(":meta\r\ndraft = true\r\n:meta\r\n# Hello", &remove("draft")).expect("to remove draft"), "# Hello");
+
+    // A key can't be renamed to one that already exists.
+    let rename = MetaEdit::Rename { from: "author".to_string(), to: "summary".to_string() };
+    assert!(edit_meta(markdown, &rename).is_err());
+    assert!(edit_meta(markdown, &set("1st", "true")).is_err());
+    assert!(edit_meta("---\ntitle: Hello\n---\n", &set("draft", "true")).is_err());
+    assert_eq!(edit_meta("# Hello", &remove("draft")).expect("to remove nothing"), "# Hello");
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn can_write_json_outputs() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-json");
+    let template = std::env::temp_dir().join("blogs-md-easy-json.html");
+    std::fs::write(&template, "<h1>{{ £title }}</h1>").expect("to write template");
+    let markdowns = vec![(PathBuf::from("posts/hello.md"), ":meta\nauthor = John Doe\nauthor.url = https://example.com\n:meta\n# Hello".to_string())];
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), format: OutputFormat::Json, on_missing: MissingVariablePolicy::ReplaceWithEmpty, ..Default::default() };
+
+    let outputs = render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs, vec![output_dir.join("hello.json")]);
+    let json = serde_json::from_str::<serde_json::Value>(&std::fs::read_to_string(&outputs[0]).expect("to read output")).expect("to parse output");
+    assert_eq!(json, serde_json::json!({
+        "path": "posts/hello.md",
+        "title": "Hello",
+        "meta": {
+            "author": "John Doe",
+            "author.url": "https://example.com",
+        },
+        "html": "<h1>Hello</h1>",
+    }));
+
+    // A single Markdown is rendered in the same way.
+    let output = render_markdown(&template, Path::new("posts/hello.md"), &markdowns[0].1, &options).expect("to render");
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&output).expect("to parse output"), json);
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_dry_run() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-dry-run");
+    let template = std::env::temp_dir().join("blogs-md-easy-dry-run.html");
+    std::fs::write(&template, "{{ £title }}").expect("to write template");
+    let markdowns = vec![(PathBuf::from("post.md"), "# Post".to_string())];
+    let build_cache = BuildCache::new();
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), build_cache: Some(build_cache.clone()), dry_run: true, ..Default::default() };
+
+    let mut progresses = vec![];
+    let outputs = render_batch(&[template.to_owned()], &markdowns, &options, |progress| progresses.push(progress.to_owned()), &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs, vec![output_dir.join("post.html")]);
+    assert!(!progresses[0].skipped);
+    // Nothing is written, and the output isn't remembered as being up to date.
+    assert!(!output_dir.exists());
+    assert_eq!(build_cache, BuildCache::new());
+
+    let index = std::env::temp_dir().join("blogs-md-easy-dry-run-index.html");
+    std::fs::write(&index, "{{ £posts.count }}").expect("to write index");
+    let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+    assert_eq!(render_index(&index, &posts, &options).expect("to render index"), vec![output_dir.join("blogs-md-easy-dry-run-index.html")]);
+    assert!(!output_dir.exists());
+}
+
+#[test]
+fn can_render_webmentions() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-webmentions");
+    let template = std::env::temp_dir().join("blogs-md-easy-webmentions.html");
+    std::fs::write(&template, "<html><head></head><body>{{ £title }}{{ £webmentions }}</body></html>").expect("to write template");
+    let markdowns = vec![
+        (PathBuf::from("posts/hello.md"), "# Hello".to_string()),
+        (PathBuf::from("posts/quiet.md"), "# Quiet".to_string()),
+    ];
+
+    let mentions = vec![
+        Webmention {
+            source: "https://jane.example/replies/1".to_string(),
+            target: "https://example.com/hello.html".to_string(),
+            kind: "in-reply-to".to_string(),
+            author: "Jane".to_string(),
+            author_url: Some("https://jane.example".to_string()),
+            published: Some("2024-05-02T10:00:00Z".to_string()),
+            content: Some("Great post & thanks!".to_string()),
+        },
+        Webmention {
+            source: "https://amy.example/likes/1".to_string(),
+            target: "https://example.com/hello/".to_string(),
+            kind: "like-of".to_string(),
+            author: "Amy".to_string(),
+            author_url: None,
+            published: Some("2024-05-01T10:00:00Z".to_string()),
+            content: None,
+        },
+    ];
+    #[cfg(feature = "webmentions")]
+    assert_eq!(Webmentions::load("tests/webmentions.json").expect("to load webmentions")[..2], mentions[..]);
+
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        input_dirs: vec![PathBuf::from("posts")],
+        webmentions: Some(Webmentions {
+            endpoint: Some("https://webmention.io/example.com/webmention".to_string()),
+            pingback: Some("https://webmention.io/example.com/xmlrpc".to_string()),
+            mentions,
+            markup: ListMarkup::new("section", "", "Mentions", "ul"),
+        }),
+        ..Default::default()
+    };
+    let outputs = render_batch(&[template], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let outputs = outputs.iter().map(|output| std::fs::read_to_string(output).expect("to read output")).collect::<Vec<String>>();
+    let head = "<html><head><link rel=\"webmention\" href=\"https://webmention.io/example.com/webmention\">\n<link rel=\"pingback\" href=\"https://webmention.io/example.com/xmlrpc\">\n</head>";
+    assert_eq!(outputs, [
+        format!("{head}<body>Hello<section aria-label=\"Mentions\">\n<ul>\n<li class=\"webmention like-of\"><a href=\"https://amy.example/likes/1\">Amy</a> <a href=\"https://amy.example/likes/1\">liked this</a></li>\n<li class=\"webmention in-reply-to\"><a href=\"https://jane.example\">Jane</a> <a href=\"https://jane.example/replies/1\">replied</a>: Great post &amp; thanks!</li>\n</ul>\n</section></body></html>"),
+        format!("{head}<body>Quiet</body></html>"),
+    ]);
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_attribute_fediverse_creator() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-fediverse");
+    let template = std::env::temp_dir().join("blogs-md-easy-fediverse.html");
+    std::fs::write(&template, "<head></head>{{ £title }} by {{ £fediverse_creator }}").expect("to write template");
+    let markdowns = vec![
+        (PathBuf::from("jane.md"), ":meta\nauthor = Jane Doe\ndate = 2024-02-01\ntags = [rust, nom]\n:meta\n# Jane\nBy Jane.".to_string()),
+        (PathBuf::from("guest.md"), ":meta\nauthor = Guest\ndate = 2024-01-01\n:meta\n# Guest".to_string()),
+        (PathBuf::from("own.md"), ":meta\nfediverse_creator = @own@example.social\n:meta\n# Own".to_string()),
+    ];
+    let fediverse = Fediverse::new(vec![
+        Meta::new("creator", "@blog@example.social"),
+        Meta::new("authors.Jane Doe", "@jane@example.social"),
+    ]).expect("to parse accounts");
+    #[cfg(feature = "toml")]
+    assert_eq!(Fediverse::load(Path::new("tests/fediverse.toml")).expect("to load accounts"), fediverse);
+
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), fediverse: Some(fediverse.to_owned()), ..Default::default() };
+    let outputs = render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let outputs = outputs.iter().map(|output| std::fs::read_to_string(output).expect("to read output")).collect::<Vec<String>>();
+    assert_eq!(outputs, [
+        "<head><meta name=\"fediverse:creator\" content=\"@jane@example.social\">\n</head>Jane by @jane@example.social",
+        "<head><meta name=\"fediverse:creator\" content=\"@blog@example.social\">\n</head>Guest by @blog@example.social",
+        "<head><meta name=\"fediverse:creator\" content=\"@own@example.social\">\n</head>Own by @own@example.social",
+    ]);
+
+    #[cfg(feature = "json")]
+    {
+        let posts = collect_posts(&[template.to_owned()], &markdowns[..2], &options).expect("to collect posts");
+        let json = serde_json::from_str::<serde_json::Value>(&fediverse.to_json(&posts, &output_dir, "https://example.com")).expect("to parse export");
+        assert_eq!(json[0], serde_json::json!({
+            "url": "https://example.com/jane.html",
+            "title": "Jane",
+            "summary": "By Jane.",
+            "published": "2024-02-01",
+            "author": "Jane Doe",
+            "creator": "@jane@example.social",
+            "tags": ["rust", "nom"],
+        }));
+        assert_eq!(json[1]["creator"], "@blog@example.social");
+    }
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_export_calendar() {
+    let template = PathBuf::from("post.html");
+    let markdowns = vec![
+        (PathBuf::from("meetup.md"), ":meta\npublish_date = 2024-04-01\nevent_date = 2024-05-01 18:30\n:meta\n# Rust Meetup; Spring, 2024\nTalks, and pizza.".to_string()),
+        (PathBuf::from("release.md"), ":meta\ndate = 2024-03-31T23:30:00-01:00\n:meta\n# A release with a title that is long enough to be folded onto the next line".to_string()),
+        (PathBuf::from("undated.md"), "# Undated".to_string()),
+    ];
+    let options = BatchOptions { output_dir: Some(PathBuf::from("dist")), ..Default::default() };
+    let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+
+    let ics = posts_to_ics(&posts, Path::new("dist"), "https://example.com/", "Events").expect("to export calendar");
+    assert_eq!(ics, [
+        "BEGIN:VCALENDAR",
+        "VERSION:2.0",
+        &format!("PRODID:-//blogs-md-easy//{}//EN", env!("CARGO_PKG_VERSION")),
+        "X-WR-CALNAME:Events",
+        "BEGIN:VEVENT",
+        "UID:https://example.com/release.html",
+        "DTSTAMP:20240401T003000Z",
+        "DTSTART:20240401T003000Z",
+        "SUMMARY:A release with a title that is long enough to be folded onto the ne",
+        " xt line",
+        "URL:https://example.com/release.html",
+        "END:VEVENT",
+        "BEGIN:VEVENT",
+        "UID:https://example.com/meetup.html",
+        "DTSTAMP:20240501T183000Z",
+        "DTSTART:20240501T183000",
+        "SUMMARY:Rust Meetup\\; Spring\\, 2024",
+        "DESCRIPTION:Talks\\, and pizza.",
+        "URL:https://example.com/meetup.html",
+        "END:VEVENT",
+        "END:VCALENDAR",
+        "",
+    ].join("\r\n"));
+
+    // Dates that can't be understood fail the export.
+    let markdowns = vec![(PathBuf::from("typo.md"), ":meta\ndate = 1st May\n:meta\n# Typo".to_string())];
+    let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+    assert!(posts_to_ics(&posts, Path::new("dist"), "", "Events").is_err());
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn can_load_config() {
+    let config = Config::load(Path::new("tests/blogs-md-easy.toml")).expect("to load config");
+    // Paths are relative to the config.
+    assert_eq!(config.templates, vec![PathBuf::from("tests/template.html")]);
+    assert_eq!(config.markdowns, vec![PathBuf::from("tests/one.md")]);
+    assert_eq!(config.output_dir, Some(PathBuf::from("tests/dist")));
+    assert_eq!(config.wrap_sections.as_deref(), Some("section"));
+    // Commands are left as they are written.
+    assert_eq!(config.pre_build, vec!["echo Building"]);
+    assert_eq!(config.post_render, vec!["echo \"Rendered $BLOGS_MD_EASY_OUTPUT\""]);
+    assert!(config.post_build.is_empty());
+    assert_eq!(config.variables(), vec![
+        Meta::new("site_name", "My Blog"),
+        Meta::new("author.name", "John Doe"),
+        Meta::new("base_url", "https://example.com"),
+    ]);
+    assert_eq!(config.filter_defaults(), vec![
+        Meta::new("first_words", "2"),
+        Meta::new("truncate", "characters: 5, trail: ..."),
+    ]);
+    assert_eq!(config.filter_aliases(), vec![Meta::new("upper", "uppercase")]);
+    assert_eq!(config.attributes(), vec![
+        Meta::new("blockquote.class", "quote"),
+        Meta::new("img[alt].loading", "lazy"),
+        Meta::new("img[alt].decoding", "async"),
+    ]);
+
+    // Only filters without arguments are given the defaults.
+    let template = apply_filter_defaults("{{ £title | truncate }} {{ £title | truncate = 2 }}", &config.filter_defaults());
+    let template = Template::new(&template).expect("to parse template");
+    let variables = HashMap::from([("title".to_string(), "Hello, World!".to_string())]);
+    assert_eq!(template.render(&variables).expect("to render"), "Hello... He...");
+
+    assert_eq!(config.profiles(), BTreeMap::from([("minimal".to_string(), Profile {
+        template: Some(PathBuf::from("landing.html")),
+        features: vec!["nav".to_string()],
+        filter_defaults: vec![Meta::new("first_words", "5")],
+        alternates: None,
+    })]));
+    assert_eq!(config.schema(), MetaSchema::new()
+        .with("publish_date", MetaRule { kind: Some(MetaType::Date), ..Default::default() })
+        .with("title", MetaRule { required: true, ..Default::default() }));
+
+    // Unknown keys are likely to be mistakes.
+    assert!(toml::from_str::<Config>("template = \"post.html\"").is_err());
+}
+
+#[test]
+fn can_alias_filters() {
+    let aliases = vec![Meta::new("upper", "uppercase"), Meta::new("slugify", "text = kebab"), Meta::new("Truncatewords", "first_words")];
+    let template = "{{ £title | UPPER }} {{ £title ?? \"a | upper\" | slugify }} {{ £title | truncatewords = 1 }} \\{{ £title | upper }} {{ raw }}{{ £title | upper }}{{ endraw }} {{ if feature \"upper\" }}";
+    assert_eq!(apply_filter_aliases(template, &aliases), "{{ £title | uppercase }} {{ £title ?? \"a | upper\" | text = kebab }} {{ £title | first_words = 1 }} \\{{ £title | upper }} {{ raw }}{{ £title | upper }}{{ endraw }} {{ if feature \"upper\" }}");
+
+    // Templates are given the aliases before they are parsed.
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-filter-aliases");
+    let template = std::env::temp_dir().join("blogs-md-easy-filter-aliases.html");
+    std::fs::write(&template, "<h1>{{ £title | upper }}</h1><p>{{ £title | slugify }}</p>").expect("to write template");
+    let markdowns = vec![(PathBuf::from("aliases.md"), "# Hello World".to_string())];
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), filter_aliases: aliases, ..Default::default() };
+    let outputs = render_batch(&[template], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(std::fs::read_to_string(&outputs[0]).expect("to read output"), "<h1>HELLO WORLD</h1><p>hello-world</p>");
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_add_attributes_to_markdown() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-attributes");
+    let template = std::env::temp_dir().join("blogs-md-easy-attributes.html");
+    std::fs::write(&template, "<blockquote>{{ £title }}</blockquote>{{ £content | markdown }}").expect("to write template");
+    let markdowns = vec![(PathBuf::from("quote.md"), "# Quote\n> Hello\n\n![Logo](logo.png) ![](spacer.png)".to_string())];
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        attributes: vec![Meta::new("blockquote.class", "quote"), Meta::new("img[alt].loading", "lazy")],
+        ..Default::default()
+    };
+
+    // Only the elements of the Markdown are given attributes, not the template.
+    let outputs = render_batch(&[template], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let html = std::fs::read_to_string(&outputs[0]).expect("to read output");
+    assert!(html.starts_with("<blockquote>Quote</blockquote>"));
+    assert!(html.contains("<blockquote class=\"quote\">\n<p>Hello</p>\n</blockquote>"));
+    assert!(html.contains("<img src=\"logo.png\" alt=\"Logo\" loading=\"lazy\" />"));
+    assert!(html.contains("<img src=\"spacer.png\" alt=\"\" loading=\"lazy\" />"));
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_wrap_sections() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-sections");
+    let template = std::env::temp_dir().join("blogs-md-easy-sections.html");
+    std::fs::write(&template, "<main>{{ £content | markdown }}</main><aside><h2>Related</h2></aside>").expect("to write template");
+    let markdowns = vec![(PathBuf::from("guide.md"), "# Guide\n## Install\nRun it.\n## Usage\n### Install\n{{ ref \"guide#install-1\" }}".to_string())];
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        heading_ids: true,
+        wrap_sections: Some("section".to_string()),
+        attributes: vec![Meta::new("section.class", "prose")],
+        ..Default::default()
+    };
+
+    // Only the sections of the Markdown are wrapped, and the headings within
+    // them keep their anchors.
+    let outputs = render_batch(&[template], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(std::fs::read_to_string(&outputs[0]).expect("to read output"), [
+        "<main><h1 id=\"guide\">Guide</h1>",
+        "<section aria-labelledby=\"install\" class=\"prose\">",
+        "<h2 id=\"install\">Install</h2>",
+        "<p>Run it.</p>",
+        "</section>",
+        "<section aria-labelledby=\"usage\" class=\"prose\">",
+        "<h2 id=\"usage\">Usage</h2>",
+        "<h3 id=\"install-1\">Install</h3>",
+        "<p>guide.html#install-1</p>",
+        "</section></main><aside>",
+        "<h2>Related</h2></aside>",
+    ].join("\n"));
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+#[cfg(feature = "compression")]
+fn can_write_compressed_outputs() {
+    use std::io::Read;
+
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-compression");
+    let template = std::env::temp_dir().join("blogs-md-easy-compression.html");
+    std::fs::write(&template, "<main>{{ £content }}</main>").expect("to write template");
+    let markdowns = vec![(PathBuf::from("hello.md"), "# Hello
+Hello, World!".to_string())];
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        compression: vec![Compression::Gzip, Compression::Brotli],
+        ..Default::default()
+    };
+
+    // Each copy is written beside the output, and decompresses to it.
+    let outputs = render_batch(&[template], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let html = std::fs::read_to_string(&outputs[0]).expect("to read output");
+
+    let mut gzip = String::new();
+    flate2::read::GzDecoder::new(std::fs::File::open(output_dir.join("hello.html.gz")).expect("to open gzip"))
+        .read_to_string(&mut gzip).expect("to decompress gzip");
+    assert_eq!(gzip, html);
+
+    let mut brotli = String::new();
+    brotli::Decompressor::new(std::fs::File::open(output_dir.join("hello.html.br")).expect("to open brotli"), 4096)
+        .read_to_string(&mut brotli).expect("to decompress brotli");
+    assert_eq!(brotli, html);
+
+    assert!("zip".parse::<Compression>().is_err());
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+#[cfg(all(feature = "compression", feature = "json"))]
+fn can_export_post_as_zip() {
+    use std::io::Read;
+    use blogs_md_easy::export_post;
+
+    let root = std::env::temp_dir().join("blogs-md-easy-export");
+    std::fs::create_dir_all(root.join("posts")).expect("to create posts");
+    std::fs::create_dir_all(root.join("assets")).expect("to create assets");
+    std::fs::write(root.join("assets/logo.png"), b"PNG").expect("to write image");
+    let template = root.join("template.html");
+    std::fs::write(&template, "<main>{{ £content }}</main>").expect("to write template");
+
+    let markdown = ":meta\nauthor = John Doe\n:meta\n# Hello\n![Logo](../assets/logo.png)";
+    let zip = export_post(&template, &root.join("posts/hello.md"), markdown, &BatchOptions::default()).expect("to export");
+
+    // Read each local file header, and inflate its contents.
+    let mut files = vec![];
+    let mut offset = 0;
+    while zip[offset..].starts_with(b"PK\x03\x04") {
+        let u16_at = |at: usize| u16::from_le_bytes([zip[offset + at], zip[offset + at + 1]]) as usize;
+        let compressed_size = u32::from_le_bytes(zip[offset + 18..offset + 22].try_into().unwrap()) as usize;
+        let name_end = offset + 30 + u16_at(26);
+        let name = String::from_utf8(zip[offset + 30..name_end].to_vec()).expect("a UTF-8 name");
+        let data_start = name_end + u16_at(28);
+        let mut contents = String::new();
+        flate2::read::DeflateDecoder::new(&zip[data_start..data_start + compressed_size])
+            .read_to_string(&mut contents).expect("to inflate");
+        files.push((name, contents));
+        offset = data_start + compressed_size;
+    }
+
+    assert_eq!(files, vec![
+        ("hello.md".to_string(), markdown.to_string()),
+        ("hello.html".to_string(), "<main><h1>Hello</h1>\n<p><img src=\"assets/logo.png\" alt=\"Logo\" /></p></main>".to_string()),
+        ("meta.json".to_string(), "{\n  \"author\": \"John Doe\"\n}".to_string()),
+        ("assets/logo.png".to_string(), "PNG".to_string()),
+    ]);
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[test]
+fn can_add_permalinks_to_headings() {
+    let template = std::env::temp_dir().join("blogs-md-easy-permalinks.html");
+    std::fs::write(&template, "<h1>{{ £title }}</h1>{{ £content }}").expect("to write template");
+    let options = BatchOptions { heading_ids: true, permalinks: true, remove_heading: true, ..Default::default() };
+
+    // The template's own headings are given an id, but not a permalink.
+    let html = render_markdown(&template, Path::new("guide.md"), "# Guide\n## Install\n### Install", &options).expect("to render");
+    assert_eq!(html, [
+        "<h1 id=\"guide\">Guide</h1><h2 id=\"install\">Install <a class=\"permalink\" href=\"#install\" aria-label=\"Link to Install\">¶</a></h2>",
+        "<h3 id=\"install-1\">Install <a class=\"permalink\" href=\"#install-1\" aria-label=\"Link to Install\">¶</a></h3>",
+    ].join("\n"));
+}
+
+#[test]
+fn can_render_markdown_with_several_templates() {
+    let dir = std::env::temp_dir().join("blogs-md-easy-several-templates");
+    std::fs::create_dir_all(&dir).expect("to create directory");
+    std::fs::write(dir.join("post.html"), "<h1>{{ £title }}</h1>").expect("to write template");
+    std::fs::write(dir.join("page.html"), "<main>{{ £title }}</main>").expect("to write template");
+    std::fs::write(dir.join("amp.html"), "<amp>{{ £title }}</amp>").expect("to write template");
+    std::fs::write(dir.join("item.xml"), "<item><title>{{ £title }}</title>{{ £summary }}</item>").expect("to write template");
+    let markdowns = vec![
+        (PathBuf::from("plain.md"), "# Plain".to_string()),
+        (PathBuf::from("hello.md"), ":meta\ntemplates = [page.html, amp.html, item.xml]\n:meta\n# Hello".to_string()),
+        (PathBuf::from("world.md"), ":meta\ntemplate = post.html\ntemplates = [amp.html]\n:meta\n# World".to_string()),
+    ];
+    let output_dir = dir.join("dist");
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), on_missing: MissingVariablePolicy::ReplaceWithEmpty, ..Default::default() };
+
+    // The first of the `templates` is chosen, and the others are written
+    // beside it.
+    let mut progress = vec![];
+    let outputs = render_batch(&[dir.join("post.html")], &markdowns, &options, |p| progress.push((p.variant_paths.to_owned(), p.warnings.to_owned())), &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs, vec![
+        output_dir.join("plain.html"),
+        output_dir.join("hello.html"),
+        output_dir.join("hello.amp.html"),
+        output_dir.join("hello.item.xml"),
+        output_dir.join("world.html"),
+        output_dir.join("world.amp.html"),
+    ]);
+    let read = |name: &str| std::fs::read_to_string(output_dir.join(name)).expect("to read output");
+    assert_eq!(read("hello.html"), "<main>Hello</main>");
+    assert_eq!(read("hello.amp.html"), "<amp>Hello</amp>");
+    assert_eq!(read("hello.item.xml"), "<item><title>Hello</title></item>");
+    assert_eq!(read("world.html"), "<h1>World</h1>");
+    assert_eq!(read("world.amp.html"), "<amp>World</amp>");
+    assert_eq!(progress[1], (vec![output_dir.join("hello.amp.html"), output_dir.join("hello.item.xml")], vec![Warning::MissingVariable("summary".to_string())]));
+
+    // Every one of the templates must exist.
+    let markdowns = vec![(PathBuf::from("missing.md"), ":meta\ntemplates = [post.html, missing.html]\n:meta\n# Missing".to_string())];
+    assert!(render_batch(&[dir.join("post.html")], &markdowns, &options, |_| {}, &CancellationToken::new()).is_err());
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn can_choose_template_from_meta() {
+    let dir = std::env::temp_dir().join("blogs-md-easy-chosen-template");
+    let templates_dir = dir.join("layouts");
+    std::fs::create_dir_all(&templates_dir).expect("to create templates");
+    std::fs::write(dir.join("post.html"), "<p>{{ £title }}</p>").expect("to write template");
+    std::fs::write(dir.join("amp.html"), "<amp>{{ £title }}</amp>").expect("to write template");
+    std::fs::write(dir.join("review.html"), "<div>{{ £content | markdown }}</div>").expect("to write template");
+    std::fs::write(templates_dir.join("review.html"), "<section>{{ £title }}</section>").expect("to write template");
+    let templates = [dir.join("post.html"), dir.join("amp.html")];
+    let markdowns = vec![
+        (PathBuf::from("plain.md"), "# Plain".to_string()),
+        (PathBuf::from("review.md"), ":meta\ntemplate = review.html\n:meta\n# Review\n{{ ref \"plain\" }}".to_string()),
+    ];
+    let output_dir = dir.join("dist");
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), ..Default::default() };
+
+    // The review is only rendered once, with its own template, beside the
+    // template.
+    let mut progress = vec![];
+    let outputs = render_batch(&templates, &markdowns, &options, |p| progress.push((p.template_path.to_owned(), p.unused_variables.to_owned(), p.total)), &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs, vec![output_dir.join("post-plain.html"), output_dir.join("post-review.html"), output_dir.join("amp-plain.html")]);
+    assert_eq!(std::fs::read_to_string(&outputs[1]).expect("to read output"), "<div><h1>Review</h1>\n<p>post-plain.html</p></div>");
+    assert_eq!(progress[1], (dir.join("review.html"), vec![], 3));
+
+    // Or within the templates directory.
+    let options = BatchOptions { templates_dir: Some(templates_dir), ..options };
+    let outputs = render_batch(&templates[..1], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(std::fs::read_to_string(&outputs[1]).expect("to read output"), "<section>Review</section>");
+    assert_eq!(render_markdown(&templates[0], Path::new("review.md"), ":meta\ntemplate = review.html\n:meta\n# Review", &options).expect("to render"), "<section>Review</section>");
+
+    // The chosen template must exist.
+    let markdowns = vec![(PathBuf::from("missing.md"), ":meta\ntemplate = missing.html\n:meta\n# Missing".to_string())];
+    assert!(render_batch(&templates, &markdowns, &options, |_| {}, &CancellationToken::new()).is_err());
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn can_choose_profile_from_meta() {
+    let dir = std::env::temp_dir().join("blogs-md-easy-profile");
+    std::fs::create_dir_all(&dir).expect("to create templates");
+    std::fs::write(dir.join("post.html"), "{{ if feature \"nav\" }}<nav></nav>{{ endif }}<p>{{ £title | truncate }}</p>").expect("to write template");
+    std::fs::write(dir.join("amp.html"), "<amp>{{ £title }}</amp>").expect("to write template");
+    std::fs::write(dir.join("landing.html"), "<main>{{ £title | truncate }}</main>").expect("to write template");
+    let templates = [dir.join("post.html"), dir.join("amp.html")];
+    let markdowns = vec![
+        (PathBuf::from("post.md"), "# Hello, World".to_string()),
+        (PathBuf::from("now.md"), ":meta\nprofile = minimal\n:meta\n# Hello, World".to_string()),
+        (PathBuf::from("landing.md"), ":meta\nprofile = landing\n:meta\n# Hello, World".to_string()),
+    ];
+    let output_dir = dir.join("dist");
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        features: vec!["nav".to_string()],
+        filter_defaults: vec![Meta::new("truncate", "characters: 5, trail: ...")],
+        profiles: BTreeMap::from([
+            ("minimal".to_string(), Profile { filter_defaults: vec![Meta::new("truncate", "2")], ..Default::default() }),
+            ("landing".to_string(), Profile { template: Some(PathBuf::from("landing.html")), ..Default::default() }),
+        ]),
+        ..Default::default()
+    };
+
+    // Markdowns with a profile are only rendered once, into the first
+    // template unless the profile has its own.
+    let mut progress = vec![];
+    let outputs = render_batch(&templates, &markdowns, &options, |p| progress.push(p.unused_variables.to_owned()), &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs, vec![output_dir.join("post-post.html"), output_dir.join("post-now.html"), output_dir.join("post-landing.html"), output_dir.join("amp-post.html")]);
+    let html = outputs.iter().map(|output| std::fs::read_to_string(output).expect("to read output")).collect::<Vec<String>>();
+    assert_eq!(html, ["<nav></nav><p>Hello...</p>", "<p>He...</p>", "<main>Hello...</main>", "<amp>Hello, World</amp>"]);
+    assert!(progress.iter().all(|unused| unused.is_empty()));
+
+    // The chosen profile must exist.
+    let markdowns = vec![(PathBuf::from("missing.md"), ":meta\nprofile = missing\n:meta\n# Missing".to_string())];
+    let error = render_batch(&templates, &markdowns, &options, |_| {}, &CancellationToken::new()).expect_err("to be missing");
+    assert_eq!(error.to_string(), "The profile 'missing' of markdown 'missing.md' does not exist.");
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn can_hook_into_render_pipeline() {
+    struct Shortcodes;
+
+    impl Plugin for Shortcodes {
+        fn after_meta(&self, _markdown_path: &Path, meta_values: &mut Vec<Meta>) -> Result<(), Box<dyn std::error::Error>> {
+            meta_values.push(Meta::new("site", "My Blog"));
+            Ok(())
+        }
+
+        fn before_render(&self, markdown_path: &Path, variables: &mut HashMap<String, String>) -> Result<(), Box<dyn std::error::Error>> {
+            let content = variables.get("content").cloned().unwrap_or_default();
+            if content.contains("[[broken]]") {
+                Err(format!("Unknown shortcode in '{}'.", markdown_path.to_string_lossy()))?;
+            }
+            variables.insert("content".to_string(), content.replace("[[year]]", "2024"));
+            Ok(())
+        }
+
+        fn before_write(&self, _output_path: &Path, contents: &mut String) -> Result<(), Box<dyn std::error::Error>> {
+            contents.push_str("<!-- built -->");
+            Ok(())
+        }
+    }
+
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-hooks");
+    let template = std::env::temp_dir().join("blogs-md-easy-hooks.html");
+    std::fs::write(&template, "{{ £site }}: {{ £content | markdown }}").expect("to write template");
+    let markdowns = vec![(PathBuf::from("hello.md"), "# Hello\n\nCopyright [[year]]".to_string())];
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), hooks: Hooks::new().with(Shortcodes), ..Default::default() };
+
+    let outputs = render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(std::fs::read_to_string(&outputs[0]).expect("to read output"), "My Blog: <h1>Hello</h1>\n<p>Copyright 2024</p><!-- built -->");
+    // Nothing is written when rendering a single Markdown.
+    let html = render_markdown(&template, Path::new("hello.md"), &markdowns[0].1, &options).expect("to render");
+    assert_eq!(html, "My Blog: <h1>Hello</h1>\n<p>Copyright 2024</p>");
+    assert_eq!(format!("{:?}", options.hooks), "[\"tests::can_hook_into_render_pipeline::Shortcodes\"]");
+
+    let error = render_markdown(&template, Path::new("broken.md"), "# Broken\n[[broken]]", &options).expect_err("to be broken");
+    assert_eq!(error.to_string(), "Unknown shortcode in 'broken.md'. In markdown 'broken.md'.");
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_render_placeholders_in_any_order() {
+    use blogs_md_easy::render_placeholders;
+
+    let template = "<h1>{{ £title | uppercase }}</h1><p>{{ £author }}</p><p>{{ £title | reverse }}</p>";
+    let mut placeholders = parse_placeholder_locations(Span::new(template)).expect("to parse placeholders");
+    let variables = HashMap::from([("title".to_string(), "Hello".to_string()), ("author".to_string(), "John Doe".to_string())]);
+    let expected = "<h1>HELLO</h1><p>John Doe</p><p>olleH</p>";
+
+    assert_eq!(render_placeholders(template, &placeholders, &variables).expect("to render"), expected);
+    placeholders.reverse();
+    assert_eq!(render_placeholders(template, &placeholders, &variables).expect("to render"), expected);
+    placeholders.swap(0, 1);
+    assert_eq!(render_placeholders(template, &placeholders, &variables).expect("to render"), expected);
+}
+
+#[test]
+fn can_render_markdowns_with_parsed_template() {
+    let source = "<h1>{{ £title }}</h1><p>{{ £rating | ceil }}</p>{{ £content }}";
+    let template = Template::new(source).expect("to parse template");
+    for markdown in [
+        ":meta\nrating = 4.2\n:meta\n# Hello\nHello, World!",
+        ":meta\nrating = great\nauthor = John Doe\n:meta\n# Hello\n![](photo.jpg)",
+        ":meta\nrating = 1\n:meta\nNo title.",
+        "# Hello",
+    ] {
+        assert_eq!(template.render_markdown_with_warnings(markdown), render_with_warnings(source, markdown));
+        assert_eq!(template.render_markdown(markdown), render(source, markdown));
+    }
+}
+
+#[test]
+fn can_render_to_writer() {
+    use blogs_md_easy::render_to;
+
+    /// A writer that accepts a number of bytes, then fails.
+    struct Full(usize);
+
+    impl std::io::Write for Full {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if buf.len() > self.0 {
+                return Err(std::io::Error::new(std::io::ErrorKind::StorageFull, "disk full"));
+            }
+            self.0 -= buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let template = "<h1>{{ £title | uppercase }}</h1>{{ £author }}{{ £content }}";
+    for markdown in [":meta\nauthor = John Doe\n:meta\n# Hello\nHello, World!", "# Hello", "No title."] {
+        let mut output = Vec::new();
+        let streamed = render_to(&mut output, template, markdown).map(|_| String::from_utf8(output).expect("UTF-8 output"));
+        assert_eq!(streamed, render(template, markdown));
+    }
+
+    let markdown = ":meta\nauthor = John Doe\n:meta\n# Hello";
+    assert_eq!(render_to(Full(12), template, markdown), Err(RenderError::Write("disk full".to_string())));
+    let parsed = Template::new(template).expect("to parse template");
+    assert_eq!(parsed.render_markdown_to(Full(100), markdown), Ok(()));
+    assert_eq!(RenderError::Write("disk full".to_string()).to_string(), "Unable to write the output: disk full");
+}
+
+#[test]
+fn can_map_placeholders_to_output() {
+    use blogs_md_easy::render_with_source_map;
+
+    let template = "<title>{{ £title }}</title>\n<p>{{ £author | uppercase }}, {{ £author }}</p>\n{{ £content }}";
+    let markdown = ":meta\nauthor = Jo\n:meta\n# A longer title\nHello, World!";
+    let (output, source_map) = render_with_source_map(template, markdown).expect("to render");
+    assert_eq!(output, render(template, markdown).expect("to render"));
+
+    let rendered = source_map.iter().map(|mapping| (mapping.name.as_str(), &output[mapping.output.clone()])).collect::<Vec<_>>();
+    assert_eq!(rendered, vec![
+        ("title", "A longer title"),
+        ("author", "JO"),
+        ("author", "Jo"),
+        ("content", "<h1>A longer title</h1>\n<p>Hello, World!</p>"),
+    ]);
+    assert_eq!(&template[source_map[1].selection.start.offset..source_map[1].selection.end.offset], "{{ £author | uppercase }}");
+}
+
+#[test]
+fn can_compose_render_pipeline() {
+    struct Shortcodes;
+
+    impl Stage for Shortcodes {
+        fn run(&self, rendering: &mut Rendering) -> Result<(), RenderError> {
+            if rendering.markdown.contains("[[broken]]") {
+                return Err(RenderError::Stage("Unknown shortcode.".to_string()));
+            }
+            rendering.markdown = rendering.markdown.replace("[[year]]", "2024");
+            Ok(())
+        }
+    }
+
+    let template = "<h1>{{ £title }}</h1>{{ £content }}";
+    let markdown = ":meta\nauthor = John Doe\n:meta\n# Hello\nCopyright [[year]]";
+    // The built-in stages render the same as `render_with_warnings`.
+    assert_eq!(Pipeline::new().run(template, markdown), render_with_warnings(template, markdown));
+
+    let pipeline = Pipeline::new()
+        .after(Step::Parse, Shortcodes)
+        .after(Step::Postprocess, |rendering: &mut Rendering| {
+            rendering.warnings.clear();
+            Ok(())
+        })
+        .before(Step::Postprocess, |rendering: &mut Rendering| {
+            rendering.output = rendering.output.replacen("<h1>", "<h1 class=\"title\">", 1);
+            Ok(())
+        });
+    assert_eq!(pipeline.run(template, markdown), Ok(("<h1 class=\"title\">Hello</h1><h1>Hello</h1>\n<p>Copyright 2024</p>".to_string(), vec![])));
+    assert_eq!(pipeline.run(template, "# Hello\n[[broken]]"), Err(RenderError::Stage("Unknown shortcode.".to_string())));
+
+    // Replacing the values without one for each placeholder is an error.
+    let pipeline = pipeline.replace(Step::Filter, |rendering: &mut Rendering| {
+        rendering.values = vec![];
+        Ok(())
+    });
+    assert_eq!(pipeline.run(template, markdown), Err(RenderError::Stage("Expected 2 values, but there were 0.".to_string())));
+    assert!(format!("{:?}", pipeline).starts_with("[(Parse, Instead, \"parse\"), (Parse, After, \"tests::can_compose_render_pipeline::Shortcodes\"), (Resolve, Instead, \"resolve\")"));
+
+    // A batch runs the same stages around its own, which still create the
+    // variables of its options, and filter with its aliases.
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-pipeline");
+    let template_path = std::env::temp_dir().join("blogs-md-easy-pipeline.html");
+    std::fs::write(&template_path, "<h1>{{ £title | shout }}</h1>{{ £content }}<p>{{ £site }}</p>").expect("to write template");
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        variables: vec![Meta::new("site", "Example")],
+        filter_aliases: vec![Meta::new("shout", "uppercase")],
+        pipeline: Pipeline::new()
+            .after(Step::Parse, Shortcodes)
+            .before(Step::Substitute, |rendering: &mut Rendering| {
+                rendering.values.iter_mut().for_each(|value| *value = value.replace("Example", "Example.com"));
+                Ok(())
+            }),
+        ..Default::default()
+    };
+    let markdowns = vec![(PathBuf::from("hello.md"), markdown.to_string())];
+    let outputs = render_batch(&[template_path.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(std::fs::read_to_string(&outputs[0]).expect("to read output"), "<h1>HELLO</h1><h1>Hello</h1>\n<p>Copyright 2024</p><p>Example.com</p>");
+
+    let markdowns = vec![(PathBuf::from("broken.md"), "# Hello\n[[broken]]".to_string())];
+    let error = render_batch(&[template_path.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect_err("to fail to render");
+    assert!(error.to_string().contains("Unknown shortcode. In markdown 'broken.md'."), "{}", error);
+    std::fs::remove_file(template_path).ok();
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_render_changelog() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-changelog");
+    let template = std::env::temp_dir().join("blogs-md-easy-changelog.html");
+    std::fs::write(&template, "{{ for £group in £groups }}[{{ £group.title }}: {{ for £entry in £group.entries }}<a href=\"{{ £entry.url }}\">{{ £entry.title }}</a>{{ endfor }}]{{ endfor }}").expect("to write template");
+    let markdowns = vec![
+        (PathBuf::from("parser.md"), ":meta\ndate = 2024-01-01\nversion = 1.0.0\n:meta\n# Parser".to_string()),
+        (PathBuf::from("fix.md"), ":meta\ndate = 2024-02-10\nversion = 1.0.1\n:meta\n# Fix".to_string()),
+        (PathBuf::from("loops.md"), ":meta\ndate = 2024-02-01\nversion = 1.0.1\n:meta\n# Loops".to_string()),
+        (PathBuf::from("next.md"), "# Next".to_string()),
+    ];
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), ..Default::default() };
+    let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+
+    let output_path = render_changelog(&template, &posts, &options, ChangelogGroup::Version).expect("to render changelog");
+    assert_eq!(output_path, output_dir.join("blogs-md-easy-changelog.html"));
+    assert_eq!(
+        std::fs::read_to_string(&output_path).expect("to read changelog"),
+        "[1.0.1: <a href=\"#fix\">Fix</a><a href=\"#loops\">Loops</a>][1.0.0: <a href=\"#parser\">Parser</a>][Unreleased: <a href=\"#next\">Next</a>]"
+    );
+
+    render_changelog(&template, &posts, &options, ChangelogGroup::Month).expect("to render changelog");
+    assert_eq!(
+        std::fs::read_to_string(&output_path).expect("to read changelog"),
+        "[February 2024: <a href=\"#fix\">Fix</a><a href=\"#loops\">Loops</a>][January 2024: <a href=\"#parser\">Parser</a>][Undated: <a href=\"#next\">Next</a>]"
+    );
+
+    // Dates that can't be grouped by month fail the changelog.
+    let markdowns = vec![(PathBuf::from("typo.md"), ":meta\ndate = May 2024\n:meta\n# Typo".to_string())];
+    let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+    assert!(render_changelog(&template, &posts, &options, ChangelogGroup::Month).is_err());
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+#[cfg(all(feature = "json", feature = "markdown"))]
+fn can_export_search_index() {
+    let template = PathBuf::from("post.html");
+    let markdowns = vec![
+        (PathBuf::from("posts/hello.md"), ":meta\ndate = 2024-05-01\ntags = rust, nom\n:meta\n# Hello\nThe **first** post.\n\n{{ note text: \"Aside\" }}\n\n- One\n- Two".to_string()),
+        (PathBuf::from("posts/draft.md"), ":meta\ndraft = true\n:meta\n# Draft".to_string()),
+        (PathBuf::from("posts/hidden.md"), ":meta\nnoindex = true\n:meta\n# Hidden".to_string()),
+    ];
+    let options = BatchOptions { output_dir: Some(PathBuf::from("dist")), ..Default::default() };
+    let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+
+    let json = posts_to_search_index(&posts, Path::new("dist"), "https://example.com");
+    let index: serde_json::Value = serde_json::from_str(&json).expect("to parse index");
+    assert_eq!(index, serde_json::json!([{
+        "title": "Hello",
+        "url": "https://example.com/hello.html",
+        "excerpt": "The **first** post.",
+        "tags": ["rust", "nom"],
+        "body": "The first post.\n\n- One\n- Two",
+    }]));
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn can_write_build_manifest() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-build-manifest");
+    let template = std::env::temp_dir().join("blogs-md-easy-build-manifest.html");
+    std::fs::write(&template, "<h1>{{ £title }}</h1>").expect("to write template");
+    let markdowns = vec![(PathBuf::from("hello.md"), ":meta\nauthor = John Doe\n:meta\n# Hello".to_string())];
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), build_cache: Some(BuildCache::new()), ..Default::default() };
+
+    let mut progresses = vec![];
+    render_batch(&[template.to_owned()], &markdowns, &options, |progress| progresses.push(progress.to_owned()), &CancellationToken::new()).expect("to render");
+    assert_eq!(progresses[0].meta, vec![Meta::new("author", "John Doe")]);
+    assert_eq!(progresses[0].content_hash, Some(hash_hex(b"<h1>Hello</h1>")));
+
+    // A skipped output still has its meta, but no hash, as nothing was written.
+    render_batch(&[template.to_owned()], &markdowns, &options, |progress| progresses.push(progress.to_owned()), &CancellationToken::new()).expect("to render");
+    assert!(progresses[1].skipped);
+    assert_eq!(progresses[1].content_hash, None);
+
+    let manifest: serde_json::Value = serde_json::from_str(&progress_to_manifest(&progresses)).expect("to parse manifest");
+    assert_eq!(manifest[0]["markdown"], "hello.md");
+    assert_eq!(manifest[0]["meta"], serde_json::json!({ "author": "John Doe" }));
+    assert_eq!(manifest[0]["content_hash"], hash_hex(b"<h1>Hello</h1>"));
+    assert!(manifest[0]["duration_ms"].is_f64());
+    assert_eq!(manifest[1]["skipped"], true);
+    assert!(manifest[1]["content_hash"].is_null());
+    std::fs::remove_dir_all(output_dir).ok();
+    std::fs::remove_file(template).ok();
+}
+
+#[test]
+fn can_export_podcast_feed() {
+    let template = PathBuf::from("post.html");
+    let markdowns = vec![
+        (PathBuf::from("pilot.md"), ":meta\ndate = 2024-05-01T18:30:00+01:00\nenclosure = /episodes/1.m4a\nenclosure_length = 24986239\nduration = 01:02:03\nseason = 1\nepisode = 1\n:meta\n# Pilot\nWhere it all began.".to_string()),
+        (PathBuf::from("news.md"), ":meta\ndate = 2024-04-30\nguid = news-2024\n:meta\n# Tom & Jerry".to_string()),
+    ];
+    let options = BatchOptions { output_dir: Some(PathBuf::from("dist")), ..Default::default() };
+    let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+    let channel = vec![
+        Meta::new("title", "The Show"),
+        Meta::new("author", "Jane Doe"),
+        Meta::new("image", "cover.jpg"),
+        Meta::new("explicit", "false"),
+    ];
+
+    let rss = posts_to_rss(&posts, Path::new("dist"), "https://example.com/", &channel).expect("to export feed");
+    assert_eq!(rss, [
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>",
+        "<rss version=\"2.0\" xmlns:itunes=\"http://www.itunes.com/dtds/podcast-1.0.dtd\" xmlns:podcast=\"https://podcastindex.org/namespace/1.0\">",
+        "  <channel>",
+        "    <title>The Show</title>",
+        "    <link>https://example.com/</link>",
+        "    <description>The Show</description>",
+        &format!("    <generator>blogs-md-easy {}</generator>", env!("CARGO_PKG_VERSION")),
+        "    <itunes:author>Jane Doe</itunes:author>",
+        "    <itunes:image href=\"https://example.com/cover.jpg\"/>",
+        "    <itunes:explicit>false</itunes:explicit>",
+        "    <item>",
+        "      <title>Pilot</title>",
+        "      <link>https://example.com/pilot.html</link>",
+        "      <guid>https://example.com/pilot.html</guid>",
+        "      <description>Where it all began.</description>",
+        "      <pubDate>Wed, 01 May 2024 17:30:00 +0000</pubDate>",
+        "      <enclosure url=\"https://example.com/episodes/1.m4a\" length=\"24986239\" type=\"audio/x-m4a\"/>",
+        "      <itunes:duration>01:02:03</itunes:duration>",
+        "      <itunes:season>1</itunes:season>",
+        "      <podcast:season>1</podcast:season>",
+        "      <itunes:episode>1</itunes:episode>",
+        "      <podcast:episode>1</podcast:episode>",
+        "    </item>",
+        "    <item>",
+        "      <title>Tom &amp; Jerry</title>",
+        "      <link>https://example.com/news.html</link>",
+        "      <guid isPermaLink=\"false\">news-2024</guid>",
+        "      <pubDate>Tue, 30 Apr 2024 00:00:00 +0000</pubDate>",
+        "    </item>",
+        "  </channel>",
+        "</rss>",
+        "",
+    ].join("\n"));
+
+    // Episodes that can't be understood fail the export.
+    for meta in ["episode = 0", "season = two", "duration = 1:xx", "enclosure = a.mp3\nenclosure_length = big", "date = 2024-13-01"] {
+        let markdowns = vec![(PathBuf::from("typo.md"), format!(":meta\n{}\n:meta\n# Typo", meta))];
+        let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+        assert!(posts_to_rss(&posts, Path::new("dist"), "", &[]).is_err(), "{}", meta);
+    }
+}
+
+#[test]
+fn can_report_errors_with_source() {
+    let template = std::env::temp_dir().join("blogs-md-easy-report.html");
+    std::fs::write(&template, "<h1>{{ £title }}</h1>\n<p>{{ £autor | uppercase }}</p>").expect("to write template");
+    let markdowns = vec![(PathBuf::from("one.md"), "# Hello".to_string())];
+    let options = BatchOptions { output_dir: Some(std::env::temp_dir().join("blogs-md-easy-report")), ..Default::default() };
+
+    let error = render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect_err("a missing variable");
+    let error = error.downcast::<SourceError>().expect("a source error");
+    assert_eq!(error.path, template);
+    let Error::Render(diagnostic) = &error.error else {
+        panic!("expected a render error");
+    };
+    assert_eq!(diagnostic.message, "missing variable 'autor', in markdown 'one.md'");
+    assert_eq!((diagnostic.line, diagnostic.column, diagnostic.length), (2, 4, 25));
+
+    #[cfg(feature = "pretty")]
+    assert_eq!(error.report(false), [
+        "Error: Unable to render the placeholder",
+        &format!("   ╭─[ {}:2:4 ]", template.to_string_lossy()),
+        "   │",
+        " 2 │ <p>{{ £autor | uppercase }}</p>",
+        "   │    ────────────┬───────────  ",
+        "   │                ╰───────────── missing variable 'autor', in markdown 'one.md'",
+        "───╯",
+        "",
+    ].join("\n"));
+}
+
+#[test]
+fn can_render_sections() {
+    let markdown = "# Title\nIntroduction.\n## Getting Started!\nInstall it.\n### Requirements\nRust.\n```md\n## Not a section\n```\n## 2. Usage\nRun it.";
+    let template = "<header>{{ £section.getting-started | markdown }}</header><main>{{ £section.usage }}</main>";
+
+    let output = render(template, markdown).expect("to render");
+    #[cfg(feature = "markdown")]
+    assert_eq!(output, "<header><p>Install it.</p>\n<h3>Requirements</h3>\n<p>Rust.</p>\n<pre><code class=\"language-md\">## Not a section\n</code></pre></header><main>Run it.</main>");
+
+    let variables = create_variables(Span::new(markdown), vec![]).expect("to create variables");
+    assert!(!variables.contains_key("section.not-a-section"));
+    assert!(output.contains("<main>Run it.</main>"));
+
+    // Headings within tilde fences aren't sections either, and a repeated
+    // heading is numbered.
+    let markdown = "# Title\n## Usage\nOne.\n~~~\n## Not a section\n~~~\n## Usage ##\nTwo.";
+    let variables = create_variables(Span::new(markdown), vec![]).expect("to create variables");
+    assert_eq!(variables.get("section.usage").expect("to have a section"), "One.\n~~~\n## Not a section\n~~~");
+    assert_eq!(variables.get("section.usage-1").expect("to have a section"), "Two.");
+    assert_eq!(variables.get("section.usage-1.heading").expect("to have a heading"), "Usage");
+    assert!(!variables.contains_key("section.not-a-section"));
+
+    // A fence is only closed by the same character, at least as many times.
+    let markdown = "# Title\n## Usage\n````md\n```\n~~~\n## Not a section\n````\n## Café au lait\nThree.\n## 日本語\nFour.";
+    let variables = create_variables(Span::new(markdown), vec![]).expect("to create variables");
+    assert!(!variables.contains_key("section.not-a-section"));
+    assert_eq!(variables.get("section.café-au-lait").expect("to have a section"), "Three.");
+    assert_eq!(variables.get("section.日本語").expect("to have a section"), "Four.");
+    assert_eq!(render("{{ £section.café-au-lait }} {{ £section.日本語 }}", markdown).expect("to render"), "Three. Four.");
+}
+
+#[test]
+fn can_escape_literal_placeholders() {
+    let template = Template::new("<h1>{{ £title }}</h1><p>\\{{ £title }}</p>{{ raw }}<code>{{ £title | uppercase }}</code>{{ endraw }}<p>{{ £author }}</p>").expect("to parse template");
+    assert_eq!(template.required_variables(), vec!["author", "title"]);
+    let variables = HashMap::from([("title".to_string(), "Hello".to_string()), ("author".to_string(), "John".to_string())]);
+    assert_eq!(template.render(&variables).expect("to render"), "<h1>Hello</h1><p>{{ £title }}</p><code>{{ £title | uppercase }}</code><p>John</p>");
+
+    // The tags within a raw block are left for the reader.
+    let output = apply_features("{{ raw }}{{ if feature \"comments\" }}{{ endraw }}", &[]).expect("to apply features");
+    assert_eq!(output, "{{ raw }}{{ if feature \"comments\" }}{{ endraw }}");
+    let output = apply_loops("{{ for £post in £posts }}{{ £post.title }} {{ raw }}{{ £post.title }}{{ endraw }}{{ endfor }}", &HashMap::from([("posts".to_string(), 1)])).expect("to apply loops");
+    assert_eq!(output, "{{ £posts.0.title }} {{ raw }}{{ £post.title }}{{ endraw }}");
+
+    let Err(Error::Placeholder(diagnostic)) = parse_placeholder_locations(Span::new("<p>\n{{ raw }}{{ £title }}</p>")) else {
+        panic!("expected a placeholder error");
+    };
+    assert_eq!((diagnostic.line, diagnostic.column), (2, 1));
+
+    // Raw blocks of a Markdown bypass the `markdown` filter too.
+    let markdown = "# Tags\nUse \\{{ video \"clip.mp4\" }}, or `{{ raw }}{{ youtube \"id\" }}{{ endraw }}`.\n\n{{ raw }}\n*Not emphasised*, {{ £title | uppercase }}\n{{ endraw }}";
+    let template = std::env::temp_dir().join("blogs-md-easy-literals.html");
+    std::fs::write(&template, "{{ £content }}").expect("to write template");
+    let options = BatchOptions { remove_heading: true, ..Default::default() };
+    let output = render_markdown(&template, Path::new("tags.md"), markdown, &options).expect("to render");
+    #[cfg(feature = "markdown")]
+    assert_eq!(output, "<p>Use {{ video &quot;clip.mp4&quot; }}, or <code>{{ youtube \"id\" }}</code>.</p>\n\n*Not emphasised*, {{ £title | uppercase }}\n");
+
+    let output = render_filter("**Bold** {{ raw }}**{{ £x }}**{{ endraw }}", &Filter::MarkdownInline).expect("to render");
+    #[cfg(feature = "markdown")]
+    assert_eq!(output, "<strong>Bold</strong> **{{ £x }}**");
+    #[cfg(not(feature = "markdown"))]
+    assert_eq!(output, "**Bold** **{{ £x }}**");
+}
+
+#[test]
+fn can_report_warnings() {
+    let template = std::env::temp_dir().join("blogs-md-easy-warnings.html");
+    std::fs::write(&template, "<h1>{{ £title }}</h1><p>{{ £rating | round = 1 }}</p><img src=\"logo.png\" alt=\"\" role=\"presentation\">{{ £content }}").expect("to write template");
+    let markdowns = vec![(PathBuf::from("warnings.md"), ":meta\nrating = 4.25\nauthor = John Doe\n:meta\n# Warnings\n![](photo.jpg) <img src=\"chart.png\">\n![A chart](chart.png)".to_string())];
+    let options = BatchOptions { dry_run: true, on_missing: MissingVariablePolicy::ReplaceWithEmpty, ..Default::default() };
+    let mut warnings = vec![];
+    render_batch(&[template], &markdowns, &options, |progress| warnings = progress.warnings.to_owned(), &CancellationToken::new()).expect("to render");
+
+    assert_eq!(warnings[..1], [Warning::UnusedVariable("author".to_string())]);
+    #[cfg(feature = "markdown")]
+    assert_eq!(warnings[1..], [Warning::MissingAltText("photo.jpg".to_string()), Warning::MissingAltText("chart.png".to_string())]);
+}
+
+#[test]
+fn can_use_custom_syntax() {
+    let syntax = Syntax::new('@', "[[", "]]").expect("to create syntax");
+    assert!(!syntax.is_default());
+    assert!(Syntax::default().is_default());
+    assert_eq!(Syntax::default().translate("{{ £title }}"), "{{ £title }}");
+    assert!(Syntax::new('£', "", "]]").is_err());
+
+    let template = std::env::temp_dir().join("blogs-md-easy-syntax.html");
+    std::fs::write(&template, "<h1>[[ @title | uppercase ]]</h1>[[ if feature \"vue\" ]]<p v-if=\"open\">{{ message }}</p>[[ endif ]][[ $content ]]").expect("to write template");
+    let options = BatchOptions { syntax, features: vec!["vue".to_string()], remove_heading: true, ..Default::default() };
+    let markdown = "# Hello\n[[ audio \"episode.mp3\" ]] \\[[ @title ]]";
+    let output = render_markdown(&template, Path::new("syntax.md"), markdown, &options).expect("to render");
+    assert!(output.starts_with("<h1>HELLO</h1><p v-if=\"open\">{{ message }}</p>"));
+    assert!(output.contains("<figure class=\"embed embed-audio\">"));
+    assert!(output.contains("[[ @title ]]") && !output.contains("\\[["));
+
+    // Errors are reported as they were written, not as they were translated.
+    std::fs::write(&template, "<p>{{ message }}</p>\n<h1>[[ @title | shout ]]</h1>\n<p>[[ @author | whisper ]]</p>").expect("to write template");
+    let error = render_markdown(&template, Path::new("syntax.md"), markdown, &options).expect_err("to fail");
+    let error = error.downcast::<SourceError>().expect("to be a source error");
+    assert_eq!((error.error.diagnostic().line, error.error.diagnostic().column, error.error.diagnostic().length), (2, 17, 5));
+    assert_eq!(error.error.diagnostic().snippet, "2 | <h1>[[ @title | shout ]]</h1>\n  |                 ^");
+    assert_eq!(error.others.iter().map(|error| (error.diagnostic().line, error.diagnostic().column)).collect::<Vec<(u32, usize)>>(), vec![(3, 17)]);
+    assert!(error.source.contains("[[ @title | shout ]]"));
+    std::fs::remove_file(&template).expect("to remove template");
+
+    // The `{{` that is escaped in the translation doesn't move the column.
+    let text = "{{ x }} [[ @title | shout ]]";
+    let error = parse_placeholder_locations(Span::new(&options.syntax.translate(text))).expect_err("to be an unknown filter");
+    let error = options.syntax.locate(text, error);
+    assert_eq!((error.diagnostic().column, error.diagnostic().length), (21, 5));
+}
+
+#[test]
+fn can_report_every_error_of_a_file() {
+    let template = std::env::temp_dir().join("blogs-md-easy-errors.html");
+    std::fs::write(&template, "<h1>{{ £title | shout }}</h1>\n<p>{{ £author </p>\n{{ £content }}").expect("to write template");
+    let markdowns = vec![(PathBuf::from("errors.md"), "# Errors".to_string())];
+    let options = BatchOptions { dry_run: true, ..Default::default() };
+    let error = render_batch(std::slice::from_ref(&template), &markdowns, &options, |_| {}, &CancellationToken::new()).expect_err("to fail");
+    let error = error.downcast::<SourceError>().expect("to be a source error");
+    assert_eq!(error.error.diagnostic().line, 1);
+    assert_eq!(error.others.iter().map(|error| error.diagnostic().line).collect::<Vec<u32>>(), vec![2]);
+    assert!(error.to_string().contains("expected `}}` to close the placeholder"));
+
+    std::fs::write(&template, "{{ £content }}").expect("to write template");
+    let markdowns = vec![(PathBuf::from("errors.md"), ":meta\nauthor John Doe\ntitle = Errors\ndate 2024-01-01\n:meta\n# Errors".to_string())];
+    let error = render_batch(&[template], &markdowns, &options, |_| {}, &CancellationToken::new()).expect_err("to fail");
+    let error = error.downcast::<SourceError>().expect("to be a source error");
+    assert_eq!(error.error.diagnostic().line, 2);
+    assert_eq!(error.others.iter().map(|error| error.diagnostic().line).collect::<Vec<u32>>(), vec![4]);
+}
+
+#[test]
+fn can_recover_malformed_placeholders() {
+    let template = Template::recover("<h1>{{ £title | shout }}</h1>\n<p>{{ £author </p>\n{{ £content }}");
+    assert_eq!(template.errors().iter().map(|error| error.diagnostic().line).collect::<Vec<u32>>(), vec![1, 2]);
+    assert_eq!(template.required_variables(), vec!["content"]);
+
+    let path = std::env::temp_dir().join("blogs-md-easy-recover.html");
+    std::fs::write(&path, "<h1>{{ £title | shout }}</h1>\n{{ £content }}").expect("to write template");
+    let markdowns = vec![(PathBuf::from("recover.md"), "# Recover".to_string())];
+    let options = BatchOptions { recover: true, dry_run: true, ..Default::default() };
+    let mut warnings = vec![];
+    render_batch(std::slice::from_ref(&path), &markdowns, &options, |progress| warnings = progress.warnings.to_owned(), &CancellationToken::new()).expect("to render");
+    assert!(matches!(&warnings[0], Warning::Malformed(error) if error.diagnostic().message == "unknown filter 'shout'"));
+    let output = render_markdown(&path, Path::new("recover.md"), "# Recover", &options).expect("to render");
+    assert!(output.starts_with("<h1>{{ £title | shout }}</h1>\n<h1>Recover</h1>"));
+}
+
+#[test]
+fn can_control_whitespace() {
+    let template = std::env::temp_dir().join("blogs-md-easy-whitespace.html");
+    std::fs::write(&template, "<header>\n  {{- if feature \"nav\" -}}\n  <nav></nav>\n  {{- endif }}\n</header>\n<h1>{{- £title | uppercase -}}</h1>\n{{ £content }}").expect("to write template");
+    let options = BatchOptions { remove_heading: true, ..Default::default() };
+    let output = render_markdown(&template, Path::new("whitespace.md"), "# Hello\nWorld", &options).expect("to render");
+    assert_eq!(output, "<header>\n</header>\n<h1>HELLO</h1>\n<p>World</p>");
+
+    let options = BatchOptions { features: vec!["nav".to_string()], ..options };
+    let output = render_markdown(&template, Path::new("whitespace.md"), "# Hello\nWorld", &options).expect("to render");
+    assert!(output.starts_with("<header><nav></nav>\n</header>"));
+
+    // Literal text is left as it is.
+    assert_eq!(apply_whitespace_control("<p> {{ raw }} {{- £title -}} {{ endraw }} </p>"), "<p> {{ raw }} {{- £title -}} {{ endraw }} </p>");
+}
+
+#[test]
+fn can_serialize_templates_and_meta() {
+    let filters = [
+        "ceil", "round = 2", "add = -1.5", "multiply = 1.2", "first_words = 3", "hash = algo: md5, length: 8", "if = then: Yes, else: No", "join = <br>", "last_words", "lowercase", "text = kebab", "time_ago = 2024-05-04",
+        "markdown = tables: true, footnotes: true", "markdown_inline", "replace = find: World, replacement: Rust, limit: 1", "split = ;", "truncate = characters: 42, trail: --", "truncate_words = words: 5", "wrap = before: <h2>, after: </h2>",
+    ];
+    for source in filters {
+        let (_, filter) = parse_filter(Span::new(source)).expect("to parse filter");
+        assert_eq!(filter.to_string(), source);
+    }
+
+    let template = "<title>{{£title|truncate = trail: --|uppercase}}</title>\n{{ raw }}{{ £title }}{{ endraw }}\n{{ $content | markdown = tables: true }}";
+    let formatted = format_template(template).expect("to format");
+    assert_eq!(formatted, "<title>{{ £title | truncate = trail: -- | uppercase }}</title>\n{{ raw }}{{ £title }}{{ endraw }}\n{{ £content | markdown = tables: true }}");
+    assert_eq!(format_template(&formatted).expect("to format"), formatted);
+    assert_eq!(Template::new(&formatted).expect("to parse").placeholders().iter().map(|p| &p.filters).collect::<Vec<_>>(), Template::new(template).expect("to parse").placeholders().iter().map(|p| &p.filters).collect::<Vec<_>>());
+
+    let meta_values = vec![Meta::new("title", "Hello, World!"), Meta::new("tags", "rust, nom"), Meta::new("summary", "Over\ntwo lines")];
+    let markdown = format!("{}# Hello", format_meta_section(&meta_values));
+    let (_, parsed) = parse_meta_section(Span::new(&markdown)).expect("to parse meta");
+    assert_eq!(parsed, meta_values);
+}
+
+#[test]
+#[cfg(all(feature = "serde", feature = "json"))]
+fn can_serde_placeholders_and_meta() {
+    use blogs_md_easy::Placeholder;
+
+    let placeholders = parse_placeholder_locations(Span::new("<h1>{{ £title | round = 2 | text = kebab }}</h1>")).expect("to parse");
+    let json = serde_json::to_value(&placeholders).expect("to serialize");
+    assert_eq!(json, serde_json::json!([{
+        "name": "title",
+        "selection": { "start": { "line": 1, "offset": 4 }, "end": { "line": 1, "offset": 44 } },
+        "filters": [{ "name": "round", "precision": 2 }, { "name": "text", "case": "kebab" }],
+    }]));
+    assert_eq!(serde_json::from_value::<Vec<Placeholder>>(json).expect("to deserialize"), placeholders);
+
+    let filters = serde_json::from_str::<Vec<Filter>>(r#"[{ "name": "ceil" }, { "name": "replace", "find": "a", "replacement": "b", "limit": null }]"#).expect("to deserialize");
+    assert_eq!(filters, vec![Filter::Ceil, Filter::Replace { find: "a".to_string(), replacement: "b".to_string(), limit: None }]);
+
+    let meta = Meta::new("author", "John Doe");
+    assert_eq!(serde_json::to_string(&meta).expect("to serialize"), r#"{"key":"author","value":"John Doe"}"#);
+    assert_eq!(serde_json::from_str::<Meta>(r#"{"key":"author","value":"John Doe"}"#).expect("to deserialize"), meta);
+}
+
+#[test]
+fn can_quote_filter_arguments() {
+    let input = Span::new("{{ £tags | replace = find: \", \", replacement: \" | \" | truncate = trail: \"…}}\" }}");
+    let (input, placeholder) = parse_placeholder(input).expect("to parse placeholder");
+    assert_eq!(input.fragment(), &"");
+    assert_eq!(placeholder.filters, vec![
+        Filter::Replace { find: ", ".to_string(), replacement: " | ".to_string(), limit: None },
+        Filter::Truncate { characters: 100, trail: "…}}".to_string() },
+    ]);
+    assert_eq!(render_filter("rust, nom".to_string(), &placeholder.filters[0]).expect("to render filter"), "rust | nom");
+
+    let (_, parsed) = parse_placeholder(Span::new(&placeholder.to_string())).expect("to parse placeholder");
+    assert_eq!(parsed.filters, placeholder.filters);
+
+    let defaults = vec![Meta::new("join", "\" / \"")];
+    assert_eq!(blogs_md_easy::apply_filter_defaults("{{ £tags | replace = \"|\" | join }}", &defaults), "{{ £tags | replace = \"|\" | join = \" / \" }}");
+}
+
+#[test]
+fn can_derive_meta_values_with_filters() {
+    let markdown = ":meta\nsubtitle = Part One\nlabel = £subtitle | uppercase\nshout = £label | replace = find: \" \", replacement: _\nprice = £5 | ceil\n:meta\n# Hello World";
+    let (markdown, meta_values) = parse_meta_section(Span::new(markdown)).expect("to parse meta");
+    let template = Template::new("{{ £label }} {{ £shout }}").expect("to parse template");
+    assert_eq!(template.unused_variables(&meta_values), vec!["price"]);
+
+    let variables = create_variables(markdown, meta_values).expect("to create variables");
+    assert_eq!(variables["label"], "PART ONE");
+    assert_eq!(variables["shout"], "PART_ONE");
+    // A value that isn't a variable is left as it is.
+    assert_eq!(variables["price"], "£5 | ceil");
+
+    let output = render("<a href=\"{{ £slug }}\">{{ £title }}</a>", ":meta\nslug = £title | text = kebab-case\n:meta\n# Hello World").expect("to render");
+    assert_eq!(output, "<a href=\"hello-world\">Hello World</a>");
+    assert!(matches!(render("{{ £slug }}", ":meta\nslug = £nope | uppercase\n:meta\n# Hello"), Err(RenderError::MissingVariable(name)) if name == "nope"));
+
+    let post = Post::new(PathBuf::from("posts/hello.md"), ":meta\nslug = £title | text = kebab-case\n:meta\n# Hello World", PathBuf::from("hello.html"), TitlePrecedence::Meta).expect("to summarise post");
+    assert_eq!(post.variables["slug"], "hello-world");
+}
+
+#[test]
+#[cfg(feature = "ffi")]
+fn can_render_through_c_abi() {
+    use blogs_md_easy::ffi::{blogs_md_easy_free, blogs_md_easy_parse_meta, blogs_md_easy_render};
+    use std::ffi::{c_char, CStr, CString};
+
+    // Take ownership of a returned string, freeing it.
+    let take = |string: *mut c_char| {
+        let owned = unsafe { CStr::from_ptr(string) }.to_string_lossy().to_string();
+        unsafe { blogs_md_easy_free(string) };
+        owned
+    };
+    let template = CString::new("<h1>{{ £title }}</h1><p>{{ £author }}</p>").expect("to create template");
+    let markdown = CString::new(":meta\nauthor = John Doe\n:meta\n# Hello").expect("to create markdown");
+    let mut error = std::ptr::null_mut();
+
+    let html = unsafe { blogs_md_easy_render(template.as_ptr(), markdown.as_ptr(), &mut error) };
+    assert!(error.is_null());
+    assert_eq!(take(html), "<h1>Hello</h1><p>John Doe</p>");
+
+    let meta = unsafe { blogs_md_easy_parse_meta(markdown.as_ptr(), &mut error) };
+    assert_eq!(take(meta), "{\"author\":\"John Doe\"}");
+
+    let markdown = CString::new("# Hello").expect("to create markdown");
+    let html = unsafe { blogs_md_easy_render(template.as_ptr(), markdown.as_ptr(), &mut error) };
+    assert!(html.is_null());
+    assert_eq!(take(error), RenderError::MissingVariable("author".to_string()).to_string());
+
+    // The error can be ignored.
+    assert!(unsafe { blogs_md_easy_render(std::ptr::null(), markdown.as_ptr(), std::ptr::null_mut()) }.is_null());
+}
+
+#[test]
+fn can_render_each_locale() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-locales");
+    let template = output_dir.with_extension("html");
+    std::fs::write(&template, "<html lang=\"{{ £lang }}\"><p>{{ £title }}</p>{{ £content | markdown }}</html>").expect("to write template");
+    let markdowns = vec![
+        (PathBuf::from("hello.md"), ":meta\ntitle = Hello\ntitle.fr = Bonjour\ntitle.de = Hallo\n:meta\nWorld".to_string()),
+        (PathBuf::from("about.md"), ":meta\ntitle = About\n:meta\nMe".to_string()),
+        (PathBuf::from("about.fr.md"), ":meta\ntitle = À propos\n:meta\nMoi".to_string()),
+    ];
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), locales: vec!["en".to_string(), "fr".to_string()], ..Default::default() };
+
+    let mut progress = vec![];
+    let outputs = render_batch(&[template], &markdowns, &options, |p| progress.push(p.index), &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs, vec![
+        output_dir.join("hello.html"),
+        output_dir.join("about.html"),
+        output_dir.join("fr").join("hello.html"),
+        output_dir.join("fr").join("about.html"),
+    ]);
+    assert_eq!(progress, vec![0, 1, 2, 3]);
+
+    let read = |index: usize| std::fs::read_to_string(&outputs[index]).expect("to read output");
+    assert_eq!(read(0), "<html lang=\"en\"><p>Hello</p><p>World</p></html>");
+    assert_eq!(read(1), "<html lang=\"en\"><p>About</p><p>Me</p></html>");
+    assert_eq!(read(2), "<html lang=\"fr\"><p>Bonjour</p><p>World</p></html>");
+    assert_eq!(read(3), "<html lang=\"fr\"><p>À propos</p><p>Moi</p></html>");
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_write_alternate_outputs() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-alternates");
+    let template = output_dir.with_extension("html");
+    std::fs::write(&template, "<html><head><title>{{ £title }}</title><style>h1 { color: navy; } a.button { padding: 1em; }</style></head><body><h1>{{ £title }}</h1>{{ £content | markdown }}<a class=\"button\" href=\"https://example.com/subscribe\">Subscribe</a><script>track();</script></body></html>").expect("to write template");
+    let markdowns = vec![
+        (PathBuf::from("hello.md"), "# Hello\nSome *text*.\n\n- One\n- Two".to_string()),
+        (PathBuf::from("now.md"), ":meta\nprofile = web\n:meta\n# Now".to_string()),
+    ];
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        alternates: vec![Alternate::Text, Alternate::Email],
+        profiles: BTreeMap::from([("web".to_string(), Profile { alternates: Some(vec![]), ..Default::default() })]),
+        ..Default::default()
+    };
+
+    render_batch(&[template], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let text = std::fs::read_to_string(output_dir.join("hello.txt")).expect("to read text");
+    assert_eq!(text, "Hello\n\nHello\n\nSome text.\n\n- One\n- Two\n\nSubscribe (https://example.com/subscribe)\n");
+    let email = std::fs::read_to_string(output_dir.join("hello.email.html")).expect("to read email");
+    assert!(email.contains("<h1 style=\"color: navy\">Hello</h1>"));
+    assert!(email.contains("<a class=\"button\" href=\"https://example.com/subscribe\" style=\"padding: 1em\">"));
+    assert!(!email.contains("<style>") && !email.contains("<script>"));
+
+    // The profile writes no alternates.
+    assert!(output_dir.join("now.html").exists());
+    assert!(!output_dir.join("now.txt").exists());
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_validate_meta_schema() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-schema");
+    let template = std::env::temp_dir().join("blogs-md-easy-schema.html");
+    std::fs::write(&template, "<h1>{{ £title }}</h1>").expect("to write template");
+    let schema = MetaSchema::new()
+        .with("title", MetaRule { required: true, ..Default::default() })
+        .with("publish_date", MetaRule { required: true, kind: Some(MetaType::Date), ..Default::default() })
+        .with("draft", MetaRule { kind: Some(MetaType::Bool), ..Default::default() });
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), schema, ..Default::default() };
+
+    let markdowns = vec![
+        (PathBuf::from("posts/good.md"), ":meta\npublish_date = 2024-05-01\n:meta\n# Good".to_string()),
+        (PathBuf::from("posts/untitled.md"), ":meta\npublish_date = 2024-05-01T09:00:00Z\n:meta\nNo heading.".to_string()),
+        (PathBuf::from("posts/typo.md"), ":meta\ntitle = Typo\npublish_date = 1st May\ndraft = yes\n:meta\n".to_string()),
+    ];
+    let error = render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect_err("to fail the schema");
+    // Every problem of every Markdown is reported, and nothing is written.
+    assert_eq!(error.to_string(), [
+        "The meta sections do not match the schema.",
+        "posts/untitled.md: Missing 'title'.",
+        "posts/typo.md: 'draft' must be a bool, but was 'yes'.",
+        "posts/typo.md: 'publish_date' must be a date, but was '1st May'.",
+    ].join("\n"));
+    assert!(!output_dir.exists());
+
+    let outputs = render_batch(&[template], &markdowns[..1], &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs.len(), 1);
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_collect_every_batch_error() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-collect-errors");
+    let template = std::env::temp_dir().join("blogs-md-easy-collect-errors.html");
+    std::fs::write(&template, "<h1>{{ £title }}</h1>\n<p>{{ £author }}</p>\n<p>{{ £date }}</p>").expect("to write template");
+    let markdowns = vec![
+        (PathBuf::from("posts/missing.md"), "# Missing".to_string()),
+        (PathBuf::from("posts/good.md"), ":meta\nauthor = John Doe\ndate = 2024-05-01\n:meta\n# Good".to_string()),
+        (PathBuf::from("posts/malformed.md"), ":meta\nauthor John Doe\ndate 2024-05-01\n:meta\n# Malformed".to_string()),
+    ];
+
+    // Without collecting, the first error stops the batch.
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), ..Default::default() };
+    let error = render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect_err("to fail");
+    assert!(error.downcast_ref::<BatchError>().is_none());
+
+    let options = BatchOptions { collect_errors: true, ..options };
+    let mut rendered = vec![];
+    let error = render_batch(&[template], &markdowns, &options, |progress| rendered.push(progress.markdown_path.to_owned()), &CancellationToken::new()).expect_err("to fail");
+    assert_eq!(rendered, vec![PathBuf::from("posts/good.md")]);
+    assert!(output_dir.join("good.html").exists());
+
+    let error = error.downcast::<BatchError>().expect("to collect every error");
+    assert_eq!(error.errors.len(), 2);
+    assert!(error.to_string().starts_with("2 of the markdowns could not be rendered."));
+    let messages = error.diagnostics().iter().map(|diagnostic| diagnostic.message.to_owned()).collect::<Vec<String>>();
+    assert_eq!(messages, vec![
+        "missing variable 'author', in markdown 'posts/missing.md'",
+        "missing variable 'date', in markdown 'posts/missing.md'",
+        "expected at least one `key = value`",
+        "expected `key = value`, or the closing `:meta`",
+    ]);
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_write_to_permalinks() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-permalinks");
+    let template = std::env::temp_dir().join("blogs-md-easy-permalinks.html");
+    std::fs::write(&template, "<h1>{{ £title }}</h1>{{ £content }}").expect("to write template");
+    let options = BatchOptions { output_dir: Some(output_dir.to_owned()), permalink_pattern: Some("/:year/:month/:slug/".to_string()), ..Default::default() };
+
+    let markdowns = vec![
+        (PathBuf::from("posts/hello.md"), ":meta\ndate = 2024-05-01\n:meta\n# Hello\nSee [the next post](next.md).".to_string()),
+        (PathBuf::from("posts/next.md"), ":meta\ndate = 2024-06-02T09:00:00Z\nslug = the-next-one\n:meta\n# Next".to_string()),
+    ];
+    let outputs = render_batch(&[template.to_owned()], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs, vec![
+        output_dir.join("2024/05/hello/index.html"),
+        output_dir.join("2024/06/the-next-one/index.html"),
+    ]);
+    // Links between Markdowns follow their permalinks.
+    let html = std::fs::read_to_string(&outputs[0]).expect("to read output");
+    assert!(html.contains(r#"<a href="../../06/the-next-one/index.html">"#), "{}", html);
+
+    let posts = collect_posts(&[template.to_owned()], &markdowns, &options).expect("to collect posts");
+    assert_eq!(posts[0].output_path, output_dir.join("2024/06/the-next-one/index.html"));
+
+    // Every variable must exist, and no two Markdowns can share a permalink.
+    let undated = vec![(PathBuf::from("posts/undated.md"), "# Undated".to_string())];
+    let error = render_batch(&[template.to_owned()], &undated, &options, |_| {}, &CancellationToken::new()).expect_err("to need a date");
+    assert_eq!(error.to_string(), "The permalink '/:year/:month/:slug/' needs a 'date' or 'publish_date'. In markdown 'posts/undated.md'.");
+    let dot = vec![(PathBuf::from("posts/dot.md"), ":meta\ndate = 2024-05-01\nslug = .\n:meta\n# Dot".to_string())];
+    let error = render_batch(&[template.to_owned()], &dot, &options, |_| {}, &CancellationToken::new()).expect_err("to need a slug");
+    assert_eq!(error.to_string(), "The 'slug' of the permalink '/:year/:month/:slug/' can't be '.'. In markdown 'posts/dot.md'.");
+    let options = BatchOptions { permalink_pattern: Some("/:year/index".to_string()), ..options };
+    let error = render_batch(&[template], &markdowns[..1].iter().chain(&markdowns[..1]).cloned().collect::<Vec<_>>(), &options, |_| {}, &CancellationToken::new()).expect_err("to collide");
+    assert!(error.to_string().contains("have the same permalink"));
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+fn can_derive_open_graph_variables() {
+    let output_dir = std::env::temp_dir().join("blogs-md-easy-open-graph");
+    let template = std::env::temp_dir().join("blogs-md-easy-open-graph.html");
+    std::fs::write(&template, "{{ £og_title }}\n{{ £og_description }}\n{{ £og_image }}\n{{ £og_url }}").expect("to write template");
+    let options = BatchOptions {
+        output_dir: Some(output_dir.to_owned()),
+        open_graph: true,
+        variables: vec![Meta::new("base_url", "https://example.com/"), Meta::new("og_image", "https://example.com/default.png")],
+        ..Default::default()
+    };
+
+    let markdowns = vec![
+        (PathBuf::from("posts/shared.md"), ":meta\nog_title = Shared Widely\ndescription = All about sharing.\nimage = /images/cover.png\n:meta\n# Shared\nThe content.".to_string()),
+        (PathBuf::from("posts/plain.md"), "# Plain\nThe first paragraph.\n\n![A chart](chart.png)".to_string()),
+        (PathBuf::from("posts/bare.md"), "# Bare\nNo images here.".to_string()),
+    ];
+    let outputs = render_batch(&[template], &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    let read = |index: usize| std::fs::read_to_string(&outputs[index]).expect("to read output");
+    assert_eq!(read(0), "Shared Widely\nAll about sharing.\nhttps://example.com/images/cover.png\nhttps://example.com/shared.html");
+    assert_eq!(read(1), "Plain\nThe first paragraph.\nhttps://example.com/chart.png\nhttps://example.com/plain.html");
+    // The image of the site is the last resort.
+    assert_eq!(read(2), "Bare\nNo images here.\nhttps://example.com/default.png\nhttps://example.com/bare.html");
+    std::fs::remove_dir_all(output_dir).ok();
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn can_init_a_project_that_builds() {
+    let project_dir = std::env::temp_dir().join("blogs-md-easy-init");
+    std::fs::remove_dir_all(&project_dir).ok();
+    init_project(&project_dir, "2024-05-01").expect("to create project");
+    assert!(std::fs::read_to_string(project_dir.join("templates/post.html")).expect("to read template").contains("{{ £date }}"));
+
+    let config = Config::load(&project_dir.join(Config::FILE)).expect("to load config");
+    let options = BatchOptions {
+        output_dir: config.output_dir.to_owned(),
+        shortcodes: Shortcodes::load(config.shortcodes.as_deref().expect("a shortcodes directory")).expect("to load shortcodes"),
+        variables: config.variables(),
+        ..Default::default()
+    };
+    let markdowns = vec![(project_dir.join("posts/hello-world.md"), std::fs::read_to_string(project_dir.join("posts/hello-world.md")).expect("to read post"))];
+    let outputs = render_batch(&config.templates, &markdowns, &options, |_| {}, &CancellationToken::new()).expect("to render");
+    assert_eq!(outputs, vec![project_dir.join("dist/hello-world.html")]);
+
+    let html = std::fs::read_to_string(&outputs[0]).expect("to read output");
+    assert!(html.contains("<title>Hello, World! | My Blog</title>"));
+    assert!(html.contains(r#"<time datetime="2024-05-01">"#));
+    assert!(html.contains(r#"<aside class="note">"#));
+
+    // Nothing is overwritten.
+    assert!(init_project(&project_dir, "2024-05-01").is_err());
+    std::fs::remove_dir_all(project_dir).ok();
+}
+
+#[test]
+fn can_create_a_new_post_from_the_schema() {
+    let posts_dir = std::env::temp_dir().join("blogs-md-easy-new-post");
+    std::fs::remove_dir_all(&posts_dir).ok();
+    let schema = MetaSchema::new()
+        .with("title", MetaRule { required: true, ..Default::default() })
+        .with("publish_date", MetaRule { required: true, kind: Some(MetaType::Date), ..Default::default() })
+        .with("category", MetaRule { required: true, values: vec!["news".to_string(), "tutorial".to_string()], ..Default::default() })
+        .with("featured", MetaRule { required: true, kind: Some(MetaType::Bool), ..Default::default() })
+        .with("author", MetaRule { required: true, ..Default::default() })
+        .with("tags", MetaRule::default());
+
+    let path = new_post(&posts_dir, "My Post Title", "2024-05-01", &schema).expect("to create post");
+    assert_eq!(path, posts_dir.join("2024-05-01-my-post-title.md"));
+    let markdown = std::fs::read_to_string(&path).expect("to read post");
+    assert_eq!(markdown, ":meta\ntitle = My Post Title\npublish_date = 2024-05-01\ndraft = true\nauthor = \"\"\ncategory = news\nfeatured = false\n:meta\n");
+
+    // Every required key is declared, though the author is left to be filled in.
+    assert_eq!(schema.validate(&markdown), Vec::<String>::new());
+    assert!(is_draft(&markdown));
+
+    assert!(new_post(&posts_dir, "My Post Title", "2024-05-01", &schema).is_err());
+    assert!(new_post(&posts_dir, "!!!", "2024-05-01", &schema).is_err());
+    std::fs::remove_dir_all(posts_dir).ok();
+}
+
+#[test]
+fn can_extract_meta_and_title() {
+    // Placeholders and shortcodes are never looked at.
+    let markdown = ":meta\ndate = 2024-05-01\n:meta\n  # Listed Post\n{{ £missing | shout }}";
+    assert_eq!(extract_meta(markdown), vec![Meta::new("date", "2024-05-01")]);
+    assert_eq!(extract_title(markdown).as_deref(), Some("Listed Post"));
+
+    #[cfg(feature = "yaml")]
+    {
+        let markdown = "---\ntitle: From YAML\ndate: 2024-05-02\n---\n# Heading";
+        assert_eq!(extract_meta(markdown), vec![Meta::new("title", "From YAML"), Meta::new("date", "2024-05-02")]);
+        assert_eq!(extract_title(markdown).as_deref(), Some("From YAML"));
+    }
+
+    // A malformed meta section has no meta values.
+    assert_eq!(extract_meta(":meta\nbroken line\n:meta\n# Hello"), vec![]);
+}
+
+#[test]
+fn can_validate_template() {
+    let template = "<title>{{ £title }}</title><time>{{ £publish_date }}</time><p>{{ £summary | first_words }}</p>{{ £content }}";
+
+    let markdown = ":meta\npublish_date = 2024-05-01\ntags = [rust]\n:meta\n# Hello";
+    let validation = validate_template(template, markdown).expect("to validate");
+    assert_eq!(validation, TemplateValidation {
+        missing_variables: vec!["summary".to_string()],
+        unused_variables: vec!["tags".to_string()],
+    });
+    assert!(!validation.is_valid());
+
+    // A Markdown without a title is missing it, rather than an error.
+    let validation = validate_template(template, ":meta\npublish_date = 2024-05-01\nsummary = Hi\n:meta\nNo heading.").expect("to validate");
+    assert_eq!(validation.missing_variables, vec!["title"]);
+
+    // The schema is every Markdown, along with the variables that they all have.
+    let schema = MetaSchema::new()
+        .with("publish_date", MetaRule { required: true, kind: Some(MetaType::Date), ..Default::default() })
+        .with("summary", MetaRule::default())
+        .with("author", MetaRule::default());
+    let validation = validate_template(template, &schema).expect("to validate");
+    assert_eq!(validation.missing_variables, Vec::<String>::new());
+    assert_eq!(validation.unused_variables, vec!["author"]);
+
+    assert!(validate_template("{{ £title | shout }}", markdown).is_err());
+    assert!(validate_template(template, ":meta\nbroken line\n:meta\n# Hello").is_err());
+}
+
+#[test]
+fn can_expand_shortcodes() {
+    let dir = std::env::temp_dir().join("blogs-md-easy-shortcodes");
+    std::fs::create_dir_all(&dir).expect("to create shortcodes");
+    std::fs::write(dir.join("youtube.html"), "<div class=\"video\">\n\n<iframe src=\"https://www.youtube-nocookie.com/embed/{{ £id }}\"></iframe>\n</div>\n").expect("to write shortcode");
+    std::fs::write(dir.join("note.md"), "> **Note:** {{ £text }}").expect("to write shortcode");
+    let template = dir.with_extension("html");
+    std::fs::write(&template, "{{ £content | markdown }}").expect("to write template");
+
+    let options = BatchOptions { shortcodes: Shortcodes::load(&dir).expect("to load shortcodes"), ..Default::default() };
+    let markdown = "# Hello\n{{ youtube id: dQw4w9WgXcQ }}\n\n{{ note text: \"Quoted, with a comma.\" }}\n\n{{ raw }}{{ note text: Kept }}{{ endraw }}";
+    let html = render_markdown(&template, Path::new("hello.md"), markdown, &options).expect("to render");
+    #[cfg(feature = "markdown")]
+    assert_eq!(html, "<h1>Hello</h1>\n<div class=\"video\">\n<iframe src=\"https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ\"></iframe>\n</div>\n<blockquote>\n<p><strong>Note:</strong> Quoted, with a comma.</p>\n</blockquote>\n{{ note text: Kept }}");
+
+    // The built-in embeds are still available.
+    let html = render_markdown(&template, Path::new("hello.md"), "# Hello\n{{ youtube \"dQw4w9WgXcQ\" }}", &options).expect("to render");
+    assert!(html.contains("youtube-nocookie.com"));
+
+    assert!(Shortcodes::load(&dir.join("missing")).is_err());
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+#[cfg(feature = "tokio")]
+fn can_render_asynchronously() {
+    use blogs_md_easy::asynchronous;
+
+    let directory = std::env::temp_dir().join("blogs-md-easy-asynchronous");
+    std::fs::create_dir_all(&directory).expect("to create directory");
+    let template = directory.join("post.html");
+    std::fs::write(&template, "<h1>{{ £title }}</h1>").expect("to write template");
+    let markdowns = vec![directory.join("one.md"), directory.join("two.md")];
+    std::fs::write(&markdowns[0], "# One").expect("to write markdown");
+    std::fs::write(&markdowns[1], "# Two").expect("to write markdown");
+    let options = BatchOptions { output_dir: Some(directory.join("dist")), ..Default::default() };
+
+    let runtime = tokio::runtime::Builder::new_current_thread().build().expect("to build runtime");
+    // The futures are `Send`, so they can be spawned, such as by a web server.
+    let (html, outputs) = runtime.block_on(runtime.spawn(async move {
+        let html = asynchronous::render_markdown(&template, &markdowns[1], &options).await?;
+        let outputs = asynchronous::render_batch(&[template], &markdowns, &options, &CancellationToken::new()).await?;
+        Ok::<_, asynchronous::Error>((html, outputs))
+    })).expect("to join").expect("to render");
+    assert_eq!(html, "<h1>Two</h1>");
+    assert_eq!(outputs, vec![directory.join("dist/one.html"), directory.join("dist/two.html")]);
+    assert_eq!(std::fs::read_to_string(&outputs[0]).expect("to read output"), "<h1>One</h1>");
+
+    // A Markdown that can't be read is an error, rather than a panic.
+    let missing = runtime.block_on(asynchronous::render_markdown(&directory.join("post.html"), &directory.join("missing.md"), &BatchOptions::default()));
+    assert!(missing.expect_err("to fail").to_string().contains("missing.md"));
+    std::fs::remove_dir_all(directory).ok();
+}